@@ -0,0 +1,94 @@
+//! ## Evaluation benchmark
+//!
+//! Runs check cycles over 2,000 alerts on 100 symbols, spelled in two formats, against the
+//! fixture server and a `MockProvider`, and reports the allocations and time per cycle.
+//!
+//! The check logs every row, run with stdout discarded:
+//! `cargo bench --features fixtures --bench evaluation > /dev/null`
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use trade_alerts::data::MockProvider;
+use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+
+const SYMBOLS: usize = 100;
+const ALERTS_PER_SYMBOL: usize = 20;
+const CYCLES: usize = 5;
+
+/// Counts every allocation made by the process.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Returns the symbol `index` as `AAB/USD` or, for odd alerts, as `AABUSD`.
+fn symbol(index: usize, alert: usize) -> String {
+    let base: String = [index / 676 % 26, index / 26 % 26, index % 26]
+        .iter()
+        .map(|letter| (b'A' + *letter as u8) as char)
+        .collect();
+    match alert % 2 {
+        0 => format!("{}/USD", base),
+        _ => format!("{}USD", base),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let config = table_config("alerts");
+    let server = FixtureServer::start().await;
+    let provider = MockProvider::new();
+
+    let mut rows: Vec<Value> = Vec::with_capacity(SYMBOLS * ALERTS_PER_SYMBOL);
+    for index in 0..SYMBOLS {
+        provider.set_price(&symbol(index, 0), 1.0);
+        for alert in 0..ALERTS_PER_SYMBOL {
+            let level: f64 = 1.5 + alert as f64 / 100.0;
+            rows.push(AlertFixture::new(&format!("bench-{}-{}", index, alert), &symbol(index, alert), level).latest_price(1.0).row(&config));
+        }
+    }
+    server.insert_rows("alerts", rows);
+
+    let supabase = server.supabase();
+    let api = server.xylex_api();
+
+    let mut allocations: usize = 0;
+    let mut elapsed: Duration = Duration::ZERO;
+    for _ in 0..CYCLES {
+        let before: usize = ALLOCATIONS.load(Ordering::Relaxed);
+        let started: Instant = Instant::now();
+        let report = api.check_alerts_with(&provider, &supabase, &config).await.expect("Check failed");
+        elapsed += started.elapsed();
+        allocations += ALLOCATIONS.load(Ordering::Relaxed) - before;
+        assert!(report.triggered.is_empty());
+    }
+
+    eprintln!(
+        "{} alerts: {} allocations and {:?} per cycle",
+        SYMBOLS * ALERTS_PER_SYMBOL,
+        allocations / CYCLES,
+        elapsed / CYCLES as u32
+    );
+}
@@ -0,0 +1,232 @@
+//! ## Signed one-click actions
+//!
+//! Notifications can carry links such as "mute this symbol" or "delete this alert". Each link
+//! holds a token naming the action, the alert and a pseudonym of its owner, signed with
+//! HMAC-SHA256 and valid until it expires, so the server (feature `server`) can act on it
+//! without a login. Tokens end up in mail and chat logs, so they never carry the user ID.
+//!
+//! A token is `base64url(claims JSON).base64url(signature)`, without padding. The server runs
+//! every token at most once.
+//!
+//! The server only acts on alerts created before the token was issued, so a link from an old
+//! notification can't touch alerts the user added since.
+
+use std::fmt;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::Sha256;
+
+use crate::TriggeredAlert;
+use crate::errors::ActionTokenError;
+use crate::utils::pseudonym::Pseudonymizer;
+
+/// How long action links stay valid when no lifetime is set.
+pub const DEFAULT_ACTION_TTL_DAYS: i64 = 7;
+
+/// ## An action a notification link can trigger
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertAction {
+    /// Mutes the alerts of the user on the symbol, they keep triggering but no further
+    /// notifications arrive for them, see [`crate::notify::router::NOTIFY_METADATA_KEY`].
+    MuteSymbol,
+    /// Deletes the alert which sent the notification.
+    DeleteAlert,
+}
+
+/// ## The signed content of an action token
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ActionClaims {
+    pub action: AlertAction,
+    pub hash: String,
+    /// Pseudonym of the alert's owner, see [`ActionSigner::resolve_owner`].
+    pub owner: String,
+    pub symbol: String,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// ## Signs and verifies action tokens
+/// `Debug` output never contains the secret.
+#[derive(Clone)]
+pub struct ActionSigner {
+    secret: Vec<u8>,
+    /// Maps owners to the pseudonyms in the claims, keyed with the secret.
+    owners: Pseudonymizer,
+    /// How long a token stays valid after it was signed.
+    pub ttl: Duration,
+    /// The URL action links point to, the token is appended as the last path segment,
+    /// e.g. `https://alerts.example.com/actions`.
+    pub base_url: Option<String>,
+}
+
+impl AlertAction {
+    /// Every action, in the order links are rendered.
+    pub const ALL: [AlertAction; 2] = [AlertAction::MuteSymbol, AlertAction::DeleteAlert];
+
+    /// Returns the name used in payloads, e.g. `mute_symbol`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertAction::MuteSymbol => "mute_symbol",
+            AlertAction::DeleteAlert => "delete_alert",
+        }
+    }
+}
+
+impl ActionClaims {
+    /// Creates the claims of an action on a triggered alert owned by `owner`, a pseudonym,
+    /// expiring `ttl` after `now`.
+    pub fn new(
+        action: AlertAction,
+        alert: &TriggeredAlert,
+        owner: String,
+        now: DateTime<Utc>,
+        ttl: Duration
+    ) -> Self {
+        Self {
+            action,
+            hash: alert.hash.clone(),
+            owner,
+            symbol: alert.symbol.clone(),
+            issued_at: now,
+            expires_at: now + ttl,
+        }
+    }
+}
+
+impl ActionSigner {
+    /// Creates a signer whose tokens stay valid for `DEFAULT_ACTION_TTL_DAYS`.
+    ///
+    /// # Arguments
+    /// * `secret` - The signing key, shared between whatever sends notifications and the server.
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            secret: secret.as_ref().to_vec(),
+            owners: Pseudonymizer::new(secret.as_ref()),
+            ttl: Duration::days(DEFAULT_ACTION_TTL_DAYS),
+            base_url: None,
+        }
+    }
+
+    /// Sets how long tokens stay valid.
+    pub fn with_ttl(
+        mut self,
+        ttl: Duration
+    ) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the URL action links point to, see [`ActionSigner::link`].
+    pub fn with_base_url(
+        mut self,
+        base_url: &str
+    ) -> Self {
+        self.base_url = Some(base_url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Signs the claims into a token.
+    pub fn sign(&self, claims: &ActionClaims) -> String {
+        let payload: String = URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims).expect("Claims serialize to JSON"));
+        let signature: String = URL_SAFE_NO_PAD.encode(self.mac(payload.as_bytes()).finalize().into_bytes());
+        format!("{}.{}", payload, signature)
+    }
+
+    /// Returns a token for an action on a triggered alert, valid for `ttl` from `now`.
+    pub fn token(
+        &self,
+        action: AlertAction,
+        alert: &TriggeredAlert,
+        now: DateTime<Utc>
+    ) -> String {
+        let owner: String = self.owners.pseudonymize(&alert.user_id);
+        self.sign(&ActionClaims::new(action, alert, owner, now, self.ttl))
+    }
+
+    /// Returns the owner named in the claims among `user_ids`, e.g. the owner of the alert
+    /// the action is on, see [`Pseudonymizer::resolve_among`].
+    pub fn resolve_owner<'a>(
+        &self,
+        claims: &ActionClaims,
+        user_ids: impl IntoIterator<Item = &'a str>
+    ) -> Option<String> {
+        let user_ids: Vec<&str> = user_ids.into_iter().collect();
+        self.owners
+            .resolve_among(&claims.owner, user_ids.iter().copied())
+            .filter(|owner| user_ids.contains(&owner.as_str()))
+    }
+
+    /// Verifies a token and returns its claims.
+    ///
+    /// # Errors
+    /// Returns `ActionTokenError::Malformed` if the token can't be decoded,
+    /// `ActionTokenError::InvalidSignature` if it was altered or signed with another secret,
+    /// or `ActionTokenError::Expired` if it expired before `now`.
+    pub fn verify(
+        &self,
+        token: &str,
+        now: DateTime<Utc>
+    ) -> Result<ActionClaims, ActionTokenError> {
+        let (payload, signature) = token.trim().split_once('.').ok_or(ActionTokenError::Malformed)?;
+        let signature: Vec<u8> = URL_SAFE_NO_PAD.decode(signature).map_err(|_| ActionTokenError::Malformed)?;
+        self.mac(payload.as_bytes())
+            .verify_slice(&signature)
+            .map_err(|_| ActionTokenError::InvalidSignature)?;
+
+        let claims: ActionClaims = URL_SAFE_NO_PAD
+            .decode(payload)
+            .ok()
+            .and_then(|payload| serde_json::from_slice(&payload).ok())
+            .ok_or(ActionTokenError::Malformed)?;
+        if claims.expires_at <= now {
+            return Err(ActionTokenError::Expired);
+        }
+        Ok(claims)
+    }
+
+    /// Returns the link triggering an action on a triggered alert, `None` without a base URL.
+    pub fn link(
+        &self,
+        action: AlertAction,
+        alert: &TriggeredAlert,
+        now: DateTime<Utc>
+    ) -> Option<String> {
+        let base_url: &str = self.base_url.as_deref()?;
+        Some(format!("{}/{}", base_url, self.token(action, alert, now)))
+    }
+
+    /// Returns the links of every action keyed by action name, e.g. `{"mute_symbol": "https://..."}`.
+    pub fn links(
+        &self,
+        alert: &TriggeredAlert,
+        now: DateTime<Utc>
+    ) -> Map<String, Value> {
+        AlertAction::ALL
+            .into_iter()
+            .filter_map(|action| Some((action.as_str().to_string(), Value::String(self.link(action, alert, now)?))))
+            .collect()
+    }
+
+    fn mac(&self, payload: &[u8]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(payload);
+        mac
+    }
+}
+
+/// Debug implementation for `ActionSigner` which never prints the secret.
+impl fmt::Debug for ActionSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ActionSigner")
+            .field("secret", &"<redacted>")
+            .field("ttl", &self.ttl)
+            .field("base_url", &self.base_url)
+            .finish()
+    }
+}
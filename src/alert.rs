@@ -5,8 +5,14 @@
 //! and triggered when certain conditions are met.
 
 use std::error::Error;
-use crate::Alert;
+
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value, json};
+
+use crate::{Alert, AlertUpdate, BandMode, Condition, EvaluateOn, Tolerance, TriggeredAlert};
 use crate::db::{Supabase, TableConfig};
+use crate::errors::SupabaseError;
+use crate::market_hours::sessions_from_value;
 
 impl Alert {
     /// Constructs a new `Alert`.
@@ -17,25 +23,203 @@ impl Alert {
     /// # Parameters
     /// - `hash`: A unique identifier for the alert.
     /// - `price_level`: The price level at which the alert should trigger.
-    /// - `symbol`: The trading symbol associated with the alert.
+    /// - `symbol`: The trading symbol associated with the alert, a `String` or a validated `Symbol`.
     /// - `user_id`: The ID of the user who owns the alert.
     ///
     /// # Returns
-    /// Returns a new instance of `Alert`.
+    /// Returns a new instance of `Alert` using the `Condition::PriceLevel` condition.
     pub fn new(
         hash: String,
         price_level: f64,
-        symbol: String,
+        symbol: impl Into<String>,
         user_id: String
     ) -> Self {
         Self {
             hash,
             price_level,
-            symbol,
+            symbol: symbol.into(),
             user_id,
+            condition: Condition::PriceLevel,
+            expires_at: None,
+            direction: None,
+            metadata: None,
+            evaluate_on: EvaluateOn::Tick,
+            tags: Vec::new(),
+            tolerance: None,
+            version: None,
+            upper_price_level: None,
+            sessions: Vec::new(),
         }
     }
 
+    /// Builds an `Alert` from a row fetched from the database.
+    ///
+    /// # Parameters
+    /// - `row`: The JSON row as returned by Supabase.
+    /// - `config`: Configuration with the column names of the alerts table.
+    ///
+    /// # Returns
+    /// Returns the `Alert` stored in the row.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if a required column is missing or has the wrong type.
+    pub fn from_row(
+        row: &Value,
+        config: &TableConfig
+    ) -> Result<Self, SupabaseError> {
+        let string_column = |column: &str| -> Result<String, SupabaseError> {
+            row.get(column)
+                .and_then(|v| v.as_str())
+                .map(String::from)
+                .ok_or_else(|| SupabaseError::FetchError(format!("Column `{}` not found", column)))
+        };
+
+        let price_level: f64 = row.get(&config.price_level_column_name)
+            .and_then(|v| v.as_f64())
+            .ok_or_else(|| SupabaseError::FetchError("Price level not found".to_string()))?;
+
+        let condition: Condition = Condition::from_value(row.get(&config.condition_column_name))
+            .ok_or_else(|| SupabaseError::FetchError("Condition is not valid".to_string()))?;
+
+        let evaluate_on: EvaluateOn = EvaluateOn::from_value(row.get(&config.evaluate_on_column_name))
+            .ok_or_else(|| SupabaseError::FetchError("Evaluation mode is not valid".to_string()))?;
+
+        let expires_at: Option<DateTime<Utc>> = row.get(&config.expiry_column_name)
+            .and_then(|v| v.as_str())
+            .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+            .map(|v| v.with_timezone(&Utc));
+
+        let tags: Vec<String> = row.get(&config.tags_column_name)
+            .and_then(|v| v.as_array())
+            .map(|tags| tags.iter().filter_map(|tag| tag.as_str()).map(String::from).collect())
+            .unwrap_or_default();
+
+        Ok(Self {
+            hash: string_column(&config.hash_column_name)?,
+            price_level,
+            user_id: string_column(&config.user_id_column_name)?,
+            symbol: string_column(&config.symbol_column_name)?,
+            condition,
+            expires_at,
+            direction: string_column(&config.direction_column_name).ok(),
+            metadata: row.get(&config.metadata_column_name).filter(|v| !v.is_null()).cloned(),
+            evaluate_on,
+            tags,
+            tolerance: Tolerance::from_value(row.get(&config.tolerance_column_name)),
+            version: row.get(&config.version_column_name).and_then(|v| v.as_i64()),
+            upper_price_level: row.get(&config.upper_price_level_column_name).and_then(|v| v.as_f64()),
+            sessions: sessions_from_value(row.get(&config.sessions_column_name)),
+        })
+    }
+
+    /// Sets the condition the alert is evaluated against.
+    ///
+    /// # Parameters
+    /// - `condition`: The `Condition` that should trigger the alert.
+    ///
+    /// # Returns
+    /// Returns the `Alert` with the condition applied.
+    pub fn with_condition(
+        mut self,
+        condition: Condition
+    ) -> Self {
+        self.condition = condition;
+        self
+    }
+
+    /// Sets when the alert's condition is evaluated.
+    ///
+    /// # Parameters
+    /// - `evaluate_on`: `EvaluateOn::Tick`, or `EvaluateOn::CandleClose` to ignore intrabar spikes.
+    ///
+    /// # Returns
+    /// Returns the `Alert` with the evaluation mode applied.
+    pub fn with_evaluate_on(
+        mut self,
+        evaluate_on: EvaluateOn
+    ) -> Self {
+        self.evaluate_on = evaluate_on;
+        self
+    }
+
+    /// Attaches freeform JSON metadata to the alert.
+    ///
+    /// # Parameters
+    /// - `metadata`: Any JSON value, e.g. `{"note": "breakout retest", "strategy": "swing"}`.
+    ///
+    /// # Returns
+    /// Returns the `Alert` with the metadata applied.
+    pub fn with_metadata(
+        mut self,
+        metadata: Value
+    ) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Requires the price to move past the level by `tolerance` before the alert triggers.
+    ///
+    /// # Parameters
+    /// - `tolerance`: E.g. `Tolerance::Pips(2.0)` to ignore the price merely kissing the level.
+    ///
+    /// # Returns
+    /// Returns the `Alert` with the tolerance applied.
+    pub fn with_tolerance(
+        mut self,
+        tolerance: Tolerance
+    ) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Sets the tags the alert is organised by.
+    ///
+    /// # Parameters
+    /// - `tags`: The tags, e.g. `vec!["breakout".to_string()]`.
+    ///
+    /// # Returns
+    /// Returns the `Alert` with the tags applied.
+    pub fn with_tags(
+        mut self,
+        tags: Vec<String>
+    ) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Sets the moment after which the alert is no longer evaluated.
+    ///
+    /// # Parameters
+    /// - `expires_at`: The expiry timestamp in UTC.
+    ///
+    /// # Returns
+    /// Returns the `Alert` with the expiry applied.
+    pub fn with_expiry(
+        mut self,
+        expires_at: DateTime<Utc>
+    ) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Turns the alert into a band alert between its price level and `upper_price_level`.
+    ///
+    /// # Parameters
+    /// - `upper_price_level`: The other edge of the band, stored in the upper price level column.
+    /// - `mode`: E.g. `BandMode::Enter` to trigger once the price moves into the band.
+    ///
+    /// # Returns
+    /// Returns the `Alert` with the `Condition::Band` condition applied.
+    pub fn with_band(
+        mut self,
+        upper_price_level: f64,
+        mode: BandMode
+    ) -> Self {
+        self.upper_price_level = Some(upper_price_level);
+        self.condition = Condition::Band(mode);
+        self
+    }
+
     /// ### Adds an alert to the database and handles its triggering.
     ///
     /// This asynchronous method takes a reference to a `Supabase` client and a `TableConfig`,
@@ -69,4 +253,109 @@ impl Alert {
             Err(e) => Err(e)
         }
     }
-}
\ No newline at end of file
+
+    /// ### Updates the alert in the database and applies the changes locally.
+    ///
+    /// ##### Parameters
+    /// - `supabase`: A reference to the `Supabase` client used for database operations.
+    /// - `update`: An `AlertUpdate` with the fields to change.
+    /// - `table_config`: Configuration for the database table where alerts are stored.
+    ///
+    /// ##### Returns
+    /// Returns `Ok(())` if the alert was successfully updated, or an `Err(e)`
+    /// if an error occurred during the operation.
+    ///
+    /// ##### Errors
+    /// Returns an error if the database operation fails, in which case the alert is left unchanged.
+    pub async fn update(
+        &mut self,
+        supabase: &Supabase,
+        update: AlertUpdate,
+        table_config: &TableConfig
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        supabase.update_alert_by_hash(
+            &self.hash,
+            update.clone(),
+            table_config.clone()
+        ).await?;
+
+        if let Some(price_level) = update.price_level {
+            self.price_level = price_level;
+        }
+        if let Some(symbol) = update.symbol {
+            self.symbol = symbol;
+        }
+        if let Some(expires_at) = update.expires_at {
+            self.expires_at = expires_at;
+        }
+        if let Some(direction) = update.direction {
+            self.direction = Some(direction);
+        }
+        if let Some(metadata) = update.metadata {
+            self.metadata = Some(metadata);
+        }
+
+        Ok(())
+    }
+}
+
+impl AlertUpdate {
+    /// Returns `true` if the update does not change any field.
+    pub fn is_empty(&self) -> bool {
+        self.price_level.is_none()
+            && self.symbol.is_none()
+            && self.expires_at.is_none()
+            && self.direction.is_none()
+            && self.metadata.is_none()
+    }
+
+    /// Converts the update to the JSON body of a partial update, using the configured column names.
+    pub fn to_value(&self, config: &TableConfig) -> Value {
+        let mut body: Map<String, Value> = Map::new();
+
+        if let Some(price_level) = self.price_level {
+            body.insert(config.price_level_column_name.clone(), json!(price_level));
+        }
+        if let Some(symbol) = &self.symbol {
+            body.insert(config.symbol_column_name.clone(), json!(symbol));
+        }
+        if let Some(expires_at) = self.expires_at {
+            // A removed expiry is sent as `null`
+            body.insert(config.expiry_column_name.clone(), json!(expires_at.map(|expires_at| expires_at.to_rfc3339())));
+        }
+        if let Some(direction) = &self.direction {
+            body.insert(config.direction_column_name.clone(), json!(direction));
+        }
+        if let Some(metadata) = &self.metadata {
+            body.insert(config.metadata_column_name.clone(), metadata.clone());
+        }
+
+        Value::Object(body)
+    }
+}
+
+/// Returns `true` if the stored expiry timestamp lies in the past.
+pub(crate) fn is_expired(expires_at: Option<&Value>) -> bool {
+    expires_at
+        .and_then(|v| v.as_str())
+        .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+        .is_some_and(|expires_at| expires_at < Utc::now())
+}
+
+/// Parses a stored creation timestamp.
+pub(crate) fn created_at(created_at: Option<&Value>) -> Option<DateTime<Utc>> {
+    created_at
+        .and_then(|v| v.as_str())
+        .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+        .map(|v| v.with_timezone(&Utc))
+}
+
+impl TriggeredAlert {
+    /// Returns how long after the provider's quote time the alert was evaluated.
+    ///
+    /// Negative when the quote is stamped after the evaluation, which points to a clock being off.
+    /// `None` if the provider did not send a quote time.
+    pub fn clock_skew(&self) -> Option<chrono::Duration> {
+        self.quote_time.map(|quote_time| self.triggered_at - quote_time)
+    }
+}
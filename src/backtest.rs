@@ -0,0 +1,244 @@
+//! ## Backtesting alerts against historical candles
+//!
+//! Replays candles in time order and records when each alert would have triggered.
+//! Price conditions are checked against the high and low of every candle, indicator
+//! conditions against the closes seen so far and compound conditions against the latest
+//! close of every symbol they reference. Band conditions compare consecutive closes.
+//! Conditions needing bid/ask or volume data never trigger in a backtest.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::{Alert, Condition};
+use crate::data::{Candle, Quote};
+use crate::utils::output::OutputFormat;
+use crate::utils::symbol::canonical;
+
+/// Columns written by [`BacktestReport::to_csv`], in order.
+pub const BACKTEST_CSV_COLUMNS: &[&str] = &[
+    "hash", "user_id", "symbol", "price_level", "direction", "triggered", "triggered_at", "trigger_price",
+    "time_to_trigger_secs",
+];
+
+/// ## The outcome of a single alert in a backtest
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AlertOutcome {
+    pub hash: String,
+    pub user_id: String,
+    pub symbol: String,
+    pub price_level: f64,
+    /// The direction the alert was evaluated with, stored or derived from the first candle.
+    pub direction: Option<String>,
+    /// The open time of the candle in which the alert triggered, `None` if it never did.
+    pub triggered_at: Option<DateTime<Utc>>,
+    /// The price the alert triggered at: its level when crossed within a candle, the close otherwise.
+    pub trigger_price: Option<f64>,
+    /// Seconds between the first candle of the symbol and the trigger.
+    pub time_to_trigger_secs: Option<i64>,
+}
+
+/// ## Distribution of the time it took alerts to trigger, in seconds
+/// Every field is `None` when no alert triggered.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeToTrigger {
+    pub min_secs: Option<i64>,
+    pub median_secs: Option<i64>,
+    pub p90_secs: Option<i64>,
+    pub max_secs: Option<i64>,
+    pub mean_secs: Option<f64>,
+}
+
+/// ## The result of replaying alerts against historical candles
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BacktestReport {
+    /// The open time of the earliest candle replayed.
+    pub start: Option<DateTime<Utc>>,
+    /// The open time of the latest candle replayed.
+    pub end: Option<DateTime<Utc>>,
+    /// One outcome per alert, in the order the alerts were given.
+    pub outcomes: Vec<AlertOutcome>,
+    pub triggered_count: usize,
+    pub untriggered_count: usize,
+    pub time_to_trigger: TimeToTrigger,
+}
+
+impl BacktestReport {
+    /// Returns the outcomes of the alerts which triggered.
+    pub fn triggered(&self) -> Vec<&AlertOutcome> {
+        self.outcomes.iter().filter(|outcome| outcome.triggered_at.is_some()).collect()
+    }
+
+    /// Returns the outcomes of the alerts which never triggered.
+    pub fn untriggered(&self) -> Vec<&AlertOutcome> {
+        self.outcomes.iter().filter(|outcome| outcome.triggered_at.is_none()).collect()
+    }
+
+    /// Renders one row per alert as CSV, with the columns in [`BACKTEST_CSV_COLUMNS`].
+    pub fn to_csv(&self) -> String {
+        let rows: Vec<Value> = self
+            .outcomes
+            .iter()
+            .map(|outcome| json!({
+                "hash": outcome.hash,
+                "user_id": outcome.user_id,
+                "symbol": outcome.symbol,
+                "price_level": outcome.price_level,
+                "direction": outcome.direction,
+                "triggered": outcome.triggered_at.is_some(),
+                "triggered_at": outcome.triggered_at.map(|at| at.to_rfc3339()),
+                "trigger_price": outcome.trigger_price,
+                "time_to_trigger_secs": outcome.time_to_trigger_secs,
+            }))
+            .collect();
+        OutputFormat::Csv.render(BACKTEST_CSV_COLUMNS, &rows)
+    }
+}
+
+/// Replays `candles` and reports when each of `alerts` would have triggered.
+///
+/// # Parameters
+/// - `alerts`: The alerts to backtest, alerts without a stored direction get one from the
+///   open of the first candle of their symbol.
+/// - `candles`: Candles of any number of symbols, in any order.
+///
+/// # Returns
+/// Returns a `BacktestReport` with one outcome per alert. Alerts without candles for their
+/// symbol are reported as untriggered.
+pub fn backtest(
+    alerts: &[Alert],
+    candles: &[Candle]
+) -> BacktestReport {
+    let mut by_symbol: HashMap<String, Vec<&Candle>> = HashMap::new();
+    for candle in candles {
+        by_symbol.entry(canonical(&candle.symbol)).or_default().push(candle);
+    }
+    for series in by_symbol.values_mut() {
+        series.sort_by_key(|candle| candle.open_time);
+    }
+
+    let outcomes: Vec<AlertOutcome> = alerts.iter().map(|alert| replay(alert, &by_symbol)).collect();
+
+    let mut durations: Vec<i64> = outcomes.iter().filter_map(|outcome| outcome.time_to_trigger_secs).collect();
+    durations.sort_unstable();
+    let triggered_count: usize = outcomes.iter().filter(|outcome| outcome.triggered_at.is_some()).count();
+
+    BacktestReport {
+        start: candles.iter().map(|candle| candle.open_time).min(),
+        end: candles.iter().map(|candle| candle.open_time).max(),
+        triggered_count,
+        untriggered_count: outcomes.len() - triggered_count,
+        outcomes,
+        time_to_trigger: distribution(&durations),
+    }
+}
+
+fn replay(
+    alert: &Alert,
+    by_symbol: &HashMap<String, Vec<&Candle>>
+) -> AlertOutcome {
+    let series: &[&Candle] = by_symbol.get(&canonical(&alert.symbol)).map(Vec::as_slice).unwrap_or_default();
+    let direction: Option<String> = alert.direction.clone().or_else(|| {
+        series.first().map(|first| if first.open > alert.price_level { "buy" } else { "sell" }.to_string())
+    });
+
+    let mut outcome = AlertOutcome {
+        hash: alert.hash.clone(),
+        user_id: alert.user_id.clone(),
+        symbol: alert.symbol.clone(),
+        price_level: alert.price_level,
+        direction: direction.clone(),
+        triggered_at: None,
+        trigger_price: None,
+        time_to_trigger_secs: None,
+    };
+    let (Some(first), Some(direction)) = (series.first(), direction) else {
+        return outcome;
+    };
+
+    let mut closes: Vec<f64> = Vec::with_capacity(series.len());
+    for candle in series {
+        if alert.expires_at.is_some_and(|expires_at| candle.open_time > expires_at) {
+            break;
+        }
+        closes.push(candle.close);
+
+        if let Some(price) = trigger_price(alert, &direction, candle, &closes, by_symbol) {
+            outcome.triggered_at = Some(candle.open_time);
+            outcome.trigger_price = Some(price);
+            outcome.time_to_trigger_secs = Some((candle.open_time - first.open_time).num_seconds());
+            break;
+        }
+    }
+    outcome
+}
+
+fn trigger_price(
+    alert: &Alert,
+    direction: &str,
+    candle: &Candle,
+    closes: &[f64],
+    by_symbol: &HashMap<String, Vec<&Candle>>
+) -> Option<f64> {
+    let quote = |price: f64| Quote::new(candle.symbol.clone(), price);
+    let level: f64 = alert.price_level;
+
+    match &alert.condition {
+        Condition::PriceLevel => {
+            let extreme: f64 = if direction == "sell" { candle.high } else { candle.low };
+            alert
+                .condition
+                .is_met_with_tolerance(level, direction, &quote(extreme), alert.tolerance)
+                .then_some(level)
+        },
+        Condition::PriceAbove(above) => {
+            alert.condition.is_met_with_tolerance(level, direction, &quote(candle.high), alert.tolerance)
+                .then_some(above.max(candle.open))
+        },
+        Condition::PriceBelow(below) => {
+            alert.condition.is_met_with_tolerance(level, direction, &quote(candle.low), alert.tolerance)
+                .then_some(below.min(candle.open))
+        },
+        Condition::At(_) => alert.condition.is_due(candle.open_time).then_some(candle.close),
+        Condition::Band(_) => {
+            let upper: f64 = alert.upper_price_level?;
+            let previous: Option<f64> = closes.len().checked_sub(2).map(|i| closes[i]);
+            alert.condition.is_met_on_band(level, upper, previous, candle.close).then_some(candle.close)
+        },
+        Condition::Compound(tree) => {
+            let quotes: Vec<Quote> = tree
+                .symbols()
+                .into_iter()
+                .filter_map(|symbol| {
+                    let series = by_symbol.get(&canonical(symbol))?;
+                    let seen: usize = series.partition_point(|other| other.open_time <= candle.open_time);
+                    seen.checked_sub(1).map(|last| Quote::new(symbol.to_string(), series[last].close))
+                })
+                .collect();
+            tree.is_met(level, direction, &quotes).then_some(candle.close)
+        },
+        condition if condition.candles_needed().is_some() => {
+            condition.is_met_on_closes(closes).then_some(candle.close)
+        },
+        condition => condition.is_met(level, direction, &quote(candle.close)).then_some(candle.close),
+    }
+}
+
+fn distribution(sorted: &[i64]) -> TimeToTrigger {
+    if sorted.is_empty() {
+        return TimeToTrigger::default();
+    }
+    let percentile = |p: f64| -> i64 {
+        let rank: usize = ((p * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len());
+        sorted[rank - 1]
+    };
+    TimeToTrigger {
+        min_secs: sorted.first().copied(),
+        median_secs: Some(percentile(0.5)),
+        p90_secs: Some(percentile(0.9)),
+        max_secs: sorted.last().copied(),
+        mean_secs: Some(sorted.iter().sum::<i64>() as f64 / sorted.len() as f64),
+    }
+}
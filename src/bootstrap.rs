@@ -0,0 +1,196 @@
+//! ## First-run setup verification
+//!
+//! Walks through everything a new installation needs — environment, table configuration,
+//! Supabase connectivity and schema, price provider credentials and a test fetch — and
+//! returns a checklist that CLIs and apps can render.
+//!
+//! Checks which depend on an earlier failed check are skipped instead of failing with a
+//! confusing follow-up error.
+
+use std::env::var;
+use std::fmt;
+
+use dotenv::dotenv;
+
+use crate::data::XylexApi;
+use crate::db::{Supabase, TableConfig};
+
+/// The symbol fetched to verify the price provider when none is given.
+pub const DEFAULT_TEST_SYMBOL: &str = "EUR/USD";
+
+/// Environment variables without which the crate can't run.
+pub const REQUIRED_ENV_VARS: &[&str] = &[
+    "SUPABASE_KEY",
+    "SUPABASE_URL",
+    "XYLEX_API_KEY",
+    "XYLEX_API_ENDPOINT",
+    "TABLE_NAME",
+    "HASH_COLUMN_NAME",
+    "PRICE_LEVEL_COLUMN_NAME",
+    "USER_ID_COLUMN_NAME",
+    "SYMBOL_COLUMN_NAME",
+];
+
+/// ## Outcome of a single setup check
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    /// Not run because a check it depends on failed.
+    Skipped,
+}
+
+/// ## A single item of the setup checklist
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SetupCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    /// What was verified, or what went wrong and how to fix it.
+    pub detail: String,
+}
+
+/// ## The full setup checklist, in the order the checks ran
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SetupReport {
+    pub checks: Vec<SetupCheck>,
+}
+
+/// Verifies the setup using [`DEFAULT_TEST_SYMBOL`] for the test fetch.
+pub async fn verify_setup() -> SetupReport {
+    verify_setup_with_symbol(DEFAULT_TEST_SYMBOL).await
+}
+
+/// Verifies the setup, fetching a quote for `test_symbol` to check the price provider.
+///
+/// # Returns
+/// A `SetupReport` with the checks `environment`, `table_config`, `supabase_connectivity`,
+/// `table_schema`, `provider_auth` and `price_fetch`.
+pub async fn verify_setup_with_symbol(test_symbol: &str) -> SetupReport {
+    dotenv().ok();
+    let mut report: SetupReport = SetupReport::default();
+
+    let missing: Vec<&str> = REQUIRED_ENV_VARS.iter().copied().filter(|name| var(name).is_err()).collect();
+    if missing.is_empty() {
+        report.pass("environment", "All required environment variables are set");
+    } else {
+        report.fail("environment", format!("Missing environment variables: {}", missing.join(", ")));
+    }
+
+    let config: Option<TableConfig> = match TableConfig::new_env() {
+        Ok(config) => {
+            report.pass("table_config", format!("Using table `{}`", config.tablename));
+            Some(config)
+        },
+        Err(e) => {
+            report.fail("table_config", e.to_string());
+            None
+        },
+    };
+
+    let tablename: String = config.as_ref().map_or_else(|| TableConfig::default().tablename, |c| c.tablename.clone());
+    let supabase: Result<Supabase, String> = Supabase::new_env().await.map_err(|e| e.to_string());
+    let supabase: Option<Supabase> = match supabase {
+        Ok(supabase) => match supabase.rest_select(&tablename, &limit_one("id")).await {
+            Ok(_) => {
+                report.pass("supabase_connectivity", format!("Connected to {}", supabase.url));
+                Some(supabase)
+            },
+            Err(e) => {
+                report.fail("supabase_connectivity", format!("Could not query Supabase: {}", e));
+                None
+            },
+        },
+        Err(e) => {
+            report.fail("supabase_connectivity", e);
+            None
+        },
+    };
+
+    match (&supabase, &config) {
+        (Some(supabase), Some(config)) => {
+            let columns: String = [
+                &config.hash_column_name,
+                &config.price_level_column_name,
+                &config.user_id_column_name,
+                &config.symbol_column_name,
+                &config.direction_column_name,
+            ]
+            .map(String::as_str)
+            .join(",");
+            match supabase.rest_select(&config.tablename, &limit_one(&columns)).await {
+                Ok(_) => report.pass("table_schema", format!("Table `{}` has the columns {}", config.tablename, columns)),
+                Err(e) => report.fail("table_schema", format!("Table `{}` is missing columns: {}", config.tablename, e)),
+            }
+        },
+        _ => report.skip("table_schema", "Needs a table configuration and a Supabase connection"),
+    }
+
+    let api: Option<XylexApi> = match XylexApi::new_env().await {
+        Ok(api) => {
+            report.pass("provider_auth", format!("Using {}", api.endpoint));
+            Some(api)
+        },
+        Err(e) => {
+            report.fail("provider_auth", e.to_string());
+            None
+        },
+    };
+
+    match api {
+        Some(api) => match api.request_real_time_quote(test_symbol).await {
+            Ok(quote) => report.pass("price_fetch", format!("{} is at {}", test_symbol, quote.price)),
+            Err(e) => report.fail("price_fetch", format!("Could not fetch {}: {}", test_symbol, e)),
+        },
+        None => report.skip("price_fetch", "Needs price provider credentials"),
+    }
+
+    report
+}
+
+impl SetupReport {
+    /// Returns `true` if every check passed.
+    pub fn is_ready(&self) -> bool {
+        self.checks.iter().all(|check| check.status == CheckStatus::Passed)
+    }
+
+    /// Returns the checks which failed.
+    pub fn failures(&self) -> Vec<&SetupCheck> {
+        self.checks.iter().filter(|check| check.status == CheckStatus::Failed).collect()
+    }
+
+    fn pass(&mut self, name: &str, detail: impl Into<String>) {
+        self.push(name, CheckStatus::Passed, detail.into());
+    }
+
+    fn fail(&mut self, name: &str, detail: impl Into<String>) {
+        self.push(name, CheckStatus::Failed, detail.into());
+    }
+
+    fn skip(&mut self, name: &str, detail: impl Into<String>) {
+        self.push(name, CheckStatus::Skipped, detail.into());
+    }
+
+    fn push(&mut self, name: &str, status: CheckStatus, detail: String) {
+        self.checks.push(SetupCheck { name: name.to_string(), status, detail });
+    }
+}
+
+/// Display implementation for `SetupReport`, one `[ok]`, `[failed]` or `[skipped]` line per check.
+impl fmt::Display for SetupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            let status: &str = match check.status {
+                CheckStatus::Passed => "ok",
+                CheckStatus::Failed => "failed",
+                CheckStatus::Skipped => "skipped",
+            };
+            writeln!(f, "[{}] {}: {}", status, check.name, check.detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// Selects a single row with only the given columns.
+fn limit_one(columns: &str) -> [(String, String); 2] {
+    [("select".to_string(), columns.to_string()), ("limit".to_string(), "1".to_string())]
+}
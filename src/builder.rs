@@ -0,0 +1,175 @@
+//! ## Alert builder
+//!
+//! Named setters for every field of an `Alert`, so the `symbol` and `user_id`
+//! strings can't be swapped by accident as they can with [`Alert::new`].
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::errors::AlertError;
+use crate::market_hours::Session;
+use crate::utils::format::hash_alert;
+use crate::{Alert, AlertBuilder, BandMode, Condition, EvaluateOn, Tolerance};
+
+impl Alert {
+    /// Starts building an `Alert`.
+    ///
+    /// ### Usage example
+    /// ```rust
+    /// use trade_alerts::Alert;
+    ///
+    /// let alert = Alert::builder()
+    ///     .user_id("user123")
+    ///     .symbol("eur/usd")
+    ///     .price_level(1.1)
+    ///     .tags(vec!["breakout".to_string()])
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(!alert.hash.is_empty());
+    /// ```
+    pub fn builder() -> AlertBuilder {
+        AlertBuilder::default()
+    }
+}
+
+impl AlertBuilder {
+    /// Sets the hash. When not set, it is generated from the user ID, symbol and price level.
+    pub fn hash(mut self, hash: impl Into<String>) -> Self {
+        self.hash = Some(hash.into());
+        self
+    }
+
+    /// Sets the prefix of the generated hash, ignored when the hash is set explicitly.
+    pub fn hash_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.hash_prefix = prefix.into();
+        self
+    }
+
+    /// Sets the user who owns the alert.
+    pub fn user_id(mut self, user_id: impl Into<String>) -> Self {
+        self.user_id = Some(user_id.into());
+        self
+    }
+
+    /// Sets the symbol the alert is set on.
+    pub fn symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    /// Sets the price level at which the alert should trigger.
+    pub fn price_level(mut self, price_level: f64) -> Self {
+        self.price_level = Some(price_level);
+        self
+    }
+
+    /// Sets the condition, `Condition::PriceLevel` by default.
+    pub fn condition(mut self, condition: Condition) -> Self {
+        self.condition = condition;
+        self
+    }
+
+    /// Sets the moment after which the alert is no longer evaluated.
+    pub fn expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Attaches freeform JSON metadata.
+    pub fn metadata(mut self, metadata: Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Sets when the condition is evaluated, `EvaluateOn::Tick` by default.
+    pub fn evaluate_on(mut self, evaluate_on: EvaluateOn) -> Self {
+        self.evaluate_on = evaluate_on;
+        self
+    }
+
+    /// Sets the tags the alert is organised by.
+    pub fn tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Requires the price to move past the level by `tolerance` before the alert triggers.
+    pub fn tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Restricts the alert to trading sessions, e.g. only during the London session.
+    pub fn sessions(mut self, sessions: Vec<Session>) -> Self {
+        self.sessions = sessions;
+        self
+    }
+
+    /// Makes the alert a band alert between the price level and `upper_price_level`.
+    pub fn band(mut self, upper_price_level: f64, mode: BandMode) -> Self {
+        self.upper_price_level = Some(upper_price_level);
+        self.condition = Condition::Band(mode);
+        self
+    }
+
+    /// Validates the fields and builds the `Alert`.
+    ///
+    /// # Errors
+    /// Returns `AlertError::MissingField` if the user ID, symbol or price level is not set,
+    /// or `AlertError::InvalidField` if one of them is empty or the price level is not a finite number.
+    /// Band alerts additionally need a finite upper price level.
+    pub fn build(self) -> Result<Alert, AlertError> {
+        let user_id: String = required_text(self.user_id, "user_id")?;
+        let symbol: String = required_text(self.symbol, "symbol")?;
+        let price_level: f64 = self.price_level.ok_or_else(|| AlertError::MissingField("price_level".to_string()))?;
+        if !price_level.is_finite() {
+            return Err(AlertError::InvalidField(format!("price_level must be a finite number, got {}", price_level)));
+        }
+
+        if let Condition::Band(_) = self.condition {
+            match self.upper_price_level {
+                None => return Err(AlertError::MissingField("upper_price_level".to_string())),
+                Some(upper) if !upper.is_finite() => {
+                    return Err(AlertError::InvalidField(format!(
+                        "upper_price_level must be a finite number, got {}",
+                        upper
+                    )));
+                },
+                Some(_) => {},
+            }
+        }
+
+        let hash: String = match self.hash {
+            Some(hash) => required_text(Some(hash), "hash")?,
+            None => hash_alert(&user_id, &symbol, price_level, &self.hash_prefix),
+        };
+
+        Ok(Alert {
+            hash,
+            price_level,
+            user_id,
+            symbol,
+            condition: self.condition,
+            expires_at: self.expires_at,
+            direction: None,
+            metadata: self.metadata,
+            evaluate_on: self.evaluate_on,
+            tags: self.tags,
+            tolerance: self.tolerance,
+            version: None,
+            upper_price_level: self.upper_price_level,
+            sessions: self.sessions,
+        })
+    }
+}
+
+fn required_text(
+    value: Option<String>,
+    field: &str
+) -> Result<String, AlertError> {
+    match value {
+        None => Err(AlertError::MissingField(field.to_string())),
+        Some(value) if value.trim().is_empty() => Err(AlertError::InvalidField(format!("{} must not be empty", field))),
+        Some(value) => Ok(value),
+    }
+}
@@ -0,0 +1,62 @@
+//! ## Chat commands
+//!
+//! Parses the simple text commands accepted by the inbound command webhook, e.g.
+//! `alert eurusd above 1.10`, `alert btcusd at 65000` or `delete <hash>`.
+
+use std::str::FromStr;
+
+use crate::Condition;
+
+/// ## A parsed chat command
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlertCommand {
+    /// Creates an alert on `symbol` at `price_level`.
+    Create {
+        symbol: String,
+        price_level: f64,
+        condition: Condition,
+    },
+    /// Deletes the alert with the given hash.
+    Delete {
+        hash: String,
+    },
+    /// Lists the user's alerts.
+    List,
+}
+
+/// Usage shown when a command can't be parsed.
+pub const USAGE: &str = "Usage: `alert <symbol> above|below|at <price>`, `delete <hash>` or `list`";
+
+impl FromStr for AlertCommand {
+    type Err = String;
+
+    /// Parses a command, case insensitive.
+    ///
+    /// - `above` creates a `Condition::PriceAbove` alert.
+    /// - `below` creates a `Condition::PriceBelow` alert.
+    /// - `at` and `cross` create a `Condition::PriceLevel` alert, triggering when the price crosses the level.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<String> = s.split_whitespace().map(str::to_lowercase).collect();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+
+        match words.as_slice() {
+            ["alert", symbol, comparison, price] => {
+                let price_level: f64 = price
+                    .parse()
+                    .ok()
+                    .filter(|price: &f64| price.is_finite() && *price > 0.0)
+                    .ok_or_else(|| format!("`{}` is not a valid price", price))?;
+                let condition: Condition = match *comparison {
+                    "above" => Condition::PriceAbove(price_level),
+                    "below" => Condition::PriceBelow(price_level),
+                    "at" | "cross" => Condition::PriceLevel,
+                    other => return Err(format!("Unknown comparison `{}`. {}", other, USAGE)),
+                };
+                Ok(AlertCommand::Create { symbol: symbol.to_string(), price_level, condition })
+            },
+            ["delete", hash] => Ok(AlertCommand::Delete { hash: hash.to_string() }),
+            ["list"] => Ok(AlertCommand::List),
+            _ => Err(USAGE.to_string()),
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! ## Alert conditions
+//!
+//! Conversion of conditions to and from their database representation and
+//! evaluation of a condition against a freshly fetched quote.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::{BandMode, Condition, ConditionTree, EvaluateOn, Tolerance};
+use crate::data::Quote;
+use crate::indicators::{RSI_PERIOD, rsi, sma_crossed};
+use crate::utils::symbol::same_symbol;
+
+impl Condition {
+    /// Reads a condition from the value stored in the condition column.
+    ///
+    /// # Parameters
+    /// - `value`: The stored value, `None` or `null` when the column is empty or missing.
+    ///
+    /// # Returns
+    /// Returns `Some(Condition::PriceLevel)` for empty values, the parsed condition otherwise,
+    /// or `None` if the stored value is not a valid condition.
+    pub fn from_value(value: Option<&Value>) -> Option<Self> {
+        match value {
+            None | Some(Value::Null) => Some(Condition::PriceLevel),
+            Some(value) => serde_json::from_value(value.clone()).ok(),
+        }
+    }
+
+    /// Converts the condition to the JSON value stored in the condition column.
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    /// Returns `true` if the condition is evaluated against the clock instead of price data.
+    pub fn is_time_based(&self) -> bool {
+        matches!(self, Condition::At(_))
+    }
+
+    /// Checks whether a time-based condition is due at the given time.
+    ///
+    /// # Returns
+    /// Returns `true` if the condition is time-based and its time has passed, `false` otherwise.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        match self {
+            Condition::At(at) => now >= *at,
+            _ => false,
+        }
+    }
+
+    /// Returns the price levels the condition triggers at, given the alert's price level and
+    /// upper price level, empty for conditions which don't trigger on a price level.
+    pub fn levels(
+        &self,
+        price_level: f64,
+        upper_price_level: Option<f64>
+    ) -> Vec<f64> {
+        match self {
+            Condition::PriceLevel => vec![price_level],
+            Condition::Band(_) => std::iter::once(price_level).chain(upper_price_level).collect(),
+            Condition::PriceAbove(level) | Condition::PriceBelow(level) => vec![*level],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the condition a crossing of the price must still meet on the next check to be
+    /// confirmed, `None` for conditions which don't trigger on the price crossing a level.
+    ///
+    /// Level conditions confirm themselves, band crossings are confirmed by the price staying
+    /// on the side of the band it crossed to.
+    pub fn crossing_confirmation(&self) -> Option<Condition> {
+        match self {
+            Condition::PriceLevel | Condition::PriceAbove(_) | Condition::PriceBelow(_) => Some(self.clone()),
+            Condition::Band(BandMode::Enter) => Some(Condition::Band(BandMode::Inside)),
+            Condition::Band(BandMode::Exit) => Some(Condition::Band(BandMode::Outside)),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of candles needed to evaluate an indicator condition,
+    /// `None` for conditions evaluated against a quote.
+    pub fn candles_needed(&self) -> Option<usize> {
+        match self {
+            // Wilder's smoothing needs more closes than the period to settle
+            Condition::RsiAbove(_) | Condition::RsiBelow(_) => Some(RSI_PERIOD * 7),
+            Condition::SmaCross { fast, slow } => Some(fast.max(slow) + 1),
+            _ => None,
+        }
+    }
+
+    /// Checks whether an indicator condition is met by the given candle closes, oldest first.
+    ///
+    /// # Returns
+    /// Returns `false` for conditions which aren't indicator conditions, or when there are too few closes.
+    pub fn is_met_on_closes(
+        &self,
+        closes: &[f64]
+    ) -> bool {
+        match self {
+            Condition::RsiAbove(value) => rsi(closes, RSI_PERIOD).is_some_and(|rsi| rsi > *value),
+            Condition::RsiBelow(value) => rsi(closes, RSI_PERIOD).is_some_and(|rsi| rsi < *value),
+            Condition::SmaCross { fast, slow } => sma_crossed(closes, *fast, *slow),
+            _ => false,
+        }
+    }
+
+    /// Checks whether a band condition is met by the price and the price at the previous check.
+    ///
+    /// # Parameters
+    /// - `price_level`: One edge of the band, the alert's price level.
+    /// - `upper_price_level`: The other edge of the band, the edges may be given in either order.
+    /// - `previous_price`: The price at the previous check, `None` on the first check of a symbol.
+    /// - `price`: The latest price.
+    ///
+    /// # Returns
+    /// Returns `false` for conditions which aren't band conditions. `BandMode::Enter` and
+    /// `BandMode::Exit` never hold without a previous price, the first check only records it.
+    pub fn is_met_on_band(
+        &self,
+        price_level: f64,
+        upper_price_level: f64,
+        previous_price: Option<f64>,
+        price: f64
+    ) -> bool {
+        let (low, high) = if price_level <= upper_price_level {
+            (price_level, upper_price_level)
+        } else {
+            (upper_price_level, price_level)
+        };
+        let inside = |price: f64| (low..=high).contains(&price);
+
+        match self {
+            Condition::Band(BandMode::Enter) => previous_price.is_some_and(|previous| !inside(previous)) && inside(price),
+            Condition::Band(BandMode::Exit) => previous_price.is_some_and(inside) && !inside(price),
+            Condition::Band(BandMode::Inside) => inside(price),
+            Condition::Band(BandMode::Outside) => !inside(price),
+            _ => false,
+        }
+    }
+
+    /// Checks whether the condition is met by the given quote.
+    ///
+    /// Indicator conditions need candle history and never hold here, see [`Condition::is_met_on_closes`].
+    /// Band conditions need both levels and the previous price, see [`Condition::is_met_on_band`].
+    ///
+    /// Time-based conditions are checked against the current time, see [`Condition::is_due`].
+    /// Compound conditions only see the given quote, use [`ConditionTree::is_met`] to evaluate
+    /// them against the quotes of every symbol they reference.
+    ///
+    /// # Parameters
+    /// - `price_level`: The price level stored on the alert.
+    /// - `initial_direction`: The direction stored when the alert was created (`buy` or `sell`).
+    /// - `quote`: The latest quote for the alert's symbol.
+    ///
+    /// # Returns
+    /// Returns `true` if the alert should trigger.
+    pub fn is_met(
+        &self,
+        price_level: f64,
+        initial_direction: &str,
+        quote: &Quote
+    ) -> bool {
+        match self {
+            Condition::PriceLevel => {
+                (initial_direction == "sell" && quote.price >= price_level)
+                    || (initial_direction == "buy" && quote.price <= price_level)
+            },
+            Condition::SpreadAbove(pips) => {
+                quote.spread_pips().is_some_and(|spread| spread > *pips)
+            },
+            Condition::At(_) => self.is_due(Utc::now()),
+            Condition::PriceAbove(level) => quote.price > *level,
+            Condition::PriceBelow(level) => quote.price < *level,
+            Condition::VolumeAbove(volume) => quote.volume.is_some_and(|v| v > *volume),
+            Condition::VolumeSpike { multiple_of_average } => match (quote.volume, quote.average_volume) {
+                (Some(volume), Some(average)) => average > 0.0 && volume > average * multiple_of_average,
+                _ => false,
+            },
+            Condition::RsiAbove(_) | Condition::RsiBelow(_) | Condition::SmaCross { .. } => false,
+            Condition::Band(_) => false,
+            Condition::Compound(tree) => {
+                tree.is_met(price_level, initial_direction, std::slice::from_ref(quote))
+            },
+        }
+    }
+
+    /// Checks whether the condition is met, requiring price conditions to clear their level by `tolerance`.
+    ///
+    /// The level of `PriceLevel`, `PriceAbove` and `PriceBelow` conditions is moved away from the
+    /// side the price approaches from; every other condition is checked as in [`Condition::is_met`].
+    pub fn is_met_with_tolerance(
+        &self,
+        price_level: f64,
+        initial_direction: &str,
+        quote: &Quote,
+        tolerance: Option<Tolerance>
+    ) -> bool {
+        let Some(tolerance) = tolerance else {
+            return self.is_met(price_level, initial_direction, quote);
+        };
+
+        match self {
+            Condition::PriceLevel => {
+                let offset: f64 = tolerance.offset(price_level, quote);
+                match initial_direction {
+                    "sell" => quote.price >= price_level + offset,
+                    "buy" => quote.price <= price_level - offset,
+                    _ => false,
+                }
+            },
+            Condition::PriceAbove(level) => quote.price > level + tolerance.offset(*level, quote),
+            Condition::PriceBelow(level) => quote.price < level - tolerance.offset(*level, quote),
+            condition => condition.is_met(price_level, initial_direction, quote),
+        }
+    }
+}
+
+impl Tolerance {
+    /// Reads a tolerance from the value stored in the tolerance column.
+    ///
+    /// # Returns
+    /// Returns `None` for empty or invalid values, so those alerts trigger on touch.
+    pub fn from_value(value: Option<&Value>) -> Option<Self> {
+        match value {
+            None | Some(Value::Null) => None,
+            Some(value) => serde_json::from_value(value.clone()).ok(),
+        }
+    }
+
+    /// Converts the tolerance to the JSON value stored in the tolerance column.
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    /// Returns the tolerance as a price distance from `level`.
+    pub fn offset(
+        &self,
+        level: f64,
+        quote: &Quote
+    ) -> f64 {
+        match self {
+            Tolerance::Pips(pips) => pips.abs() * quote.pip_size(),
+            Tolerance::Percent(percent) => (level * percent / 100.0).abs(),
+        }
+    }
+}
+
+impl FromStr for Tolerance {
+    type Err = String;
+
+    /// Parses a distance such as `5pips` or `0.1%`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s: &str = s.trim();
+        let (amount, tolerance): (&str, fn(f64) -> Tolerance) = match (s.strip_suffix('%'), s.strip_suffix("pips")) {
+            (Some(amount), _) => (amount, Tolerance::Percent),
+            (_, Some(amount)) => (amount, Tolerance::Pips),
+            _ => return Err(format!("`{}` is not in pips or percent, e.g. `5pips` or `0.1%`", s)),
+        };
+        match amount.trim().parse::<f64>() {
+            Ok(amount) if amount.is_finite() && amount >= 0.0 => Ok(tolerance(amount)),
+            _ => Err(format!("`{}` is not a valid distance", s)),
+        }
+    }
+}
+
+impl EvaluateOn {
+    /// Reads the evaluation mode from the value stored in the evaluate-on column.
+    ///
+    /// # Returns
+    /// Returns `Some(EvaluateOn::Tick)` for empty values, the parsed mode otherwise,
+    /// or `None` if the stored value is not a valid mode.
+    pub fn from_value(value: Option<&Value>) -> Option<Self> {
+        match value {
+            None | Some(Value::Null) => Some(EvaluateOn::Tick),
+            Some(value) => serde_json::from_value(value.clone()).ok(),
+        }
+    }
+
+    /// Converts the evaluation mode to the JSON value stored in the evaluate-on column.
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+}
+
+impl ConditionTree {
+    /// Returns every symbol referenced by the tree, including nested compound conditions.
+    pub fn symbols(&self) -> Vec<&str> {
+        match self {
+            ConditionTree::Leaf { symbol, condition } => {
+                let mut symbols: Vec<&str> = vec![symbol.as_str()];
+                if let Condition::Compound(tree) = condition.as_ref() {
+                    symbols.extend(tree.symbols());
+                }
+                symbols
+            },
+            ConditionTree::All(children) | ConditionTree::Any(children) => {
+                children.iter().flat_map(ConditionTree::symbols).collect()
+            },
+        }
+    }
+
+    /// Evaluates the tree against the latest quotes.
+    ///
+    /// # Parameters
+    /// - `price_level`: The price level stored on the alert, used by `Condition::PriceLevel` leaves.
+    /// - `initial_direction`: The direction stored when the alert was created.
+    /// - `quotes`: The latest quotes, a leaf whose symbol has no quote does not hold.
+    ///
+    /// # Returns
+    /// Returns `true` if the expression holds. An empty `All` holds, an empty `Any` does not.
+    pub fn is_met(
+        &self,
+        price_level: f64,
+        initial_direction: &str,
+        quotes: &[Quote]
+    ) -> bool {
+        match self {
+            ConditionTree::Leaf { symbol, condition } => match condition.as_ref() {
+                Condition::Compound(tree) => tree.is_met(price_level, initial_direction, quotes),
+                condition => quotes
+                    .iter()
+                    .find(|quote| same_symbol(&quote.symbol, symbol))
+                    .is_some_and(|quote| condition.is_met(price_level, initial_direction, quote)),
+            },
+            ConditionTree::All(children) => {
+                children.iter().all(|child| child.is_met(price_level, initial_direction, quotes))
+            },
+            ConditionTree::Any(children) => {
+                children.iter().any(|child| child.is_met(price_level, initial_direction, quotes))
+            },
+        }
+    }
+}
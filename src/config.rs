@@ -0,0 +1,92 @@
+//! ## Configuration helpers
+//! Shared parsing of configuration values such as polling intervals, cooldowns and TTLs.
+//!
+//! Durations are written as a number followed by a unit, e.g. `"30s"`, `"5m"` or `"1h"`.
+//! Units can be combined (`"1h30m"`) and a bare number is read as seconds, so existing
+//! `*_SECS` settings keep working.
+//!
+//! | Unit | Meaning      |
+//! |------|--------------|
+//! | `ms` | milliseconds |
+//! | `s`  | seconds      |
+//! | `m`  | minutes      |
+//! | `h`  | hours        |
+//! | `d`  | days         |
+
+use std::env::var;
+use std::time::Duration;
+
+use crate::errors::ConfigError;
+
+/// Parses a human-readable duration such as `"30s"`, `"5m"` or `"1h30m"`.
+///
+/// # Errors
+/// Returns a description of the problem if the value is empty, has an unknown unit or overflows.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value: &str = value.trim();
+    if value.is_empty() {
+        return Err("duration is empty".to_string());
+    }
+    if let Ok(secs) = value.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total: Duration = Duration::ZERO;
+    let mut rest: &str = value;
+    while !rest.is_empty() {
+        let digits: usize = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if digits == 0 {
+            return Err(format!("expected a number in `{}`", value));
+        }
+        let amount: u64 = rest[..digits].parse().map_err(|_| format!("`{}` is too large", value))?;
+        rest = &rest[digits..];
+
+        let unit_len: usize = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let unit: Duration = match rest[..unit_len].trim() {
+            "ms" => Duration::from_millis(1),
+            "s" => Duration::from_secs(1),
+            "m" => Duration::from_secs(60),
+            "h" => Duration::from_secs(60 * 60),
+            "d" => Duration::from_secs(24 * 60 * 60),
+            "" => return Err(format!("missing unit after `{}` in `{}`", amount, value)),
+            unit => return Err(format!("unknown unit `{}` in `{}`, expected ms, s, m, h or d", unit, value)),
+        };
+        rest = &rest[unit_len..];
+
+        total = unit
+            .checked_mul(u32::try_from(amount).map_err(|_| format!("`{}` is too large", value))?)
+            .and_then(|part| total.checked_add(part))
+            .ok_or_else(|| format!("`{}` is too large", value))?;
+    }
+
+    Ok(total)
+}
+
+/// Parses the duration set for a configuration field.
+///
+/// # Arguments
+/// * `field` - The name of the setting, used in the error.
+/// * `value` - The human-readable duration.
+///
+/// # Errors
+/// Returns `ConfigError::InvalidField` naming `field` if the value is not a valid duration.
+pub fn duration_field(
+    field: &str,
+    value: &str
+) -> Result<Duration, ConfigError> {
+    parse_duration(value).map_err(|reason| ConfigError::InvalidField { field: field.to_string(), reason })
+}
+
+/// Reads an optional duration from an environment variable.
+///
+/// # Returns
+/// `Ok(None)` if the variable is not set.
+///
+/// # Errors
+/// Returns `ConfigError::InvalidField` naming the variable if it is set to an invalid duration.
+pub fn env_duration(name: &str) -> Result<Option<Duration>, ConfigError> {
+    match var(name) {
+        Ok(value) => duration_field(name, &value).map(Some),
+        Err(_) => Ok(None),
+    }
+}
@@ -0,0 +1,275 @@
+//! ## Alpaca price provider
+//! US equity prices from Alpaca's market data API, so stock alerts such as "AAPL above 200" work.
+//!
+//! Snapshots carry the latest trade, the best bid and ask and the daily bars. Trades outside the
+//! regular session (09:30 to 16:00 New York) are only quoted with `extended_hours` enabled;
+//! otherwise the close of the last regular session is quoted until the market opens again.
+
+use std::env::var;
+use std::fmt;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveTime, Utc};
+use serde_json::Value;
+
+use crate::data::{AlpacaPlan, AlpacaProvider, PriceProvider, Quote, RateLimiter};
+use crate::data::request::{parse_optional_number, parse_timestamp};
+use crate::errors::XylexApiError;
+use crate::market_hours::{AssetClass, new_york_time};
+
+/// Base URL of Alpaca's market data API.
+pub const ALPACA_DATA_ENDPOINT: &str = "https://data.alpaca.markets";
+
+impl AlpacaPlan {
+    /// Returns the data feed included in the plan, `iex` or `sip`.
+    pub fn feed(&self) -> &'static str {
+        match self {
+            AlpacaPlan::Basic => "iex",
+            AlpacaPlan::AlgoTraderPlus => "sip",
+        }
+    }
+
+    /// Returns the number of market data requests the plan allows per minute.
+    pub fn requests_per_minute(&self) -> u32 {
+        match self {
+            AlpacaPlan::Basic => 200,
+            AlpacaPlan::AlgoTraderPlus => 10_000,
+        }
+    }
+
+    /// Returns a `RateLimiter` keeping to the plan's limit.
+    pub fn rate_limiter(&self) -> RateLimiter {
+        RateLimiter::new(self.requests_per_minute() as f64 / 60.0)
+    }
+}
+
+impl AlpacaProvider {
+    /// Creates an `AlpacaProvider` on the free `Basic` plan, quoting regular session prices only.
+    ///
+    /// # Arguments
+    /// * `key_id` - The API key ID.
+    /// * `secret_key` - The API secret key.
+    pub fn new(
+        key_id: String,
+        secret_key: String
+    ) -> Self {
+        Self {
+            key_id,
+            secret_key,
+            endpoint: ALPACA_DATA_ENDPOINT.to_string(),
+            plan: AlpacaPlan::Basic,
+            extended_hours: false,
+            rate_limit: AlpacaPlan::Basic.rate_limiter(),
+            #[cfg(feature = "replay-http")]
+            replay: None,
+        }
+    }
+
+    /// Creates an `AlpacaProvider` from environment variables.
+    ///
+    /// `ALPACA_API_KEY_ID` and `ALPACA_API_SECRET_KEY` are required. `ALPACA_PLAN` optionally sets
+    /// the plan (`basic` or `algo_trader_plus`), `ALPACA_EXTENDED_HOURS=true` quotes pre- and
+    /// post-market trades and `ALPACA_DATA_ENDPOINT` overrides the endpoint.
+    ///
+    /// # Errors
+    /// Returns `XylexApiError::EnvAuthenticationError` if a key is missing, or
+    /// `XylexApiError::ConfigurationError` if the plan is unknown.
+    pub fn new_env() -> Result<Self, XylexApiError> {
+        let key = |name: &str| {
+            var(name).map_err(|_| XylexApiError::EnvAuthenticationError(format!("{} is not set", name)))
+        };
+        let mut provider: Self = Self::new(key("ALPACA_API_KEY_ID")?, key("ALPACA_API_SECRET_KEY")?);
+        if let Ok(plan) = var("ALPACA_PLAN") {
+            provider = provider.with_plan(plan.parse().map_err(XylexApiError::ConfigurationError)?);
+        }
+        if let Ok(endpoint) = var("ALPACA_DATA_ENDPOINT") {
+            provider = provider.with_endpoint(&endpoint);
+        }
+        provider.extended_hours = var("ALPACA_EXTENDED_HOURS").is_ok_and(|value| value == "true");
+        Ok(provider)
+    }
+
+    /// Sets the API endpoint.
+    ///
+    /// # Arguments
+    /// * `endpoint` - The base URL, without a trailing `/v2`.
+    pub fn with_endpoint(
+        mut self,
+        endpoint: &str
+    ) -> Self {
+        self.endpoint = endpoint.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Sets the plan, which picks the data feed and the rate limit.
+    pub fn with_plan(
+        mut self,
+        plan: AlpacaPlan
+    ) -> Self {
+        self.plan = plan;
+        self.rate_limit = plan.rate_limiter();
+        self
+    }
+
+    /// Quotes pre- and post-market trades instead of the last regular session close.
+    pub fn with_extended_hours(mut self) -> Self {
+        self.extended_hours = true;
+        self
+    }
+
+    /// Replaces the plan's rate limit, e.g. to share the limit with another client of the same account.
+    pub fn with_rate_limit(
+        mut self,
+        rate_limit: RateLimiter
+    ) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    async fn get(
+        &self,
+        url: &str,
+        query: &[(&str, String)]
+    ) -> Result<Value, XylexApiError> {
+        let mut url: reqwest::Url = reqwest::Url::parse(url)
+            .map_err(|e| XylexApiError::ConfigurationError(format!("Invalid Alpaca endpoint: {}", e)))?;
+        url.query_pairs_mut().extend_pairs(query).append_pair("feed", self.plan.feed());
+
+        let send = async {
+            self.rate_limit.acquire().await;
+            let response: reqwest::Response = reqwest::Client::new()
+                .get(url.clone())
+                .header("APCA-API-KEY-ID", &self.key_id)
+                .header("APCA-API-SECRET-KEY", &self.secret_key)
+                .send()
+                .await
+                .map_err(|e| XylexApiError::NetworkError(format!("Failed to send request: {}", e)))?;
+
+            let status: u16 = response.status().as_u16();
+            let body: Value = response
+                .json::<Value>()
+                .await
+                .map_err(|_| XylexApiError::UnexpectedError("Failed to parse JSON".to_string()))?;
+            Ok((status, body))
+        };
+        #[cfg(feature = "replay-http")]
+        let (status, body) = match &self.replay {
+            Some(replay) => replay.exchange("GET", url.as_str(), send).await?,
+            None => send.await?,
+        };
+        #[cfg(not(feature = "replay-http"))]
+        let (status, body) = send.await?;
+        if (200..300).contains(&status) {
+            return Ok(body);
+        }
+
+        let message: String = body["message"].as_str().unwrap_or("unknown error").to_string();
+        match status {
+            400 | 404 | 422 => Err(XylexApiError::InvalidSymbol(message)),
+            401 | 403 => Err(XylexApiError::ConfigurationError(format!("Alpaca rejected the API keys: {}", message))),
+            _ => Err(XylexApiError::NetworkError(format!("Alpaca returned {}: {}", status, message))),
+        }
+    }
+
+    /// Turns a snapshot into a quote for `symbol`, the symbol as the caller wrote it.
+    fn snapshot_to_quote(
+        &self,
+        symbol: &str,
+        snapshot: &Value
+    ) -> Result<Quote, XylexApiError> {
+        let trade: &Value = &snapshot["latestTrade"];
+        let traded_at: Option<DateTime<Utc>> = parse_timestamp(&trade["t"]);
+        let regular: bool = traded_at.is_some_and(|at| AssetClass::Equity.is_open(at));
+
+        if !regular && !self.extended_hours {
+            // Before the open the previous session's close holds, after the close today's
+            let open: NaiveTime = NaiveTime::from_hms_opt(9, 30, 0).expect("Open time is valid");
+            let before_open: bool = traded_at.is_some_and(|at| new_york_time(at).time() < open);
+            let bar: &Value = match before_open {
+                true => &snapshot["prevDailyBar"],
+                false => &snapshot["dailyBar"],
+            };
+            if let Some(close) = parse_optional_number(&bar["c"]) {
+                return Ok(Quote {
+                    symbol: symbol.to_string(),
+                    price: close,
+                    bid: None,
+                    ask: None,
+                    volume: parse_optional_number(&bar["v"]),
+                    average_volume: None,
+                    timestamp: parse_timestamp(&bar["t"]),
+                });
+            }
+        }
+
+        let price: f64 = parse_optional_number(&trade["p"])
+            .ok_or_else(|| XylexApiError::UnexpectedError(format!("Missing price for {}", symbol)))?;
+        Ok(Quote {
+            symbol: symbol.to_string(),
+            price,
+            bid: parse_optional_number(&snapshot["latestQuote"]["bp"]).filter(|bid| *bid > 0.0),
+            ask: parse_optional_number(&snapshot["latestQuote"]["ap"]).filter(|ask| *ask > 0.0),
+            volume: parse_optional_number(&snapshot["dailyBar"]["v"]),
+            average_volume: None,
+            timestamp: traded_at,
+        })
+    }
+}
+
+impl fmt::Debug for AlpacaProvider {
+    /// Never prints the API keys.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlpacaProvider")
+            .field("endpoint", &self.endpoint)
+            .field("plan", &self.plan)
+            .field("extended_hours", &self.extended_hours)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Normalizes a ticker to Alpaca's format, e.g. `brk/b` or `BRK-B` to `BRK.B`.
+pub fn normalize_alpaca_symbol(symbol: &str) -> String {
+    symbol.trim().to_uppercase().replace(['/', '-'], ".")
+}
+
+#[async_trait]
+impl PriceProvider for AlpacaProvider {
+    async fn fetch_price(&self, symbol: &str) -> Result<Quote, XylexApiError> {
+        let url: String = format!("{}/v2/stocks/{}/snapshot", self.endpoint, normalize_alpaca_symbol(symbol));
+        let snapshot: Value = self.get(&url, &[]).await?;
+
+        self.snapshot_to_quote(symbol, &snapshot)
+    }
+
+    /// Fetches every symbol in a single request.
+    async fn fetch_prices(&self, symbols: &[&str]) -> Result<Vec<Quote>, XylexApiError> {
+        if symbols.is_empty() {
+            return Ok(Vec::new());
+        }
+        let normalized: Vec<String> = symbols.iter().map(|symbol| normalize_alpaca_symbol(symbol)).collect();
+
+        let url: String = format!("{}/v2/stocks/snapshots", self.endpoint);
+        let snapshots: Value = self.get(&url, &[("symbols", normalized.join(","))]).await?;
+
+        symbols
+            .iter()
+            .zip(&normalized)
+            .map(|(symbol, ticker)| match snapshots.get(ticker).filter(|snapshot| !snapshot.is_null()) {
+                Some(snapshot) => self.snapshot_to_quote(symbol, snapshot),
+                None => Err(XylexApiError::InvalidSymbol(format!("No snapshot for {}", symbol))),
+            })
+            .collect()
+    }
+}
+
+impl std::str::FromStr for AlpacaPlan {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace(['-', ' '], "_").as_str() {
+            "basic" | "free" => Ok(AlpacaPlan::Basic),
+            "algo_trader_plus" | "unlimited" => Ok(AlpacaPlan::AlgoTraderPlus),
+            other => Err(format!("Unknown Alpaca plan `{}`, expected basic or algo_trader_plus", other)),
+        }
+    }
+}
@@ -1,9 +1,22 @@
 //! ## Authentication to data API's
 
+use std::collections::BTreeMap;
 use std::env::var;
+use std::time::Duration;
 use dotenv::dotenv;
-use crate::data::XylexApi;
+
+use crate::{ResourceLimits, ScheduleConfig};
+use crate::config::env_duration;
+use crate::data::request::{DEFAULT_BATCH_SIZE, DEFAULT_CONCURRENCY};
+use crate::data::{
+    CandleAggregator, CreditBudget, CycleBudget, MovementGate, NumberFormat, PeriodCandles, PreviousPrices, RateLimiter, VolumeHistory,
+    XylexApi,
+};
 use crate::errors::XylexApiError;
+use crate::market_hours::MarketHours;
+use crate::retry::RetryPolicy;
+use crate::timeout::Timeouts;
+use crate::utils::lru::CacheStats;
 
 /// ## Implementing the XylexApi struct for authentication to the Xylex API
 impl XylexApi {
@@ -14,12 +27,296 @@ impl XylexApi {
     /// * `endpoint` - A `String` that specifies the API endpoint URL.
     ///
     /// # Returns
-    /// Returns a new `XylexApi` instance containing the provided `key` and `endpoint`.
+    /// Returns a new `XylexApi` instance containing the provided `key` and `endpoint`, fetching
+    /// up to `DEFAULT_BATCH_SIZE` symbols per request, see [`XylexApi::with_batch_size`], or
+    /// `DEFAULT_CONCURRENCY` symbols at a time without batching, see [`XylexApi::with_concurrency`].
     pub fn new(
         key: String,
         endpoint: String
     ) -> Self {
-        Self { key, endpoint }
+        Self {
+            key,
+            endpoint,
+            history_endpoint: None,
+            search_endpoint: None,
+            budget: None,
+            candles: CandleAggregator::default(),
+            period_candles: PeriodCandles::default(),
+            volumes: VolumeHistory::default(),
+            previous_prices: PreviousPrices::default(),
+            max_clock_skew: None,
+            movement_gate: None,
+            batch_size: Some(DEFAULT_BATCH_SIZE),
+            concurrency: DEFAULT_CONCURRENCY,
+            rate_limit: None,
+            retry: RetryPolicy::default(),
+            timeouts: Timeouts::default(),
+            number_format: NumberFormat::default(),
+            cycle_budget: None,
+            market_hours: None,
+            schedule: None,
+            dry_run: false,
+            resource_limits: ResourceLimits::default(),
+            #[cfg(feature = "replay-http")]
+            replay: None,
+            batch_unsupported: Default::default(),
+            unconfirmed_crossings: Default::default(),
+            evaluated_at: Default::default(),
+            shard: Default::default(),
+        }
+    }
+
+    /// Sets the endpoint serving historical candles, needed by indicator conditions.
+    ///
+    /// # Arguments
+    /// * `history_endpoint` - The URL of a time series endpoint, e.g. TwelveData's `time_series`.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the history endpoint applied.
+    pub fn with_history_endpoint(
+        mut self,
+        history_endpoint: String
+    ) -> Self {
+        self.history_endpoint = Some(history_endpoint);
+        self
+    }
+
+    /// Sets the endpoint searching instruments, needed by symbol search.
+    ///
+    /// # Arguments
+    /// * `search_endpoint` - The URL of a symbol search endpoint, e.g. TwelveData's `symbol_search`.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the search endpoint applied.
+    pub fn with_search_endpoint(
+        mut self,
+        search_endpoint: String
+    ) -> Self {
+        self.search_endpoint = Some(search_endpoint);
+        self
+    }
+
+    /// Enables credit accounting for every request made through this client.
+    ///
+    /// # Arguments
+    /// * `budget` - The `CreditBudget` to charge requests against.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the budget applied.
+    pub fn with_budget(
+        mut self,
+        budget: CreditBudget
+    ) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Logs a warning whenever a triggered alert was evaluated on a quote older (or newer) than `max_clock_skew`.
+    ///
+    /// Useful to find delayed feeds causing late alerts.
+    ///
+    /// # Arguments
+    /// * `max_clock_skew` - The largest accepted difference between the quote time and the evaluation time.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the threshold applied.
+    pub fn with_max_clock_skew(
+        mut self,
+        max_clock_skew: Duration
+    ) -> Self {
+        self.max_clock_skew = Some(max_clock_skew);
+        self
+    }
+
+    /// Only re-evaluates the price alerts of a symbol once its price moved enough since their last evaluation.
+    ///
+    /// # Arguments
+    /// * `movement_gate` - The `MovementGate` deciding which symbols are evaluated.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the gate applied.
+    pub fn with_movement_gate(
+        mut self,
+        movement_gate: MovementGate
+    ) -> Self {
+        movement_gate.set_capacity(self.resource_limits.max_symbols);
+        self.movement_gate = Some(movement_gate);
+        self
+    }
+
+    /// Sets the largest number of symbols fetched in a single request.
+    ///
+    /// # Arguments
+    /// * `batch_size` - Symbols per request, `None` or `Some(0)` to fetch symbols one by one.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the batch size applied.
+    pub fn with_batch_size(
+        mut self,
+        batch_size: Option<usize>
+    ) -> Self {
+        self.batch_size = batch_size.filter(|size| *size > 0);
+        self
+    }
+
+    /// Sets the largest number of requests in flight when symbols are fetched one by one.
+    ///
+    /// # Arguments
+    /// * `concurrency` - Requests in flight, `1` to fetch symbols serially.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the limit applied.
+    pub fn with_concurrency(
+        mut self,
+        concurrency: usize
+    ) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Limits the requests sent by this client, shared by the batched and per-symbol paths
+    /// and by every clone of the client.
+    ///
+    /// # Arguments
+    /// * `requests_per_second` - The sustained request rate, bursts of up to one second of requests are allowed.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the rate limit applied.
+    pub fn with_rate_limit(
+        mut self,
+        requests_per_second: f64
+    ) -> Self {
+        self.rate_limit = Some(RateLimiter::new(requests_per_second));
+        self
+    }
+
+    /// Sets how failed price requests are retried, network errors are retried and every other error is returned at once.
+    ///
+    /// # Arguments
+    /// * `retry` - The `RetryPolicy` to apply, [`RetryPolicy::none`] to never retry.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the policy applied.
+    pub fn with_retry_policy(
+        mut self,
+        retry: RetryPolicy
+    ) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets the connect and read timeouts of every request, a timed out request fails with
+    /// `XylexApiError::Timeout` and is retried like a network error.
+    ///
+    /// # Arguments
+    /// * `timeouts` - The `Timeouts` to apply.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the timeouts applied.
+    pub fn with_timeouts(
+        mut self,
+        timeouts: Timeouts
+    ) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Sets how prices sent by the provider are read, e.g. with a `,` decimal separator or strictly.
+    ///
+    /// # Arguments
+    /// * `number_format` - The `NumberFormat` to apply.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the format applied.
+    pub fn with_number_format(
+        mut self,
+        number_format: NumberFormat
+    ) -> Self {
+        self.number_format = number_format;
+        self
+    }
+
+    /// Evaluates the alerts without writing the trigger time and price to their rows.
+    ///
+    /// Triggered alerts are still reported by [`XylexApi::check_alerts`] while their rows are
+    /// left untouched, see [`crate::TradeAlertsBuilder::with_dry_run`] for a whole system.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance in dry-run mode.
+    pub fn with_dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Skips symbols whose market is closed, e.g. equities outside of US exchange hours.
+    ///
+    /// # Arguments
+    /// * `market_hours` - The `MarketHours` classifying the symbols.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the market hours applied.
+    pub fn with_market_hours(
+        mut self,
+        market_hours: MarketHours
+    ) -> Self {
+        self.market_hours = Some(market_hours);
+        self
+    }
+
+    /// Only fetches the symbols whose check interval is due, see [`ScheduleConfig`].
+    ///
+    /// # Arguments
+    /// * `schedule` - The `ScheduleConfig` with the intervals of the symbols.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the schedule applied.
+    pub fn with_schedule(
+        mut self,
+        schedule: ScheduleConfig
+    ) -> Self {
+        self.schedule = Some(schedule);
+        self
+    }
+
+    /// Bounds the caches kept across checks, evicting the least recently used entries once full.
+    ///
+    /// `max_symbols` applies to the volumes, previous prices, movement gate and the last quotes
+    /// of streamed checks, `max_candles` to the open and completed candles.
+    ///
+    /// # Arguments
+    /// * `limits` - The `ResourceLimits` with the largest number of entries per cache.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the limits applied.
+    pub fn with_resource_limits(
+        mut self,
+        limits: ResourceLimits
+    ) -> Self {
+        self.candles.set_capacity(limits.max_candles);
+        self.period_candles.set_capacity(limits.max_candles);
+        self.volumes.set_capacity(limits.max_symbols);
+        self.previous_prices.set_capacity(limits.max_symbols);
+        if let Some(movement_gate) = &self.movement_gate {
+            movement_gate.set_capacity(limits.max_symbols);
+        }
+        self.evaluated_at.lock().unwrap_or_else(|e| e.into_inner()).set_capacity(limits.max_alerts);
+        self.resource_limits = limits;
+        self
+    }
+
+    /// Returns the occupancy of the caches kept across checks, keyed by cache name.
+    pub fn cache_stats(&self) -> BTreeMap<String, CacheStats> {
+        let mut stats: BTreeMap<String, CacheStats> = BTreeMap::from([
+            ("candles".to_string(), self.candles.stats()),
+            ("period_candles".to_string(), self.period_candles.stats()),
+            ("volumes".to_string(), self.volumes.stats()),
+            ("previous_prices".to_string(), self.previous_prices.stats()),
+            ("evaluated_at".to_string(), self.evaluated_at.lock().unwrap_or_else(|e| e.into_inner()).stats()),
+        ]);
+        if let Some(movement_gate) = &self.movement_gate {
+            stats.insert("movement_gate".to_string(), movement_gate.stats());
+        }
+        stats
     }
 
     /// Asynchronously creates a new instance of `XylexApi` using environment variables.
@@ -27,8 +324,28 @@ impl XylexApi {
     /// This method retrieves the API key and endpoint URL from the environment variables `XYLEX_API_KEY` and `XYLEX_API_ENDPOINT`, respectively.
     /// It requires the `.env` file to be set up with these variables.
     ///
+    /// `XYLEX_HISTORY_ENDPOINT` optionally sets the endpoint serving historical candles,
+    /// `XYLEX_SEARCH_ENDPOINT` the endpoint searching instruments and
+    /// `XYLEX_MAX_CLOCK_SKEW` (or the older `XYLEX_MAX_CLOCK_SKEW_SECS`) the largest accepted
+    /// quote age before a warning is logged, as a duration such as `"5s"`, see [`crate::config`].
+    /// `XYLEX_MIN_MOVE_PERCENT` enables a `MovementGate` with that minimum move.
+    /// `XYLEX_BATCH_SIZE` sets the number of symbols per request, `0` disables batching.
+    /// `XYLEX_CONCURRENCY` sets the number of requests in flight without batching.
+    /// `XYLEX_RATE_LIMIT` limits the requests per second, see [`XylexApi::with_rate_limit`].
+    /// `XYLEX_RETRY_ATTEMPTS` sets the attempts of the default `RetryPolicy`, `1` disables retrying.
+    /// `XYLEX_CONNECT_TIMEOUT` and `XYLEX_READ_TIMEOUT` override the default `Timeouts`.
+    /// `XYLEX_CYCLE_BUDGET` limits the time spent fetching quotes per cycle, see [`CycleBudget`].
+    /// `XYLEX_DECIMAL_SEPARATOR` and `XYLEX_THOUSANDS_SEPARATOR` set the separators of prices and
+    /// `XYLEX_STRICT_PRICES=true` rejects lenient prices, see [`NumberFormat`].
+    /// `XYLEX_DRY_RUN=true` evaluates alerts without recording their triggers, see [`XylexApi::with_dry_run`].
+    ///
+    /// When `XYLEX_MONTHLY_BUDGET` is set, credit accounting is enabled with a cost of
+    /// `XYLEX_COST_PER_REQUEST` (defaults to `1`) per request.
+    ///
     /// # Errors
     /// Returns `XylexApiError::EnvAuthenticationError` if either the `XYLEX_API_KEY` or `XYLEX_API_ENDPOINT` environment variables are not found.
+    /// Returns `XylexApiError::ConfigurationError` if the budget variables are not valid numbers,
+    /// the timeouts are not valid durations or the decimal separator is not a single character.
     ///
     /// # Returns
     /// Returns a `Result` which is `Ok` containing a new `XylexApi` instance if both environment variables are found, or an `Err` containing `XylexApiError` if any variable is missing.
@@ -45,6 +362,122 @@ impl XylexApi {
             Err(_) => return Err(XylexApiError::EnvAuthenticationError("XYLEX_API_ENDPOINT not found in .env file".to_string())),
         };
 
-        Ok(Self { key, endpoint })
+        let history_endpoint: Option<String> = var("XYLEX_HISTORY_ENDPOINT").ok();
+        let search_endpoint: Option<String> = var("XYLEX_SEARCH_ENDPOINT").ok();
+
+        let max_clock_skew: Option<Duration> = match env_duration("XYLEX_MAX_CLOCK_SKEW") {
+            Ok(None) => env_duration("XYLEX_MAX_CLOCK_SKEW_SECS"),
+            skew => skew,
+        }
+        .map_err(|e| XylexApiError::ConfigurationError(e.to_string()))?;
+
+        let movement_gate: Option<MovementGate> = match var("XYLEX_MIN_MOVE_PERCENT") {
+            Ok(percent) => Some(MovementGate::new(percent.parse().map_err(|_| {
+                XylexApiError::ConfigurationError("XYLEX_MIN_MOVE_PERCENT must be a number".to_string())
+            })?)),
+            Err(_) => None,
+        };
+
+        let batch_size: Option<usize> = match var("XYLEX_BATCH_SIZE") {
+            Ok(size) => match size.parse::<usize>() {
+                Ok(0) => None,
+                Ok(size) => Some(size),
+                Err(_) => return Err(XylexApiError::ConfigurationError("XYLEX_BATCH_SIZE must be a whole number".to_string())),
+            },
+            Err(_) => Some(DEFAULT_BATCH_SIZE),
+        };
+
+        let concurrency: usize = match var("XYLEX_CONCURRENCY") {
+            Ok(concurrency) => concurrency.parse::<usize>().map_err(|_| {
+                XylexApiError::ConfigurationError("XYLEX_CONCURRENCY must be a whole number".to_string())
+            })?.max(1),
+            Err(_) => DEFAULT_CONCURRENCY,
+        };
+
+        let rate_limit: Option<RateLimiter> = match var("XYLEX_RATE_LIMIT") {
+            Ok(rate) => match rate.parse::<f64>() {
+                Ok(rate) if rate.is_finite() && rate > 0.0 => Some(RateLimiter::new(rate)),
+                _ => return Err(XylexApiError::ConfigurationError("XYLEX_RATE_LIMIT must be a positive finite number".to_string())),
+            },
+            Err(_) => None,
+        };
+
+        let retry: RetryPolicy = match var("XYLEX_RETRY_ATTEMPTS") {
+            Ok(attempts) => RetryPolicy::default().with_max_attempts(attempts.parse().map_err(|_| {
+                XylexApiError::ConfigurationError("XYLEX_RETRY_ATTEMPTS must be a whole number".to_string())
+            })?),
+            Err(_) => RetryPolicy::default(),
+        };
+
+        let timeouts: Timeouts = Timeouts::from_env("XYLEX")
+            .map_err(|e| XylexApiError::ConfigurationError(e.to_string()))?;
+
+        let cycle_budget: Option<CycleBudget> = env_duration("XYLEX_CYCLE_BUDGET")
+            .map_err(|e| XylexApiError::ConfigurationError(e.to_string()))?
+            .map(CycleBudget::new);
+
+        let mut number_format: NumberFormat = NumberFormat::default();
+        if let Ok(separator) = var("XYLEX_DECIMAL_SEPARATOR") {
+            let mut chars = separator.chars();
+            number_format.decimal_separator = match (chars.next(), chars.next()) {
+                (Some(separator), None) => separator,
+                _ => return Err(XylexApiError::ConfigurationError("XYLEX_DECIMAL_SEPARATOR must be a single character".to_string())),
+            };
+        }
+        if let Ok(separator) = var("XYLEX_THOUSANDS_SEPARATOR") {
+            let mut chars = separator.chars();
+            number_format.thousands_separator = match (chars.next(), chars.next()) {
+                (Some(separator), None) => Some(separator),
+                _ => return Err(XylexApiError::ConfigurationError("XYLEX_THOUSANDS_SEPARATOR must be a single character".to_string())),
+            };
+        }
+        number_format.strict = var("XYLEX_STRICT_PRICES").is_ok_and(|value| value == "true");
+
+        let budget = match var("XYLEX_MONTHLY_BUDGET") {
+            Ok(monthly_budget) => {
+                let monthly_budget: f64 = monthly_budget.parse().map_err(|_| {
+                    XylexApiError::ConfigurationError("XYLEX_MONTHLY_BUDGET must be a number".to_string())
+                })?;
+                let cost_per_request: f64 = match var("XYLEX_COST_PER_REQUEST") {
+                    Ok(cost) => cost.parse().map_err(|_| {
+                        XylexApiError::ConfigurationError("XYLEX_COST_PER_REQUEST must be a number".to_string())
+                    })?,
+                    Err(_) => 1.0,
+                };
+                Some(CreditBudget::new(cost_per_request, monthly_budget))
+            },
+            Err(_) => None,
+        };
+
+        Ok(Self {
+            key,
+            endpoint,
+            history_endpoint,
+            search_endpoint,
+            budget,
+            candles: CandleAggregator::default(),
+            period_candles: PeriodCandles::default(),
+            volumes: VolumeHistory::default(),
+            previous_prices: PreviousPrices::default(),
+            max_clock_skew,
+            movement_gate,
+            batch_size,
+            concurrency,
+            rate_limit,
+            retry,
+            timeouts,
+            number_format,
+            cycle_budget,
+            market_hours: None,
+            schedule: None,
+            dry_run: var("XYLEX_DRY_RUN").is_ok_and(|value| value == "true"),
+            resource_limits: ResourceLimits::default(),
+            #[cfg(feature = "replay-http")]
+            replay: None,
+            batch_unsupported: Default::default(),
+            unconfirmed_crossings: Default::default(),
+            evaluated_at: Default::default(),
+            shard: Default::default(),
+        })
     }
 }
\ No newline at end of file
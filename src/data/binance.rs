@@ -0,0 +1,219 @@
+//! ## Binance price provider
+//! Spot prices from Binance's public REST API, so crypto alerts can run without a Xylex key.
+
+use std::env::var;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::data::{BinanceProvider, Instrument, PriceProvider, Quote};
+use crate::data::request::{parse_optional_number, parse_timestamp};
+use crate::errors::XylexApiError;
+
+/// Base URL of Binance's public spot API.
+pub const BINANCE_API_ENDPOINT: &str = "https://api.binance.com";
+
+impl Default for BinanceProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BinanceProvider {
+    /// Creates a `BinanceProvider` fetching last prices from the public spot API.
+    pub fn new() -> Self {
+        Self {
+            endpoint: BINANCE_API_ENDPOINT.to_string(),
+            include_24h_stats: false,
+            #[cfg(feature = "replay-http")]
+            replay: None,
+        }
+    }
+
+    /// Creates a `BinanceProvider` from environment variables.
+    ///
+    /// `BINANCE_API_ENDPOINT` optionally overrides the endpoint, e.g. for `https://api.binance.us`,
+    /// and `BINANCE_24H_STATS=true` enables the 24h ticker.
+    pub fn new_env() -> Self {
+        Self {
+            endpoint: var("BINANCE_API_ENDPOINT").unwrap_or_else(|_| BINANCE_API_ENDPOINT.to_string()),
+            include_24h_stats: var("BINANCE_24H_STATS").is_ok_and(|value| value == "true"),
+            #[cfg(feature = "replay-http")]
+            replay: None,
+        }
+    }
+
+    /// Sets the API endpoint.
+    ///
+    /// # Arguments
+    /// * `endpoint` - The base URL, without a trailing `/api/v3`.
+    pub fn with_endpoint(
+        mut self,
+        endpoint: &str
+    ) -> Self {
+        self.endpoint = endpoint.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Takes quotes from the 24h ticker, adding the best bid and ask and the 24h volume.
+    ///
+    /// The 24h ticker has a higher request weight than the price ticker.
+    pub fn with_24h_stats(mut self) -> Self {
+        self.include_24h_stats = true;
+        self
+    }
+
+    fn ticker_url(&self) -> String {
+        match self.include_24h_stats {
+            true => format!("{}/api/v3/ticker/24hr", self.endpoint),
+            false => format!("{}/api/v3/ticker/price", self.endpoint),
+        }
+    }
+
+    async fn get(
+        &self,
+        url: &str,
+        query: &[(&str, String)]
+    ) -> Result<Value, XylexApiError> {
+        let mut url: reqwest::Url = reqwest::Url::parse(url)
+            .map_err(|e| XylexApiError::ConfigurationError(format!("Invalid Binance endpoint: {}", e)))?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        let send = async {
+            let response: reqwest::Response = reqwest::Client::new()
+                .get(url.clone())
+                .send()
+                .await
+                .map_err(|e| XylexApiError::NetworkError(format!("Failed to send request: {}", e)))?;
+
+            let status: u16 = response.status().as_u16();
+            let body: Value = response
+                .json::<Value>()
+                .await
+                .map_err(|_| XylexApiError::UnexpectedError("Failed to parse JSON".to_string()))?;
+            Ok((status, body))
+        };
+        #[cfg(feature = "replay-http")]
+        let (status, body) = match &self.replay {
+            Some(replay) => replay.exchange("GET", url.as_str(), send).await?,
+            None => send.await?,
+        };
+        #[cfg(not(feature = "replay-http"))]
+        let (status, body) = send.await?;
+        if (200..300).contains(&status) {
+            return Ok(body);
+        }
+
+        let message: String = body["msg"].as_str().unwrap_or("unknown error").to_string();
+        match status {
+            // Binance answers unknown symbols with 400 and code -1121
+            400 if body["code"] == -1121 => Err(XylexApiError::InvalidSymbol(message)),
+            _ => Err(XylexApiError::NetworkError(format!("Binance returned {}: {}", status, message))),
+        }
+    }
+}
+
+/// Normalizes a symbol to Binance's format, e.g. `btc/usdt` to `BTCUSDT`.
+pub fn normalize_binance_symbol(symbol: &str) -> String {
+    symbol
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Turns a ticker into a quote for `symbol`, the symbol as the caller wrote it.
+fn ticker_to_quote(
+    symbol: &str,
+    ticker: &Value
+) -> Result<Quote, XylexApiError> {
+    let price: f64 = parse_optional_number(&ticker["price"])
+        .or_else(|| parse_optional_number(&ticker["lastPrice"]))
+        .ok_or_else(|| XylexApiError::UnexpectedError(format!("Missing price for {}", symbol)))?;
+
+    Ok(Quote {
+        symbol: symbol.to_string(),
+        price,
+        bid: parse_optional_number(&ticker["bidPrice"]),
+        ask: parse_optional_number(&ticker["askPrice"]),
+        volume: parse_optional_number(&ticker["volume"]),
+        average_volume: None,
+        timestamp: parse_timestamp(&ticker["closeTime"]),
+    })
+}
+
+#[async_trait]
+impl PriceProvider for BinanceProvider {
+    async fn fetch_price(&self, symbol: &str) -> Result<Quote, XylexApiError> {
+        let ticker: Value = self
+            .get(&self.ticker_url(), &[("symbol", normalize_binance_symbol(symbol))])
+            .await?;
+
+        ticker_to_quote(symbol, &ticker)
+    }
+
+    /// Fetches every symbol in a single request.
+    async fn fetch_prices(&self, symbols: &[&str]) -> Result<Vec<Quote>, XylexApiError> {
+        if symbols.is_empty() {
+            return Ok(Vec::new());
+        }
+        let normalized: Vec<String> = symbols.iter().map(|symbol| normalize_binance_symbol(symbol)).collect();
+        let list: String = serde_json::to_string(&normalized)
+            .map_err(|e| XylexApiError::UnexpectedError(e.to_string()))?;
+
+        let tickers: Value = self.get(&self.ticker_url(), &[("symbols", list)]).await?;
+        let tickers: &Vec<Value> = tickers
+            .as_array()
+            .ok_or_else(|| XylexApiError::UnexpectedError("Expected a list of tickers".to_string()))?;
+
+        symbols
+            .iter()
+            .zip(&normalized)
+            .map(|(symbol, binance_symbol)| {
+                let ticker: &Value = tickers
+                    .iter()
+                    .find(|ticker| ticker["symbol"].as_str() == Some(binance_symbol.as_str()))
+                    .ok_or_else(|| XylexApiError::InvalidSymbol(format!("No ticker for {}", symbol)))?;
+                ticker_to_quote(symbol, ticker)
+            })
+            .collect()
+    }
+
+    /// Searches the trading spot pairs whose base or quote asset starts with `query`.
+    ///
+    /// Pairs are returned as `BASE/QUOTE`, pairs whose base asset matches exactly first.
+    async fn search_symbols(&self, query: &str) -> Result<Option<Vec<Instrument>>, XylexApiError> {
+        let query: String = normalize_binance_symbol(query);
+        let info: Value = self.get(&format!("{}/api/v3/exchangeInfo", self.endpoint), &[]).await?;
+
+        let mut matches: Vec<(bool, Instrument)> = info["symbols"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter(|pair| pair["status"] == "TRADING")
+            .filter_map(|pair| {
+                let base: &str = pair["baseAsset"].as_str()?;
+                let quote: &str = pair["quoteAsset"].as_str()?;
+                let symbol: &str = pair["symbol"].as_str()?;
+                let found: bool = query.is_empty()
+                    || base.starts_with(&query)
+                    || quote.starts_with(&query)
+                    || symbol.starts_with(&query);
+                found.then(|| {
+                    (base == query, Instrument {
+                        symbol: format!("{}/{}", base, quote),
+                        name: None,
+                        exchange: Some("Binance".to_string()),
+                        asset_type: Some("crypto".to_string()),
+                        currency: Some(quote.to_string()),
+                    })
+                })
+            })
+            .collect();
+        matches.sort_by_key(|(exact, _)| !exact);
+
+        Ok(Some(matches.into_iter().map(|(_, instrument)| instrument).collect()))
+    }
+}
@@ -0,0 +1,215 @@
+//! ## Credit accounting for data providers
+//!
+//! Tracks the credits spent against a provider's monthly quota and decides
+//! how aggressively the remaining credits may be used.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{Datelike, Utc};
+use serde_json::Value;
+
+use crate::data::{BudgetMetrics, BudgetPolicy, BudgetState, CreditBudget, CreditUsage, XylexApi};
+use crate::db::TableConfig;
+use crate::errors::XylexApiError;
+
+impl XylexApi {
+    /// Returns a snapshot of the credits spent this month, if a budget is configured.
+    pub fn budget_metrics(&self) -> Option<BudgetMetrics> {
+        self.budget.as_ref().map(CreditBudget::metrics)
+    }
+
+    /// Orders and limits the symbols to fetch according to the budget policy.
+    ///
+    /// While the budget is in the `Prioritize` or `Exhausted` state, symbols are sorted by how
+    /// close their nearest alert is to the last recorded price (`latest_price` column) and only
+    /// as many symbols as the remaining credits allow are returned. Otherwise all symbols are
+    /// returned unchanged.
+    ///
+    /// # Arguments
+    /// * `symbols` - The symbols that have active alerts.
+    /// * `alerts` - The alert rows, as returned by `Supabase::fetch_all_data`.
+    /// * `config` - A reference to a `TableConfig` with the column names of the alert rows.
+    pub fn prioritize_symbols<'a>(
+        &self,
+        symbols: &'a HashSet<String>,
+        alerts: &[HashMap<String, Value>],
+        config: &TableConfig,
+    ) -> Vec<&'a str> {
+        let mut ordered: Vec<&str> = symbols.iter().map(String::as_str).collect();
+
+        let budget = match &self.budget {
+            Some(budget) => budget,
+            None => return ordered,
+        };
+        if !matches!(budget.state(), BudgetState::Prioritize | BudgetState::Exhausted) {
+            return ordered;
+        }
+
+        let mut distances: HashMap<&str, f64> = HashMap::new();
+        for alert in alerts {
+            if let (Some(symbol), Some(level), Some(latest)) = (
+                alert.get(&config.symbol_column_name).and_then(|v| v.as_str()),
+                alert.get(&config.price_level_column_name).and_then(|v| v.as_f64()),
+                alert.get("latest_price").and_then(|v| v.as_f64()),
+            ) {
+                if level == 0.0 {
+                    continue;
+                }
+                let distance = ((latest - level) / level).abs();
+                let nearest = distances.entry(symbol).or_insert(f64::INFINITY);
+                *nearest = nearest.min(distance);
+            }
+        }
+
+        ordered.sort_by(|a, b| {
+            let a = distances.get(a).copied().unwrap_or(f64::INFINITY);
+            let b = distances.get(b).copied().unwrap_or(f64::INFINITY);
+            a.total_cmp(&b)
+        });
+        ordered.truncate(budget.remaining_requests().min(usize::MAX as u64) as usize);
+
+        println!("Budget is running low, only fetching prioritized symbols: {:?}", ordered);
+        ordered
+    }
+}
+
+impl CreditBudget {
+    /// Creates a new `CreditBudget` using the default `BudgetPolicy`.
+    ///
+    /// # Arguments
+    /// * `cost_per_request` - Credits charged by the provider for a single request.
+    /// * `monthly_budget` - Credits available per calendar month.
+    pub fn new(
+        cost_per_request: f64,
+        monthly_budget: f64
+    ) -> Self {
+        Self {
+            cost_per_request,
+            monthly_budget,
+            policy: BudgetPolicy::default(),
+            usage: Arc::new(Mutex::new(CreditUsage::default())),
+        }
+    }
+
+    /// Replaces the policy applied when approaching the budget.
+    pub fn with_policy(
+        mut self,
+        policy: BudgetPolicy
+    ) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Charges a single request for a single symbol against the budget.
+    ///
+    /// # Returns
+    /// Returns the `BudgetState` after charging, so the caller can slow down if needed.
+    ///
+    /// # Errors
+    /// Returns `XylexApiError::BudgetExceeded` if the request does not fit in the remaining budget.
+    pub fn charge(&self) -> Result<BudgetState, XylexApiError> {
+        self.charge_symbols(1)
+    }
+
+    /// Charges a single request for `symbols` symbols against the budget, providers like
+    /// TwelveData charge a batched request per symbol.
+    ///
+    /// # Returns
+    /// Returns the `BudgetState` after charging, so the caller can slow down if needed.
+    ///
+    /// # Errors
+    /// Returns `XylexApiError::BudgetExceeded` if the request does not fit in the remaining budget.
+    pub fn charge_symbols(
+        &self,
+        symbols: usize
+    ) -> Result<BudgetState, XylexApiError> {
+        let cost: f64 = self.cost_per_request * symbols.max(1) as f64;
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        usage.roll_over();
+
+        if usage.spent + cost > self.monthly_budget {
+            return Err(XylexApiError::BudgetExceeded(format!(
+                "{} of {} credits spent this month",
+                usage.spent, self.monthly_budget
+            )));
+        }
+
+        usage.spent += cost;
+        usage.requests += 1;
+
+        Ok(self.state_for(usage.spent))
+    }
+
+    /// Returns the current `BudgetState`.
+    pub fn state(&self) -> BudgetState {
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        usage.roll_over();
+
+        self.state_for(usage.spent)
+    }
+
+    /// Returns the number of requests that still fit in this month's budget.
+    pub fn remaining_requests(&self) -> u64 {
+        if self.cost_per_request <= 0.0 {
+            return u64::MAX;
+        }
+        let metrics = self.metrics();
+
+        (metrics.remaining / self.cost_per_request).floor() as u64
+    }
+
+    /// Returns a snapshot of the spend for the current month.
+    pub fn metrics(&self) -> BudgetMetrics {
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        usage.roll_over();
+
+        BudgetMetrics {
+            period: usage.period,
+            requests: usage.requests,
+            spent: usage.spent,
+            remaining: (self.monthly_budget - usage.spent).max(0.0),
+            state: self.state_for(usage.spent),
+        }
+    }
+
+    fn state_for(
+        &self,
+        spent: f64
+    ) -> BudgetState {
+        if spent + self.cost_per_request > self.monthly_budget {
+            BudgetState::Exhausted
+        } else if spent >= self.monthly_budget * self.policy.prioritize_at {
+            BudgetState::Prioritize
+        } else if spent >= self.monthly_budget * self.policy.slow_down_at {
+            BudgetState::SlowDown
+        } else {
+            BudgetState::Normal
+        }
+    }
+}
+
+impl Default for BudgetPolicy {
+    /// Slows down at 80% of the budget and prioritizes near-trigger symbols at 95%.
+    fn default() -> Self {
+        Self {
+            slow_down_at: 0.8,
+            slow_down_delay: Duration::from_millis(500),
+            prioritize_at: 0.95,
+        }
+    }
+}
+
+impl CreditUsage {
+    /// Resets the counters when a new calendar month has started.
+    fn roll_over(&mut self) {
+        let now = Utc::now();
+        let period = (now.year(), now.month());
+        if self.period != period {
+            self.period = period;
+            self.requests = 0;
+            self.spent = 0.0;
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! ## Candle aggregation
+//! Builds OHLC candles from the prices fetched on every check, so alerts can be
+//! evaluated at candle close instead of on every tick.
+//!
+//! Weekly and monthly candles follow the calendar and span longer than a process usually
+//! runs, so they are fetched from the history endpoint once they closed instead.
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveTime, TimeZone, Utc};
+
+use crate::Timeframe;
+use crate::data::{Candle, CandleAggregator, PeriodCandles, XylexApi};
+use crate::errors::XylexApiError;
+use crate::utils::lru::CacheStats;
+
+impl Timeframe {
+    /// Returns the length of a single candle, 30 days for months, see [`Timeframe::next_candle_start`].
+    pub fn duration(&self) -> Duration {
+        match self {
+            Timeframe::FiveMinutes => Duration::minutes(5),
+            Timeframe::OneHour => Duration::hours(1),
+            Timeframe::OneDay => Duration::days(1),
+            Timeframe::OneWeek => Duration::weeks(1),
+            Timeframe::OneMonth => Duration::days(30),
+        }
+    }
+
+    /// Whether candles follow the calendar, weeks and months, and are fetched once they closed
+    /// instead of built from fetched prices.
+    pub fn is_calendar(&self) -> bool {
+        matches!(self, Timeframe::OneWeek | Timeframe::OneMonth)
+    }
+
+    /// Returns the interval name used by the history endpoint, e.g. `5min`.
+    pub fn interval(&self) -> &'static str {
+        match self {
+            Timeframe::FiveMinutes => "5min",
+            Timeframe::OneHour => "1h",
+            Timeframe::OneDay => "1day",
+            Timeframe::OneWeek => "1week",
+            Timeframe::OneMonth => "1month",
+        }
+    }
+
+    /// Returns the start of the candle containing `at`.
+    ///
+    /// Candles are aligned to the Unix epoch (UTC), weeks start on Monday and months on the first.
+    pub fn candle_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let midnight = |date: chrono::NaiveDate| Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN));
+        match self {
+            Timeframe::OneWeek => midnight(at.date_naive() - Duration::days(at.weekday().num_days_from_monday() as i64)),
+            Timeframe::OneMonth => midnight(at.date_naive().with_day(1).unwrap_or(at.date_naive())),
+            _ => {
+                let length: i64 = self.duration().num_seconds();
+                let start: i64 = at.timestamp() - at.timestamp().rem_euclid(length);
+
+                DateTime::from_timestamp(start, 0).unwrap_or(at)
+            },
+        }
+    }
+
+    /// Returns the start of the candle after the one containing `at`, i.e. when that candle closes.
+    pub fn next_candle_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let start: DateTime<Utc> = self.candle_start(at);
+        match self {
+            Timeframe::OneMonth => start.checked_add_months(Months::new(1)).unwrap_or(start + self.duration()),
+            _ => start + self.duration(),
+        }
+    }
+
+    /// Returns the start of the last candle which closed at or before `at`.
+    pub fn previous_candle_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        self.candle_start(self.candle_start(at) - Duration::seconds(1))
+    }
+}
+
+impl XylexApi {
+    /// Returns the last candle of a calendar timeframe which closed before `now`.
+    ///
+    /// The candle is fetched from the history endpoint once per period and cached until the
+    /// next one closes. If the endpoint has no candle for the period yet, the latest closed
+    /// one is returned without caching it, so it is fetched again on the next call.
+    ///
+    /// # Errors
+    /// - `XylexApiError::ConfigurationError` if no history endpoint is set.
+    /// - `XylexApiError::InvalidSymbol` if the endpoint returned no closed candle.
+    /// - Any error of [`XylexApi::request_candles`].
+    pub async fn completed_candle(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        now: DateTime<Utc>
+    ) -> Result<Candle, XylexApiError> {
+        let period: DateTime<Utc> = timeframe.previous_candle_start(now);
+        if let Some(candle) = self.period_candles.get(symbol, timeframe).filter(|candle| candle.open_time == period) {
+            return Ok(candle);
+        }
+
+        let current: DateTime<Utc> = timeframe.candle_start(now);
+        let candle: Candle = self
+            .request_candles(symbol, timeframe, 2)
+            .await?
+            .into_iter()
+            .filter(|candle| candle.open_time < current)
+            .max_by_key(|candle| candle.open_time)
+            .ok_or_else(|| XylexApiError::InvalidSymbol(format!("No closed {} candle for {}", timeframe.interval(), symbol)))?;
+
+        if candle.open_time == period {
+            self.period_candles.insert(candle.clone());
+        }
+        Ok(candle)
+    }
+}
+
+impl PeriodCandles {
+    /// Returns the cached candle of a symbol and timeframe, if any.
+    pub fn get(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe
+    ) -> Option<Candle> {
+        let mut completed = self.completed.lock().unwrap_or_else(|e| e.into_inner());
+
+        completed.get(&(symbol.to_string(), timeframe)).cloned()
+    }
+
+    /// Caches a candle, replacing the one of the previous period.
+    pub fn insert(&self, candle: Candle) {
+        let mut completed = self.completed.lock().unwrap_or_else(|e| e.into_inner());
+
+        completed.insert((candle.symbol.clone(), candle.timeframe), candle);
+    }
+
+    /// Bounds the cached candles, one per symbol and timeframe, see [`crate::ResourceLimits`].
+    pub fn set_capacity(&self, capacity: Option<usize>) {
+        self.completed.lock().unwrap_or_else(|e| e.into_inner()).set_capacity(capacity);
+    }
+
+    /// Returns the occupancy of the cache.
+    pub fn stats(&self) -> CacheStats {
+        self.completed.lock().unwrap_or_else(|e| e.into_inner()).stats()
+    }
+}
+
+impl CandleAggregator {
+    /// Records a price for a symbol and timeframe.
+    ///
+    /// # Arguments
+    /// * `symbol` - The symbol the price belongs to.
+    /// * `timeframe` - The timeframe of the candle to update.
+    /// * `price` - The fetched price.
+    /// * `at` - The time the price was observed.
+    ///
+    /// # Returns
+    /// Returns the previous candle once `at` falls into a new candle, `None` while the candle is still open.
+    pub fn record(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        price: f64,
+        at: DateTime<Utc>
+    ) -> Option<Candle> {
+        let open_time: DateTime<Utc> = timeframe.candle_start(at);
+        let mut open = self.open.lock().unwrap_or_else(|e| e.into_inner());
+
+        let candle = open.get_or_insert_with((symbol.to_string(), timeframe), || Candle {
+            symbol: symbol.to_string(),
+            timeframe,
+            open_time,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+        });
+
+        if candle.open_time < open_time {
+            let closed: Candle = candle.clone();
+            *candle = Candle {
+                symbol: symbol.to_string(),
+                timeframe,
+                open_time,
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+            };
+            return Some(closed);
+        }
+
+        candle.high = candle.high.max(price);
+        candle.low = candle.low.min(price);
+        candle.close = price;
+        None
+    }
+
+    /// Returns the currently open candle for a symbol and timeframe, if any.
+    pub fn current(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe
+    ) -> Option<Candle> {
+        let open = self.open.lock().unwrap_or_else(|e| e.into_inner());
+
+        open.peek(&(symbol.to_string(), timeframe)).cloned()
+    }
+
+    /// Bounds the open candles, one per symbol and timeframe, see [`crate::ResourceLimits`].
+    ///
+    /// A candle evicted while open starts over as a partial candle on its next price.
+    pub fn set_capacity(&self, capacity: Option<usize>) {
+        self.open.lock().unwrap_or_else(|e| e.into_inner()).set_capacity(capacity);
+    }
+
+    /// Returns the occupancy of the open candles.
+    pub fn stats(&self) -> CacheStats {
+        self.open.lock().unwrap_or_else(|e| e.into_inner()).stats()
+    }
+}
@@ -0,0 +1,241 @@
+//! ## Provider failover
+//! Falls back to other price providers, then to the last known quote, when a provider fails.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use futures_util::future::join_all;
+
+use crate::ResourceLimits;
+use crate::data::{Instrument, PriceProvider, ProviderChain, Quote, ServedQuote};
+use crate::errors::XylexApiError;
+use crate::utils::lru::{CacheStats, LruCache};
+use crate::utils::symbol::canonical;
+
+/// Source recorded for quotes served from the cache.
+pub const CACHE_SOURCE: &str = "cache";
+
+impl ProviderChain {
+    /// Creates a chain with `primary` as its first provider, recorded as `name` when it serves a quote.
+    pub fn new(
+        name: impl Into<String>,
+        primary: impl PriceProvider + 'static
+    ) -> Self {
+        Self {
+            providers: vec![(name.into(), Arc::new(primary))],
+            timeout: None,
+            cache_max_age: None,
+            switch_suppression: None,
+            served: Arc::new(Mutex::new(LruCache::default())),
+        }
+    }
+
+    /// Adds a provider tried after every provider added before it.
+    pub fn with_fallback(
+        mut self,
+        name: impl Into<String>,
+        fallback: impl PriceProvider + 'static
+    ) -> Self {
+        self.providers.push((name.into(), Arc::new(fallback)));
+        self
+    }
+
+    /// Moves on to the next provider once a provider took longer than `timeout`.
+    pub fn with_timeout(
+        mut self,
+        timeout: Duration
+    ) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Serves the last quote of a symbol, if not older than `max_age`, when every provider failed.
+    pub fn with_cache(
+        mut self,
+        max_age: Duration
+    ) -> Self {
+        self.cache_max_age = Some(max_age);
+        self
+    }
+
+    /// Makes crossings of a symbol await confirmation by the next poll for `window` after its
+    /// quotes switched source, since the prices of two sources can differ enough to fake a crossing.
+    ///
+    /// Switching back to the primary counts as a switch too, like a provider serving the symbol after the cache did.
+    pub fn with_switch_suppression(
+        mut self,
+        window: Duration
+    ) -> Self {
+        self.switch_suppression = Some(window);
+        self
+    }
+
+    /// Bounds the symbols whose last quote and source are kept, see [`crate::ResourceLimits`].
+    pub fn with_resource_limits(
+        self,
+        limits: ResourceLimits
+    ) -> Self {
+        self.set_resource_limits(limits);
+        self
+    }
+
+    /// Returns the occupancy of the cached quotes.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.lock().stats()
+    }
+
+    /// Returns the names of the providers, in the order they are tried.
+    pub fn providers(&self) -> Vec<&str> {
+        self.providers.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Returns the name of the provider which served the last quote of `symbol`,
+    /// [`CACHE_SOURCE`] if it was served from the cache.
+    pub fn source(&self, symbol: &str) -> Option<String> {
+        self.lock().peek(&canonical(symbol)).map(|served| served.source.clone())
+    }
+
+    /// Fetches `symbol` from each provider in turn, then from the cache.
+    async fn fetch_through_chain(&self, symbol: &str) -> Result<Quote, XylexApiError> {
+        let mut last_error: Option<XylexApiError> = None;
+        for (name, provider) in &self.providers {
+            match self.within_timeout(name, provider.fetch_price(symbol)).await {
+                Ok(quote) => {
+                    self.record(name, &quote);
+                    return Ok(quote);
+                },
+                Err(e) => {
+                    println!("Price provider {} failed for {}: {}", name, symbol, e);
+                    last_error = Some(e);
+                },
+            }
+        }
+
+        if let Some(quote) = self.cached(symbol) {
+            println!("Serving the cached quote of {}", symbol);
+            return Ok(quote);
+        }
+        Err(last_error.unwrap_or_else(|| XylexApiError::ConfigurationError("No price provider in the chain".to_string())))
+    }
+
+    async fn within_timeout<T>(
+        &self,
+        name: &str,
+        request: impl std::future::Future<Output = Result<T, XylexApiError>>
+    ) -> Result<T, XylexApiError> {
+        match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, request)
+                .await
+                .unwrap_or_else(|_| Err(XylexApiError::Timeout(format!("{} did not answer within {:?}", name, timeout)))),
+            None => request.await,
+        }
+    }
+
+    fn record(
+        &self,
+        source: &str,
+        quote: &Quote
+    ) {
+        let symbol: String = canonical(&quote.symbol);
+        let mut served = self.lock();
+        let switched_at: Option<Instant> = match served.peek(&symbol) {
+            Some(previous) if previous.source != source => {
+                println!("Quotes of {} switched from {} to {}", quote.symbol, previous.source, source);
+                Some(Instant::now())
+            },
+            Some(previous) => previous.switched_at,
+            None => None,
+        };
+        served.insert(symbol, ServedQuote {
+            quote: quote.clone(),
+            source: source.to_string(),
+            fetched_at: Instant::now(),
+            switched_at,
+        });
+    }
+
+    /// Returns the cached quote of `symbol` if it is recent enough, recording the cache as its source.
+    fn cached(&self, symbol: &str) -> Option<Quote> {
+        let max_age: Duration = self.cache_max_age?;
+        let mut served = self.lock();
+        let entry: &mut ServedQuote = served.get_mut(&canonical(symbol))?;
+        if entry.fetched_at.elapsed() > max_age {
+            return None;
+        }
+        entry.source = CACHE_SOURCE.to_string();
+        Some(Quote { symbol: symbol.to_string(), ..entry.quote.clone() })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LruCache<String, ServedQuote>> {
+        self.served.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl PriceProvider for ProviderChain {
+    async fn fetch_price(&self, symbol: &str) -> Result<Quote, XylexApiError> {
+        self.fetch_through_chain(symbol).await
+    }
+
+    /// Fetches every symbol from the primary at once, and each symbol through the whole chain if that fails.
+    async fn fetch_prices(&self, symbols: &[&str]) -> Result<Vec<Quote>, XylexApiError> {
+        let (name, primary) = &self.providers[0];
+        match self.within_timeout(name, primary.fetch_prices(symbols)).await {
+            Ok(quotes) => {
+                for quote in &quotes {
+                    self.record(name, quote);
+                }
+                Ok(quotes)
+            },
+            Err(e) => {
+                println!("Price provider {} failed for {:?}, fetching them one by one: {}", name, symbols, e);
+                join_all(symbols.iter().map(|symbol| self.fetch_through_chain(symbol)))
+                    .await
+                    .into_iter()
+                    .collect()
+            },
+        }
+    }
+
+    /// The symbols any provider can quote, `None` if a provider accepts any symbol.
+    async fn supported_symbols(&self) -> Result<Option<Vec<String>>, XylexApiError> {
+        let mut supported: Vec<String> = Vec::new();
+        for (_, provider) in &self.providers {
+            match provider.supported_symbols().await? {
+                Some(symbols) => supported.extend(symbols),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(supported))
+    }
+
+    /// Whether `symbol` switched source within the switch suppression window.
+    fn in_failover_transition(&self, symbol: &str) -> bool {
+        let Some(window) = self.switch_suppression else {
+            return false;
+        };
+        self.lock()
+            .peek(&canonical(symbol))
+            .and_then(|served| served.switched_at)
+            .is_some_and(|switched_at| switched_at.elapsed() <= window)
+    }
+
+    /// Bounds the last quotes of the chain and the caches of its providers.
+    fn set_resource_limits(&self, limits: ResourceLimits) {
+        self.lock().set_capacity(limits.max_symbols);
+        for (_, provider) in &self.providers {
+            provider.set_resource_limits(limits);
+        }
+    }
+
+    /// Searches through the first provider able to search.
+    async fn search_symbols(&self, query: &str) -> Result<Option<Vec<Instrument>>, XylexApiError> {
+        for (_, provider) in &self.providers {
+            if let Some(instruments) = provider.search_symbols(query).await? {
+                return Ok(Some(instruments));
+            }
+        }
+        Ok(None)
+    }
+}
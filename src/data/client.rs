@@ -1,11 +1,24 @@
 //! This module contains the implementation of the `XylexApi` struct which provides functionalities to interact with financial data APIs and calling relevant database operations.
-use crate::data::XylexApi;
-use crate::db::{Supabase, TableConfig, auth};
-use std::collections::HashSet;
+use crate::{Condition, EvaluateOn, Timeframe, Tolerance, TriggeredAlert};
+use crate::alert::{created_at, is_expired};
+use crate::data::{Candle, CheckReport, PriceProvider, Quote, XylexApi};
+use crate::data::quote::QuoteIndex;
+use crate::events::{UserTriggers, group_by_user};
+use crate::db::{Supabase, TableConfig};
+use crate::db::triggering::TRIGGERING;
+use std::collections::{HashMap, HashSet};
+use std::sync::MutexGuard;
+use std::time::Instant;
+use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, TryStreamExt, stream};
 use dotenv::dotenv;
 use std::env::var;
 use crate::errors::XylexApiError;
-use anyhow::anyhow;
+use crate::market_hours::{in_sessions, sessions_from_value};
+use crate::notify::{Delivery, NotificationRouter};
+use crate::shard::Shard;
+use crate::utils::privacy::scrub_row;
+use crate::utils::symbol::{canonical, same_symbol};
 
 /// Implementation of `XylexApi` providing functionalities to interact with financial data APIs and calling relevant database operations.
 impl XylexApi {
@@ -29,25 +42,89 @@ impl XylexApi {
         &self,
         symbols: HashSet<&str>,
     ) -> Result<Vec<(String, f64)>, XylexApiError> {
+        let quotes: Vec<Quote> = self.fetch_quotes_for_symbols(symbols).await?;
+
+        Ok(quotes.into_iter().map(|quote| (quote.symbol, quote.price)).collect())
+    }
+
+    /// Fetches real-time quotes, including bid and ask when available, for a set of symbols.
+    ///
+    /// Quotes are returned in iteration order. Symbols are fetched in batches of up to
+    /// `batch_size` symbols. When the endpoint turns out not to support batching, the symbols
+    /// are fetched one by one from then on, with up to `concurrency` requests in flight.
+    ///
+    /// # Arguments
+    /// * `symbols` - The symbol strings for which quotes need to be fetched.
+    ///
+    /// # Returns
+    /// A `Result` which is either:
+    /// - `Ok(Vec<Quote>)` - A vector with one quote per symbol.
+    /// - `Err(XylexApiError)` - An error occurred during the fetching of quotes.
+    pub async fn fetch_quotes_for_symbols<'a>(
+        &self,
+        symbols: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Vec<Quote>, XylexApiError> {
+        let symbols: Vec<&str> = symbols.into_iter().collect();
         let mut results = Vec::new();
-        for symbol in symbols {
-            println!("Fetching price for symbol: {}", symbol);
-            match self.request_real_time_price(symbol).await {
-                Ok(price) => {
-                    println!("Fetched price for {}: {}", symbol, price);
-                    results.push((symbol.to_string(), price));
-                }
-                Err(e) => {
-                    println!("Error fetching price for {}: {}", symbol, e);
-                    return Err(XylexApiError::NetworkError(e.to_string()));
+
+        if let Some(batch_size) = self.batching().filter(|_| symbols.len() > 1) {
+            for batch in symbols.chunks(batch_size) {
+                println!("Fetching prices for {} symbols in one request", batch.len());
+                // Endpoints answer a single symbol with a plain quote, even when they support batching
+                let quotes: Result<Option<Vec<Quote>>, XylexApiError> = match batch {
+                    [symbol] => self.request_real_time_quote(symbol).await.map(|quote| Some(vec![quote])),
+                    batch => self.request_real_time_quotes(batch).await,
+                };
+                match quotes {
+                    Ok(Some(quotes)) => results.extend(quotes),
+                    Ok(None) => {
+                        println!("Batched requests are not supported, fetching symbols one by one");
+                        self.disable_batching();
+                        results.clear();
+                        break;
+                    },
+                    Err(e) => {
+                        println!("Error fetching prices for {:?}: {}", batch, e);
+                        return Err(XylexApiError::NetworkError(e.to_string()));
+                    },
                 }
             }
+            if results.len() == symbols.len() {
+                println!("Fetched quotes for all symbols: {:?}", results);
+                return Ok(results);
+            }
         }
-        println!("Fetched prices for all symbols: {:?}", results);
+
+        // Futures own their symbol, so they can run concurrently in the Send future of a PriceProvider
+        let requests: Vec<_> = symbols
+            .into_iter()
+            .map(String::from)
+            .enumerate()
+            .map(|(index, symbol)| async move {
+                println!("Fetching price for symbol: {}", symbol);
+                match self.request_real_time_quote(&symbol).await {
+                    Ok(quote) => {
+                        println!("Fetched price for {}: {}", symbol, quote.price);
+                        Ok((index, quote))
+                    },
+                    Err(e) => {
+                        println!("Error fetching price for {}: {}", symbol, e);
+                        Err(XylexApiError::NetworkError(e.to_string()))
+                    },
+                }
+            })
+            .collect();
+        let mut fetched: Vec<(usize, Quote)> = stream::iter(requests)
+            .buffer_unordered(self.concurrency.max(1))
+            .try_collect()
+            .await?;
+        fetched.sort_by_key(|(index, _)| *index);
+        results.extend(fetched.into_iter().map(|(_, quote)| quote));
+        println!("Fetched quotes for all symbols: {:?}", results);
         Ok(results)
     }
 
-    pub async fn mark_alert_as_hit(alert_hash: &str) -> Result<(), XylexApiError> {
+    pub async fn mark_alert_as_hit(_alert_hash: &str) -> Result<(), XylexApiError> {
         dotenv().ok();
         let supabase_key = match var("SUPABASE_KEY") {
             Ok(key) => key,
@@ -63,7 +140,7 @@ impl XylexApi {
         
     
         
-        let client = supabase.authenticate().await;
+        let _client = supabase.authenticate().await;
 
 
         Ok(())
@@ -71,13 +148,19 @@ impl XylexApi {
 
     /// Checks and fetches alerts that are triggered based on current price levels.
     ///
+    /// Every triggered alert is marked as hit with its trigger time and price, see [`Supabase::record_trigger`].
+    ///
+    /// When a `CreditBudget` is running low, only the symbols closest to triggering are
+    /// fetched, see [`XylexApi::prioritize_symbols`]. When a `CycleBudget` runs out, the
+    /// remaining symbols are deferred to the next cycle, see [`XylexApi::with_cycle_budget`].
+    ///
     /// # Arguments
     /// * `supabase` - A reference to a `Supabase` client.
     /// * `config` - A reference to a `TableConfig` which contains configuration for database tables.
     ///
     /// # Returns
     /// A `Result` which is either:
-    /// - `Ok(Vec<String>)` - A vector of hash strings representing the triggered alerts.
+    /// - `Ok(Vec<TriggeredAlert>)` - The triggered alerts with their hash, level, trigger price and trigger time.
     /// - `Err(XylexApiError)` - An error occurred during the operation.
     ///
     /// # Examples
@@ -107,67 +190,549 @@ impl XylexApi {
         &self,
         supabase: &Supabase,
         config: &TableConfig,
-    ) -> Result<Vec<String>, XylexApiError> {
-        // Fetch current prices for all symbols
-        println!("Fetching unique symbols from Supabase...");
-        let (symbols, _success) = supabase.fetch_unique_symbols(config).await.map_err(|e| {
-            println!("Error fetching unique symbols: {}", e);
-            XylexApiError::NetworkError(e.to_string())
-        })?;
-        println!("Fetched symbols: {:#?}", symbols);
-
-        let symbol_refs: HashSet<&str> = symbols.iter().map(String::as_str).collect();
-        println!("Fetching prices for symbols: {:#?}", symbol_refs);
-        let prices = self.fetch_prices_for_symbols(symbol_refs).await?;
-        println!("Fetched prices: {:#?}", prices);
-
-        // Fetch all alert data
-        println!("Fetching all alert data from Supabase...");
-        let all_data = supabase.fetch_all_data(config).await.map_err(|e| {
-            println!("Error fetching all alert data: {}", e);
-            XylexApiError::NetworkError(e.to_string())
-        })?;
-        println!("Fetched alert data: {:#?}", all_data);
+    ) -> Result<Vec<TriggeredAlert>, XylexApiError> {
+        self.check_and_fetch_triggered_alerts(supabase, config).await
+    }
+
+    /// Checks and fetches alerts that are triggered based on current price levels,
+    /// returning the full details of every triggered alert including its metadata.
+    ///
+    /// When a `CreditBudget` is running low, only the symbols closest to triggering are
+    /// fetched, see [`XylexApi::prioritize_symbols`].
+    ///
+    /// # Arguments
+    /// * `supabase` - A reference to a `Supabase` client.
+    /// * `config` - A reference to a `TableConfig` which contains configuration for database tables.
+    ///
+    /// # Returns
+    /// A `Result` which is either:
+    /// - `Ok(Vec<TriggeredAlert>)` - The triggered alerts with the price that triggered them.
+    /// - `Err(XylexApiError)` - An error occurred during the operation.
+    pub async fn check_and_fetch_triggered_alerts(
+        &self,
+        supabase: &Supabase,
+        config: &TableConfig,
+    ) -> Result<Vec<TriggeredAlert>, XylexApiError> {
+        let report: CheckReport = self.check_alerts(supabase, config).await?;
+
+        Ok(report.triggered)
+    }
+
+    /// Runs a single check cycle and delivers every triggered alert through `router`.
+    ///
+    /// Meant for callers driving the cycle themselves instead of a [`crate::TradeAlerts`] system.
+    /// Every alert is routed on its own, see [`NotificationRouter::route`], unless the router is
+    /// grouped, in which case the triggers of every user are delivered together, see
+    /// [`NotificationRouter::route_grouped`], and every alert gets the deliveries which reached it.
+    /// A failed delivery is reported and does not stop the others. Archiving or deleting the alerts
+    /// is left to the caller. In a dry run nothing is delivered and every alert has no deliveries.
+    ///
+    /// # Arguments
+    /// * `supabase` - A reference to a `Supabase` client.
+    /// * `config` - A reference to a `TableConfig` which contains configuration for database tables.
+    /// * `router` - The `NotificationRouter` resolving the notifiers of every alert.
+    ///
+    /// # Returns
+    /// A `Result` which is either:
+    /// - `Ok(Vec<(TriggeredAlert, Vec<Delivery>)>)` - Every triggered alert with one `Delivery` per target.
+    /// - `Err(XylexApiError)` - An error occurred during the operation.
+    pub async fn check_and_notify(
+        &self,
+        supabase: &Supabase,
+        config: &TableConfig,
+        router: &NotificationRouter,
+    ) -> Result<Vec<(TriggeredAlert, Vec<Delivery>)>, XylexApiError> {
+        let report: CheckReport = self.check_alerts(supabase, config).await?;
+
+        if self.dry_run {
+            return Ok(report.triggered.into_iter().map(|alert| (alert, Vec::new())).collect());
+        }
+        if router.is_grouped() {
+            let mut routed: Vec<(TriggeredAlert, Vec<Delivery>)> = Vec::with_capacity(report.triggered.len());
+            for (group, deliveries) in router.route_grouped(&report.triggered).await {
+                for alert in group.alerts {
+                    let reached: Vec<Delivery> = deliveries
+                        .iter()
+                        .filter(|delivery| router.reaches(&alert, &delivery.target))
+                        .cloned()
+                        .collect();
+                    routed.push((alert, reached));
+                }
+            }
+            return Ok(routed);
+        }
+        let deliveries: Vec<Vec<Delivery>> = router.route_all(&report.triggered).await;
+        Ok(report.triggered.into_iter().zip(deliveries).collect())
+    }
+
+    /// Runs a single check cycle and returns its triggers grouped per user.
+    ///
+    /// Meant for consumers which handle notifications themselves, so downstream fan-out
+    /// is one call per user.
+    ///
+    /// # Arguments
+    /// * `supabase` - A reference to a `Supabase` client.
+    /// * `config` - A reference to a `TableConfig` which contains configuration for database tables.
+    ///
+    /// # Returns
+    /// A `Result` which is either:
+    /// - `Ok(Vec<UserTriggers>)` - One entry per user with at least one triggered alert.
+    /// - `Err(XylexApiError)` - An error occurred during the operation.
+    pub async fn collect_triggers_grouped_by_user(
+        &self,
+        supabase: &Supabase,
+        config: &TableConfig,
+    ) -> Result<Vec<UserTriggers>, XylexApiError> {
+        let report: CheckReport = self.check_alerts(supabase, config).await?;
+
+        Ok(report.triggers_by_user())
+    }
+
+    /// Runs a single check cycle and reports everything it observed.
+    ///
+    /// Used by monitors which need the fetched quotes and cycle timings next to the triggered alerts,
+    /// see [`XylexApi::check_and_fetch_triggered_alerts`] for the evaluation rules.
+    ///
+    /// # Arguments
+    /// * `supabase` - A reference to a `Supabase` client.
+    /// * `config` - A reference to a `TableConfig` which contains configuration for database tables.
+    ///
+    /// # Returns
+    /// A `Result` which is either:
+    /// - `Ok(CheckReport)` - The fetched alerts and quotes, the triggered alerts and the cycle duration.
+    /// - `Err(XylexApiError)` - An error occurred during the operation.
+    pub async fn check_alerts(
+        &self,
+        supabase: &Supabase,
+        config: &TableConfig,
+    ) -> Result<CheckReport, XylexApiError> {
+        self.check_alerts_with(self, supabase, config).await
+    }
+
+    /// Runs a single check cycle with quotes from another `PriceProvider`.
+    ///
+    /// Everything else, such as the candles, volume history, movement gate and history endpoint,
+    /// is still taken from this `XylexApi`. Symbols the provider does not support are not fetched.
+    ///
+    /// # Arguments
+    /// * `provider` - The feed quotes are fetched from.
+    /// * `supabase` - A reference to a `Supabase` client.
+    /// * `config` - A reference to a `TableConfig` which contains configuration for database tables.
+    ///
+    /// # Returns
+    /// A `Result` which is either:
+    /// - `Ok(CheckReport)` - The fetched alerts and quotes, the triggered alerts and the cycle duration.
+    /// - `Err(XylexApiError)` - An error occurred during the operation.
+    pub async fn check_alerts_with<P: PriceProvider + ?Sized>(
+        &self,
+        provider: &P,
+        supabase: &Supabase,
+        config: &TableConfig,
+    ) -> Result<CheckReport, XylexApiError> {
+        let started: Instant = Instant::now();
+
+        let owned: Option<Vec<String>> = self.shard().as_ref().and_then(|shard| shard.symbols.clone()).map(Vec::from_iter);
+        let (symbols, mut all_data) = match owned {
+            // A sharded worker only fetches the alerts of the symbols it owns
+            Some(owned) => {
+                println!("Fetching the alert data of {} owned symbols from Supabase...", owned.len());
+                let owned: Vec<&str> = owned.iter().map(String::as_str).collect();
+                let all_data = supabase.fetch_data_for_symbols(&owned, config).await.map_err(|e| {
+                    println!("Error fetching the alert data of owned symbols: {}", e);
+                    XylexApiError::NetworkError(e.to_string())
+                })?;
+                let symbols: HashSet<String> = all_data
+                    .iter()
+                    .filter_map(|data| data.get(&config.symbol_column_name).and_then(|v| v.as_str()))
+                    .map(String::from)
+                    .collect();
+                (symbols, all_data)
+            },
+            None => {
+                // Fetch current prices for all symbols
+                println!("Fetching unique symbols from Supabase...");
+                let (symbols, _success) = supabase.fetch_unique_symbols(config).await.map_err(|e| {
+                    println!("Error fetching unique symbols: {}", e);
+                    XylexApiError::NetworkError(e.to_string())
+                })?;
+                println!("Fetched symbols: {:#?}", symbols);
+
+                // Fetch all alert data
+                println!("Fetching all alert data from Supabase...");
+                let all_data = supabase.fetch_all_data(config).await.map_err(|e| {
+                    println!("Error fetching all alert data: {}", e);
+                    XylexApiError::NetworkError(e.to_string())
+                })?;
+                (symbols, all_data)
+            },
+        };
+        // A sharded worker only checks the alerts of its own symbols
+        if let Some(shard) = self.shard().as_ref() {
+            all_data.retain(|data| shard.owns(data.get(&config.symbol_column_name).and_then(|v| v.as_str()).unwrap_or_default()));
+        }
+        // Crossings of alerts which were deleted or moved to another shard are never confirmed
+        {
+            let fetched: HashSet<&str> = all_data
+                .iter()
+                .filter_map(|data| data.get(&config.hash_column_name).and_then(|v| v.as_str()))
+                .collect();
+            self.unconfirmed_crossings().retain(|hash| fetched.contains(hash.as_str()));
+        }
+        println!(
+            "Fetched alert data: {:#?}",
+            all_data.iter().map(|row| scrub_row(row, config)).collect::<Vec<_>>()
+        );
+
+        // Conditions are parsed once per cycle, the rows are borrowed from here on
+        let conditions: Vec<Option<Condition>> = all_data
+            .iter()
+            .map(|data| Condition::from_value(data.get(&config.condition_column_name)))
+            .collect();
+
+        // Time-based alerts don't need price data, only fetch symbols with price-based alerts
+        let mut priced: HashSet<&str> = HashSet::new();
+        // Compound conditions need the quotes of every symbol they reference
+        let mut referenced: HashSet<&str> = HashSet::new();
+        for (data, condition) in all_data.iter().zip(&conditions) {
+            if !condition.as_ref().is_some_and(Condition::is_time_based) {
+                priced.extend(data.get(&config.symbol_column_name).and_then(|v| v.as_str()));
+            }
+            if let Some(Condition::Compound(tree)) = condition {
+                referenced.extend(tree.symbols());
+            }
+        }
+        let mut symbols: HashSet<String> = symbols
+            .into_iter()
+            .filter(|symbol| priced.contains(symbol.as_str()))
+            .collect();
+        symbols.extend(referenced.into_iter().map(String::from));
+        // Alerts spelling a symbol differently, e.g. `eur/usd` and `EURUSD`, share a single quote
+        let mut seen: HashSet<String> = HashSet::new();
+        symbols.retain(|symbol| seen.insert(canonical(symbol)));
+        if let Some(supported) = provider.supported_symbols().await? {
+            let (kept, unsupported): (HashSet<String>, HashSet<String>) = symbols
+                .into_iter()
+                .partition(|symbol| supported.iter().any(|s| same_symbol(s, symbol)));
+            if !unsupported.is_empty() {
+                println!("Skipping symbols the provider does not support: {:?}", unsupported);
+            }
+            symbols = kept;
+        }
+        if let Some(market_hours) = &self.market_hours {
+            let now = Utc::now();
+            let (open, closed): (HashSet<String>, HashSet<String>) = symbols
+                .into_iter()
+                .partition(|symbol| market_hours.is_open(symbol, now));
+            if !closed.is_empty() {
+                println!("Skipping symbols of closed markets: {:?}", closed);
+            }
+            symbols = open;
+        }
+        let scheduled_at: Instant = Instant::now();
+        if let Some(schedule) = &self.schedule {
+            let (due, waiting): (HashSet<String>, HashSet<String>) = schedule.take_due(symbols, scheduled_at);
+            if !waiting.is_empty() {
+                println!("Skipping symbols which are not due yet: {:?}", waiting);
+            }
+            symbols = due;
+        }
+        if let Some(max_symbols) = self.resource_limits.max_symbols.filter(|max_symbols| symbols.len() > *max_symbols) {
+            println!(
+                "Checking {} symbols with max_symbols {}, band crossings of symbols whose previous price is evicted are missed",
+                symbols.len(), max_symbols
+            );
+        }
+        let symbol_refs: Vec<&str> = self.prioritize_symbols(&symbols, &all_data, config);
+        println!("Fetching quotes for symbols: {:#?}", symbol_refs);
+        let (quotes, deferred) = self
+            .fetch_within_budget(provider, symbol_refs, &all_data, config, started)
+            .await?;
+        // Groups whose fetch failed or was deferred stay due for the next cycle
+        if let Some(schedule) = &self.schedule {
+            schedule.mark_checked(quotes.iter().map(|quote| quote.symbol.as_str()), scheduled_at);
+        }
+        let quotes: Vec<Quote> = quotes
+            .into_iter()
+            .map(|mut quote| {
+                if let Some(volume) = quote.volume {
+                    quote.average_volume = self.volumes.record(&quote.symbol, volume);
+                }
+                quote
+            })
+            .collect();
+        println!("Fetched quotes: {:#?}", quotes);
+
+        // Band alerts compare against the price at the previous check
+        let previous_prices: HashMap<&str, Option<f64>> = quotes
+            .iter()
+            .map(|quote| (quote.symbol.as_str(), self.previous_prices.record(&quote.symbol, quote.price)))
+            .collect();
+
+        // Symbols which did not move enough since their last evaluation skip their price alerts
+        let unmoved: HashSet<&str> = match &self.movement_gate {
+            Some(gate) => quotes
+                .iter()
+                .filter(|quote| !gate.should_evaluate(&quote.symbol, quote.price))
+                .map(|quote| quote.symbol.as_str())
+                .collect(),
+            None => HashSet::new(),
+        };
+        if !unmoved.is_empty() {
+            println!("Skipping price alerts of unmoved symbols: {:?}", unmoved);
+        }
+
+        // Indicator conditions are evaluated on candle closes, fetched once per symbol and timeframe
+        let mut closes: HashMap<(&str, Timeframe), Vec<f64>> = HashMap::new();
+        for (data, condition) in all_data.iter().zip(&conditions) {
+            let (Some(symbol), Some(condition)) = (
+                data.get(&config.symbol_column_name).and_then(|v| v.as_str()),
+                condition,
+            ) else {
+                continue;
+            };
+            let Some(count) = condition.candles_needed() else {
+                continue;
+            };
+            let timeframe: Timeframe = match EvaluateOn::from_value(data.get(&config.evaluate_on_column_name)) {
+                Some(EvaluateOn::CandleClose(timeframe)) => timeframe,
+                _ => Timeframe::OneHour,
+            };
+            let key = (symbol, timeframe);
+            if closes.get(&key).is_some_and(|closes| closes.len() >= count) {
+                continue;
+            }
+            match self.request_candles(symbol, timeframe, count).await {
+                Ok(candles) => {
+                    closes.insert(key, candles.iter().map(|candle| candle.close).collect());
+                },
+                Err(e) => println!("Error fetching candles for {}: {}", symbol, e),
+            }
+        }
+
+        // Weekly and monthly alerts are evaluated on the last closed period, fetched once per symbol and timeframe
+        let now = Utc::now();
+        let mut period_closes: HashMap<(&str, Timeframe), Option<Candle>> = HashMap::new();
+        for data in &all_data {
+            let (Some(symbol), Some(EvaluateOn::CandleClose(timeframe))) = (
+                data.get(&config.symbol_column_name).and_then(|v| v.as_str()),
+                EvaluateOn::from_value(data.get(&config.evaluate_on_column_name)),
+            ) else {
+                continue;
+            };
+            if !timeframe.is_calendar() || period_closes.contains_key(&(symbol, timeframe)) {
+                continue;
+            }
+            let candle: Option<Candle> = match self.completed_candle(symbol, timeframe, now).await {
+                Ok(candle) => Some(candle),
+                Err(e) => {
+                    println!("Error fetching the closed {} candle of {}: {}", timeframe.interval(), symbol, e);
+                    None
+                },
+            };
+            period_closes.insert((symbol, timeframe), candle);
+        }
 
         // Check which alerts are triggered
-        let mut triggered_hashes = Vec::new();
+        let mut triggered_alerts = Vec::new();
+        // Candles closed by this check, recorded once per symbol and timeframe
+        let mut closed_candles: HashMap<(&str, Timeframe), Option<Candle>> = HashMap::new();
+        let mut quote_index: QuoteIndex = QuoteIndex::new(&quotes);
 
-        for data in all_data {
+        for (data, condition) in all_data.iter().zip(&conditions) {
             match (
                 data.get(&config.symbol_column_name)
                     .and_then(|v| v.as_str()),
                 data.get(&config.price_level_column_name)
                     .and_then(|v| v.as_f64()),
                 data.get(&config.hash_column_name).and_then(|v| v.as_str()),
-                data.get("initial_direction").and_then(|v| v.as_str()),
+                data.get(&config.user_id_column_name).and_then(|v| v.as_str()),
+                data.get(&config.direction_column_name).and_then(|v| v.as_str()),
+                condition,
             ) {
-                (Some(symbol), Some(price_level), Some(hash), Some(initial_direction)) => {
+                (Some(symbol), Some(price_level), Some(hash), Some(user_id), Some(initial_direction), Some(condition)) => {
+                    if is_expired(data.get(&config.expiry_column_name)) {
+                        println!("Skipping expired alert: {}", hash);
+                        continue;
+                    }
+                    if data.get(&config.trigger_state_column_name).and_then(|v| v.as_str()) == Some(TRIGGERING) {
+                        println!("Skipping alert which is being delivered: {}", hash);
+                        continue;
+                    }
+                    if !in_sessions(&sessions_from_value(data.get(&config.sessions_column_name)), now) {
+                        println!("Skipping alert outside of its trading sessions: {}", hash);
+                        continue;
+                    }
                     println!(
                         "Checking alert for symbol: {}, price level: {}, hash: {}",
                         symbol, price_level, hash
                     );
-                    if let Some((_, fetched_price)) = prices.iter().find(|(s, _)| s == symbol) {
-                        println!("Fetched price for symbol {}: {}", symbol, fetched_price);
+                    if condition.is_time_based() {
+                        self.record_evaluation(hash, now);
+                        if condition.is_due(now) {
+                            println!("Time-based alert triggered for hash: {}", hash);
+                            triggered_alerts.push(TriggeredAlert {
+                                hash: hash.to_string(),
+                                user_id: user_id.to_string(),
+                                symbol: symbol.to_string(),
+                                price_level,
+                                trigger_price: data
+                                    .get("latest_price")
+                                    .and_then(|v| v.as_f64())
+                                    .unwrap_or(price_level),
+                                condition: condition.clone(),
+                                metadata: data
+                                    .get(&config.metadata_column_name)
+                                    .filter(|v| !v.is_null())
+                                    .cloned(),
+                                triggered_at: now,
+                                quote_time: None,
+                            });
+                        }
+                        continue;
+                    }
+                    if let Some(quote) = quote_index.get(symbol) {
+                        println!("Fetched price for symbol {}: {}", symbol, quote.price);
+
+                        let quote: Quote = match EvaluateOn::from_value(data.get(&config.evaluate_on_column_name)) {
+                            Some(EvaluateOn::CandleClose(timeframe)) if timeframe.is_calendar() => {
+                                let Some(candle) = period_closes.get(&(symbol, timeframe)).and_then(Option::as_ref) else {
+                                    continue;
+                                };
+                                // Only periods which closed after the alert was created count
+                                let closed_at: DateTime<Utc> = timeframe.next_candle_start(candle.open_time);
+                                if created_at(data.get(&config.created_at_column_name)).is_some_and(|created_at| created_at > closed_at) {
+                                    continue;
+                                }
+                                Quote { price: candle.close, timestamp: Some(closed_at), ..quote.clone() }
+                            },
+                            Some(EvaluateOn::CandleClose(timeframe)) => {
+                                let closed: &Option<Candle> = closed_candles
+                                    .entry((symbol, timeframe))
+                                    .or_insert_with(|| self.candles.record(symbol, timeframe, quote.price, now));
+                                match closed {
+                                    Some(candle) => Quote { price: candle.close, ..quote.clone() },
+                                    None => continue,
+                                }
+                            },
+                            Some(EvaluateOn::Tick) => quote.clone(),
+                            None => {
+                                println!("Invalid evaluation mode for alert: {}", hash);
+                                continue;
+                            },
+                        };
+                        let quote: &Quote = &quote;
                         
-                        println!("\x1b[1;33mChecking alert: initial_direction: {}, price_level: {}, fetched_price: {}\x1b[0m", initial_direction, price_level, fetched_price);
-                        if 
-                            (initial_direction == "sell" && fetched_price >= &price_level)
-                            || 
-                            (initial_direction == "buy" && fetched_price <= &price_level)
-                        {
+                        println!("\x1b[1;33mChecking alert: condition: {:?}, initial_direction: {}, price_level: {}, fetched_price: {}, spread: {:?}\x1b[0m", condition, initial_direction, price_level, quote.price, quote.spread_pips());
+                        // A crossing suppressed during a provider switch is confirmed by the next check
+                        let unconfirmed: bool = self.unconfirmed_crossings().remove(hash);
+                        let confirmation: Option<Condition> = condition.crossing_confirmation();
+                        let evaluated: &Condition = match &confirmation {
+                            Some(confirmation) if unconfirmed => confirmation,
+                            _ => condition,
+                        };
+                        let is_met: bool = match evaluated {
+                            Condition::Compound(tree) => tree.is_met(price_level, initial_direction, &quotes),
+                            condition if condition.candles_needed().is_some() => {
+                                let timeframe: Timeframe = match EvaluateOn::from_value(data.get(&config.evaluate_on_column_name)) {
+                                    Some(EvaluateOn::CandleClose(timeframe)) => timeframe,
+                                    _ => Timeframe::OneHour,
+                                };
+                                closes
+                                    .get(&(symbol, timeframe))
+                                    .is_some_and(|closes| condition.is_met_on_closes(closes))
+                            },
+                            Condition::Band(_) => data
+                                .get(&config.upper_price_level_column_name)
+                                .and_then(|v| v.as_f64())
+                                .is_some_and(|upper_price_level| evaluated.is_met_on_band(
+                                    price_level,
+                                    upper_price_level,
+                                    previous_prices.get(quote.symbol.as_str()).copied().flatten(),
+                                    quote.price,
+                                )),
+                            // A small move across one of the levels is still evaluated, conditions without
+                            // price levels, e.g. on the spread or volume, are never gated
+                            evaluated if unmoved.contains(quote.symbol.as_str()) && !unconfirmed && {
+                                let levels: Vec<f64> = evaluated.levels(price_level, None);
+                                !levels.is_empty() && !self
+                                    .movement_gate
+                                    .as_ref()
+                                    .is_some_and(|gate| gate.crossed(&quote.symbol, quote.price, &levels))
+                            } => false,
+                            condition => condition.is_met_with_tolerance(
+                                price_level,
+                                initial_direction,
+                                quote,
+                                Tolerance::from_value(data.get(&config.tolerance_column_name)),
+                            ),
+                        };
+                        self.record_evaluation(hash, now);
+                        let is_met: bool = if unconfirmed {
+                            if !is_met {
+                                println!("Crossing of {} during a provider switch was not confirmed", hash);
+                            }
+                            is_met
+                        } else if is_met && confirmation.is_some() && provider.in_failover_transition(symbol) {
+                            println!("Suppressing crossing of {} during a provider switch, confirming it on the next check", hash);
+                            self.unconfirmed_crossings().insert(hash.to_string());
+                            false
+                        } else {
+                            is_met
+                        };
+                        if is_met {
                             println!("Alert triggered for hash: {}", hash);
-                            triggered_hashes.push(hash.to_string());
+                            triggered_alerts.push(TriggeredAlert {
+                                hash: hash.to_string(),
+                                user_id: user_id.to_string(),
+                                symbol: symbol.to_string(),
+                                price_level,
+                                trigger_price: quote.price,
+                                condition: condition.clone(),
+                                metadata: data
+                                    .get(&config.metadata_column_name)
+                                    .filter(|v| !v.is_null())
+                                    .cloned(),
+                                triggered_at: now,
+                                quote_time: quote.timestamp,
+                            });
                         }
                     }
                 }
                 _ => {
-                    println!("Incomplete data for alert: {:#?}", data);
+                    println!("Incomplete data for alert: {:#?}", scrub_row(data, config));
+                }
+            }
+        }
+
+        println!(
+            "Triggered hashes: {:#?}",
+            triggered_alerts.iter().map(|alert| &alert.hash).collect::<Vec<_>>()
+        );
+
+        if let Some(max_clock_skew) = self.max_clock_skew {
+            for alert in &triggered_alerts {
+                if let Some(skew) = alert.clock_skew().filter(|skew| skew.abs().to_std().is_ok_and(|skew| skew > max_clock_skew)) {
+                    println!(
+                        "Warning: alert {} was evaluated {}ms after its quote time, the {} feed may be delayed or a clock is off",
+                        alert.hash, skew.num_milliseconds(), alert.symbol
+                    );
                 }
             }
         }
 
-        println!("Triggered hashes: {:#?}", triggered_hashes);
-        Ok(triggered_hashes)
+        // Record the trigger time and price before the alerts are deleted or archived
+        for alert in triggered_alerts.iter().filter(|_| !self.dry_run) {
+            if let Err(e) = supabase.record_trigger(alert, config).await {
+                println!("Error recording trigger for {}: {}", alert.hash, e);
+            }
+        }
+        Ok(CheckReport {
+            alerts: all_data,
+            quotes,
+            triggered: triggered_alerts,
+            duration: started.elapsed(),
+            deferred,
+        })
     }
 
     /// Deletes alerts identified by their hashes.
@@ -223,4 +788,50 @@ impl XylexApi {
         }
         Ok(())
     }
+
+    /// Returns when a check last evaluated the alert with the given hash, `None` if none did.
+    pub fn last_evaluated(&self, hash: &str) -> Option<DateTime<Utc>> {
+        self.evaluated_at.lock().unwrap_or_else(|e| e.into_inner()).peek(&hash.to_string()).copied()
+    }
+
+    /// Returns whether the crossing of the alert with the given hash awaits confirmation, see
+    /// [`crate::data::ProviderChain::with_switch_suppression`].
+    pub fn awaiting_confirmation(&self, hash: &str) -> bool {
+        self.unconfirmed_crossings().contains(hash)
+    }
+
+    /// Restricts checks to the alerts of the symbols `shard` owns, or lifts the restriction for `None`.
+    ///
+    /// Alerts without a symbol belong to the owner of the empty symbol, so exactly one worker checks them.
+    pub fn set_shard(&self, shard: Option<Shard>) {
+        *self.shard() = shard;
+    }
+
+    /// Returns whether this worker checks the alerts of a symbol, always `true` when not sharded.
+    pub fn owns_symbol(&self, symbol: &str) -> bool {
+        self.shard().as_ref().is_none_or(|shard| shard.owns(symbol))
+    }
+
+    fn record_evaluation(
+        &self,
+        hash: &str,
+        at: DateTime<Utc>
+    ) {
+        self.evaluated_at.lock().unwrap_or_else(|e| e.into_inner()).insert(hash.to_string(), at);
+    }
+
+    fn unconfirmed_crossings(&self) -> MutexGuard<'_, HashSet<String>> {
+        self.unconfirmed_crossings.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn shard(&self) -> MutexGuard<'_, Option<Shard>> {
+        self.shard.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl CheckReport {
+    /// Returns the cycle's triggered alerts grouped per user.
+    pub fn triggers_by_user(&self) -> Vec<UserTriggers> {
+        group_by_user(&self.triggered)
+    }
 }
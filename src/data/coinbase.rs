@@ -0,0 +1,302 @@
+//! ## Coinbase price provider
+//! Spot prices from Coinbase's public market API, a second crypto venue next to Binance so an
+//! outage of one exchange doesn't blind the alert system, see [`crate::data::ProviderChain`].
+
+use std::collections::HashSet;
+use std::env::var;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::data::{CoinbaseProvider, Instrument, PriceProvider, Quote};
+use crate::data::request::parse_optional_number;
+use crate::errors::XylexApiError;
+use crate::utils::symbol::{Symbol, SymbolFormat};
+
+/// Base URL of Coinbase's public Advanced Trade API.
+pub const COINBASE_API_ENDPOINT: &str = "https://api.coinbase.com";
+
+/// Number of products requested per page when listing products.
+pub const COINBASE_PAGE_SIZE: usize = 250;
+
+impl Default for CoinbaseProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CoinbaseProvider {
+    /// Creates a `CoinbaseProvider` fetching last prices from the public market API.
+    pub fn new() -> Self {
+        Self {
+            endpoint: COINBASE_API_ENDPOINT.to_string(),
+            page_size: COINBASE_PAGE_SIZE,
+            #[cfg(feature = "replay-http")]
+            replay: None,
+        }
+    }
+
+    /// Creates a `CoinbaseProvider` from environment variables.
+    ///
+    /// `COINBASE_API_ENDPOINT` optionally overrides the endpoint.
+    pub fn new_env() -> Self {
+        Self {
+            endpoint: var("COINBASE_API_ENDPOINT").unwrap_or_else(|_| COINBASE_API_ENDPOINT.to_string()),
+            ..Self::new()
+        }
+    }
+
+    /// Sets the API endpoint.
+    ///
+    /// # Arguments
+    /// * `endpoint` - The base URL, without a trailing `/api/v3`.
+    pub fn with_endpoint(
+        mut self,
+        endpoint: &str
+    ) -> Self {
+        self.endpoint = endpoint.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Sets the number of products requested per page, at least `1`.
+    pub fn with_page_size(
+        mut self,
+        page_size: usize
+    ) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Lists every spot product which is online, following the pages of the product list.
+    ///
+    /// Stops at a page without new products, so an endpoint ignoring the offset isn't paged forever.
+    ///
+    /// # Errors
+    /// Returns a `XylexApiError` if a page could not be fetched.
+    pub async fn products(&self) -> Result<Vec<Value>, XylexApiError> {
+        let page_size: usize = self.page_size.max(1);
+        let mut products: Vec<Value> = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        loop {
+            let query = [
+                ("product_type", "SPOT".to_string()),
+                ("limit", page_size.to_string()),
+                ("offset", products.len().to_string()),
+            ];
+            let page: Value = self.get(&self.products_url(), &query).await?;
+            let listed: &[Value] = page["products"].as_array().map(Vec::as_slice).unwrap_or_default();
+            let before: usize = products.len();
+            products.extend(
+                listed
+                    .iter()
+                    .filter(|product| seen.insert(product["product_id"].as_str().unwrap_or_default().to_string()))
+                    .cloned(),
+            );
+
+            let total: Option<usize> = page["num_products"].as_u64().map(|total| total as usize);
+            if listed.len() < page_size || products.len() == before || total.is_some_and(|total| products.len() >= total) {
+                break;
+            }
+        }
+        products.retain(is_online);
+        Ok(products)
+    }
+
+    /// Fetches the symbols one by one, skipping the ones Coinbase has no product for.
+    async fn fetch_each(
+        &self,
+        symbols: &[&str]
+    ) -> Result<Vec<Quote>, XylexApiError> {
+        let mut quotes: Vec<Quote> = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            match self.fetch_price(symbol).await {
+                Ok(quote) => quotes.push(quote),
+                Err(XylexApiError::InvalidSymbol(message)) => println!("Skipping {} on Coinbase: {}", symbol, message),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(quotes)
+    }
+
+    fn products_url(&self) -> String {
+        format!("{}/api/v3/brokerage/market/products", self.endpoint)
+    }
+
+    async fn get(
+        &self,
+        url: &str,
+        query: &[(&str, String)]
+    ) -> Result<Value, XylexApiError> {
+        let mut url: reqwest::Url = reqwest::Url::parse(url)
+            .map_err(|e| XylexApiError::ConfigurationError(format!("Invalid Coinbase endpoint: {}", e)))?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        let send = async {
+            let response: reqwest::Response = reqwest::Client::new()
+                .get(url.clone())
+                .send()
+                .await
+                .map_err(|e| XylexApiError::NetworkError(format!("Failed to send request: {}", e)))?;
+
+            let status: u16 = response.status().as_u16();
+            let body: Value = response
+                .json::<Value>()
+                .await
+                .map_err(|_| XylexApiError::UnexpectedError("Failed to parse JSON".to_string()))?;
+            Ok((status, body))
+        };
+        #[cfg(feature = "replay-http")]
+        let (status, body) = match &self.replay {
+            Some(replay) => replay.exchange("GET", url.as_str(), send).await?,
+            None => send.await?,
+        };
+        #[cfg(not(feature = "replay-http"))]
+        let (status, body) = send.await?;
+        if (200..300).contains(&status) {
+            return Ok(body);
+        }
+
+        let message: String = body["message"].as_str().unwrap_or("unknown error").to_string();
+        match status {
+            // Unknown products are answered with 404, malformed product ids with 400 naming the product id
+            404 => Err(XylexApiError::InvalidSymbol(message)),
+            400 if is_product_error(&body) => Err(XylexApiError::InvalidSymbol(message)),
+            _ => Err(XylexApiError::NetworkError(format!("Coinbase returned {}: {}", status, message))),
+        }
+    }
+}
+
+/// Normalizes a symbol to Coinbase's product id, e.g. `btc/usd` or `BTCUSD` to `BTC-USD`.
+pub fn normalize_coinbase_symbol(symbol: &str) -> String {
+    match Symbol::parse(symbol) {
+        Ok(symbol) => symbol.format(SymbolFormat::Dashed),
+        Err(_) => symbol.trim().to_uppercase(),
+    }
+}
+
+/// Returns `true` if a `400` body rejects the product id, rather than another part of the request.
+fn is_product_error(body: &Value) -> bool {
+    ["message", "error_details"]
+        .iter()
+        .filter_map(|field| body[*field].as_str())
+        .any(|text| text.to_lowercase().replace(['_', ' '], "").contains("productid"))
+}
+
+/// Returns `true` if the product can be traded.
+fn is_online(product: &Value) -> bool {
+    product["status"] == "online" && product["trading_disabled"] != true
+}
+
+/// Turns a product into a quote for `symbol`, the symbol as the caller wrote it.
+fn product_to_quote(
+    symbol: &str,
+    product: &Value
+) -> Result<Quote, XylexApiError> {
+    let price: f64 = parse_optional_number(&product["price"])
+        .ok_or_else(|| XylexApiError::UnexpectedError(format!("Missing price for {}", symbol)))?;
+
+    Ok(Quote {
+        symbol: symbol.to_string(),
+        price,
+        bid: None,
+        ask: None,
+        volume: parse_optional_number(&product["volume_24h"]),
+        average_volume: None,
+        timestamp: None,
+    })
+}
+
+/// Turns a product into the instrument listed by searches, e.g. `BTC/USD`.
+fn product_to_instrument(product: &Value) -> Option<Instrument> {
+    let base: &str = product["base_display_symbol"].as_str().or_else(|| product["base_currency_id"].as_str())?;
+    let quote: &str = product["quote_display_symbol"].as_str().or_else(|| product["quote_currency_id"].as_str())?;
+    Some(Instrument {
+        symbol: format!("{}/{}", base, quote),
+        name: product["base_name"].as_str().filter(|name| !name.is_empty()).map(String::from),
+        exchange: Some("Coinbase".to_string()),
+        asset_type: Some("crypto".to_string()),
+        currency: Some(quote.to_string()),
+    })
+}
+
+#[async_trait]
+impl PriceProvider for CoinbaseProvider {
+    async fn fetch_price(&self, symbol: &str) -> Result<Quote, XylexApiError> {
+        let url: String = format!("{}/{}", self.products_url(), normalize_coinbase_symbol(symbol));
+        let product: Value = self.get(&url, &[]).await?;
+
+        product_to_quote(symbol, &product)
+    }
+
+    /// Fetches every symbol in a single request.
+    ///
+    /// Symbols Coinbase lists no product for are skipped and logged, so fewer quotes than
+    /// symbols may be returned rather than failing the symbols which were found. A batch
+    /// rejected for a malformed product id is fetched one symbol at a time.
+    async fn fetch_prices(&self, symbols: &[&str]) -> Result<Vec<Quote>, XylexApiError> {
+        if symbols.is_empty() {
+            return Ok(Vec::new());
+        }
+        let normalized: Vec<String> = symbols.iter().map(|symbol| normalize_coinbase_symbol(symbol)).collect();
+        let query: Vec<(&str, String)> = normalized.iter().map(|id| ("product_ids", id.clone())).collect();
+
+        let products: Value = match self.get(&self.products_url(), &query).await {
+            Ok(products) => products,
+            Err(XylexApiError::InvalidSymbol(message)) => {
+                println!("Coinbase rejected the batch {:?}, fetching it one by one: {}", symbols, message);
+                return self.fetch_each(symbols).await;
+            },
+            Err(e) => return Err(e),
+        };
+        let products: &Vec<Value> = products["products"]
+            .as_array()
+            .ok_or_else(|| XylexApiError::UnexpectedError("Expected a list of products".to_string()))?;
+
+        let mut quotes: Vec<Quote> = Vec::with_capacity(symbols.len());
+        for (symbol, product_id) in symbols.iter().zip(&normalized) {
+            match products.iter().find(|product| product["product_id"].as_str() == Some(product_id.as_str())) {
+                Some(product) => quotes.push(product_to_quote(symbol, product)?),
+                None => println!("Coinbase has no product for {}, skipping it", symbol),
+            }
+        }
+        Ok(quotes)
+    }
+
+    /// Lists the online spot products as `BASE/QUOTE`.
+    async fn supported_symbols(&self) -> Result<Option<Vec<String>>, XylexApiError> {
+        let products: Vec<Value> = self.products().await?;
+        Ok(Some(
+            products
+                .iter()
+                .filter_map(product_to_instrument)
+                .map(|instrument| instrument.symbol)
+                .collect(),
+        ))
+    }
+
+    /// Searches the online spot products whose base or quote currency starts with `query`.
+    ///
+    /// Products are returned as `BASE/QUOTE`, products whose base currency matches exactly first.
+    async fn search_symbols(&self, query: &str) -> Result<Option<Vec<Instrument>>, XylexApiError> {
+        let query: String = query.trim().to_uppercase().replace(['/', '-', '_', ' '], "");
+        let products: Vec<Value> = self.products().await?;
+
+        let mut matches: Vec<(bool, Instrument)> = products
+            .iter()
+            .filter_map(product_to_instrument)
+            .filter_map(|instrument| {
+                let (base, quote) = instrument.symbol.split_once('/')?;
+                let found: bool = query.is_empty()
+                    || base.starts_with(&query)
+                    || quote.starts_with(&query)
+                    || format!("{}{}", base, quote).starts_with(&query);
+                let exact: bool = base == query;
+                found.then_some((exact, instrument))
+            })
+            .collect();
+        matches.sort_by_key(|(exact, _)| !exact);
+
+        Ok(Some(matches.into_iter().map(|(_, instrument)| instrument).collect()))
+    }
+}
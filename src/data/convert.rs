@@ -0,0 +1,98 @@
+//! ## Currency conversion
+//!
+//! Converts amounts between currencies with real-time FX quotes, e.g. for alerts whose levels
+//! are expressed in the account currency rather than the quote currency of the symbol.
+
+use crate::data::XylexApi;
+use crate::errors::XylexApiError;
+
+/// Currencies tried, in order, to triangulate a conversion without a direct pair.
+pub const BRIDGE_CURRENCIES: &[&str] = &["USD", "EUR"];
+
+impl XylexApi {
+    /// Converts `amount` from one currency to another at the real-time rate.
+    ///
+    /// See [`XylexApi::conversion_rate`] for how the rate is derived.
+    ///
+    /// # Parameters
+    /// - `amount`: The amount in `from_ccy`.
+    /// - `from_ccy`: The ISO code of the currency to convert from, e.g. `"EUR"`.
+    /// - `to_ccy`: The ISO code of the currency to convert to, e.g. `"JPY"`.
+    ///
+    /// # Returns
+    /// A `Result` containing the amount in `to_ccy` or an error.
+    pub async fn convert(
+        &self,
+        amount: f64,
+        from_ccy: &str,
+        to_ccy: &str
+    ) -> Result<f64, XylexApiError> {
+        Ok(amount * self.conversion_rate(from_ccy, to_ccy).await?)
+    }
+
+    /// Returns how many units of `to_ccy` one unit of `from_ccy` is worth.
+    ///
+    /// The direct pair `FROM/TO` is tried first, then the inverse `TO/FROM`. Without either,
+    /// the rate is triangulated over the first of the [`BRIDGE_CURRENCIES`] quoted against both.
+    /// Converting a currency to itself needs no request.
+    ///
+    /// # Errors
+    /// - `XylexApiError::InvalidSymbol` if a code is not three letters or no pair connects them.
+    /// - Any other error of the price requests, which stops the search.
+    pub async fn conversion_rate(
+        &self,
+        from_ccy: &str,
+        to_ccy: &str
+    ) -> Result<f64, XylexApiError> {
+        let from: String = currency_code(from_ccy)?;
+        let to: String = currency_code(to_ccy)?;
+        if from == to {
+            return Ok(1.0);
+        }
+
+        if let Some(rate) = self.pair_rate(&from, &to).await? {
+            return Ok(rate);
+        }
+        for bridge in BRIDGE_CURRENCIES.iter().filter(|bridge| **bridge != from && **bridge != to) {
+            let Some(first) = self.pair_rate(&from, bridge).await? else {
+                continue;
+            };
+            if let Some(second) = self.pair_rate(bridge, &to).await? {
+                return Ok(first * second);
+            }
+        }
+
+        Err(XylexApiError::InvalidSymbol(format!("No FX pair converts {} to {}", from, to)))
+    }
+
+    /// The rate of the direct or inverse pair, `None` if neither is quoted.
+    async fn pair_rate(
+        &self,
+        from: &str,
+        to: &str
+    ) -> Result<Option<f64>, XylexApiError> {
+        if let Some(price) = self.quoted_price(&format!("{}/{}", from, to)).await? {
+            return Ok(Some(price));
+        }
+        Ok(self.quoted_price(&format!("{}/{}", to, from)).await?.map(|price| 1.0 / price))
+    }
+
+    /// The price of a pair, `None` if the provider does not quote it.
+    async fn quoted_price(&self, pair: &str) -> Result<Option<f64>, XylexApiError> {
+        match self.request_real_time_price(pair).await {
+            Ok(price) if price > 0.0 && price.is_finite() => Ok(Some(price)),
+            Ok(price) => Err(XylexApiError::UnexpectedError(format!("{} quoted at {}", pair, price))),
+            Err(XylexApiError::InvalidSymbol(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Validates and upper-cases an ISO currency code.
+fn currency_code(code: &str) -> Result<String, XylexApiError> {
+    let code: &str = code.trim();
+    if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(XylexApiError::InvalidSymbol(format!("Not a currency code: {}", code)));
+    }
+    Ok(code.to_ascii_uppercase())
+}
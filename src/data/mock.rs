@@ -0,0 +1,131 @@
+//! ## Mock price provider
+//! Prices from memory for the crate's tests, examples and dry runs, so the full trigger pipeline
+//! runs without network or API keys.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::MutexGuard;
+
+use async_trait::async_trait;
+
+use crate::data::{MockPrices, MockProvider, PriceProvider, Quote};
+use crate::errors::XylexApiError;
+use crate::utils::symbol::canonical;
+
+impl MockProvider {
+    /// Creates a `MockProvider` without any prices.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Quotes `symbol` at a fixed price.
+    pub fn with_price(
+        self,
+        symbol: &str,
+        price: f64
+    ) -> Self {
+        self.set_price(symbol, price);
+        self
+    }
+
+    /// Quotes `symbol` from a scripted sequence, one price per fetch.
+    pub fn with_script(
+        self,
+        symbol: &str,
+        prices: impl IntoIterator<Item = f64>
+    ) -> Self {
+        self.set_script(symbol, prices);
+        self
+    }
+
+    /// Quotes `symbol` moving in a straight line from `from` to `to` over `ticks` fetches, see [`ramp`].
+    pub fn with_ramp(
+        self,
+        symbol: &str,
+        from: f64,
+        to: f64,
+        ticks: usize
+    ) -> Self {
+        self.set_script(symbol, ramp(from, to, ticks));
+        self
+    }
+
+    /// Sets the price of `symbol`, replacing any script.
+    pub fn set_price(
+        &self,
+        symbol: &str,
+        price: f64
+    ) {
+        self.set_script(symbol, [price]);
+    }
+
+    /// Replaces the prices of `symbol` with a script, an empty script removes the symbol.
+    pub fn set_script(
+        &self,
+        symbol: &str,
+        prices: impl IntoIterator<Item = f64>
+    ) {
+        let ticks: VecDeque<f64> = prices.into_iter().collect();
+        let mut quotes = self.lock();
+        match ticks.is_empty() {
+            true => quotes.remove(&canonical(symbol)),
+            false => quotes.insert(canonical(symbol), MockPrices { symbol: symbol.to_string(), ticks }),
+        };
+    }
+
+    /// Returns the price the next fetch of `symbol` quotes, without advancing its script.
+    pub fn price(&self, symbol: &str) -> Option<f64> {
+        self.lock()
+            .get(&canonical(symbol))
+            .and_then(|prices| prices.ticks.front().copied())
+    }
+
+    /// Returns `true` if every script has reached its last price.
+    pub fn is_exhausted(&self) -> bool {
+        self.lock().values().all(|prices| prices.ticks.len() <= 1)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, MockPrices>> {
+        self.prices.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Returns `ticks` prices evenly spaced from `from` to `to`, both included.
+///
+/// A single tick is `to`, no ticks is an empty sequence.
+pub fn ramp(
+    from: f64,
+    to: f64,
+    ticks: usize
+) -> Vec<f64> {
+    match ticks {
+        0 => Vec::new(),
+        1 => vec![to],
+        _ => {
+            let step: f64 = (to - from) / (ticks - 1) as f64;
+            (0..ticks).map(|tick| from + step * tick as f64).collect()
+        },
+    }
+}
+
+#[async_trait]
+impl PriceProvider for MockProvider {
+    /// Quotes the next price of the symbol's script, carrying the symbol as the caller wrote it.
+    async fn fetch_price(&self, symbol: &str) -> Result<Quote, XylexApiError> {
+        let mut quotes = self.lock();
+        let prices: &mut MockPrices = quotes
+            .get_mut(&canonical(symbol))
+            .ok_or_else(|| XylexApiError::InvalidSymbol(format!("No mock price for {}", symbol)))?;
+
+        let price: f64 = match prices.ticks.len() {
+            1 => prices.ticks[0],
+            _ => prices.ticks.pop_front().expect("Scripts are never empty"),
+        };
+        Ok(Quote::new(symbol.to_string(), price))
+    }
+
+    async fn supported_symbols(&self) -> Result<Option<Vec<String>>, XylexApiError> {
+        let mut symbols: Vec<String> = self.lock().values().map(|prices| prices.symbol.clone()).collect();
+        symbols.sort();
+        Ok(Some(symbols))
+    }
+}
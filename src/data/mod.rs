@@ -1,11 +1,603 @@
 //! Data management for incoming price data feeds
 
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{Timeframe, TriggeredAlert};
+use crate::errors::XylexApiError;
+use crate::market_hours::MarketHours;
+use crate::retry::RetryPolicy;
+use crate::timeout::Timeouts;
+use crate::utils::lru::LruCache;
+
+pub mod alpaca;
 pub mod auth;
+pub mod binance;
+pub mod budget;
+pub mod candle;
+pub mod chain;
 pub mod client;
+pub mod coinbase;
+pub mod convert;
+pub mod mock;
+pub mod movement;
+pub mod number;
+pub mod previous;
+pub mod priority;
+pub mod provider;
+pub mod quote;
+pub mod rate_limit;
+pub mod replay;
 pub mod request;
+pub mod snapshot;
+pub mod stream;
+pub mod subscription;
+pub mod volume;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+
+/// ## A source of real-time quotes
+///
+/// `XylexApi` is the built-in implementation, other feeds can be plugged into the
+/// trigger checker through [`XylexApi::check_alerts_with`].
+#[async_trait]
+pub trait PriceProvider: Send + Sync {
+    /// Fetches the current quote of a single symbol.
+    ///
+    /// # Errors
+    /// Returns a `XylexApiError` if the quote could not be fetched or parsed.
+    async fn fetch_price(&self, symbol: &str) -> Result<Quote, XylexApiError>;
+
+    /// Fetches the current quotes of several symbols, one quote per symbol.
+    ///
+    /// Fetches the symbols one by one unless the provider has a cheaper way.
+    ///
+    /// # Errors
+    /// Returns a `XylexApiError` if any of the quotes could not be fetched.
+    async fn fetch_prices(&self, symbols: &[&str]) -> Result<Vec<Quote>, XylexApiError> {
+        let mut quotes: Vec<Quote> = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            quotes.push(self.fetch_price(symbol).await?);
+        }
+        Ok(quotes)
+    }
+
+    /// The symbols this provider can quote, `None` if it accepts any symbol.
+    ///
+    /// # Errors
+    /// Returns a `XylexApiError` if the provider could not list its symbols.
+    async fn supported_symbols(&self) -> Result<Option<Vec<String>>, XylexApiError> {
+        Ok(None)
+    }
+
+    /// Searches the instruments matching `query`, e.g. for autocomplete when creating alerts.
+    ///
+    /// # Returns
+    /// The matching instruments, best matches first, or `None` if the provider can't search.
+    ///
+    /// # Errors
+    /// Returns a `XylexApiError` if the search request failed.
+    async fn search_symbols(&self, _query: &str) -> Result<Option<Vec<Instrument>>, XylexApiError> {
+        Ok(None)
+    }
+
+    /// Whether the quotes of `symbol` switched to another source recently, e.g. to a fallback provider.
+    ///
+    /// A crossing seen during such a transition may only be the gap between the two sources' prices,
+    /// so it triggers once the next poll confirms it, see [`ProviderChain::with_switch_suppression`].
+    fn in_failover_transition(&self, _symbol: &str) -> bool {
+        false
+    }
+
+    /// Bounds the caches the provider keeps, see [`crate::ResourceLimits`]. Providers without
+    /// caches ignore it.
+    fn set_resource_limits(&self, _limits: crate::ResourceLimits) {}
+}
+
+/// ## An instrument found by [`PriceProvider::search_symbols`]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Instrument {
+    /// The symbol to set alerts on, as the provider expects it.
+    pub symbol: String,
+    /// The full name, e.g. `Apple Inc`.
+    pub name: Option<String>,
+    pub exchange: Option<String>,
+    /// The kind of instrument, e.g. `Common Stock`, `Physical Currency` or `crypto`.
+    pub asset_type: Option<String>,
+    /// The currency the instrument is quoted in.
+    pub currency: Option<String>,
+}
+
+/// ## A primary price provider with fallbacks
+///
+/// Every symbol is fetched from the first provider answering in time, in the order the providers
+/// were added. If all of them fail, the last quote served within `cache_max_age` is returned
+/// instead. The source of the last quote of every symbol is kept, see [`ProviderChain::source`].
+/// Cloning a `ProviderChain` shares the cached quotes and sources.
+#[derive(Clone)]
+pub struct ProviderChain {
+    providers: Vec<(String, Arc<dyn PriceProvider>)>,
+    /// Longest wait for a provider before the next one is tried, `None` to wait for every provider.
+    pub timeout: Option<Duration>,
+    /// Oldest cached quote served when every provider failed, `None` to never serve cached quotes.
+    pub cache_max_age: Option<Duration>,
+    /// How long after a symbol switched source its crossings await confirmation, `None` to never suppress them.
+    pub switch_suppression: Option<Duration>,
+    /// Last quote and its source per symbol, keyed by canonical symbol.
+    served: Arc<Mutex<LruCache<String, ServedQuote>>>,
+}
+
+#[derive(Clone, Debug)]
+struct ServedQuote {
+    quote: Quote,
+    source: String,
+    fetched_at: std::time::Instant,
+    /// When the symbol was last served by another source than before.
+    switched_at: Option<std::time::Instant>,
+}
+
+/// ## How prices sent by a provider are read
+///
+/// Lenient by default: a price may be sent as a number or a string, in scientific notation and
+/// with thousands separators, e.g. `"1,234.5"`, `"1 234,5"` with a `,` decimal separator or
+/// `"1.2e-4"`. A lone `.` or `,` followed by exactly three digits, e.g. `"1,234"`, is rejected
+/// unless it is the configured thousands separator. Strict formats only accept numbers, and
+/// strings with the decimal separator and no grouping.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// The character separating the integer from the fractional part, `.` by default.
+    pub decimal_separator: char,
+    /// The character grouping the digits by thousands, if known. A lone `.` or `,` followed by
+    /// exactly three digits is only read as a thousands separator when it is this character.
+    pub thousands_separator: Option<char>,
+    /// Rejects prices sent with thousands separators.
+    pub strict: bool,
+}
 
 /// ## Xylex API authentication and fetching
+#[derive(Clone)]
 pub struct XylexApi {
     pub key: String,
     pub endpoint: String,
+    /// Endpoint serving historical candles, needed by indicator conditions.
+    pub history_endpoint: Option<String>,
+    /// Endpoint searching instruments, needed by [`PriceProvider::search_symbols`].
+    pub search_endpoint: Option<String>,
+    /// Optional credit accounting for the provider's request quota.
+    pub budget: Option<CreditBudget>,
+    /// Candles built from fetched prices, used by `EvaluateOn::CandleClose` alerts.
+    pub candles: CandleAggregator,
+    /// Completed weekly and monthly candles, used by `EvaluateOn::CandleClose` alerts on calendar timeframes.
+    pub period_candles: PeriodCandles,
+    /// Volumes seen on recent checks, used by `Condition::VolumeSpike` alerts.
+    pub volumes: VolumeHistory,
+    /// Prices seen on the previous check, used by `Condition::Band` alerts.
+    pub previous_prices: PreviousPrices,
+    /// Largest accepted difference between the quote time and the evaluation time before a warning is logged.
+    pub max_clock_skew: Option<Duration>,
+    /// Skips price alerts of symbols which did not move enough since their last evaluation.
+    pub movement_gate: Option<MovementGate>,
+    /// Largest number of symbols fetched in a single request, `None` to fetch symbols one by one.
+    pub batch_size: Option<usize>,
+    /// Largest number of requests in flight when symbols are fetched one by one.
+    pub concurrency: usize,
+    /// Client-side limit applied to every request, including retries.
+    pub rate_limit: Option<RateLimiter>,
+    /// Retries of failed price requests.
+    pub retry: RetryPolicy,
+    /// Connect and read timeouts of every request.
+    pub timeouts: Timeouts,
+    /// How prices sent as strings are read, see [`XylexApi::with_number_format`].
+    pub number_format: NumberFormat,
+    /// Longest time spent fetching quotes per cycle, the remaining symbols are deferred.
+    pub cycle_budget: Option<CycleBudget>,
+    /// Trading hours of the symbols, symbols of closed markets are not fetched.
+    pub market_hours: Option<MarketHours>,
+    /// Check intervals of the symbols, symbols which are not due are not fetched.
+    pub schedule: Option<crate::ScheduleConfig>,
+    /// Reports triggered alerts without recording their trigger, see [`XylexApi::with_dry_run`].
+    pub dry_run: bool,
+    /// Bounds the caches kept across checks, see [`XylexApi::with_resource_limits`].
+    pub resource_limits: crate::ResourceLimits,
+    /// Records or replays the price requests, see [`crate::replay_http`].
+    #[cfg(feature = "replay-http")]
+    pub replay: Option<crate::replay_http::HttpReplay>,
+    /// Set once the endpoint answered a batched request as if it does not support batching.
+    batch_unsupported: Arc<AtomicBool>,
+    /// Hashes of the alerts whose crossing during a provider switch awaits confirmation by the next check.
+    unconfirmed_crossings: Arc<Mutex<HashSet<String>>>,
+    /// When every alert was last evaluated, keyed by hash.
+    evaluated_at: Arc<Mutex<LruCache<String, DateTime<Utc>>>>,
+    /// The symbols this worker checks when sharded, see [`XylexApi::set_shard`].
+    shard: Arc<Mutex<Option<crate::shard::Shard>>>,
+}
+
+/// Quotes pushed by a `StreamingProvider`, in the order they were received.
+pub type QuoteStream = Pin<Box<dyn Stream<Item = Quote> + Send>>;
+
+/// ## A source pushing a quote on every tick
+///
+/// Used by [`XylexApi::watch_alerts`] to check alerts as soon as prices move instead of on
+/// a polling interval.
+#[async_trait]
+pub trait StreamingProvider: Send + Sync {
+    /// Subscribes to the quotes of `symbols`.
+    ///
+    /// The stream ends when the connection is closed.
+    ///
+    /// # Errors
+    /// Returns a `XylexApiError` if the subscription could not be set up.
+    async fn subscribe(&self, symbols: &[String]) -> Result<QuoteStream, XylexApiError>;
+}
+
+/// ## Keeps a `StreamingProvider` subscribed across dropped connections
+///
+/// Whenever the stream ends, the same symbols are subscribed again with exponential backoff.
+/// After every reconnect the quotes of all symbols are fetched from the snapshot provider, if
+/// set, so price moves during the outage are still evaluated.
+#[derive(Clone)]
+pub struct ReconnectingProvider {
+    inner: Arc<dyn StreamingProvider>,
+    /// REST provider fetching the quotes missed while disconnected.
+    snapshot: Option<Arc<dyn PriceProvider>>,
+    /// Backoff between reconnect attempts, the stream ends after `max_attempts` failed attempts in a row.
+    pub retry: RetryPolicy,
+}
+
+/// ## Keeps a streaming subscription in line with the symbols that have alerts
+///
+/// Streaming providers can't change the symbols of a running subscription, so whenever the
+/// symbols with alerts change the provider is subscribed again with the new set. Used by
+/// [`XylexApi::watch_subscribed`], which reconciles on `reconcile_interval` and whenever
+/// [`SubscriptionManager::refresh`] is called. Cloning a `SubscriptionManager` shares its state.
+#[derive(Clone)]
+pub struct SubscriptionManager {
+    provider: Arc<dyn StreamingProvider>,
+    /// Time between two reconciliations with the alerts table, one minute by default.
+    pub reconcile_interval: Duration,
+    /// Subscribed symbols as first written, keyed by their canonical form.
+    subscribed: Arc<Mutex<BTreeMap<String, String>>>,
+    refresh: Arc<tokio::sync::Notify>,
+}
+
+/// ## A new subscription made by a `SubscriptionManager`
+pub struct SubscriptionChange {
+    /// Symbols which were not subscribed before.
+    pub added: Vec<String>,
+    /// Symbols which are no longer subscribed.
+    pub removed: Vec<String>,
+    /// Quotes of every subscribed symbol, replacing the previous stream.
+    pub quotes: QuoteStream,
+}
+
+/// ## The last streamed quote per symbol
+/// Serves as the `PriceProvider` of checks driven by a `StreamingProvider`, and only
+/// supports the symbols it received a quote for. Cloning a `LatestQuotes` shares the quotes.
+#[derive(Clone, Debug, Default)]
+pub struct LatestQuotes {
+    quotes: Arc<Mutex<LruCache<String, Quote>>>,
+}
+
+/// ## Simulated prices for tests and dry runs
+/// Serves prices from memory, without network or API keys: a fixed price per symbol or a
+/// scripted sequence advancing one tick per fetch, e.g. a ramp from 1.08 to 1.12.
+/// Cloning a `MockProvider` shares the prices, so a test can move them while alerts are checked.
+#[derive(Clone, Debug, Default)]
+pub struct MockProvider {
+    prices: Arc<Mutex<HashMap<String, MockPrices>>>,
+}
+
+/// The remaining ticks of a symbol, the last one holds once the script ends.
+#[derive(Clone, Debug)]
+struct MockPrices {
+    symbol: String,
+    ticks: VecDeque<f64>,
+}
+
+/// ## Historical prices replayed as a live feed
+/// Replays the ticks of a CSV or, with the `parquet` feature, a Parquet file, so alert
+/// configurations can be validated against past price action before going live.
+///
+/// The replay clock starts at the first tick on the first fetch or subscription and runs
+/// `speed` times faster than real time. Cloning a `ReplayProvider` shares the clock.
+#[derive(Clone, Debug)]
+pub struct ReplayProvider {
+    /// The ticks, oldest first, every quote carries a timestamp.
+    ticks: Arc<Vec<Quote>>,
+    /// The indices of each symbol's ticks, oldest first, keyed by canonical symbol.
+    by_symbol: Arc<HashMap<String, Vec<usize>>>,
+    /// How many times faster than real time prices are replayed, e.g. `60.0` replays an hour per minute.
+    pub speed: f64,
+    started: Arc<Mutex<Option<std::time::Instant>>>,
+}
+
+/// ## Quotes pushed over a WebSocket
+/// Every text message holds a quote, or a list of quotes, with the same fields as the
+/// REST endpoint: `symbol`, `price` and optionally `bid`, `ask`, `volume` and `timestamp`.
+#[cfg(feature = "websocket")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebSocketProvider {
+    pub url: String,
+    /// Message sent after connecting, `{symbols}` is replaced by a JSON array of the symbols.
+    pub subscribe_message: Option<String>,
+}
+
+/// ## Binance spot prices from the public REST API
+/// Needs no API key. Symbols such as `BTC/USDT`, `btc-usdt` and `BTCUSDT` are all
+/// accepted, the returned quotes keep the symbol as requested.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BinanceProvider {
+    pub endpoint: String,
+    /// Whether quotes are taken from the 24h ticker, which adds the bid, ask and volume.
+    pub include_24h_stats: bool,
+    /// Records or replays the ticker requests, see [`crate::replay_http`].
+    #[cfg(feature = "replay-http")]
+    pub replay: Option<crate::replay_http::HttpReplay>,
+}
+
+/// ## Market data plan of an Alpaca account
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AlpacaPlan {
+    /// The free plan: IEX prices, 200 requests per minute.
+    #[default]
+    Basic,
+    /// The paid plan: consolidated SIP prices, 10,000 requests per minute.
+    AlgoTraderPlus,
+}
+
+/// ## US equity prices from Alpaca's market data API
+/// Quotes snapshots of tickers such as `AAPL` or `BRK.B`, keeping to the rate limit of the plan.
+/// `Debug` output never contains the API keys.
+#[derive(Clone)]
+pub struct AlpacaProvider {
+    pub key_id: String,
+    pub secret_key: String,
+    pub endpoint: String,
+    pub plan: AlpacaPlan,
+    /// Whether pre- and post-market trades are quoted, otherwise the last regular session close is.
+    pub extended_hours: bool,
+    /// Client-side limit shared by every request, set from the plan.
+    pub rate_limit: RateLimiter,
+    /// Records or replays the market data requests, see [`crate::replay_http`].
+    #[cfg(feature = "replay-http")]
+    pub replay: Option<crate::replay_http::HttpReplay>,
+}
+
+/// ## Coinbase spot prices from the public market API
+/// Needs no API key. Symbols such as `BTC/USD`, `btc-usd` and `BTCUSD` are all
+/// accepted, the returned quotes keep the symbol as requested.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoinbaseProvider {
+    pub endpoint: String,
+    /// Number of products requested per page when listing products.
+    pub page_size: usize,
+    /// Records or replays the market requests, see [`crate::replay_http`].
+    #[cfg(feature = "replay-http")]
+    pub replay: Option<crate::replay_http::HttpReplay>,
+}
+
+/// ## Real-time quote for a symbol
+/// Holds the last traded (or mid) price and, when the provider supplies them,
+/// the best bid and ask so the spread can be derived, and the traded volume.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quote {
+    pub symbol: String,
+    pub price: f64,
+    pub bid: Option<f64>,
+    pub ask: Option<f64>,
+    pub volume: Option<f64>,
+    /// Average volume over recent checks, filled in by the trigger checker.
+    pub average_volume: Option<f64>,
+    /// When the provider quoted the price, if it says so.
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// ## Skips re-evaluating symbols whose price barely moved
+/// Prices are compared against the price of the last evaluation, so slow drifts still
+/// add up, and alerts whose level lies between the two prices are evaluated regardless.
+/// Cloning a `MovementGate` shares the recorded prices.
+///
+/// The recorded prices are part of the price state store: they are written to the price
+/// snapshot file after every cycle and seeded from it on startup, see
+/// [`crate::TradeAlertsBuilder::with_price_snapshot_file`]. Without a snapshot file they are
+/// only kept in memory, and the first price of every symbol after a restart is evaluated.
+#[derive(Clone, Debug, Default)]
+pub struct MovementGate {
+    /// Minimum move in percent since the last evaluation.
+    pub min_move_percent: f64,
+    /// Overrides of `min_move_percent` per symbol.
+    pub per_symbol: HashMap<String, f64>,
+    last_evaluated: Arc<Mutex<LruCache<String, f64>>>,
+}
+
+/// ## Rolling volume history per symbol
+/// Cloning a `VolumeHistory` shares the recorded samples.
+#[derive(Clone, Debug)]
+pub struct VolumeHistory {
+    /// Number of samples the average is taken over.
+    pub window: usize,
+    samples: Arc<Mutex<LruCache<String, VecDeque<f64>>>>,
+}
+
+/// ## The price of every symbol at the previous check
+/// Used by `Condition::Band` alerts to detect the price entering or leaving a band.
+/// Cloning `PreviousPrices` shares the recorded prices.
+#[derive(Clone, Debug, Default)]
+pub struct PreviousPrices {
+    prices: Arc<Mutex<LruCache<String, f64>>>,
+}
+
+/// ## The price state of a previous run
+/// Seeds the previous prices and the movement gate on startup, so band crossings and the
+/// gate work from the first cycle instead of after a warm-up cycle, see [`XylexApi::seed_prices`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PriceSnapshot {
+    /// When the prices were recorded.
+    pub taken_at: DateTime<Utc>,
+    /// The price of every symbol at the last check.
+    pub prices: BTreeMap<String, f64>,
+    /// The price every symbol was last evaluated at by the movement gate.
+    pub evaluated: BTreeMap<String, f64>,
+}
+
+/// ## Everything observed during a single check cycle
+#[derive(Clone, Debug)]
+pub struct CheckReport {
+    /// The alert rows fetched from the database.
+    pub alerts: Vec<HashMap<String, Value>>,
+    /// The quotes fetched for this cycle.
+    pub quotes: Vec<Quote>,
+    /// The alerts which triggered.
+    pub triggered: Vec<TriggeredAlert>,
+    /// How long the cycle took.
+    pub duration: Duration,
+    /// Symbols not fetched because the `CycleBudget` ran out, fetched first next cycle.
+    pub deferred: Vec<String>,
+}
+
+/// ## Time budget of a check cycle
+///
+/// Symbols are fetched in chunks in priority order: symbols deferred by the previous cycle
+/// first, then the symbols with alerts of the highest priority users, then the symbols whose
+/// nearest alert is closest to the price at the previous check. Once `time_budget` has passed
+/// the remaining symbols are deferred to the next cycle, so important alerts keep a bounded
+/// latency when there are too many symbols. Cloning a `CycleBudget` shares its stats.
+#[derive(Clone, Debug)]
+pub struct CycleBudget {
+    /// Time after which no further chunk is fetched, counted from the start of the cycle.
+    pub time_budget: Duration,
+    /// Symbols fetched per request.
+    pub chunk_size: usize,
+    /// Priority of every user, higher first, users not listed have priority `0`.
+    pub user_priorities: HashMap<String, i64>,
+    stats: Arc<Mutex<DeferralStats>>,
+}
+
+/// ## Work deferred by a `CycleBudget`
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct DeferralStats {
+    /// Cycles run with the budget.
+    pub cycles: u64,
+    /// Cycles which deferred at least one symbol.
+    pub over_budget_cycles: u64,
+    /// Symbols deferred over all cycles.
+    pub deferred_symbols: u64,
+    /// Symbols deferred by the last cycle.
+    pub last_deferred: Vec<String>,
+}
+
+/// ## OHLC candle for a symbol and timeframe
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candle {
+    pub symbol: String,
+    pub timeframe: Timeframe,
+    /// Start of the candle (UTC).
+    pub open_time: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+/// ## Aggregates fetched prices into candles
+/// Candles are only as precise as the polling interval, the first candle after startup
+/// is partial. Cloning a `CandleAggregator` shares the open candles.
+#[derive(Clone, Debug, Default)]
+pub struct CandleAggregator {
+    open: Arc<Mutex<LruCache<(String, Timeframe), Candle>>>,
+}
+
+/// ## The last completed weekly and monthly candles
+/// Fetched from the history endpoint once per period, see [`XylexApi::completed_candle`].
+/// Cloning a `PeriodCandles` shares the cache.
+#[derive(Clone, Debug, Default)]
+pub struct PeriodCandles {
+    completed: Arc<Mutex<LruCache<(String, Timeframe), Candle>>>,
+}
+
+/// ## Credit accounting for a provider's monthly request quota
+/// Cloning a `CreditBudget` shares the underlying spend counter.
+#[derive(Clone, Debug)]
+pub struct CreditBudget {
+    /// Credits charged by the provider for a single request.
+    pub cost_per_request: f64,
+    /// Credits available per calendar month (UTC).
+    pub monthly_budget: f64,
+    /// What to do when the spend approaches the budget.
+    pub policy: BudgetPolicy,
+    usage: Arc<Mutex<CreditUsage>>,
+}
+
+/// ## Policies applied when approaching the monthly budget
+#[derive(Clone, Debug, PartialEq)]
+pub struct BudgetPolicy {
+    /// Fraction of the budget after which requests are delayed.
+    pub slow_down_at: f64,
+    /// Delay added before every request while slowing down.
+    pub slow_down_delay: Duration,
+    /// Fraction of the budget after which only the symbols closest to triggering are fetched.
+    pub prioritize_at: f64,
+}
+
+/// ## Current state of a budget relative to its policy
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BudgetState {
+    /// Spend is below every policy threshold.
+    Normal,
+    /// Requests are delayed to stretch the remaining credits.
+    SlowDown,
+    /// Only symbols with alerts close to triggering are fetched.
+    Prioritize,
+    /// No credits are left for this month.
+    Exhausted,
+}
+
+/// ## Snapshot of the spend for the current month
+#[derive(Clone, Debug, PartialEq)]
+pub struct BudgetMetrics {
+    /// Year and month (UTC) the snapshot belongs to.
+    pub period: (i32, u32),
+    /// Number of requests charged this month.
+    pub requests: u64,
+    /// Credits spent this month.
+    pub spent: f64,
+    /// Credits left this month.
+    pub remaining: f64,
+    /// State of the budget at the time of the snapshot.
+    pub state: BudgetState,
+}
+
+/// ## Client-side rate limit shared by every request of a client
+/// A token bucket refilled at `requests_per_second`, holding up to `burst` tokens.
+/// Cloning a `RateLimiter` shares the bucket, so concurrent fetches are limited together.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    pub requests_per_second: f64,
+    /// Requests allowed back to back after the limiter was idle.
+    pub burst: f64,
+    bucket: Arc<Mutex<TokenBucket>>,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    updated: tokio::time::Instant,
+}
+
+#[derive(Debug, Default)]
+struct CreditUsage {
+    period: (i32, u32),
+    requests: u64,
+    spent: f64,
 }
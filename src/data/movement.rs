@@ -0,0 +1,75 @@
+//! ## Change detection
+//! Decides whether a symbol moved enough since its last evaluation to be worth re-evaluating.
+
+use crate::data::MovementGate;
+use crate::utils::lru::{CacheStats, LruCache};
+
+impl MovementGate {
+    /// Creates a `MovementGate` requiring a move of `min_move_percent` percent for every symbol.
+    pub fn new(min_move_percent: f64) -> Self {
+        Self { min_move_percent, ..Self::default() }
+    }
+
+    /// Overrides the minimum move of a single symbol, e.g. a wider one for volatile crypto pairs.
+    pub fn with_symbol(
+        mut self,
+        symbol: &str,
+        min_move_percent: f64
+    ) -> Self {
+        self.per_symbol.insert(symbol.to_string(), min_move_percent);
+        self
+    }
+
+    /// Returns `true` if the symbol's alerts should be evaluated at `price`, and records it as the last evaluated price.
+    ///
+    /// The first price of a symbol is always evaluated. Prices which did not move enough are
+    /// not recorded, so moves smaller than the minimum still add up over several checks.
+    pub fn should_evaluate(
+        &self,
+        symbol: &str,
+        price: f64
+    ) -> bool {
+        let min_move_percent: f64 = self.per_symbol.get(symbol).copied().unwrap_or(self.min_move_percent);
+        let mut last_evaluated: std::sync::MutexGuard<'_, LruCache<String, f64>> =
+            self.last_evaluated.lock().unwrap_or_else(|e| e.into_inner());
+
+        let moved: bool = match last_evaluated.get(&symbol.to_string()) {
+            Some(last) if *last != 0.0 => ((price - last) / last).abs() * 100.0 >= min_move_percent,
+            _ => true,
+        };
+        if moved {
+            last_evaluated.insert(symbol.to_string(), price);
+        }
+
+        moved
+    }
+
+    /// Returns `true` if a level lies between the price the symbol was last evaluated at and
+    /// `price`, both inclusive, so a small move across a level is still evaluated.
+    ///
+    /// Symbols without a recorded price are always evaluated by `should_evaluate`, they never
+    /// cross a level here.
+    pub fn crossed(
+        &self,
+        symbol: &str,
+        price: f64,
+        levels: &[f64]
+    ) -> bool {
+        let last_evaluated = self.last_evaluated.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(last) = last_evaluated.peek(&symbol.to_string()).copied() else {
+            return false;
+        };
+        let (low, high): (f64, f64) = (last.min(price), last.max(price));
+        levels.iter().any(|level| (low..=high).contains(level))
+    }
+
+    /// Bounds the symbols with a recorded price, see [`crate::ResourceLimits`].
+    pub fn set_capacity(&self, capacity: Option<usize>) {
+        self.last_evaluated.lock().unwrap_or_else(|e| e.into_inner()).set_capacity(capacity);
+    }
+
+    /// Returns the occupancy of the recorded prices.
+    pub fn stats(&self) -> CacheStats {
+        self.last_evaluated.lock().unwrap_or_else(|e| e.into_inner()).stats()
+    }
+}
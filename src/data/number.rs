@@ -0,0 +1,130 @@
+//! ## Reading prices sent with locale quirks
+//!
+//! Providers send prices as strings, as TwelveData does, but some send numbers, scientific
+//! notation or group the digits by thousands. [`NumberFormat`] reads all of them unless it is
+//! strict, see [`crate::data::XylexApi::with_number_format`].
+//!
+//! A value like `"1,234"` reads as `1234` with a thousands comma and as `1.234` with a decimal
+//! comma, so it is rejected unless the thousands separator is configured.
+
+use serde_json::Value;
+
+use crate::data::NumberFormat;
+
+/// Characters grouping the digits of the integer part, besides `.` and `,`.
+const GROUP_SEPARATORS: [char; 5] = [' ', '\u{a0}', '\u{202f}', '\'', '_'];
+
+impl Default for NumberFormat {
+    /// Creates a lenient `NumberFormat` with a `.` decimal separator.
+    fn default() -> Self {
+        Self {
+            decimal_separator: '.',
+            thousands_separator: None,
+            strict: false,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Creates a lenient `NumberFormat` with a `.` decimal separator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the character separating the integer from the fractional part, e.g. `,` for `"1.234,5"`.
+    pub fn with_decimal_separator(
+        mut self,
+        decimal_separator: char
+    ) -> Self {
+        self.decimal_separator = decimal_separator;
+        self
+    }
+
+    /// Sets the character grouping the digits by thousands, e.g. `,` for `"1,234"`, so a lone
+    /// `.` or `,` followed by exactly three digits is no longer ambiguous.
+    pub fn with_thousands_separator(
+        mut self,
+        thousands_separator: char
+    ) -> Self {
+        self.thousands_separator = Some(thousands_separator);
+        self
+    }
+
+    /// Only accepts numbers, and strings with the decimal separator and without thousands separators.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Reads a price, either a number or a string.
+    ///
+    /// # Errors
+    /// Returns why the value was rejected, e.g. a misplaced thousands separator or a value
+    /// which is not finite.
+    pub fn parse(
+        &self,
+        value: &Value
+    ) -> Result<f64, String> {
+        let price: f64 = match value {
+            Value::String(s) => self.parse_str(s)?,
+            Value::Number(n) => n.as_f64().ok_or("not representable as a float")?,
+            Value::Null => return Err("missing".to_string()),
+            _ => return Err("neither a number nor a string".to_string()),
+        };
+
+        match price.is_finite() {
+            true => Ok(price),
+            false => Err("not a finite number".to_string()),
+        }
+    }
+
+    /// Reads a price sent as a string.
+    fn parse_str(
+        &self,
+        raw: &str
+    ) -> Result<f64, String> {
+        let raw: &str = raw.trim();
+        let (mantissa, exponent) = match raw.find(['e', 'E']) {
+            Some(index) => raw.split_at(index),
+            None => (raw, ""),
+        };
+        let (integer, fraction) = match mantissa.split_once(self.decimal_separator) {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (mantissa, None),
+        };
+        let (sign, integer) = match integer.strip_prefix(['-', '+']) {
+            Some(digits) => (&integer[..1], digits),
+            None => ("", integer),
+        };
+
+        let is_separator = |c: char| c != self.decimal_separator && (c == '.' || c == ',' || GROUP_SEPARATORS.contains(&c));
+        if fraction.is_some_and(|fraction| fraction.contains(is_separator)) {
+            return Err("thousands separator after the decimal separator".to_string());
+        }
+        let groups: Vec<&str> = integer.split(is_separator).collect();
+        if groups.len() > 1 {
+            if self.strict {
+                return Err("thousands separators are rejected in strict mode".to_string());
+            }
+            let first: usize = groups[0].len();
+            if !(1..=3).contains(&first) || groups[1..].iter().any(|group| group.len() != 3) {
+                return Err("misplaced thousands separator".to_string());
+            }
+            // `1,234` is `1234` or `1.234` depending on the locale, only the configuration tells
+            let separator: Option<char> = integer.chars().find(|c| is_separator(*c));
+            let ambiguous: bool = groups.len() == 2 && fraction.is_none() && matches!(separator, Some('.' | ','));
+            if ambiguous && separator != self.thousands_separator {
+                return Err("ambiguous separator, configure the thousands separator".to_string());
+            }
+        }
+
+        let normalized: String = match fraction {
+            Some(fraction) => format!("{}{}.{}{}", sign, groups.concat(), fraction, exponent),
+            None => format!("{}{}{}", sign, groups.concat(), exponent),
+        };
+        if !normalized.trim_start_matches(['-', '+']).starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            return Err("not a number".to_string());
+        }
+        normalized.parse().map_err(|_| "not a number".to_string())
+    }
+}
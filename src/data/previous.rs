@@ -0,0 +1,41 @@
+//! ## Previous prices
+//! Remembers the price of every symbol at the previous check, so band alerts can
+//! tell whether the price entered or left their band in between.
+
+use crate::data::PreviousPrices;
+use crate::utils::lru::CacheStats;
+
+impl PreviousPrices {
+    /// Records the latest price of a symbol.
+    ///
+    /// # Returns
+    /// Returns the price recorded by the previous call for the symbol, `None` the first time.
+    pub fn record(
+        &self,
+        symbol: &str,
+        price: f64
+    ) -> Option<f64> {
+        self.prices
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(symbol.to_string(), price)
+    }
+
+    /// Returns the last recorded price of a symbol.
+    pub fn get(
+        &self,
+        symbol: &str
+    ) -> Option<f64> {
+        self.prices.lock().unwrap_or_else(|e| e.into_inner()).peek(&symbol.to_string()).copied()
+    }
+
+    /// Bounds the symbols with a recorded price, see [`crate::ResourceLimits`].
+    pub fn set_capacity(&self, capacity: Option<usize>) {
+        self.prices.lock().unwrap_or_else(|e| e.into_inner()).set_capacity(capacity);
+    }
+
+    /// Returns the occupancy of the recorded prices.
+    pub fn stats(&self) -> CacheStats {
+        self.prices.lock().unwrap_or_else(|e| e.into_inner()).stats()
+    }
+}
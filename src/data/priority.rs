@@ -0,0 +1,180 @@
+//! ## Evaluation under time pressure
+//! Orders the symbols of a cycle by priority and defers the tail once the cycle's time budget runs out.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::data::request::DEFAULT_CONCURRENCY;
+use crate::data::{CycleBudget, DeferralStats, PreviousPrices, PriceProvider, Quote, XylexApi};
+use crate::db::TableConfig;
+use crate::errors::XylexApiError;
+use crate::utils::symbol::same_symbol;
+
+impl CycleBudget {
+    /// Creates a budget of `time_budget` per cycle, fetching `DEFAULT_CONCURRENCY` symbols per request.
+    pub fn new(time_budget: Duration) -> Self {
+        Self {
+            time_budget,
+            chunk_size: DEFAULT_CONCURRENCY,
+            user_priorities: HashMap::new(),
+            stats: Arc::new(Mutex::new(DeferralStats::default())),
+        }
+    }
+
+    /// Sets the number of symbols fetched per request, at least `1`.
+    pub fn with_chunk_size(
+        mut self,
+        chunk_size: usize
+    ) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Sets the priority of a user's alerts, higher first.
+    pub fn with_user_priority(
+        mut self,
+        user_id: impl Into<String>,
+        priority: i64
+    ) -> Self {
+        self.user_priorities.insert(user_id.into(), priority);
+        self
+    }
+
+    /// Returns the work deferred so far.
+    pub fn stats(&self) -> DeferralStats {
+        self.lock().clone()
+    }
+
+    /// Orders `symbols` by priority, most important first.
+    ///
+    /// # Arguments
+    /// * `symbols` - The symbols to fetch this cycle.
+    /// * `alerts` - The alert rows, as returned by `Supabase::fetch_all_data`.
+    /// * `config` - A reference to a `TableConfig` with the column names of the alert rows.
+    /// * `previous_prices` - The prices of the previous check, used to find the nearest alerts.
+    pub fn order<'a>(
+        &self,
+        mut symbols: Vec<&'a str>,
+        alerts: &[HashMap<String, Value>],
+        config: &TableConfig,
+        previous_prices: &PreviousPrices,
+    ) -> Vec<&'a str> {
+        let mut priorities: HashMap<&str, i64> = HashMap::new();
+        let mut distances: HashMap<&str, f64> = HashMap::new();
+        for alert in alerts {
+            let Some(symbol) = alert.get(&config.symbol_column_name).and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let priority: i64 = alert
+                .get(&config.user_id_column_name)
+                .and_then(|v| v.as_str())
+                .and_then(|user_id| self.user_priorities.get(user_id))
+                .copied()
+                .unwrap_or(0);
+            let highest = priorities.entry(symbol).or_insert(priority);
+            *highest = (*highest).max(priority);
+
+            if let (Some(level), Some(previous)) = (
+                alert.get(&config.price_level_column_name).and_then(|v| v.as_f64()),
+                previous_prices.get(symbol),
+            ) {
+                if level != 0.0 {
+                    let nearest = distances.entry(symbol).or_insert(f64::INFINITY);
+                    *nearest = nearest.min(((previous - level) / level).abs());
+                }
+            }
+        }
+
+        let last_deferred: Vec<String> = self.lock().last_deferred.clone();
+        let was_deferred = |symbol: &str| last_deferred.iter().any(|deferred| same_symbol(deferred, symbol));
+        symbols.sort_by(|a, b| {
+            was_deferred(b)
+                .cmp(&was_deferred(a))
+                .then_with(|| priorities.get(b).unwrap_or(&0).cmp(priorities.get(a).unwrap_or(&0)))
+                .then_with(|| {
+                    let a = distances.get(a).copied().unwrap_or(f64::INFINITY);
+                    let b = distances.get(b).copied().unwrap_or(f64::INFINITY);
+                    a.total_cmp(&b)
+                })
+        });
+        symbols
+    }
+
+    fn record(&self, deferred: &[String]) {
+        let mut stats = self.lock();
+        stats.cycles += 1;
+        if !deferred.is_empty() {
+            stats.over_budget_cycles += 1;
+            stats.deferred_symbols += deferred.len() as u64;
+        }
+        stats.last_deferred = deferred.to_vec();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, DeferralStats> {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl XylexApi {
+    /// Limits the time spent fetching quotes per cycle, see [`CycleBudget`].
+    ///
+    /// # Arguments
+    /// * `cycle_budget` - The `CycleBudget` to apply.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the budget applied.
+    pub fn with_cycle_budget(
+        mut self,
+        cycle_budget: CycleBudget
+    ) -> Self {
+        self.cycle_budget = Some(cycle_budget);
+        self
+    }
+
+    /// Fetches the quotes of `symbols` from `provider`, in priority order within the `CycleBudget` if any.
+    ///
+    /// # Arguments
+    /// * `provider` - The feed quotes are fetched from.
+    /// * `symbols` - The symbols to fetch.
+    /// * `alerts` - The alert rows, as returned by `Supabase::fetch_all_data`.
+    /// * `config` - A reference to a `TableConfig` with the column names of the alert rows.
+    /// * `started` - The start of the cycle, the budget counts from there.
+    ///
+    /// # Returns
+    /// The fetched quotes and the deferred symbols.
+    ///
+    /// # Errors
+    /// Returns a `XylexApiError` if a chunk could not be fetched.
+    pub async fn fetch_within_budget<P: PriceProvider + ?Sized>(
+        &self,
+        provider: &P,
+        symbols: Vec<&str>,
+        alerts: &[HashMap<String, Value>],
+        config: &TableConfig,
+        started: Instant,
+    ) -> Result<(Vec<Quote>, Vec<String>), XylexApiError> {
+        let Some(budget) = &self.cycle_budget else {
+            return Ok((provider.fetch_prices(&symbols).await?, Vec::new()));
+        };
+
+        let ordered: Vec<&str> = budget.order(symbols, alerts, config, &self.previous_prices);
+        let mut quotes: Vec<Quote> = Vec::with_capacity(ordered.len());
+        let mut deferred: Vec<String> = Vec::new();
+        for chunk in ordered.chunks(budget.chunk_size.max(1)) {
+            if !deferred.is_empty() || started.elapsed() >= budget.time_budget {
+                deferred.extend(chunk.iter().map(|symbol| symbol.to_string()));
+                continue;
+            }
+            quotes.extend(provider.fetch_prices(chunk).await?);
+        }
+
+        budget.record(&deferred);
+        if !deferred.is_empty() {
+            println!("Cycle budget of {:?} exceeded, deferring symbols: {:?}", budget.time_budget, deferred);
+        }
+        Ok((quotes, deferred))
+    }
+}
@@ -0,0 +1,27 @@
+//! ## Price providers
+//! `XylexApi` as a `PriceProvider`, so it can be swapped for any other feed.
+
+use async_trait::async_trait;
+
+use crate::data::{Instrument, PriceProvider, Quote, XylexApi};
+use crate::errors::XylexApiError;
+
+#[async_trait]
+impl PriceProvider for XylexApi {
+    async fn fetch_price(&self, symbol: &str) -> Result<Quote, XylexApiError> {
+        self.request_real_time_quote(symbol).await
+    }
+
+    async fn fetch_prices(&self, symbols: &[&str]) -> Result<Vec<Quote>, XylexApiError> {
+        let symbols: Vec<&str> = symbols.to_vec();
+        self.fetch_quotes_for_symbols(symbols).await
+    }
+
+    /// Searches through the search endpoint, `None` if it is not set.
+    async fn search_symbols(&self, query: &str) -> Result<Option<Vec<Instrument>>, XylexApiError> {
+        match self.search_endpoint {
+            Some(_) => self.request_symbol_search(query).await.map(Some),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! ## Quote helpers
+//! Spread and pip calculations on top of the raw `Quote` returned by the data feeds.
+
+use std::collections::HashMap;
+
+use crate::data::Quote;
+use crate::utils::symbol::canonical;
+
+impl Quote {
+    /// Creates a new `Quote` with only a price and no bid/ask, volume or timestamp information.
+    ///
+    /// # Arguments
+    /// * `symbol` - The symbol the quote belongs to.
+    /// * `price` - The last traded (or mid) price.
+    pub fn new(
+        symbol: String,
+        price: f64
+    ) -> Self {
+        Self { symbol, price, bid: None, ask: None, volume: None, average_volume: None, timestamp: None }
+    }
+
+    /// Returns the raw spread (`ask - bid`) if both sides of the book are known.
+    pub fn spread(&self) -> Option<f64> {
+        match (self.bid, self.ask) {
+            (Some(bid), Some(ask)) => Some(ask - bid),
+            _ => None,
+        }
+    }
+
+    /// Returns the size of a single pip for this quote's symbol.
+    ///
+    /// JPY-quoted pairs use `0.01`, every other symbol uses `0.0001`.
+    pub fn pip_size(&self) -> f64 {
+        if self.symbol.to_lowercase().contains("jpy") {
+            0.01
+        } else {
+            0.0001
+        }
+    }
+
+    /// Returns the spread expressed in pips if both sides of the book are known.
+    pub fn spread_pips(&self) -> Option<f64> {
+        self.spread().map(|spread| spread / self.pip_size())
+    }
+}
+
+/// Finds the quote of a symbol in any of its spellings during a check cycle.
+///
+/// Each spelling is canonicalized once, later lookups borrow it from the alert rows.
+pub(crate) struct QuoteIndex<'a> {
+    by_symbol: HashMap<String, &'a Quote>,
+    by_spelling: HashMap<&'a str, Option<&'a Quote>>,
+}
+
+impl<'a> QuoteIndex<'a> {
+    /// Indexes quotes of distinct symbols, the first quote wins if a symbol is quoted twice.
+    pub(crate) fn new(quotes: &'a [Quote]) -> Self {
+        let mut by_symbol: HashMap<String, &'a Quote> = HashMap::with_capacity(quotes.len());
+        for quote in quotes {
+            by_symbol.entry(canonical(&quote.symbol)).or_insert(quote);
+        }
+        Self { by_symbol, by_spelling: HashMap::new() }
+    }
+
+    /// Returns the quote of `symbol`, e.g. the `EUR/USD` quote for `eurusd`.
+    pub(crate) fn get(&mut self, symbol: &'a str) -> Option<&'a Quote> {
+        let by_symbol = &self.by_symbol;
+        *self
+            .by_spelling
+            .entry(symbol)
+            .or_insert_with(|| by_symbol.get(&canonical(symbol)).copied())
+    }
+}
@@ -0,0 +1,69 @@
+//! ## Client-side rate limiting
+//! A token bucket in front of the data API, so large symbol sets don't exceed the
+//! provider's request rate and get the API key banned.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::data::{RateLimiter, TokenBucket};
+
+/// Longest a single request waits for the limiter, whatever its rate.
+pub const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60 * 60);
+
+impl RateLimiter {
+    /// Creates a limiter allowing `requests_per_second` requests, in bursts of up to one second of requests.
+    ///
+    /// A rate which is not positive or not finite is clamped to the nearest valid rate, a rate of
+    /// zero makes every request after the burst wait `MAX_RATE_LIMIT_WAIT`. Validate rates read
+    /// from configuration before, as `XylexApi::new_env` does for `XYLEX_RATE_LIMIT`.
+    ///
+    /// # Arguments
+    /// * `requests_per_second` - The sustained request rate, e.g. `8.0` for a plan allowing 8 requests per second.
+    pub fn new(requests_per_second: f64) -> Self {
+        let requests_per_second: f64 = match requests_per_second.is_nan() {
+            true => f64::MIN_POSITIVE,
+            false => requests_per_second.clamp(f64::MIN_POSITIVE, f64::MAX),
+        };
+        let burst: f64 = requests_per_second.max(1.0);
+        Self {
+            requests_per_second,
+            burst,
+            bucket: Arc::new(Mutex::new(TokenBucket { tokens: burst, updated: Instant::now() })),
+        }
+    }
+
+    /// Sets the number of requests allowed back to back, at least `1`.
+    pub fn with_burst(
+        mut self,
+        burst: f64
+    ) -> Self {
+        self.burst = burst.max(1.0);
+        self.bucket = Arc::new(Mutex::new(TokenBucket { tokens: self.burst, updated: Instant::now() }));
+        self
+    }
+
+    /// Waits until a request may be sent.
+    ///
+    /// Waiting callers reserve their token up front, so they are served in the order they arrived.
+    pub async fn acquire(&self) {
+        let wait: Duration = {
+            let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+            let now: Instant = Instant::now();
+            let refill: f64 = now.duration_since(bucket.updated).as_secs_f64() * self.requests_per_second;
+            bucket.tokens = (bucket.tokens + refill).min(self.burst) - 1.0;
+            bucket.updated = now;
+
+            if bucket.tokens >= 0.0 {
+                Duration::ZERO
+            } else {
+                Duration::try_from_secs_f64(-bucket.tokens / self.requests_per_second)
+                    .map_or(MAX_RATE_LIMIT_WAIT, |wait| wait.min(MAX_RATE_LIMIT_WAIT))
+            }
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
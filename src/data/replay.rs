@@ -0,0 +1,332 @@
+//! ## Replay provider
+//! Loads historical ticks from CSV or Parquet files and serves them through the polling and
+//! streaming provider traits at a configurable speed.
+//!
+//! Files need a header naming the columns, in any order and case:
+//!
+//! | Column                 | Aliases                    | Content                                               |
+//! |------------------------|----------------------------|-------------------------------------------------------|
+//! | `timestamp`            | `time`, `date`, `datetime` | RFC 3339, `YYYY-MM-DD HH:MM:SS` or Unix (milli)seconds |
+//! | `symbol`               | `ticker`                   | The symbol, in any spelling                           |
+//! | `price`                | `close`, `last`            | The traded price                                      |
+//! | `bid`, `ask`, `volume` |                            | Optional                                              |
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+use futures_util::{StreamExt, stream};
+use serde_json::Value;
+
+use crate::data::{PriceProvider, Quote, QuoteStream, ReplayProvider, StreamingProvider};
+use crate::data::request::{parse_optional_number, parse_timestamp};
+use crate::errors::XylexApiError;
+use crate::utils::symbol::canonical;
+
+const TIMESTAMP_COLUMNS: &[&str] = &["timestamp", "time", "date", "datetime"];
+const SYMBOL_COLUMNS: &[&str] = &["symbol", "ticker"];
+const PRICE_COLUMNS: &[&str] = &["price", "close", "last"];
+
+impl ReplayProvider {
+    /// Creates a `ReplayProvider` replaying `ticks` in real time, sorted oldest first.
+    ///
+    /// # Errors
+    /// Returns `XylexApiError::ConfigurationError` if there are no ticks or a tick has no timestamp.
+    pub fn new(mut ticks: Vec<Quote>) -> Result<Self, XylexApiError> {
+        if ticks.is_empty() {
+            return Err(XylexApiError::ConfigurationError("No ticks to replay".to_string()));
+        }
+        if let Some(tick) = ticks.iter().find(|tick| tick.timestamp.is_none()) {
+            return Err(XylexApiError::ConfigurationError(format!("Tick of {} has no timestamp", tick.symbol)));
+        }
+        ticks.sort_by_key(|tick| tick.timestamp);
+
+        let mut by_symbol: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, tick) in ticks.iter().enumerate() {
+            by_symbol.entry(canonical(&tick.symbol)).or_default().push(index);
+        }
+        Ok(Self {
+            ticks: Arc::new(ticks),
+            by_symbol: Arc::new(by_symbol),
+            speed: 1.0,
+            started: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// Loads the ticks of a CSV file, see the [module documentation](self) for the columns.
+    ///
+    /// # Errors
+    /// Returns `XylexApiError::ConfigurationError` if the file can't be read or a row can't be parsed.
+    pub fn from_csv(path: impl AsRef<Path>) -> Result<Self, XylexApiError> {
+        let path: &Path = path.as_ref();
+        let text: String = fs::read_to_string(path)
+            .map_err(|e| XylexApiError::ConfigurationError(format!("Failed to read {}: {}", path.display(), e)))?;
+        Self::from_csv_str(&text)
+            .map_err(|e| XylexApiError::ConfigurationError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parses the ticks of CSV text, see [`ReplayProvider::from_csv`].
+    ///
+    /// # Errors
+    /// Returns the line and reason of the first row which can't be parsed.
+    pub fn from_csv_str(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+        let header: Vec<String> = match lines.next() {
+            Some((_, line)) => split_csv_line(line),
+            None => return Err("The file is empty".to_string()),
+        };
+
+        let mut ticks: Vec<Quote> = Vec::new();
+        for (index, line) in lines {
+            let record: HashMap<String, Value> = header
+                .iter()
+                .zip(split_csv_line(line))
+                .map(|(column, cell)| (column.to_ascii_lowercase(), cell_value(&cell)))
+                .collect();
+            ticks.push(record_to_quote(&record).map_err(|e| format!("line {}: {}", index + 1, e))?);
+        }
+        Self::new(ticks).map_err(|e| e.to_string())
+    }
+
+    /// Loads the ticks of a Parquet file, with the columns of a CSV file.
+    ///
+    /// Timestamps may be Parquet timestamps, dates, Unix seconds or milliseconds or strings.
+    ///
+    /// # Errors
+    /// Returns `XylexApiError::ConfigurationError` if the file can't be read or a row can't be parsed.
+    #[cfg(feature = "parquet")]
+    pub fn from_parquet(path: impl AsRef<Path>) -> Result<Self, XylexApiError> {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let path: &Path = path.as_ref();
+        let error = |e: String| XylexApiError::ConfigurationError(format!("{}: {}", path.display(), e));
+        let file: fs::File = fs::File::open(path).map_err(|e| error(e.to_string()))?;
+        let reader = SerializedFileReader::new(file).map_err(|e| error(e.to_string()))?;
+
+        let mut ticks: Vec<Quote> = Vec::new();
+        for (index, row) in reader.get_row_iter(None).map_err(|e| error(e.to_string()))?.enumerate() {
+            let row = row.map_err(|e| error(e.to_string()))?;
+            let record: HashMap<String, Value> = row
+                .get_column_iter()
+                .map(|(column, field)| (column.to_ascii_lowercase(), field_value(field)))
+                .collect();
+            ticks.push(record_to_quote(&record).map_err(|e| error(format!("row {}: {}", index + 1, e)))?);
+        }
+        Self::new(ticks)
+    }
+
+    /// Sets how many times faster than real time prices are replayed, `f64::INFINITY` replays
+    /// every tick at once.
+    pub fn with_speed(
+        mut self,
+        speed: f64
+    ) -> Self {
+        self.speed = speed.max(f64::MIN_POSITIVE);
+        self
+    }
+
+    /// Returns the ticks, oldest first.
+    pub fn ticks(&self) -> &[Quote] {
+        &self.ticks
+    }
+
+    /// Returns the time of the first and the last tick.
+    pub fn range(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        (self.first_time(), self.ticks.last().and_then(|tick| tick.timestamp).unwrap_or_default())
+    }
+
+    /// Starts the replay clock again from the first tick.
+    pub fn restart(&self) {
+        *self.started.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+    }
+
+    /// Returns the replayed time, `None` before the clock started.
+    pub fn replay_time(&self) -> Option<DateTime<Utc>> {
+        let started: Instant = (*self.started.lock().unwrap_or_else(|e| e.into_inner()))?;
+        Some(self.time_at(started.elapsed()))
+    }
+
+    /// Returns `true` once the clock passed the last tick.
+    pub fn is_finished(&self) -> bool {
+        self.replay_time().is_some_and(|time| time >= self.range().1)
+    }
+
+    /// Starts the clock unless it runs already and returns when it started.
+    fn start(&self) -> Instant {
+        *self
+            .started
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_or_insert_with(Instant::now)
+    }
+
+    fn first_time(&self) -> DateTime<Utc> {
+        self.ticks[0].timestamp.unwrap_or_default()
+    }
+
+    /// Returns the replayed time after `elapsed` of real time.
+    fn time_at(&self, elapsed: Duration) -> DateTime<Utc> {
+        let millis: f64 = elapsed.as_secs_f64() * self.speed * 1000.0;
+        TimeDelta::try_milliseconds(millis as i64)
+            .and_then(|offset| self.first_time().checked_add_signed(offset))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+
+    /// Returns the real time after which a tick is replayed.
+    fn delay_of(&self, tick: &Quote) -> Option<Duration> {
+        let offset: TimeDelta = tick.timestamp? - self.first_time();
+        Duration::try_from_secs_f64(offset.as_seconds_f64() / self.speed).ok()
+    }
+}
+
+#[async_trait]
+impl PriceProvider for ReplayProvider {
+    /// Quotes the last tick of the symbol at the replayed time, starting the clock on the first fetch.
+    async fn fetch_price(&self, symbol: &str) -> Result<Quote, XylexApiError> {
+        self.start();
+        let now: DateTime<Utc> = self.replay_time().unwrap_or_else(|| self.first_time());
+
+        let indices: &Vec<usize> = self
+            .by_symbol
+            .get(&canonical(symbol))
+            .ok_or_else(|| XylexApiError::InvalidSymbol(format!("No ticks of {} to replay", symbol)))?;
+        let passed: usize = indices.partition_point(|index| self.ticks[*index].timestamp.is_some_and(|at| at <= now));
+        passed
+            .checked_sub(1)
+            .map(|last| Quote { symbol: symbol.to_string(), ..self.ticks[indices[last]].clone() })
+            .ok_or_else(|| XylexApiError::InvalidSymbol(format!("No tick of {} before {}", symbol, now)))
+    }
+
+    async fn supported_symbols(&self) -> Result<Option<Vec<String>>, XylexApiError> {
+        let mut seen: HashSet<String> = HashSet::new();
+        Ok(Some(
+            self.ticks
+                .iter()
+                .filter(|tick| seen.insert(canonical(&tick.symbol)))
+                .map(|tick| tick.symbol.clone())
+                .collect(),
+        ))
+    }
+}
+
+#[async_trait]
+impl StreamingProvider for ReplayProvider {
+    /// Streams the ticks of `symbols` as the clock reaches them, starting with the current tick
+    /// of each symbol. The stream ends after the last tick.
+    async fn subscribe(&self, symbols: &[String]) -> Result<QuoteStream, XylexApiError> {
+        let started: Instant = self.start();
+        let now: Instant = Instant::now();
+        let wanted: HashMap<String, &String> = symbols.iter().map(|symbol| (canonical(symbol), symbol)).collect();
+
+        // The last passed tick of each symbol is sent at once, so subscribers start with a price
+        let mut current: HashMap<&String, usize> = HashMap::new();
+        let mut pending: Vec<(Instant, Quote)> = Vec::new();
+        for tick in self.ticks.iter() {
+            let (Some(symbol), Some(due)) = (
+                wanted.get(&canonical(&tick.symbol)).copied(),
+                self.delay_of(tick).and_then(|delay| started.checked_add(delay)),
+            ) else {
+                continue;
+            };
+            let quote: Quote = Quote { symbol: symbol.to_string(), ..tick.clone() };
+            match (due <= now, current.get(symbol)) {
+                (true, Some(&index)) => pending[index] = (now, quote),
+                (true, None) => {
+                    current.insert(symbol, pending.len());
+                    pending.push((now, quote));
+                },
+                (false, _) => pending.push((due, quote)),
+            }
+        }
+
+        Ok(stream::iter(pending)
+            .then(|(due, quote)| async move {
+                tokio::time::sleep_until(due.into()).await;
+                quote
+            })
+            .boxed())
+    }
+}
+
+/// Splits a CSV line into its cells, removing the quotes around quoted cells.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut cells: Vec<String> = Vec::new();
+    let mut cell: String = String::new();
+    let mut quoted: bool = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            },
+            ('"', _) => quoted = !quoted,
+            (',', false) => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            _ => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+/// Reads a CSV cell, whole numbers as numbers so Unix timestamps parse.
+fn cell_value(cell: &str) -> Value {
+    match cell.parse::<i64>() {
+        Ok(number) => Value::from(number),
+        Err(_) => Value::String(cell.to_string()),
+    }
+}
+
+/// Reads a Parquet field the way a CSV cell would read.
+#[cfg(feature = "parquet")]
+fn field_value(field: &parquet::record::Field) -> Value {
+    use parquet::record::Field;
+
+    match field {
+        Field::Byte(n) => Value::from(*n),
+        Field::Short(n) => Value::from(*n),
+        Field::Int(n) => Value::from(*n),
+        Field::Long(n) => Value::from(*n),
+        Field::UByte(n) => Value::from(*n),
+        Field::UShort(n) => Value::from(*n),
+        Field::UInt(n) => Value::from(*n),
+        Field::ULong(n) => Value::from(*n),
+        Field::Float(n) => Value::from(*n as f64),
+        Field::Double(n) => Value::from(*n),
+        Field::Str(s) => Value::String(s.clone()),
+        Field::Date(days) => Value::from(*days as i64 * 86_400),
+        Field::TimestampMillis(millis) => Value::from(*millis),
+        Field::TimestampMicros(micros) => Value::from(*micros / 1000),
+        _ => Value::Null,
+    }
+}
+
+/// Builds a tick from the cells of a row, keyed by lowercase column name.
+fn record_to_quote(record: &HashMap<String, Value>) -> Result<Quote, String> {
+    let column = |names: &[&str]| names.iter().find_map(|name| record.get(*name)).filter(|value| !value.is_null());
+
+    let timestamp: DateTime<Utc> = column(TIMESTAMP_COLUMNS)
+        .ok_or("missing timestamp")
+        .and_then(|value| parse_timestamp(value).ok_or("invalid timestamp"))?;
+    let symbol: String = match column(SYMBOL_COLUMNS) {
+        Some(Value::String(symbol)) if !symbol.is_empty() => symbol.clone(),
+        _ => return Err("missing symbol".to_string()),
+    };
+    let price: f64 = column(PRICE_COLUMNS)
+        .and_then(parse_optional_number)
+        .ok_or("missing or invalid price")?;
+
+    Ok(Quote {
+        symbol,
+        price,
+        bid: column(&["bid"]).and_then(parse_optional_number),
+        ask: column(&["ask"]).and_then(parse_optional_number),
+        volume: column(&["volume"]).and_then(parse_optional_number),
+        average_volume: None,
+        timestamp: Some(timestamp),
+    })
+}
@@ -3,15 +3,27 @@
 //! - `TwelveData`
 //!
 
-use crate::data::XylexApi;
+use std::sync::atomic::Ordering;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use serde_json::Value;
+
+use crate::Timeframe;
+use crate::data::{BudgetState, Candle, Instrument, NumberFormat, Quote, XylexApi};
 use crate::errors::XylexApiError;
-#[allow(unused_imports)]
+use crate::timeout::Timeouts;
+
+/// Symbols fetched per request by default, the most TwelveData accepts in a single request.
+pub const DEFAULT_BATCH_SIZE: usize = 120;
+
+/// Requests in flight by default when symbols are fetched one by one.
+pub const DEFAULT_CONCURRENCY: usize = 8;
 
 impl XylexApi {
     /// Requests the real-time price of a specified symbol using the Xylex API.
     ///
-    /// This method constructs a URL using the stored API endpoint and key, sends a GET request,
-    /// and parses the JSON response to extract the price as a floating-point number.
+    /// This is a convenience wrapper around [`XylexApi::request_real_time_quote`]
+    /// which only returns the price of the quote.
     ///
     /// # Parameters
     /// - `symbol`: A string slice that holds the symbol for which the price is being requested.
@@ -24,12 +36,44 @@ impl XylexApi {
     /// # Errors
     /// This method can return an error in several cases, including:
     /// - Network issues or server errors during the HTTP request.
-    /// - Missing or invalid `price` field in the JSON response.
-    /// - Failure to parse the `price` field as a floating-point number.
+    /// - Missing `price` field in the JSON response.
+    /// - A `price` field which can't be read with the configured [`NumberFormat`], returned as
+    ///   `XylexApiError::InvalidPrice` with the raw value.
     pub async fn request_real_time_price(
         &self,
         symbol: &str
     ) -> Result<f64, XylexApiError> {
+        let quote: Quote = self.request_real_time_quote(symbol).await?;
+
+        Ok(quote.price)
+    }
+
+    /// Requests the real-time quote of a specified symbol using the Xylex API.
+    ///
+    /// This method constructs a URL using the stored API endpoint and key, sends a GET request,
+    /// and parses the JSON response into a `Quote`, including the provider's `timestamp` when present. When a `CreditBudget` is configured the
+    /// every attempt is charged against it, and delayed once the budget leaves its normal state. The `bid`, `ask` and `volume` fields are optional;
+    /// when the provider does not return them the quote simply carries no spread or volume information.
+    ///
+    /// # Parameters
+    /// - `symbol`: A string slice that holds the symbol for which the quote is being requested.
+    ///
+    /// # Returns
+    /// A `Result` which is:
+    /// - `Ok(Quote)` containing the price and, if available, the bid and ask.
+    /// - `Err(XylexApiError)` if there is an error during the request or parsing.
+    ///
+    /// # Errors
+    /// This method can return an error in several cases, including:
+    /// - Network issues or server errors during the HTTP request.
+    /// - Missing `price` field in the JSON response.
+    /// - A `price` field which can't be read with the configured [`NumberFormat`], returned as
+    ///   `XylexApiError::InvalidPrice` with the raw value.
+    /// - The configured credit budget being exhausted.
+    pub async fn request_real_time_quote(
+        &self,
+        symbol: &str
+    ) -> Result<Quote, XylexApiError> {
         let url = format!(
             "{}?symbol={}&api_key={}", 
             self.endpoint, 
@@ -37,23 +81,325 @@ impl XylexApi {
             self.key
         );
 
-        let response: serde_json::Value = reqwest::Client::new()
-            .get(&url)
-            .send()
-            .await
-            .map_err(|_| XylexApiError::NetworkError("Failed to send request".to_string()))?
-            .json::<serde_json::Value>()
+        let response: Value = self.request_json(&url, 1).await?;
+
+        parse_quote(symbol, &response, &self.number_format)
+    }
+
+    /// Requests the real-time quotes of several symbols in a single request.
+    ///
+    /// The symbols are sent comma-separated, and the endpoint is expected to answer with one
+    /// quote per symbol keyed by the symbol, as TwelveData does. A batch is charged against the
+    /// credit budget once per symbol, as TwelveData charges it.
+    ///
+    /// Only a definitive answer disables batching: a single plain quote, i.e. the endpoint ignored
+    /// all but one symbol, or an error body with code 400, 404 or 501. Rate limiting is retried by
+    /// `request_json`, other incomplete answers are returned as errors.
+    ///
+    /// # Parameters
+    /// - `symbols`: The symbols for which quotes are being requested.
+    ///
+    /// # Returns
+    /// A `Result` which is:
+    /// - `Ok(Some(Vec<Quote>))` containing one quote per symbol, in the order of `symbols`.
+    /// - `Ok(None)` if the endpoint definitively does not support batching.
+    /// - `Err(XylexApiError)` if the request fails, a symbol is missing from the answer or the quote
+    ///   of a symbol could not be parsed.
+    pub async fn request_real_time_quotes(
+        &self,
+        symbols: &[&str]
+    ) -> Result<Option<Vec<Quote>>, XylexApiError> {
+        let url = format!(
+            "{}?symbol={}&api_key={}",
+            self.endpoint,
+            symbols.join(","),
+            self.key
+        );
+
+        let response: Value = self.request_json(&url, symbols.len()).await?;
+        let unsupported: bool = !response["price"].is_null()
+            || matches!(response["code"].as_u64(), Some(400 | 404 | 501));
+        if unsupported {
+            return Ok(None);
+        }
+        if let Some(missing) = symbols.iter().find(|symbol| !response[**symbol].is_object()) {
+            return Err(XylexApiError::UnexpectedError(format!("Batched answer is missing {}", missing)));
+        }
+
+        symbols
+            .iter()
+            .map(|symbol| parse_quote(symbol, &response[*symbol], &self.number_format))
+            .collect::<Result<Vec<Quote>, XylexApiError>>()
+            .map(Some)
+    }
+
+    /// Whether symbols should still be fetched in batches.
+    pub(crate) fn batching(&self) -> Option<usize> {
+        self.batch_size.filter(|_| !self.batch_unsupported.load(Ordering::Relaxed))
+    }
+
+    /// Remembers that the endpoint does not support batching, so later fetches go symbol by symbol.
+    pub(crate) fn disable_batching(&self) {
+        self.batch_unsupported.store(true, Ordering::Relaxed);
+    }
+}
+
+impl XylexApi {
+    /// Requests historical candles of a symbol from the history endpoint.
+    ///
+    /// The endpoint is expected to answer with a TwelveData style time series,
+    /// `{"values": [{"datetime", "open", "high", "low", "close"}]}`, newest first.
+    ///
+    /// # Parameters
+    /// - `symbol`: The symbol to request candles for.
+    /// - `timeframe`: The timeframe of the candles.
+    /// - `count`: The number of candles to request.
+    ///
+    /// # Returns
+    /// A `Result` which is:
+    /// - `Ok(Vec<Candle>)` containing the candles, oldest first.
+    /// - `Err(XylexApiError)` if there is an error during the request or parsing.
+    ///
+    /// # Errors
+    /// - `XylexApiError::ConfigurationError` if no history endpoint is set.
+    /// - `XylexApiError::NetworkError` or `XylexApiError::UnexpectedError` if the request fails.
+    /// - The configured credit budget being exhausted.
+    pub async fn request_candles(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        count: usize
+    ) -> Result<Vec<Candle>, XylexApiError> {
+        let endpoint: &str = self.history_endpoint.as_deref().ok_or_else(|| {
+            XylexApiError::ConfigurationError("No history endpoint set".to_string())
+        })?;
+
+        let url = format!(
+            "{}?symbol={}&interval={}&outputsize={}&api_key={}",
+            endpoint,
+            symbol,
+            timeframe.interval(),
+            count,
+            self.key
+        );
+
+        let response: Value = self.request_json(&url, 1).await?;
+
+        let values = response["values"]
+            .as_array()
+            .ok_or(XylexApiError::InvalidSymbol("Values field missing or not an array".to_string()))?;
+
+        let mut candles: Vec<Candle> = values
+            .iter()
+            .filter_map(|value| {
+                Some(Candle {
+                    symbol: symbol.to_string(),
+                    timeframe,
+                    open_time: parse_datetime(value["datetime"].as_str()?)?,
+                    open: parse_optional_number(&value["open"])?,
+                    high: parse_optional_number(&value["high"])?,
+                    low: parse_optional_number(&value["low"])?,
+                    close: parse_optional_number(&value["close"])?,
+                })
+            })
+            .collect();
+        candles.sort_by_key(|candle| candle.open_time);
+
+        Ok(candles)
+    }
+
+    /// Searches the instruments matching `query` through the search endpoint.
+    ///
+    /// The endpoint is expected to answer like TwelveData's `symbol_search`,
+    /// `{"data": [{"symbol", "instrument_name", "exchange", "instrument_type", "currency"}]}`.
+    ///
+    /// # Parameters
+    /// - `query`: A symbol or name fragment, e.g. `AA` or `apple`.
+    ///
+    /// # Returns
+    /// A `Result` which is:
+    /// - `Ok(Vec<Instrument>)` containing the matches in the order of the endpoint.
+    /// - `Err(XylexApiError)` if there is an error during the request or parsing.
+    ///
+    /// # Errors
+    /// - `XylexApiError::ConfigurationError` if no search endpoint is set.
+    /// - `XylexApiError::NetworkError` or `XylexApiError::UnexpectedError` if the request fails.
+    /// - The configured credit budget being exhausted.
+    pub async fn request_symbol_search(
+        &self,
+        query: &str
+    ) -> Result<Vec<Instrument>, XylexApiError> {
+        let endpoint: &str = self.search_endpoint.as_deref().ok_or_else(|| {
+            XylexApiError::ConfigurationError("No search endpoint set".to_string())
+        })?;
+
+        let url: reqwest::Url = reqwest::Url::parse_with_params(endpoint, &[("symbol", query), ("api_key", &self.key)])
+            .map_err(|e| XylexApiError::ConfigurationError(format!("Invalid search endpoint: {}", e)))?;
+        let response: Value = self.request_json(url.as_str(), 1).await?;
+
+        let data = response["data"]
+            .as_array()
+            .ok_or(XylexApiError::UnexpectedError("Data field missing or not an array".to_string()))?;
+
+        let text = |value: &Value| value.as_str().filter(|s| !s.is_empty()).map(String::from);
+        Ok(data
+            .iter()
+            .filter_map(|value| {
+                Some(Instrument {
+                    symbol: text(&value["symbol"])?,
+                    name: text(&value["instrument_name"]),
+                    exchange: text(&value["exchange"]),
+                    asset_type: text(&value["instrument_type"]),
+                    currency: text(&value["currency"]),
+                })
+            })
+            .collect())
+    }
+
+    /// Sends a GET request and parses the response as JSON, retrying network errors, timeouts and
+    /// rate limiting according to the `RetryPolicy`, whose backoff spaces out the attempts. Every attempt
+    /// is charged for `symbols` symbols against the credit budget and waits for the `RateLimiter`, if any.
+    async fn request_json(
+        &self,
+        url: &str,
+        symbols: usize
+    ) -> Result<Value, XylexApiError> {
+        let attempt = || async {
+            self.charge_budget(symbols).await?;
+            if let Some(rate_limit) = &self.rate_limit {
+                rate_limit.acquire().await;
+            }
+            #[cfg(feature = "replay-http")]
+            if let Some(replay) = &self.replay {
+                let send = async { request_json_once(&self.timeouts, url).await.map(|body| (200, body)) };
+                return replay.exchange("GET", url, send).await.map(|(_, body)| body);
+            }
+            request_json_once(&self.timeouts, url).await
+        };
+        self.retry
+            .run(attempt, |e| {
+                matches!(e, XylexApiError::NetworkError(_) | XylexApiError::Timeout(_) | XylexApiError::RateLimited(_))
+            })
             .await
-            .map_err(|_| XylexApiError::UnexpectedError("Failed to parse JSON".to_string()))?;
+    }
+
+    /// Charges a request for `symbols` symbols against the credit budget, if any, and delays it
+    /// once the budget leaves its normal state.
+    async fn charge_budget(
+        &self,
+        symbols: usize
+    ) -> Result<(), XylexApiError> {
+        if let Some(budget) = &self.budget {
+            if budget.charge_symbols(symbols)? != BudgetState::Normal {
+                tokio::time::sleep(budget.policy.slow_down_delay).await;
+            }
+        }
+        Ok(())
+    }
+}
 
-        let price_str = response["price"]
-            .as_str()
-            .ok_or(XylexApiError::InvalidSymbol("Price field missing or not a string".to_string()))?;
+/// Sends a GET request and parses the response as JSON, an HTTP 429 or a body with code 429
+/// is returned as `XylexApiError::RateLimited`.
+async fn request_json_once(
+    timeouts: &Timeouts,
+    url: &str
+) -> Result<Value, XylexApiError> {
+    let timed_out = || {
+        XylexApiError::Timeout(format!(
+            "No answer within {:?} to connect or {:?} between reads",
+            timeouts.connect, timeouts.read
+        ))
+    };
+    let response = timeouts
+        .client()
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| match e.is_timeout() {
+            true => timed_out(),
+            false => XylexApiError::NetworkError("Failed to send request".to_string()),
+        })?;
+    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(XylexApiError::RateLimited("HTTP 429".to_string()));
+    }
+    let body: Value = response
+        .json::<Value>()
+        .await
+        .map_err(|e| match e.is_timeout() {
+            true => timed_out(),
+            false => XylexApiError::UnexpectedError("Failed to parse JSON".to_string()),
+        })?;
+    if body["code"].as_u64() == Some(429) {
+        let message: &str = body["message"].as_str().unwrap_or("Code 429");
+        return Err(XylexApiError::RateLimited(message.to_string()));
+    }
+    Ok(body)
+}
 
-        let price: f64 = price_str
-            .parse()
-            .map_err(|_| XylexApiError::UnexpectedError("Failed to parse price as float".to_string()))?;
+/// Parses the quote of a single symbol, the price is required and read with `format`.
+///
+/// The optional `bid`, `ask` and `volume` fields are read with the same format, and left out
+/// when they can't be read.
+fn parse_quote(
+    symbol: &str,
+    response: &Value,
+    format: &NumberFormat
+) -> Result<Quote, XylexApiError> {
+    if response["price"].is_null() {
+        return Err(XylexApiError::InvalidSymbol("Price field missing".to_string()));
+    }
+    let price: f64 = format
+        .parse(&response["price"])
+        .map_err(|reason| XylexApiError::InvalidPrice {
+            symbol: symbol.to_string(),
+            field: "price".to_string(),
+            raw: response["price"].to_string(),
+            reason,
+        })?;
+
+    Ok(Quote {
+        symbol: symbol.to_string(),
+        price,
+        bid: format.parse(&response["bid"]).ok(),
+        ask: format.parse(&response["ask"]).ok(),
+        volume: format.parse(&response["volume"]).ok(),
+        average_volume: None,
+        timestamp: parse_timestamp(&response["timestamp"]),
+    })
+}
+
+/// Parses a candle timestamp, either `YYYY-MM-DD HH:MM:SS` or `YYYY-MM-DD` in UTC.
+fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|date| date.and_time(NaiveTime::MIN)))
+        .ok()
+        .map(|datetime| datetime.and_utc())
+}
+
+/// Parses the quote time, sent either as Unix seconds or milliseconds, or as a date string.
+pub(crate) fn parse_timestamp(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    match value {
+        serde_json::Value::Number(n) => {
+            let n: i64 = n.as_i64()?;
+            if n.abs() >= 1_000_000_000_000 {
+                DateTime::from_timestamp_millis(n)
+            } else {
+                DateTime::from_timestamp(n, 0)
+            }
+        },
+        serde_json::Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .map(|datetime| datetime.with_timezone(&Utc))
+            .ok()
+            .or_else(|| parse_datetime(s)),
+        _ => None,
+    }
+}
 
-        Ok(price)
+/// Parses an optional price or volume field which may be sent either as a string or as a number.
+pub(crate) fn parse_optional_number(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::String(s) => s.parse().ok(),
+        serde_json::Value::Number(n) => n.as_f64(),
+        _ => None,
     }
-}
\ No newline at end of file
+}
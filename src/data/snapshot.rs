@@ -0,0 +1,131 @@
+//! ## Warm start
+//! Saves the price state after every cycle and seeds it on startup, so the first cycle after a
+//! restart detects band crossings against the last price seen before the restart.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::data::{MovementGate, PreviousPrices, PriceSnapshot, Quote, XylexApi};
+
+impl PriceSnapshot {
+    /// Creates a snapshot of recorded quotes, the last quote of every symbol winning.
+    pub fn from_quotes(quotes: &[Quote]) -> Self {
+        let mut quotes: Vec<&Quote> = quotes.iter().collect();
+        quotes.sort_by_key(|quote| quote.timestamp);
+        Self {
+            taken_at: quotes.last().and_then(|quote| quote.timestamp).unwrap_or_else(Utc::now),
+            prices: quotes.iter().map(|quote| (quote.symbol.clone(), quote.price)).collect(),
+            evaluated: BTreeMap::new(),
+        }
+    }
+
+    /// Reads a snapshot written by [`PriceSnapshot::save`].
+    ///
+    /// # Returns
+    /// `None` if the file does not exist or does not hold a snapshot.
+    pub fn load(path: &Path) -> Option<Self> {
+        let json: Vec<u8> = std::fs::read(path).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+
+    /// Writes the snapshot to a JSON file.
+    ///
+    /// The snapshot is written to a temporary file next to `path` and renamed over it, so a
+    /// crash while saving leaves the previous snapshot instead of a truncated one.
+    ///
+    /// # Errors
+    /// Returns a description of the problem if the file could not be written.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json: Vec<u8> = serde_json::to_vec_pretty(self).map_err(|e| e.to_string())?;
+        let temporary: PathBuf = path.with_extension("saving");
+        let write = || -> std::io::Result<()> {
+            let mut file: File = File::create(&temporary)?;
+            file.write_all(&json)?;
+            file.sync_all()?;
+            std::fs::rename(&temporary, path)
+        };
+        write().map_err(|e| {
+            let _ = std::fs::remove_file(&temporary);
+            e.to_string()
+        })
+    }
+
+    /// Returns how old the snapshot is at `now`, zero for snapshots from the future.
+    pub fn age(&self, now: DateTime<Utc>) -> Duration {
+        (now - self.taken_at).to_std().unwrap_or_default()
+    }
+
+    /// Whether the snapshot holds no prices.
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty() && self.evaluated.is_empty()
+    }
+}
+
+impl XylexApi {
+    /// Returns the current price state, see [`PriceSnapshot`].
+    pub fn price_snapshot(&self) -> PriceSnapshot {
+        PriceSnapshot {
+            taken_at: Utc::now(),
+            prices: self.previous_prices.all(),
+            evaluated: self.movement_gate.as_ref().map(MovementGate::evaluated).unwrap_or_default(),
+        }
+    }
+
+    /// Seeds the previous prices and the movement gate, if set, from a snapshot.
+    ///
+    /// Prices already recorded are kept, so seeding never rolls the state back.
+    ///
+    /// # Returns
+    /// The number of symbols seeded.
+    pub fn seed_prices(&self, snapshot: &PriceSnapshot) -> usize {
+        let mut seeded: usize = 0;
+        for (symbol, price) in &snapshot.prices {
+            if self.previous_prices.get(symbol).is_none() {
+                self.previous_prices.record(symbol, *price);
+                seeded += 1;
+            }
+        }
+        if let Some(gate) = &self.movement_gate {
+            for (symbol, price) in &snapshot.evaluated {
+                gate.seed(symbol, *price);
+            }
+        }
+        seeded
+    }
+}
+
+impl PreviousPrices {
+    /// Returns the recorded price of every symbol.
+    pub fn all(&self) -> BTreeMap<String, f64> {
+        let prices = self.prices.lock().unwrap_or_else(|e| e.into_inner());
+
+        prices.iter().map(|(symbol, price)| (symbol.clone(), *price)).collect()
+    }
+}
+
+impl MovementGate {
+    /// Returns the price every symbol was last evaluated at.
+    pub fn evaluated(&self) -> BTreeMap<String, f64> {
+        let last_evaluated = self.last_evaluated.lock().unwrap_or_else(|e| e.into_inner());
+
+        last_evaluated.iter().map(|(symbol, price)| (symbol.clone(), *price)).collect()
+    }
+
+    /// Records the price a symbol was last evaluated at, unless one is recorded already.
+    pub fn seed(
+        &self,
+        symbol: &str,
+        price: f64
+    ) {
+        let mut last_evaluated = self.last_evaluated.lock().unwrap_or_else(|e| e.into_inner());
+
+        if last_evaluated.peek(&symbol.to_string()).is_none() {
+            last_evaluated.insert(symbol.to_string(), price);
+        }
+    }
+}
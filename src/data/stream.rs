@@ -0,0 +1,223 @@
+//! ## Streaming quotes
+//! Checks alerts on every tick of a `StreamingProvider` instead of on a polling interval.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::{FutureExt, StreamExt, stream};
+use tokio::sync::mpsc::Sender;
+
+use crate::data::{
+    CheckReport, LatestQuotes, PriceProvider, Quote, QuoteStream, ReconnectingProvider, StreamingProvider, XylexApi,
+};
+use crate::db::{Supabase, TableConfig};
+use crate::errors::XylexApiError;
+use crate::retry::RetryPolicy;
+use crate::utils::lru::{CacheStats, LruCache};
+use crate::utils::symbol::canonical;
+
+impl LatestQuotes {
+    /// Creates an empty `LatestQuotes`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a `LatestQuotes` keeping the quotes of up to `capacity` symbols, the least
+    /// recently updated symbols are forgotten first.
+    pub fn with_capacity(capacity: Option<usize>) -> Self {
+        Self { quotes: Arc::new(Mutex::new(LruCache::new(capacity))) }
+    }
+
+    /// Returns the occupancy of the quotes.
+    pub fn stats(&self) -> CacheStats {
+        self.lock().stats()
+    }
+
+    /// Stores a quote, replacing the previous quote of its symbol in any of its formats.
+    pub fn record(&self, quote: Quote) {
+        self.lock().insert(canonical(&quote.symbol), quote);
+    }
+
+    /// Returns the last quote of a symbol, if any was received, carrying the symbol as the caller wrote it.
+    pub fn get(&self, symbol: &str) -> Option<Quote> {
+        self.lock()
+            .peek(&canonical(symbol))
+            .map(|quote| Quote { symbol: symbol.to_string(), ..quote.clone() })
+    }
+
+    /// Forgets the quote of a symbol, e.g. once it is no longer streamed.
+    pub fn remove(&self, symbol: &str) {
+        self.lock().remove(&canonical(symbol));
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LruCache<String, Quote>> {
+        self.quotes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl PriceProvider for LatestQuotes {
+    async fn fetch_price(&self, symbol: &str) -> Result<Quote, XylexApiError> {
+        self.get(symbol)
+            .ok_or_else(|| XylexApiError::InvalidSymbol(format!("No quote received for {}", symbol)))
+    }
+
+    async fn supported_symbols(&self) -> Result<Option<Vec<String>>, XylexApiError> {
+        Ok(Some(self.lock().values().map(|quote| quote.symbol.clone()).collect()))
+    }
+}
+
+impl ReconnectingProvider {
+    /// Wraps a streaming provider, reconnecting forever with a backoff from 500ms up to 30s.
+    pub fn new(inner: impl StreamingProvider + 'static) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            snapshot: None,
+            retry: RetryPolicy::default()
+                .with_max_attempts(u32::MAX)
+                .with_backoff(Duration::from_millis(500), Duration::from_secs(30)),
+        }
+    }
+
+    /// Fetches the quotes of every subscribed symbol from `snapshot` after each reconnect.
+    pub fn with_snapshot(
+        mut self,
+        snapshot: impl PriceProvider + 'static
+    ) -> Self {
+        self.snapshot = Some(Arc::new(snapshot));
+        self
+    }
+
+    /// Sets the backoff between reconnect attempts and how many failed attempts in a row end the stream.
+    pub fn with_retry_policy(
+        mut self,
+        retry: RetryPolicy
+    ) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Subscribes again with backoff, then queues the snapshot of every symbol.
+    ///
+    /// # Returns
+    /// Returns `None` once `max_attempts` attempts in a row failed.
+    async fn reconnect(
+        &self,
+        symbols: &[String],
+        pending: &mut VecDeque<Quote>
+    ) -> Option<QuoteStream> {
+        let mut attempt: u32 = 1;
+        let stream: QuoteStream = loop {
+            tokio::time::sleep(self.retry.backoff(attempt)).await;
+            match self.inner.subscribe(symbols).await {
+                Ok(stream) => break stream,
+                Err(e) if attempt < self.retry.max_attempts => {
+                    println!("Error resubscribing to the price stream (attempt {}): {}", attempt, e);
+                    attempt += 1;
+                },
+                Err(e) => {
+                    println!("Giving up on the price stream after {} attempts: {}", attempt, e);
+                    return None;
+                },
+            }
+        };
+
+        if let Some(snapshot) = &self.snapshot {
+            let symbols: Vec<&str> = symbols.iter().map(String::as_str).collect();
+            match snapshot.fetch_prices(&symbols).await {
+                Ok(quotes) => pending.extend(quotes),
+                Err(e) => println!("Error fetching the snapshot after reconnecting: {}", e),
+            }
+        }
+        Some(stream)
+    }
+}
+
+#[async_trait]
+impl StreamingProvider for ReconnectingProvider {
+    /// Subscribes through the wrapped provider, the first subscription is not retried.
+    ///
+    /// The returned stream only ends once reconnecting failed `max_attempts` times in a row.
+    async fn subscribe(&self, symbols: &[String]) -> Result<QuoteStream, XylexApiError> {
+        let first: QuoteStream = self.inner.subscribe(symbols).await?;
+        let state = (self.clone(), symbols.to_vec(), Some(first), VecDeque::new());
+
+        let quotes = stream::unfold(state, |(provider, symbols, mut current, mut pending)| async move {
+            loop {
+                if let Some(quote) = pending.pop_front() {
+                    return Some((quote, (provider, symbols, current, pending)));
+                }
+                if let Some(stream) = current.as_mut() {
+                    if let Some(quote) = stream.next().await {
+                        return Some((quote, (provider, symbols, current, pending)));
+                    }
+                    println!("Price stream ended, reconnecting");
+                }
+                current = Some(provider.reconnect(&symbols, &mut pending).await?);
+            }
+        });
+
+        Ok(Box::pin(quotes))
+    }
+}
+
+impl XylexApi {
+    /// Checks the alerts every time the streaming provider pushes a new quote.
+    ///
+    /// Subscribes to the symbols that have alerts when called, alerts on other symbols added
+    /// later are not streamed until `watch_alerts` is called again, see
+    /// [`XylexApi::watch_subscribed`] to follow them. Ticks received while a
+    /// check is running are coalesced into the next check, so a busy feed never queues up
+    /// checks. Like [`XylexApi::check_alerts`], triggered alerts are left in the table for
+    /// the caller to notify and remove.
+    ///
+    /// # Arguments
+    /// * `provider` - The feed pushing the quotes.
+    /// * `supabase` - A reference to a `Supabase` client.
+    /// * `config` - A reference to a `TableConfig` which contains configuration for database tables.
+    /// * `reports` - Receives the report of every check.
+    ///
+    /// # Returns
+    /// `Ok(())` once the stream ends or the receiver of `reports` is dropped.
+    ///
+    /// # Errors
+    /// Returns a `XylexApiError` if the symbols could not be fetched or the subscription failed.
+    /// Failed checks are logged and do not stop watching.
+    pub async fn watch_alerts<P: StreamingProvider + ?Sized>(
+        &self,
+        provider: &P,
+        supabase: &Supabase,
+        config: &TableConfig,
+        reports: Sender<CheckReport>,
+    ) -> Result<(), XylexApiError> {
+        let (symbols, _success) = supabase
+            .fetch_unique_symbols(config)
+            .await
+            .map_err(|e| XylexApiError::NetworkError(e.to_string()))?;
+        let symbols: Vec<String> = symbols.into_iter().collect();
+
+        let mut stream: QuoteStream = provider.subscribe(&symbols).await?;
+        let latest: LatestQuotes = LatestQuotes::with_capacity(self.resource_limits.max_symbols);
+
+        while let Some(quote) = stream.next().await {
+            latest.record(quote);
+            // Coalesce the ticks which arrived while the previous check ran
+            while let Some(Some(quote)) = stream.next().now_or_never() {
+                latest.record(quote);
+            }
+
+            match self.check_alerts_with(&latest, supabase, config).await {
+                Ok(report) => {
+                    if reports.send(report).await.is_err() {
+                        break;
+                    }
+                },
+                Err(e) => println!("Error checking streamed quotes: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+//! ## Subscription management
+//! Follows the symbols that have alerts, so alerts added or removed while streaming are
+//! subscribed or unsubscribed without restarting the watcher.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use futures_util::{FutureExt, StreamExt, stream};
+use tokio::sync::Notify;
+use tokio::sync::mpsc::Sender;
+use tokio::time::{Instant, Interval, interval_at};
+
+use crate::data::{
+    CheckReport, LatestQuotes, QuoteStream, StreamingProvider, SubscriptionChange, SubscriptionManager, XylexApi,
+};
+use crate::db::{Supabase, TableConfig};
+use crate::errors::XylexApiError;
+use crate::utils::symbol::canonical;
+
+/// Time between two reconciliations when no interval is set.
+pub const DEFAULT_RECONCILE_INTERVAL: Duration = Duration::from_secs(60);
+
+impl SubscriptionManager {
+    /// Creates a manager of `provider`'s subscription, reconciling every minute.
+    pub fn new(provider: impl StreamingProvider + 'static) -> Self {
+        Self {
+            provider: Arc::new(provider),
+            reconcile_interval: DEFAULT_RECONCILE_INTERVAL,
+            subscribed: Arc::new(Mutex::new(BTreeMap::new())),
+            refresh: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Sets the time between two reconciliations with the alerts table.
+    pub fn with_reconcile_interval(
+        mut self,
+        reconcile_interval: Duration
+    ) -> Self {
+        self.reconcile_interval = reconcile_interval;
+        self
+    }
+
+    /// Returns the subscribed symbols, ordered by their canonical form.
+    pub fn symbols(&self) -> Vec<String> {
+        self.lock().values().cloned().collect()
+    }
+
+    /// Reconciles on the next opportunity instead of waiting for the interval,
+    /// call it after alerts are added or removed.
+    pub fn refresh(&self) {
+        self.refresh.notify_one();
+    }
+
+    /// Subscribes to `symbols` if they differ from the subscribed symbols.
+    ///
+    /// Symbols are compared in canonical form, so `eur/usd` and `EURUSD` are the same symbol.
+    /// The subscribed symbols only change once the provider accepted the new subscription.
+    ///
+    /// # Returns
+    /// `Ok(None)` if the symbols did not change, otherwise the new subscription. Without any
+    /// symbols the returned stream stays pending instead of ending.
+    ///
+    /// # Errors
+    /// Returns a `XylexApiError` if the provider refused the subscription.
+    pub async fn update(
+        &self,
+        symbols: impl IntoIterator<Item = String>
+    ) -> Result<Option<SubscriptionChange>, XylexApiError> {
+        let mut desired: BTreeMap<String, String> = BTreeMap::new();
+        for symbol in symbols {
+            desired.entry(canonical(&symbol)).or_insert(symbol);
+        }
+
+        let (added, removed): (Vec<String>, Vec<String>) = {
+            let subscribed = self.lock();
+            (
+                desired.iter().filter(|(key, _)| !subscribed.contains_key(*key)).map(|(_, s)| s.clone()).collect(),
+                subscribed.iter().filter(|(key, _)| !desired.contains_key(*key)).map(|(_, s)| s.clone()).collect(),
+            )
+        };
+        if added.is_empty() && removed.is_empty() {
+            return Ok(None);
+        }
+
+        let symbols: Vec<String> = desired.values().cloned().collect();
+        let quotes: QuoteStream = if symbols.is_empty() {
+            Box::pin(stream::pending())
+        } else {
+            self.provider.subscribe(&symbols).await?
+        };
+        *self.lock() = desired;
+        Ok(Some(SubscriptionChange { added, removed, quotes }))
+    }
+
+    /// Subscribes to the symbols of every alert in the table.
+    ///
+    /// # Errors
+    /// Returns a `XylexApiError` if the symbols could not be fetched or the provider refused the subscription.
+    pub async fn reconcile(
+        &self,
+        supabase: &Supabase,
+        config: &TableConfig
+    ) -> Result<Option<SubscriptionChange>, XylexApiError> {
+        let (symbols, _success) = supabase
+            .fetch_unique_symbols(config)
+            .await
+            .map_err(|e| XylexApiError::NetworkError(e.to_string()))?;
+        self.update(symbols).await
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, String>> {
+        self.subscribed.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl XylexApi {
+    /// Checks the alerts every time a quote is streamed, following the symbols that have alerts.
+    ///
+    /// Unlike [`XylexApi::watch_alerts`], the subscription is reconciled with the alerts table
+    /// every `reconcile_interval` of the manager and whenever [`SubscriptionManager::refresh`]
+    /// is called, so symbols of new alerts start streaming and symbols without alerts stop.
+    /// A failed reconciliation is logged and the current subscription kept.
+    ///
+    /// # Arguments
+    /// * `manager` - The manager of the streaming provider's subscription.
+    /// * `supabase` - A reference to a `Supabase` client.
+    /// * `config` - A reference to a `TableConfig` which contains configuration for database tables.
+    /// * `reports` - Receives the report of every check.
+    ///
+    /// # Returns
+    /// `Ok(())` once the stream ends or the receiver of `reports` is dropped.
+    ///
+    /// # Errors
+    /// Returns a `XylexApiError` if the first subscription could not be set up.
+    pub async fn watch_subscribed(
+        &self,
+        manager: &SubscriptionManager,
+        supabase: &Supabase,
+        config: &TableConfig,
+        reports: Sender<CheckReport>,
+    ) -> Result<(), XylexApiError> {
+        let mut stream: QuoteStream = match manager.reconcile(supabase, config).await? {
+            Some(change) => change.quotes,
+            None => Box::pin(stream::pending()),
+        };
+        let latest: LatestQuotes = LatestQuotes::with_capacity(self.resource_limits.max_symbols);
+        let mut ticker: Interval = interval_at(Instant::now() + manager.reconcile_interval, manager.reconcile_interval);
+
+        loop {
+            tokio::select! {
+                quote = stream.next() => {
+                    let Some(quote) = quote else {
+                        break;
+                    };
+                    latest.record(quote);
+                    // Coalesce the ticks which arrived while the previous check ran
+                    while let Some(Some(quote)) = stream.next().now_or_never() {
+                        latest.record(quote);
+                    }
+
+                    match self.check_alerts_with(&latest, supabase, config).await {
+                        Ok(report) => {
+                            if reports.send(report).await.is_err() {
+                                break;
+                            }
+                        },
+                        Err(e) => println!("Error checking streamed quotes: {}", e),
+                    }
+                },
+                _ = ticker.tick() => resubscribe(manager, supabase, config, &latest, &mut stream).await,
+                _ = manager.refresh.notified() => resubscribe(manager, supabase, config, &latest, &mut stream).await,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Replaces `stream` if the symbols with alerts changed, forgetting the quotes of removed symbols.
+async fn resubscribe(
+    manager: &SubscriptionManager,
+    supabase: &Supabase,
+    config: &TableConfig,
+    latest: &LatestQuotes,
+    stream: &mut QuoteStream,
+) {
+    match manager.reconcile(supabase, config).await {
+        Ok(Some(change)) => {
+            println!("Subscribed to {:?}, unsubscribed from {:?}", change.added, change.removed);
+            for symbol in &change.removed {
+                latest.remove(symbol);
+            }
+            *stream = change.quotes;
+        },
+        Ok(None) => {},
+        Err(e) => println!("Error reconciling subscriptions: {}", e),
+    }
+}
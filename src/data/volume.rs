@@ -0,0 +1,72 @@
+//! ## Volume history
+//! Keeps the volumes seen on recent checks so volume spikes can be detected
+//! without fetching historical data.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use crate::data::VolumeHistory;
+use crate::utils::lru::CacheStats;
+
+/// Number of samples averaged by `VolumeHistory::default()`.
+pub const DEFAULT_VOLUME_WINDOW: usize = 20;
+
+impl VolumeHistory {
+    /// Creates a `VolumeHistory` averaging over the last `window` samples per symbol.
+    pub fn new(window: usize) -> Self {
+        Self { window: window.max(1), samples: Arc::new(Mutex::new(Default::default())) }
+    }
+
+    /// Records a volume sample for a symbol.
+    ///
+    /// # Returns
+    /// Returns the average of the samples recorded before this one, `None` for the first sample.
+    pub fn record(
+        &self,
+        symbol: &str,
+        volume: f64
+    ) -> Option<f64> {
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        let history: &mut VecDeque<f64> = samples.get_or_insert_with(symbol.to_string(), VecDeque::new);
+
+        let average: Option<f64> = average(history);
+        history.push_back(volume);
+        while history.len() > self.window {
+            history.pop_front();
+        }
+        average
+    }
+
+    /// Returns the average of the recorded samples for a symbol.
+    pub fn average(
+        &self,
+        symbol: &str
+    ) -> Option<f64> {
+        let samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+
+        samples.peek(&symbol.to_string()).and_then(average)
+    }
+
+    /// Bounds the symbols with recorded samples, see [`crate::ResourceLimits`].
+    pub fn set_capacity(&self, capacity: Option<usize>) {
+        self.samples.lock().unwrap_or_else(|e| e.into_inner()).set_capacity(capacity);
+    }
+
+    /// Returns the occupancy of the history, one entry per symbol.
+    pub fn stats(&self) -> CacheStats {
+        self.samples.lock().unwrap_or_else(|e| e.into_inner()).stats()
+    }
+}
+
+impl Default for VolumeHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_VOLUME_WINDOW)
+    }
+}
+
+fn average(history: &VecDeque<f64>) -> Option<f64> {
+    if history.is_empty() {
+        return None;
+    }
+    Some(history.iter().sum::<f64>() / history.len() as f64)
+}
@@ -0,0 +1,84 @@
+//! ## WebSocket price feed
+//! A `StreamingProvider` reading quotes pushed over a WebSocket connection.
+
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt, stream};
+use serde_json::Value;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::data::{Quote, QuoteStream, StreamingProvider, WebSocketProvider};
+use crate::data::request::{parse_optional_number, parse_timestamp};
+use crate::errors::XylexApiError;
+
+impl WebSocketProvider {
+    /// Creates a `WebSocketProvider` for a `ws://` or `wss://` URL.
+    pub fn new(url: String) -> Self {
+        Self { url, subscribe_message: None }
+    }
+
+    /// Sets the message sent after connecting, e.g. `{"action": "subscribe", "symbols": {symbols}}`.
+    pub fn with_subscribe_message(
+        mut self,
+        message: &str
+    ) -> Self {
+        self.subscribe_message = Some(message.to_string());
+        self
+    }
+}
+
+#[async_trait]
+impl StreamingProvider for WebSocketProvider {
+    /// Connects and sends the subscribe message, if any.
+    ///
+    /// Messages which are not quotes, such as heartbeats, are skipped. The stream ends on
+    /// the first connection error.
+    async fn subscribe(&self, symbols: &[String]) -> Result<QuoteStream, XylexApiError> {
+        let (mut socket, _response) = connect_async(self.url.as_str())
+            .await
+            .map_err(|e| XylexApiError::NetworkError(format!("Failed to connect to {}: {}", self.url, e)))?;
+
+        if let Some(template) = &self.subscribe_message {
+            let symbols: String = serde_json::to_string(symbols)
+                .map_err(|e| XylexApiError::UnexpectedError(e.to_string()))?;
+            socket
+                .send(Message::Text(template.replace("{symbols}", &symbols)))
+                .await
+                .map_err(|e| XylexApiError::NetworkError(format!("Failed to subscribe: {}", e)))?;
+        }
+
+        let quotes = socket
+            .take_while(|message| std::future::ready(message.is_ok()))
+            .flat_map(|message| {
+                let quotes: Vec<Quote> = match message {
+                    Ok(Message::Text(text)) => serde_json::from_str::<Value>(&text)
+                        .map(|value| parse_quotes(&value))
+                        .unwrap_or_default(),
+                    _ => Vec::new(),
+                };
+                stream::iter(quotes)
+            });
+
+        Ok(Box::pin(quotes))
+    }
+}
+
+/// Parses a message holding a single quote or a list of quotes, skipping entries without a symbol or price.
+fn parse_quotes(value: &Value) -> Vec<Quote> {
+    let parse = |value: &Value| -> Option<Quote> {
+        Some(Quote {
+            symbol: value["symbol"].as_str()?.to_string(),
+            price: parse_optional_number(&value["price"])?,
+            bid: parse_optional_number(&value["bid"]),
+            ask: parse_optional_number(&value["ask"]),
+            volume: parse_optional_number(&value["volume"]),
+            average_volume: None,
+            timestamp: parse_timestamp(&value["timestamp"]),
+        })
+    };
+
+    match value {
+        Value::Array(values) => values.iter().filter_map(parse).collect(),
+        value => parse(value).into_iter().collect(),
+    }
+}
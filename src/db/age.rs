@@ -0,0 +1,150 @@
+//! ## Maximum alert age
+//!
+//! Finds alerts which have been active longer than the configured `AgePolicy`,
+//! warns their owners and optionally moves them to the history table.
+//! Meant to run once a day as part of the maintenance pass.
+
+use std::error::Error;
+
+use chrono::{DateTime, Duration, Utc};
+use serde_json::{Value, json};
+
+use crate::Alert;
+use crate::db::rest::{Filter, before, eq, is_null};
+use crate::db::{AgePolicy, AgeReport, StaleAlert, Supabase, TableConfig};
+use crate::errors::SupabaseError;
+use crate::notify::Notifier;
+
+impl AgePolicy {
+    /// Creates an `AgePolicy` which only warns about alerts older than `max_age`.
+    pub fn new(max_age: Duration) -> Self {
+        Self { max_age, auto_archive: false }
+    }
+
+    /// Sets whether stale alerts are archived after the warning.
+    pub fn with_auto_archive(
+        mut self,
+        auto_archive: bool
+    ) -> Self {
+        self.auto_archive = auto_archive;
+        self
+    }
+
+    /// Returns `true` if an alert created at `created_at` is stale at `now`.
+    pub fn is_stale(
+        &self,
+        created_at: DateTime<Utc>,
+        now: DateTime<Utc>
+    ) -> bool {
+        now - created_at > self.max_age
+    }
+}
+
+impl Supabase {
+    /// Fetches the alerts which are older than the policy allows.
+    ///
+    /// Rows without a creation timestamp are never considered stale. Without `auto_archive`,
+    /// alerts whose owner was already warned, see `TableConfig::stale_warned_at_column_name`,
+    /// are left out.
+    ///
+    /// # Parameters
+    /// - `policy`: The `AgePolicy` to apply.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    /// - `now`: The current time.
+    ///
+    /// # Returns
+    /// A `Result` containing the stale alerts, oldest first, or an error.
+    ///
+    /// # Errors
+    /// Returns an error if the query execution fails or any of the rows is incomplete.
+    pub async fn fetch_stale_alerts(
+        &self,
+        policy: &AgePolicy,
+        config: &TableConfig,
+        now: DateTime<Utc>
+    ) -> Result<Vec<StaleAlert>, Box<dyn Error + Send + Sync>> {
+        let mut filters: Vec<Filter> = vec![before(&config.created_at_column_name, now - policy.max_age)];
+        if !policy.auto_archive {
+            filters.push(is_null(&config.stale_warned_at_column_name));
+        }
+        let values: Vec<Value> = self
+            .rest_select(&config.tablename, &filters)
+            .await
+            .map_err(SupabaseError::FetchError)?;
+
+        let mut stale: Vec<StaleAlert> = Vec::with_capacity(values.len());
+        for mut value in values {
+            let created_at: Option<DateTime<Utc>> = value
+                .get(&config.created_at_column_name)
+                .and_then(|v| v.as_str())
+                .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+                .map(|v| v.with_timezone(&Utc));
+
+            if let Some(created_at) = created_at.filter(|created_at| policy.is_stale(*created_at, now)) {
+                self.open_row(config, &mut value)?;
+                stale.push(StaleAlert { alert: Alert::from_row(&value, config)?, created_at });
+            }
+        }
+        stale.sort_by_key(|stale| stale.created_at);
+
+        Ok(stale)
+    }
+
+    /// Warns the owners of stale alerts and archives them if the policy says so.
+    ///
+    /// An alert is only archived once every notifier delivered the warning, so a user is never
+    /// left wondering where an alert went. Alerts which are not archived are marked as warned,
+    /// so the next pass doesn't warn about them again.
+    ///
+    /// # Parameters
+    /// - `policy`: The `AgePolicy` to apply.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    /// - `history_config`: The `TableConfig` of the history table stale alerts are archived to.
+    /// - `notifiers`: The channels the warnings are sent through.
+    ///
+    /// # Returns
+    /// An `AgeReport` with the stale and archived alerts and any failures.
+    ///
+    /// # Errors
+    /// Returns an error if the stale alerts cannot be fetched.
+    pub async fn enforce_age_policy(
+        &self,
+        policy: &AgePolicy,
+        config: &TableConfig,
+        history_config: &TableConfig,
+        notifiers: &[&dyn Notifier]
+    ) -> Result<AgeReport, Box<dyn Error + Send + Sync>> {
+        let now: DateTime<Utc> = Utc::now();
+        let mut report: AgeReport = AgeReport {
+            stale: self.fetch_stale_alerts(policy, config, now).await?,
+            ..AgeReport::default()
+        };
+
+        for stale in &report.stale {
+            let hash: &str = &stale.alert.hash;
+            let mut warned: bool = true;
+            for notifier in notifiers {
+                if let Err(e) = notifier.notify_stale(&stale.alert, now - stale.created_at).await {
+                    report.errors.push((hash.to_string(), format!("{}: {}", notifier.name(), e)));
+                    warned = false;
+                }
+            }
+
+            match (warned, policy.auto_archive) {
+                (false, _) => {},
+                (true, true) => match self.archive_alert(hash, config, history_config).await {
+                    Ok(_) => report.archived.push(hash.to_string()),
+                    Err(e) => report.errors.push((hash.to_string(), e.to_string())),
+                },
+                (true, false) => {
+                    let body: Value = json!({ config.stale_warned_at_column_name.clone(): now.to_rfc3339() });
+                    if let Err(e) = self.rest_update(&config.tablename, &[eq(&config.hash_column_name, hash)], &body).await {
+                        report.errors.push((hash.to_string(), e));
+                    }
+                },
+            }
+        }
+
+        Ok(report)
+    }
+}
@@ -1,11 +1,16 @@
 //! ## Datbase Authentication
 
 use std::env::var;
+use std::fmt;
+use std::future::Future;
 
-use dotenv::dotenv;
 use supabase_rs::SupabaseClient;
 
-use crate::db::Supabase;
+use crate::db::{DistancePolicy, DuplicatePolicy, QuotaPolicy, Supabase};
+use crate::retry::RetryPolicy;
+use crate::timeout::Timeouts;
+use crate::utils::crypto::{ENCRYPTION_KEY_SECRET, FieldCipher};
+use crate::utils::secrets::{EnvSecrets, SecretsProvider};
 
 impl Supabase {
     /// ## New
@@ -28,7 +33,74 @@ impl Supabase {
         key: String,
         url: String)
         -> Self {
-        Self { key, url }
+        Self {
+            key,
+            url,
+            cipher: None,
+            quota: None,
+            duplicates: DuplicatePolicy::default(),
+            min_distance: None,
+            retry: RetryPolicy::default(),
+            timeouts: Timeouts::default(),
+            private_webhooks: false,
+        }
+    }
+
+    /// ## With Cipher
+    /// Enables field-level encryption for the columns listed in `TableConfig::encrypted_columns`
+    ///
+    /// ### Usage example
+    /// ```rust
+    /// use trade_alerts::db::Supabase;
+    /// use trade_alerts::utils::crypto::FieldCipher;
+    ///
+    /// let cipher = FieldCipher::new(&[7u8; 32]).unwrap();
+    /// let supabase = Supabase::new("key".to_string(), "url".to_string()).with_cipher(cipher);
+    /// ```
+    pub fn with_cipher(
+        mut self,
+        cipher: FieldCipher
+    ) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// ## With Retry Policy
+    /// Sets how failed reads, updates and deletes are retried, inserts are never retried
+    ///
+    /// ### Usage example
+    /// ```rust
+    /// use trade_alerts::db::Supabase;
+    /// use trade_alerts::retry::RetryPolicy;
+    ///
+    /// let supabase = Supabase::new("key".to_string(), "url".to_string()).with_retry_policy(RetryPolicy::none());
+    /// ```
+    pub fn with_retry_policy(
+        mut self,
+        retry: RetryPolicy
+    ) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// ## With Timeouts
+    /// Sets the connect and read timeouts of every request, a call timing out is retried like any other failure
+    ///
+    /// ### Usage example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use trade_alerts::db::Supabase;
+    /// use trade_alerts::timeout::Timeouts;
+    ///
+    /// let timeouts = Timeouts::new(Duration::from_secs(2), Duration::from_secs(5));
+    /// let supabase = Supabase::new("key".to_string(), "url".to_string()).with_timeouts(timeouts);
+    /// ```
+    pub fn with_timeouts(
+        mut self,
+        timeouts: Timeouts
+    ) -> Self {
+        self.timeouts = timeouts;
+        self
     }
 
     /// ## New Env
@@ -39,18 +111,68 @@ impl Supabase {
     /// SUPABASE_URL=your_url_here
     /// ```
     ///
+    /// If `TRADE_ALERTS_ENCRYPTION_KEY` is set, field-level encryption is enabled as well,
+    /// and if `MAX_ACTIVE_ALERTS_PER_USER` is set, a `QuotaPolicy` is applied.
+    /// `DUPLICATE_POLICY` (`reject`, `replace` or `allow`) sets the `DuplicatePolicy`.
+    /// `MIN_ALERT_DISTANCE` (e.g. `5pips` or `0.1%`) rejects alerts closer to the price, or only
+    /// warns about them with `MIN_ALERT_DISTANCE_ACTION=warn`, see [`DistancePolicy`].
+    /// `SUPABASE_RETRY_ATTEMPTS` sets the attempts of the default `RetryPolicy`.
+    /// `SUPABASE_CONNECT_TIMEOUT` and `SUPABASE_READ_TIMEOUT` override the default `Timeouts`.
+    ///
     /// ### Errors
     /// - This function will panic if the key or url is not found in the `.env` file
+    /// - Returns an error if the encryption key is set but invalid
+    /// - Returns an error if `MAX_ACTIVE_ALERTS_PER_USER` is not a number
+    /// - Returns an error if `DUPLICATE_POLICY` is not a known policy
+    /// - Returns an error if `MIN_ALERT_DISTANCE` is not a distance in pips or percent
+    /// - Returns an error if `SUPABASE_RETRY_ATTEMPTS` is not a number
+    /// - Returns an error if the timeouts are not valid durations
     pub async fn new_env() 
     -> Result<Self, Box<dyn std::error::Error>> {
 
         let key = var("SUPABASE_KEY").map_err(|e| format!("SUPABASE_KEY error: {}", e))?;
         let url = var("SUPABASE_URL").map_err(|e| format!("SUPABASE_URL error: {}", e))?;
 
-        Ok(Self { key, url })
+        let cipher = match EnvSecrets.get_secret(ENCRYPTION_KEY_SECRET) {
+            Some(_) => Some(FieldCipher::from_secrets(&EnvSecrets)?),
+            None => None,
+        };
+
+        let quota = match var("MAX_ACTIVE_ALERTS_PER_USER") {
+            Ok(max) => Some(QuotaPolicy {
+                max_active_alerts_per_user: max
+                    .parse()
+                    .map_err(|e| format!("MAX_ACTIVE_ALERTS_PER_USER error: {}", e))?,
+            }),
+            Err(_) => None,
+        };
+
+        let duplicates = match var("DUPLICATE_POLICY") {
+            Ok(policy) => policy.parse().map_err(|e| format!("DUPLICATE_POLICY error: {}", e))?,
+            Err(_) => DuplicatePolicy::default(),
+        };
+
+        let min_distance = match var("MIN_ALERT_DISTANCE") {
+            Ok(distance) => Some(DistancePolicy {
+                min_distance: distance.parse().map_err(|e| format!("MIN_ALERT_DISTANCE error: {}", e))?,
+                reject: !var("MIN_ALERT_DISTANCE_ACTION").is_ok_and(|action| action.eq_ignore_ascii_case("warn")),
+            }),
+            Err(_) => None,
+        };
+
+        let retry = match var("SUPABASE_RETRY_ATTEMPTS") {
+            Ok(attempts) => RetryPolicy::default().with_max_attempts(
+                attempts.parse().map_err(|e| format!("SUPABASE_RETRY_ATTEMPTS error: {}", e))?,
+            ),
+            Err(_) => RetryPolicy::default(),
+        };
+
+        let timeouts = Timeouts::from_env("SUPABASE")?;
+
+        Ok(Self { key, url, cipher, quota, duplicates, min_distance, retry, timeouts, private_webhooks: false })
     }
     /// ## Authenticate the Supabase client
-    /// This function authenticates the Supabase client
+    /// This function authenticates the Supabase client with the key and url of this instance
     /// It returns a `SupabaseClient` instance
     ///
     /// ### Usage example
@@ -58,11 +180,62 @@ impl Supabase {
     pub async fn authenticate(
         &self
     ) -> SupabaseClient {
-        dotenv().ok();
-
-        let supabase_client: SupabaseClient =
-            SupabaseClient::new(var("SUPABASE_URL").unwrap(), var("SUPABASE_KEY").unwrap());
+        let supabase_client: SupabaseClient = SupabaseClient::new(self.url.clone(), self.key.clone());
 
         supabase_client
     }
+
+    /// Runs a read or idempotent write according to the `RetryPolicy`, retrying only
+    /// [transient](is_transient) errors. Every attempt is bounded by the total of the `Timeouts`.
+    pub(crate) async fn retrying<T, F, Fut>(
+        &self,
+        mut operation: F
+    ) -> Result<T, String>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        self.retry.run(|| self.within_timeouts(operation()), |e: &String| is_transient(e)).await
+    }
+
+    /// Runs a single request, bounded by the total of the `Timeouts`. Used for the `supabase_rs`
+    /// calls which are never retried, such as inserts, as its client has no timeouts of its own.
+    pub(crate) async fn within_timeouts<T>(
+        &self,
+        request: impl Future<Output = Result<T, String>>
+    ) -> Result<T, String> {
+        let limit = self.timeouts.total();
+        tokio::time::timeout(limit, request)
+            .await
+            .unwrap_or_else(|_| Err(format!("Request timed out after {:?}", limit)))
+    }
+}
+
+/// Whether a failed request is worth another attempt: network errors, timeouts, `429` and `5xx`.
+///
+/// Failed responses read `<status>: <body>`, or just the status from `supabase_rs`.
+pub(crate) fn is_transient(error: &str) -> bool {
+    match error.get(..3).and_then(|code| code.parse::<u16>().ok()) {
+        Some(status) => status == 429 || (500..600).contains(&status),
+        None => ["error sending request", "request or response body error", "Request timed out"]
+            .iter()
+            .any(|prefix| error.starts_with(prefix)),
+    }
+}
+
+/// Debug implementation for `Supabase` which never prints the key.
+impl fmt::Debug for Supabase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Supabase")
+            .field("key", &"<redacted>")
+            .field("url", &self.url)
+            .field("cipher", &self.cipher)
+            .field("quota", &self.quota)
+            .field("duplicates", &self.duplicates)
+            .field("min_distance", &self.min_distance)
+            .field("retry", &self.retry)
+            .field("timeouts", &self.timeouts)
+            .field("private_webhooks", &self.private_webhooks)
+            .finish()
+    }
 }
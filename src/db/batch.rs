@@ -0,0 +1,275 @@
+//! ## Bulk alert creation
+//!
+//! Inserts many alerts with a handful of requests instead of one round-trip
+//! (and one price request) per alert.
+
+use std::collections::{HashMap, HashSet};
+use std::env;
+
+use serde_json::Value;
+use supabase_rs::generate_random_id;
+
+use crate::Alert;
+use crate::data::{PriceProvider, XylexApi};
+use crate::db::rest::is_in;
+use crate::db::{BatchInsertion, DuplicatePolicy, Supabase, TableConfig};
+
+/// Number of rows sent per insert request.
+pub const BATCH_CHUNK_SIZE: usize = 100;
+
+impl Supabase {
+    /// Adds many alerts at once.
+    ///
+    /// Prices are fetched once per symbol, existing hashes are looked up with a single query
+    /// and new rows are inserted in chunks of `BATCH_CHUNK_SIZE`. If a chunk is rejected, its
+    /// rows are retried one by one so the failure can be attributed to the offending alert.
+    ///
+    /// Alerts whose hash already exists, or is repeated within the batch, are not inserted.
+    /// A `QuotaPolicy` is enforced per user across the whole batch, and a `DistancePolicy`
+    /// against the price fetched for each symbol.
+    ///
+    /// The `DuplicatePolicy` applies as in `add_alert`, and also between identical alerts of
+    /// the same batch: with `Reject` only the first of them is inserted, with `Replace` only
+    /// the last one, and the others are reported as not inserted.
+    ///
+    /// # Parameters
+    /// - `alerts`: The alerts to add.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// One `BatchInsertion` per alert, in the order the alerts were given.
+    pub async fn add_alerts_batch(
+        &self,
+        alerts: Vec<Alert>,
+        config: TableConfig
+    ) -> Vec<BatchInsertion> {
+        match (env::var("XYLEX_KEY"), env::var("XYLEX_URL")) {
+            (Ok(key), Ok(url)) => self.add_alerts_batch_with(&XylexApi::new(key, url), alerts, config).await,
+            _ => fail_all(
+                alerts.iter().map(|alert| BatchInsertion { hash: alert.hash.clone(), error: None }).collect(),
+                "XYLEX_KEY and XYLEX_URL must be set",
+            ),
+        }
+    }
+
+    /// Adds many alerts at once, pricing them with another `PriceProvider`.
+    ///
+    /// Behaves like `add_alerts_batch`.
+    ///
+    /// # Parameters
+    /// - `provider`: The `PriceProvider` quoting the symbols of the alerts.
+    /// - `alerts`: The alerts to add.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// One `BatchInsertion` per alert, in the order the alerts were given.
+    pub async fn add_alerts_batch_with<P: PriceProvider + ?Sized>(
+        &self,
+        provider: &P,
+        alerts: Vec<Alert>,
+        config: TableConfig
+    ) -> Vec<BatchInsertion> {
+        let mut results: Vec<BatchInsertion> = alerts
+            .iter()
+            .map(|alert| BatchInsertion { hash: alert.hash.clone(), error: None })
+            .collect();
+
+        // Duplicates within the batch and of existing alerts
+        let hashes: Vec<&str> = alerts.iter().map(|alert| alert.hash.as_str()).collect();
+        let existing: HashSet<String> = match self.existing_hashes(&hashes, &config).await {
+            Ok(existing) => existing,
+            Err(e) => return fail_all(results, &format!("Failed to check existing alerts: {}", e)),
+        };
+        let mut seen: HashSet<&str> = HashSet::new();
+        for (result, alert) in results.iter_mut().zip(&alerts) {
+            if existing.contains(&alert.hash) || !seen.insert(alert.hash.as_str()) {
+                result.error = Some("Duplicate alert".to_string());
+            }
+        }
+
+        // Quotas are counted once per user
+        if let Some(quota) = &self.quota {
+            let mut active: HashMap<&str, usize> = HashMap::new();
+            for (result, alert) in results.iter_mut().zip(&alerts) {
+                if result.error.is_some() {
+                    continue;
+                }
+                let count: usize = match active.get(alert.user_id.as_str()) {
+                    Some(count) => *count,
+                    None => match self.count_alerts_by_user_id(&alert.user_id, &config).await {
+                        Ok(count) => count,
+                        Err(e) => {
+                            result.error = Some(e.to_string());
+                            continue;
+                        },
+                    },
+                };
+                if count >= quota.max_active_alerts_per_user {
+                    result.error = Some(format!(
+                        "Quota exceeded: user already has {} of {} active alerts",
+                        count, quota.max_active_alerts_per_user
+                    ));
+                    active.insert(&alert.user_id, count);
+                } else {
+                    active.insert(&alert.user_id, count + 1);
+                }
+            }
+        }
+
+        // Prices are fetched once per symbol
+        let mut prices: HashMap<String, Result<f64, String>> = HashMap::new();
+        for (result, alert) in results.iter().zip(&alerts) {
+            if result.error.is_none() && !prices.contains_key(&alert.symbol) {
+                let price = provider.fetch_price(&alert.symbol).await.map(|quote| quote.price).map_err(|e| e.to_string());
+                prices.insert(alert.symbol.clone(), price);
+            }
+        }
+
+        // Rows to insert, with the index of the alert they belong to
+        let mut rows: Vec<(usize, Value)> = Vec::new();
+        // Existing alerts each row replaces, keyed by the index of its alert
+        let mut replaced: HashMap<usize, Vec<Value>> = HashMap::new();
+        // Index of the row of each identical alert, keyed by user, symbol, level and direction
+        let mut identical: HashMap<Vec<String>, usize> = HashMap::new();
+        for (index, alert) in alerts.into_iter().enumerate() {
+            if results[index].error.is_some() {
+                continue;
+            }
+            let price: f64 = match &prices[&alert.symbol] {
+                Ok(price) => *price,
+                Err(e) => {
+                    results[index].error = Some(e.clone());
+                    continue;
+                },
+            };
+            if let Err(e) = self.check_distance(&alert, price) {
+                results[index].error = Some(e.to_string());
+                continue;
+            }
+            let mut row: Value = match self.alert_row(alert, price, &config) {
+                Ok(row) => row,
+                Err(e) => {
+                    results[index].error = Some(e.to_string());
+                    continue;
+                },
+            };
+            match self.resolve_duplicates(&row, &config).await {
+                Ok(duplicates) => {
+                    replaced.insert(index, duplicates);
+                },
+                Err(e) => {
+                    results[index].error = Some(e.to_string());
+                    continue;
+                },
+            }
+
+            if self.duplicates != DuplicatePolicy::Allow {
+                let key: Vec<String> = identity(&row, &config);
+                match (identical.get(&key).copied(), self.duplicates) {
+                    (Some(first), DuplicatePolicy::Reject) => {
+                        results[index].error =
+                            Some(format!("Duplicate alert: identical to {}", results[first].hash));
+                        continue;
+                    },
+                    (Some(previous), _) => {
+                        results[previous].error =
+                            Some(format!("Duplicate alert: replaced by {}", results[index].hash));
+                        rows.retain(|(row_index, _)| *row_index != previous);
+                        replaced.remove(&previous);
+                    },
+                    (None, _) => {},
+                }
+                identical.insert(key, index);
+            }
+
+            row["id"] = Value::from(generate_random_id());
+            rows.push((index, row));
+        }
+
+        for chunk in rows.chunks(BATCH_CHUNK_SIZE) {
+            let bodies: Vec<Value> = chunk.iter().map(|(_, row)| row.clone()).collect();
+            match self.insert_within_quota(&bodies, &config).await {
+                Ok(inserted) => {
+                    // The database skips the rows of users who reached their quota meanwhile
+                    for (index, row) in chunk {
+                        if !inserted.iter().any(|inserted| inserted["id"] == row["id"]) {
+                            results[*index].error = Some("Quota exceeded".to_string());
+                        }
+                    }
+                },
+                Err(_) => {
+                    // Retry one by one to find out which rows were rejected
+                    for (index, row) in chunk {
+                        match self.insert_within_quota(std::slice::from_ref(row), &config).await {
+                            Ok(inserted) if inserted.is_empty() => results[*index].error = Some("Quota exceeded".to_string()),
+                            Ok(_) => {},
+                            Err(e) => results[*index].error = Some(e),
+                        }
+                    }
+                },
+            }
+
+            for (index, _) in chunk.iter().filter(|(index, _)| results[*index].is_inserted()) {
+                let duplicates: &[Value] = replaced.get(index).map(Vec::as_slice).unwrap_or_default();
+                if let Err(e) = self.replace_duplicates(duplicates, &config).await {
+                    println!("Failed to replace the alerts identical to {}: {}", results[*index].hash, e);
+                }
+            }
+        }
+
+        results
+    }
+
+    /// Returns the hashes which already exist in the table.
+    async fn existing_hashes(
+        &self,
+        hashes: &[&str],
+        config: &TableConfig
+    ) -> Result<HashSet<String>, String> {
+        let mut existing: HashSet<String> = HashSet::new();
+        for chunk in hashes.chunks(BATCH_CHUNK_SIZE) {
+            let rows: Vec<Value> = self
+                .rest_select(&config.tablename, &[is_in(&config.hash_column_name, chunk)])
+                .await?;
+            existing.extend(
+                rows.iter()
+                    .filter_map(|row| row.get(&config.hash_column_name).and_then(|v| v.as_str()))
+                    .map(String::from),
+            );
+        }
+        Ok(existing)
+    }
+}
+
+/// The columns identical alerts share: user ID, symbol, price level and initial direction.
+fn identity(
+    row: &Value,
+    config: &TableConfig
+) -> Vec<String> {
+    [
+        &config.user_id_column_name,
+        &config.symbol_column_name,
+        &config.price_level_column_name,
+        &config.direction_column_name,
+    ]
+    .into_iter()
+    .map(|column| row.get(column).map(Value::to_string).unwrap_or_default())
+    .collect()
+}
+
+fn fail_all(
+    mut results: Vec<BatchInsertion>,
+    error: &str
+) -> Vec<BatchInsertion> {
+    for result in results.iter_mut().filter(|result| result.error.is_none()) {
+        result.error = Some(error.to_string());
+    }
+    results
+}
+
+impl BatchInsertion {
+    /// Returns `true` if the alert was inserted.
+    pub fn is_inserted(&self) -> bool {
+        self.error.is_none()
+    }
+}
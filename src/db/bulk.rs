@@ -0,0 +1,77 @@
+//! ## Bulk deletion
+//!
+//! Deletes every alert matching a filter with a single request instead of
+//! fetching the hashes and deleting the alerts one at a time.
+
+use std::error::Error;
+
+use crate::db::rest::{Filter, eq};
+use crate::db::{Supabase, TableConfig};
+use crate::errors::SupabaseError;
+
+impl Supabase {
+    /// Deletes all alerts of a user.
+    ///
+    /// # Parameters
+    /// - `user_id`: The user whose alerts should be deleted.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of deleted alerts or an error.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::DeletionError` if the request fails.
+    pub async fn delete_alerts_by_user_id(
+        &self,
+        user_id: &str,
+        config: &TableConfig
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        self.delete_where(&[eq(&config.user_id_column_name, user_id)], config).await
+    }
+
+    /// Deletes all alerts on a symbol, e.g. after it has been delisted.
+    ///
+    /// # Parameters
+    /// - `symbol`: The symbol whose alerts should be deleted.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of deleted alerts or an error.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::DeletionError` if the request fails.
+    pub async fn delete_alerts_by_symbol(
+        &self,
+        symbol: &str,
+        config: &TableConfig
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        self.delete_where(&[eq(&config.symbol_column_name, symbol)], config).await
+    }
+
+    /// Deletes all alerts matching every one of the filters.
+    ///
+    /// Filters are built with the helpers in `db::rest`, e.g. `eq` and `is_in`.
+    ///
+    /// # Parameters
+    /// - `filters`: The PostgREST filters the alerts must match. May not be empty.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of deleted alerts or an error.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::DeletionError` if no filters are given or the request fails.
+    pub async fn delete_where(
+        &self,
+        filters: &[Filter],
+        config: &TableConfig
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        if filters.is_empty() {
+            return Err(Box::new(SupabaseError::DeletionError("Refusing to delete without filters".to_string())));
+        }
+        match self.rest_delete(&config.tablename, filters).await {
+            Ok(rows) => Ok(rows.len()),
+            Err(e) => Err(Box::new(SupabaseError::DeletionError(e))),
+        }
+    }
+}
@@ -11,17 +11,31 @@ use std::collections::{HashSet, HashMap};
 use dotenv::dotenv;
 use serde_json::{Value, json};
 
-use supabase_rs::SupabaseClient;
+use supabase_rs::{SupabaseClient, generate_random_id};
 
+use crate::alert::is_expired;
+use crate::db::rest::{contains, eq, gte, lte};
+use crate::db::triggering::TRIGGERING;
 use crate::db::{Supabase, TableConfig};
 use crate::errors::{SupabaseError, TableConfigError};
 use crate::success::SupabaseSuccess;
-use crate::Alert;
-use crate::data::XylexApi;
+use crate::{Alert, AlertUpdate, Condition, EvaluateOn, TriggeredAlert};
+use crate::data::{PriceProvider, XylexApi};
 
 impl Supabase {
     /// Adds an alert to the Supabase database using the provided `Alert` struct.
     ///
+    /// Alerts with a condition other than `Condition::PriceLevel` also store their
+    /// condition in the configured condition column. The expiry, metadata, tags, tolerance, upper
+    /// price level and a non-tick evaluation mode are only stored when set, in their configured columns.
+    ///
+    /// If a `QuotaPolicy` is set, the user's active alerts are counted first and
+    /// `SupabaseError::QuotaExceeded` is returned when the limit is reached. The alert is then
+    /// inserted by the `QUOTA_INSERT_FUNCTION`, so concurrent calls can't exceed the limit.
+    /// Identical alerts of the same user are handled according to the `DuplicatePolicy`, and
+    /// alerts too close to the live price according to the `DistancePolicy`, returning
+    /// `SupabaseError::TooCloseToMarket` if it rejects them.
+    ///
     /// # Parameters
     /// - `alert`: An instance of the `Alert` struct containing all necessary data.
     ///
@@ -32,39 +46,63 @@ impl Supabase {
         alert: Alert, 
         config: TableConfig
     ) -> Result<SupabaseSuccess, Box<dyn Error + Send + Sync>> {
-        let supabase: SupabaseClient = Supabase::authenticate(&self).await;
-
-        let mut direction: String = "sell".to_string();
-        let symbol: String = alert.symbol.clone();
-
         let realtime_price: XylexApi = XylexApi::new(
             env::var("XYLEX_KEY").unwrap(),
             env::var("XYLEX_URL").unwrap()
         );
 
-        let price: f64 = realtime_price.request_real_time_price(&symbol).await?;
+        self.add_alert_with(&realtime_price, alert, config).await
+    }
+
+    /// Adds an alert, checking it against the price quoted by another `PriceProvider`.
+    ///
+    /// Behaves like `add_alert`.
+    ///
+    /// # Parameters
+    /// - `provider`: The `PriceProvider` quoting the symbol of the alert.
+    /// - `alert`: An instance of the `Alert` struct containing all necessary data.
+    ///
+    /// # Returns
+    /// A `Result` indicating success or error in insertion.
+    pub async fn add_alert_with<P: PriceProvider + ?Sized>(
+        &self,
+        provider: &P,
+        alert: Alert,
+        config: TableConfig
+    ) -> Result<SupabaseSuccess, Box<dyn Error + Send + Sync>> {
+        self.check_quota(&alert.user_id, &config).await?;
+
+        let supabase: SupabaseClient = Supabase::authenticate(self).await;
+
+        let price: f64 = provider.fetch_price(&alert.symbol).await?.price;
+        self.check_distance(&alert, price)?;
+
+        let body: Value = self.alert_row(alert, price, &config)?;
+        let replaced: Vec<Value> = self.resolve_duplicates(&body, &config).await?;
+
+        let response: Result<String, String> = match &self.quota {
+            Some(quota) => {
+                let mut body: Value = body;
+                body["id"] = Value::from(generate_random_id());
+                match self.insert_within_quota(std::slice::from_ref(&body), &config).await {
+                    Ok(inserted) if inserted.is_empty() => {
+                        return Err(Box::new(SupabaseError::QuotaExceeded(format!(
+                            "user reached the limit of {} active alerts",
+                            quota.max_active_alerts_per_user
+                        ))));
+                    },
+                    Ok(_) => Ok(body["id"].to_string()),
+                    Err(e) => Err(e),
+                }
+            },
+            None => self.within_timeouts(supabase.insert_if_unique(&config.tablename, body)).await,
+        };
 
-        if price > alert.price_level {
-            direction = "buy".to_string();
-        }
-    
-        let response: Result<String, String> = supabase
-            .insert_if_unique(
-                &config.tablename,
-                json!({
-                    config.hash_column_name: alert.hash,
-                    config.price_level_column_name: alert.price_level,
-                    config.user_id_column_name: alert.user_id,
-                    config.symbol_column_name: alert.symbol,
-                    "initial_direction": direction,
-                    "hit": false,
-                    "latest_price": price
-                }),
-            )
-            .await;
-    
         match response {
-            Ok(_) => Ok(SupabaseSuccess::InsertionSuccess),
+            Ok(_) => {
+                self.replace_duplicates(&replaced, &config).await?;
+                Ok(SupabaseSuccess::InsertionSuccess)
+            },
             Err(e) => Err(Box::new(SupabaseError::InsertionError(e)))
         }
     }
@@ -89,7 +127,7 @@ impl Supabase {
         config: TableConfig
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
 
-        let supabase: SupabaseClient = Supabase::authenticate(&self).await;
+        let supabase: SupabaseClient = Supabase::authenticate(self).await;
     
         let id_result = self.fetch_id_with_hash(
             hash,
@@ -98,7 +136,8 @@ impl Supabase {
 
         match id_result {
             Ok(id) => {
-                let delete_result = supabase.delete(&config.tablename, &id.to_string()).await;
+                let id: String = id.to_string();
+                let delete_result = self.retrying(|| supabase.delete(&config.tablename, &id)).await;
                 match delete_result {
                     Ok(_) => Ok(()),
                     Err(e) => Err(Box::new(SupabaseError::DeletionError(e)))
@@ -108,6 +147,71 @@ impl Supabase {
         }
     }
 
+    /// Updates an existing alert in the Supabase database using the provided hash.
+    ///
+    /// Only the fields set on the `AlertUpdate` are written, every other column is left untouched.
+    /// Changing the price level does not recompute the initial direction; set it explicitly
+    /// if the new level is on the other side of the current price.
+    ///
+    /// # Parameters
+    /// - `hash`: The hash of the alert to be updated.
+    /// - `update`: An `AlertUpdate` with the fields to change.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// A `Result` indicating success or error in the update.
+    ///
+    /// The alert's version is incremented, see `update_alert_if_version`.
+    ///
+    /// # Errors
+    /// Returns an error if fetching the alert or updating it fails.
+    pub async fn update_alert_by_hash(
+        &self,
+        hash: &str,
+        update: AlertUpdate,
+        config: TableConfig
+    ) -> Result<SupabaseSuccess, Box<dyn Error + Send + Sync>> {
+        if update.is_empty() {
+            return Ok(SupabaseSuccess::UpdateSuccess);
+        }
+
+        let mut body: Value = update.to_value(&config);
+        self.seal_row(&config, &mut body)?;
+
+        self.write_alert(hash, body, &config).await
+    }
+
+    /// Records that an alert triggered, marking it as hit and storing when it triggered
+    /// and the price that tripped it, before it is deleted or archived. The provider's quote time
+    /// is stored as well when known.
+    ///
+    /// # Parameters
+    /// - `alert`: The triggered alert.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// A `Result` indicating success or error in the update.
+    ///
+    /// # Errors
+    /// Returns an error if fetching or updating the alert fails, e.g. when the
+    /// table has no columns for the trigger timestamp and price.
+    pub async fn record_trigger(
+        &self,
+        alert: &TriggeredAlert,
+        config: &TableConfig
+    ) -> Result<SupabaseSuccess, Box<dyn Error + Send + Sync>> {
+        let mut body: Value = json!({
+            "hit": true,
+            config.triggered_at_column_name.clone(): alert.triggered_at.to_rfc3339(),
+            config.trigger_price_column_name.clone(): alert.trigger_price,
+        });
+        if let Some(quote_time) = alert.quote_time {
+            body[&config.quote_time_column_name] = json!(quote_time.to_rfc3339());
+        }
+
+        self.write_alert(&alert.hash, body, config).await
+    }
+
     /// Fetches all hashes for a given user ID from the Supabase database.
     ///
     /// # Parameters
@@ -125,12 +229,15 @@ impl Supabase {
         config: TableConfig
     ) -> Result<(Vec<String>, SupabaseSuccess), Box<dyn Error + Send + Sync>> {
         
-        let supabase: SupabaseClient = Supabase::authenticate(&self).await;
+        let supabase: SupabaseClient = Supabase::authenticate(self).await;
     
-        let response: Result<Vec<Value>, String> = supabase
-            .select(&config.tablename)
-            .eq(&config.user_id_column_name, user_id)
-            .execute()
+        let response: Result<Vec<Value>, String> = self
+            .retrying(|| {
+                supabase
+                    .select(&config.tablename)
+                    .eq(&config.user_id_column_name, user_id)
+                    .execute()
+            })
             .await;
     
         match response {
@@ -197,17 +304,22 @@ impl Supabase {
         hash: &str,
         config: &TableConfig
     ) -> Result<(String, String, String, SupabaseSuccess), Box<dyn Error + Send + Sync>> {
-        let supabase: SupabaseClient = Supabase::authenticate(&self).await;
+        let supabase: SupabaseClient = Supabase::authenticate(self).await;
     
-        let response: Result<Vec<Value>, String> = supabase
-            .select(&config.tablename)
-            .eq(&config.hash_column_name, hash)
-            .execute()
+        let response: Result<Vec<Value>, String> = self
+            .retrying(|| {
+                supabase
+                    .select(&config.tablename)
+                    .eq(&config.hash_column_name, hash)
+                    .execute()
+            })
             .await;
         
         match response {
-            Ok(values) => {
-                if let Some(value) = values.first() {
+            Ok(mut values) => {
+                if let Some(value) = values.first_mut() {
+                    self.open_row(config, value)?;
+
                     let user_id = value.get(&config.user_id_column_name)
                         .and_then(|v| v.as_str())
                         .map(String::from)
@@ -232,6 +344,200 @@ impl Supabase {
         }
     }
 
+    /// Fetches the alert stored under the given hash from the Supabase database.
+    ///
+    /// # Parameters
+    /// - `hash`: The hash of the alert to fetch.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// A `Result` containing the `Alert` or an error.
+    ///
+    /// # Errors
+    /// Returns an error if the query execution fails, no alert exists with the hash or the row is incomplete.
+    pub async fn fetch_alert_by_hash(
+        &self,
+        hash: &str,
+        config: &TableConfig
+    ) -> Result<Alert, Box<dyn Error + Send + Sync>> {
+        let supabase: SupabaseClient = Supabase::authenticate(self).await;
+
+        let response: Result<Vec<Value>, String> = self
+            .retrying(|| {
+                supabase
+                    .select(&config.tablename)
+                    .eq(&config.hash_column_name, hash)
+                    .execute()
+            })
+            .await;
+
+        match response {
+            Ok(mut values) => match values.first_mut() {
+                Some(value) => {
+                    self.open_row(config, value)?;
+                    Ok(Alert::from_row(value, config)?)
+                },
+                None => Err(Box::new(SupabaseError::FetchError("No results found".to_string())))
+            },
+            Err(e) => Err(Box::new(SupabaseError::FetchError(e)))
+        }
+    }
+
+    /// Fetches all alerts belonging to a user from the Supabase database.
+    ///
+    /// # Parameters
+    /// - `user_id`: The user ID for which to fetch alerts.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// A `Result` containing a vector of `Alert`s or an error.
+    ///
+    /// # Errors
+    /// Returns an error if the query execution fails or any of the rows is incomplete.
+    pub async fn fetch_alerts_by_user_id(
+        &self,
+        user_id: &str,
+        config: &TableConfig
+    ) -> Result<Vec<Alert>, Box<dyn Error + Send + Sync>> {
+        let supabase: SupabaseClient = Supabase::authenticate(self).await;
+
+        let response: Result<Vec<Value>, String> = self
+            .retrying(|| {
+                supabase
+                    .select(&config.tablename)
+                    .eq(&config.user_id_column_name, user_id)
+                    .execute()
+            })
+            .await;
+
+        match response {
+            Ok(values) => {
+                let mut alerts: Vec<Alert> = Vec::with_capacity(values.len());
+                for mut value in values {
+                    self.open_row(config, &mut value)?;
+                    alerts.push(Alert::from_row(&value, config)?);
+                }
+                Ok(alerts)
+            },
+            Err(e) => Err(Box::new(SupabaseError::FetchError(e)))
+        }
+    }
+
+    /// Fetches the alerts of a user which can still trigger.
+    ///
+    /// Expired alerts, which includes those disabled with `AlertOp::Disable`, alerts which
+    /// already triggered and alerts being triggered are left out.
+    ///
+    /// # Errors
+    /// Returns an error if the query execution fails or a row cannot be parsed.
+    pub async fn fetch_active_alerts_by_user_id(
+        &self,
+        user_id: &str,
+        config: &TableConfig
+    ) -> Result<Vec<Alert>, Box<dyn Error + Send + Sync>> {
+        let rows: Vec<Value> = self
+            .rest_select(&config.tablename, &[eq(&config.user_id_column_name, user_id)])
+            .await
+            .map_err(SupabaseError::FetchError)?;
+
+        let mut alerts: Vec<Alert> = Vec::with_capacity(rows.len());
+        for mut row in rows {
+            let hit: bool = row.get("hit").and_then(|v| v.as_bool()) == Some(true);
+            let triggering: bool = row.get(&config.trigger_state_column_name).and_then(|v| v.as_str()) == Some(TRIGGERING);
+            if hit || triggering || is_expired(row.get(&config.expiry_column_name)) {
+                continue;
+            }
+            self.open_row(config, &mut row)?;
+            alerts.push(Alert::from_row(&row, config)?);
+        }
+        Ok(alerts)
+    }
+
+    /// Fetches the alerts of a user carrying a tag.
+    ///
+    /// # Parameters
+    /// - `user_id`: The user ID for which to fetch alerts.
+    /// - `tag`: The tag to filter on, e.g. `breakout`.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// A `Result` containing a vector of `Alert`s or an error.
+    ///
+    /// # Errors
+    /// Returns an error if the query execution fails or any of the rows is incomplete.
+    pub async fn fetch_alerts_by_tag(
+        &self,
+        user_id: &str,
+        tag: &str,
+        config: &TableConfig
+    ) -> Result<Vec<Alert>, Box<dyn Error + Send + Sync>> {
+        let filters = [
+            eq(&config.user_id_column_name, user_id),
+            contains(&config.tags_column_name, &[tag]),
+        ];
+
+        let values: Vec<Value> = self
+            .rest_select(&config.tablename, &filters)
+            .await
+            .map_err(SupabaseError::FetchError)?;
+
+        let mut alerts: Vec<Alert> = Vec::with_capacity(values.len());
+        for mut value in values {
+            self.open_row(config, &mut value)?;
+            alerts.push(Alert::from_row(&value, config)?);
+        }
+
+        Ok(alerts)
+    }
+
+    /// Fetches the alerts of a user on a symbol whose price level lies within `tolerance` of `price_level`.
+    ///
+    /// Meant to warn users before they create an alert that is nearly identical to one they
+    /// already have. The range is filtered server-side, so the price level column must not be encrypted.
+    ///
+    /// # Parameters
+    /// - `user_id`: The user ID for which to fetch alerts.
+    /// - `symbol`: The symbol of the new alert.
+    /// - `price_level`: The price level of the new alert.
+    /// - `tolerance`: The maximum absolute distance between the price levels, e.g. `0.0005` for 5 pips on EURUSD.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// A `Result` containing the similar alerts, nearest price level first, or an error.
+    ///
+    /// # Errors
+    /// Returns an error if the query execution fails or any of the rows is incomplete.
+    pub async fn find_similar_alerts(
+        &self,
+        user_id: &str,
+        symbol: &str,
+        price_level: f64,
+        tolerance: f64,
+        config: &TableConfig
+    ) -> Result<Vec<Alert>, Box<dyn Error + Send + Sync>> {
+        let tolerance: f64 = tolerance.abs();
+        let filters = [
+            eq(&config.user_id_column_name, user_id),
+            eq(&config.symbol_column_name, symbol),
+            gte(&config.price_level_column_name, price_level - tolerance),
+            lte(&config.price_level_column_name, price_level + tolerance),
+        ];
+
+        let values: Vec<Value> = self
+            .rest_select(&config.tablename, &filters)
+            .await
+            .map_err(SupabaseError::FetchError)?;
+
+        let mut alerts: Vec<Alert> = Vec::with_capacity(values.len());
+        for mut value in values {
+            self.open_row(config, &mut value)?;
+            alerts.push(Alert::from_row(&value, config)?);
+        }
+        alerts.sort_by(|a, b| (a.price_level - price_level).abs().total_cmp(&(b.price_level - price_level).abs()));
+
+        Ok(alerts)
+    }
+
     /// Fetches all unique symbols from the Supabase database.
     ///
     /// # Parameters
@@ -246,11 +552,10 @@ impl Supabase {
         &self,
         config: &TableConfig
     ) -> Result<(HashSet<String>, SupabaseSuccess), Box<dyn Error + Send + Sync>> {
-        let supabase: SupabaseClient = Supabase::authenticate(&self).await;
+        let supabase: SupabaseClient = Supabase::authenticate(self).await;
     
-        let response: Result<Vec<Value>, String> = supabase
-            .select(&config.tablename)
-            .execute()
+        let response: Result<Vec<Value>, String> = self
+            .retrying(|| supabase.select(&config.tablename).execute())
             .await;
     
         match response {
@@ -283,18 +588,18 @@ impl Supabase {
         &self,
         config: &TableConfig
     ) -> Result<Vec<HashMap<String, Value>>, Box<dyn Error + Send + Sync>> {
-        let supabase = Supabase::authenticate(&self).await;
+        let supabase = Supabase::authenticate(self).await;
 
-        let response: Result<Vec<Value>, String> = supabase
-            .select(&config.tablename)
-            .execute()
+        let response: Result<Vec<Value>, String> = self
+            .retrying(|| supabase.select(&config.tablename).execute())
             .await;
 
         // Convert Vec<Value> to Vec<HashMap<String, Value>>
         match response {
             Ok(values) => {
                 let mut hash_maps = Vec::new();
-                for value in values {
+                for mut value in values {
+                    self.open_row(config, &mut value)?;
                     if let Value::Object(map) = value {
                         let hash_map: HashMap<String, Value> = map.into_iter().collect();
                         hash_maps.push(hash_map);
@@ -326,12 +631,15 @@ impl Supabase {
         hash: &str,
         config: TableConfig
     ) -> Result<i64, Box<dyn Error + Send + Sync>> {
-        let supabase = Supabase::authenticate(&self).await;
-
-        let response: Result<Vec<Value>, String> = supabase
-            .select(&config.tablename)
-            .eq(&config.hash_column_name, hash)
-            .execute()
+        let supabase = Supabase::authenticate(self).await;
+
+        let response: Result<Vec<Value>, String> = self
+            .retrying(|| {
+                supabase
+                    .select(&config.tablename)
+                    .eq(&config.hash_column_name, hash)
+                    .execute()
+            })
             .await;
 
         match response {
@@ -354,6 +662,96 @@ impl Supabase {
             Err(e) => Err(Box::new(SupabaseError::FetchError(e)))
         }
     }
+
+    /// Builds the row stored for a new alert, with its direction computed from the current price.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::EncryptionError` if encrypted columns are configured without a cipher.
+    pub(crate) fn alert_row(
+        &self,
+        alert: Alert,
+        price: f64,
+        config: &TableConfig
+    ) -> Result<Value, SupabaseError> {
+        let direction: &str = if price > alert.price_level { "buy" } else { "sell" };
+
+        let mut body: Value = json!({
+            config.hash_column_name.clone(): alert.hash,
+            config.price_level_column_name.clone(): alert.price_level,
+            config.user_id_column_name.clone(): alert.user_id,
+            config.symbol_column_name.clone(): alert.symbol,
+            config.direction_column_name.clone(): direction,
+            "hit": false,
+            "latest_price": price
+        });
+
+        // Optional fields are only stored when set so existing tables keep working
+        if alert.condition != Condition::PriceLevel {
+            body[&config.condition_column_name] = alert.condition.to_value();
+        }
+        if let Some(expires_at) = alert.expires_at {
+            body[&config.expiry_column_name] = json!(expires_at.to_rfc3339());
+        }
+        if let Some(metadata) = alert.metadata {
+            body[&config.metadata_column_name] = metadata;
+        }
+        if alert.evaluate_on != EvaluateOn::Tick {
+            body[&config.evaluate_on_column_name] = alert.evaluate_on.to_value();
+        }
+        if !alert.tags.is_empty() {
+            body[&config.tags_column_name] = json!(alert.tags);
+        }
+        if let Some(tolerance) = alert.tolerance {
+            body[&config.tolerance_column_name] = tolerance.to_value();
+        }
+        if let Some(upper_price_level) = alert.upper_price_level {
+            body[&config.upper_price_level_column_name] = json!(upper_price_level);
+        }
+        if !alert.sessions.is_empty() {
+            body[&config.sessions_column_name] = json!(alert.sessions);
+        }
+
+        self.seal_row(config, &mut body)?;
+        Ok(body)
+    }
+
+    /// Encrypts the columns listed in `TableConfig::encrypted_columns` before a row is written.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::EncryptionError` if encrypted columns are configured without a cipher,
+    /// so plaintext is never written to those columns.
+    pub(crate) fn seal_row(
+        &self,
+        config: &TableConfig,
+        row: &mut Value
+    ) -> Result<(), SupabaseError> {
+        if config.encrypted_columns.is_empty() {
+            return Ok(());
+        }
+        match &self.cipher {
+            Some(cipher) => cipher.encrypt_columns(row, &config.encrypted_columns),
+            None => Err(SupabaseError::EncryptionError(
+                "Encrypted columns are configured but no cipher is set".to_string()
+            )),
+        }
+    }
+
+    /// Decrypts the columns listed in `TableConfig::encrypted_columns` after a row is fetched.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::EncryptionError` if a column cannot be decrypted.
+    pub(crate) fn open_row(
+        &self,
+        config: &TableConfig,
+        row: &mut Value
+    ) -> Result<(), SupabaseError> {
+        match &self.cipher {
+            Some(cipher) if !config.encrypted_columns.is_empty() => {
+                cipher.decrypt_columns(row, &config.encrypted_columns)
+            },
+            _ => Ok(()),
+        }
+    }
 }
 
 
@@ -368,6 +766,8 @@ impl TableConfig {
     /// - `user_id_column_name`: The column name for user IDs.
     /// - `symbol_column_name`: The column name for symbols.
     ///
+    /// All other columns use the names from `TableConfig::default()` and no columns are encrypted.
+    ///
     /// # Returns
     /// Returns a `TableConfig` instance with the specified values.
     pub fn new(
@@ -383,17 +783,7 @@ impl TableConfig {
             price_level_column_name,
             user_id_column_name,
             symbol_column_name,
-        }
-    }
-
-
-    pub fn default() -> Self {
-        TableConfig {
-            tablename: "alerts".to_string(),
-            hash_column_name: "hash".to_string(),
-            price_level_column_name: "price_level".to_string(),
-            user_id_column_name: "user_id".to_string(),
-            symbol_column_name: "symbol".to_string(),
+            ..TableConfig::default()
         }
     }
 
@@ -413,6 +803,25 @@ impl TableConfig {
     /// - `PRICE_LEVEL_COLUMN_NAME`: Specifies the column name for price levels.
     /// - `USER_ID_COLUMN_NAME`: Specifies the column name for user IDs.
     /// - `SYMBOL_COLUMN_NAME`: Specifies the column name for symbols.
+    /// - `CONDITION_COLUMN_NAME`: Optional, specifies the column name for alert conditions (defaults to `condition`).
+    /// - `DIRECTION_COLUMN_NAME`: Optional, specifies the column name for the initial direction (defaults to `initial_direction`).
+    /// - `EXPIRY_COLUMN_NAME`: Optional, specifies the column name for the expiry timestamp (defaults to `expires_at`).
+    /// - `METADATA_COLUMN_NAME`: Optional, specifies the column name for alert metadata (defaults to `metadata`).
+    /// - `EVALUATE_ON_COLUMN_NAME`: Optional, specifies the column name for the evaluation mode (defaults to `evaluate_on`).
+    /// - `TRIGGERED_AT_COLUMN_NAME`: Optional, specifies the column name for the trigger timestamp (defaults to `triggered_at`).
+    /// - `TRIGGER_PRICE_COLUMN_NAME`: Optional, specifies the column name for the trigger price (defaults to `trigger_price`).
+    /// - `CREATED_AT_COLUMN_NAME`: Optional, specifies the column name for the creation timestamp (defaults to `created_at`).
+    /// - `QUOTE_TIME_COLUMN_NAME`: Optional, specifies the column name for the provider's quote timestamp (defaults to `quote_time`).
+    /// - `TAGS_COLUMN_NAME`: Optional, specifies the JSON array column name for alert tags (defaults to `tags`).
+    /// - `TOLERANCE_COLUMN_NAME`: Optional, specifies the column name for the trigger tolerance (defaults to `tolerance`).
+    /// - `VERSION_COLUMN_NAME`: Optional, specifies the column name for the row version (defaults to `version`).
+    /// - `UPPER_PRICE_LEVEL_COLUMN_NAME`: Optional, specifies the column name for the second level of band alerts (defaults to `upper_price_level`).
+    /// - `SESSIONS_COLUMN_NAME`: Optional, specifies the JSON array column name for the trading sessions of an alert (defaults to `sessions`).
+    /// - `CLAIMED_BY_COLUMN_NAME`: Optional, specifies the column name for the instance which claimed a trigger (defaults to `claimed_by`).
+    /// - `CLAIMED_AT_COLUMN_NAME`: Optional, specifies the column name for the claim time of a trigger (defaults to `claimed_at`).
+    /// - `CHART_URL_COLUMN_NAME`: Optional, specifies the column name for the URL of the chart snapshot of a trigger (defaults to `chart_url`).
+    /// - `STALE_WARNED_AT_COLUMN_NAME`: Optional, specifies the column name for when the owner was warned an alert is stale (defaults to `stale_warned_at`).
+    /// - `ENCRYPTED_COLUMNS`: Optional, comma separated list of columns to encrypt at rest.
     ///
     /// # Errors
     /// Returns `TableConfigError::InvalidConfiguration` if any of the required environment variables are not set.
@@ -445,12 +854,82 @@ impl TableConfig {
             Err(_) => return Err(TableConfigError::InvalidConfiguration("SYMBOL_COLUMN_NAME not set in .env".to_string())),
         };
 
+        let defaults: TableConfig = TableConfig::default();
+
+        let encrypted_columns: Vec<String> = env::var("ENCRYPTED_COLUMNS")
+            .map(|columns| {
+                columns
+                    .split(',')
+                    .map(|column| column.trim().to_string())
+                    .filter(|column| !column.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
         Ok(TableConfig {
             tablename,
             hash_column_name,
             price_level_column_name,
             user_id_column_name,
             symbol_column_name,
+            condition_column_name: env::var("CONDITION_COLUMN_NAME").unwrap_or(defaults.condition_column_name),
+            direction_column_name: env::var("DIRECTION_COLUMN_NAME").unwrap_or(defaults.direction_column_name),
+            expiry_column_name: env::var("EXPIRY_COLUMN_NAME").unwrap_or(defaults.expiry_column_name),
+            metadata_column_name: env::var("METADATA_COLUMN_NAME").unwrap_or(defaults.metadata_column_name),
+            evaluate_on_column_name: env::var("EVALUATE_ON_COLUMN_NAME").unwrap_or(defaults.evaluate_on_column_name),
+            triggered_at_column_name: env::var("TRIGGERED_AT_COLUMN_NAME").unwrap_or(defaults.triggered_at_column_name),
+            trigger_price_column_name: env::var("TRIGGER_PRICE_COLUMN_NAME").unwrap_or(defaults.trigger_price_column_name),
+            created_at_column_name: env::var("CREATED_AT_COLUMN_NAME").unwrap_or(defaults.created_at_column_name),
+            quote_time_column_name: env::var("QUOTE_TIME_COLUMN_NAME").unwrap_or(defaults.quote_time_column_name),
+            tags_column_name: env::var("TAGS_COLUMN_NAME").unwrap_or(defaults.tags_column_name),
+            tolerance_column_name: env::var("TOLERANCE_COLUMN_NAME").unwrap_or(defaults.tolerance_column_name),
+            version_column_name: env::var("VERSION_COLUMN_NAME").unwrap_or(defaults.version_column_name),
+            upper_price_level_column_name: env::var("UPPER_PRICE_LEVEL_COLUMN_NAME")
+                .unwrap_or(defaults.upper_price_level_column_name),
+            sessions_column_name: env::var("SESSIONS_COLUMN_NAME").unwrap_or(defaults.sessions_column_name),
+            trigger_state_column_name: env::var("TRIGGER_STATE_COLUMN_NAME").unwrap_or(defaults.trigger_state_column_name),
+            idempotency_key_column_name: env::var("IDEMPOTENCY_KEY_COLUMN_NAME")
+                .unwrap_or(defaults.idempotency_key_column_name),
+            claimed_by_column_name: env::var("CLAIMED_BY_COLUMN_NAME").unwrap_or(defaults.claimed_by_column_name),
+            claimed_at_column_name: env::var("CLAIMED_AT_COLUMN_NAME").unwrap_or(defaults.claimed_at_column_name),
+            chart_url_column_name: env::var("CHART_URL_COLUMN_NAME").unwrap_or(defaults.chart_url_column_name),
+            stale_warned_at_column_name: env::var("STALE_WARNED_AT_COLUMN_NAME")
+                .unwrap_or(defaults.stale_warned_at_column_name),
+            encrypted_columns,
         })
     }
-}
\ No newline at end of file
+}
+
+impl Default for TableConfig {
+    /// Creates a `TableConfig` using the default `alerts` table layout.
+    fn default() -> Self {
+        TableConfig {
+            tablename: "alerts".to_string(),
+            hash_column_name: "hash".to_string(),
+            price_level_column_name: "price_level".to_string(),
+            user_id_column_name: "user_id".to_string(),
+            symbol_column_name: "symbol".to_string(),
+            condition_column_name: "condition".to_string(),
+            direction_column_name: "initial_direction".to_string(),
+            expiry_column_name: "expires_at".to_string(),
+            metadata_column_name: "metadata".to_string(),
+            evaluate_on_column_name: "evaluate_on".to_string(),
+            triggered_at_column_name: "triggered_at".to_string(),
+            trigger_price_column_name: "trigger_price".to_string(),
+            created_at_column_name: "created_at".to_string(),
+            quote_time_column_name: "quote_time".to_string(),
+            tags_column_name: "tags".to_string(),
+            tolerance_column_name: "tolerance".to_string(),
+            version_column_name: "version".to_string(),
+            upper_price_level_column_name: "upper_price_level".to_string(),
+            sessions_column_name: "sessions".to_string(),
+            trigger_state_column_name: "trigger_state".to_string(),
+            idempotency_key_column_name: "idempotency_key".to_string(),
+            claimed_by_column_name: "claimed_by".to_string(),
+            claimed_at_column_name: "claimed_at".to_string(),
+            chart_url_column_name: "chart_url".to_string(),
+            stale_warned_at_column_name: "stale_warned_at".to_string(),
+            encrypted_columns: Vec::new(),
+        }
+    }
+}
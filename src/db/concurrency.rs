@@ -0,0 +1,195 @@
+//! ## Optimistic concurrency
+//!
+//! Updates and deletes which only apply if the alert is still at the version the caller
+//! read, so concurrent writers from several app instances can't silently overwrite each
+//! other. The alerts table needs a `version integer not null default 1` column.
+//!
+//! On `SupabaseError::Conflict`, re-fetch the alert, reapply the change and retry.
+//!
+//! Every other writer of the alerts table bumps the version as well, so a versioned update
+//! never succeeds over a change it did not see.
+
+use std::error::Error;
+
+use serde_json::{Value, json};
+
+use crate::AlertUpdate;
+use crate::db::rest::{Filter, eq};
+use crate::db::{Supabase, TableConfig};
+use crate::errors::SupabaseError;
+use crate::success::SupabaseSuccess;
+
+/// How often an unconditional write re-reads the version after losing a race.
+const WRITE_ATTEMPTS: usize = 3;
+
+impl Supabase {
+    /// Fetches the current version of an alert.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if the alert does not exist, has no version or the query fails.
+    pub async fn fetch_alert_version(
+        &self,
+        hash: &str,
+        config: &TableConfig
+    ) -> Result<i64, Box<dyn Error + Send + Sync>> {
+        let rows: Vec<Value> = self
+            .rest_select(&config.tablename, &[eq(&config.hash_column_name, hash)])
+            .await
+            .map_err(SupabaseError::FetchError)?;
+
+        let row: &Value = rows
+            .first()
+            .ok_or_else(|| SupabaseError::FetchError("No results found".to_string()))?;
+
+        row.get(&config.version_column_name)
+            .and_then(|v| v.as_i64())
+            .ok_or_else(|| Box::new(SupabaseError::FetchError("Version field is missing".to_string())) as _)
+    }
+
+    /// Updates an alert only if it is still at `expected_version`, incrementing its version.
+    ///
+    /// # Parameters
+    /// - `hash`: The hash of the alert to be updated.
+    /// - `update`: An `AlertUpdate` with the fields to change.
+    /// - `expected_version`: The version the caller read the alert at.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// A `Result` containing the new version of the alert or an error.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::Conflict` if the alert was changed since it was read,
+    /// `SupabaseError::FetchError` if it no longer exists, or `SupabaseError::UpdateError` if the update fails.
+    pub async fn update_alert_if_version(
+        &self,
+        hash: &str,
+        update: AlertUpdate,
+        expected_version: i64,
+        config: &TableConfig
+    ) -> Result<i64, Box<dyn Error + Send + Sync>> {
+        let mut body: Value = update.to_value(config);
+        self.seal_row(config, &mut body)?;
+        body[&config.version_column_name] = json!(expected_version + 1);
+
+        let updated: Vec<Value> = self
+            .rest_update(&config.tablename, &version_filters(hash, expected_version, config), &body)
+            .await
+            .map_err(SupabaseError::UpdateError)?;
+
+        if updated.is_empty() && !self.landed(hash, &body, config).await {
+            return Err(self.conflict(hash, expected_version, config).await);
+        }
+        Ok(expected_version + 1)
+    }
+
+    /// Deletes an alert only if it is still at `expected_version`.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::Conflict` if the alert was changed since it was read,
+    /// `SupabaseError::FetchError` if it no longer exists, or `SupabaseError::DeletionError` if the deletion fails.
+    pub async fn delete_alert_if_version(
+        &self,
+        hash: &str,
+        expected_version: i64,
+        config: &TableConfig
+    ) -> Result<SupabaseSuccess, Box<dyn Error + Send + Sync>> {
+        let deleted: Vec<Value> = self
+            .rest_delete(&config.tablename, &version_filters(hash, expected_version, config))
+            .await
+            .map_err(SupabaseError::DeletionError)?;
+
+        if deleted.is_empty() {
+            return Err(self.conflict(hash, expected_version, config).await);
+        }
+        Ok(SupabaseSuccess::DeletionSuccess)
+    }
+
+    /// Writes `body` to an alert regardless of its version, incrementing the version.
+    ///
+    /// The version is read first and the write is conditional on it, so a concurrent
+    /// versioned update can't be overwritten without a bump. Tables without a version
+    /// column are updated directly.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if the alert does not exist, `SupabaseError::Conflict`
+    /// if the alert kept changing between reads, or `SupabaseError::UpdateError` if the update fails.
+    pub(crate) async fn write_alert(
+        &self,
+        hash: &str,
+        mut body: Value,
+        config: &TableConfig
+    ) -> Result<SupabaseSuccess, Box<dyn Error + Send + Sync>> {
+        let mut version: i64 = 0;
+        for _ in 0..WRITE_ATTEMPTS {
+            let rows: Vec<Value> = self
+                .rest_select(&config.tablename, &[eq(&config.hash_column_name, hash)])
+                .await
+                .map_err(SupabaseError::FetchError)?;
+            let row: &Value = rows
+                .first()
+                .ok_or_else(|| SupabaseError::FetchError("No results found".to_string()))?;
+
+            let Some(current) = row.get(&config.version_column_name).and_then(|v| v.as_i64()) else {
+                self.rest_update(&config.tablename, &[eq(&config.hash_column_name, hash)], &body)
+                    .await
+                    .map_err(SupabaseError::UpdateError)?;
+                return Ok(SupabaseSuccess::UpdateSuccess);
+            };
+
+            version = current;
+            body[&config.version_column_name] = json!(version + 1);
+            let updated: Vec<Value> = self
+                .rest_update(&config.tablename, &version_filters(hash, version, config), &body)
+                .await
+                .map_err(SupabaseError::UpdateError)?;
+            if !updated.is_empty() || self.landed(hash, &body, config).await {
+                return Ok(SupabaseSuccess::UpdateSuccess);
+            }
+        }
+        Err(self.conflict(hash, version, config).await)
+    }
+
+    /// Whether the alert already holds `body`, new version included. A retried update matches
+    /// no rows when an earlier attempt was applied but its answer was lost.
+    async fn landed(
+        &self,
+        hash: &str,
+        body: &Value,
+        config: &TableConfig
+    ) -> bool {
+        let Ok(rows) = self.rest_select(&config.tablename, &[eq(&config.hash_column_name, hash)]).await else {
+            return false;
+        };
+        let (Some(row), Some(fields)) = (rows.first(), body.as_object()) else {
+            return false;
+        };
+        fields.iter().all(|(column, value)| row.get(column) == Some(value))
+    }
+
+    /// Explains why a versioned write matched no rows.
+    async fn conflict(
+        &self,
+        hash: &str,
+        expected_version: i64,
+        config: &TableConfig
+    ) -> Box<dyn Error + Send + Sync> {
+        match self.fetch_alert_version(hash, config).await {
+            Ok(version) => Box::new(SupabaseError::Conflict(format!(
+                "alert {} is at version {} but version {} was expected, re-fetch it and retry",
+                hash, version, expected_version
+            ))),
+            Err(e) => e,
+        }
+    }
+}
+
+fn version_filters(
+    hash: &str,
+    version: i64,
+    config: &TableConfig
+) -> [Filter; 2] {
+    [
+        eq(&config.hash_column_name, hash),
+        eq(&config.version_column_name, &version.to_string()),
+    ]
+}
@@ -0,0 +1,116 @@
+//! ## Front-run protection
+//!
+//! Rejection of new alerts whose level is so close to the current price that they would
+//! fire on the next check, and enforcement of the configured `DistancePolicy`.
+
+use std::error::Error;
+
+use crate::data::Quote;
+use crate::db::{DistancePolicy, Supabase};
+use crate::errors::SupabaseError;
+use crate::{Alert, BandMode, Condition, Tolerance};
+
+impl DistancePolicy {
+    /// Rejects alerts closer to the price than `min_distance`.
+    pub fn reject(min_distance: Tolerance) -> Self {
+        Self { min_distance, reject: true }
+    }
+
+    /// Only logs alerts closer to the price than `min_distance`.
+    pub fn warn(min_distance: Tolerance) -> Self {
+        Self { min_distance, reject: false }
+    }
+
+    /// Describes why an alert is too close to `price`, or already met at it.
+    ///
+    /// The price level is checked for price level, band, price above and price below alerts,
+    /// and the upper price level of bands as well. Other conditions are not checked.
+    /// Price above and price below alerts are also rejected when the price is already past
+    /// their level, band alerts entering or inside the band when the price is already inside
+    /// it, and those exiting or outside it when the price is already outside.
+    ///
+    /// # Returns
+    /// Returns `None` if every level is far enough from the price.
+    pub fn violation(
+        &self,
+        alert: &Alert,
+        price: f64
+    ) -> Option<String> {
+        if let Some(reason) = already_met(alert, price) {
+            return Some(reason);
+        }
+        let levels: Vec<f64> = alert.condition.levels(alert.price_level, alert.upper_price_level);
+        let min_distance: f64 = self.min_distance.offset(price, &Quote::new(alert.symbol.clone(), price));
+        levels.into_iter().find(|level| (level - price).abs() < min_distance).map(|level| {
+            format!(
+                "{} level {} is within {} of the price {}",
+                alert.symbol, level, min_distance, price
+            )
+        })
+    }
+}
+
+/// Describes why an alert's condition already holds at `price`, so it would fire on the next check.
+fn already_met(
+    alert: &Alert,
+    price: f64
+) -> Option<String> {
+    match (&alert.condition, alert.upper_price_level) {
+        (Condition::PriceAbove(level), _) if price > *level => {
+            Some(format!("{} price {} is already above {}", alert.symbol, price, level))
+        },
+        (Condition::PriceBelow(level), _) if price < *level => {
+            Some(format!("{} price {} is already below {}", alert.symbol, price, level))
+        },
+        (Condition::Band(mode), Some(upper_price_level)) => {
+            let inside: bool = Condition::Band(BandMode::Inside).is_met_on_band(alert.price_level, upper_price_level, None, price);
+            let side: &str = match (mode, inside) {
+                (BandMode::Enter | BandMode::Inside, true) => "inside",
+                (BandMode::Exit | BandMode::Outside, false) => "outside",
+                _ => return None,
+            };
+            Some(format!(
+                "{} price {} is already {} the band {} to {}",
+                alert.symbol, price, side, alert.price_level, upper_price_level
+            ))
+        },
+        _ => None,
+    }
+}
+
+impl Supabase {
+    /// Sets the smallest accepted distance between a new alert's level and the current price.
+    ///
+    /// # Parameters
+    /// - `min_distance`: The `DistancePolicy` to enforce.
+    pub fn with_distance_policy(
+        mut self,
+        min_distance: DistancePolicy
+    ) -> Self {
+        self.min_distance = Some(min_distance);
+        self
+    }
+
+    /// Checks a new alert against the configured `DistancePolicy`, using the live `price`.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::TooCloseToMarket` if the policy rejects alerts and a level of
+    /// the alert is too close to the price.
+    pub fn check_distance(
+        &self,
+        alert: &Alert,
+        price: f64
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let Some(policy) = &self.min_distance else {
+            return Ok(());
+        };
+        match policy.violation(alert, price) {
+            Some(reason) if policy.reject => Err(Box::new(SupabaseError::TooCloseToMarket(reason))),
+            Some(reason) => {
+                println!("Warning: alert {} would fire instantly, {}", alert.hash, reason);
+                Ok(())
+            },
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! ## Duplicate alerts
+//!
+//! Detection of alerts which are functionally identical to an existing alert
+//! of the same user, and enforcement of the configured `DuplicatePolicy`.
+
+use std::error::Error;
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::db::rest::{Filter, eq, is_in};
+use crate::db::{DuplicatePolicy, Supabase, TableConfig};
+use crate::errors::SupabaseError;
+
+impl Supabase {
+    /// Sets how identical alerts of the same user are handled when adding alerts.
+    ///
+    /// # Parameters
+    /// - `duplicates`: The `DuplicatePolicy` to enforce.
+    pub fn with_duplicate_policy(
+        mut self,
+        duplicates: DuplicatePolicy
+    ) -> Self {
+        self.duplicates = duplicates;
+        self
+    }
+
+    /// Fetches the existing alerts identical to a new alert row.
+    ///
+    /// Rows are compared on user ID, symbol, price level and initial direction. Encrypted
+    /// columns never compare equal, so alerts are not detected as duplicates on those.
+    ///
+    /// # Parameters
+    /// - `row`: The row of the new alert, as built for insertion.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// A `Result` containing the identical rows or an error.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if the query execution fails.
+    pub async fn find_duplicates(
+        &self,
+        row: &Value,
+        config: &TableConfig
+    ) -> Result<Vec<Value>, Box<dyn Error + Send + Sync>> {
+        let filters: Vec<Filter> = [
+            &config.user_id_column_name,
+            &config.symbol_column_name,
+            &config.price_level_column_name,
+            &config.direction_column_name,
+        ]
+        .into_iter()
+        .map(|column| eq(column, &column_text(row.get(column))))
+        .collect();
+
+        match self.rest_select(&config.tablename, &filters).await {
+            Ok(rows) => Ok(rows),
+            Err(e) => Err(Box::new(SupabaseError::FetchError(e))),
+        }
+    }
+
+    /// Applies the `DuplicatePolicy` before a new alert row is inserted.
+    ///
+    /// # Returns
+    /// A `Result` containing the rows to delete with `replace_duplicates` once the new alert
+    /// was inserted, so a failed insertion never loses the existing alert.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::DuplicateAlert` if the policy is `Reject` and an identical alert exists,
+    /// or the underlying error if fetching the identical alerts fails.
+    pub(crate) async fn resolve_duplicates(
+        &self,
+        row: &Value,
+        config: &TableConfig
+    ) -> Result<Vec<Value>, Box<dyn Error + Send + Sync>> {
+        if self.duplicates == DuplicatePolicy::Allow {
+            return Ok(Vec::new());
+        }
+
+        let duplicates: Vec<Value> = self.find_duplicates(row, config).await?;
+        if duplicates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let hashes: Vec<&str> = duplicates
+            .iter()
+            .filter_map(|duplicate| duplicate.get(&config.hash_column_name).and_then(|v| v.as_str()))
+            .collect();
+
+        match self.duplicates {
+            DuplicatePolicy::Reject => Err(Box::new(SupabaseError::DuplicateAlert(format!(
+                "identical to {}",
+                hashes.join(", ")
+            )))),
+            DuplicatePolicy::Replace => Ok(duplicates),
+            DuplicatePolicy::Allow => Ok(Vec::new()),
+        }
+    }
+
+    /// Deletes the alerts replaced by a newly inserted alert.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::Conflict` if an identical alert changed since it was read,
+    /// or the underlying error if deleting the identical alerts fails.
+    pub(crate) async fn replace_duplicates(
+        &self,
+        duplicates: &[Value],
+        config: &TableConfig
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // Versioned rows are only deleted at the version read, so a concurrent edit isn't lost
+        let mut ids: Vec<String> = Vec::new();
+        for duplicate in duplicates {
+            let hash: Option<&str> = duplicate.get(&config.hash_column_name).and_then(|v| v.as_str());
+            let version: Option<i64> = duplicate.get(&config.version_column_name).and_then(|v| v.as_i64());
+            match (hash, version) {
+                (Some(hash), Some(version)) => {
+                    self.delete_alert_if_version(hash, version, config).await?;
+                },
+                _ => ids.extend(duplicate.get("id").and_then(|v| v.as_i64()).map(|id| id.to_string())),
+            }
+        }
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+
+        match self.rest_delete(&config.tablename, &[is_in("id", &ids)]).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(Box::new(SupabaseError::DeletionError(e))),
+        }
+    }
+}
+
+impl FromStr for DuplicatePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "reject" => Ok(DuplicatePolicy::Reject),
+            "replace" => Ok(DuplicatePolicy::Replace),
+            "allow" => Ok(DuplicatePolicy::Allow),
+            other => Err(format!("Unknown duplicate policy: {}", other)),
+        }
+    }
+}
+
+/// Formats a column value for an equality filter.
+fn column_text(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(value) => value.to_string(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,68 @@
+//! ## GDPR data removal
+//!
+//! Removes every row belonging to a user across all tables the application stores user data in.
+
+use crate::db::rest::eq;
+use crate::db::{DeletionReport, Supabase, TableConfig, TableDeletion, UserDataTable};
+
+impl Supabase {
+    /// Deletes all data belonging to a user from every given table.
+    ///
+    /// Every table is processed even if an earlier one fails, so the report shows exactly
+    /// which tables still contain data for the user.
+    ///
+    /// # Parameters
+    /// - `user_id`: The user whose data should be removed.
+    /// - `tables`: The tables holding user data (alerts, history, preferences, delivery logs, queued notifications, ...).
+    ///
+    /// # Returns
+    /// A `DeletionReport` with the number of deleted rows or the error for each table.
+    pub async fn delete_all_user_data(
+        &self,
+        user_id: &str,
+        tables: &[UserDataTable]
+    ) -> DeletionReport {
+        let mut report = DeletionReport { user_id: user_id.to_string(), tables: Vec::new() };
+
+        for table in tables {
+            let result = self
+                .rest_delete(&table.tablename, &[eq(&table.user_id_column_name, user_id)])
+                .await;
+
+            report.tables.push(match result {
+                Ok(rows) => TableDeletion { tablename: table.tablename.clone(), deleted: rows.len(), error: None },
+                Err(e) => TableDeletion { tablename: table.tablename.clone(), deleted: 0, error: Some(e) },
+            });
+        }
+
+        report
+    }
+}
+
+impl UserDataTable {
+    /// Creates a new `UserDataTable`.
+    pub fn new(
+        tablename: String,
+        user_id_column_name: String
+    ) -> Self {
+        Self { tablename, user_id_column_name }
+    }
+}
+
+impl From<&TableConfig> for UserDataTable {
+    fn from(config: &TableConfig) -> Self {
+        Self::new(config.tablename.clone(), config.user_id_column_name.clone())
+    }
+}
+
+impl DeletionReport {
+    /// Returns `true` if the data was removed from every table.
+    pub fn is_complete(&self) -> bool {
+        self.tables.iter().all(|table| table.error.is_none())
+    }
+
+    /// Returns the total number of deleted rows.
+    pub fn total_deleted(&self) -> usize {
+        self.tables.iter().map(|table| table.deleted).sum()
+    }
+}
@@ -0,0 +1,74 @@
+//! ## Heartbeats
+//!
+//! A running system writes its [`Health`] to a row per instance after every cycle, so
+//! monitoring can alert on a stale `updated_at` or a `stuck` or `degraded` state before users
+//! notice missed alerts, see [`crate::TradeAlertsBuilder::with_heartbeat`].
+
+use chrono::Utc;
+use serde_json::{Map, Value};
+
+use crate::Health;
+use crate::db::rest::eq;
+use crate::db::{HeartbeatTable, Supabase};
+use crate::errors::SupabaseError;
+
+impl Default for HeartbeatTable {
+    /// Creates a `HeartbeatTable` using the default `heartbeats` table layout.
+    fn default() -> Self {
+        Self {
+            tablename: "heartbeats".to_string(),
+            instance_id_column_name: "instance_id".to_string(),
+            updated_at_column_name: "updated_at".to_string(),
+        }
+    }
+}
+
+impl Supabase {
+    /// Writes the health of an instance, replacing the row it wrote before.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::UpdateError` or `SupabaseError::InsertionError` if the row could not be written.
+    pub async fn write_heartbeat(
+        &self,
+        instance_id: &str,
+        health: &Health,
+        table: &HeartbeatTable
+    ) -> Result<(), SupabaseError> {
+        let mut row: Map<String, Value> = match serde_json::to_value(health) {
+            Ok(Value::Object(row)) => row,
+            _ => return Err(SupabaseError::InsertionError("Health is not an object".to_string())),
+        };
+        row.insert(table.instance_id_column_name.clone(), Value::from(instance_id));
+        row.insert(table.updated_at_column_name.clone(), Value::from(Utc::now().to_rfc3339()));
+        let row: Value = Value::Object(row);
+
+        let filters = [eq(&table.instance_id_column_name, instance_id)];
+        let updated: Vec<Value> = self
+            .rest_update(&table.tablename, &filters, &row)
+            .await
+            .map_err(SupabaseError::UpdateError)?;
+        if updated.is_empty() {
+            self.rest_insert(&table.tablename, &[row]).await.map_err(SupabaseError::InsertionError)?;
+        }
+        Ok(())
+    }
+
+    /// Fetches the last health an instance wrote, `None` if it never wrote one.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if the row could not be fetched or parsed.
+    pub async fn fetch_heartbeat(
+        &self,
+        instance_id: &str,
+        table: &HeartbeatTable
+    ) -> Result<Option<Health>, SupabaseError> {
+        let rows: Vec<Value> = self
+            .rest_select(&table.tablename, &[eq(&table.instance_id_column_name, instance_id)])
+            .await
+            .map_err(SupabaseError::FetchError)?;
+        rows.into_iter()
+            .next()
+            .map(|row| serde_json::from_value(row).map_err(|e| SupabaseError::FetchError(e.to_string())))
+            .transpose()
+    }
+}
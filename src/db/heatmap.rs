@@ -0,0 +1,122 @@
+//! ## Alert level heat maps
+//!
+//! Aggregates where users have clustered their alerts, so frontends can render
+//! heat maps without downloading every alert.
+
+use std::error::Error;
+
+use serde_json::Value;
+
+use crate::db::rest::Filter;
+use crate::db::{HeatMap, LevelBucket, LevelHistogram, Supabase, TableConfig};
+use crate::errors::SupabaseError;
+
+/// Number of rows fetched per page while aggregating.
+pub const HEAT_MAP_PAGE_SIZE: usize = 1000;
+
+impl HeatMap {
+    /// Creates an empty `HeatMap` whose buckets are `bucket_percent` percent wide.
+    pub fn new(bucket_percent: f64) -> Self {
+        Self { bucket_percent, ..Self::default() }
+    }
+
+    /// Counts a single alert level. Non-positive levels can't be bucketed and are ignored.
+    pub fn record(
+        &mut self,
+        symbol: &str,
+        price_level: f64
+    ) {
+        if price_level <= 0.0 || !price_level.is_finite() || self.bucket_percent <= 0.0 {
+            return;
+        }
+        let bucket: i64 = (price_level.ln() / self.growth().ln()).floor() as i64;
+
+        *self.counts
+            .entry(symbol.to_string())
+            .or_default()
+            .entry(bucket)
+            .or_insert(0) += 1;
+    }
+
+    /// Returns the histogram of every symbol, sorted by symbol.
+    pub fn histograms(&self) -> Vec<LevelHistogram> {
+        let growth: f64 = self.growth();
+
+        let mut histograms: Vec<LevelHistogram> = self.counts
+            .iter()
+            .map(|(symbol, counts)| LevelHistogram {
+                symbol: symbol.clone(),
+                buckets: counts
+                    .iter()
+                    .map(|(bucket, count)| LevelBucket {
+                        lower: growth.powi(*bucket as i32),
+                        upper: growth.powi(*bucket as i32 + 1),
+                        count: *count,
+                    })
+                    .collect(),
+            })
+            .collect();
+        histograms.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        histograms
+    }
+
+    fn growth(&self) -> f64 {
+        1.0 + self.bucket_percent / 100.0
+    }
+}
+
+impl Supabase {
+    /// Builds a histogram of alert levels per symbol.
+    ///
+    /// Alerts are fetched page by page and folded into the histograms as they arrive,
+    /// so memory use does not grow with the number of alerts.
+    ///
+    /// # Parameters
+    /// - `bucket_percent`: The width of a bucket in percent of its lower bound, e.g. `0.5`.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// A `Result` containing one `LevelHistogram` per symbol with alerts, or an error.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if fetching a page fails, or an error if decrypting a row fails.
+    pub async fn alert_level_heat_map(
+        &self,
+        bucket_percent: f64,
+        config: &TableConfig
+    ) -> Result<Vec<LevelHistogram>, Box<dyn Error + Send + Sync>> {
+        let mut heat_map: HeatMap = HeatMap::new(bucket_percent);
+        let mut offset: usize = 0;
+
+        loop {
+            let page: [Filter; 4] = [
+                ("select".to_string(), format!("{},{}", config.symbol_column_name, config.price_level_column_name)),
+                ("order".to_string(), "id.asc".to_string()),
+                ("limit".to_string(), HEAT_MAP_PAGE_SIZE.to_string()),
+                ("offset".to_string(), offset.to_string()),
+            ];
+            let rows: Vec<Value> = self
+                .rest_select(&config.tablename, &page)
+                .await
+                .map_err(SupabaseError::FetchError)?;
+
+            for mut row in rows.iter().cloned() {
+                self.open_row(config, &mut row)?;
+                if let (Some(symbol), Some(price_level)) = (
+                    row.get(&config.symbol_column_name).and_then(|v| v.as_str()),
+                    row.get(&config.price_level_column_name).and_then(|v| v.as_f64()),
+                ) {
+                    heat_map.record(symbol, price_level);
+                }
+            }
+
+            if rows.len() < HEAT_MAP_PAGE_SIZE {
+                break;
+            }
+            offset += rows.len();
+        }
+
+        Ok(heat_map.histograms())
+    }
+}
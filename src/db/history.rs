@@ -0,0 +1,223 @@
+//! ## History of triggered alerts
+//!
+//! Triggered alerts can be moved to a separate history table instead of being deleted,
+//! so UIs can show a user's past alerts.
+
+use std::error::Error;
+
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value, json};
+use supabase_rs::SupabaseClient;
+
+use crate::{Condition, TriggeredAlert};
+use crate::db::{Supabase, TableConfig};
+use crate::errors::SupabaseError;
+use crate::success::SupabaseSuccess;
+
+impl Supabase {
+    /// Moves an alert to the history table.
+    ///
+    /// The row is copied to the history table with its configured columns renamed to the
+    /// history table's column names, then deleted from the alerts table. If the trigger time
+    /// and price were not recorded yet (see [`Supabase::record_trigger`]), the current time
+    /// and the last recorded price are used.
+    ///
+    /// # Parameters
+    /// - `hash`: The hash of the alert to archive.
+    /// - `config`: The configuration of the alerts table.
+    /// - `history_config`: The configuration of the history table.
+    ///
+    /// # Returns
+    /// A `Result` indicating success or error in archiving.
+    ///
+    /// # Errors
+    /// Returns an error if the alert cannot be found, or the copy or deletion fails. The alert
+    /// is only deleted once the copy succeeded.
+    pub async fn archive_alert(
+        &self,
+        hash: &str,
+        config: &TableConfig,
+        history_config: &TableConfig
+    ) -> Result<SupabaseSuccess, Box<dyn Error + Send + Sync>> {
+        let supabase: SupabaseClient = Supabase::authenticate(self).await;
+
+        let response: Result<Vec<Value>, String> = self
+            .retrying(|| {
+                supabase
+                    .select(&config.tablename)
+                    .eq(&config.hash_column_name, hash)
+                    .execute()
+            })
+            .await;
+
+        let mut row: Value = match response {
+            Ok(values) => values
+                .into_iter()
+                .next()
+                .ok_or_else(|| SupabaseError::FetchError("No results found".to_string()))?,
+            Err(e) => return Err(Box::new(SupabaseError::FetchError(e))),
+        };
+        self.open_row(config, &mut row)?;
+
+        let id: i64 = row
+            .get("id")
+            .and_then(|v| v.as_i64())
+            .ok_or_else(|| SupabaseError::FetchError("ID field is missing".to_string()))?;
+
+        let mut body: Value = history_row(&row, config, history_config);
+        self.seal_row(history_config, &mut body)?;
+
+        if let Err(e) = self.within_timeouts(supabase.insert(&history_config.tablename, body)).await {
+            return Err(Box::new(SupabaseError::InsertionError(e)));
+        }
+
+        let id: String = id.to_string();
+        match self.retrying(|| supabase.delete(&config.tablename, &id)).await {
+            Ok(_) => Ok(SupabaseSuccess::InsertionSuccess),
+            Err(e) => Err(Box::new(SupabaseError::DeletionError(e))),
+        }
+    }
+
+    /// Fetches the archived alerts of a user, most recently triggered first.
+    ///
+    /// # Parameters
+    /// - `user_id`: The user ID for which to fetch the history.
+    /// - `history_config`: The configuration of the history table.
+    ///
+    /// # Returns
+    /// A `Result` containing the archived alerts or an error.
+    ///
+    /// # Errors
+    /// Returns an error if the query fails or a row misses a required column.
+    pub async fn fetch_alert_history_by_user_id(
+        &self,
+        user_id: &str,
+        history_config: &TableConfig
+    ) -> Result<Vec<TriggeredAlert>, Box<dyn Error + Send + Sync>> {
+        let supabase: SupabaseClient = Supabase::authenticate(self).await;
+
+        let response: Result<Vec<Value>, String> = self
+            .retrying(|| {
+                supabase
+                    .select(&history_config.tablename)
+                    .eq(&history_config.user_id_column_name, user_id)
+                    .execute()
+            })
+            .await;
+
+        match response {
+            Ok(values) => {
+                let mut history: Vec<TriggeredAlert> = Vec::with_capacity(values.len());
+                for mut value in values {
+                    self.open_row(history_config, &mut value)?;
+                    history.push(TriggeredAlert::from_row(&value, history_config)?);
+                }
+                history.sort_by_key(|alert| std::cmp::Reverse(alert.triggered_at));
+                Ok(history)
+            },
+            Err(e) => Err(Box::new(SupabaseError::FetchError(e)))
+        }
+    }
+}
+
+impl TriggeredAlert {
+    /// Builds a `TriggeredAlert` from a row of the history table.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if a required column, including the trigger
+    /// time and price, is missing or has the wrong type.
+    pub fn from_row(
+        row: &Value,
+        config: &TableConfig
+    ) -> Result<Self, SupabaseError> {
+        let string_column = |column: &str| -> Result<String, SupabaseError> {
+            row.get(column)
+                .and_then(|v| v.as_str())
+                .map(String::from)
+                .ok_or_else(|| SupabaseError::FetchError(format!("Column `{}` not found", column)))
+        };
+        let number_column = |column: &str| -> Result<f64, SupabaseError> {
+            row.get(column)
+                .and_then(|v| v.as_f64())
+                .ok_or_else(|| SupabaseError::FetchError(format!("Column `{}` not found", column)))
+        };
+
+        let triggered_at: DateTime<Utc> = DateTime::parse_from_rfc3339(&string_column(&config.triggered_at_column_name)?)
+            .map_err(|e| SupabaseError::FetchError(e.to_string()))?
+            .with_timezone(&Utc);
+
+        Ok(Self {
+            hash: string_column(&config.hash_column_name)?,
+            user_id: string_column(&config.user_id_column_name)?,
+            symbol: string_column(&config.symbol_column_name)?,
+            price_level: number_column(&config.price_level_column_name)?,
+            trigger_price: number_column(&config.trigger_price_column_name)?,
+            condition: Condition::from_value(row.get(&config.condition_column_name))
+                .ok_or_else(|| SupabaseError::FetchError("Condition is not valid".to_string()))?,
+            metadata: row.get(&config.metadata_column_name).filter(|v| !v.is_null()).cloned(),
+            triggered_at,
+            quote_time: row
+                .get(&config.quote_time_column_name)
+                .and_then(|v| v.as_str())
+                .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+                .map(|v| v.with_timezone(&Utc)),
+        })
+    }
+}
+
+/// Copies an alert row to the layout of the history table.
+///
+/// Configured columns are renamed to the history table's names, other columns are copied as is
+/// and the `id` is dropped so the history table assigns its own, like the trigger state.
+pub fn history_row(
+    row: &Value,
+    config: &TableConfig,
+    history_config: &TableConfig
+) -> Value {
+    let renames: [(&str, &str); 18] = [
+        (&config.hash_column_name, &history_config.hash_column_name),
+        (&config.price_level_column_name, &history_config.price_level_column_name),
+        (&config.user_id_column_name, &history_config.user_id_column_name),
+        (&config.symbol_column_name, &history_config.symbol_column_name),
+        (&config.condition_column_name, &history_config.condition_column_name),
+        (&config.direction_column_name, &history_config.direction_column_name),
+        (&config.expiry_column_name, &history_config.expiry_column_name),
+        (&config.metadata_column_name, &history_config.metadata_column_name),
+        (&config.evaluate_on_column_name, &history_config.evaluate_on_column_name),
+        (&config.triggered_at_column_name, &history_config.triggered_at_column_name),
+        (&config.trigger_price_column_name, &history_config.trigger_price_column_name),
+        (&config.quote_time_column_name, &history_config.quote_time_column_name),
+        (&config.tags_column_name, &history_config.tags_column_name),
+        (&config.tolerance_column_name, &history_config.tolerance_column_name),
+        (&config.upper_price_level_column_name, &history_config.upper_price_level_column_name),
+        (&config.sessions_column_name, &history_config.sessions_column_name),
+        (&config.idempotency_key_column_name, &history_config.idempotency_key_column_name),
+        (&config.chart_url_column_name, &history_config.chart_url_column_name),
+    ];
+
+    let mut history: Map<String, Value> = Map::new();
+    if let Some(columns) = row.as_object() {
+        for (column, value) in columns {
+            // The trigger state and claim only matter while the alert is being delivered
+            let claim: [&String; 3] = [&config.trigger_state_column_name, &config.claimed_by_column_name, &config.claimed_at_column_name];
+            if column == "id" || claim.contains(&column) {
+                continue;
+            }
+            let name: &str = renames
+                .iter()
+                .find(|(from, _)| *from == column)
+                .map_or(column.as_str(), |(_, to)| *to);
+            history.insert(name.to_string(), value.clone());
+        }
+    }
+
+    if history.get(&history_config.triggered_at_column_name).is_none_or(Value::is_null) {
+        history.insert(history_config.triggered_at_column_name.clone(), json!(Utc::now().to_rfc3339()));
+    }
+    if history.get(&history_config.trigger_price_column_name).is_none_or(Value::is_null) {
+        if let Some(price) = row.get("latest_price").filter(|v| !v.is_null()) {
+            history.insert(history_config.trigger_price_column_name.clone(), price.clone());
+        }
+    }
+    Value::Object(history)
+}
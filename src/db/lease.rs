@@ -0,0 +1,161 @@
+//! ## Leader leases
+//!
+//! Replicas of a scheduler run side by side for high availability, but only the one holding
+//! the lease runs cycles. The holder renews the lease on every tick, and once it stops doing
+//! so the lease expires and the next replica trying takes it over, see
+//! [`crate::TradeAlertsBuilder::with_leader_lease`].
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+
+use crate::db::rest::{before, eq, ilike};
+use crate::db::{Lease, LeaseTable, Supabase};
+use crate::errors::SupabaseError;
+
+impl Default for LeaseTable {
+    /// Creates a `LeaseTable` using the default `leases` table layout.
+    fn default() -> Self {
+        Self {
+            tablename: "leases".to_string(),
+            name_column_name: "name".to_string(),
+            holder_column_name: "holder".to_string(),
+            expires_at_column_name: "expires_at".to_string(),
+        }
+    }
+}
+
+impl Supabase {
+    /// Acquires or renews the lease `name` for `holder` until `ttl` from now.
+    ///
+    /// The lease is acquired when `holder` already holds it, when it expired, or when nobody
+    /// ever held it. The name column should be unique, so two replicas creating the lease at
+    /// once can't both succeed.
+    ///
+    /// # Returns
+    /// `true` if `holder` holds the lease, `false` if another instance does.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::UpdateError` or `SupabaseError::FetchError` if the lease could not be written or read.
+    pub async fn acquire_lease(
+        &self,
+        name: &str,
+        holder: &str,
+        ttl: Duration,
+        table: &LeaseTable
+    ) -> Result<bool, SupabaseError> {
+        let now: DateTime<Utc> = Utc::now();
+        let expires_at: DateTime<Utc> = chrono::Duration::from_std(ttl)
+            .ok()
+            .and_then(|ttl| now.checked_add_signed(ttl))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        let row: Value = json!({
+            table.name_column_name.clone(): name,
+            table.holder_column_name.clone(): holder,
+            table.expires_at_column_name.clone(): expires_at.to_rfc3339(),
+        });
+
+        // Renew our own lease, or take over an expired one
+        for filter in [eq(&table.holder_column_name, holder), before(&table.expires_at_column_name, now)] {
+            let updated: Vec<Value> = self
+                .rest_update(&table.tablename, &[eq(&table.name_column_name, name), filter], &row)
+                .await
+                .map_err(SupabaseError::UpdateError)?;
+            if !updated.is_empty() {
+                return Ok(true);
+            }
+        }
+
+        if self.fetch_lease(name, table).await?.is_some() {
+            return Ok(false);
+        }
+        // Losing a race to create the lease fails the insert, the winner is read back
+        if let Err(e) = self.rest_insert(&table.tablename, &[row]).await {
+            println!("Error creating the lease {}: {}", name, e);
+        }
+        Ok(self.fetch_lease(name, table).await?.is_some_and(|lease| lease.holder == holder))
+    }
+
+    /// Releases the lease `name` if `holder` holds it, so another replica can take over right away.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::UpdateError` if the lease could not be written.
+    pub async fn release_lease(
+        &self,
+        name: &str,
+        holder: &str,
+        table: &LeaseTable
+    ) -> Result<(), SupabaseError> {
+        let filters = [eq(&table.name_column_name, name), eq(&table.holder_column_name, holder)];
+        let body: Value = json!({ table.expires_at_column_name.clone(): Utc::now().to_rfc3339() });
+        self.rest_update(&table.tablename, &filters, &body)
+            .await
+            .map(|_| ())
+            .map_err(SupabaseError::UpdateError)
+    }
+
+    /// Fetches the lease `name`, `None` if nobody ever held it.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if the lease could not be fetched or parsed.
+    pub async fn fetch_lease(
+        &self,
+        name: &str,
+        table: &LeaseTable
+    ) -> Result<Option<Lease>, SupabaseError> {
+        let rows: Vec<Value> = self
+            .rest_select(&table.tablename, &[eq(&table.name_column_name, name)])
+            .await
+            .map_err(SupabaseError::FetchError)?;
+        rows.first().map(|row| lease_from_row(row, table)).transpose()
+    }
+
+    /// Fetches every lease whose name starts with `prefix`, expired or not.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if the leases could not be fetched or parsed.
+    pub async fn fetch_leases(
+        &self,
+        prefix: &str,
+        table: &LeaseTable
+    ) -> Result<Vec<Lease>, SupabaseError> {
+        let rows: Vec<Value> = self
+            .rest_select(&table.tablename, &[ilike(&table.name_column_name, &format!("{}*", prefix))])
+            .await
+            .map_err(SupabaseError::FetchError)?;
+        let leases: Vec<Lease> = rows
+            .iter()
+            .map(|row| lease_from_row(row, table))
+            .collect::<Result<Vec<Lease>, SupabaseError>>()?;
+        // `ilike` ignores case and treats `_` as a wildcard
+        Ok(leases.into_iter().filter(|lease| lease.name.starts_with(prefix)).collect())
+    }
+}
+
+/// Reads a lease from a row of the `LeaseTable`.
+fn lease_from_row(
+    row: &Value,
+    table: &LeaseTable
+) -> Result<Lease, SupabaseError> {
+    let column = |column: &str| {
+        row.get(column)
+            .and_then(Value::as_str)
+            .ok_or_else(|| SupabaseError::FetchError(format!("Column `{}` not found", column)))
+    };
+    let expires_at: DateTime<Utc> = DateTime::parse_from_rfc3339(column(&table.expires_at_column_name)?)
+        .map_err(|e| SupabaseError::FetchError(e.to_string()))?
+        .with_timezone(&Utc);
+    Ok(Lease {
+        name: column(&table.name_column_name)?.to_string(),
+        holder: column(&table.holder_column_name)?.to_string(),
+        expires_at,
+    })
+}
+
+impl Lease {
+    /// Returns whether the lease expired at `now`, so any replica may take it over.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+}
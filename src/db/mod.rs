@@ -1,20 +1,421 @@
 //! Databasing module for the pricing alerts
+use serde::{Deserialize, Serialize};
+
+use crate::notify::PayloadFormat;
+use crate::utils::crypto::FieldCipher;
+use crate::retry::RetryPolicy;
+use crate::timeout::Timeouts;
+
+pub mod age;
 pub mod auth;
+pub mod batch;
+pub mod bulk;
 pub mod client;
+pub mod concurrency;
+pub mod distance;
+pub mod duplicate;
+pub mod gdpr;
+pub mod heartbeat;
+pub mod heatmap;
+pub mod history;
+pub mod lease;
+pub mod operations;
+pub mod quota;
+pub mod rest;
+pub mod search;
+pub mod shard;
+pub mod sharing;
+pub mod storage;
+pub mod triggering;
+pub mod webhooks;
 
 /// ## Supabase API authentication
-#[derive(Clone, Debug)]
+/// `Debug` output never contains the key.
+#[derive(Clone)]
 pub struct Supabase {
     pub key: String,
     pub url: String,
+    /// Cipher used for the columns listed in `TableConfig::encrypted_columns`.
+    pub cipher: Option<FieldCipher>,
+    /// Limits enforced when adding alerts.
+    pub quota: Option<QuotaPolicy>,
+    /// What to do when a user adds an alert identical to an existing one.
+    pub duplicates: DuplicatePolicy,
+    /// Smallest accepted distance between a new alert's level and the current price.
+    pub min_distance: Option<DistancePolicy>,
+    /// Retries of failed reads and idempotent writes, inserts are never retried.
+    pub retry: RetryPolicy,
+    /// Timeouts of every request, each attempt of a retried call is bounded by their total.
+    pub timeouts: Timeouts,
+    /// Whether users may register webhooks on loopback and private networks.
+    pub private_webhooks: bool,
+}
+
+/// ## Per-user limits enforced when adding alerts
+///
+/// The limit is enforced by a Postgres function, which has to exist in the database,
+/// see [`quota::QUOTA_INSERT_FUNCTION`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuotaPolicy {
+    pub max_active_alerts_per_user: usize,
+}
+
+/// ## Minimum distance of a new alert's level from the current price
+///
+/// An alert whose level is closer to the price than `min_distance` would fire on the next
+/// check, e.g. after a typo or a stale chart. It is rejected with
+/// `SupabaseError::TooCloseToMarket`, or only logged when `reject` is `false`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DistancePolicy {
+    /// In pips or in percent of the current price.
+    pub min_distance: crate::Tolerance,
+    pub reject: bool,
+}
+
+/// ## Handling of alerts identical to an existing alert of the same user
+///
+/// Alerts are identical when their user ID, symbol, price level and initial direction match,
+/// regardless of their hash.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Reject the new alert with `SupabaseError::DuplicateAlert`.
+    Reject,
+    /// Insert the new alert, then delete the existing alerts.
+    Replace,
+    /// Insert the new alert anyway.
+    #[default]
+    Allow,
+}
+
+/// ## Maximum age of alerts which have not triggered
+#[derive(Clone, Debug, PartialEq)]
+pub struct AgePolicy {
+    /// How long an alert may stay active before its owner is warned.
+    pub max_age: chrono::Duration,
+    /// Whether stale alerts are moved to the history table after the warning.
+    pub auto_archive: bool,
+}
+
+/// ## An alert which has been active longer than the `AgePolicy` allows
+#[derive(Clone, Debug, PartialEq)]
+pub struct StaleAlert {
+    pub alert: crate::Alert,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// ## Outcome of a maintenance pass of the `AgePolicy`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AgeReport {
+    /// Every stale alert that was found.
+    pub stale: Vec<StaleAlert>,
+    /// Hashes of the stale alerts moved to the history table.
+    pub archived: Vec<String>,
+    /// Notification and archival failures, as `(hash, error)`.
+    pub errors: Vec<(String, String)>,
 }
 
 /// ## Table configuration for the trade_alerts table
-#[derive(Clone,)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TableConfig {
     pub tablename: String,
     pub symbol_column_name: String,
     pub price_level_column_name: String,
     pub user_id_column_name: String,
     pub hash_column_name: String,
-}
\ No newline at end of file
+    pub condition_column_name: String,
+    pub direction_column_name: String,
+    pub expiry_column_name: String,
+    pub metadata_column_name: String,
+    pub evaluate_on_column_name: String,
+    pub triggered_at_column_name: String,
+    pub trigger_price_column_name: String,
+    pub created_at_column_name: String,
+    pub quote_time_column_name: String,
+    pub tags_column_name: String,
+    pub tolerance_column_name: String,
+    pub version_column_name: String,
+    pub upper_price_level_column_name: String,
+    pub sessions_column_name: String,
+    /// Whether a triggered alert is being delivered, see [`Supabase::claim_trigger`].
+    pub trigger_state_column_name: String,
+    pub idempotency_key_column_name: String,
+    /// The instance which claimed a triggered alert, see [`Supabase::claim_trigger`].
+    pub claimed_by_column_name: String,
+    /// When a triggered alert was claimed, stale claims are recovered by another instance.
+    pub claimed_at_column_name: String,
+    /// URL of the chart snapshot uploaded when the alert triggered, see [`Supabase::upload_object`].
+    pub chart_url_column_name: String,
+    /// When the owner was warned the alert is stale, so alerts kept by an `AgePolicy` are warned about once.
+    pub stale_warned_at_column_name: String,
+    /// Columns which are encrypted before they are written and decrypted when fetched.
+    pub encrypted_columns: Vec<String>,
+}
+
+/// ## Result of inserting a single alert of a batch
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchInsertion {
+    pub hash: String,
+    /// Why the alert was not inserted, `None` if it was.
+    pub error: Option<String>,
+}
+
+/// ## Join table of alerts shared with users other than their owner
+#[derive(Clone, Debug, PartialEq)]
+pub struct SharedAlertTable {
+    pub tablename: String,
+    /// Column holding the hash of the shared alert.
+    pub hash_column_name: String,
+    /// Column holding the user the alert is shared with.
+    pub user_id_column_name: String,
+}
+
+/// ## Streaming aggregation of alert levels into per-symbol histograms
+///
+/// Buckets grow geometrically by `bucket_percent`, so symbols trading at very
+/// different prices get comparable resolution without knowing their range upfront.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HeatMap {
+    pub bucket_percent: f64,
+    counts: std::collections::HashMap<String, std::collections::BTreeMap<i64, usize>>,
+}
+
+/// ## Histogram of the alert levels set on a symbol
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LevelHistogram {
+    pub symbol: String,
+    /// Non-empty buckets, lowest level first.
+    pub buckets: Vec<LevelBucket>,
+}
+
+/// ## Number of alerts with a level in `[lower, upper)`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LevelBucket {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: usize,
+}
+
+/// ## A table holding user data, used for GDPR removal
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserDataTable {
+    pub tablename: String,
+    pub user_id_column_name: String,
+}
+
+/// ## Per-table result of removing a user's data
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeletionReport {
+    pub user_id: String,
+    pub tables: Vec<TableDeletion>,
+}
+
+/// ## Result of removing a user's data from a single table
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableDeletion {
+    pub tablename: String,
+    /// Number of rows removed.
+    pub deleted: usize,
+    /// The error if the table could not be cleaned.
+    pub error: Option<String>,
+}
+
+/// ## How the text of an alert search is matched against the notes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextMatch {
+    /// Case-insensitive substring match (`ilike`).
+    #[default]
+    Contains,
+    /// Postgres full-text search with web search syntax (`wfts`), e.g. `breakout -failed`.
+    FullText,
+}
+
+/// ## Which tables an alert search covers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchScope {
+    /// Alerts which have not triggered yet.
+    #[default]
+    Active,
+    /// Alerts moved to the history table.
+    Archived,
+    /// Active alerts first, then archived alerts.
+    All,
+}
+
+/// ## Search over active and archived alerts by note text and tags
+///
+/// Every set criterion must match. Notes are read from the `note` key of the metadata column.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AlertSearch {
+    pub user_id: Option<String>,
+    pub symbol: Option<String>,
+    /// Text to find in the note of the alert.
+    pub text: Option<String>,
+    pub text_match: TextMatch,
+    /// Tags the alert must all carry.
+    pub tags: Vec<String>,
+    pub scope: SearchScope,
+    /// Number of results skipped.
+    pub offset: usize,
+    /// Largest number of results returned.
+    pub limit: usize,
+}
+
+/// ## An alert found by a search
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "status", content = "alert", rename_all = "snake_case")]
+pub enum SearchHit {
+    Active(crate::Alert),
+    Archived(crate::TriggeredAlert),
+}
+
+/// ## A page of search results
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SearchPage {
+    pub hits: Vec<SearchHit>,
+    pub offset: usize,
+    pub limit: usize,
+    /// The offset of the next page, `None` on the last page.
+    pub next_offset: Option<usize>,
+}
+
+/// ## Table of the heartbeat rows of running systems
+/// Holds a row per instance with the columns of [`crate::Health`], see [`Supabase::write_heartbeat`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeartbeatTable {
+    pub tablename: String,
+    /// Column holding the ID of the instance the row belongs to.
+    pub instance_id_column_name: String,
+    /// Column holding when the row was last written.
+    pub updated_at_column_name: String,
+}
+
+/// ## Table of the leases electing a leader among replicas
+/// Holds a row per lease, see [`Supabase::acquire_lease`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeaseTable {
+    pub tablename: String,
+    /// Column holding the name of the lease, unique per row.
+    pub name_column_name: String,
+    /// Column holding the ID of the instance holding the lease.
+    pub holder_column_name: String,
+    /// Column holding when the lease expires unless it is renewed.
+    pub expires_at_column_name: String,
+}
+
+/// ## A lease as stored in a `LeaseTable`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lease {
+    pub name: String,
+    /// The ID of the instance holding the lease.
+    pub holder: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// ## Table of the symbols assigned to sharded workers
+/// Holds a row per symbol, see [`Supabase::assign_symbols`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShardTable {
+    pub tablename: String,
+    /// Column holding the symbol, unique per row.
+    pub symbol_column_name: String,
+    /// Column holding the ID of the worker checking the symbol.
+    pub worker_column_name: String,
+    /// Column holding when the symbol was assigned to the worker.
+    pub assigned_at_column_name: String,
+}
+
+/// ## A symbol assignment as stored in a `ShardTable`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShardAssignment {
+    pub symbol: String,
+    /// The ID of the worker checking the symbol.
+    pub worker: String,
+    pub assigned_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// ## A Supabase Storage bucket holding uploaded files
+///
+/// Files are uploaded under `prefix` and linked by their public URL, so the bucket should be
+/// public. Files older than `retention` are deleted by [`Supabase::delete_objects_before`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct StorageBucket {
+    pub name: String,
+    /// Folder the files are uploaded to, empty for the root of the bucket.
+    pub prefix: String,
+    /// How long files are kept, `None` to keep them forever.
+    pub retention: Option<std::time::Duration>,
+}
+
+/// ## Table of the webhook endpoints users registered
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebhookTable {
+    pub tablename: String,
+    /// Column holding the generated ID of the webhook.
+    pub id_column_name: String,
+    pub user_id_column_name: String,
+    pub url_column_name: String,
+    /// Column holding the payload format, e.g. `flat`.
+    pub format_column_name: String,
+    /// Column holding the secret every delivery is signed with.
+    pub secret_column_name: String,
+    /// Column holding whether the webhook receives deliveries.
+    pub enabled_column_name: String,
+}
+
+/// ## A webhook endpoint registered by a user
+/// `Debug` output never contains the secret.
+#[derive(Clone, PartialEq)]
+pub struct UserWebhook {
+    pub id: String,
+    pub user_id: String,
+    pub url: String,
+    pub format: PayloadFormat,
+    /// Secret deliveries are signed with, see [`crate::notify::webhook::SIGNATURE_HEADER`].
+    pub secret: String,
+    /// Disabled webhooks are kept but receive no deliveries.
+    pub enabled: bool,
+}
+
+/// ## A single operation of an edit session, see [`Supabase::apply_operations`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlertOp {
+    Create(crate::Alert),
+    Update { hash: String, update: crate::AlertUpdate },
+    Delete { hash: String },
+    /// Stops the alert from triggering by expiring it now, it stays stored until deleted.
+    Disable { hash: String },
+}
+
+/// ## How `Supabase::apply_operations` handles a failing operation
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ApplyMode {
+    /// Every operation is attempted, failures don't affect the others.
+    #[default]
+    BestEffort,
+    /// Stops at the first failure and undoes the operations applied before it.
+    AllOrNothing,
+}
+
+/// ## What happened to a single operation
+#[derive(Clone, Debug, PartialEq)]
+pub enum OpStatus {
+    Applied,
+    Failed(String),
+    /// Applied, then undone because a later operation failed.
+    RolledBack,
+    /// Applied, but undoing it after a later failure failed as well.
+    RollbackFailed(String),
+    /// Not attempted because an earlier operation failed.
+    Skipped,
+}
+
+/// ## Result of a single operation, in the order the operations were given
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpResult {
+    /// The hash of the alert the operation applies to.
+    pub hash: String,
+    pub status: OpStatus,
+}
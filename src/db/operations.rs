@@ -0,0 +1,211 @@
+//! ## Edit sessions
+//!
+//! Applies a list of alert operations in order with one call, so a UI can submit everything
+//! a user changed at once. PostgREST has no transactions across requests, so all-or-nothing
+//! mode undoes the applied operations itself: creations are deleted, updated and disabled
+//! alerts get their previous row back and deleted alerts are inserted again.
+
+use chrono::Utc;
+use serde_json::Value;
+
+use crate::AlertUpdate;
+use crate::data::{PriceProvider, XylexApi};
+use crate::db::rest::eq;
+use crate::db::{AlertOp, ApplyMode, BatchInsertion, OpResult, OpStatus, Supabase, TableConfig};
+
+/// How to undo an applied operation.
+enum Undo {
+    /// Delete the created alert.
+    Delete,
+    /// Write the row back, over the changed alert or in place of the deleted one.
+    Restore { row: Value, deleted: bool },
+}
+
+impl AlertOp {
+    /// Returns the hash of the alert the operation applies to.
+    pub fn hash(&self) -> &str {
+        match self {
+            AlertOp::Create(alert) => &alert.hash,
+            AlertOp::Update { hash, .. } | AlertOp::Delete { hash } | AlertOp::Disable { hash } => hash,
+        }
+    }
+}
+
+impl OpResult {
+    /// Returns `true` if the operation is in effect.
+    pub fn is_applied(&self) -> bool {
+        self.status == OpStatus::Applied
+    }
+}
+
+impl Supabase {
+    /// Applies operations in order and reports the outcome of each.
+    ///
+    /// Creations go through [`Supabase::add_alerts_batch`], so quotas and duplicate hashes are
+    /// checked as usual. Updates and disables are conditional on the version the alert was read
+    /// at, so a concurrent change fails the operation instead of being overwritten.
+    /// In `ApplyMode::AllOrNothing` the first failure stops the session and
+    /// every operation applied before it is undone, newest first; undoing is best-effort,
+    /// a failure to undo is reported as `OpStatus::RollbackFailed`.
+    ///
+    /// # Parameters
+    /// - `operations`: The operations, applied in the given order.
+    /// - `mode`: Whether a failure undoes the session.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// One `OpResult` per operation, in the order the operations were given.
+    pub async fn apply_operations(
+        &self,
+        operations: Vec<AlertOp>,
+        mode: ApplyMode,
+        config: &TableConfig
+    ) -> Vec<OpResult> {
+        self.apply_session(None::<&XylexApi>, operations, mode, config).await
+    }
+
+    /// Applies operations like `apply_operations`, pricing created alerts with another `PriceProvider`.
+    ///
+    /// # Parameters
+    /// - `provider`: The feed the created alerts are priced with.
+    /// - `operations`: The operations, applied in the given order.
+    /// - `mode`: Whether a failure undoes the session.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    pub async fn apply_operations_with<P: PriceProvider + ?Sized>(
+        &self,
+        provider: &P,
+        operations: Vec<AlertOp>,
+        mode: ApplyMode,
+        config: &TableConfig
+    ) -> Vec<OpResult> {
+        self.apply_session(Some(provider), operations, mode, config).await
+    }
+
+    /// Applies a session, creations are priced with `provider` or the Xylex API from the environment.
+    async fn apply_session<P: PriceProvider + ?Sized>(
+        &self,
+        provider: Option<&P>,
+        operations: Vec<AlertOp>,
+        mode: ApplyMode,
+        config: &TableConfig
+    ) -> Vec<OpResult> {
+        let mut results: Vec<OpResult> = operations
+            .iter()
+            .map(|operation| OpResult { hash: operation.hash().to_string(), status: OpStatus::Skipped })
+            .collect();
+        let mut undo: Vec<(usize, Undo)> = Vec::new();
+
+        for (index, operation) in operations.into_iter().enumerate() {
+            match self.apply_operation(provider, operation, config).await {
+                Ok(step) => {
+                    results[index].status = OpStatus::Applied;
+                    undo.push((index, step));
+                },
+                Err(e) => {
+                    results[index].status = OpStatus::Failed(e);
+                    if mode == ApplyMode::AllOrNothing {
+                        break;
+                    }
+                },
+            }
+        }
+
+        let failed: bool = results.iter().any(|result| matches!(result.status, OpStatus::Failed(_)));
+        if mode == ApplyMode::AllOrNothing && failed {
+            for (index, step) in undo.into_iter().rev() {
+                results[index].status = match self.undo_operation(&results[index].hash, step, config).await {
+                    Ok(()) => OpStatus::RolledBack,
+                    Err(e) => {
+                        println!("Error rolling back the operation on {}: {}", results[index].hash, e);
+                        OpStatus::RollbackFailed(e)
+                    },
+                };
+            }
+        }
+        results
+    }
+
+    /// Applies a single operation and returns how to undo it.
+    async fn apply_operation<P: PriceProvider + ?Sized>(
+        &self,
+        provider: Option<&P>,
+        operation: AlertOp,
+        config: &TableConfig
+    ) -> Result<Undo, String> {
+        if let AlertOp::Create(alert) = operation {
+            let inserted: Vec<BatchInsertion> = match provider {
+                Some(provider) => self.add_alerts_batch_with(provider, vec![alert], config.clone()).await,
+                None => self.add_alerts_batch(vec![alert], config.clone()).await,
+            };
+            return match inserted.into_iter().next().and_then(|insertion| insertion.error) {
+                Some(e) => Err(e),
+                None => Ok(Undo::Delete),
+            };
+        }
+
+        let hash: String = operation.hash().to_string();
+        let filters = [eq(&config.hash_column_name, &hash)];
+        let update: AlertUpdate = match operation {
+            AlertOp::Delete { .. } => {
+                let deleted: Vec<Value> = self.rest_delete(&config.tablename, &filters).await?;
+                return match deleted.into_iter().next() {
+                    Some(row) => Ok(Undo::Restore { row, deleted: true }),
+                    None => Err(format!("No alert with hash {}", hash)),
+                };
+            },
+            AlertOp::Update { update, .. } => update,
+            AlertOp::Disable { .. } => AlertUpdate { expires_at: Some(Some(Utc::now())), ..AlertUpdate::default() },
+            AlertOp::Create(_) => unreachable!("Creations are applied above"),
+        };
+
+        // The row as it was, so the change can be undone
+        let row: Value = self
+            .rest_select(&config.tablename, &filters)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("No alert with hash {}", hash))?;
+
+        if !update.is_empty() {
+            match row.get(&config.version_column_name).and_then(Value::as_i64) {
+                Some(version) => {
+                    self.update_alert_if_version(&hash, update, version, config)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                },
+                // Tables without a version column are updated directly
+                None => {
+                    let mut body: Value = update.to_value(config);
+                    self.seal_row(config, &mut body).map_err(|e| e.to_string())?;
+                    self.rest_update(&config.tablename, &filters, &body).await?;
+                },
+            }
+        }
+        Ok(Undo::Restore { row, deleted: false })
+    }
+
+    async fn undo_operation(
+        &self,
+        hash: &str,
+        undo: Undo,
+        config: &TableConfig
+    ) -> Result<(), String> {
+        let filters = [eq(&config.hash_column_name, hash)];
+        match undo {
+            Undo::Delete => self.rest_delete(&config.tablename, &filters).await.map(|_| ()),
+            Undo::Restore { mut row, deleted } => {
+                // The id is generated by the database, the version is bumped by the write
+                if let Some(columns) = row.as_object_mut() {
+                    columns.remove("id");
+                }
+                if deleted {
+                    return self.rest_insert(&config.tablename, &[row]).await.map(|_| ());
+                }
+                if let Some(columns) = row.as_object_mut() {
+                    columns.remove(&config.version_column_name);
+                }
+                self.write_alert(hash, row, config).await.map(|_| ()).map_err(|e| e.to_string())
+            },
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! ## Per-user quotas
+//!
+//! Counting of a user's active alerts and enforcement of the configured `QuotaPolicy`.
+//!
+//! Counting and then inserting would let concurrent requests of a user both pass the count,
+//! so with a quota set new alerts are inserted by the `insert_alerts_within_quota` Postgres
+//! function, which counts and inserts under a per-user lock in a single transaction:
+//!
+//! ```sql
+//! create function insert_alerts_within_quota(
+//!     rows jsonb, max_active integer, table_name text, user_id_column text, expiry_column text
+//! ) returns setof jsonb language plpgsql as $$
+//! declare
+//!     new_row jsonb;
+//!     active integer;
+//! begin
+//!     for new_row in select * from jsonb_array_elements(rows) loop
+//!         perform pg_advisory_xact_lock(hashtext(table_name || (new_row ->> user_id_column)));
+//!         execute format('select count(*) from %I where %I = $1 and (%I is null or %I > now())',
+//!                 table_name, user_id_column, expiry_column, expiry_column)
+//!             into active using new_row ->> user_id_column;
+//!         if active < max_active then
+//!             execute format('insert into %I select * from jsonb_populate_record(null::%I, $1)',
+//!                 table_name, table_name) using new_row;
+//!             return next new_row;
+//!         end if;
+//!     end loop;
+//! end $$;
+//! ```
+
+use std::error::Error;
+
+use serde_json::{Value, json};
+use supabase_rs::SupabaseClient;
+
+use crate::alert::is_expired;
+use crate::db::{QuotaPolicy, Supabase, TableConfig};
+use crate::errors::SupabaseError;
+
+/// Postgres function inserting alert rows only while their user is under the quota, see the module docs.
+pub const QUOTA_INSERT_FUNCTION: &str = "insert_alerts_within_quota";
+
+impl Supabase {
+    /// Sets the limits enforced when adding alerts.
+    ///
+    /// # Parameters
+    /// - `quota`: The `QuotaPolicy` to enforce.
+    pub fn with_quota_policy(
+        mut self,
+        quota: QuotaPolicy
+    ) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    /// Counts the active (not expired) alerts of a user.
+    ///
+    /// # Parameters
+    /// - `user_id`: The user ID for which to count alerts.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of active alerts or an error.
+    ///
+    /// # Errors
+    /// Returns an error if the query execution fails.
+    pub async fn count_alerts_by_user_id(
+        &self,
+        user_id: &str,
+        config: &TableConfig
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let supabase: SupabaseClient = Supabase::authenticate(self).await;
+
+        let response: Result<Vec<Value>, String> = self
+            .retrying(|| {
+                supabase
+                    .select(&config.tablename)
+                    .eq(&config.user_id_column_name, user_id)
+                    .execute()
+            })
+            .await;
+
+        match response {
+            Ok(values) => Ok(values
+                .iter()
+                .filter(|value| !is_expired(value.get(&config.expiry_column_name)))
+                .count()),
+            Err(e) => Err(Box::new(SupabaseError::FetchError(e)))
+        }
+    }
+
+    /// Checks whether a user may add another alert under the configured `QuotaPolicy`.
+    ///
+    /// Only an early check, to fail before fetching prices: the limit is enforced when the
+    /// alert is inserted, see [`QUOTA_INSERT_FUNCTION`].
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::QuotaExceeded` if the user already has the maximum number of active alerts,
+    /// or the underlying error if counting the alerts fails.
+    pub async fn check_quota(
+        &self,
+        user_id: &str,
+        config: &TableConfig
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let quota = match &self.quota {
+            Some(quota) => quota,
+            None => return Ok(()),
+        };
+
+        let active: usize = self.count_alerts_by_user_id(user_id, config).await?;
+        if active >= quota.max_active_alerts_per_user {
+            return Err(Box::new(SupabaseError::QuotaExceeded(format!(
+                "user already has {} of {} active alerts",
+                active, quota.max_active_alerts_per_user
+            ))));
+        }
+
+        Ok(())
+    }
+
+    /// Inserts alert rows, skipping those whose user reached the configured `QuotaPolicy`.
+    ///
+    /// With a quota, the rows are inserted by [`QUOTA_INSERT_FUNCTION`], which counts and
+    /// inserts atomically, so concurrent insertions never exceed the limit.
+    ///
+    /// # Returns
+    /// A `Result` containing the rows which were inserted.
+    ///
+    /// # Errors
+    /// Returns the response of the database if the insertion fails.
+    pub(crate) async fn insert_within_quota(
+        &self,
+        rows: &[Value],
+        config: &TableConfig
+    ) -> Result<Vec<Value>, String> {
+        let Some(quota) = &self.quota else {
+            return self.rest_insert(&config.tablename, rows).await;
+        };
+
+        let args: Value = json!({
+            "rows": rows,
+            "max_active": quota.max_active_alerts_per_user,
+            "table_name": config.tablename,
+            "user_id_column": config.user_id_column_name,
+            "expiry_column": config.expiry_column_name,
+        });
+        self.rest_rpc(QUOTA_INSERT_FUNCTION, &args).await
+    }
+}
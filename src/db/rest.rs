@@ -0,0 +1,186 @@
+//! ## Raw PostgREST requests
+//!
+//! `supabase_rs` only supports updates and deletes by `id` and a single filter per column.
+//! These helpers send filtered requests straight to the PostgREST endpoint of the project
+//! for the operations that need more than that.
+
+use chrono::{DateTime, Utc};
+use reqwest::RequestBuilder;
+use serde_json::Value;
+
+use crate::db::Supabase;
+
+/// A PostgREST filter as a `(column, "operator.value")` query pair, e.g. `("user_id", "eq.user123")`.
+pub type Filter = (String, String);
+
+/// Builds an equality filter.
+pub fn eq(column: &str, value: &str) -> Filter {
+    (column.to_string(), format!("eq.{}", value))
+}
+
+/// Builds a filter matching values greater than or equal to `value`.
+pub fn gte(column: &str, value: f64) -> Filter {
+    (column.to_string(), format!("gte.{}", value))
+}
+
+/// Builds a filter matching values less than or equal to `value`.
+pub fn lte(column: &str, value: f64) -> Filter {
+    (column.to_string(), format!("lte.{}", value))
+}
+
+/// Builds a filter matching timestamps strictly before `time`.
+pub fn before(column: &str, time: DateTime<Utc>) -> Filter {
+    (column.to_string(), format!("lt.{}", time.to_rfc3339()))
+}
+
+/// Builds a filter matching JSON array columns containing all of the values.
+pub fn contains(column: &str, values: &[&str]) -> Filter {
+    (column.to_string(), format!("cs.{}", serde_json::json!(values)))
+}
+
+/// Builds a case-insensitive pattern filter, `*` matches any characters.
+pub fn ilike(column: &str, pattern: &str) -> Filter {
+    (column.to_string(), format!("ilike.{}", pattern))
+}
+
+/// Builds a full-text filter using web search syntax, e.g. `breakout -failed`.
+pub fn full_text(column: &str, query: &str) -> Filter {
+    (column.to_string(), format!("wfts.{}", query))
+}
+
+/// Builds a filter matching rows where the column is `null`.
+pub fn is_null(column: &str) -> Filter {
+    (column.to_string(), "is.null".to_string())
+}
+
+/// Builds a filter matching any of the values.
+pub fn is_in(column: &str, values: &[&str]) -> Filter {
+    let quoted: Vec<String> = values
+        .iter()
+        .map(|value| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect();
+    (column.to_string(), format!("in.({})", quoted.join(",")))
+}
+
+impl Supabase {
+    /// Deletes all rows matching the filters and returns the deleted rows.
+    ///
+    /// Refuses to run without filters so a table is never wiped by accident.
+    pub(crate) async fn rest_delete(
+        &self,
+        table: &str,
+        filters: &[Filter]
+    ) -> Result<Vec<Value>, String> {
+        if filters.is_empty() {
+            return Err("Refusing to delete without filters".to_string());
+        }
+        let request = self
+            .rest_request(self.timeouts.client().delete(self.rest_endpoint(table)), filters)
+            .header("Prefer", "return=representation");
+
+        self.retrying(|| resend(&request)).await
+    }
+
+    /// Selects all rows matching the filters.
+    pub(crate) async fn rest_select(
+        &self,
+        table: &str,
+        filters: &[Filter]
+    ) -> Result<Vec<Value>, String> {
+        let request = self.rest_request(self.timeouts.client().get(self.rest_endpoint(table)), filters);
+
+        self.retrying(|| resend(&request)).await
+    }
+
+    /// Updates all rows matching the filters and returns the updated rows.
+    ///
+    /// Refuses to run without filters so a table is never overwritten by accident.
+    pub(crate) async fn rest_update(
+        &self,
+        table: &str,
+        filters: &[Filter],
+        body: &Value
+    ) -> Result<Vec<Value>, String> {
+        if filters.is_empty() {
+            return Err("Refusing to update without filters".to_string());
+        }
+        let request = self
+            .rest_request(self.timeouts.client().patch(self.rest_endpoint(table)), filters)
+            .header("Prefer", "return=representation")
+            .json(body);
+
+        self.retrying(|| resend(&request)).await
+    }
+
+    /// Inserts rows in a single request and returns the inserted rows.
+    pub(crate) async fn rest_insert(
+        &self,
+        table: &str,
+        rows: &[Value]
+    ) -> Result<Vec<Value>, String> {
+        let request = self
+            .rest_request(self.timeouts.client().post(self.rest_endpoint(table)), &[])
+            .header("Prefer", "return=representation")
+            .json(rows);
+
+        send(request).await
+    }
+
+    /// Calls a Postgres function with named arguments and returns the rows it returned.
+    ///
+    /// Not retried, as the function may not be idempotent.
+    pub(crate) async fn rest_rpc(
+        &self,
+        function: &str,
+        args: &Value
+    ) -> Result<Vec<Value>, String> {
+        let request = self
+            .rest_request(self.timeouts.client().post(self.rest_endpoint(&format!("rpc/{}", function))), &[])
+            .json(args);
+
+        send(request).await
+    }
+
+    fn rest_endpoint(&self, table: &str) -> String {
+        format!("{}/rest/v1/{}", self.url.trim_end_matches('/'), table)
+    }
+
+    fn rest_request(
+        &self,
+        request: RequestBuilder,
+        filters: &[Filter]
+    ) -> RequestBuilder {
+        request
+            .query(filters)
+            .header("apikey", &self.key)
+            .header("Authorization", format!("Bearer {}", &self.key))
+            .header("Content-Type", "application/json")
+    }
+}
+
+/// Sends a clone of a request, so the request can be sent again on the next attempt.
+pub(crate) async fn resend(request: &RequestBuilder) -> Result<Vec<Value>, String> {
+    let request: RequestBuilder = request
+        .try_clone()
+        .ok_or_else(|| "Request body can't be cloned for another attempt".to_string())?;
+    send(request).await
+}
+
+/// Sends a request and parses the returned rows.
+pub(crate) async fn send(request: RequestBuilder) -> Result<Vec<Value>, String> {
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    let text = response.text().await.map_err(|e| e.to_string())?;
+
+    if !status.is_success() {
+        return Err(format!("{}: {}", status, text));
+    }
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    match serde_json::from_str::<Value>(&text).map_err(|e| e.to_string())? {
+        Value::Array(rows) => Ok(rows),
+        row => Ok(vec![row]),
+    }
+}
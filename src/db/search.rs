@@ -0,0 +1,255 @@
+//! ## Alert search
+//!
+//! Finds active and archived alerts by the text of their note and their tags, page by page,
+//! for support tooling and search boxes. Filters run server-side, so the metadata and tags
+//! columns must not be encrypted.
+
+use std::error::Error;
+
+use serde_json::Value;
+
+use crate::{Alert, TriggeredAlert};
+use crate::db::rest::{Filter, contains, eq, full_text, ilike};
+use crate::db::{AlertSearch, SearchHit, SearchPage, SearchScope, Supabase, TableConfig, TextMatch};
+use crate::errors::SupabaseError;
+
+/// Key of the metadata object holding the note of an alert.
+pub const NOTE_KEY: &str = "note";
+
+/// Number of results per page when no limit is set.
+pub const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// Largest number of results per page.
+pub const MAX_SEARCH_LIMIT: usize = 1000;
+
+impl Default for AlertSearch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AlertSearch {
+    /// Creates a search matching every active alert, returning the first `DEFAULT_SEARCH_LIMIT` results.
+    pub fn new() -> Self {
+        Self {
+            user_id: None,
+            symbol: None,
+            text: None,
+            text_match: TextMatch::Contains,
+            tags: Vec::new(),
+            scope: SearchScope::Active,
+            offset: 0,
+            limit: DEFAULT_SEARCH_LIMIT,
+        }
+    }
+
+    /// Only matches the alerts of a user.
+    pub fn with_user_id(
+        mut self,
+        user_id: impl Into<String>
+    ) -> Self {
+        self.user_id = Some(user_id.into());
+        self
+    }
+
+    /// Only matches the alerts on a symbol.
+    pub fn with_symbol(
+        mut self,
+        symbol: impl Into<String>
+    ) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    /// Only matches alerts whose note contains `text`, ignoring case.
+    pub fn with_text(
+        mut self,
+        text: impl Into<String>
+    ) -> Self {
+        self.text = Some(text.into());
+        self.text_match = TextMatch::Contains;
+        self
+    }
+
+    /// Only matches alerts whose note matches the full-text `query`, e.g. `breakout -failed`.
+    pub fn with_full_text(
+        mut self,
+        query: impl Into<String>
+    ) -> Self {
+        self.text = Some(query.into());
+        self.text_match = TextMatch::FullText;
+        self
+    }
+
+    /// Only matches alerts carrying `tag`, may be called several times to require every tag.
+    pub fn with_tag(
+        mut self,
+        tag: impl Into<String>
+    ) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Sets the tables searched.
+    pub fn with_scope(
+        mut self,
+        scope: SearchScope
+    ) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Sets the page returned, `limit` is clamped to `1..=MAX_SEARCH_LIMIT`.
+    pub fn with_page(
+        mut self,
+        offset: usize,
+        limit: usize
+    ) -> Self {
+        self.offset = offset;
+        self.limit = limit.clamp(1, MAX_SEARCH_LIMIT);
+        self
+    }
+
+    /// Returns the PostgREST filters of the search on a table, without ordering or paging.
+    pub fn filters(&self, config: &TableConfig) -> Vec<Filter> {
+        let mut filters: Vec<Filter> = Vec::new();
+        if let Some(user_id) = &self.user_id {
+            filters.push(eq(&config.user_id_column_name, user_id));
+        }
+        if let Some(symbol) = &self.symbol {
+            filters.push(eq(&config.symbol_column_name, symbol));
+        }
+        if let Some(text) = self.text.as_deref().map(str::trim).filter(|text| !text.is_empty()) {
+            let note: String = format!("{}->>{}", config.metadata_column_name, NOTE_KEY);
+            filters.push(match self.text_match {
+                TextMatch::Contains => ilike(&note, &format!("*{}*", escape_pattern(text))),
+                TextMatch::FullText => full_text(&note, text),
+            });
+        }
+        if !self.tags.is_empty() {
+            let tags: Vec<&str> = self.tags.iter().map(String::as_str).collect();
+            filters.push(contains(&config.tags_column_name, &tags));
+        }
+        filters
+    }
+}
+
+impl SearchHit {
+    /// Returns the hash of the alert.
+    pub fn hash(&self) -> &str {
+        match self {
+            SearchHit::Active(alert) => &alert.hash,
+            SearchHit::Archived(alert) => &alert.hash,
+        }
+    }
+
+    /// Returns the note of the alert, if any.
+    pub fn note(&self) -> Option<&str> {
+        let metadata: Option<&Value> = match self {
+            SearchHit::Active(alert) => alert.metadata.as_ref(),
+            SearchHit::Archived(alert) => alert.metadata.as_ref(),
+        };
+        metadata.and_then(|metadata| metadata[NOTE_KEY].as_str())
+    }
+}
+
+impl Supabase {
+    /// Searches alerts by note text and tags.
+    ///
+    /// Active alerts are returned newest first, archived alerts most recently triggered first.
+    /// With `SearchScope::All` the active alerts come before the archived ones; pages deep into
+    /// the archive fetch every active match before it.
+    ///
+    /// # Parameters
+    /// - `search`: The criteria and page of the search.
+    /// - `config`: The configuration of the alerts table.
+    /// - `history_config`: The configuration of the history table.
+    ///
+    /// # Returns
+    /// A `Result` containing the page of matching alerts or an error.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if a query fails or a row misses a required column.
+    pub async fn search_alerts(
+        &self,
+        search: &AlertSearch,
+        config: &TableConfig,
+        history_config: &TableConfig
+    ) -> Result<SearchPage, Box<dyn Error + Send + Sync>> {
+        let limit: usize = search.limit.clamp(1, MAX_SEARCH_LIMIT);
+        // One extra row tells whether another page follows
+        let wanted: usize = limit + 1;
+
+        let mut hits: Vec<SearchHit> = Vec::with_capacity(wanted);
+        let mut skip: usize = search.offset;
+        if search.scope != SearchScope::Archived {
+            let rows: Vec<Value> = self.search_table(search, config, "id", skip, wanted).await?;
+            skip = skip.saturating_sub(self.count_skipped(search, config, skip, rows.len()).await?);
+            for mut row in rows {
+                self.open_row(config, &mut row)?;
+                hits.push(SearchHit::Active(Alert::from_row(&row, config)?));
+            }
+        }
+        if search.scope != SearchScope::Active && hits.len() < wanted {
+            let order: &str = &history_config.triggered_at_column_name;
+            let rows: Vec<Value> = self.search_table(search, history_config, order, skip, wanted - hits.len()).await?;
+            for mut row in rows {
+                self.open_row(history_config, &mut row)?;
+                hits.push(SearchHit::Archived(TriggeredAlert::from_row(&row, history_config)?));
+            }
+        }
+
+        let next_offset: Option<usize> = (hits.len() > limit).then_some(search.offset + limit);
+        hits.truncate(limit);
+        Ok(SearchPage { hits, offset: search.offset, limit, next_offset })
+    }
+
+    async fn search_table(
+        &self,
+        search: &AlertSearch,
+        config: &TableConfig,
+        order: &str,
+        offset: usize,
+        limit: usize
+    ) -> Result<Vec<Value>, SupabaseError> {
+        let mut filters: Vec<Filter> = search.filters(config);
+        filters.push(("order".to_string(), format!("{}.desc", order)));
+        filters.push(("offset".to_string(), offset.to_string()));
+        filters.push(("limit".to_string(), limit.to_string()));
+
+        self.rest_select(&config.tablename, &filters)
+            .await
+            .map_err(SupabaseError::FetchError)
+    }
+
+    /// Returns how many active matches lie before the archived part of an `All` search page.
+    ///
+    /// When the page found active rows, all `offset` skipped rows were active. Otherwise the
+    /// active matches are counted, so the remaining offset applies to the archive.
+    async fn count_skipped(
+        &self,
+        search: &AlertSearch,
+        config: &TableConfig,
+        offset: usize,
+        found: usize
+    ) -> Result<usize, SupabaseError> {
+        if found > 0 || offset == 0 || search.scope != SearchScope::All {
+            return Ok(offset);
+        }
+        let mut filters: Vec<Filter> = search.filters(config);
+        filters.push(("select".to_string(), "id".to_string()));
+        let rows: Vec<Value> = self
+            .rest_select(&config.tablename, &filters)
+            .await
+            .map_err(SupabaseError::FetchError)?;
+        Ok(rows.len())
+    }
+}
+
+/// Escapes the `LIKE` wildcards of user input, PostgREST turns `*` into `%` so it is dropped.
+fn escape_pattern(text: &str) -> String {
+    text.replace('*', "")
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
@@ -0,0 +1,117 @@
+//! ## Persisted shard assignments
+//!
+//! Sharded workers record the symbols they check, so operators can see which worker checks
+//! which symbol, and the row of a symbol moves to its new worker once the workers rebalance,
+//! see [`crate::TradeAlertsBuilder::with_sharding`].
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+
+use crate::db::rest::{eq, is_in};
+use crate::db::{ShardAssignment, ShardTable, Supabase, TableConfig};
+use crate::errors::SupabaseError;
+
+/// Symbols per query of [`Supabase::fetch_data_for_symbols`], keeping the URL short.
+const SYMBOLS_PER_QUERY: usize = 100;
+
+impl Default for ShardTable {
+    /// Creates a `ShardTable` using the default `shard_assignments` table layout.
+    fn default() -> Self {
+        Self {
+            tablename: "shard_assignments".to_string(),
+            symbol_column_name: "symbol".to_string(),
+            worker_column_name: "worker".to_string(),
+            assigned_at_column_name: "assigned_at".to_string(),
+        }
+    }
+}
+
+impl Supabase {
+    /// Fetches the alerts on the given symbols, like [`Supabase::fetch_all_data`] for the rows of a shard.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if a query fails, or a field could not be decrypted.
+    pub async fn fetch_data_for_symbols(
+        &self,
+        symbols: &[&str],
+        config: &TableConfig
+    ) -> Result<Vec<HashMap<String, Value>>, SupabaseError> {
+        let mut rows: Vec<HashMap<String, Value>> = Vec::new();
+        for chunk in symbols.chunks(SYMBOLS_PER_QUERY) {
+            let values: Vec<Value> = self
+                .rest_select(&config.tablename, &[is_in(&config.symbol_column_name, chunk)])
+                .await
+                .map_err(SupabaseError::FetchError)?;
+            for mut value in values {
+                self.open_row(config, &mut value).map_err(|e| SupabaseError::FetchError(e.to_string()))?;
+                if let Value::Object(map) = value {
+                    rows.push(map.into_iter().collect());
+                }
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Assigns every symbol to `worker`, taking over the rows of their previous workers.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::UpdateError` or `SupabaseError::InsertionError` if a row could not be written.
+    pub async fn assign_symbols(
+        &self,
+        symbols: &[&str],
+        worker: &str,
+        table: &ShardTable
+    ) -> Result<(), SupabaseError> {
+        let assigned_at: String = Utc::now().to_rfc3339();
+        for symbol in symbols {
+            let row: Value = json!({
+                table.symbol_column_name.clone(): symbol,
+                table.worker_column_name.clone(): worker,
+                table.assigned_at_column_name.clone(): assigned_at,
+            });
+            let updated: Vec<Value> = self
+                .rest_update(&table.tablename, &[eq(&table.symbol_column_name, symbol)], &row)
+                .await
+                .map_err(SupabaseError::UpdateError)?;
+            if updated.is_empty() {
+                self.rest_insert(&table.tablename, &[row]).await.map_err(SupabaseError::InsertionError)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetches the assignment of every symbol, in no particular order.
+    ///
+    /// Rows which cannot be read are logged and skipped.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if the rows could not be fetched.
+    pub async fn fetch_shard_assignments(
+        &self,
+        table: &ShardTable
+    ) -> Result<Vec<ShardAssignment>, SupabaseError> {
+        let rows: Vec<Value> = self
+            .rest_select(&table.tablename, &[])
+            .await
+            .map_err(SupabaseError::FetchError)?;
+
+        let mut assignments: Vec<ShardAssignment> = Vec::with_capacity(rows.len());
+        for row in rows {
+            let column = |column: &str| row.get(column).and_then(Value::as_str);
+            let assigned_at: Option<DateTime<Utc>> = column(&table.assigned_at_column_name)
+                .and_then(|assigned_at| DateTime::parse_from_rfc3339(assigned_at).ok())
+                .map(|assigned_at| assigned_at.with_timezone(&Utc));
+            match (column(&table.symbol_column_name), column(&table.worker_column_name), assigned_at) {
+                (Some(symbol), Some(worker), Some(assigned_at)) => assignments.push(ShardAssignment {
+                    symbol: symbol.to_string(),
+                    worker: worker.to_string(),
+                    assigned_at,
+                }),
+                _ => println!("Skipping unreadable shard assignment: {}", row),
+            }
+        }
+        Ok(assignments)
+    }
+}
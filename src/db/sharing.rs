@@ -0,0 +1,206 @@
+//! ## Ownership transfer and shared alerts
+//!
+//! An alert has a single owner, but can be shared with other users through a join table
+//! so a whole trading desk is notified when a single watched level triggers.
+
+use std::error::Error;
+
+use serde_json::{Value, json};
+use supabase_rs::SupabaseClient;
+
+use crate::db::rest::{eq, is_in};
+use crate::db::{SharedAlertTable, Supabase, TableConfig};
+use crate::errors::SupabaseError;
+use crate::success::SupabaseSuccess;
+use crate::{Alert, TriggeredAlert};
+
+impl Supabase {
+    /// Transfers an alert to another user.
+    ///
+    /// The new owner's `QuotaPolicy`, if any, is checked first.
+    ///
+    /// # Parameters
+    /// - `hash`: The hash of the alert to transfer.
+    /// - `new_user_id`: The user who becomes the owner.
+    /// - `config`: A `TableConfig` struct containing the table and column names configuration.
+    ///
+    /// # Returns
+    /// A `Result` indicating success or error in the update.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::QuotaExceeded` if the new owner has no room for the alert,
+    /// or an error if fetching or updating the alert fails.
+    pub async fn transfer_alert(
+        &self,
+        hash: &str,
+        new_user_id: &str,
+        config: &TableConfig
+    ) -> Result<SupabaseSuccess, Box<dyn Error + Send + Sync>> {
+        self.check_quota(new_user_id, config).await?;
+
+        let mut body: Value = json!({ config.user_id_column_name.clone(): new_user_id });
+        self.seal_row(config, &mut body)?;
+
+        self.write_alert(hash, body, config).await
+    }
+
+    /// Shares an alert with another user, who is notified whenever it triggers.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::InsertionError` if the alert is already shared with the user
+    /// or the insertion fails.
+    pub async fn share_alert(
+        &self,
+        hash: &str,
+        user_id: &str,
+        shares: &SharedAlertTable
+    ) -> Result<SupabaseSuccess, Box<dyn Error + Send + Sync>> {
+        let supabase: SupabaseClient = Supabase::authenticate(self).await;
+
+        let body: Value = json!({
+            shares.hash_column_name.clone(): hash,
+            shares.user_id_column_name.clone(): user_id,
+        });
+
+        match self.within_timeouts(supabase.insert_if_unique(&shares.tablename, body)).await {
+            Ok(_) => Ok(SupabaseSuccess::InsertionSuccess),
+            Err(e) => Err(Box::new(SupabaseError::InsertionError(e)))
+        }
+    }
+
+    /// Stops sharing an alert with a user.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::DeletionError` if the deletion fails.
+    pub async fn unshare_alert(
+        &self,
+        hash: &str,
+        user_id: &str,
+        shares: &SharedAlertTable
+    ) -> Result<SupabaseSuccess, Box<dyn Error + Send + Sync>> {
+        let filters = [eq(&shares.hash_column_name, hash), eq(&shares.user_id_column_name, user_id)];
+
+        match self.rest_delete(&shares.tablename, &filters).await {
+            Ok(_) => Ok(SupabaseSuccess::DeletionSuccess),
+            Err(e) => Err(Box::new(SupabaseError::DeletionError(e)))
+        }
+    }
+
+    /// Fetches the users an alert is shared with, not including its owner.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if the query execution fails.
+    pub async fn fetch_alert_members(
+        &self,
+        hash: &str,
+        shares: &SharedAlertTable
+    ) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+        let rows: Vec<Value> = self
+            .rest_select(&shares.tablename, &[eq(&shares.hash_column_name, hash)])
+            .await
+            .map_err(SupabaseError::FetchError)?;
+
+        Ok(column_strings(&rows, &shares.user_id_column_name))
+    }
+
+    /// Fetches the alerts other users shared with a user.
+    ///
+    /// # Errors
+    /// Returns an error if either query fails or any of the alert rows is incomplete.
+    pub async fn fetch_shared_alerts(
+        &self,
+        user_id: &str,
+        shares: &SharedAlertTable,
+        config: &TableConfig
+    ) -> Result<Vec<Alert>, Box<dyn Error + Send + Sync>> {
+        let rows: Vec<Value> = self
+            .rest_select(&shares.tablename, &[eq(&shares.user_id_column_name, user_id)])
+            .await
+            .map_err(SupabaseError::FetchError)?;
+        let hashes: Vec<String> = column_strings(&rows, &shares.hash_column_name);
+        if hashes.is_empty() {
+            return Ok(Vec::new());
+        }
+        let hashes: Vec<&str> = hashes.iter().map(String::as_str).collect();
+
+        let values: Vec<Value> = self
+            .rest_select(&config.tablename, &[is_in(&config.hash_column_name, &hashes)])
+            .await
+            .map_err(SupabaseError::FetchError)?;
+
+        let mut alerts: Vec<Alert> = Vec::with_capacity(values.len());
+        for mut value in values {
+            self.open_row(config, &mut value)?;
+            alerts.push(Alert::from_row(&value, config)?);
+        }
+
+        Ok(alerts)
+    }
+
+    /// Adds a copy of every triggered alert for each user it is shared with.
+    ///
+    /// The copies carry the member's user ID, so routing and per-user grouping notify the
+    /// whole desk without knowing about shares.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if the shares cannot be fetched.
+    pub async fn expand_shared_triggers(
+        &self,
+        triggered: Vec<TriggeredAlert>,
+        shares: &SharedAlertTable
+    ) -> Result<Vec<TriggeredAlert>, Box<dyn Error + Send + Sync>> {
+        if triggered.is_empty() {
+            return Ok(triggered);
+        }
+        let hashes: Vec<&str> = triggered.iter().map(|alert| alert.hash.as_str()).collect();
+
+        let rows: Vec<Value> = self
+            .rest_select(&shares.tablename, &[is_in(&shares.hash_column_name, &hashes)])
+            .await
+            .map_err(SupabaseError::FetchError)?;
+
+        let mut expanded: Vec<TriggeredAlert> = Vec::with_capacity(triggered.len() + rows.len());
+        for alert in triggered {
+            let members: Vec<String> = rows
+                .iter()
+                .filter(|row| row.get(&shares.hash_column_name).and_then(|v| v.as_str()) == Some(alert.hash.as_str()))
+                .filter_map(|row| row.get(&shares.user_id_column_name).and_then(|v| v.as_str()))
+                .filter(|member| *member != alert.user_id)
+                .map(String::from)
+                .collect();
+
+            expanded.push(alert.clone());
+            for member in members {
+                expanded.push(TriggeredAlert { user_id: member, ..alert.clone() });
+            }
+        }
+
+        Ok(expanded)
+    }
+}
+
+impl SharedAlertTable {
+    /// Creates a new `SharedAlertTable`.
+    pub fn new(
+        tablename: String,
+        hash_column_name: String,
+        user_id_column_name: String
+    ) -> Self {
+        Self { tablename, hash_column_name, user_id_column_name }
+    }
+}
+
+impl Default for SharedAlertTable {
+    /// The `alert_shares` table with `alert_hash` and `user_id` columns.
+    fn default() -> Self {
+        Self::new("alert_shares".to_string(), "alert_hash".to_string(), "user_id".to_string())
+    }
+}
+
+/// Collects the string values of a column.
+fn column_strings(rows: &[Value], column: &str) -> Vec<String> {
+    rows.iter()
+        .filter_map(|row| row.get(column).and_then(|v| v.as_str()))
+        .map(String::from)
+        .collect()
+}
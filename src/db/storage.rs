@@ -0,0 +1,161 @@
+//! ## Supabase Storage uploads
+//!
+//! Files such as chart snapshots are uploaded to a [`StorageBucket`] through the Storage API
+//! of the project and linked by their public URL. Old files are listed and deleted in pages,
+//! see [`Supabase::delete_objects_before`].
+
+use chrono::{DateTime, Utc};
+use reqwest::RequestBuilder;
+use serde_json::{Value, json};
+
+use crate::db::rest::resend;
+use crate::db::{StorageBucket, Supabase};
+use crate::errors::SupabaseError;
+
+/// Files listed per request when looking for old files.
+const LIST_PAGE_SIZE: usize = 100;
+
+impl StorageBucket {
+    /// Creates a bucket uploading to its root and keeping files forever.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            prefix: String::new(),
+            retention: None,
+        }
+    }
+
+    /// Uploads files to a folder of the bucket, e.g. `charts`.
+    pub fn with_prefix(
+        mut self,
+        prefix: &str
+    ) -> Self {
+        self.prefix = prefix.trim_matches('/').to_string();
+        self
+    }
+
+    /// Deletes files once they are older than `retention`.
+    pub fn with_retention(
+        mut self,
+        retention: std::time::Duration
+    ) -> Self {
+        self.retention = Some(retention);
+        self
+    }
+
+    /// Returns the path of a file named `name` in the bucket, under the prefix.
+    pub fn path(&self, name: &str) -> String {
+        match self.prefix.is_empty() {
+            true => name.to_string(),
+            false => format!("{}/{}", self.prefix, name),
+        }
+    }
+}
+
+impl Supabase {
+    /// Uploads a file to `path` in the bucket, replacing any file already there.
+    ///
+    /// # Returns
+    /// The public URL of the file.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::InsertionError` if the file could not be uploaded, e.g. when the bucket does not exist.
+    pub async fn upload_object(
+        &self,
+        bucket: &StorageBucket,
+        path: &str,
+        content_type: &str,
+        bytes: Vec<u8>
+    ) -> Result<String, SupabaseError> {
+        let request = self
+            .storage_request(self.timeouts.client().post(self.storage_endpoint(&format!("object/{}/{}", bucket.name, path))))
+            .header("Content-Type", content_type)
+            .header("x-upsert", "true")
+            .body(bytes);
+
+        self.retrying(|| resend(&request))
+            .await
+            .map_err(SupabaseError::InsertionError)?;
+        Ok(self.public_url(bucket, path))
+    }
+
+    /// Returns the public URL of the file at `path` in the bucket.
+    pub fn public_url(
+        &self,
+        bucket: &StorageBucket,
+        path: &str
+    ) -> String {
+        self.storage_endpoint(&format!("object/public/{}/{}", bucket.name, path))
+    }
+
+    /// Deletes the files under the prefix of the bucket which were created before `cutoff`.
+    ///
+    /// Files are listed oldest first, so listing stops at the first file created after `cutoff`.
+    ///
+    /// # Returns
+    /// The number of deleted files.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if the files could not be listed, or
+    /// `SupabaseError::DeletionError` if they could not be deleted.
+    pub async fn delete_objects_before(
+        &self,
+        bucket: &StorageBucket,
+        cutoff: DateTime<Utc>
+    ) -> Result<usize, SupabaseError> {
+        let mut old: Vec<String> = Vec::new();
+        'pages: for offset in (0..).step_by(LIST_PAGE_SIZE) {
+            let body: Value = json!({
+                "prefix": bucket.prefix,
+                "limit": LIST_PAGE_SIZE,
+                "offset": offset,
+                "sortBy": { "column": "created_at", "order": "asc" },
+            });
+            let request = self
+                .storage_request(self.timeouts.client().post(self.storage_endpoint(&format!("object/list/{}", bucket.name))))
+                .json(&body);
+            let files: Vec<Value> = self
+                .retrying(|| resend(&request))
+                .await
+                .map_err(SupabaseError::FetchError)?;
+
+            // Folders are listed without an ID
+            for file in files.iter().filter(|file| !file["id"].is_null()) {
+                let (Some(created_at), Some(name)) = (
+                    file["created_at"].as_str().and_then(|created_at| created_at.parse::<DateTime<Utc>>().ok()),
+                    file["name"].as_str(),
+                ) else {
+                    continue;
+                };
+                if created_at >= cutoff {
+                    break 'pages;
+                }
+                old.push(bucket.path(name));
+            }
+            if files.len() < LIST_PAGE_SIZE {
+                break;
+            }
+        }
+        if old.is_empty() {
+            return Ok(0);
+        }
+
+        let request = self
+            .storage_request(self.timeouts.client().delete(self.storage_endpoint(&format!("object/{}", bucket.name))))
+            .json(&json!({ "prefixes": old }));
+        self.retrying(|| resend(&request))
+            .await
+            .map(|deleted| deleted.len())
+            .map_err(SupabaseError::DeletionError)
+    }
+
+    fn storage_endpoint(&self, path: &str) -> String {
+        format!("{}/storage/v1/{}", self.url.trim_end_matches('/'), path)
+    }
+
+    fn storage_request(&self, request: RequestBuilder) -> RequestBuilder {
+        request
+            .header("apikey", &self.key)
+            .header("Authorization", format!("Bearer {}", &self.key))
+    }
+}
@@ -0,0 +1,182 @@
+//! ## Two-phase trigger delivery
+//!
+//! A triggered alert is first claimed by marking its row `triggering` with the alert's
+//! idempotency key, the claimant and the claim time, then delivered, then archived or deleted.
+//! A delivery reaching none of its targets releases the claim, so a later cycle triggers the
+//! alert again. A process crashing in between leaves the row claimed, so once the claim is
+//! stale it is delivered again under the same key instead of being lost, and receivers drop
+//! the duplicate by its key, see [`crate::TradeAlertsBuilder::with_two_phase_triggers`].
+
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde_json::{Value, json};
+
+use crate::TriggeredAlert;
+use crate::db::rest::{eq, is_null};
+use crate::db::{Supabase, TableConfig};
+use crate::errors::SupabaseError;
+
+/// Value of the trigger state column of a claimed alert.
+pub const TRIGGERING: &str = "triggering";
+
+impl Supabase {
+    /// Claims a triggered alert for delivery, marking its row as `triggering` with its
+    /// idempotency key, `claimant` and the current time.
+    ///
+    /// Only a row which is not claimed yet is claimed, so two instances never both deliver a
+    /// trigger. The trigger time is stored to the millisecond, the precision of the key, so a
+    /// recovered alert gets the same key, see [`TriggeredAlert::idempotency_key`].
+    ///
+    /// # Returns
+    /// `true` if the row was claimed, `false` if it is gone or was claimed already.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::UpdateError` if the row could not be updated, e.g. when the table
+    /// has no trigger state or idempotency key column.
+    pub async fn claim_trigger(
+        &self,
+        alert: &TriggeredAlert,
+        claimant: &str,
+        config: &TableConfig
+    ) -> Result<bool, SupabaseError> {
+        let triggered_at = alert
+            .triggered_at
+            .duration_trunc(Duration::milliseconds(1))
+            .unwrap_or(alert.triggered_at);
+        let body: Value = json!({
+            config.trigger_state_column_name.clone(): TRIGGERING,
+            config.idempotency_key_column_name.clone(): alert.idempotency_key(),
+            config.triggered_at_column_name.clone(): triggered_at.to_rfc3339(),
+            config.trigger_price_column_name.clone(): alert.trigger_price,
+            config.claimed_by_column_name.clone(): claimant,
+            config.claimed_at_column_name.clone(): Utc::now().to_rfc3339(),
+        });
+
+        let filters = [eq(&config.hash_column_name, &alert.hash), is_null(&config.trigger_state_column_name)];
+        let claimed: Vec<Value> = self
+            .rest_update(&config.tablename, &filters, &body)
+            .await
+            .map_err(SupabaseError::UpdateError)?;
+        Ok(!claimed.is_empty())
+    }
+
+    /// Releases the claim `claimant` holds on a triggered alert, e.g. when no delivery succeeded,
+    /// so the alert is checked again instead of being recovered.
+    ///
+    /// # Returns
+    /// `true` if the claim was released, `false` if the row is gone or claimed by another instance.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::UpdateError` if the row could not be updated.
+    pub async fn release_trigger(
+        &self,
+        alert: &TriggeredAlert,
+        claimant: &str,
+        config: &TableConfig
+    ) -> Result<bool, SupabaseError> {
+        let body: Value = json!({
+            config.trigger_state_column_name.clone(): Value::Null,
+            config.claimed_by_column_name.clone(): Value::Null,
+            config.claimed_at_column_name.clone(): Value::Null,
+        });
+
+        let filters = [
+            eq(&config.hash_column_name, &alert.hash),
+            eq(&config.trigger_state_column_name, TRIGGERING),
+            eq(&config.claimed_by_column_name, claimant),
+        ];
+        let released: Vec<Value> = self
+            .rest_update(&config.tablename, &filters, &body)
+            .await
+            .map_err(SupabaseError::UpdateError)?;
+        Ok(!released.is_empty())
+    }
+
+    /// Takes over the alerts left `triggering` whose claim is stale: claimed by `claimant`
+    /// itself, e.g. before it restarted, or claimed before `stale_before`, or without a claim time.
+    /// Alerts `owns` rejects, e.g. those of another shard, are left alone.
+    ///
+    /// Claims of other instances which are still recent are left alone, they may still be
+    /// delivering them. A stale claim is only taken over if it did not change since it was
+    /// fetched, so two instances never both take over the same alert.
+    ///
+    /// # Returns
+    /// The alerts now claimed by `claimant`.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if the rows could not be fetched, or
+    /// `SupabaseError::UpdateError` if a claim could not be taken over.
+    pub async fn take_over_stale_triggers(
+        &self,
+        claimant: &str,
+        stale_before: DateTime<Utc>,
+        owns: impl Fn(&TriggeredAlert) -> bool,
+        config: &TableConfig
+    ) -> Result<Vec<TriggeredAlert>, SupabaseError> {
+        let mut alerts: Vec<TriggeredAlert> = Vec::new();
+        for (alert, claimed_by, claimed_at) in self.fetch_claims(config).await? {
+            let stale: bool = claimed_by.as_deref() == Some(claimant)
+                || claimed_at.is_none_or(|claimed_at| claimed_at < stale_before);
+            if !stale || !owns(&alert) {
+                continue;
+            }
+            let body: Value = json!({
+                config.claimed_by_column_name.clone(): claimant,
+                config.claimed_at_column_name.clone(): Utc::now().to_rfc3339(),
+            });
+            let mut filters = vec![eq(&config.hash_column_name, &alert.hash), eq(&config.trigger_state_column_name, TRIGGERING)];
+            filters.push(match &claimed_by {
+                Some(claimed_by) => eq(&config.claimed_by_column_name, claimed_by),
+                None => is_null(&config.claimed_by_column_name),
+            });
+            let taken: Vec<Value> = self
+                .rest_update(&config.tablename, &filters, &body)
+                .await
+                .map_err(SupabaseError::UpdateError)?;
+            if !taken.is_empty() {
+                alerts.push(alert);
+            }
+        }
+        Ok(alerts)
+    }
+
+    /// Fetches the alerts left `triggering` by a run which stopped before archiving or deleting them.
+    ///
+    /// Rows which cannot be read back as a `TriggeredAlert` are logged and skipped.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if the rows could not be fetched.
+    pub async fn fetch_triggering(
+        &self,
+        config: &TableConfig
+    ) -> Result<Vec<TriggeredAlert>, SupabaseError> {
+        Ok(self.fetch_claims(config).await?.into_iter().map(|(alert, _, _)| alert).collect())
+    }
+
+    /// Fetches the alerts left `triggering` with their claimant and claim time.
+    async fn fetch_claims(
+        &self,
+        config: &TableConfig
+    ) -> Result<Vec<(TriggeredAlert, Option<String>, Option<DateTime<Utc>>)>, SupabaseError> {
+        let rows: Vec<Value> = self
+            .rest_select(&config.tablename, &[eq(&config.trigger_state_column_name, TRIGGERING)])
+            .await
+            .map_err(SupabaseError::FetchError)?;
+
+        let mut claims = Vec::with_capacity(rows.len());
+        for mut row in rows {
+            match self.open_row(config, &mut row).and_then(|_| TriggeredAlert::from_row(&row, config)) {
+                Ok(alert) => {
+                    let claimed_by: Option<String> = row.get(&config.claimed_by_column_name).and_then(Value::as_str).map(String::from);
+                    let claimed_at: Option<DateTime<Utc>> = row
+                        .get(&config.claimed_at_column_name)
+                        .and_then(Value::as_str)
+                        .and_then(|claimed_at| DateTime::parse_from_rfc3339(claimed_at).ok())
+                        .map(|claimed_at| claimed_at.with_timezone(&Utc));
+                    claims.push((alert, claimed_by, claimed_at));
+                },
+                Err(e) => println!("Skipping unreadable triggering alert: {}", e),
+            }
+        }
+        Ok(claims)
+    }
+}
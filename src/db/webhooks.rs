@@ -0,0 +1,306 @@
+//! ## Per-user webhooks
+//!
+//! Users register their own webhook endpoints, stored in a table next to their alerts, so
+//! applications manage them entirely through this crate. A `NotificationRouter` with a
+//! webhook store delivers every trigger to the enabled webhooks of its owner, see
+//! [`crate::notify::NotificationRouter::with_webhook_store`].
+//!
+//! Every webhook gets a random secret its deliveries are signed with, stored encrypted when the
+//! client has a cipher, see [`Supabase::with_cipher`]. Every method is scoped to a user, so a
+//! user can't change another user's webhooks by guessing an ID.
+//!
+//! Webhook hosts must resolve to public addresses, see [`crate::notify::webhook::resolve_public_url`],
+//! unless [`Supabase::with_private_webhooks`] is set.
+
+use std::error::Error;
+use std::fmt;
+
+use aes_gcm::aead::OsRng;
+use aes_gcm::aead::rand_core::RngCore;
+use serde_json::{Value, json};
+
+use crate::db::rest::eq;
+use crate::db::{Supabase, UserDataTable, UserWebhook, WebhookTable};
+use crate::errors::SupabaseError;
+use crate::notify::webhook::resolve_public_url;
+use crate::notify::{PayloadFormat, WebhookNotifier};
+use crate::success::SupabaseSuccess;
+
+/// Prefix of generated webhook IDs.
+pub const WEBHOOK_ID_PREFIX: &str = "wh_";
+
+/// Prefix of generated webhook secrets.
+pub const WEBHOOK_SECRET_PREFIX: &str = "whsec_";
+
+impl Default for WebhookTable {
+    /// Creates a `WebhookTable` using the default `webhooks` table layout.
+    fn default() -> Self {
+        Self {
+            tablename: "webhooks".to_string(),
+            id_column_name: "webhook_id".to_string(),
+            user_id_column_name: "user_id".to_string(),
+            url_column_name: "url".to_string(),
+            format_column_name: "format".to_string(),
+            secret_column_name: "secret".to_string(),
+            enabled_column_name: "enabled".to_string(),
+        }
+    }
+}
+
+impl From<&WebhookTable> for UserDataTable {
+    fn from(table: &WebhookTable) -> Self {
+        Self::new(table.tablename.clone(), table.user_id_column_name.clone())
+    }
+}
+
+impl UserWebhook {
+    /// Parses a row of the webhooks table.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if a column is missing or the format is unknown.
+    pub fn from_row(
+        row: &Value,
+        table: &WebhookTable
+    ) -> Result<Self, SupabaseError> {
+        let column = |name: &str| {
+            row.get(name)
+                .and_then(|v| v.as_str())
+                .map(String::from)
+                .ok_or_else(|| SupabaseError::FetchError(format!("Webhook row is missing `{}`", name)))
+        };
+
+        Ok(Self {
+            id: column(&table.id_column_name)?,
+            user_id: column(&table.user_id_column_name)?,
+            url: column(&table.url_column_name)?,
+            format: column(&table.format_column_name)?.parse().map_err(SupabaseError::FetchError)?,
+            secret: column(&table.secret_column_name)?,
+            enabled: row.get(&table.enabled_column_name).and_then(|v| v.as_bool()).unwrap_or(true),
+        })
+    }
+
+    /// Returns the row stored for the webhook.
+    pub fn row(&self, table: &WebhookTable) -> Value {
+        json!({
+            table.id_column_name.clone(): self.id,
+            table.user_id_column_name.clone(): self.user_id,
+            table.url_column_name.clone(): self.url,
+            table.format_column_name.clone(): self.format.as_str(),
+            table.secret_column_name.clone(): self.secret,
+            table.enabled_column_name.clone(): self.enabled,
+        })
+    }
+
+    /// Returns a notifier delivering to the webhook, signing with its secret.
+    pub fn notifier(&self) -> WebhookNotifier {
+        WebhookNotifier::new(self.url.clone())
+            .with_format(self.format)
+            .with_secret(&self.secret)
+    }
+}
+
+/// Debug implementation for `UserWebhook` which never prints the secret.
+impl fmt::Debug for UserWebhook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserWebhook")
+            .field("id", &self.id)
+            .field("user_id", &self.user_id)
+            .field("url", &self.url)
+            .field("format", &self.format)
+            .field("secret", &"<redacted>")
+            .field("enabled", &self.enabled)
+            .finish()
+    }
+}
+
+impl Supabase {
+    /// Allows users to register webhooks on loopback and private networks, e.g. for receivers
+    /// running next to the engine.
+    pub fn with_private_webhooks(mut self) -> Self {
+        self.private_webhooks = true;
+        self
+    }
+
+    /// Registers a webhook for a user, enabled and with a new secret.
+    ///
+    /// # Parameters
+    /// - `user_id`: The owner of the webhook.
+    /// - `url`: The `http` or `https` URL deliveries are posted to.
+    /// - `format`: The body format of deliveries.
+    /// - `table`: The configuration of the webhooks table.
+    ///
+    /// # Returns
+    /// The stored webhook, including the secret to hand to the receiver.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::InsertionError` if the URL is invalid, does not resolve to a public
+    /// address or the insertion fails.
+    pub async fn register_webhook(
+        &self,
+        user_id: &str,
+        url: &str,
+        format: PayloadFormat,
+        table: &WebhookTable
+    ) -> Result<UserWebhook, Box<dyn Error + Send + Sync>> {
+        let url: reqwest::Url = reqwest::Url::parse(url.trim())
+            .map_err(|e| SupabaseError::InsertionError(format!("Invalid webhook URL: {}", e)))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(Box::new(SupabaseError::InsertionError(format!("Webhook URLs must use http or https, not {}", url.scheme()))));
+        }
+        if !self.private_webhooks {
+            resolve_public_url(url.as_str())
+                .await
+                .map_err(|e| SupabaseError::InsertionError(format!("Invalid webhook URL: {}", e)))?;
+        }
+
+        let webhook: UserWebhook = UserWebhook {
+            id: format!("{}{}", WEBHOOK_ID_PREFIX, random_hex(12)),
+            user_id: user_id.to_string(),
+            url: url.to_string(),
+            format,
+            secret: generate_webhook_secret(),
+            enabled: true,
+        };
+        let mut row: Value = webhook.row(table);
+        self.seal_secret(&mut row, table)?;
+        self.rest_insert(&table.tablename, &[row])
+            .await
+            .map_err(SupabaseError::InsertionError)?;
+        Ok(webhook)
+    }
+
+    /// Fetches every webhook of a user, including disabled ones.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if the query fails or a row is incomplete,
+    /// or `SupabaseError::EncryptionError` if a secret cannot be decrypted.
+    pub async fn list_webhooks(
+        &self,
+        user_id: &str,
+        table: &WebhookTable
+    ) -> Result<Vec<UserWebhook>, Box<dyn Error + Send + Sync>> {
+        let rows: Vec<Value> = self
+            .rest_select(&table.tablename, &[eq(&table.user_id_column_name, user_id)])
+            .await
+            .map_err(SupabaseError::FetchError)?;
+
+        let mut webhooks: Vec<UserWebhook> = Vec::with_capacity(rows.len());
+        for mut row in rows {
+            if let Some(cipher) = &self.cipher {
+                cipher.decrypt_columns(&mut row, std::slice::from_ref(&table.secret_column_name))?;
+            }
+            webhooks.push(UserWebhook::from_row(&row, table)?);
+        }
+        Ok(webhooks)
+    }
+
+    /// Stops deliveries to a webhook without removing it.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::UpdateError` if the user has no such webhook or the update fails.
+    pub async fn disable_webhook(
+        &self,
+        user_id: &str,
+        id: &str,
+        table: &WebhookTable
+    ) -> Result<SupabaseSuccess, Box<dyn Error + Send + Sync>> {
+        self.update_webhook(user_id, id, json!({ table.enabled_column_name.clone(): false }), table).await?;
+        Ok(SupabaseSuccess::UpdateSuccess)
+    }
+
+    /// Resumes deliveries to a disabled webhook.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::UpdateError` if the user has no such webhook or the update fails.
+    pub async fn enable_webhook(
+        &self,
+        user_id: &str,
+        id: &str,
+        table: &WebhookTable
+    ) -> Result<SupabaseSuccess, Box<dyn Error + Send + Sync>> {
+        self.update_webhook(user_id, id, json!({ table.enabled_column_name.clone(): true }), table).await?;
+        Ok(SupabaseSuccess::UpdateSuccess)
+    }
+
+    /// Replaces the secret of a webhook, deliveries are signed with the new secret right away.
+    ///
+    /// # Returns
+    /// The new secret.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::UpdateError` if the user has no such webhook or the update fails.
+    pub async fn rotate_webhook_secret(
+        &self,
+        user_id: &str,
+        id: &str,
+        table: &WebhookTable
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let secret: String = generate_webhook_secret();
+        let mut body: Value = json!({ table.secret_column_name.clone(): secret });
+        self.seal_secret(&mut body, table)?;
+        self.update_webhook(user_id, id, body, table).await?;
+        Ok(secret)
+    }
+
+    /// Removes a webhook.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::DeletionError` if the user has no such webhook or the deletion fails.
+    pub async fn delete_webhook(
+        &self,
+        user_id: &str,
+        id: &str,
+        table: &WebhookTable
+    ) -> Result<SupabaseSuccess, Box<dyn Error + Send + Sync>> {
+        let filters = [eq(&table.user_id_column_name, user_id), eq(&table.id_column_name, id)];
+        let deleted: Vec<Value> = self
+            .rest_delete(&table.tablename, &filters)
+            .await
+            .map_err(SupabaseError::DeletionError)?;
+        if deleted.is_empty() {
+            return Err(Box::new(SupabaseError::DeletionError(format!("No webhook {} of {}", id, user_id))));
+        }
+        Ok(SupabaseSuccess::DeletionSuccess)
+    }
+
+    /// Encrypts the secret of a webhook row, if the client has a cipher.
+    fn seal_secret(
+        &self,
+        row: &mut Value,
+        table: &WebhookTable
+    ) -> Result<(), SupabaseError> {
+        match &self.cipher {
+            Some(cipher) => cipher.encrypt_columns(row, std::slice::from_ref(&table.secret_column_name)),
+            None => Ok(()),
+        }
+    }
+
+    async fn update_webhook(
+        &self,
+        user_id: &str,
+        id: &str,
+        body: Value,
+        table: &WebhookTable
+    ) -> Result<(), SupabaseError> {
+        let filters = [eq(&table.user_id_column_name, user_id), eq(&table.id_column_name, id)];
+        let updated: Vec<Value> = self
+            .rest_update(&table.tablename, &filters, &body)
+            .await
+            .map_err(SupabaseError::UpdateError)?;
+        if updated.is_empty() {
+            return Err(SupabaseError::UpdateError(format!("No webhook {} of {}", id, user_id)));
+        }
+        Ok(())
+    }
+}
+
+/// Returns a new random webhook secret.
+pub fn generate_webhook_secret() -> String {
+    format!("{}{}", WEBHOOK_SECRET_PREFIX, random_hex(32))
+}
+
+fn random_hex(bytes: usize) -> String {
+    let mut buffer: Vec<u8> = vec![0; bytes];
+    OsRng.fill_bytes(&mut buffer);
+    buffer.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,1421 @@
+//! ## One-stop setup of a running alert system
+//!
+//! [`TradeAlerts`] wires a price provider, the alerts table and the notification channels
+//! together and checks the alerts on a fixed interval. Every cycle:
+//! 1. Checks the alerts against fresh quotes, see [`XylexApi::check_alerts_with`].
+//! 2. Routes every triggered alert to its channels, see [`NotificationRouter::route`].
+//! 3. Archives the triggered alerts when a history table is set, or deletes them otherwise.
+//!
+//! With two-phase triggers, every triggered alert is claimed in the alerts table before step 2,
+//! so a crash before step 3 delivers it again on startup, see [`TradeAlertsBuilder::with_two_phase_triggers`].
+//! With chart snapshots, a chart of every triggered alert is uploaded to Supabase Storage and
+//! linked from its notifications and history row, see [`TradeAlertsBuilder::with_chart_snapshots`].
+//! In a dry run, every cycle only reports what would have fired, see [`TradeAlertsBuilder::with_dry_run`].
+//! With a leader lease, only one of several replicas runs cycles at a time, see [`TradeAlertsBuilder::with_leader_lease`].
+//! With sharding, every replica checks the alerts of its own share of the symbols instead, see [`TradeAlertsBuilder::with_sharding`].
+//!
+//! The outcome of every cycle is kept in an [`EngineStatus`], see [`TradeAlerts::status`].
+//! With an idle interval, cycles slow down while the alerts table holds no active alert, see
+//! [`TradeAlertsBuilder::with_idle_interval`].
+//! Every delivered alert is also sent to the in-process subscribers of [`TradeAlerts::subscribe`].
+//! With an intent log, notifications interrupted by a crash are replayed when the system starts.
+//! With a price snapshot file, band crossings which happened across a restart are detected on
+//! the first cycle, see [`TradeAlertsBuilder::with_price_snapshot_file`].
+//!
+//! ```rust,no_run
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! use std::time::Duration;
+//! use trade_alerts::TradeAlerts;
+//! use trade_alerts::notify::WebhookNotifier;
+//!
+//! let alerts = TradeAlerts::builder()
+//!     .from_env()
+//!     .await?
+//!     .with_interval(Duration::from_secs(30))
+//!     .with_notifier(WebhookNotifier::new("https://example.com/hook".to_string()))
+//!     .build()?;
+//!
+//! let handle = alerts.start().await;
+//! tokio::signal::ctrl_c().await?;
+//! handle.stop().await;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use futures_util::{FutureExt, Stream};
+use serde_json::{Value, json};
+use tokio::sync::broadcast;
+
+use crate::{Alert, AlertStatus, EngineStatus, Health, ResourceLimits, Schedule, ScheduleConfig, ShutdownSummary, TradeAlerts, TradeAlertsBuilder, TradeAlertsHandle, TriggeredAlert};
+use crate::alert::is_expired;
+use crate::data::{CheckReport, PriceProvider, PriceSnapshot, XylexApi};
+use crate::db::{HeartbeatTable, Lease, LeaseTable, ShardTable, SharedAlertTable, StorageBucket, Supabase, TableConfig};
+use crate::db::rest::eq;
+use crate::db::triggering::TRIGGERING;
+use crate::errors::{ConfigError, SupabaseError, XylexApiError};
+use crate::market_hours::in_sessions;
+use crate::health::DEFAULT_STALL_THRESHOLD;
+use crate::notify::{ChartRenderer, Delivery, IntentLog, NotificationRouter, Notifier, PreflightReport};
+use crate::shard::{DEFAULT_VIRTUAL_NODES, HashRing, Shard};
+use crate::utils::cron::CronExpression;
+use crate::utils::lru::CacheStats;
+use crate::utils::privacy::privacy_policy;
+
+/// Age after which a price snapshot is too stale to seed the price state from.
+pub const DEFAULT_SNAPSHOT_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Time between two cycles when no interval is set.
+pub const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Age after which a sharded worker fetches the symbols of the alerts table again, see
+/// [`TradeAlertsBuilder::with_sharding`].
+pub const SHARD_SYMBOLS_REFRESH: Duration = Duration::from_secs(60);
+
+/// Every symbol of the alerts table and when it was fetched, see [`SHARD_SYMBOLS_REFRESH`].
+pub(crate) type KnownSymbols = Option<(Instant, HashSet<String>)>;
+
+/// Time a chart can take to be drawn and uploaded before its alert is delivered without it,
+/// see [`TradeAlertsBuilder::with_chart_snapshots`].
+pub const CHART_UPLOAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Minimum time between two deletions of the charts older than the retention of the bucket.
+pub const CHART_CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Triggered alerts buffered for a subscriber, see [`TradeAlerts::subscribe`].
+pub const TRIGGER_CHANNEL_CAPACITY: usize = 1024;
+
+impl TradeAlerts {
+    /// Starts building a `TradeAlerts` system.
+    pub fn builder() -> TradeAlertsBuilder {
+        TradeAlertsBuilder::default()
+    }
+
+    /// The time between two cycles, unless a cron expression is set.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Returns the occupancy of the caches kept across cycles, see [`XylexApi::cache_stats`].
+    pub fn cache_stats(&self) -> BTreeMap<String, CacheStats> {
+        self.xylex.cache_stats()
+    }
+
+    /// The cron expression matching the minutes cycles run at, if set.
+    pub fn cron(&self) -> Option<&CronExpression> {
+        self.cron.as_ref()
+    }
+
+    /// Returns a snapshot of the last cycles.
+    pub fn status(&self) -> EngineStatus {
+        lock(&self.status).clone()
+    }
+
+    /// Returns the health of the system, see [`EngineStatus::health`].
+    pub fn health(&self) -> Health {
+        self.status().health(Utc::now(), self.stall_after)
+    }
+
+    /// Returns what the alerts table and this system know about an alert, for support tooling
+    /// answering "why hasn't this fired?".
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::FetchError` if the alert could not be fetched, does not exist or its row is incomplete.
+    pub async fn get_alert_status(&self, hash: &str) -> Result<AlertStatus, SupabaseError> {
+        let mut row: Value = self
+            .supabase
+            .rest_select(&self.config.tablename, &[eq(&self.config.hash_column_name, hash)])
+            .await
+            .map_err(SupabaseError::FetchError)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| SupabaseError::FetchError(format!("No alert found with hash {}", hash)))?;
+        self.supabase.open_row(&self.config, &mut row)?;
+        let alert: Alert = Alert::from_row(&row, &self.config)?;
+
+        let now: DateTime<Utc> = Utc::now();
+        let expired: bool = alert.expires_at.is_some_and(|expires_at| expires_at <= now);
+        let in_session: bool = in_sessions(&alert.sessions, now);
+        let triggering: bool = row.get(&self.config.trigger_state_column_name).and_then(Value::as_str) == Some(TRIGGERING);
+        let market_open: bool = self.xylex.market_hours.as_ref().is_none_or(|hours| hours.is_open(&alert.symbol, now));
+        let scheduled: bool = self.xylex.schedule.as_ref().is_none_or(|schedule| schedule.is_due(&alert.symbol, Instant::now()));
+        let last_price: Option<f64> = self.xylex.previous_prices.get(&alert.symbol);
+        let distance: Option<f64> = last_price.map(|price| alert.price_level - price);
+        let distance_percent: Option<f64> = match (distance, last_price) {
+            (Some(distance), Some(price)) if price != 0.0 => Some(distance / price * 100.0),
+            _ => None,
+        };
+
+        Ok(AlertStatus {
+            active: !expired && in_session && market_open && scheduled && !triggering,
+            expired,
+            in_session,
+            market_open,
+            scheduled,
+            triggering,
+            awaiting_confirmation: self.xylex.awaiting_confirmation(hash),
+            last_evaluated_at: self.xylex.last_evaluated(hash),
+            last_price,
+            distance,
+            distance_percent,
+            pending_notifications: self.router.pending_intents().into_iter().filter(|intent| intent.alert.hash == hash).collect(),
+            alert,
+        })
+    }
+
+    /// Runs a single cycle: checks the alerts, notifies and archives or deletes the triggered ones.
+    ///
+    /// Failed deliveries and archivals are logged and do not fail the cycle. The outcome is
+    /// recorded in the status, and written to the status file if one is set.
+    ///
+    /// # Returns
+    /// A `Result` which is either:
+    /// - `Ok(CheckReport)` - Everything the cycle observed.
+    /// - `Err(XylexApiError)` - The alerts could not be checked.
+    pub async fn run_once(&self) -> Result<CheckReport, XylexApiError> {
+        self.run_holding(&AtomicBool::new(false)).await
+    }
+
+    /// Runs a single cycle like [`TradeAlerts::run_once`], abandoning it before anything is
+    /// notified once `lease_lost` is set.
+    async fn run_holding(&self, lease_lost: &AtomicBool) -> Result<CheckReport, XylexApiError> {
+        let started_at: DateTime<Utc> = Utc::now();
+        let started: Instant = Instant::now();
+
+        let result: Result<CheckReport, XylexApiError> = self.cycle(lease_lost).await;
+
+        let status: EngineStatus = {
+            let mut status: MutexGuard<'_, EngineStatus> = lock(&self.status);
+            status.last_run_at = Some(started_at);
+            status.last_duration = Some(started.elapsed());
+            status.cycles += 1;
+            match &result {
+                Ok(report) => {
+                    status.consecutive_errors = 0;
+                    status.last_error = None;
+                    status.last_symbols_checked = report.quotes.len();
+                    (status.last_triggered, status.last_dry_run_triggered) = match self.xylex.dry_run {
+                        true => (0, report.triggered.len()),
+                        false => (report.triggered.len(), 0),
+                    };
+                    status.idle = self.idle_interval.is_some() && !self.has_active_alerts(report);
+                },
+                Err(e) => {
+                    status.consecutive_errors += 1;
+                    status.last_error = Some(e.to_string());
+                    status.last_symbols_checked = 0;
+                    status.last_triggered = 0;
+                    status.last_dry_run_triggered = 0;
+                },
+            }
+            status.clone()
+        };
+        self.persist_status(&status);
+        self.save_price_snapshot();
+
+        result
+    }
+
+    /// Writes what a stopping system still holds: the status file without a next run, the price
+    /// snapshot, the intent log compacted to its pending intents and the heartbeat.
+    async fn flush(&self) {
+        self.schedule_next(None);
+        self.save_price_snapshot();
+        self.router.compact_intents();
+        self.write_heartbeat().await;
+    }
+
+    /// Writes the price state to the snapshot file, if set, logging failures.
+    fn save_price_snapshot(&self) {
+        let Some(path) = &self.price_snapshot_path else {
+            return;
+        };
+        if let Err(e) = self.xylex.price_snapshot().save(path) {
+            println!("Error writing the price snapshot to {}: {}", path.display(), e);
+        }
+    }
+
+    async fn cycle(&self, lease_lost: &AtomicBool) -> Result<CheckReport, XylexApiError> {
+        let mut report: CheckReport = self
+            .xylex
+            .check_alerts_with(self.provider.as_ref(), &self.supabase, &self.config)
+            .await?;
+        if self.xylex.dry_run {
+            for alert in &report.triggered {
+                println!("Dry run, {} would have fired: {} at {}", alert.hash, alert.symbol, alert.trigger_price);
+            }
+            return Ok(report);
+        }
+        // Past this point the cycle runs to the end, so alerts are never notified without being
+        // archived or deleted
+        if lease_lost.load(Ordering::SeqCst) {
+            return Err(XylexApiError::UnexpectedError("Lost the lease during the cycle, abandoning it".to_string()));
+        }
+
+        if self.two_phase_triggers {
+            let mut claimed: Vec<TriggeredAlert> = Vec::with_capacity(report.triggered.len());
+            for alert in report.triggered {
+                match self.supabase.claim_trigger(&alert, &self.instance_id, &self.config).await {
+                    Ok(true) => claimed.push(alert),
+                    Ok(false) => println!("Not delivering {}, it was claimed by another run", alert.hash),
+                    Err(e) => println!("Error claiming {}, retrying on the next cycle: {}", alert.hash, e),
+                }
+            }
+            report.triggered = claimed;
+        }
+
+        report.triggered = self.attach_charts(report.triggered).await;
+        self.deliver(&report.triggered).await;
+        if !report.triggered.is_empty() {
+            self.clean_up_charts().await;
+        }
+        Ok(report)
+    }
+
+    /// Draws, uploads and links the chart of every triggered alert, if chart snapshots are set.
+    ///
+    /// Charts are drawn and uploaded concurrently, each within [`CHART_UPLOAD_TIMEOUT`]. With a
+    /// history table, the URL is also stored on the alert's row so it is archived with it.
+    /// Alerts whose chart could not be drawn or uploaded in time are delivered without one.
+    async fn attach_charts(&self, triggered: Vec<TriggeredAlert>) -> Vec<TriggeredAlert> {
+        let Some((renderer, bucket)) = &self.charts else {
+            return triggered;
+        };
+        let urls: Vec<Option<String>> = join_all(triggered.iter().map(|alert| async move {
+            let upload = async {
+                let png: Vec<u8> = renderer.render(alert).await?;
+                match self.supabase.upload_object(bucket, &bucket.path(&alert.chart_file_name()), "image/png", png).await {
+                    Ok(url) => Some(url),
+                    Err(e) => {
+                        println!("Error uploading the chart of {}: {}", alert.hash, e);
+                        None
+                    },
+                }
+            };
+            tokio::time::timeout(CHART_UPLOAD_TIMEOUT, upload).await.unwrap_or_else(|_| {
+                println!("Timed out uploading the chart of {}", alert.hash);
+                None
+            })
+        }))
+        .await;
+
+        let mut attached: Vec<TriggeredAlert> = Vec::with_capacity(triggered.len());
+        for (alert, url) in triggered.into_iter().zip(urls) {
+            let Some(url) = url else {
+                attached.push(alert);
+                continue;
+            };
+            if self.history_config.is_some() {
+                let body: Value = json!({ self.config.chart_url_column_name.clone(): url });
+                let filters = [eq(&self.config.hash_column_name, &alert.hash)];
+                if let Err(e) = self.supabase.rest_update(&self.config.tablename, &filters, &body).await {
+                    println!("Error storing the chart URL of {}: {}", alert.hash, e);
+                }
+            }
+            attached.push(alert.with_chart_url(&url));
+        }
+        attached
+    }
+
+    /// Deletes the charts older than the retention of the bucket, at most every [`CHART_CLEANUP_INTERVAL`].
+    async fn clean_up_charts(&self) {
+        let Some((_, bucket)) = &self.charts else {
+            return;
+        };
+        let Some(cutoff) = bucket
+            .retention
+            .and_then(|retention| chrono::Duration::from_std(retention).ok())
+            .and_then(|retention| Utc::now().checked_sub_signed(retention))
+        else {
+            return;
+        };
+        {
+            let mut last_cleanup = self.last_chart_cleanup.lock().unwrap_or_else(|e| e.into_inner());
+            if last_cleanup.is_some_and(|at| at.elapsed() < CHART_CLEANUP_INTERVAL) {
+                return;
+            }
+            *last_cleanup = Some(Instant::now());
+        }
+        match self.supabase.delete_objects_before(bucket, cutoff).await {
+            Ok(0) => {},
+            Ok(deleted) => println!("Deleted {} charts older than {}", deleted, cutoff),
+            Err(e) => println!("Error deleting old charts: {}", e),
+        }
+    }
+
+    /// Adds a copy of every triggered alert for each user it is shared with, if shared alerts are set.
+    ///
+    /// When the shares cannot be fetched, only the owners are notified.
+    async fn share_triggers(&self, triggered: &[TriggeredAlert]) -> Vec<TriggeredAlert> {
+        let Some(shares) = &self.shares else {
+            return triggered.to_vec();
+        };
+        match self.supabase.expand_shared_triggers(triggered.to_vec(), shares).await {
+            Ok(recipients) => recipients,
+            Err(e) => {
+                println!("Error fetching the members of the triggered alerts, notifying their owners only: {}", e);
+                triggered.to_vec()
+            },
+        }
+    }
+
+    /// Routes the triggered alerts to their channels, then archives or deletes those which
+    /// reached at least one of their targets.
+    ///
+    /// An alert whose every delivery failed is kept, so a later cycle delivers it again, and
+    /// with two-phase triggers its claim is released. Alerts without any target are archived
+    /// or deleted right away. With shared alerts, the members are notified as well.
+    async fn deliver(&self, triggered: &[TriggeredAlert]) {
+        let recipients: Vec<TriggeredAlert> = self.share_triggers(triggered).await;
+        let mut delivered: Vec<&TriggeredAlert> = Vec::with_capacity(triggered.len());
+        let mut mark_delivered = |hash: &str| {
+            if !delivered.iter().any(|alert| alert.hash == hash) {
+                delivered.extend(triggered.iter().find(|alert| alert.hash == hash));
+            }
+        };
+        if self.router.is_grouped() {
+            for (group, deliveries) in self.router.route_grouped(&recipients).await {
+                for delivery in &deliveries {
+                    if let Some(e) = &delivery.error {
+                        println!(
+                            "Error notifying {:?} of the alerts of {}: {}",
+                            delivery.target, privacy_policy().redact_user_id(&group.user_id), e
+                        );
+                    }
+                }
+                for alert in &group.alerts {
+                    let reached: Vec<&Delivery> = deliveries
+                        .iter()
+                        .filter(|delivery| self.router.reaches(alert, &delivery.target))
+                        .collect();
+                    if is_delivered(&reached) {
+                        mark_delivered(&alert.hash);
+                    }
+                }
+            }
+        } else {
+            for (alert, deliveries) in recipients.iter().zip(self.router.route_all(&recipients).await) {
+                for delivery in &deliveries {
+                    if let Some(e) = &delivery.error {
+                        println!("Error notifying {:?} of {}: {}", delivery.target, alert.hash, e);
+                    }
+                }
+                if is_delivered(&deliveries.iter().collect::<Vec<_>>()) {
+                    mark_delivered(&alert.hash);
+                }
+            }
+        }
+        if delivered.len() < triggered.len() {
+            println!("Keeping {} alerts for the next cycle, none of their deliveries succeeded", triggered.len() - delivered.len());
+        }
+        if self.two_phase_triggers {
+            for alert in triggered.iter().filter(|alert| !delivered.iter().any(|delivered| delivered.hash == alert.hash)) {
+                if let Err(e) = self.supabase.release_trigger(alert, &self.instance_id, &self.config).await {
+                    println!("Error releasing the claim on {}: {}", alert.hash, e);
+                }
+            }
+        }
+
+        let hashes: Vec<String> = delivered.iter().map(|alert| alert.hash.clone()).collect();
+        match &self.history_config {
+            Some(history_config) => {
+                for hash in &hashes {
+                    if let Err(e) = self.supabase.archive_alert(hash, &self.config, history_config).await {
+                        println!("Error archiving {}: {}", hash, e);
+                    }
+                }
+            },
+            None if !hashes.is_empty() => {
+                if let Err(e) = self.xylex.delete_triggered_alerts_by_hashes(&self.supabase, &self.config, hashes).await {
+                    println!("Error deleting triggered alerts: {}", e);
+                }
+            },
+            None => {},
+        }
+
+        // Sending only fails without subscribers
+        if self.events.receiver_count() > 0 {
+            for alert in delivered {
+                let _ = self.events.send(alert.clone());
+            }
+        }
+    }
+
+    /// Returns a stream of the alerts triggered from now on, each sent once it was notified
+    /// and archived or deleted, including alerts recovered on startup.
+    ///
+    /// A subscriber more than [`TRIGGER_CHANNEL_CAPACITY`] alerts behind skips the oldest
+    /// ones. The stream ends once the system and every handle to it are dropped.
+    pub fn subscribe(&self) -> impl Stream<Item = TriggeredAlert> + Send + 'static {
+        trigger_stream(self.events.subscribe())
+    }
+
+    /// Delivers the alerts a previous run claimed but did not archive or delete, see
+    /// [`TradeAlertsBuilder::with_two_phase_triggers`].
+    ///
+    /// Only stale claims are recovered: those of this instance, see [`TradeAlerts::instance_id`],
+    /// and those older than the lease TTL, so alerts another live instance is still delivering
+    /// are not fired twice. They are delivered under the idempotency key of their first delivery
+    /// attempt, so receivers can tell a redelivery apart from a new trigger.
+    ///
+    /// # Returns
+    /// The recovered alerts, empty if none were left or they could not be fetched.
+    pub async fn recover_triggers(&self) -> Vec<TriggeredAlert> {
+        if self.xylex.dry_run {
+            let mut left: Vec<TriggeredAlert> = self.supabase.fetch_triggering(&self.config).await.unwrap_or_default();
+            left.retain(|alert| self.xylex.owns_symbol(&alert.symbol));
+            println!("Dry run, not delivering {} alerts left triggering by a previous run", left.len());
+            return left;
+        }
+        let stale_before: DateTime<Utc> = chrono::Duration::from_std(self.lease_ttl)
+            .ok()
+            .and_then(|ttl| Utc::now().checked_sub_signed(ttl))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        // A sharded worker only delivers the alerts of its own symbols
+        let owns = |alert: &TriggeredAlert| self.xylex.owns_symbol(&alert.symbol);
+        let fetched = self.supabase.take_over_stale_triggers(&self.instance_id, stale_before, owns, &self.config).await;
+        let recovered: Vec<TriggeredAlert> = match fetched {
+            Ok(recovered) => recovered,
+            Err(e) => {
+                println!("Error fetching the alerts left triggering: {}", e);
+                return Vec::new();
+            },
+        };
+        let recovered: Vec<TriggeredAlert> = self.attach_charts(recovered).await;
+        if !recovered.is_empty() {
+            println!("Delivering {} alerts left triggering by a previous run", recovered.len());
+            self.deliver(&recovered).await;
+        }
+        recovered
+    }
+
+    /// Verifies every notification channel, see [`NotificationRouter::preflight`].
+    ///
+    /// Call it before [`TradeAlerts::start`] to refuse to start with a misconfigured channel.
+    pub async fn preflight(&self) -> PreflightReport {
+        self.router.preflight().await
+    }
+
+    /// Returns the ID triggered alerts are claimed under, the instance ID of the leader lease or
+    /// sharded worker if set, or one generated on build otherwise.
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Runs a cycle every interval in the background until the returned handle is stopped.
+    ///
+    /// See [`TradeAlerts::run_until`] for what happens before the first cycle.
+    pub async fn start(self) -> TradeAlertsHandle {
+        let (stop, mut stopped) = tokio::sync::watch::channel(false);
+        let status: Arc<Mutex<EngineStatus>> = self.status.clone();
+        self.schedule_next(self.first_delay());
+
+        let stall_after: Duration = self.stall_after;
+        let wake: Arc<tokio::sync::Notify> = self.wake.clone();
+        let task: tokio::task::JoinHandle<()> = tokio::spawn(async move {
+            self.run_until(async move {
+                let _ = stopped.changed().await;
+            })
+            .await;
+        });
+
+        TradeAlertsHandle { status, stall_after, wake, stop, task }
+    }
+
+    /// Runs a cycle every interval, or at the minutes matching the cron expression, until `shutdown` completes.
+    ///
+    /// Channels failing their preflight check are logged, then intents left incomplete by a
+    /// previous run are replayed, unless in a dry run. With two-phase triggers, alerts left triggering are delivered
+    /// before the first cycle, and again whenever this instance takes over the leader lease or
+    /// the symbols of another worker.
+    ///
+    /// With a leader lease, ticks on which another instance holds the lease run no cycle, and
+    /// the lease is released once the system stops. With sharding, the symbols are rebalanced
+    /// between the live workers on every tick, and this worker leaves once the system stops.
+    /// The first cycle runs immediately, or at the first matching minute with a cron expression.
+    /// A failed cycle is logged and retried on the next tick.
+    ///
+    /// No cycle starts once `shutdown` completed, and a cycle in progress finishes first,
+    /// including its notifications and the archival or deletion of its triggered alerts. Then the
+    /// status, price snapshot, intent log and heartbeat are flushed before the lease is released.
+    ///
+    /// # Returns
+    /// A `ShutdownSummary` of the cycle that was in progress, if any.
+    pub async fn run_until(self, shutdown: impl Future<Output = ()>) -> ShutdownSummary {
+        for check in self.preflight().await.failures() {
+            let error: &str = check.error.as_deref().unwrap_or_default();
+            println!("Notification channel {} failed its preflight check: {}", check.channel, error);
+        }
+        match self.xylex.dry_run {
+            true => println!("Dry run, not replaying notifications left incomplete by a previous run"),
+            false => {
+                self.router.replay_intents().await;
+            },
+        }
+
+        tokio::pin!(shutdown);
+        let mut summary: ShutdownSummary = ShutdownSummary::default();
+        let mut ticker: tokio::time::Interval = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut leading: bool = false;
+        loop {
+            tokio::select! {
+                // A shutdown wins over a tick which is due at the same time
+                biased;
+                _ = &mut shutdown => break,
+                _ = self.next_tick(&mut ticker) => {
+                    let gained: Option<bool> = match self.hold_lease().await {
+                        true => self.rebalance().await,
+                        false => None,
+                    };
+                    let Some(gained) = gained else {
+                        leading = false;
+                        self.schedule_next(self.next_delay());
+                        self.write_heartbeat().await;
+                        continue;
+                    };
+                    // Alerts a crashed leader or worker left triggering are ours now
+                    if (!leading || gained) && self.two_phase_triggers {
+                        self.recover_triggers().await;
+                    }
+                    leading = true;
+
+                    // A wake before this cycle started is served by it, only later ones are kept
+                    let _ = self.wake.notified().now_or_never();
+                    let result: Result<CheckReport, XylexApiError> = self.run_leased().await;
+                    if let Err(e) = &result {
+                        println!("Error checking alerts: {}", e);
+                    }
+                    // A shutdown requested while the cycle ran waited for it
+                    if shutdown.as_mut().now_or_never().is_some() {
+                        summary.cycle_in_flight = true;
+                        summary.drained = result
+                            .map(|report| report.triggered.into_iter().map(|alert| alert.hash).collect())
+                            .unwrap_or_default();
+                        break;
+                    }
+                    self.schedule_next(self.next_delay());
+                    self.write_heartbeat().await;
+                },
+            }
+        }
+        self.flush().await;
+        if leading {
+            self.release_lease().await;
+            self.leave_shard().await;
+        }
+
+        summary.status = self.status();
+        summary
+    }
+
+    /// Runs the next cycle now if the system is idle, e.g. once an alert was added, see
+    /// [`TradeAlertsBuilder::with_idle_interval`].
+    ///
+    /// A wake while a cycle runs is kept, so the alert is picked up even if that cycle missed it.
+    /// Earlier wakes are dropped once the next cycle starts.
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Whether the alerts checked by a cycle include one which is not expired.
+    fn has_active_alerts(&self, report: &CheckReport) -> bool {
+        report.alerts.iter().any(|row| !is_expired(row.get(&self.config.expiry_column_name)))
+    }
+
+    /// Waits for the next tick of the interval, or the next minute matching the cron expression.
+    ///
+    /// While idle, waits for the idle interval or a wake instead, then restarts the interval.
+    async fn next_tick(&self, ticker: &mut tokio::time::Interval) {
+        let Some(cron) = &self.cron else {
+            match self.idle_interval.filter(|_| lock(&self.status).idle) {
+                Some(idle_interval) => {
+                    tokio::select! {
+                        _ = tokio::time::sleep(idle_interval) => {},
+                        _ = self.wake.notified() => println!("Woken up, resuming full speed"),
+                    }
+                    ticker.reset();
+                },
+                None => {
+                    ticker.tick().await;
+                },
+            }
+            return;
+        };
+        match cron.next_after(Utc::now()) {
+            Some(next) => tokio::time::sleep((next - Utc::now()).to_std().unwrap_or_default()).await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Returns the time until the first cycle of a run.
+    pub(crate) fn first_delay(&self) -> Option<chrono::Duration> {
+        match &self.cron {
+            Some(_) => self.next_delay(),
+            None => Some(chrono::Duration::zero()),
+        }
+    }
+
+    /// Returns the time until the cycle after the one which just ran, `None` if no minute matches the cron expression.
+    fn next_delay(&self) -> Option<chrono::Duration> {
+        match (&self.cron, self.idle_interval) {
+            (Some(cron), _) => cron.next_after(Utc::now()).map(|next| next - Utc::now()),
+            (None, Some(idle_interval)) if lock(&self.status).idle => chrono::Duration::from_std(idle_interval).ok(),
+            (None, _) => chrono::Duration::from_std(self.interval).ok(),
+        }
+    }
+
+    pub(crate) fn schedule_next(&self, delay: Option<chrono::Duration>) {
+        let status: EngineStatus = {
+            let mut status: MutexGuard<'_, EngineStatus> = lock(&self.status);
+            status.next_run_at = delay.map(|delay| Utc::now() + delay);
+            status.clone()
+        };
+        self.persist_status(&status);
+    }
+
+    /// Acquires or renews the leader lease, always `true` without one.
+    ///
+    /// An instance which can't reach the lease table does not run cycles, so two instances never both do.
+    async fn hold_lease(&self) -> bool {
+        let Some((table, instance_id)) = &self.lease else {
+            return true;
+        };
+        let leader: bool = match self.supabase.acquire_lease(&self.config.tablename, instance_id, self.lease_ttl, table).await {
+            Ok(leader) => leader,
+            Err(e) => {
+                println!("Error acquiring the lease of {}: {}", instance_id, e);
+                false
+            },
+        };
+        lock(&self.status).is_leader = Some(leader);
+        leader
+    }
+
+    /// Runs a cycle while renewing the leader lease, or the lease of this worker, every third of
+    /// the lease TTL, so a cycle longer than the TTL keeps its lease.
+    ///
+    /// A cycle whose lease could not be renewed is abandoned before it notifies, as another
+    /// instance may take over. A cycle which is already notifying finishes, so its alerts are
+    /// archived or deleted and not fired again by the next leader.
+    async fn run_leased(&self) -> Result<CheckReport, XylexApiError> {
+        if self.lease.is_none() && self.sharding.is_none() {
+            return self.run_once().await;
+        }
+        let lease_lost: AtomicBool = AtomicBool::new(false);
+        let renewing = async {
+            loop {
+                tokio::time::sleep(self.lease_ttl / 3).await;
+                if !self.renew_lease().await {
+                    lease_lost.store(true, Ordering::SeqCst);
+                    return;
+                }
+            }
+        };
+        let cycle = self.run_holding(&lease_lost);
+        tokio::pin!(cycle);
+        tokio::select! {
+            biased;
+            result = &mut cycle => return result,
+            _ = renewing => {},
+        }
+        cycle.await
+    }
+
+    /// Renews the leader lease or the lease of this worker without rebalancing, always `true` without one.
+    async fn renew_lease(&self) -> bool {
+        if self.lease.is_some() {
+            return self.hold_lease().await;
+        }
+        let Some((leases, _, instance_id)) = &self.sharding else {
+            return true;
+        };
+        let name: String = format!("{}{}", self.worker_prefix(), instance_id);
+        match self.supabase.acquire_lease(&name, instance_id, self.lease_ttl, leases).await {
+            Ok(held) => held,
+            Err(e) => {
+                println!("Error renewing the lease of worker {}: {}", instance_id, e);
+                false
+            },
+        }
+    }
+
+    /// Releases the leader lease, if set, logging failures.
+    async fn release_lease(&self) {
+        let Some((table, instance_id)) = &self.lease else {
+            return;
+        };
+        if let Err(e) = self.supabase.release_lease(&self.config.tablename, instance_id, table).await {
+            println!("Error releasing the lease of {}: {}", instance_id, e);
+        }
+        lock(&self.status).is_leader = Some(false);
+    }
+
+    /// Renews this worker's lease and rebalances the symbols between the live workers.
+    ///
+    /// Symbols this worker did not check before are recorded in the assignment table. A worker
+    /// which can't reach the lease table, or never fetched the symbols, runs no cycle, as it can't
+    /// tell which symbols are its own.
+    ///
+    /// # Returns
+    /// Whether this worker gained symbols, always `Some(false)` when not sharded, `None` if no cycle should run.
+    async fn rebalance(&self) -> Option<bool> {
+        let Some((leases, assignments, instance_id)) = &self.sharding else {
+            return Some(false);
+        };
+        let prefix: String = self.worker_prefix();
+        let name: String = format!("{}{}", prefix, instance_id);
+        let workers: Vec<Lease> = match self.supabase.acquire_lease(&name, instance_id, self.lease_ttl, leases).await {
+            Ok(_) => match self.supabase.fetch_leases(&prefix, leases).await {
+                Ok(workers) => workers,
+                Err(e) => {
+                    println!("Error fetching the workers sharing {}: {}", self.config.tablename, e);
+                    return None;
+                },
+            },
+            Err(e) => {
+                println!("Error renewing the lease of worker {}: {}", instance_id, e);
+                return None;
+            },
+        };
+        let now: DateTime<Utc> = Utc::now();
+        let ring: HashRing = HashRing::new(
+            workers.iter().filter(|lease| !lease.is_expired(now)).map(|lease| lease.holder.as_str()),
+            DEFAULT_VIRTUAL_NODES,
+        );
+        let shard: Shard = Shard::new(ring, instance_id);
+
+        let stale: bool = self
+            .known_symbols()
+            .as_ref()
+            .is_none_or(|(fetched_at, _)| fetched_at.elapsed() >= SHARD_SYMBOLS_REFRESH);
+        if stale {
+            match self.supabase.fetch_unique_symbols(&self.config).await {
+                Ok((symbols, _)) => *self.known_symbols() = Some((Instant::now(), symbols)),
+                // The symbols fetched last are kept, so a failed fetch does not empty the shard
+                Err(e) => println!("Error fetching the symbols to shard: {}", e),
+            }
+        }
+        let owned: BTreeSet<String> = {
+            let known = self.known_symbols();
+            let (_, symbols) = known.as_ref()?;
+            symbols.iter().filter(|symbol| shard.owns(symbol)).cloned().collect()
+        };
+        let worker_count: usize = shard.ring.len();
+        self.xylex.set_shard(Some(shard.with_symbols(owned.clone())));
+
+        let gained: Vec<String> = owned.difference(&self.assigned_symbols()).cloned().collect();
+        let recorded: bool = match gained.is_empty() {
+            true => true,
+            false => {
+                println!(
+                    "Worker {} took over {} symbols, checking {} across {} workers",
+                    instance_id, gained.len(), owned.len(), worker_count
+                );
+                let symbols: Vec<&str> = gained.iter().map(String::as_str).collect();
+                match self.supabase.assign_symbols(&symbols, instance_id, assignments).await {
+                    Ok(()) => true,
+                    Err(e) => {
+                        println!("Error recording the symbols of worker {}: {}", instance_id, e);
+                        false
+                    },
+                }
+            },
+        };
+        // Symbols which could not be recorded are recorded on the next tick
+        let mut assigned: MutexGuard<'_, BTreeSet<String>> = self.assigned_symbols();
+        *assigned = match recorded {
+            true => owned.clone(),
+            false => owned.intersection(&assigned).cloned().collect(),
+        };
+        drop(assigned);
+
+        let mut status: MutexGuard<'_, EngineStatus> = lock(&self.status);
+        status.shard_workers = Some(worker_count);
+        status.shard_symbols = Some(owned.len());
+        Some(!gained.is_empty())
+    }
+
+    /// Releases this worker's lease, if sharded, so the other workers take over its symbols on their next tick.
+    async fn leave_shard(&self) {
+        let Some((leases, _, instance_id)) = &self.sharding else {
+            return;
+        };
+        let name: String = format!("{}{}", self.worker_prefix(), instance_id);
+        if let Err(e) = self.supabase.release_lease(&name, instance_id, leases).await {
+            println!("Error releasing the lease of worker {}: {}", instance_id, e);
+        }
+        self.xylex.set_shard(None);
+        self.assigned_symbols().clear();
+    }
+
+    /// Prefix of the lease names of the workers sharing the alerts table.
+    fn worker_prefix(&self) -> String {
+        format!("{}/worker/", self.config.tablename)
+    }
+
+    fn known_symbols(&self) -> MutexGuard<'_, KnownSymbols> {
+        self.known_symbols.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn assigned_symbols(&self) -> MutexGuard<'_, BTreeSet<String>> {
+        self.assigned_symbols.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Writes the health to the heartbeat table, if set, logging failures.
+    async fn write_heartbeat(&self) {
+        let Some((table, instance_id)) = &self.heartbeat else {
+            return;
+        };
+        if let Err(e) = self.supabase.write_heartbeat(instance_id, &self.health(), table).await {
+            println!("Error writing the heartbeat of {}: {}", instance_id, e);
+        }
+    }
+
+    /// Writes the status to the status file, logging failures.
+    fn persist_status(&self, status: &EngineStatus) {
+        let Some(path) = &self.status_path else {
+            return;
+        };
+        let written = serde_json::to_vec_pretty(status)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            println!("Error writing engine status to {}: {}", path.display(), e);
+        }
+    }
+}
+
+impl EngineStatus {
+    /// Reads a status written by a previous run.
+    ///
+    /// # Returns
+    /// `None` if the file does not exist or does not hold a status.
+    pub fn load(path: &Path) -> Option<Self> {
+        let json: Vec<u8> = std::fs::read(path).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+}
+
+impl TradeAlertsHandle {
+    /// Returns a snapshot of the last cycles.
+    pub fn status(&self) -> EngineStatus {
+        lock(&self.status).clone()
+    }
+
+    /// Returns the health of the system, see [`EngineStatus::health`].
+    pub fn health(&self) -> Health {
+        self.status().health(Utc::now(), self.stall_after)
+    }
+
+    /// Runs the next cycle now if the system is idle, see [`TradeAlerts::wake`].
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Stops the system, letting a cycle in progress finish first.
+    pub async fn stop(self) {
+        let _ = self.stop.send(true);
+        let _ = self.task.await;
+    }
+
+    /// Whether the background task has stopped, e.g. because it panicked.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl TradeAlertsBuilder {
+    /// Fills in everything that is not set yet from environment variables.
+    ///
+    /// Uses [`Supabase::new_env`], [`TableConfig::new_env`] and, when no provider is set,
+    /// [`XylexApi::new_env`].
+    ///
+    /// # Errors
+    /// Returns `ConfigError::InvalidField` naming the part that could not be configured.
+    pub async fn from_env(mut self) -> Result<Self, ConfigError> {
+        if self.supabase.is_none() {
+            let supabase: Result<Supabase, String> = Supabase::new_env().await.map_err(|e| e.to_string());
+            self.supabase = Some(supabase.map_err(|reason| invalid("supabase", reason))?);
+        }
+        if self.config.is_none() {
+            self.config = Some(TableConfig::new_env().map_err(|e| invalid("table_config", e.to_string()))?);
+        }
+        if self.xylex.is_none() && self.provider.is_none() {
+            self.xylex = Some(XylexApi::new_env().await.map_err(|e| invalid("xylex", e.to_string()))?);
+        }
+        Ok(self)
+    }
+
+    /// Uses a `XylexApi` both as the price provider, unless another one is set, and for the
+    /// cycle state such as candles, the history endpoint and the movement gate.
+    pub fn with_xylex(
+        mut self,
+        xylex: XylexApi
+    ) -> Self {
+        self.xylex = Some(xylex);
+        self
+    }
+
+    /// Fetches quotes from another `PriceProvider`, e.g. a `BinanceProvider`.
+    pub fn with_provider(
+        mut self,
+        provider: impl PriceProvider + 'static
+    ) -> Self {
+        self.provider = Some(Arc::new(provider));
+        self
+    }
+
+    /// Sets the Supabase client holding the alerts.
+    pub fn with_supabase(
+        mut self,
+        supabase: Supabase
+    ) -> Self {
+        self.supabase = Some(supabase);
+        self
+    }
+
+    /// Sets the configuration of the alerts table.
+    pub fn with_table_config(
+        mut self,
+        config: TableConfig
+    ) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Runs a cycle every `idle_interval` instead while the alerts table holds no active alert,
+    /// a slow heartbeat saving quota on quiet deployments.
+    ///
+    /// Full speed resumes once a cycle finds an alert, or immediately after [`TradeAlerts::wake`],
+    /// e.g. from a realtime subscription on the alerts table. Has no effect with a cron expression.
+    pub fn with_idle_interval(
+        mut self,
+        idle_interval: Duration
+    ) -> Self {
+        self.idle_interval = Some(idle_interval);
+        self
+    }
+
+    /// Also delivers every triggered alert to the users it is shared with, see [`Supabase::share_alert`].
+    ///
+    /// Each member gets a copy of the alert under their user ID, see [`Supabase::expand_shared_triggers`].
+    /// The alert is archived or deleted once any copy reached one of its targets.
+    pub fn with_shared_alerts(
+        mut self,
+        shares: SharedAlertTable
+    ) -> Self {
+        self.shares = Some(shares);
+        self
+    }
+
+    /// Archives triggered alerts to a history table instead of deleting them.
+    pub fn with_history(
+        mut self,
+        history_config: TableConfig
+    ) -> Self {
+        self.history_config = Some(history_config);
+        self
+    }
+
+    /// Sets the time between two cycles, defaults to [`DEFAULT_CHECK_INTERVAL`] or the tick of the schedule.
+    pub fn with_interval(
+        mut self,
+        interval: Duration
+    ) -> Self {
+        self.interval = Some(interval);
+        self.cron = None;
+        self
+    }
+
+    /// Runs cycles every interval or at the minutes matching a cron expression, in UTC.
+    ///
+    /// With `Schedule::Cron` the first cycle runs at the first matching minute, not immediately.
+    /// The expression is validated on build, see [`Schedule::validate`].
+    pub fn with_cycle_schedule(
+        mut self,
+        schedule: Schedule
+    ) -> Self {
+        match schedule {
+            Schedule::Interval(interval) => {
+                self.interval = Some(interval);
+                self.cron = None;
+            },
+            Schedule::Cron(expression) => self.cron = Some(expression),
+        }
+        self
+    }
+
+    /// Bounds the caches kept across cycles, see [`XylexApi::with_resource_limits`].
+    pub fn with_resource_limits(
+        mut self,
+        limits: ResourceLimits
+    ) -> Self {
+        self.resource_limits = Some(limits);
+        self
+    }
+
+    /// Writes the price state to a JSON file after every cycle and seeds it from the file on
+    /// build, see [`PriceSnapshot`], unless it is older than the snapshot max age.
+    pub fn with_price_snapshot_file(
+        mut self,
+        path: impl Into<PathBuf>
+    ) -> Self {
+        self.price_snapshot_path = Some(path.into());
+        self
+    }
+
+    /// Seeds the price state on build from a snapshot, e.g. one built from recorded quotes with
+    /// [`PriceSnapshot::from_quotes`], over the snapshot file, unless it is older than the snapshot max age.
+    pub fn with_warm_start(
+        mut self,
+        snapshot: PriceSnapshot
+    ) -> Self {
+        self.warm_start = Some(snapshot);
+        self
+    }
+
+    /// Sets the age after which a snapshot is too stale to seed from, defaults to [`DEFAULT_SNAPSHOT_MAX_AGE`].
+    pub fn with_snapshot_max_age(
+        mut self,
+        max_age: Duration
+    ) -> Self {
+        self.snapshot_max_age = Some(max_age);
+        self
+    }
+
+    /// Writes the health to a row of `table` for `instance_id` after every cycle and once the
+    /// system stops, see [`Supabase::write_heartbeat`].
+    ///
+    /// A stuck cycle writes no heartbeat, so monitoring should also alert on a stale row.
+    pub fn with_heartbeat(
+        mut self,
+        table: HeartbeatTable,
+        instance_id: &str
+    ) -> Self {
+        self.heartbeat = Some((table, instance_id.to_string()));
+        self
+    }
+
+    /// Runs cycles only while `instance_id` holds the leader lease in `table`, so replicas can
+    /// run side by side for high availability without firing alerts twice.
+    ///
+    /// The lease is named after the alerts table and renewed on every tick and while a cycle runs. Once the leader
+    /// stops renewing it, the next replica ticking after it expired takes over, see [`Supabase::acquire_lease`].
+    pub fn with_leader_lease(
+        mut self,
+        table: LeaseTable,
+        instance_id: &str
+    ) -> Self {
+        self.lease = Some((table, instance_id.to_string()));
+        self
+    }
+
+    /// Splits the symbols between every worker running with the same `leases` table, so each
+    /// checks a disjoint share of the alerts.
+    ///
+    /// Every worker renews a lease named after the alerts table and `instance_id` on every tick,
+    /// and the workers whose lease did not expire are placed on a consistent [`HashRing`]. A
+    /// worker joining or leaving only moves the symbols of its neighbours on the ring, and the
+    /// symbols of every worker are recorded in `assignments`, see [`Supabase::assign_symbols`].
+    /// Workers should tick on the same interval, as a symbol may be checked twice or skipped
+    /// once while they disagree on who is live.
+    ///
+    /// Every worker only fetches the alerts of its own symbols. The symbols of the whole table
+    /// are fetched again every [`SHARD_SYMBOLS_REFRESH`], so the alerts of a new symbol are
+    /// checked from the next refresh on. A failed refresh keeps the symbols fetched last.
+    pub fn with_sharding(
+        mut self,
+        leases: LeaseTable,
+        assignments: ShardTable,
+        instance_id: &str
+    ) -> Self {
+        self.sharding = Some((leases, assignments, instance_id.to_string()));
+        self
+    }
+
+    /// Sets how long the leader lease, or the lease of a sharded worker, lasts without being
+    /// renewed, defaults to three times the longest of the interval and the idle interval.
+    ///
+    /// It must be longer than both intervals, as the lease is renewed on every tick, and every
+    /// third of the TTL while a cycle runs. A TTL under twice the longest is accepted with a warning.
+    pub fn with_lease_ttl(
+        mut self,
+        ttl: Duration
+    ) -> Self {
+        self.lease_ttl = Some(ttl);
+        self
+    }
+
+    /// Sets how long the next cycle can be overdue before the system counts as stuck,
+    /// defaults to [`DEFAULT_STALL_THRESHOLD`].
+    pub fn with_stall_threshold(
+        mut self,
+        stall_after: Duration
+    ) -> Self {
+        self.stall_after = Some(stall_after);
+        self
+    }
+
+    /// Checks symbols at the intervals of a [`ScheduleConfig`] instead of all of them every cycle.
+    ///
+    /// Cycles run every [`ScheduleConfig::tick`] unless an interval is set.
+    pub fn with_schedule(
+        mut self,
+        schedule: ScheduleConfig
+    ) -> Self {
+        self.schedule = Some(schedule);
+        self
+    }
+
+    /// Delivers every triggered alert through a notifier, registered under its name.
+    ///
+    /// Notifiers added this way become default channels of the router, next to those of a
+    /// router set with [`TradeAlertsBuilder::with_router`], in either order.
+    pub fn with_notifier(
+        mut self,
+        notifier: impl Notifier + 'static
+    ) -> Self {
+        let name: String = notifier.name().to_string();
+        self.notifiers.push((name, Arc::new(notifier)));
+        self
+    }
+
+    /// Delivers the alerts a user triggered during a cycle together, e.g. as a single webhook
+    /// payload holding every alert, see [`NotificationRouter::with_grouping`].
+    pub fn with_grouped_notifications(mut self) -> Self {
+        self.grouped = true;
+        self
+    }
+
+    /// Draws a chart of every triggered alert with `renderer` and uploads it to `bucket`.
+    ///
+    /// The public URL is added to the metadata of the notifications, see [`TriggeredAlert::chart_url`],
+    /// and with a history table stored in the `chart_url` column of the archived row. Charts
+    /// older than the retention of the bucket are deleted after delivering, at most every
+    /// [`CHART_CLEANUP_INTERVAL`], see [`StorageBucket::with_retention`].
+    pub fn with_chart_snapshots(
+        mut self,
+        renderer: impl ChartRenderer + 'static,
+        bucket: StorageBucket
+    ) -> Self {
+        self.charts = Some((Arc::new(renderer), bucket));
+        self
+    }
+
+    /// Claims every triggered alert before delivering it and recovers the claimed ones on startup,
+    /// for at-least-once delivery across crashes without an intent log on every instance.
+    ///
+    /// A triggered alert's row is marked `triggering` with its idempotency key, the instance ID
+    /// and the claim time, see [`Supabase::claim_trigger`], then it is delivered and archived or
+    /// deleted. A delivery reaching none of its targets releases the claim instead. Alerts left
+    /// `triggering` by a crash are delivered again under the same key before the first cycle once
+    /// their claim is stale, see [`TradeAlerts::recover_triggers`], and are skipped by checks
+    /// meanwhile. The table needs the trigger state, idempotency key and claim columns.
+    pub fn with_two_phase_triggers(mut self) -> Self {
+        self.two_phase_triggers = true;
+        self
+    }
+
+    /// Evaluates the alerts on every cycle and reports what would have fired, without claiming,
+    /// notifying, archiving or deleting anything, see [`XylexApi::with_dry_run`].
+    ///
+    /// Useful to validate a new `TableConfig` or provider against production data: the triggered
+    /// alerts are in the [`CheckReport`] of [`TradeAlerts::run_once`] and logged every cycle.
+    pub fn with_dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Records notifications in a write-ahead intent log, for at-least-once delivery across crashes.
+    pub fn with_intent_log(
+        mut self,
+        path: impl Into<PathBuf>
+    ) -> Self {
+        self.intent_log = Some(IntentLog::new(path));
+        self
+    }
+
+    /// Writes the status to a JSON file after every cycle.
+    ///
+    /// A status left by a previous run is loaded on build, so the last run survives restarts.
+    pub fn with_status_file(
+        mut self,
+        path: impl Into<PathBuf>
+    ) -> Self {
+        self.status_path = Some(path.into());
+        self
+    }
+
+    /// Routes triggered alerts with a preconfigured router, e.g. one with per-user channels.
+    ///
+    /// Notifiers added with [`TradeAlertsBuilder::with_notifier`], before or after, are
+    /// registered on it as default channels.
+    pub fn with_router(
+        mut self,
+        router: NotificationRouter
+    ) -> Self {
+        self.router = router;
+        self
+    }
+
+    /// Builds the system.
+    ///
+    /// # Errors
+    /// Returns `ConfigError::MissingField` if no Supabase client, table configuration or
+    /// price provider is set, and `ConfigError::InvalidField` for a zero interval or idle
+    /// interval or an invalid cron expression.
+    pub fn build(self) -> Result<TradeAlerts, ConfigError> {
+        let supabase: Supabase = self.supabase.ok_or_else(|| ConfigError::MissingField("supabase".to_string()))?;
+        let config: TableConfig = self.config.ok_or_else(|| ConfigError::MissingField("table_config".to_string()))?;
+
+        let (mut xylex, provider): (XylexApi, Arc<dyn PriceProvider>) = match (self.xylex, self.provider) {
+            (Some(xylex), Some(provider)) => (xylex, provider),
+            (Some(xylex), None) => (xylex.clone(), Arc::new(xylex)),
+            // Only the cycle state is used, quotes come from the provider
+            (None, Some(provider)) => (XylexApi::new(String::new(), String::new()), provider),
+            (None, None) => return Err(ConfigError::MissingField("provider".to_string())),
+        };
+
+        let interval: Duration = match (self.interval, &self.schedule) {
+            (Some(interval), _) => interval,
+            (None, Some(schedule)) => schedule.tick(),
+            (None, None) => DEFAULT_CHECK_INTERVAL,
+        };
+        if let Some(schedule) = self.schedule {
+            xylex.schedule = Some(schedule);
+        }
+        if self.dry_run {
+            xylex.dry_run = true;
+        }
+        if let Some(limits) = self.resource_limits {
+            xylex = xylex.with_resource_limits(limits);
+        }
+        let snapshot: Option<PriceSnapshot> = self
+            .warm_start
+            .or_else(|| self.price_snapshot_path.as_deref().and_then(PriceSnapshot::load));
+        if let Some(snapshot) = snapshot {
+            let max_age: Duration = self.snapshot_max_age.unwrap_or(DEFAULT_SNAPSHOT_MAX_AGE);
+            match snapshot.age(Utc::now()) {
+                age if age > max_age => println!("Not seeding prices from a snapshot taken {:?} ago", age),
+                _ => println!("Seeded the prices of {} symbols", xylex.seed_prices(&snapshot)),
+            }
+        }
+        if interval.is_zero() {
+            return Err(invalid("interval", "must be greater than zero".to_string()));
+        }
+        if self.idle_interval.is_some_and(|idle_interval| idle_interval.is_zero()) {
+            return Err(invalid("idle_interval", "must be greater than zero".to_string()));
+        }
+        if self.lease.is_some() && self.sharding.is_some() {
+            return Err(invalid("sharding", "can't be combined with a leader lease".to_string()));
+        }
+        // Ticks are at most this far apart, the lease must outlast the gap
+        let longest_delay: Duration = self.idle_interval.map_or(interval, |idle_interval| idle_interval.max(interval));
+        if let Some(ttl) = self.lease_ttl.filter(|_| self.lease.is_some() || self.sharding.is_some()) {
+            if ttl <= longest_delay {
+                return Err(invalid("lease_ttl", format!("must be longer than the interval of {:?}", longest_delay)));
+            }
+            if ttl < longest_delay * 2 {
+                println!("A lease TTL of {:?} leaves little margin over the interval of {:?}, a slow renewal may lose the lease", ttl, longest_delay);
+            }
+        }
+        let cron: Option<CronExpression> = match &self.cron {
+            Some(expression) => Some(expression.parse().map_err(|reason| invalid("schedule", reason))?),
+            None => None,
+        };
+
+        let instance_id: String = match (&self.lease, &self.sharding) {
+            (Some((_, instance_id)), _) | (None, Some((_, _, instance_id))) => instance_id.clone(),
+            (None, None) => format!("instance-{}-{}", std::process::id(), Utc::now().timestamp_millis()),
+        };
+        let mut router: NotificationRouter = self.router;
+        for (name, notifier) in self.notifiers {
+            router = router.with_default_channel(&name, notifier);
+        }
+        if self.grouped {
+            router = router.with_grouping();
+        }
+        if let Some(intent_log) = self.intent_log {
+            router = router.with_intent_log(intent_log);
+        }
+        if let Some(limits) = self.resource_limits {
+            provider.set_resource_limits(limits);
+            router.set_resource_limits(limits);
+        }
+
+        // Cycles of a previous run are kept, but it no longer schedules anything
+        let status: EngineStatus = self
+            .status_path
+            .as_deref()
+            .and_then(EngineStatus::load)
+            .map(|status| EngineStatus { next_run_at: None, is_leader: None, shard_workers: None, shard_symbols: None, idle: false, ..status })
+            .unwrap_or_default();
+
+        Ok(TradeAlerts {
+            xylex,
+            provider,
+            supabase,
+            config,
+            history_config: self.history_config,
+            router,
+            interval,
+            idle_interval: self.idle_interval,
+            wake: Default::default(),
+            cron,
+            status: Arc::new(Mutex::new(status)),
+            status_path: self.status_path,
+            price_snapshot_path: self.price_snapshot_path,
+            heartbeat: self.heartbeat,
+            lease: self.lease,
+            lease_ttl: self.lease_ttl.unwrap_or(longest_delay * 3),
+            sharding: self.sharding,
+            charts: self.charts,
+            shares: self.shares,
+            last_chart_cleanup: Default::default(),
+            events: self.events.unwrap_or_else(|| broadcast::channel(TRIGGER_CHANNEL_CAPACITY).0),
+            assigned_symbols: Default::default(),
+            known_symbols: Default::default(),
+            two_phase_triggers: self.two_phase_triggers,
+            instance_id,
+            stall_after: self.stall_after.unwrap_or(DEFAULT_STALL_THRESHOLD),
+        })
+    }
+}
+
+/// Whether an alert reached at least one of its targets, or had none to reach.
+fn is_delivered(deliveries: &[&Delivery]) -> bool {
+    deliveries.is_empty() || deliveries.iter().any(|delivery| delivery.error.is_none())
+}
+
+/// Turns a subscription into a stream, skipping the alerts a lagging subscriber missed.
+pub(crate) fn trigger_stream(receiver: broadcast::Receiver<TriggeredAlert>) -> impl Stream<Item = TriggeredAlert> + Send + 'static {
+    futures_util::stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(alert) => return Some((alert, receiver)),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => println!("Subscriber lagged, skipped {} triggered alerts", skipped),
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+fn lock(status: &Mutex<EngineStatus>) -> MutexGuard<'_, EngineStatus> {
+    status.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn invalid(
+    field: &str,
+    reason: String
+) -> ConfigError {
+    ConfigError::InvalidField { field: field.to_string(), reason }
+}
@@ -3,7 +3,7 @@
 use std::fmt;
 
 /// Errors related to Supabase service operations.
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SupabaseError {
     /// Error during authentication.
     AuthenticationError(String),
@@ -15,6 +15,16 @@ pub enum SupabaseError {
     DeletionError(String),
     /// Error during data fetching.
     FetchError(String),
+    /// Error while encrypting or decrypting a field.
+    EncryptionError(String),
+    /// The user already has the maximum number of active alerts.
+    QuotaExceeded(String),
+    /// The user already has an identical alert.
+    DuplicateAlert(String),
+    /// The row was changed by someone else since it was read; re-fetch it and retry.
+    Conflict(String),
+    /// The alert's level is so close to the current price it would fire instantly.
+    TooCloseToMarket(String),
 }
 
 /// Display implementation for `SupabaseError`.
@@ -26,6 +36,11 @@ impl fmt::Display for SupabaseError {
             SupabaseError::UpdateError(msg) => write!(f, "Update Error: {}", msg),
             SupabaseError::DeletionError(msg) => write!(f, "Deletion Error: {}", msg),
             SupabaseError::FetchError(msg) => write!(f, "Fetch Error: {}", msg),
+            SupabaseError::EncryptionError(msg) => write!(f, "Encryption Error: {}", msg),
+            SupabaseError::QuotaExceeded(msg) => write!(f, "Quota Exceeded: {}", msg),
+            SupabaseError::DuplicateAlert(msg) => write!(f, "Duplicate Alert: {}", msg),
+            SupabaseError::Conflict(msg) => write!(f, "Conflict: {}", msg),
+            SupabaseError::TooCloseToMarket(msg) => write!(f, "Too Close To Market: {}", msg),
         }
     }
 }
@@ -34,7 +49,7 @@ impl fmt::Display for SupabaseError {
 impl std::error::Error for SupabaseError {}
 
 /// Errors related to table configuration operations.
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum TableConfigError {
     /// Invalid configuration.
     InvalidConfiguration(String),
@@ -59,7 +74,7 @@ impl fmt::Display for TableConfigError {
 impl std::error::Error for TableConfigError {}
 
 /// Errors related to Xylex API interactions.
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum XylexApiError {
     /// Network connectivity issues.
     NetworkError(String),
@@ -69,6 +84,25 @@ pub enum XylexApiError {
     UnexpectedError(String),
     /// Authentication error due to environment settings.
     EnvAuthenticationError(String),
+    /// Missing or invalid configuration.
+    ConfigurationError(String),
+    /// The monthly credit budget for the provider has been used up.
+    BudgetExceeded(String),
+    /// The provider did not answer in time.
+    Timeout(String),
+    /// The provider answered with HTTP 429, or a body with code 429.
+    RateLimited(String),
+    /// A price field could not be read as a number.
+    InvalidPrice {
+        /// The symbol whose quote was being read.
+        symbol: String,
+        /// The name of the offending field, e.g. `price`.
+        field: String,
+        /// The value as sent by the provider, in JSON.
+        raw: String,
+        /// Why the value was rejected.
+        reason: String,
+    },
 }
 
 /// Display implementation for `XylexApiError`.
@@ -79,9 +113,155 @@ impl fmt::Display for XylexApiError {
             XylexApiError::InvalidSymbol(symbol) => write!(f, "Invalid symbol provided: {}", symbol),
             XylexApiError::UnexpectedError(info) => write!(f, "An unexpected error occurred: {}", info),
             XylexApiError::EnvAuthenticationError(msg) => write!(f, "Environment-based authentication error: {}", msg),
+            XylexApiError::ConfigurationError(msg) => write!(f, "Configuration error: {}", msg),
+            XylexApiError::BudgetExceeded(msg) => write!(f, "Credit budget exceeded: {}", msg),
+            XylexApiError::Timeout(msg) => write!(f, "Request timed out: {}", msg),
+            XylexApiError::RateLimited(msg) => write!(f, "Rate limited by the provider: {}", msg),
+            XylexApiError::InvalidPrice { symbol, field, raw, reason } => {
+                write!(f, "Invalid {} for {}: {} ({})", field, symbol, raw, reason)
+            },
         }
     }
 }
 
 /// Error trait implementation for `XylexApiError`.
-impl std::error::Error for XylexApiError {}
\ No newline at end of file
+impl std::error::Error for XylexApiError {}
+/// Errors related to configuration values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// A required setting was not provided.
+    MissingField(String),
+    /// A setting holds a value which could not be parsed.
+    InvalidField {
+        /// The name of the offending setting.
+        field: String,
+        /// Why the value was rejected.
+        reason: String,
+    },
+}
+
+/// Display implementation for `ConfigError`.
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::MissingField(field) => write!(f, "Missing setting: {}", field),
+            ConfigError::InvalidField { field, reason } => write!(f, "Invalid value for {}: {}", field, reason),
+        }
+    }
+}
+
+/// Error trait implementation for `ConfigError`.
+impl std::error::Error for ConfigError {}
+
+/// Errors related to building an `Alert`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AlertError {
+    /// A required field was not set.
+    MissingField(String),
+    /// A field was set to an invalid value.
+    InvalidField(String),
+}
+
+/// Display implementation for `AlertError`.
+impl fmt::Display for AlertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertError::MissingField(field) => write!(f, "Missing alert field: {}", field),
+            AlertError::InvalidField(msg) => write!(f, "Invalid alert field: {}", msg),
+        }
+    }
+}
+
+/// Error trait implementation for `AlertError`.
+impl std::error::Error for AlertError {}
+
+/// Errors related to encoding and decoding trigger events.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EventError {
+    /// The event was written with a schema version that can no longer be decoded.
+    UnsupportedVersion(u64),
+    /// The event is not valid JSON or misses required fields.
+    InvalidPayload(String),
+}
+
+/// Display implementation for `EventError`.
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventError::UnsupportedVersion(version) => write!(f, "Unsupported event schema version: {}", version),
+            EventError::InvalidPayload(msg) => write!(f, "Invalid event payload: {}", msg),
+        }
+    }
+}
+
+/// Error trait implementation for `EventError`.
+impl std::error::Error for EventError {}
+
+/// Errors related to delivering notifications.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NotifyError {
+    /// The notification could not be delivered.
+    DeliveryError(String),
+    /// The notification channel is not configured correctly.
+    ConfigurationError(String),
+}
+
+/// Display implementation for `NotifyError`.
+impl fmt::Display for NotifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotifyError::DeliveryError(msg) => write!(f, "Notification delivery error: {}", msg),
+            NotifyError::ConfigurationError(msg) => write!(f, "Notification configuration error: {}", msg),
+        }
+    }
+}
+
+/// Error trait implementation for `NotifyError`.
+impl std::error::Error for NotifyError {}
+
+/// Errors related to parsing a `Symbol`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SymbolError {
+    /// The symbol is empty.
+    Empty,
+    /// The symbol is not in a recognised format.
+    Invalid(String),
+}
+
+/// Display implementation for `SymbolError`.
+impl fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolError::Empty => write!(f, "Symbol is empty"),
+            SymbolError::Invalid(symbol) => write!(f, "Invalid symbol: {}", symbol),
+        }
+    }
+}
+
+/// Error trait implementation for `SymbolError`.
+impl std::error::Error for SymbolError {}
+
+/// Errors related to verifying a signed action token.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ActionTokenError {
+    /// The token is not a well-formed action token.
+    Malformed,
+    /// The signature does not match, the token was altered or signed with another secret.
+    InvalidSignature,
+    /// The token is past its expiry.
+    Expired,
+}
+
+/// Display implementation for `ActionTokenError`.
+impl fmt::Display for ActionTokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionTokenError::Malformed => write!(f, "Action token is malformed"),
+            ActionTokenError::InvalidSignature => write!(f, "Action token signature is invalid"),
+            ActionTokenError::Expired => write!(f, "Action token has expired"),
+        }
+    }
+}
+
+/// Error trait implementation for `ActionTokenError`.
+impl std::error::Error for ActionTokenError {}
@@ -0,0 +1,126 @@
+//! ## Trigger event wire format
+//!
+//! `TriggeredAlert`s leave the process as JSON events carrying a `schema_version` field,
+//! so producers and consumers can be upgraded independently during rolling deploys.
+//!
+//! ### Evolution rules
+//! - New versions may only **add** fields; existing fields are never renamed, removed or retyped.
+//! - Added fields must have a default, so older events decode into the current struct.
+//! - Decoders ignore unknown fields, so events from newer producers decode as the current version.
+//!
+//! ### Versions
+//! - `1`: `hash`, `user_id`, `symbol`, `price_level`, `trigger_price`.
+//! - `2`: adds `condition` (defaults to `price_level`) and `metadata` (defaults to `null`).
+//! - `3`: adds `triggered_at` (defaults to the Unix epoch).
+//! - `4`: adds `quote_time` (defaults to `null`).
+//! - `5`: adds `idempotency_key`, derived from `hash` and `triggered_at` so it is not decoded.
+//!
+//! ### Grouped payloads
+//! The triggers of a user during a cycle can be sent as a single payload, see
+//! [`crate::notify::NotificationRouter::with_grouping`]. It carries its own `schema_version`,
+//! [`TRIGGER_BATCH_SCHEMA_VERSION`], next to `user_id` and the `alerts` array of versioned events.
+//! - `1`: `schema_version`, `user_id`, `alerts`.
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::TriggeredAlert;
+use crate::errors::EventError;
+
+/// The schema version written by this version of the crate.
+pub const TRIGGERED_ALERT_SCHEMA_VERSION: u64 = 5;
+
+/// The oldest schema version that can still be decoded.
+pub const MIN_TRIGGERED_ALERT_SCHEMA_VERSION: u64 = 1;
+
+/// The schema version of grouped payloads written by this version of the crate.
+pub const TRIGGER_BATCH_SCHEMA_VERSION: u64 = 1;
+
+/// ## The triggers of a single user during a check cycle
+///
+/// Lets consumers which handle notifications themselves fan out with one call per user.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct UserTriggers {
+    pub user_id: String,
+    /// The user's triggered alerts, in the order they triggered.
+    pub alerts: Vec<TriggeredAlert>,
+}
+
+/// Groups triggered alerts per user, keeping users in the order of their first trigger.
+pub fn group_by_user(triggered: &[TriggeredAlert]) -> Vec<UserTriggers> {
+    let mut groups: Vec<UserTriggers> = Vec::new();
+
+    for alert in triggered {
+        match groups.iter_mut().find(|group| group.user_id == alert.user_id) {
+            Some(group) => group.alerts.push(alert.clone()),
+            None => groups.push(UserTriggers { user_id: alert.user_id.clone(), alerts: vec![alert.clone()] }),
+        }
+    }
+
+    groups
+}
+
+impl UserTriggers {
+    /// Encodes the group as a versioned grouped payload, with every alert as a versioned event.
+    pub fn to_event(&self) -> Value {
+        json!({
+            "schema_version": TRIGGER_BATCH_SCHEMA_VERSION,
+            "user_id": self.user_id,
+            "alerts": self.alerts.iter().map(TriggeredAlert::to_event).collect::<Vec<Value>>(),
+        })
+    }
+}
+
+impl TriggeredAlert {
+    /// Encodes the alert as a versioned JSON event.
+    pub fn to_event(&self) -> Value {
+        let mut event = serde_json::to_value(self).unwrap_or(Value::Null);
+        event["schema_version"] = Value::from(TRIGGERED_ALERT_SCHEMA_VERSION);
+        event["idempotency_key"] = Value::from(self.idempotency_key());
+        event
+    }
+
+    /// Returns the key identifying this trigger of the alert, the same every time it is delivered.
+    ///
+    /// Receivers handling deliveries at least once drop events whose key they already saw.
+    /// The trigger time is taken to the millisecond, so the key survives a round trip through the alerts table.
+    pub fn idempotency_key(&self) -> String {
+        format!("{}@{}", self.hash, self.triggered_at.timestamp_millis())
+    }
+
+    /// Encodes the alert as a versioned JSON event string.
+    pub fn to_event_json(&self) -> String {
+        self.to_event().to_string()
+    }
+
+    /// Decodes a versioned JSON event.
+    ///
+    /// Events without a `schema_version` are treated as version `1`.
+    ///
+    /// # Errors
+    /// Returns `EventError::UnsupportedVersion` for versions older than
+    /// `MIN_TRIGGERED_ALERT_SCHEMA_VERSION`, or `EventError::InvalidPayload` if a required field is missing.
+    pub fn from_event(event: &Value) -> Result<Self, EventError> {
+        let version = match event.get("schema_version") {
+            None | Some(Value::Null) => 1,
+            Some(version) => version
+                .as_u64()
+                .ok_or_else(|| EventError::InvalidPayload("schema_version is not a number".to_string()))?,
+        };
+        if version < MIN_TRIGGERED_ALERT_SCHEMA_VERSION {
+            return Err(EventError::UnsupportedVersion(version));
+        }
+
+        serde_json::from_value(event.clone()).map_err(|e| EventError::InvalidPayload(e.to_string()))
+    }
+
+    /// Decodes a versioned JSON event string.
+    ///
+    /// # Errors
+    /// Returns `EventError::InvalidPayload` if the string is not valid JSON, see [`TriggeredAlert::from_event`].
+    pub fn from_event_json(event: &str) -> Result<Self, EventError> {
+        let event: Value = serde_json::from_str(event).map_err(|e| EventError::InvalidPayload(e.to_string()))?;
+
+        Self::from_event(&event)
+    }
+}
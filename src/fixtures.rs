@@ -0,0 +1,869 @@
+//! ## Test fixtures
+//!
+//! Canned alert rows, provider responses and a local server standing in for Supabase and the
+//! price endpoint, so applications built on this crate can write integration-style tests
+//! without mocks of their own. Only compiled with the `fixtures` feature.
+//!
+//! ```no_run
+//! use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+//!
+//! # async fn example() {
+//! let config = table_config("alerts");
+//! let server = FixtureServer::start().await;
+//! server.insert_rows("alerts", vec![AlertFixture::new("xlx", "eur/usd", 1.10).latest_price(1.05).row(&config)]);
+//! server.set_price("eur/usd", 1.12);
+//!
+//! let supabase = server.supabase();
+//! let api = server.xylex_api();
+//! let report = api.check_alerts(&supabase, &config).await.unwrap();
+//! assert_eq!(report.triggered.len(), 1);
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::{Alert, Condition, TriggeredAlert};
+use crate::alert::is_expired;
+use crate::data::{Candle, XylexApi};
+use crate::db::quota::QUOTA_INSERT_FUNCTION;
+use crate::db::{Supabase, TableConfig};
+use crate::retry::RetryPolicy;
+
+/// Key accepted by the `FixtureServer`, used by the clients of [`FixtureServer::supabase`].
+pub const FIXTURE_KEY: &str = "fixture-key";
+
+/// Returns the default table layout under another table name.
+pub fn table_config(tablename: &str) -> TableConfig {
+    TableConfig { tablename: tablename.to_string(), ..TableConfig::default() }
+}
+
+/// ## Builds alerts and the rows stored for them
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlertFixture {
+    alert: Alert,
+    latest_price: f64,
+}
+
+impl AlertFixture {
+    /// An alert of `user123` at `price_level`, stored while the price was at the level.
+    pub fn new(
+        hash: &str,
+        symbol: &str,
+        price_level: f64
+    ) -> Self {
+        Self {
+            alert: Alert::new(hash.to_string(), price_level, symbol.to_string(), "user123".to_string()),
+            latest_price: price_level,
+        }
+    }
+
+    /// Sets the owner of the alert.
+    pub fn user_id(
+        mut self,
+        user_id: &str
+    ) -> Self {
+        self.alert.user_id = user_id.to_string();
+        self
+    }
+
+    /// Sets the price when the alert was stored, which decides its initial direction.
+    pub fn latest_price(
+        mut self,
+        latest_price: f64
+    ) -> Self {
+        self.latest_price = latest_price;
+        self
+    }
+
+    /// Sets the condition of the alert.
+    pub fn condition(
+        mut self,
+        condition: Condition
+    ) -> Self {
+        self.alert.condition = condition;
+        self
+    }
+
+    /// Sets the tags of the alert.
+    pub fn tags(
+        mut self,
+        tags: &[&str]
+    ) -> Self {
+        self.alert.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        self
+    }
+
+    /// Returns the alert, with the direction it is stored with.
+    pub fn alert(&self) -> Alert {
+        let direction: &str = if self.latest_price > self.alert.price_level { "buy" } else { "sell" };
+        Alert { direction: Some(direction.to_string()), ..self.alert.clone() }
+    }
+
+    /// Returns the row stored for the alert, as inserted by `Supabase::add_alert`.
+    pub fn row(&self, config: &TableConfig) -> Value {
+        Supabase::new(FIXTURE_KEY.to_string(), String::new())
+            .alert_row(self.alert.clone(), self.latest_price, config)
+            .unwrap_or_else(|e| panic!("Fixture row could not be built: {}", e))
+    }
+
+    /// Returns the row as returned by `Supabase::fetch_all_data`.
+    pub fn record(&self, config: &TableConfig) -> HashMap<String, Value> {
+        match self.row(config) {
+            Value::Object(row) => row.into_iter().collect(),
+            _ => HashMap::new(),
+        }
+    }
+}
+
+/// Canned rows of the default table layout: a EUR/USD alert above the price, a GBP/USD alert
+/// below it and an AAPL alert of another user.
+pub fn alert_rows() -> Vec<Value> {
+    let config: TableConfig = TableConfig::default();
+    vec![
+        AlertFixture::new("xlx-eurusd", "EUR/USD", 1.10).latest_price(1.08).row(&config),
+        AlertFixture::new("xlx-gbpusd", "GBP/USD", 1.25).latest_price(1.27).row(&config),
+        AlertFixture::new("xlx-aapl", "AAPL", 200.0).user_id("user456").latest_price(190.0).row(&config),
+    ]
+}
+
+/// A `PriceLevel` alert of `user123` triggered just now at its level, other fields are set
+/// with struct update syntax, e.g. `TriggeredAlert { user_id, ..triggered_alert("xlx", "EUR/USD", 1.10) }`.
+pub fn triggered_alert(
+    hash: &str,
+    symbol: &str,
+    price_level: f64
+) -> TriggeredAlert {
+    TriggeredAlert {
+        hash: hash.to_string(),
+        user_id: "user123".to_string(),
+        symbol: symbol.to_string(),
+        price_level,
+        trigger_price: price_level,
+        condition: Condition::PriceLevel,
+        metadata: None,
+        triggered_at: Utc::now(),
+        quote_time: None,
+    }
+}
+
+/// The response of the price endpoint for a single symbol.
+pub fn quote_response(price: f64) -> Value {
+    json!({ "price": price.to_string() })
+}
+
+/// The response of the price endpoint for several symbols in one request.
+pub fn batch_quote_response(prices: &[(&str, f64)]) -> Value {
+    Value::Object(prices.iter().map(|(symbol, price)| (symbol.to_string(), quote_response(*price))).collect())
+}
+
+/// The response of the time series endpoint for `candles`, newest first.
+pub fn time_series_response(candles: &[Candle]) -> Value {
+    let mut values: Vec<Value> = candles
+        .iter()
+        .map(|candle| {
+            json!({
+                "datetime": candle.open_time.format("%Y-%m-%d %H:%M:%S").to_string(),
+                "open": candle.open.to_string(),
+                "high": candle.high.to_string(),
+                "low": candle.low.to_string(),
+                "close": candle.close.to_string(),
+            })
+        })
+        .collect();
+    values.reverse();
+    json!({ "values": values, "status": "ok" })
+}
+
+/// ## A local server answering like Supabase and the price endpoint
+///
+/// `/rest/v1/{table}` behaves like PostgREST on in-memory rows: `GET` selects, `POST` inserts,
+/// `PATCH` updates and `DELETE` deletes, honouring `eq`, `is.null`, `lt`, `in`, `ilike`, `wfts` and `cs` filters
+/// on columns and `->>` JSON paths, and `order`, `offset` and `limit` on selects, and runs the
+/// [`QUOTA_INSERT_FUNCTION`] under `/rest/v1/rpc/`. Every other path is
+/// the price endpoint, quoting the prices set with [`FixtureServer::set_price`], or with an
+/// `interval` the time series endpoint, serving the candles set with [`FixtureServer::set_candles`].
+/// `/storage/v1/object/` uploads, lists and deletes files like Supabase Storage, and
+/// `/hooks/{name}` accepts webhooks, see [`FixtureServer::webhook_url`].
+///
+/// Failures are scripted with [`FixtureServer::queue_responses`], [`FixtureServer::drop_connections`],
+/// [`FixtureServer::hang`] and [`FixtureServer::set_delay`]. Every request is recorded, see
+/// [`FixtureServer::requests_to`]. Cloning a `FixtureServer` shares its state, the server stops
+/// with the runtime.
+#[derive(Clone, Debug)]
+pub struct FixtureServer {
+    url: String,
+    state: Arc<Mutex<FixtureState>>,
+}
+
+#[derive(Debug, Default)]
+struct FixtureState {
+    tables: HashMap<String, Vec<Value>>,
+    prices: HashMap<String, f64>,
+    /// Bid and ask quoted with the price, keyed by symbol.
+    books: HashMap<String, (f64, f64)>,
+    candles: HashMap<String, Vec<Candle>>,
+    /// Stored files and when they were created, keyed by `bucket/path`.
+    objects: BTreeMap<String, (Vec<u8>, DateTime<Utc>)>,
+    requests: Vec<String>,
+    received: Vec<FixtureRequest>,
+    /// Columns whose values must be unique together, keyed by table.
+    unique: HashMap<String, Vec<String>>,
+    /// Responses sent instead of the usual answer, keyed by path prefix.
+    queued: Vec<(String, VecDeque<(u16, Value)>)>,
+    /// Paths answered with a `302` to the location, keyed by path prefix.
+    redirects: Vec<(String, String)>,
+    /// Connections still to be closed without an answer.
+    dropped: usize,
+    /// Path prefixes which are never answered.
+    hung: Vec<String>,
+    delay: Duration,
+    in_flight: usize,
+    peak_in_flight: usize,
+    batch_quotes_disabled: bool,
+}
+
+/// ## A request received by a `FixtureServer`
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixtureRequest {
+    pub method: String,
+    /// The path and query, e.g. `/price?symbol=EUR%2FUSD`.
+    pub target: String,
+    /// The headers, with lowercase names.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl FixtureRequest {
+    /// Returns the value of a header, `name` is case-insensitive.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Parses the body as JSON, `Value::Null` if it isn't JSON.
+    pub fn json(&self) -> Value {
+        serde_json::from_str(&self.body).unwrap_or(Value::Null)
+    }
+}
+
+impl FixtureServer {
+    /// Starts a server on a free local port.
+    ///
+    /// # Panics
+    /// Panics if no local port could be bound.
+    pub async fn start() -> Self {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").await.expect("Failed to bind the fixture server");
+        let server = Self {
+            url: format!("http://{}", listener.local_addr().expect("Fixture server has no address")),
+            state: Arc::new(Mutex::new(FixtureState::default())),
+        };
+
+        let state = server.state.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(serve(socket, state.clone()));
+            }
+        });
+        server
+    }
+
+    /// The base URL, used as the Supabase URL.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The URL of the price endpoint.
+    pub fn price_endpoint(&self) -> String {
+        format!("{}/price", self.url)
+    }
+
+    /// Returns a `Supabase` client of the server without retries.
+    pub fn supabase(&self) -> Supabase {
+        Supabase::new(FIXTURE_KEY.to_string(), self.url.clone()).with_retry_policy(RetryPolicy::none())
+    }
+
+    /// The URL of the time series endpoint.
+    pub fn history_endpoint(&self) -> String {
+        format!("{}/time_series", self.url)
+    }
+
+    /// Returns a `XylexApi` fetching from the price endpoint without retries.
+    pub fn xylex_api(&self) -> XylexApi {
+        XylexApi::new(FIXTURE_KEY.to_string(), self.price_endpoint()).with_retry_policy(RetryPolicy::none())
+    }
+
+    /// Adds rows to a table.
+    pub fn insert_rows(
+        &self,
+        table: &str,
+        rows: Vec<Value>
+    ) {
+        self.lock().tables.entry(table.to_string()).or_default().extend(rows);
+    }
+
+    /// Replaces the rows of a table.
+    pub fn set_rows(
+        &self,
+        table: &str,
+        rows: Vec<Value>
+    ) {
+        self.lock().tables.insert(table.to_string(), rows);
+    }
+
+    /// Rejects inserts into a table which would repeat the values of `columns` of another row,
+    /// like a unique index. The whole insert is rejected, as PostgREST does.
+    pub fn set_unique(
+        &self,
+        table: &str,
+        columns: &[&str]
+    ) {
+        self.lock().unique.insert(table.to_string(), columns.iter().map(|column| column.to_string()).collect());
+    }
+
+    /// Returns the rows of a table.
+    pub fn rows(&self, table: &str) -> Vec<Value> {
+        self.lock().tables.get(table).cloned().unwrap_or_default()
+    }
+
+    /// Sets the price quoted for a symbol.
+    pub fn set_price(
+        &self,
+        symbol: &str,
+        price: f64
+    ) {
+        self.lock().prices.insert(symbol.to_string(), price);
+    }
+
+    /// Sets the bid and ask quoted along with the price of a symbol.
+    pub fn set_book(
+        &self,
+        symbol: &str,
+        bid: f64,
+        ask: f64
+    ) {
+        self.lock().books.insert(symbol.to_string(), (bid, ask));
+    }
+
+    /// Sets the candles served for a symbol, of any timeframe.
+    pub fn set_candles(
+        &self,
+        symbol: &str,
+        candles: Vec<Candle>
+    ) {
+        self.lock().candles.insert(symbol.to_string(), candles);
+    }
+
+    /// Stores a file in a bucket, as if it was uploaded at `created_at`.
+    pub fn insert_object(
+        &self,
+        bucket: &str,
+        path: &str,
+        bytes: Vec<u8>,
+        created_at: DateTime<Utc>
+    ) {
+        self.lock().objects.insert(format!("{}/{}", bucket, path), (bytes, created_at));
+    }
+
+    /// Returns the paths of the files in a bucket, in order.
+    pub fn objects(&self, bucket: &str) -> Vec<String> {
+        let prefix: String = format!("{}/", bucket);
+        self.lock().objects.keys().filter_map(|key| key.strip_prefix(&prefix)).map(String::from).collect()
+    }
+
+    /// Returns every request received so far, as `METHOD /path?query`.
+    pub fn requests(&self) -> Vec<String> {
+        self.lock().requests.clone()
+    }
+
+    /// Returns the requests received on paths starting with `path`, with their headers and bodies.
+    pub fn requests_to(&self, path: &str) -> Vec<FixtureRequest> {
+        self.lock().received.iter().filter(|request| request.target.starts_with(path)).cloned().collect()
+    }
+
+    /// The URL of a webhook endpoint, answering every request with `200` unless responses are
+    /// queued for it. Its requests are returned by [`FixtureServer::webhook_requests`].
+    pub fn webhook_url(&self, name: &str) -> String {
+        format!("{}/hooks/{}", self.url, name)
+    }
+
+    /// Returns the requests received by the webhook endpoint `name`.
+    pub fn webhook_requests(&self, name: &str) -> Vec<FixtureRequest> {
+        let path: String = format!("/hooks/{}", name);
+        self.lock()
+            .received
+            .iter()
+            .filter(|request| request.target.split('?').next() == Some(path.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// Answers the next requests on paths starting with `path` with `responses` in turn, e.g. a
+    /// `503` followed by a `429`, then goes back to the usual answers.
+    pub fn queue_responses(
+        &self,
+        path: &str,
+        responses: Vec<(u16, Value)>
+    ) {
+        self.lock().queued.push((path.to_string(), responses.into()));
+    }
+
+    /// Answers every request on paths starting with `path` with a `302` to `location`.
+    pub fn redirect(
+        &self,
+        path: &str,
+        location: &str
+    ) {
+        self.lock().redirects.push((path.to_string(), location.to_string()));
+    }
+
+    /// Closes the next `connections` connections after reading their request, without answering.
+    pub fn drop_connections(&self, connections: usize) {
+        self.lock().dropped += connections;
+    }
+
+    /// Never answers requests on paths starting with `path`, keeping their connections open.
+    pub fn hang(&self, path: &str) {
+        self.lock().hung.push(path.to_string());
+    }
+
+    /// Answers every request after `delay`.
+    pub fn set_delay(&self, delay: Duration) {
+        self.lock().delay = delay;
+    }
+
+    /// The most requests which were answered at the same time.
+    pub fn peak_in_flight(&self) -> usize {
+        self.lock().peak_in_flight
+    }
+
+    /// Answers price requests for several symbols with an error, like a plan without batching.
+    pub fn disable_batch_quotes(&self) {
+        self.lock().batch_quotes_disabled = true;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FixtureState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Answers a single request and closes the connection.
+async fn serve(
+    mut socket: TcpStream,
+    state: Arc<Mutex<FixtureState>>
+) {
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end: usize = loop {
+        match socket.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+        }
+        if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end + 4;
+        }
+    };
+
+    let head: String = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let content_length: usize = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse().ok())
+        .unwrap_or(0);
+    while buffer.len() < header_end + content_length {
+        match socket.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+        }
+    }
+    let raw: &[u8] = &buffer[header_end..];
+    let body: Value = serde_json::from_slice(raw).unwrap_or(Value::Null);
+
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (method, target) = (request_line.next().unwrap_or_default(), request_line.next().unwrap_or_default());
+    let path: &str = target.split('?').next().unwrap_or_default();
+    let answer: Option<(u16, Value, Option<String>, Duration)> = {
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        state.requests.push(format!("{} {}", method, target));
+        state.received.push(FixtureRequest {
+            method: method.to_string(),
+            target: target.to_string(),
+            headers: lines
+                .filter_map(|line| line.split_once(':'))
+                .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+                .collect(),
+            body: String::from_utf8_lossy(raw).to_string(),
+        });
+        if state.dropped > 0 {
+            state.dropped -= 1;
+            return;
+        }
+        if state.hung.iter().any(|hung| path.starts_with(hung.as_str())) {
+            None
+        } else {
+            state.in_flight += 1;
+            state.peak_in_flight = state.peak_in_flight.max(state.in_flight);
+
+            let queued: Option<(u16, Value)> = state
+                .queued
+                .iter_mut()
+                .find(|(prefix, responses)| path.starts_with(prefix.as_str()) && !responses.is_empty())
+                .and_then(|(_, responses)| responses.pop_front());
+            let (status, response) = match (queued, target.strip_prefix("/storage/v1/object/")) {
+                (Some(queued), _) => queued,
+                (None, Some(path)) => storage(&mut state, method, path, raw, &body),
+                (None, None) if path.starts_with("/hooks/") => (200, json!({})),
+                (None, None) => respond(&mut state, method, target, body),
+            };
+            let location: Option<String> = state
+                .redirects
+                .iter()
+                .find(|(prefix, _)| path.starts_with(prefix.as_str()))
+                .map(|(_, location)| location.clone());
+            match location {
+                Some(location) => Some((302, json!({}), Some(location), state.delay)),
+                None => Some((status, response, None, state.delay)),
+            }
+        }
+    };
+    let Some((status, response, location, delay)) = answer else {
+        // Hung paths keep the connection open without answering
+        return std::future::pending().await;
+    };
+    tokio::time::sleep(delay).await;
+    state.lock().unwrap_or_else(|e| e.into_inner()).in_flight -= 1;
+
+    let body: String = response.to_string();
+    let response: String = format!(
+        "HTTP/1.1 {} {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n{}\r\n{}",
+        status,
+        if status == 200 { "OK" } else { "Error" },
+        body.len(),
+        location.map(|location| format!("location: {}\r\n", location)).unwrap_or_default(),
+        body
+    );
+    socket.write_all(response.as_bytes()).await.ok();
+}
+
+fn respond(
+    state: &mut FixtureState,
+    method: &str,
+    target: &str,
+    body: Value
+) -> (u16, Value) {
+    let Ok(url) = reqwest::Url::parse(&format!("http://fixture{}", target)) else {
+        return (400, json!({ "message": "Invalid request target" }));
+    };
+    let query: Vec<(String, String)> = url.query_pairs().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+
+    let Some(table) = url.path().strip_prefix("/rest/v1/") else {
+        return quote(state, &query);
+    };
+    if table == format!("rpc/{}", QUOTA_INSERT_FUNCTION) {
+        return insert_within_quota(state, &body);
+    }
+    let unique: Vec<String> = state.unique.get(table).cloned().unwrap_or_default();
+    let rows: &mut Vec<Value> = state.tables.entry(table.to_string()).or_default();
+    let matches = |row: &Value| {
+        query
+            .iter()
+            .filter(|(column, _)| !PAGING_PARAMS.contains(&column.as_str()))
+            .all(|(column, filter)| matches_filter(column_value(row, column), filter))
+    };
+
+    match method {
+        "GET" => (200, Value::Array(page(rows.iter().filter(|row| matches(row)).cloned().collect(), &query))),
+        "POST" => {
+            let inserted: Vec<Value> = match body {
+                Value::Array(inserted) => inserted,
+                row => vec![row],
+            };
+            if !unique.is_empty() {
+                let key = |row: &Value| unique.iter().map(|column| row.get(column).map(Value::to_string)).collect::<Vec<_>>();
+                let mut keys: HashSet<Vec<Option<String>>> = rows.iter().map(key).collect();
+                if !inserted.iter().all(|row| keys.insert(key(row))) {
+                    return (409, json!({ "code": "23505", "message": "duplicate key value violates unique constraint" }));
+                }
+            }
+            rows.extend(inserted.iter().cloned());
+            (200, Value::Array(inserted))
+        },
+        "PATCH" => {
+            let mut updated: Vec<Value> = Vec::new();
+            for row in rows.iter_mut().filter(|row| matches(row)) {
+                if let (Some(row), Some(changes)) = (row.as_object_mut(), body.as_object()) {
+                    row.extend(changes.clone());
+                }
+                updated.push(row.clone());
+            }
+            (200, Value::Array(updated))
+        },
+        "DELETE" => {
+            let (deleted, kept): (Vec<Value>, Vec<Value>) = rows.drain(..).partition(|row| matches(row));
+            *rows = kept;
+            (200, Value::Array(deleted))
+        },
+        _ => (405, json!({ "message": format!("Unsupported method {}", method) })),
+    }
+}
+
+/// Runs the `QUOTA_INSERT_FUNCTION`, inserting each row only while its user is under the quota.
+fn insert_within_quota(
+    state: &mut FixtureState,
+    args: &Value
+) -> (u16, Value) {
+    let (Some(table), Some(user_id_column), Some(expiry_column), Some(max_active)) = (
+        args["table_name"].as_str(),
+        args["user_id_column"].as_str(),
+        args["expiry_column"].as_str(),
+        args["max_active"].as_u64(),
+    ) else {
+        return (400, json!({ "message": "Missing arguments" }));
+    };
+    let rows: &mut Vec<Value> = state.tables.entry(table.to_string()).or_default();
+    let mut inserted: Vec<Value> = Vec::new();
+    for row in args["rows"].as_array().into_iter().flatten() {
+        let active: usize = rows
+            .iter()
+            .filter(|existing| existing.get(user_id_column) == row.get(user_id_column))
+            .filter(|existing| !is_expired(existing.get(expiry_column)))
+            .count();
+        if (active as u64) < max_active {
+            rows.push(row.clone());
+            inserted.push(row.clone());
+        }
+    }
+    (200, Value::Array(inserted))
+}
+
+/// Answers a Supabase Storage request on `path`, the part after `/storage/v1/object/`.
+fn storage(
+    state: &mut FixtureState,
+    method: &str,
+    path: &str,
+    raw: &[u8],
+    body: &Value
+) -> (u16, Value) {
+    match (method, path.strip_prefix("list/")) {
+        ("POST", Some(bucket)) => {
+            let prefix: String = match body["prefix"].as_str().unwrap_or_default() {
+                "" => format!("{}/", bucket),
+                prefix => format!("{}/{}/", bucket, prefix),
+            };
+            let offset: usize = body["offset"].as_u64().unwrap_or(0) as usize;
+            let limit: usize = body["limit"].as_u64().unwrap_or(100) as usize;
+            let mut files: Vec<(&str, &DateTime<Utc>)> = state
+                .objects
+                .iter()
+                .filter_map(|(key, (_, created_at))| Some((key.strip_prefix(&prefix)?, created_at)))
+                .filter(|(name, _)| !name.contains('/'))
+                .collect();
+            match (body["sortBy"]["column"].as_str(), body["sortBy"]["order"].as_str()) {
+                (Some("created_at"), Some("desc")) => files.sort_by(|a, b| b.1.cmp(a.1)),
+                (Some("created_at"), _) => files.sort_by(|a, b| a.1.cmp(b.1)),
+                _ => {},
+            }
+            let files: Vec<Value> = files
+                .into_iter()
+                .map(|(name, created_at)| json!({ "id": name, "name": name, "created_at": created_at.to_rfc3339() }))
+                .skip(offset)
+                .take(limit)
+                .collect();
+            (200, Value::Array(files))
+        },
+        ("POST", None) => {
+            state.objects.insert(path.to_string(), (raw.to_vec(), Utc::now()));
+            (200, json!({ "Key": path }))
+        },
+        ("DELETE", None) => {
+            let deleted: Vec<Value> = body["prefixes"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .filter(|name| state.objects.remove(&format!("{}/{}", path, name)).is_some())
+                .map(|name| json!({ "name": name }))
+                .collect();
+            (200, Value::Array(deleted))
+        },
+        _ => (405, json!({ "message": format!("Unsupported method {}", method) })),
+    }
+}
+
+/// Quotes the `symbol` parameter, comma separated symbols are answered as a batch.
+fn quote(
+    state: &FixtureState,
+    query: &[(String, String)]
+) -> (u16, Value) {
+    if let Some((_, interval)) = query.iter().find(|(name, _)| name == "interval") {
+        return time_series(state, query, interval);
+    }
+    let symbols: Vec<&str> = query
+        .iter()
+        .find(|(name, _)| name == "symbol")
+        .map(|(_, symbols)| symbols.split(',').collect())
+        .unwrap_or_default();
+    if state.batch_quotes_disabled && symbols.len() > 1 {
+        return (200, json!({ "code": 400, "message": "batch requests are not available", "status": "error" }));
+    }
+    let price = |symbol: &str| match (state.prices.get(symbol), state.books.get(symbol)) {
+        (Some(price), Some((bid, ask))) => {
+            json!({ "price": price.to_string(), "bid": bid.to_string(), "ask": ask })
+        },
+        (Some(price), None) => quote_response(*price),
+        (None, _) => json!({ "code": 400, "message": format!("symbol {} not found", symbol), "status": "error" }),
+    };
+    match symbols.as_slice() {
+        [symbol] => (200, price(symbol)),
+        symbols => (200, Value::Object(symbols.iter().map(|symbol| (symbol.to_string(), price(symbol))).collect())),
+    }
+}
+
+fn time_series(
+    state: &FixtureState,
+    query: &[(String, String)],
+    interval: &str
+) -> (u16, Value) {
+    let symbol: &str = query.iter().find(|(name, _)| name == "symbol").map(|(_, symbol)| symbol.as_str()).unwrap_or_default();
+    let candles: Vec<Candle> = state
+        .candles
+        .get(symbol)
+        .map(|candles| candles.iter().filter(|candle| candle.timeframe.interval() == interval).cloned().collect())
+        .unwrap_or_default();
+    match candles.is_empty() {
+        true => (200, json!({ "code": 400, "message": format!("no {} candles for {}", interval, symbol), "status": "error" })),
+        false => (200, time_series_response(&candles)),
+    }
+}
+
+/// Query parameters which order and page a select instead of filtering it.
+const PAGING_PARAMS: &[&str] = &["select", "order", "offset", "limit"];
+
+/// Returns a column of a row, `column->>key` reads a key of a JSON column.
+fn column_value<'a>(
+    row: &'a Value,
+    column: &str
+) -> Option<&'a Value> {
+    match column.split_once("->>") {
+        Some((column, key)) => row.get(column)?.get(key),
+        None => row.get(column),
+    }
+}
+
+/// Applies the `order`, `offset` and `limit` parameters of a select.
+fn page(
+    mut rows: Vec<Value>,
+    query: &[(String, String)]
+) -> Vec<Value> {
+    let param = |name: &str| query.iter().find(|(param, _)| param == name).map(|(_, value)| value.as_str());
+    if let Some((column, direction)) = param("order").and_then(|order| order.split_once('.')) {
+        rows.sort_by(|a, b| {
+            let (a, b) = (column_value(a, column), column_value(b, column));
+            let ordering = match (a.and_then(Value::as_f64), b.and_then(Value::as_f64)) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                _ => a.and_then(Value::as_str).cmp(&b.and_then(Value::as_str)),
+            };
+            if direction.starts_with("desc") { ordering.reverse() } else { ordering }
+        });
+    }
+    let offset: usize = param("offset").and_then(|offset| offset.parse().ok()).unwrap_or(0);
+    let limit: usize = param("limit").and_then(|limit| limit.parse().ok()).unwrap_or(usize::MAX);
+    rows.into_iter().skip(offset).take(limit).collect()
+}
+
+/// Applies a PostgREST `eq.`, `is.null`, `lt.`, `in.(...)`, `ilike.`, `wfts.` or `cs.` filter, other filters always match.
+fn matches_filter(
+    value: Option<&Value>,
+    filter: &str
+) -> bool {
+    let text = |value: &Value| match value {
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    };
+    if let Some(expected) = filter.strip_prefix("eq.") {
+        return value.is_some_and(|value| text(value) == expected);
+    }
+    if filter == "is.null" {
+        return value.is_none_or(Value::is_null);
+    }
+    if let Some(bound) = filter.strip_prefix("lt.") {
+        return value.is_some_and(|value| match (value.as_f64(), bound.parse::<f64>()) {
+            (Some(value), Ok(bound)) => value < bound,
+            // Timestamps, compared as instants since their offsets may differ
+            _ => match (DateTime::parse_from_rfc3339(&text(value)), DateTime::parse_from_rfc3339(bound)) {
+                (Ok(value), Ok(bound)) => value < bound,
+                _ => text(value).as_str() < bound,
+            },
+        });
+    }
+    if let Some(list) = filter.strip_prefix("in.(").and_then(|list| list.strip_suffix(')')) {
+        return value.is_some_and(|value| {
+            let value: String = text(value);
+            list.split(',').any(|item| item.trim_matches('"') == value)
+        });
+    }
+    if let Some(pattern) = filter.strip_prefix("ilike.") {
+        return value.is_some_and(|value| matches_pattern(&text(value).to_lowercase(), &pattern.to_lowercase()));
+    }
+    if let Some(query) = filter.strip_prefix("wfts.") {
+        return value.is_some_and(|value| {
+            let words: Vec<String> = text(value)
+                .to_lowercase()
+                .split(|c: char| !c.is_alphanumeric())
+                .map(String::from)
+                .collect();
+            query.to_lowercase().split_whitespace().all(|term| match term.strip_prefix('-') {
+                Some(term) => !words.iter().any(|word| word == term),
+                None => words.iter().any(|word| word == term),
+            })
+        });
+    }
+    if let Some(expected) = filter.strip_prefix("cs.").and_then(|list| serde_json::from_str::<Value>(list).ok()) {
+        return value.and_then(Value::as_array).is_some_and(|values| {
+            expected.as_array().is_some_and(|expected| expected.iter().all(|item| values.contains(item)))
+        });
+    }
+    true
+}
+
+/// Matches an `ilike` pattern where `*` or `%` match any characters and `\` escapes the next one.
+fn matches_pattern(
+    text: &str,
+    pattern: &str
+) -> bool {
+    let mut parts: Vec<String> = vec![String::new()];
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => parts.last_mut().expect("Parts are never empty").extend(chars.next()),
+            '*' | '%' => parts.push(String::new()),
+            c => parts.last_mut().expect("Parts are never empty").push(c),
+        }
+    }
+
+    let (first, last) = (&parts[0], &parts[parts.len() - 1]);
+    if parts.len() == 1 {
+        return text == first;
+    }
+    if !text.starts_with(first.as_str()) || !text[first.len()..].ends_with(last.as_str()) {
+        return false;
+    }
+    let mut rest: &str = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part.as_str()) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
@@ -0,0 +1,78 @@
+//! ## Health
+//!
+//! Derives the [`Health`] of a running system from its [`EngineStatus`]. A system whose next
+//! cycle is overdue by more than the stall threshold counts as stuck, e.g. because a cycle
+//! hangs on a provider, which a health check can catch before users notice missed alerts.
+
+use std::fmt;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::{EngineStatus, Health, HealthState};
+
+/// How long the next cycle can be overdue before a system counts as stuck, unless another threshold is set.
+pub const DEFAULT_STALL_THRESHOLD: Duration = Duration::from_secs(60);
+
+impl EngineStatus {
+    /// Returns the health at `now`, stuck once the next cycle is overdue by more than `stall_after`.
+    pub fn health(
+        &self,
+        now: DateTime<Utc>,
+        stall_after: Duration
+    ) -> Health {
+        let overdue: Option<Duration> = self
+            .next_run_at
+            .and_then(|next_run_at| (now - next_run_at).to_std().ok())
+            .filter(|overdue| !overdue.is_zero());
+
+        let state: HealthState = match (overdue, self.next_run_at) {
+            (Some(overdue), _) if overdue > stall_after => HealthState::Stuck,
+            (_, None) => HealthState::Stopped,
+            _ if self.consecutive_errors > 0 => HealthState::Degraded,
+            _ if self.last_run_at.is_none() => HealthState::Starting,
+            _ => HealthState::Healthy,
+        };
+
+        Health {
+            state,
+            checked_at: now,
+            last_run_at: self.last_run_at,
+            last_duration_ms: self.last_duration.map(|duration| duration.as_millis() as u64),
+            symbols_checked: self.last_symbols_checked,
+            triggered: self.last_triggered,
+            dry_run_triggered: self.last_dry_run_triggered,
+            consecutive_errors: self.consecutive_errors,
+            last_error: self.last_error.clone(),
+            cycles: self.cycles,
+            next_run_at: self.next_run_at,
+            overdue_ms: overdue.map(|overdue| overdue.as_millis() as u64),
+        }
+    }
+}
+
+impl Health {
+    /// Whether the system is starting or healthy.
+    pub fn is_healthy(&self) -> bool {
+        matches!(self.state, HealthState::Starting | HealthState::Healthy)
+    }
+}
+
+impl HealthState {
+    /// Returns the name of the state, e.g. `stuck`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthState::Starting => "starting",
+            HealthState::Healthy => "healthy",
+            HealthState::Degraded => "degraded",
+            HealthState::Stuck => "stuck",
+            HealthState::Stopped => "stopped",
+        }
+    }
+}
+
+impl fmt::Display for HealthState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
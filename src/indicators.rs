@@ -0,0 +1,88 @@
+//! ## Technical indicators
+//!
+//! Simple, exponential and relative strength indicators computed from candle closes,
+//! oldest first, as returned by [`XylexApi::request_candles`](crate::data::XylexApi::request_candles).
+
+/// Number of closes used by `Condition::RsiAbove` and `Condition::RsiBelow`.
+pub const RSI_PERIOD: usize = 14;
+
+/// Returns the simple moving average of the last `period` values.
+///
+/// # Returns
+/// Returns `None` if `period` is zero or there are fewer than `period` values.
+pub fn sma(
+    values: &[f64],
+    period: usize
+) -> Option<f64> {
+    if period == 0 || values.len() < period {
+        return None;
+    }
+    Some(values[values.len() - period..].iter().sum::<f64>() / period as f64)
+}
+
+/// Returns the exponential moving average over all values, seeded with the SMA of the first `period` values.
+///
+/// # Returns
+/// Returns `None` if `period` is zero or there are fewer than `period` values.
+pub fn ema(
+    values: &[f64],
+    period: usize
+) -> Option<f64> {
+    let seed: f64 = sma(&values[..period.min(values.len())], period)?;
+    let alpha: f64 = 2.0 / (period as f64 + 1.0);
+
+    Some(values[period..].iter().fold(seed, |ema, value| alpha * value + (1.0 - alpha) * ema))
+}
+
+/// Returns the relative strength index using Wilder's smoothing.
+///
+/// More values than `period + 1` give a smoother, more accurate result.
+///
+/// # Returns
+/// Returns a value between `0` and `100`, or `None` if `period` is zero or there are
+/// fewer than `period + 1` values.
+pub fn rsi(
+    values: &[f64],
+    period: usize
+) -> Option<f64> {
+    if period == 0 || values.len() < period + 1 {
+        return None;
+    }
+
+    let changes: Vec<f64> = values.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    let mut gain: f64 = changes[..period].iter().filter(|c| **c > 0.0).sum::<f64>() / period as f64;
+    let mut loss: f64 = changes[..period].iter().filter(|c| **c < 0.0).map(|c| -c).sum::<f64>() / period as f64;
+
+    for change in &changes[period..] {
+        gain = (gain * (period as f64 - 1.0) + change.max(0.0)) / period as f64;
+        loss = (loss * (period as f64 - 1.0) + (-change).max(0.0)) / period as f64;
+    }
+
+    if loss == 0.0 {
+        return Some(if gain == 0.0 { 50.0 } else { 100.0 });
+    }
+    Some(100.0 - 100.0 / (1.0 + gain / loss))
+}
+
+/// Checks whether the fast SMA crossed the slow SMA on the last value, in either direction.
+///
+/// # Returns
+/// Returns `false` if there are fewer than `slow + 1` values.
+pub fn sma_crossed(
+    values: &[f64],
+    fast: usize,
+    slow: usize
+) -> bool {
+    if values.len() < 2 {
+        return false;
+    }
+    let previous: &[f64] = &values[..values.len() - 1];
+
+    match (sma(previous, fast), sma(previous, slow), sma(values, fast), sma(values, slow)) {
+        (Some(fast_before), Some(slow_before), Some(fast_now), Some(slow_now)) => {
+            (fast_before - slow_before).signum() != (fast_now - slow_now).signum()
+                && fast_now != slow_now
+        },
+        _ => false,
+    }
+}
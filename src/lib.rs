@@ -160,20 +160,24 @@
 //!         Err(e) => eprintln!("{}", e),
 //!     };
 //! 
-//!     // Check and delete triggered alerts
-//!     match xylex_api.check_and_fetch_triggered_alert_hashes(
+//!     // Check, notify and delete triggered alerts
+//!     let router = NotificationRouter::new()
+//!         .with_channel("webhook", WebhookNotifier::new("https://example.com/hook".to_string()))
+//!         .with_default_channels(vec!["webhook".to_string()]);
+//!     match xylex_api.check_and_notify(
 //!         &supabase,
-//!         &config
+//!         &config,
+//!         &router
 //!     ).await {
-//!         Ok(triggered_hashes) => {
-//!             if triggered_hashes.is_empty() {
+//!         Ok(notified) => {
+//!             if notified.is_empty() {
 //!                 println!("No triggered alerts.");
 //!                 return;
 //!             }
 //!             match xylex_api.delete_triggered_alerts_by_hashes(
 //!                 &supabase,
 //!                 &config,
-//!                 triggered_hashes
+//!                 notified.into_iter().map(|(alert, _)| alert.hash).collect()
 //!             ).await {
 //!                 Ok(_) => println!("Successfully deleted triggered alerts"),
 //!                 Err(e) => eprintln!("{}", e),
@@ -222,19 +226,53 @@
 //! 
 
 
+pub mod actions;
 pub mod alert;
+pub mod backtest;
+pub mod bootstrap;
+pub mod builder;
+pub mod commands;
+pub mod condition;
+pub mod config;
 pub mod data;
 pub mod db;
+pub mod engine;
 pub mod errors;
+pub mod events;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
+pub mod health;
+pub mod indicators;
+pub mod market_hours;
+pub mod notify;
+#[cfg(feature = "replay-http")]
+pub mod replay_http;
+pub mod retry;
+pub mod scheduler;
+#[cfg(feature = "schema")]
+pub mod schema;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod shard;
 pub mod success;
+pub mod tenant;
+pub mod timeout;
+#[cfg(feature = "tui")]
+pub mod tui;
 pub mod utils;
 
 
 
 
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::market_hours::Session;
+
 /// Represents an alert for a specific user intrested in a 
 /// particular symbol at a certain price level with a unique hash.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Alert {
     /// The unique hash associated with this alert, encapsulating all its identifying components.
     pub hash: String,
@@ -244,4 +282,494 @@ pub struct Alert {
     pub user_id: String,
     /// The symbol associated with the price level for which the alert is set.
     pub symbol: String,
-}
\ No newline at end of file
+    /// The condition that decides when the alert triggers.
+    #[serde(default)]
+    pub condition: Condition,
+    /// The moment after which the alert is no longer evaluated, if any.
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// The direction (`buy` or `sell`) computed when the alert was stored, `None` for new alerts.
+    #[serde(default)]
+    pub direction: Option<String>,
+    /// Freeform data attached by the caller, e.g. a note, position id or strategy tag.
+    #[serde(default)]
+    pub metadata: Option<Value>,
+    /// When the condition is evaluated, on every tick or only at candle close.
+    #[serde(default)]
+    pub evaluate_on: EvaluateOn,
+    /// Labels to organise alerts by, e.g. the strategy (`breakout`, `earnings`, `swing`).
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// How far past the level the price must move to trigger, `None` to trigger on touch.
+    #[serde(default)]
+    pub tolerance: Option<Tolerance>,
+    /// The row version used for optimistic concurrency, `None` for new alerts.
+    #[serde(default)]
+    pub version: Option<i64>,
+    /// The second level of a `Condition::Band` alert, the band spans both levels.
+    #[serde(default)]
+    pub upper_price_level: Option<f64>,
+    /// The trading sessions the alert is evaluated in, empty to evaluate it around the clock.
+    #[serde(default)]
+    pub sessions: Vec<Session>,
+}
+
+/// Builds an `Alert` with named setters, see [`Alert::builder`].
+///
+/// The user ID, symbol and price level are required; the hash is generated from them when not set.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AlertBuilder {
+    hash: Option<String>,
+    hash_prefix: String,
+    price_level: Option<f64>,
+    user_id: Option<String>,
+    symbol: Option<String>,
+    condition: Condition,
+    expires_at: Option<DateTime<Utc>>,
+    metadata: Option<Value>,
+    evaluate_on: EvaluateOn,
+    tags: Vec<String>,
+    tolerance: Option<Tolerance>,
+    upper_price_level: Option<f64>,
+    sessions: Vec<Session>,
+}
+
+/// A ready-to-run alert system: a price provider, the alerts table and notification channels
+/// checked on a fixed interval, see [`TradeAlerts::builder`].
+#[derive(Clone)]
+pub struct TradeAlerts {
+    /// Holds the per-cycle state such as candles, volumes and the movement gate.
+    xylex: data::XylexApi,
+    provider: std::sync::Arc<dyn data::PriceProvider>,
+    supabase: db::Supabase,
+    config: db::TableConfig,
+    /// Triggered alerts are archived to this table instead of deleted, if set.
+    history_config: Option<db::TableConfig>,
+    router: notify::NotificationRouter,
+    interval: std::time::Duration,
+    /// Time between two cycles while no alert is active, if set.
+    idle_interval: Option<std::time::Duration>,
+    /// Wakes an idle system, see [`TradeAlerts::wake`].
+    wake: std::sync::Arc<tokio::sync::Notify>,
+    /// Cycles run at the minutes matching it instead of every interval, if set.
+    cron: Option<utils::cron::CronExpression>,
+    /// Shared with the handle, so the status can be read while the system runs.
+    status: std::sync::Arc<std::sync::Mutex<EngineStatus>>,
+    /// File the status is written to after every cycle, if set.
+    status_path: Option<std::path::PathBuf>,
+    /// File the price state is written to after every cycle, if set.
+    price_snapshot_path: Option<std::path::PathBuf>,
+    /// Table and instance ID the health is written to after every cycle, if set.
+    heartbeat: Option<(db::HeartbeatTable, String)>,
+    /// Table and instance ID of the leader lease cycles only run under, if set.
+    lease: Option<(db::LeaseTable, String)>,
+    /// How long the leader lease lasts without being renewed.
+    lease_ttl: std::time::Duration,
+    /// Lease table, assignment table and worker ID of the symbols shard cycles are restricted to, if set.
+    sharding: Option<(db::LeaseTable, db::ShardTable, String)>,
+    /// The symbols this worker last recorded as its own.
+    assigned_symbols: std::sync::Arc<std::sync::Mutex<std::collections::BTreeSet<String>>>,
+    /// Every symbol of the alerts table when sharded, and when it was last fetched.
+    known_symbols: std::sync::Arc<std::sync::Mutex<engine::KnownSymbols>>,
+    /// Whether triggered alerts are claimed before they are delivered.
+    two_phase_triggers: bool,
+    /// Triggered alerts are claimed under it, the ID of the leader lease or sharded worker if set.
+    instance_id: String,
+    /// Draws a chart of every triggered alert, uploaded to the bucket, if set.
+    charts: Option<(std::sync::Arc<dyn notify::ChartRenderer>, db::StorageBucket)>,
+    /// Triggered alerts are also delivered to the users they are shared with, if set.
+    shares: Option<db::SharedAlertTable>,
+    /// When the charts older than the retention of the bucket were last deleted.
+    last_chart_cleanup: std::sync::Arc<std::sync::Mutex<Option<std::time::Instant>>>,
+    /// Every delivered alert is sent to the subscribers, see [`TradeAlerts::subscribe`].
+    events: tokio::sync::broadcast::Sender<TriggeredAlert>,
+    /// How long the next cycle can be overdue before the system counts as stuck.
+    stall_after: std::time::Duration,
+}
+
+/// Snapshot of the cycles run by a [`TradeAlerts`] system, for health endpoints and UIs.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EngineStatus {
+    /// When the last cycle started.
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// How long the last cycle took.
+    pub last_duration: Option<std::time::Duration>,
+    /// When the next cycle is scheduled, `None` while the system is not running.
+    pub next_run_at: Option<DateTime<Utc>>,
+    /// Number of cycles that failed in a row, reset by a successful cycle.
+    pub consecutive_errors: u32,
+    /// The error of the last failed cycle.
+    pub last_error: Option<String>,
+    /// Number of cycles run, including those of the run the status file was left by.
+    pub cycles: u64,
+    /// Number of symbols quoted by the last cycle.
+    pub last_symbols_checked: usize,
+    /// Number of alerts triggered by the last cycle, `0` in a dry run.
+    pub last_triggered: usize,
+    /// Number of alerts the last cycle would have triggered in a dry run, `0` otherwise.
+    pub last_dry_run_triggered: usize,
+    /// Whether this instance held the leader lease on its last tick, `None` without a lease.
+    pub is_leader: Option<bool>,
+    /// Number of live workers on the last tick, `None` when not sharded.
+    pub shard_workers: Option<usize>,
+    /// Number of symbols this worker checked on the last tick, `None` when not sharded.
+    pub shard_symbols: Option<usize>,
+    /// Whether the last cycle found no active alert, so cycles run at the idle interval.
+    pub idle: bool,
+}
+
+/// Whether a [`TradeAlerts`] system keeps checking alerts, see [`Health`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthState {
+    /// Running, no cycle finished yet.
+    Starting,
+    /// The last cycle succeeded and the next one is on time.
+    Healthy,
+    /// The last cycle failed, the next one is on time.
+    Degraded,
+    /// The next cycle is overdue by more than the stall threshold, e.g. a cycle hangs.
+    Stuck,
+    /// Not running.
+    Stopped,
+}
+
+/// Health of a [`TradeAlerts`] system, for monitoring and heartbeat rows, see [`SchedulerHandle::health`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Health {
+    pub state: HealthState,
+    /// When the health was taken.
+    pub checked_at: DateTime<Utc>,
+    /// When the last cycle started.
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// How long the last cycle took, in milliseconds.
+    pub last_duration_ms: Option<u64>,
+    /// Number of symbols quoted by the last cycle.
+    pub symbols_checked: usize,
+    /// Number of alerts triggered by the last cycle, `0` in a dry run.
+    pub triggered: usize,
+    /// Number of alerts the last cycle would have triggered in a dry run.
+    pub dry_run_triggered: usize,
+    /// Number of cycles that failed in a row.
+    pub consecutive_errors: u32,
+    /// The error of the last failed cycle.
+    pub last_error: Option<String>,
+    /// Number of cycles run.
+    pub cycles: u64,
+    /// When the next cycle is scheduled.
+    pub next_run_at: Option<DateTime<Utc>>,
+    /// How long the next cycle is overdue, in milliseconds.
+    pub overdue_ms: Option<u64>,
+}
+
+/// Wires the parts of a [`TradeAlerts`] system together.
+#[derive(Clone, Default)]
+pub struct TradeAlertsBuilder {
+    xylex: Option<data::XylexApi>,
+    provider: Option<std::sync::Arc<dyn data::PriceProvider>>,
+    supabase: Option<db::Supabase>,
+    config: Option<db::TableConfig>,
+    history_config: Option<db::TableConfig>,
+    router: notify::NotificationRouter,
+    /// Added with [`TradeAlertsBuilder::with_notifier`], registered on the router on build.
+    notifiers: Vec<(String, std::sync::Arc<dyn notify::Notifier>)>,
+    grouped: bool,
+    interval: Option<std::time::Duration>,
+    idle_interval: Option<std::time::Duration>,
+    cron: Option<String>,
+    resource_limits: Option<ResourceLimits>,
+    heartbeat: Option<(db::HeartbeatTable, String)>,
+    lease: Option<(db::LeaseTable, String)>,
+    lease_ttl: Option<std::time::Duration>,
+    sharding: Option<(db::LeaseTable, db::ShardTable, String)>,
+    charts: Option<(std::sync::Arc<dyn notify::ChartRenderer>, db::StorageBucket)>,
+    shares: Option<db::SharedAlertTable>,
+    /// Set by an [`AlertScheduler`], so it can be subscribed to before it runs.
+    events: Option<tokio::sync::broadcast::Sender<TriggeredAlert>>,
+    stall_after: Option<std::time::Duration>,
+    two_phase_triggers: bool,
+    dry_run: bool,
+    price_snapshot_path: Option<std::path::PathBuf>,
+    warm_start: Option<data::PriceSnapshot>,
+    snapshot_max_age: Option<std::time::Duration>,
+    status_path: Option<std::path::PathBuf>,
+    intent_log: Option<notify::IntentLog>,
+    schedule: Option<ScheduleConfig>,
+}
+
+/// A running [`TradeAlerts`] system, stopped with [`TradeAlertsHandle::stop`].
+pub struct TradeAlertsHandle {
+    status: std::sync::Arc<std::sync::Mutex<EngineStatus>>,
+    stall_after: std::time::Duration,
+    wake: std::sync::Arc<tokio::sync::Notify>,
+    stop: tokio::sync::watch::Sender<bool>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+/// Runs the cycles of a [`TradeAlerts`] system on a background task until its
+/// cancellation token is cancelled, see [`AlertScheduler::run`].
+#[derive(Clone)]
+pub struct AlertScheduler {
+    builder: TradeAlertsBuilder,
+    cancel: tokio_util::sync::CancellationToken,
+}
+
+/// A running [`AlertScheduler`], see [`SchedulerHandle::shutdown`].
+pub struct SchedulerHandle {
+    cancel: tokio_util::sync::CancellationToken,
+    status: std::sync::Arc<std::sync::Mutex<EngineStatus>>,
+    stall_after: std::time::Duration,
+    events: tokio::sync::broadcast::Sender<TriggeredAlert>,
+    wake: std::sync::Arc<tokio::sync::Notify>,
+    task: tokio::task::JoinHandle<ShutdownSummary>,
+}
+
+/// What a [`TradeAlerts`] system was doing when it stopped, see [`SchedulerHandle::shutdown`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShutdownSummary {
+    /// Whether a cycle was running when the shutdown was requested, it was finished first.
+    pub cycle_in_flight: bool,
+    /// Hashes of the alerts the in-flight cycle triggered, notified and archived or deleted before stopping.
+    pub drained: Vec<String>,
+    /// How long the shutdown waited for the system to stop.
+    pub waited: std::time::Duration,
+    /// The status after the last cycle.
+    pub status: EngineStatus,
+}
+
+/// Largest number of entries of the caches kept across checks, see [`TradeAlertsBuilder::with_resource_limits`].
+///
+/// Caches evict their least recently used entries once full, see [`utils::lru`]. Unset limits
+/// leave the cache unbounded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// Entries of the per-symbol caches: previous prices, volumes, the movement gate and the last quotes.
+    ///
+    /// Keep it above the number of symbols with active alerts: a symbol whose previous price
+    /// was evicted has nothing to compare against, so band crossings during its next check are
+    /// missed. Cycles checking more symbols than this log a warning.
+    pub max_symbols: Option<usize>,
+    /// Entries of the open and completed candles, one per symbol and timeframe.
+    pub max_candles: Option<usize>,
+    /// Pseudonyms remembered for reverse lookups, see [`utils::pseudonym::Pseudonymizer`].
+    pub max_pseudonyms: Option<usize>,
+    /// Alerts whose last evaluation time is remembered, see [`TradeAlerts::get_alert_status`].
+    pub max_alerts: Option<usize>,
+}
+
+/// Everything known about a stored alert, answering "why hasn't this fired?", see [`TradeAlerts::get_alert_status`].
+///
+/// Merges the row in the alerts table with the state the running system keeps about it, so the
+/// engine fields only reflect the cycles of this process.
+/// Alerts have no enabled or snoozed flag: disabling an alert, see [`db::AlertOp::Disable`],
+/// expires it, which `expired` reports.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AlertStatus {
+    /// The alert as stored.
+    pub alert: Alert,
+    /// Whether the next cycle evaluates the alert: it is not expired, in one of its sessions,
+    /// its market is open, its symbol is due and it is not being delivered.
+    pub active: bool,
+    /// Whether the alert expired, which includes disabled alerts.
+    pub expired: bool,
+    /// Whether the current time is in one of the alert's trading sessions, always `true` without sessions.
+    pub in_session: bool,
+    /// Whether the market of the alert's symbol is open, always `true` without market hours, see [`data::XylexApi::with_market_hours`].
+    pub market_open: bool,
+    /// Whether the interval group of the alert's symbol is due, always `true` without a schedule, see [`TradeAlertsBuilder::with_schedule`].
+    pub scheduled: bool,
+    /// Whether the alert triggered and is being delivered, see [`TradeAlertsBuilder::with_two_phase_triggers`].
+    pub triggering: bool,
+    /// Whether a crossing seen during a provider switch awaits confirmation by the next check.
+    pub awaiting_confirmation: bool,
+    /// When a cycle last evaluated the alert.
+    pub last_evaluated_at: Option<DateTime<Utc>>,
+    /// The price of the alert's symbol at the last check.
+    pub last_price: Option<f64>,
+    /// The price level minus the last price, positive for levels above it.
+    pub distance: Option<f64>,
+    /// The distance in percent of the last price.
+    pub distance_percent: Option<f64>,
+    /// Notifications of the alert which were recorded in the intent log but not completed.
+    pub pending_notifications: Vec<notify::Intent>,
+}
+
+/// When the cycles of a [`TradeAlerts`] system run, see [`TradeAlertsBuilder::with_cycle_schedule`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Schedule {
+    /// Every interval, the first cycle immediately.
+    Interval(std::time::Duration),
+    /// At the minutes matching a cron expression, e.g. `"*/5 9-17 * * MON-FRI"`, see [`utils::cron`].
+    Cron(String),
+}
+
+/// Check intervals per symbol and asset class, see [`AlertScheduler::with_schedule`].
+///
+/// Symbols are bucketed into groups by their interval, and each cycle only fetches the
+/// groups which are due. Cloning a `ScheduleConfig` shares when each group was last checked.
+#[derive(Clone, Debug)]
+pub struct ScheduleConfig {
+    /// Interval of symbols without a more specific one.
+    pub default_interval: std::time::Duration,
+    /// Intervals of asset classes, see [`market_hours::AssetClass::of`], over the default interval.
+    pub asset_classes: std::collections::HashMap<market_hours::AssetClass, std::time::Duration>,
+    /// Intervals of single symbols, keyed by canonical symbol.
+    pub symbols: std::collections::HashMap<String, std::time::Duration>,
+    /// When each interval group was last checked.
+    last_checked: std::sync::Arc<std::sync::Mutex<std::collections::HashMap<std::time::Duration, std::time::Instant>>>,
+}
+
+/// An alert whose condition was met during a check, as handed to whatever consumes triggers.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TriggeredAlert {
+    /// The unique hash of the alert.
+    pub hash: String,
+    /// The user who owns the alert.
+    pub user_id: String,
+    /// The symbol the alert is set on.
+    pub symbol: String,
+    /// The price level of the alert.
+    pub price_level: f64,
+    /// The price that triggered the alert, which can gap past the level.
+    pub trigger_price: f64,
+    /// The condition that was met.
+    #[serde(default)]
+    pub condition: Condition,
+    /// The metadata attached to the alert.
+    #[serde(default)]
+    pub metadata: Option<Value>,
+    /// When the alert triggered.
+    #[serde(default)]
+    pub triggered_at: DateTime<Utc>,
+    /// When the provider quoted the trigger price, if it says so.
+    #[serde(default)]
+    pub quote_time: Option<DateTime<Utc>>,
+}
+
+/// A partial update of an existing alert, only the fields that are `Some` are changed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AlertUpdate {
+    /// The new price level.
+    pub price_level: Option<f64>,
+    /// The new symbol.
+    pub symbol: Option<String>,
+    /// The new expiry, `Some(None)` removes the expiry so the alert never expires.
+    pub expires_at: Option<Option<DateTime<Utc>>>,
+    /// The new initial direction (`buy` or `sell`).
+    pub direction: Option<String>,
+    /// The new metadata.
+    pub metadata: Option<Value>,
+}
+
+/// The condition an alert is evaluated against on every check.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum Condition {
+    /// Triggers when the price crosses the alert's price level,
+    /// in the opposite direction of where the price was when the alert was created.
+    #[default]
+    PriceLevel,
+    /// Triggers when the bid/ask spread widens above the given number of pips.
+    SpreadAbove(f64),
+    /// Triggers once the given time has passed, regardless of price.
+    At(DateTime<Utc>),
+    /// Triggers when the price is above the given level.
+    PriceAbove(f64),
+    /// Triggers when the price is below the given level.
+    PriceBelow(f64),
+    /// Triggers when the traded volume reported with the quote is above the given amount.
+    VolumeAbove(f64),
+    /// Triggers when the traded volume exceeds a multiple of its average over recent checks.
+    VolumeSpike {
+        multiple_of_average: f64,
+    },
+    /// Triggers when the 14 period RSI of the candle closes is above the given value.
+    RsiAbove(f64),
+    /// Triggers when the 14 period RSI of the candle closes is below the given value.
+    RsiBelow(f64),
+    /// Triggers when the fast SMA crosses the slow SMA of the candle closes, in either direction.
+    SmaCross {
+        fast: usize,
+        slow: usize,
+    },
+    /// Triggers when a compound expression over one or more symbols holds.
+    Compound(ConditionTree),
+    /// Triggers on the price relative to the band between the alert's price level and
+    /// its upper price level, both inclusive.
+    Band(BandMode),
+}
+
+/// When a `Condition::Band` alert triggers, relative to the band.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum BandMode {
+    /// The price moved into the band since the previous check.
+    Enter,
+    /// The price moved out of the band since the previous check.
+    Exit,
+    /// The price is inside the band.
+    Inside,
+    /// The price is outside the band.
+    Outside,
+}
+
+/// A compound condition, e.g. "EUR/USD above 1.10 AND GBP/USD below 1.25".
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ConditionTree {
+    /// A condition evaluated against the quote of a single symbol.
+    Leaf {
+        symbol: String,
+        condition: Box<Condition>,
+    },
+    /// Holds when every child holds.
+    All(Vec<ConditionTree>),
+    /// Holds when at least one child holds.
+    Any(Vec<ConditionTree>),
+}
+
+/// When an alert's condition is evaluated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum EvaluateOn {
+    /// Evaluated against every fetched price.
+    #[default]
+    Tick,
+    /// Only evaluated against the close of a candle of the given timeframe,
+    /// so intrabar spikes don't trigger the alert.
+    CandleClose(Timeframe),
+}
+
+/// How far past its level the price must move before a price alert triggers,
+/// so a price merely touching the level does not fire it.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum Tolerance {
+    /// A distance in pips, see [`data::Quote::pip_size`].
+    Pips(f64),
+    /// A distance in percent of the level.
+    Percent(f64),
+}
+
+/// Candle timeframes supported by `EvaluateOn::CandleClose`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Timeframe {
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "1d")]
+    OneDay,
+    /// Calendar weeks, from Monday 00:00 UTC.
+    #[serde(rename = "1w")]
+    OneWeek,
+    /// Calendar months, from the first of the month 00:00 UTC.
+    #[serde(rename = "1mo")]
+    OneMonth,
+}
@@ -1,60 +1,328 @@
-#![allow(unused_imports)]
-#![allow(unused_variables)]
-
+//! ## Command line tool for trade alerts
+//!
+//! `list` and `status` support `--output json|table|csv`. Field names are stable across
+//! releases so the output can be scripted.
+//!
+//! ### Exit codes
+//! - `0`: Success.
+//! - `2`: Invalid arguments.
+//! - `3`: Missing or invalid configuration.
+//! - `4`: The database or the price provider returned an error.
 
 use std::env::var;
-use dotenv::dotenv;
-use anyhow::{Error, Result, anyhow};
+use std::process::ExitCode;
 
+use anyhow::{Error, Result, anyhow};
+use clap::{Parser, Subcommand};
+use dotenv::dotenv;
+use serde_json::{Value, json};
 
-use trade_alerts::{Alert, db::TableConfig, db::Supabase, db::auth, db::client};
+use trade_alerts::{Alert, TriggeredAlert};
 use trade_alerts::data::XylexApi;
+use trade_alerts::db::{Supabase, TableConfig};
+use trade_alerts::utils::output::OutputFormat;
 
-#[tokio::main]
-async fn main() {
-    let hash: String = "1234".to_string();
-    let price_level: f64 = 1.09;
-    let symbol: String = "EURUSD".to_string();
-    let user_id: String = "1234".to_string();
+const EXIT_USAGE: u8 = 2;
+const EXIT_CONFIGURATION: u8 = 3;
+const EXIT_BACKEND: u8 = 4;
+
+const ALERT_COLUMNS: &[&str] = &[
+    "hash", "user_id", "symbol", "price_level", "upper_price_level", "direction", "condition", "evaluate_on",
+    "tolerance", "expires_at", "metadata", "tags", "sessions",
+];
+const TRIGGERED_COLUMNS: &[&str] = &[
+    "hash", "user_id", "symbol", "price_level", "trigger_price", "triggered_at", "condition", "metadata",
+];
 
-    let trade_alert: Alert = Alert::new(hash, price_level, symbol, user_id);
+#[derive(Parser)]
+#[command(name = "trade_alerts", about = "Create and manage trade alerts")]
+struct Cli {
+    /// Output format: json, table or csv.
+    #[arg(long, global = true, default_value = "table")]
+    output: OutputFormat,
 
-    let table_config: TableConfig = TableConfig::default();
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Lists alerts, optionally only those of a single user.
+    List {
+        #[arg(long)]
+        user: Option<String>,
+    },
+    /// Checks all alerts against the latest prices and lists the triggered ones.
+    Status {
+        /// Also show a desktop notification for every triggered alert.
+        #[cfg(feature = "desktop")]
+        #[arg(long)]
+        notify: bool,
+    },
+    /// Serves the inbound command webhook for Slack (`POST /slack`) and Discord (`POST /discord`).
+    #[cfg(feature = "server")]
+    Serve {
+        #[arg(long, default_value = "0.0.0.0:8080")]
+        addr: String,
+    },
+    /// Opens a terminal dashboard which checks the alerts every `interval`, e.g. `30s` or `1m`.
+    ///
+    /// The engine's logs are appended to `log_file` while the dashboard is open.
+    #[cfg(feature = "tui")]
+    Monitor {
+        #[arg(long, default_value = "10s", value_parser = trade_alerts::config::parse_duration)]
+        interval: std::time::Duration,
+        #[arg(long, default_value = "trade_alerts_monitor.log")]
+        log_file: std::path::PathBuf,
+    },
+}
 
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli: Cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            return if e.use_stderr() { ExitCode::from(EXIT_USAGE) } else { ExitCode::SUCCESS };
+        }
+    };
 
     let (supabase_key, supabase_url) = match inject_token() {
         Ok((key, url)) => (key, url),
         Err(e) => {
             eprintln!("Failed to inject token: {}", e);
-            return;
+            return ExitCode::from(EXIT_CONFIGURATION);
         }
     };
 
     let supabase: Supabase = Supabase::new(supabase_key, supabase_url);
+    let table_config: TableConfig = match TableConfig::new_env() {
+        Ok(table_config) => table_config,
+        Err(e) => {
+            eprintln!("Invalid table configuration: {}", e);
+            return ExitCode::from(EXIT_CONFIGURATION);
+        }
+    };
 
+    match cli.command {
+        Command::List { user } => {
+            let alerts: Result<Vec<Alert>, String> = match user {
+                Some(user_id) => supabase
+                    .fetch_alerts_by_user_id(&user_id, &table_config)
+                    .await
+                    .map_err(|e| e.to_string()),
+                None => fetch_all_alerts(&supabase, &table_config).await,
+            };
 
-    // match supabase.add_alert(trade_alert.clone(), table_config.clone()).await {
-    //     Ok(_) => println!("\x1b[32mAlert added successfully.\x1b[0m"),
-    //     Err(e) => eprintln!("\x1b[31mFailed to add alert: {}\x1b[0m", e),
-    // }
-    // println!("Trade alert: {:#?}", trade_alert);
+            match alerts {
+                Ok(alerts) => {
+                    let rows: Vec<Value> = alerts.iter().map(alert_row).collect();
+                    print!("{}", cli.output.render(ALERT_COLUMNS, &rows));
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("Failed to fetch alerts: {}", e);
+                    ExitCode::from(EXIT_BACKEND)
+                }
+            }
+        }
+        Command::Status {
+            #[cfg(feature = "desktop")]
+            notify,
+        } => {
+            let xylex_api: XylexApi = match XylexApi::new_env().await {
+                Ok(api) => api,
+                Err(e) => {
+                    eprintln!("Failed to configure price provider: {}", e);
+                    return ExitCode::from(EXIT_CONFIGURATION);
+                }
+            };
 
+            match xylex_api.check_and_fetch_triggered_alerts(&supabase, &table_config).await {
+                Ok(triggered) => {
+                    #[cfg(feature = "desktop")]
+                    match (notify, xylex_api.dry_run) {
+                        (true, true) => eprintln!("Dry run, not showing desktop notifications"),
+                        (true, false) => notify_desktop(&triggered).await,
+                        (false, _) => {},
+                    }
 
-    let xylex_api_config: XylexApi = XylexApi::new(
-        "123".to_string(),
-        "https://api.xylex.cfd/data/realtime/price".to_string()
-    );
+                    let rows: Vec<Value> = triggered.iter().map(triggered_row).collect();
+                    print!("{}", cli.output.render(TRIGGERED_COLUMNS, &rows));
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("Failed to fetch triggered alerts: {}", e);
+                    ExitCode::from(EXIT_BACKEND)
+                }
+            }
+        }
+        #[cfg(feature = "server")]
+        Command::Serve { addr } => {
+            let xylex_api: XylexApi = match XylexApi::new_env().await {
+                Ok(api) => api,
+                Err(e) => {
+                    eprintln!("Failed to configure price provider: {}", e);
+                    return ExitCode::from(EXIT_CONFIGURATION);
+                }
+            };
+            let mut server = trade_alerts::server::CommandServer::new(supabase, table_config, std::sync::Arc::new(xylex_api));
+            if let Ok(secret) = var("SLACK_SIGNING_SECRET") {
+                server = server.with_slack_signing_secret(secret);
+            }
+            if let Ok(public_key) = var("DISCORD_PUBLIC_KEY") {
+                server = match server.with_discord_public_key(&public_key) {
+                    Ok(server) => server,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return ExitCode::from(EXIT_CONFIGURATION);
+                    }
+                };
+            }
+            if server.slack_signing_secret.is_none() && server.discord_public_key.is_none() {
+                eprintln!("Set SLACK_SIGNING_SECRET or DISCORD_PUBLIC_KEY, unverified commands are never accepted");
+                return ExitCode::from(EXIT_CONFIGURATION);
+            }
 
-    match xylex_api_config.check_and_fetch_triggered_alert_hashes(&supabase, &table_config).await {
-        Ok(alerts) => println!("\x1b[32mTriggered alerts: {:?}\x1b[0m", alerts),
-        Err(e) => eprintln!("\x1b[31mFailed to fetch triggered alerts: {}\x1b[0m", e),
+            let listener = match tokio::net::TcpListener::bind(&addr).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    eprintln!("Failed to bind {}: {}", addr, e);
+                    return ExitCode::from(EXIT_CONFIGURATION);
+                }
+            };
+            match axum::serve(listener, server.router()).await {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Server failed: {}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        #[cfg(feature = "tui")]
+        Command::Monitor { interval, log_file } => {
+            let xylex_api: XylexApi = match XylexApi::new_env().await {
+                Ok(api) => api,
+                Err(e) => {
+                    eprintln!("Failed to configure price provider: {}", e);
+                    return ExitCode::from(EXIT_CONFIGURATION);
+                }
+            };
+
+            match monitor(xylex_api, supabase, table_config, interval, &log_file).await {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Monitor failed: {}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
     }
+}
 
+#[cfg(feature = "tui")]
+async fn monitor(
+    xylex_api: XylexApi,
+    supabase: Supabase,
+    table_config: TableConfig,
+    interval: std::time::Duration,
+    log_file: &std::path::Path
+) -> std::io::Result<()> {
+    use trade_alerts::tui::{self, MonitorEvent};
+
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    let config: TableConfig = table_config.clone();
+    // The engine logs to stdout, which would draw over the dashboard
+    #[cfg(unix)]
+    let (redirect, output) = {
+        let redirect = tui::log::LogRedirect::to_file(log_file)?;
+        let output = redirect.terminal()?;
+        (redirect, output)
+    };
+    #[cfg(not(unix))]
+    let output = {
+        let _ = log_file;
+        std::io::stdout()
+    };
+    let ui = tokio::task::spawn_blocking(move || tui::app::run_on(receiver, config, output));
 
+    let cycles = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval.max(std::time::Duration::from_secs(1)));
+        loop {
+            ticker.tick().await;
+            let event: MonitorEvent = match xylex_api.check_alerts(&supabase, &table_config).await {
+                Ok(report) => MonitorEvent::Cycle(report),
+                Err(e) => MonitorEvent::CycleFailed(e.to_string()),
+            };
+            if sender.send(event).is_err() {
+                break;
+            }
+            if let Some(metrics) = xylex_api.budget_metrics() {
+                let _ = sender.send(MonitorEvent::Budget(metrics));
+            }
+        }
+    });
 
-    println!("Hello, world!");
+    let result = ui.await.unwrap_or_else(|e| Err(std::io::Error::other(e)));
+    cycles.abort();
+    #[cfg(unix)]
+    drop(redirect);
+    result
 }
 
+#[cfg(feature = "desktop")]
+async fn notify_desktop(triggered: &[TriggeredAlert]) {
+    use trade_alerts::notify::{DesktopNotifier, Notifier};
+
+    let notifier: DesktopNotifier = DesktopNotifier::new();
+    for alert in triggered {
+        if let Err(e) = notifier.notify(alert).await {
+            eprintln!("Failed to show desktop notification for {}: {}", alert.hash, e);
+        }
+    }
+}
+
+async fn fetch_all_alerts(
+    supabase: &Supabase,
+    config: &TableConfig
+) -> Result<Vec<Alert>, String> {
+    let rows = supabase.fetch_all_data(config).await.map_err(|e| e.to_string())?;
+
+    rows.into_iter()
+        .map(|row| Alert::from_row(&Value::Object(row.into_iter().collect()), config).map_err(|e| e.to_string()))
+        .collect()
+}
+
+fn alert_row(alert: &Alert) -> Value {
+    json!({
+        "hash": alert.hash,
+        "user_id": alert.user_id,
+        "symbol": alert.symbol,
+        "price_level": alert.price_level,
+        "upper_price_level": alert.upper_price_level,
+        "direction": alert.direction,
+        "condition": alert.condition.to_value(),
+        "evaluate_on": alert.evaluate_on.to_value(),
+        "tolerance": alert.tolerance.map(|tolerance| tolerance.to_value()),
+        "expires_at": alert.expires_at.map(|expires_at| expires_at.to_rfc3339()),
+        "metadata": alert.metadata,
+        "tags": alert.tags,
+        "sessions": alert.sessions,
+    })
+}
+
+fn triggered_row(alert: &TriggeredAlert) -> Value {
+    json!({
+        "hash": alert.hash,
+        "user_id": alert.user_id,
+        "symbol": alert.symbol,
+        "price_level": alert.price_level,
+        "trigger_price": alert.trigger_price,
+        "triggered_at": alert.triggered_at.to_rfc3339(),
+        "condition": alert.condition.to_value(),
+        "metadata": alert.metadata,
+    })
+}
 
 pub fn inject_token() -> Result<(String, String), Error> {
     dotenv().ok();
@@ -63,4 +331,4 @@ pub fn inject_token() -> Result<(String, String), Error> {
     let supabase_url: String = var("SUPABASE_URL").map_err(|_| anyhow!("SUPABASE_URL must be set"))?;
 
     Ok((supabase_key, supabase_url))
-}
\ No newline at end of file
+}
@@ -0,0 +1,272 @@
+//! ## Market hours
+//!
+//! Trading hours per asset class and the main FX trading sessions, so checks can skip
+//! markets which are closed and alerts can be limited to sessions such as London.
+//!
+//! | Asset class | Open (local time)                                  |
+//! |-------------|----------------------------------------------------|
+//! | `Forex`     | Sunday 17:00 to Friday 17:00 New York              |
+//! | `Crypto`    | Always                                             |
+//! | `Equity`    | Monday to Friday, 09:30 to 16:00 New York          |
+//!
+//! Daylight saving time is followed for New York, London and Sydney. Exchange holidays are not known.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, Timelike, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::utils::symbol::{Symbol, canonical};
+
+/// Currencies traded as FX pairs.
+const FIAT_CURRENCIES: &[&str] = &[
+    "AUD", "CAD", "CHF", "CNH", "CZK", "DKK", "EUR", "GBP", "HKD", "HUF", "JPY", "MXN", "NOK", "NZD", "PLN",
+    "SEK", "SGD", "TRY", "USD", "ZAR",
+];
+
+/// ## The kind of market a symbol trades on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AssetClass {
+    /// Currency pairs, traded around the clock on weekdays.
+    Forex,
+    /// Crypto currencies, traded 24/7.
+    Crypto,
+    /// Stocks and ETFs, traded during US exchange hours.
+    Equity,
+}
+
+/// ## A trading session of the FX market
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Session {
+    /// 07:00 to 16:00 Sydney time.
+    Sydney,
+    /// 09:00 to 18:00 Tokyo time.
+    Tokyo,
+    /// 08:00 to 17:00 London time.
+    London,
+    /// 08:00 to 17:00 New York time.
+    NewYork,
+}
+
+/// ## Market hours of the symbols of a check
+///
+/// Symbols are classified with [`AssetClass::of`] unless overridden.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MarketHours {
+    /// Asset classes of symbols the heuristic gets wrong, keyed by canonical symbol.
+    pub overrides: HashMap<String, AssetClass>,
+}
+
+#[derive(Clone, Copy)]
+enum Zone {
+    NewYork,
+    London,
+    Tokyo,
+    Sydney,
+}
+
+impl AssetClass {
+    /// Guesses the asset class of a symbol.
+    ///
+    /// Pairs of two fiat currencies are `Forex`, other pairs `Crypto` and single tickers `Equity`.
+    pub fn of(symbol: &str) -> Self {
+        let Ok(symbol) = Symbol::parse(symbol) else {
+            return AssetClass::Equity;
+        };
+        match symbol.quote() {
+            None => AssetClass::Equity,
+            Some(quote) if FIAT_CURRENCIES.contains(&symbol.base()) && FIAT_CURRENCIES.contains(&quote) => {
+                AssetClass::Forex
+            },
+            Some(_) => AssetClass::Crypto,
+        }
+    }
+
+    /// Returns `true` if the market is open at `at`.
+    pub fn is_open(&self, at: DateTime<Utc>) -> bool {
+        match self {
+            AssetClass::Crypto => true,
+            AssetClass::Forex => {
+                let local: DateTime<FixedOffset> = Zone::NewYork.local(at);
+                let after_close: bool = local.hour() >= 17;
+                match local.weekday() {
+                    Weekday::Sat => false,
+                    Weekday::Fri => !after_close,
+                    Weekday::Sun => after_close,
+                    _ => true,
+                }
+            },
+            AssetClass::Equity => {
+                let local: DateTime<FixedOffset> = Zone::NewYork.local(at);
+                is_weekday(local.weekday()) && within(local.time(), (9, 30), (16, 0))
+            },
+        }
+    }
+}
+
+impl Session {
+    /// Every session, in the order they open.
+    pub const ALL: [Session; 4] = [Session::Sydney, Session::Tokyo, Session::London, Session::NewYork];
+
+    /// Returns `true` if the session is running at `at`, sessions only run on their local weekdays.
+    pub fn is_active(&self, at: DateTime<Utc>) -> bool {
+        let (zone, open, close) = match self {
+            Session::Sydney => (Zone::Sydney, (7, 0), (16, 0)),
+            Session::Tokyo => (Zone::Tokyo, (9, 0), (18, 0)),
+            Session::London => (Zone::London, (8, 0), (17, 0)),
+            Session::NewYork => (Zone::NewYork, (8, 0), (17, 0)),
+        };
+        let local: DateTime<FixedOffset> = zone.local(at);
+        is_weekday(local.weekday()) && within(local.time(), open, close)
+    }
+
+    /// Returns the sessions running at `at`.
+    pub fn active(at: DateTime<Utc>) -> Vec<Session> {
+        Self::ALL.into_iter().filter(|session| session.is_active(at)).collect()
+    }
+
+    /// Returns the name used in alert rows, e.g. `new_york`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Session::Sydney => "sydney",
+            Session::Tokyo => "tokyo",
+            Session::London => "london",
+            Session::NewYork => "new_york",
+        }
+    }
+}
+
+impl MarketHours {
+    /// Creates market hours classifying every symbol with [`AssetClass::of`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overrides the asset class of a symbol.
+    pub fn with_asset_class(
+        mut self,
+        symbol: &str,
+        asset_class: AssetClass
+    ) -> Self {
+        self.overrides.insert(canonical(symbol), asset_class);
+        self
+    }
+
+    /// Returns the asset class of a symbol.
+    pub fn asset_class(&self, symbol: &str) -> AssetClass {
+        self.overrides.get(&canonical(symbol)).copied().unwrap_or_else(|| AssetClass::of(symbol))
+    }
+
+    /// Returns `true` if the market of `symbol` is open at `at`.
+    pub fn is_open(
+        &self,
+        symbol: &str,
+        at: DateTime<Utc>
+    ) -> bool {
+        self.asset_class(symbol).is_open(at)
+    }
+}
+
+/// Converts `at` to New York time, where US equities trade.
+pub fn new_york_time(at: DateTime<Utc>) -> DateTime<FixedOffset> {
+    Zone::NewYork.local(at)
+}
+
+/// Parses the sessions of an alert row, unknown session names are ignored.
+pub fn sessions_from_value(value: Option<&Value>) -> Vec<Session> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|sessions| sessions.iter().filter_map(|v| v.as_str()).filter_map(|v| v.parse().ok()).collect())
+        .unwrap_or_default()
+}
+
+/// Returns `true` if no sessions are required or one of them is running at `at`.
+pub fn in_sessions(
+    sessions: &[Session],
+    at: DateTime<Utc>
+) -> bool {
+    sessions.is_empty() || sessions.iter().any(|session| session.is_active(at))
+}
+
+impl Zone {
+    /// Converts `at` to local time, following daylight saving time.
+    fn local(self, at: DateTime<Utc>) -> DateTime<FixedOffset> {
+        let year: i32 = at.year();
+        let hours: i32 = match self {
+            Zone::Tokyo => 9,
+            Zone::NewYork => {
+                // 02:00 local on the second Sunday of March to the first Sunday of November
+                let start = utc(nth_sunday(year, 3, 2), 7);
+                let end = utc(nth_sunday(year, 11, 1), 6);
+                if at >= start && at < end { -4 } else { -5 }
+            },
+            Zone::London => {
+                // 01:00 UTC on the last Sunday of March to the last Sunday of October
+                let start = utc(last_sunday(year, 3), 1);
+                let end = utc(last_sunday(year, 10), 1);
+                if at >= start && at < end { 1 } else { 0 }
+            },
+            Zone::Sydney => {
+                // Standard time from 03:00 local on the first Sunday of April to 02:00 on the first Sunday of October
+                let end = utc(nth_sunday(year, 4, 1), 0) - Duration::hours(8);
+                let start = utc(nth_sunday(year, 10, 1), 0) - Duration::hours(8);
+                if at >= end && at < start { 10 } else { 11 }
+            },
+        };
+        at.with_timezone(&FixedOffset::east_opt(hours * 3600).expect("Offsets are within a day"))
+    }
+}
+
+fn utc(date: NaiveDate, hour: u32) -> DateTime<Utc> {
+    date.and_hms_opt(hour, 0, 0).expect("Hour is valid").and_utc()
+}
+
+/// Returns the `n`th Sunday of a month, counting from `1`.
+fn nth_sunday(year: i32, month: u32, n: u32) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Sun, n as u8).expect("Every month has four Sundays")
+}
+
+fn last_sunday(year: i32, month: u32) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Sun, 5).unwrap_or_else(|| nth_sunday(year, month, 4))
+}
+
+fn is_weekday(weekday: Weekday) -> bool {
+    !matches!(weekday, Weekday::Sat | Weekday::Sun)
+}
+
+fn within(
+    time: NaiveTime,
+    open: (u32, u32),
+    close: (u32, u32)
+) -> bool {
+    let open: NaiveTime = NaiveTime::from_hms_opt(open.0, open.1, 0).expect("Open time is valid");
+    let close: NaiveTime = NaiveTime::from_hms_opt(close.0, close.1, 0).expect("Close time is valid");
+    time >= open && time < close
+}
+
+impl FromStr for Session {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace([' ', '-'], "_").as_str() {
+            "sydney" => Ok(Session::Sydney),
+            "tokyo" | "asia" => Ok(Session::Tokyo),
+            "london" | "europe" => Ok(Session::London),
+            "new_york" | "newyork" | "ny" => Ok(Session::NewYork),
+            other => Err(format!("Unknown session `{}`, expected sydney, tokyo, london or new_york", other)),
+        }
+    }
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
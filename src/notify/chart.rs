@@ -0,0 +1,43 @@
+//! ## Chart snapshots
+//!
+//! Links a triggered alert to the chart drawn by a [`crate::notify::ChartRenderer`]. The URL
+//! is stored in the metadata under [`CHART_URL_METADATA_KEY`], which every payload format
+//! carries along.
+
+use serde_json::{Map, Value};
+
+use crate::TriggeredAlert;
+
+/// The metadata key holding the URL of the chart snapshot of a notification.
+pub const CHART_URL_METADATA_KEY: &str = "chart_url";
+
+impl TriggeredAlert {
+    /// Returns the URL of the chart snapshot added to the notification, if any.
+    pub fn chart_url(&self) -> Option<&str> {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get(CHART_URL_METADATA_KEY))
+            .and_then(Value::as_str)
+    }
+
+    /// Returns the alert with the URL of its chart snapshot stored in its metadata.
+    ///
+    /// Metadata which is not a JSON object is kept under `metadata` of the new object.
+    pub fn with_chart_url(
+        &self,
+        url: &str
+    ) -> TriggeredAlert {
+        let mut metadata: Map<String, Value> = match &self.metadata {
+            Some(Value::Object(metadata)) => metadata.clone(),
+            None | Some(Value::Null) => Map::new(),
+            Some(metadata) => Map::from_iter([("metadata".to_string(), metadata.clone())]),
+        };
+        metadata.insert(CHART_URL_METADATA_KEY.to_string(), Value::from(url));
+        TriggeredAlert { metadata: Some(Value::Object(metadata)), ..self.clone() }
+    }
+
+    /// Returns the name of the chart snapshot file, the same every time the trigger is delivered.
+    pub fn chart_file_name(&self) -> String {
+        format!("{}-{}.png", self.hash, self.triggered_at.timestamp_millis())
+    }
+}
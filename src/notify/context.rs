@@ -0,0 +1,77 @@
+//! ## Notification context
+//!
+//! Adds the owner's other alerts on the triggered symbol to a notification, with their levels
+//! and distances from the trigger price, so a single message shows where the next levels are.
+//! The alerts are stored in the metadata under [`NEAREST_ALERTS_METADATA_KEY`], which every
+//! payload format carries along.
+
+use chrono::Utc;
+use serde_json::{Map, Value};
+
+use crate::{Alert, TriggeredAlert};
+use crate::notify::NearestAlert;
+use crate::utils::symbol::same_symbol;
+
+/// The metadata key holding the nearest other alerts of a notification.
+pub const NEAREST_ALERTS_METADATA_KEY: &str = "nearest_alerts";
+
+impl TriggeredAlert {
+    /// Returns the nearest other alerts added to the notification, empty if none were added.
+    pub fn nearest_alerts(&self) -> Vec<NearestAlert> {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get(NEAREST_ALERTS_METADATA_KEY))
+            .and_then(|nearest| serde_json::from_value(nearest.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Returns the alert with `nearest` stored in its metadata.
+    ///
+    /// Metadata which is not a JSON object is kept under `metadata` of the new object.
+    pub fn with_nearest_alerts(
+        &self,
+        nearest: &[NearestAlert]
+    ) -> TriggeredAlert {
+        let mut metadata: Map<String, Value> = match &self.metadata {
+            Some(Value::Object(metadata)) => metadata.clone(),
+            None | Some(Value::Null) => Map::new(),
+            Some(metadata) => Map::from_iter([("metadata".to_string(), metadata.clone())]),
+        };
+        metadata.insert(NEAREST_ALERTS_METADATA_KEY.to_string(), serde_json::to_value(nearest).unwrap_or_default());
+        TriggeredAlert { metadata: Some(Value::Object(metadata)), ..self.clone() }
+    }
+}
+
+/// Picks the `limit` active alerts on the triggered symbol closest to the trigger price.
+///
+/// # Parameters
+/// - `alert`: The triggered alert, excluded from the result.
+/// - `alerts`: The owner's alerts, on any symbol.
+/// - `limit`: The largest number of alerts returned.
+///
+/// # Returns
+/// The nearest alerts, closest first.
+pub fn nearest_alerts(
+    alert: &TriggeredAlert,
+    alerts: &[Alert],
+    limit: usize
+) -> Vec<NearestAlert> {
+    let now = Utc::now();
+    let mut nearest: Vec<NearestAlert> = alerts
+        .iter()
+        .filter(|other| other.hash != alert.hash && same_symbol(&other.symbol, &alert.symbol))
+        .filter(|other| other.expires_at.is_none_or(|expires_at| expires_at > now))
+        .map(|other| {
+            let distance: f64 = other.price_level - alert.trigger_price;
+            NearestAlert {
+                hash: other.hash.clone(),
+                price_level: other.price_level,
+                distance,
+                distance_percent: if alert.trigger_price == 0.0 { 0.0 } else { distance / alert.trigger_price * 100.0 },
+            }
+        })
+        .collect();
+    nearest.sort_by(|a, b| a.distance.abs().total_cmp(&b.distance.abs()));
+    nearest.truncate(limit);
+    nearest
+}
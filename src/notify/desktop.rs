@@ -0,0 +1,72 @@
+//! ## Desktop notifications (feature `desktop`)
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use notify_rust::{Notification, Timeout};
+
+use crate::TriggeredAlert;
+use crate::errors::NotifyError;
+use crate::notify::{DesktopNotifier, Notifier};
+
+impl DesktopNotifier {
+    /// Creates a `DesktopNotifier` showing notifications as `trade_alerts` with the system default timeout.
+    pub fn new() -> Self {
+        Self { app_name: "trade_alerts".to_string(), timeout: None }
+    }
+
+    /// Sets how long notifications stay visible.
+    pub fn with_timeout(
+        mut self,
+        timeout: Duration
+    ) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Returns the summary and body shown for a triggered alert, listing the nearest other alerts if added.
+    pub fn message(alert: &TriggeredAlert) -> (String, String) {
+        let summary: String = format!("{} alert triggered", alert.symbol.to_uppercase());
+        let mut body: String = format!(
+            "{} traded at {} (alert level {})",
+            alert.symbol.to_uppercase(), alert.trigger_price, alert.price_level
+        );
+        let nearest: Vec<String> = alert
+            .nearest_alerts()
+            .iter()
+            .map(|nearest| format!("{} ({:+.2}%)", nearest.price_level, nearest.distance_percent))
+            .collect();
+        if !nearest.is_empty() {
+            body.push_str(&format!("\nOther alerts: {}", nearest.join(", ")));
+        }
+        (summary, body)
+    }
+}
+
+impl Default for DesktopNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Notifier for DesktopNotifier {
+    fn name(&self) -> &str {
+        "desktop"
+    }
+
+    async fn notify(&self, alert: &TriggeredAlert) -> Result<(), NotifyError> {
+        let (summary, body) = Self::message(alert);
+        let mut notification: Notification = Notification::new();
+        notification.appname(&self.app_name).summary(&summary).body(&body);
+        if let Some(timeout) = self.timeout {
+            notification.timeout(Timeout::Milliseconds(timeout.as_millis() as u32));
+        }
+
+        // Showing a notification blocks on the OS notification service
+        tokio::task::spawn_blocking(move || notification.show().map(|_| ()))
+            .await
+            .map_err(|e| NotifyError::DeliveryError(e.to_string()))?
+            .map_err(|e| NotifyError::DeliveryError(e.to_string()))
+    }
+}
@@ -0,0 +1,117 @@
+//! ## Notification intent log
+//!
+//! The log is a JSON lines file. Recording an intent appends
+//! `{"id": ..., "event": <trigger event>}` and completing it appends `{"id": ..., "completed": true}`,
+//! so a crash never loses or corrupts earlier records. Both writes are flushed to disk before
+//! returning. [`IntentLog::compact`] drops completed intents from the file.
+
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde_json::{Value, json};
+
+use crate::TriggeredAlert;
+use crate::notify::{Intent, IntentLog};
+
+impl IntentLog {
+    /// Opens the intent log at `path`, creating it on the first write.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), lock: Arc::new(Mutex::new(())) }
+    }
+
+    /// The id of the intent recorded for a trigger.
+    pub fn intent_id(alert: &TriggeredAlert) -> String {
+        format!("{}@{}", alert.hash, alert.triggered_at.to_rfc3339())
+    }
+
+    /// Records the intent to deliver a triggered alert.
+    ///
+    /// # Returns
+    /// The id of the intent, to pass to [`IntentLog::complete`].
+    ///
+    /// # Errors
+    /// Returns an error if the record could not be written to disk.
+    pub fn record(&self, alert: &TriggeredAlert) -> io::Result<String> {
+        let id: String = Self::intent_id(alert);
+        self.append(&json!({ "id": id, "event": alert.to_event() }))?;
+        Ok(id)
+    }
+
+    /// Marks an intent as delivered.
+    ///
+    /// # Errors
+    /// Returns an error if the record could not be written to disk.
+    pub fn complete(&self, id: &str) -> io::Result<()> {
+        self.append(&json!({ "id": id, "completed": true }))
+    }
+
+    /// Returns the intents which were recorded but never completed, oldest first.
+    ///
+    /// Unreadable lines, such as a line cut short by a crash, are skipped.
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but could not be read.
+    pub fn pending(&self) -> io::Result<Vec<Intent>> {
+        let _guard: MutexGuard<'_, ()> = self.lock();
+        self.read_pending()
+    }
+
+    /// Rewrites the log with only its pending intents.
+    ///
+    /// # Errors
+    /// Returns an error if the file could not be read or rewritten.
+    pub fn compact(&self) -> io::Result<()> {
+        let _guard: MutexGuard<'_, ()> = self.lock();
+        let pending: Vec<Intent> = self.read_pending()?;
+
+        let temporary: PathBuf = self.path.with_extension("compacting");
+        let mut file: File = File::create(&temporary)?;
+        for intent in pending {
+            writeln!(file, "{}", json!({ "id": intent.id, "event": intent.alert.to_event() }))?;
+        }
+        file.sync_all()?;
+        std::fs::rename(&temporary, &self.path)
+    }
+
+    fn append(&self, record: &Value) -> io::Result<()> {
+        let _guard: MutexGuard<'_, ()> = self.lock();
+        let mut file: File = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", record)?;
+        file.sync_data()
+    }
+
+    fn read_pending(&self) -> io::Result<Vec<Intent>> {
+        let file: File = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut recorded: Vec<Intent> = Vec::new();
+        let mut completed: HashSet<String> = HashSet::new();
+        for line in BufReader::new(file).lines() {
+            let Ok(record) = serde_json::from_str::<Value>(&line?) else {
+                continue;
+            };
+            let Some(id) = record["id"].as_str() else {
+                continue;
+            };
+            if record["completed"] == true {
+                completed.insert(id.to_string());
+            } else if let Ok(alert) = TriggeredAlert::from_event(&record["event"]) {
+                recorded.push(Intent { id: id.to_string(), alert });
+            }
+        }
+
+        let mut seen: HashSet<String> = HashSet::new();
+        recorded.retain(|intent| !completed.contains(&intent.id) && seen.insert(intent.id.clone()));
+        Ok(recorded)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ()> {
+        self.lock.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,256 @@
+//! ## Notification channels for triggered alerts
+//!
+//! Every channel implements `Notifier`, so callers can deliver triggered alerts
+//! without knowing how they reach the user.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Alert, TriggeredAlert};
+use crate::actions::ActionSigner;
+use crate::db::{Supabase, TableConfig, WebhookTable};
+use crate::errors::NotifyError;
+use crate::retry::RetryPolicy;
+use crate::timeout::Timeouts;
+use crate::utils::pseudonym::Pseudonymizer;
+
+pub mod chart;
+pub mod context;
+#[cfg(feature = "desktop")]
+pub mod desktop;
+pub mod intent;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+pub mod payload;
+pub mod preflight;
+pub mod router;
+pub mod webhook;
+
+/// ## A channel triggered alerts are delivered through
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// A short name for the channel, used in logs and reports.
+    fn name(&self) -> &str;
+
+    /// Delivers a single triggered alert.
+    ///
+    /// # Errors
+    /// Returns `NotifyError::DeliveryError` if the alert could not be delivered.
+    async fn notify(&self, alert: &TriggeredAlert) -> Result<(), NotifyError>;
+
+    /// Delivers the alerts a user triggered during a cycle together, see [`NotificationRouter::with_grouping`].
+    ///
+    /// Channels which cannot group alerts deliver them one by one, every alert is attempted
+    /// even after a failure.
+    ///
+    /// # Errors
+    /// Returns `NotifyError::DeliveryError` if any alert could not be delivered.
+    async fn notify_batch(&self, alerts: &[TriggeredAlert]) -> Result<(), NotifyError> {
+        router::notify_each(self, alerts).await
+    }
+
+    /// Warns the owner that an alert has been active for `age` without triggering.
+    ///
+    /// Channels which cannot reach a user outside of triggers ignore the warning.
+    ///
+    /// # Errors
+    /// Returns `NotifyError::DeliveryError` if the warning could not be delivered.
+    async fn notify_stale(&self, _alert: &Alert, _age: Duration) -> Result<(), NotifyError> {
+        Ok(())
+    }
+
+    /// Checks that the channel is configured correctly and reachable, without delivering anything.
+    ///
+    /// Channels without anything to check, such as desktop notifications, always pass.
+    ///
+    /// # Errors
+    /// Returns `NotifyError::ConfigurationError` if the channel rejects its configuration,
+    /// e.g. an unknown webhook or an invalid token, or `NotifyError::DeliveryError` if it cannot be reached.
+    async fn verify(&self) -> Result<(), NotifyError> {
+        Ok(())
+    }
+}
+
+/// ## Draws a chart of a triggered alert
+///
+/// The PNG is uploaded to Supabase Storage and linked from the notification and the trigger
+/// history, see [`crate::TradeAlertsBuilder::with_chart_snapshots`].
+#[async_trait]
+pub trait ChartRenderer: Send + Sync {
+    /// Renders the chart as a PNG, `None` if none could be drawn, e.g. without candles of the symbol.
+    async fn render(&self, alert: &TriggeredAlert) -> Option<Vec<u8>>;
+}
+
+/// ## Routes triggered alerts to the channels chosen for them
+///
+/// Targets are resolved in order of precedence:
+/// 1. An override in the alert's metadata, see [`router::NOTIFY_METADATA_KEY`].
+/// 2. The channels the alert's owner prefers.
+/// 3. The default channels.
+///
+/// With a webhook store, the webhooks the owner registered are added unless the metadata overrides the targets.
+#[derive(Clone, Default)]
+pub struct NotificationRouter {
+    channels: HashMap<String, Arc<dyn Notifier>>,
+    user_channels: HashMap<String, Vec<String>>,
+    default_channels: Vec<String>,
+    /// Records every delivery before it is attempted, if set.
+    intents: Option<IntentLog>,
+    /// Storage of the webhooks users registered, delivered to next to their channels.
+    webhooks: Option<(Supabase, WebhookTable)>,
+    /// The alerts table and how many of the owner's other alerts to add to notifications, if set.
+    nearest_alerts: Option<(Supabase, TableConfig, usize)>,
+    /// Replaces the user ID in the payloads of the webhooks the router posts to, if set.
+    pseudonymizer: Option<Pseudonymizer>,
+    /// Whether the triggers of a user during a cycle are delivered together.
+    grouped: bool,
+    /// Whether webhooks from metadata overrides may resolve to non-public addresses.
+    private_webhooks: bool,
+}
+
+/// ## Another alert of the same user on the same symbol, added to a notification for context
+///
+/// See [`NotificationRouter::with_nearest_alerts`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NearestAlert {
+    pub hash: String,
+    pub price_level: f64,
+    /// The level minus the trigger price, positive for levels above it.
+    pub distance: f64,
+    /// The distance in percent of the trigger price.
+    pub distance_percent: f64,
+}
+
+/// ## Write-ahead log of notifications which may not have been delivered yet
+///
+/// An intent is appended before an alert is routed and marked complete once every target
+/// received it. Intents left incomplete by a crash are replayed on startup, so every
+/// triggered alert is delivered at least once. Cloning an `IntentLog` shares the file lock.
+#[derive(Clone, Debug)]
+pub struct IntentLog {
+    pub path: std::path::PathBuf,
+    lock: Arc<std::sync::Mutex<()>>,
+}
+
+/// ## A notification recorded in the `IntentLog` that was not completed
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Intent {
+    /// Unique per trigger, derived from the alert's hash and trigger time.
+    pub id: String,
+    pub alert: TriggeredAlert,
+}
+
+/// ## Where a triggered alert is delivered
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RouteTarget {
+    /// A channel registered on the router by name.
+    Channel(String),
+    /// A webhook URL taken from the alert itself.
+    Webhook { url: String, format: PayloadFormat },
+    /// A webhook the alert's owner registered, see [`crate::db::WebhookTable`].
+    UserWebhook { id: String, url: String, format: PayloadFormat },
+}
+
+/// ## Result of delivering an alert to a single target
+#[derive(Clone, Debug, PartialEq)]
+pub struct Delivery {
+    pub target: RouteTarget,
+    /// Why the delivery failed, `None` if it succeeded.
+    pub error: Option<String>,
+}
+
+/// ## Result of verifying a single channel, see [`Notifier::verify`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelCheck {
+    /// The name the channel is registered under.
+    pub channel: String,
+    /// Why the check failed, `None` if it passed.
+    pub error: Option<String>,
+}
+
+/// ## Result of verifying every channel of a `NotificationRouter` before alerts are checked
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PreflightReport {
+    /// One check per registered or referenced channel, ordered by channel name.
+    pub checks: Vec<ChannelCheck>,
+}
+
+/// ## Body format of outbound webhooks
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PayloadFormat {
+    /// The versioned trigger event, see [`crate::events`].
+    #[default]
+    Event,
+    /// A flat JSON object without nesting, as expected by Zapier catch hooks.
+    Flat,
+    /// `value1`/`value2`/`value3` as expected by IFTTT Webhooks.
+    Ifttt,
+}
+
+/// ## Posts triggered alerts to a webhook URL
+/// `Debug` output never contains the signing secret.
+#[derive(Clone)]
+pub struct WebhookNotifier {
+    pub url: String,
+    pub format: PayloadFormat,
+    /// Signs the one-click action links added to every payload, see [`crate::actions`].
+    pub actions: Option<ActionSigner>,
+    /// Replaces the user ID in every payload, see [`crate::utils::pseudonym`].
+    pub pseudonymizer: Option<Pseudonymizer>,
+    /// Retries of posts failing with a `5xx` response or a network error.
+    pub retry: RetryPolicy,
+    /// Signs every body, see [`webhook::SIGNATURE_HEADER`].
+    secret: Option<String>,
+    /// Connect and read timeouts of every post.
+    timeouts: Timeouts,
+    /// Addresses the host is pinned to, see [`WebhookNotifier::pinned`].
+    pinned: Option<webhook::PublicUrl>,
+    client: reqwest::Client,
+    /// Outcomes of the latest posts, oldest first, shared between clones.
+    deliveries: Arc<Mutex<VecDeque<WebhookDelivery>>>,
+}
+
+/// ## Outcome of posting a single body to a webhook, see [`WebhookNotifier::deliveries`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebhookDelivery {
+    pub url: String,
+    /// Hashes of the alerts the body was posted for, several for grouped payloads.
+    pub hashes: Vec<String>,
+    /// Status of the last response, `None` if no response was received.
+    pub status: Option<u16>,
+    /// Attempts made, including the first one.
+    pub attempts: u32,
+    /// Why the delivery failed, `None` if it succeeded.
+    pub error: Option<String>,
+    pub delivered_at: DateTime<Utc>,
+}
+
+/// ## Native OS notifications for users running the watcher locally
+#[cfg(feature = "desktop")]
+#[derive(Clone, Debug)]
+pub struct DesktopNotifier {
+    /// The application name shown by the notification center.
+    pub app_name: String,
+    /// How long the notification stays visible, `None` for the system default.
+    pub timeout: Option<std::time::Duration>,
+}
+
+/// ## Publishes trigger events to an MQTT broker for home-automation integrations
+#[cfg(feature = "mqtt")]
+#[derive(Clone)]
+pub struct MqttNotifier {
+    client: rumqttc::AsyncClient,
+    /// Topic to publish to, `{symbol}` and `{user_id}` are replaced per alert.
+    pub topic: String,
+    pub qos: rumqttc::QoS,
+    /// Whether the broker keeps the last event for new subscribers.
+    pub retain: bool,
+    /// Replaces the user ID in every event and topic, see [`crate::utils::pseudonym`].
+    pub pseudonymizer: Option<Pseudonymizer>,
+    /// The outcome of the last connection attempt, `None` before the first one completed.
+    connection: tokio::sync::watch::Receiver<Option<Result<(), String>>>,
+}
@@ -0,0 +1,140 @@
+//! ## MQTT publisher (feature `mqtt`)
+//!
+//! Publishes every triggered alert as a versioned event (see [`crate::events`]) so
+//! home dashboards and automations can subscribe to it.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use tokio::sync::watch;
+
+use crate::TriggeredAlert;
+use crate::errors::NotifyError;
+use crate::notify::{MqttNotifier, Notifier};
+use crate::utils::pseudonym::Pseudonymizer;
+
+/// How long [`Notifier::verify`] waits for the first connection attempt.
+pub const VERIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl MqttNotifier {
+    /// Connects to an MQTT broker.
+    ///
+    /// The connection is driven by a background task which reconnects on failure,
+    /// so this must be called inside a Tokio runtime.
+    ///
+    /// # Arguments
+    /// * `host` - The broker host.
+    /// * `port` - The broker port, usually `1883`.
+    /// * `client_id` - The client id announced to the broker.
+    /// * `topic` - The topic to publish to, e.g. `trade_alerts/{symbol}`.
+    pub fn new(
+        host: &str,
+        port: u16,
+        client_id: &str,
+        topic: String
+    ) -> Self {
+        let mut options: MqttOptions = MqttOptions::new(client_id, host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+
+        let (client, mut event_loop) = AsyncClient::new(options, 64);
+        let (state, connection) = watch::channel(None);
+        tokio::spawn(async move {
+            loop {
+                match event_loop.poll().await {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        state.send_replace(Some(Ok(())));
+                    },
+                    Ok(_) => {},
+                    Err(e) => {
+                        println!("MQTT connection error: {}", e);
+                        state.send_replace(Some(Err(e.to_string())));
+                        tokio::time::sleep(Duration::from_secs(5)).await;
+                    },
+                }
+            }
+        });
+
+        Self { client, topic, qos: QoS::AtLeastOnce, retain: false, pseudonymizer: None, connection }
+    }
+
+    /// Sets the quality of service events are published with.
+    pub fn with_qos(
+        mut self,
+        qos: QoS
+    ) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    /// Sets whether the broker retains the last event.
+    pub fn with_retain(
+        mut self,
+        retain: bool
+    ) -> Self {
+        self.retain = retain;
+        self
+    }
+
+    /// Publishes pseudonyms instead of user IDs, in the events and the topics.
+    pub fn with_pseudonymizer(
+        mut self,
+        pseudonymizer: Pseudonymizer
+    ) -> Self {
+        self.pseudonymizer = Some(pseudonymizer);
+        self
+    }
+
+    /// Returns the topic an alert is published to.
+    ///
+    /// `{symbol}` and `{user_id}` are replaced by the alert's values, with `/`, `+` and `#`
+    /// removed so they can't add topic levels or wildcards. With a pseudonymizer, `{user_id}`
+    /// is replaced by the pseudonym.
+    pub fn topic_for(
+        &self,
+        alert: &TriggeredAlert
+    ) -> String {
+        let clean = |value: &str| value.replace(['/', '+', '#'], "");
+        let user_id: String = match &self.pseudonymizer {
+            Some(pseudonymizer) => pseudonymizer.pseudonymize(&alert.user_id),
+            None => alert.user_id.clone(),
+        };
+
+        self.topic
+            .replace("{symbol}", &clean(&alert.symbol))
+            .replace("{user_id}", &clean(&user_id))
+    }
+}
+
+#[async_trait]
+impl Notifier for MqttNotifier {
+    fn name(&self) -> &str {
+        "mqtt"
+    }
+
+    async fn notify(&self, alert: &TriggeredAlert) -> Result<(), NotifyError> {
+        let event: String = match &self.pseudonymizer {
+            Some(pseudonymizer) => pseudonymizer.apply(alert).to_event_json(),
+            None => alert.to_event_json(),
+        };
+        self.client
+            .publish(self.topic_for(alert), self.qos, self.retain, event)
+            .await
+            .map_err(|e| NotifyError::DeliveryError(e.to_string()))
+    }
+
+    /// Waits up to `VERIFY_TIMEOUT` for the broker to accept the connection.
+    async fn verify(&self) -> Result<(), NotifyError> {
+        let mut connection = self.connection.clone();
+        let state = tokio::time::timeout(VERIFY_TIMEOUT, connection.wait_for(Option::is_some))
+            .await
+            .map_err(|_| NotifyError::DeliveryError("Timed out connecting to the MQTT broker".to_string()))?
+            .map_err(|_| NotifyError::DeliveryError("The MQTT connection task stopped".to_string()))?
+            .clone();
+
+        match state {
+            Some(Err(e)) => Err(NotifyError::DeliveryError(e)),
+            _ => Ok(()),
+        }
+    }
+}
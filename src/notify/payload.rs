@@ -0,0 +1,86 @@
+//! ## Webhook payload formats
+//!
+//! Presets matching what no-code platforms expect, so triggered alerts can be
+//! sent to them without middleware.
+
+use std::str::FromStr;
+
+use serde_json::{Map, Value, json};
+
+use crate::TriggeredAlert;
+use crate::notify::PayloadFormat;
+
+impl FromStr for PayloadFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "event" => Ok(PayloadFormat::Event),
+            "flat" | "zapier" => Ok(PayloadFormat::Flat),
+            "ifttt" => Ok(PayloadFormat::Ifttt),
+            other => Err(format!("Unknown payload format: {}", other)),
+        }
+    }
+}
+
+impl PayloadFormat {
+    /// Returns the name the format is parsed from, e.g. `flat`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PayloadFormat::Event => "event",
+            PayloadFormat::Flat => "flat",
+            PayloadFormat::Ifttt => "ifttt",
+        }
+    }
+
+    /// Renders the body sent for a triggered alert.
+    ///
+    /// - `Event`: the versioned trigger event.
+    /// - `Flat`: every field at the top level, the condition as its type name and
+    ///   metadata keys prefixed with `metadata_`. Nested values are sent as JSON strings.
+    /// - `Ifttt`: `value1` is the symbol, `value2` the trigger price and `value3` the alert level.
+    pub fn render(
+        &self,
+        alert: &TriggeredAlert
+    ) -> Value {
+        match self {
+            PayloadFormat::Event => alert.to_event(),
+            PayloadFormat::Flat => {
+                let mut flat: Map<String, Value> = Map::new();
+                flat.insert("hash".to_string(), json!(alert.hash));
+                flat.insert("user_id".to_string(), json!(alert.user_id));
+                flat.insert("symbol".to_string(), json!(alert.symbol));
+                flat.insert("price_level".to_string(), json!(alert.price_level));
+                flat.insert("trigger_price".to_string(), json!(alert.trigger_price));
+                flat.insert("triggered_at".to_string(), json!(alert.triggered_at.to_rfc3339()));
+                flat.insert("condition".to_string(), alert.condition.to_value()["type"].clone());
+                flat.insert("idempotency_key".to_string(), json!(alert.idempotency_key()));
+
+                match &alert.metadata {
+                    Some(Value::Object(metadata)) => {
+                        for (key, value) in metadata {
+                            flat.insert(format!("metadata_{}", key), flatten(value));
+                        }
+                    },
+                    Some(metadata) => {
+                        flat.insert("metadata".to_string(), flatten(metadata));
+                    },
+                    None => {},
+                }
+                Value::Object(flat)
+            },
+            PayloadFormat::Ifttt => json!({
+                "value1": alert.symbol,
+                "value2": alert.trigger_price.to_string(),
+                "value3": alert.price_level.to_string(),
+            }),
+        }
+    }
+}
+
+fn flatten(value: &Value) -> Value {
+    match value {
+        Value::Object(_) | Value::Array(_) => Value::String(value.to_string()),
+        value => value.clone(),
+    }
+}
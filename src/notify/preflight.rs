@@ -0,0 +1,43 @@
+//! ## Preflight checks of notification channels
+//!
+//! Verifies every channel before the first check cycle, so a wrong webhook URL or
+//! token is reported at startup rather than on the first missed alert.
+
+use std::collections::BTreeSet;
+
+use futures_util::future::join_all;
+
+use crate::notify::{ChannelCheck, NotificationRouter, PreflightReport};
+
+impl NotificationRouter {
+    /// Verifies every registered channel concurrently, see [`crate::notify::Notifier::verify`].
+    ///
+    /// Channel names used as user or default channels without being registered fail as well.
+    pub async fn preflight(&self) -> PreflightReport {
+        let mut names: BTreeSet<&str> = self.channels.keys().map(String::as_str).collect();
+        names.extend(self.default_channels.iter().map(String::as_str));
+        names.extend(self.user_channels.values().flatten().map(String::as_str));
+
+        let checks = names.into_iter().map(|name| async move {
+            let error: Option<String> = match self.channels.get(name) {
+                Some(notifier) => notifier.verify().await.err().map(|e| e.to_string()),
+                None => Some(format!("Unknown notification channel: {}", name)),
+            };
+            ChannelCheck { channel: name.to_string(), error }
+        });
+
+        PreflightReport { checks: join_all(checks).await }
+    }
+}
+
+impl PreflightReport {
+    /// Returns `true` if every channel passed.
+    pub fn is_ok(&self) -> bool {
+        self.checks.iter().all(|check| check.error.is_none())
+    }
+
+    /// Returns the checks which failed.
+    pub fn failures(&self) -> Vec<&ChannelCheck> {
+        self.checks.iter().filter(|check| check.error.is_some()).collect()
+    }
+}
@@ -0,0 +1,572 @@
+//! ## Notification routing
+//!
+//! Decides which channels a triggered alert goes to. Alerts created programmatically
+//! per workflow can carry their own target in their metadata, e.g.
+//! `{"notify": {"webhook_url": "https://example.com/hook", "format": "flat"}}` or
+//! `{"notify": {"channels": ["slack"]}}`, which takes precedence over user preferences.
+//! Alerts with `{"notify": {"muted": true}}` still trigger but are delivered nowhere.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::{Alert, ResourceLimits, TriggeredAlert};
+use crate::errors::NotifyError;
+use crate::events::{UserTriggers, group_by_user};
+use crate::db::{Supabase, TableConfig, UserWebhook, WebhookTable};
+use crate::notify::context::nearest_alerts;
+use crate::notify::webhook;
+use crate::notify::{
+    Delivery, Intent, IntentLog, NearestAlert, NotificationRouter, Notifier, PayloadFormat, RouteTarget, WebhookNotifier,
+};
+use crate::utils::privacy::privacy_policy;
+use crate::utils::pseudonym::Pseudonymizer;
+
+/// The metadata key holding a per-alert notification override.
+pub const NOTIFY_METADATA_KEY: &str = "notify";
+
+impl NotificationRouter {
+    /// Creates a router without channels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a channel under a name, e.g. `"slack"` or `"desktop"`.
+    pub fn with_channel(
+        mut self,
+        name: &str,
+        notifier: impl Notifier + 'static
+    ) -> Self {
+        self.channels.insert(name.to_string(), Arc::new(notifier));
+        self
+    }
+
+    /// Registers a channel and adds it to the default channels, unless it is one already.
+    pub(crate) fn with_default_channel(
+        mut self,
+        name: &str,
+        notifier: Arc<dyn Notifier>
+    ) -> Self {
+        self.channels.insert(name.to_string(), notifier);
+        if !self.default_channels.iter().any(|channel| channel == name) {
+            self.default_channels.push(name.to_string());
+        }
+        self
+    }
+
+    /// Sets the channels a user's alerts are delivered to.
+    pub fn with_user_channels(
+        mut self,
+        user_id: &str,
+        channels: Vec<String>
+    ) -> Self {
+        self.user_channels.insert(user_id.to_string(), channels);
+        self
+    }
+
+    /// Sets the channels used for alerts without an override or user preference.
+    pub fn with_default_channels(
+        mut self,
+        channels: Vec<String>
+    ) -> Self {
+        self.default_channels = channels;
+        self
+    }
+
+    /// Records every delivery in a write-ahead intent log, see [`IntentLog`].
+    ///
+    /// Call [`NotificationRouter::replay_intents`] on startup to deliver the alerts a crash
+    /// interrupted.
+    pub fn with_intent_log(
+        mut self,
+        intents: IntentLog
+    ) -> Self {
+        self.intents = Some(intents);
+        self
+    }
+
+    /// Delivers every alert to the enabled webhooks its owner registered, next to its channels.
+    ///
+    /// Alerts whose metadata overrides the targets only go to the override. A failure to fetch
+    /// the webhooks is logged and does not stop delivery to the channels.
+    pub fn with_webhook_store(
+        mut self,
+        supabase: Supabase,
+        table: WebhookTable
+    ) -> Self {
+        self.webhooks = Some((supabase, table));
+        self
+    }
+
+    /// Adds up to `limit` of the owner's other alerts on the same symbol to every notification,
+    /// closest to the trigger price first, see [`crate::notify::context`].
+    ///
+    /// Only alerts which can still trigger are added, see [`Supabase::fetch_active_alerts_by_user_id`].
+    /// They are fetched once per user and cycle. A failure to fetch the alerts is logged and
+    /// the alert is delivered without them.
+    pub fn with_nearest_alerts(
+        mut self,
+        supabase: Supabase,
+        config: TableConfig,
+        limit: usize
+    ) -> Self {
+        self.nearest_alerts = Some((supabase, config, limit));
+        self
+    }
+
+    /// Posts pseudonyms instead of user IDs to the webhooks the router builds, those from
+    /// metadata overrides and those users registered, see [`Pseudonymizer`].
+    ///
+    /// Channels registered with [`NotificationRouter::with_channel`] are configured on their own.
+    pub fn with_pseudonymizer(
+        mut self,
+        pseudonymizer: Pseudonymizer
+    ) -> Self {
+        self.pseudonymizer = Some(pseudonymizer);
+        self
+    }
+
+    /// Bounds the pseudonyms remembered by the router's pseudonymizer, if any.
+    pub(crate) fn set_resource_limits(&self, limits: ResourceLimits) {
+        if let Some(pseudonymizer) = &self.pseudonymizer {
+            pseudonymizer.clone().with_resource_limits(limits);
+        }
+    }
+
+    /// Delivers the triggers of a user during a cycle together, one call per target with every
+    /// alert going there, e.g. a single webhook `POST` holding an array of alerts.
+    ///
+    /// Receivers get fewer requests and can process a user's triggers atomically, see
+    /// [`NotificationRouter::route_grouped`] and the grouped payload in [`crate::events`].
+    pub fn with_grouping(mut self) -> Self {
+        self.grouped = true;
+        self
+    }
+
+    /// Allows webhooks from metadata overrides and registered webhooks on loopback and private
+    /// networks, e.g. for receivers running next to the engine.
+    ///
+    /// By default those webhooks must resolve to public addresses on every delivery, see
+    /// [`webhook::resolve_public_url`], since anyone creating alerts or registering webhooks could
+    /// otherwise make the engine post to internal services.
+    pub fn with_private_webhooks(mut self) -> Self {
+        self.private_webhooks = true;
+        self
+    }
+
+    /// Whether the triggers of a user during a cycle are delivered together.
+    pub fn is_grouped(&self) -> bool {
+        self.grouped
+    }
+
+    /// Resolves the targets of a triggered alert.
+    ///
+    /// An override in the alert's metadata wins over the user's preferred channels,
+    /// which win over the default channels. Registered webhooks are looked up on delivery.
+    pub fn targets(&self, alert: &TriggeredAlert) -> Vec<RouteTarget> {
+        if let Some(targets) = metadata_targets(alert.metadata.as_ref()) {
+            return targets;
+        }
+
+        self.user_channels
+            .get(&alert.user_id)
+            .unwrap_or(&self.default_channels)
+            .iter()
+            .map(|channel| RouteTarget::Channel(channel.clone()))
+            .collect()
+    }
+
+    /// Delivers a triggered alert to every one of its targets.
+    ///
+    /// A failing target does not stop delivery to the others. Unknown channel names are
+    /// reported as failed deliveries. The nearest other alerts, if enabled, are added first.
+    ///
+    /// With an intent log, the intent is recorded first and only completed once every target
+    /// received the alert, so a partly failed delivery is retried by the next replay. A failure
+    /// to write the log is logged and does not stop delivery.
+    ///
+    /// # Returns
+    /// One `Delivery` per target.
+    pub async fn route(&self, alert: &TriggeredAlert) -> Vec<Delivery> {
+        let webhooks: Vec<UserWebhook> = self.user_webhooks(alert).await;
+        let others: Option<Vec<Alert>> = self.owner_alerts(&alert.user_id).await;
+        self.route_to(alert, &webhooks, others.as_deref()).await
+    }
+
+    /// Delivers the alerts triggered during a cycle one by one, as [`NotificationRouter::route`]
+    /// does, fetching the registered webhooks and other alerts of every user once.
+    ///
+    /// # Returns
+    /// One entry per alert, in order, with one `Delivery` per target.
+    pub async fn route_all(&self, triggered: &[TriggeredAlert]) -> Vec<Vec<Delivery>> {
+        let mut webhooks: HashMap<String, Vec<UserWebhook>> = HashMap::new();
+        let mut others: HashMap<String, Option<Vec<Alert>>> = HashMap::new();
+        let mut routed: Vec<Vec<Delivery>> = Vec::with_capacity(triggered.len());
+        for alert in triggered {
+            if !others.contains_key(&alert.user_id) {
+                others.insert(alert.user_id.clone(), self.owner_alerts(&alert.user_id).await);
+            }
+            let others: Option<&[Alert]> = others[&alert.user_id].as_deref();
+            if metadata_targets(alert.metadata.as_ref()).is_some() {
+                routed.push(self.route_to(alert, &[], others).await);
+                continue;
+            }
+            if !webhooks.contains_key(&alert.user_id) {
+                webhooks.insert(alert.user_id.clone(), self.user_webhooks(alert).await);
+            }
+            routed.push(self.route_to(alert, &webhooks[&alert.user_id], others).await);
+        }
+        routed
+    }
+
+    /// Delivers an alert to its targets and the given registered webhooks of its owner,
+    /// adding the nearest of the owner's other alerts.
+    async fn route_to(
+        &self,
+        alert: &TriggeredAlert,
+        webhooks: &[UserWebhook],
+        others: Option<&[Alert]>
+    ) -> Vec<Delivery> {
+        let alert: Cow<'_, TriggeredAlert> = self.enrich(alert, others);
+        let alert: &TriggeredAlert = &alert;
+        let id: Option<String> = self.record_intent(alert);
+
+        let deliveries: Vec<Delivery> = self.deliver(alert, webhooks).await;
+        if let Some(id) = id {
+            self.complete_intent(&id, &deliveries);
+        }
+        deliveries
+    }
+
+    /// Delivers the alerts triggered during a cycle grouped per user, see [`NotificationRouter::with_grouping`].
+    ///
+    /// The alerts of a user are grouped per target, and every target receives its alerts with
+    /// a single [`Notifier::notify_batch`] call. Targets are resolved, enriched and recorded in
+    /// the intent log per alert as with [`NotificationRouter::route`], an intent is completed
+    /// once every batch holding its alert was delivered.
+    ///
+    /// # Returns
+    /// One entry per user, in the order of their first trigger, with one `Delivery` per target.
+    pub async fn route_grouped(&self, triggered: &[TriggeredAlert]) -> Vec<(UserTriggers, Vec<Delivery>)> {
+        let mut routed: Vec<(UserTriggers, Vec<Delivery>)> = Vec::new();
+        for group in group_by_user(triggered) {
+            let others: Option<Vec<Alert>> = self.owner_alerts(&group.user_id).await;
+            let alerts: Vec<TriggeredAlert> = group
+                .alerts
+                .iter()
+                .map(|alert| self.enrich(alert, others.as_deref()).into_owned())
+                .collect();
+            let ids: Vec<Option<String>> = alerts.iter().map(|alert| self.record_intent(alert)).collect();
+
+            // Alerts by target, in the order the targets were first resolved
+            let mut batches: Vec<(RouteTarget, Vec<usize>)> = Vec::new();
+            let mut add = |target: RouteTarget, index: usize| match batches.iter_mut().find(|(batched, _)| *batched == target) {
+                Some((_, indices)) => indices.push(index),
+                None => batches.push((target, vec![index])),
+            };
+            for (index, alert) in alerts.iter().enumerate() {
+                for target in self.targets(alert) {
+                    add(target, index);
+                }
+            }
+            // Registered webhooks are fetched once per user, for the alerts without an override
+            let registering: Vec<usize> = (0..alerts.len())
+                .filter(|index| metadata_targets(alerts[*index].metadata.as_ref()).is_none())
+                .collect();
+            let mut webhooks: Vec<(RouteTarget, Result<WebhookNotifier, NotifyError>)> = Vec::new();
+            if let Some(first) = registering.first() {
+                for webhook in self.user_webhooks(&alerts[*first]).await {
+                    let notifier: Result<WebhookNotifier, NotifyError> = self.registered_webhook(&webhook).await;
+                    let target: RouteTarget = RouteTarget::UserWebhook { id: webhook.id, url: webhook.url, format: webhook.format };
+                    for index in &registering {
+                        add(target.clone(), *index);
+                    }
+                    webhooks.push((target, notifier));
+                }
+            }
+
+            let mut deliveries: Vec<Delivery> = Vec::with_capacity(batches.len());
+            let mut failed: Vec<bool> = vec![false; alerts.len()];
+            for (target, indices) in batches {
+                let batch: Vec<TriggeredAlert> = indices.iter().map(|index| alerts[*index].clone()).collect();
+                let result: Result<(), String> = match &target {
+                    RouteTarget::Channel(name) => match self.channels.get(name) {
+                        Some(notifier) => notifier.notify_batch(&batch).await.map_err(|e| e.to_string()),
+                        None => Err(format!("Unknown notification channel: {}", name)),
+                    },
+                    RouteTarget::Webhook { url, format } => match self.metadata_webhook(url, *format).await {
+                        Ok(notifier) => notifier.notify_batch(&batch).await.map_err(|e| e.to_string()),
+                        Err(e) => Err(e.to_string()),
+                    },
+                    RouteTarget::UserWebhook { id, .. } => match webhooks.iter().find(|(registered, _)| *registered == target) {
+                        Some((_, Ok(notifier))) => notifier.notify_batch(&batch).await.map_err(|e| e.to_string()),
+                        Some((_, Err(e))) => Err(e.to_string()),
+                        None => Err(format!(
+                            "Webhook {} is not registered to {}", id, privacy_policy().redact_user_id(&group.user_id)
+                        )),
+                    },
+                };
+                if result.is_err() {
+                    indices.iter().for_each(|index| failed[*index] = true);
+                }
+                deliveries.push(Delivery { target, error: result.err() });
+            }
+
+            for (id, failed) in ids.into_iter().zip(failed) {
+                if let (Some(id), false) = (id, failed) {
+                    self.complete_intent(&id, &[]);
+                }
+            }
+            routed.push((UserTriggers { user_id: group.user_id, alerts }, deliveries));
+        }
+        routed
+    }
+
+    /// Whether a target of a grouped delivery received `alert`, see [`NotificationRouter::route_grouped`].
+    pub(crate) fn reaches(
+        &self,
+        alert: &TriggeredAlert,
+        target: &RouteTarget
+    ) -> bool {
+        match target {
+            // Registered webhooks receive every alert of their owner without an override
+            RouteTarget::UserWebhook { .. } => metadata_targets(alert.metadata.as_ref()).is_none(),
+            target => self.targets(alert).contains(target),
+        }
+    }
+
+    /// Returns the intents left incomplete, empty without an intent log or if it could not be read.
+    pub fn pending_intents(&self) -> Vec<Intent> {
+        let Some(intents) = &self.intents else {
+            return Vec::new();
+        };
+        intents.pending().unwrap_or_else(|e| {
+            println!("Error reading notification intents: {}", e);
+            Vec::new()
+        })
+    }
+
+    /// Delivers every intent left incomplete, e.g. by a crash, then compacts the log.
+    ///
+    /// # Returns
+    /// The replayed intents with their deliveries, empty without an intent log.
+    pub async fn replay_intents(&self) -> Vec<(Intent, Vec<Delivery>)> {
+        let Some(intents) = &self.intents else {
+            return Vec::new();
+        };
+        let pending: Vec<Intent> = match intents.pending() {
+            Ok(pending) => pending,
+            Err(e) => {
+                println!("Error reading notification intents: {}", e);
+                return Vec::new();
+            },
+        };
+
+        let mut replayed: Vec<(Intent, Vec<Delivery>)> = Vec::with_capacity(pending.len());
+        for intent in pending {
+            println!("Replaying notification intent {}", intent.id);
+            // Replayed alerts were enriched before they were recorded
+            let webhooks: Vec<UserWebhook> = self.user_webhooks(&intent.alert).await;
+            let deliveries: Vec<Delivery> = self.deliver(&intent.alert, &webhooks).await;
+            self.complete_intent(&intent.id, &deliveries);
+            replayed.push((intent, deliveries));
+        }
+        self.compact_intents();
+
+        replayed
+    }
+
+    /// Fetches the active alerts of a user for the nearest alerts, `None` if disabled or the fetch failed.
+    async fn owner_alerts(&self, user_id: &str) -> Option<Vec<Alert>> {
+        let (supabase, config, _) = self.nearest_alerts.as_ref()?;
+        match supabase.fetch_active_alerts_by_user_id(user_id, config).await {
+            Ok(alerts) => Some(alerts),
+            Err(e) => {
+                println!("Error fetching the other alerts of {}: {}", privacy_policy().redact_user_id(user_id), e);
+                None
+            },
+        }
+    }
+
+    /// Adds the nearest of the owner's other alerts, if enabled and there are any.
+    fn enrich<'a>(
+        &self,
+        alert: &'a TriggeredAlert,
+        others: Option<&[Alert]>
+    ) -> Cow<'a, TriggeredAlert> {
+        let (Some((_, _, limit)), Some(others)) = (&self.nearest_alerts, others) else {
+            return Cow::Borrowed(alert);
+        };
+        let nearest: Vec<NearestAlert> = nearest_alerts(alert, others, *limit);
+        match nearest.is_empty() {
+            true => Cow::Borrowed(alert),
+            false => Cow::Owned(alert.with_nearest_alerts(&nearest)),
+        }
+    }
+
+    /// Rewrites the intent log, if any, with only its pending intents, logging failures.
+    pub(crate) fn compact_intents(&self) {
+        if let Some(Err(e)) = self.intents.as_ref().map(|intents| intents.compact()) {
+            println!("Error compacting notification intents: {}", e);
+        }
+    }
+
+    /// Records an alert in the intent log, if any, returning the ID of its intent.
+    fn record_intent(&self, alert: &TriggeredAlert) -> Option<String> {
+        self.intents.as_ref().and_then(|intents| match intents.record(alert) {
+            Ok(id) => Some(id),
+            Err(e) => {
+                println!("Error recording notification intent for {}: {}", alert.hash, e);
+                None
+            },
+        })
+    }
+
+    /// Marks an intent complete if every delivery succeeded.
+    fn complete_intent(&self, id: &str, deliveries: &[Delivery]) {
+        let Some(intents) = &self.intents else {
+            return;
+        };
+        if deliveries.iter().all(|delivery| delivery.error.is_none()) {
+            if let Err(e) = intents.complete(id) {
+                println!("Error completing notification intent {}: {}", id, e);
+            }
+        }
+    }
+
+    async fn deliver(
+        &self,
+        alert: &TriggeredAlert,
+        webhooks: &[UserWebhook]
+    ) -> Vec<Delivery> {
+        let mut deliveries: Vec<Delivery> = Vec::new();
+
+        for target in self.targets(alert) {
+            let result = match &target {
+                RouteTarget::Channel(name) => match self.channels.get(name) {
+                    Some(notifier) => notifier.notify(alert).await.map_err(|e| e.to_string()),
+                    None => Err(format!("Unknown notification channel: {}", name)),
+                },
+                RouteTarget::Webhook { url, format } => match self.metadata_webhook(url, *format).await {
+                    Ok(notifier) => notifier.notify(alert).await.map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                },
+                // Registered webhooks are delivered below, with their secret
+                RouteTarget::UserWebhook { id, .. } => Err(format!(
+                    "Webhook {} is not registered to {}", id, privacy_policy().redact_user_id(&alert.user_id)
+                )),
+            };
+            deliveries.push(Delivery { target, error: result.err() });
+        }
+
+        for webhook in webhooks {
+            let result = match self.registered_webhook(webhook).await {
+                Ok(notifier) => notifier.notify(alert).await.map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            let target: RouteTarget = RouteTarget::UserWebhook { id: webhook.id.clone(), url: webhook.url.clone(), format: webhook.format };
+            deliveries.push(Delivery { target, error: result.err() });
+        }
+
+        deliveries
+    }
+
+    /// Builds the notifier of a webhook from a metadata override, pinned to its public addresses
+    /// unless private webhooks are allowed.
+    async fn metadata_webhook(
+        &self,
+        url: &str,
+        format: PayloadFormat
+    ) -> Result<WebhookNotifier, NotifyError> {
+        let notifier: WebhookNotifier = self.pseudonymized(WebhookNotifier::new(url.to_string()).with_format(format));
+        if self.private_webhooks {
+            return Ok(notifier);
+        }
+        notifier.pinned(&webhook::resolve_public_url(url).await?)
+    }
+
+    /// Builds the notifier of a registered webhook, pinned to its public addresses unless
+    /// private webhooks are allowed.
+    async fn registered_webhook(&self, webhook: &UserWebhook) -> Result<WebhookNotifier, NotifyError> {
+        let notifier: WebhookNotifier = self.pseudonymized(webhook.notifier());
+        if self.private_webhooks {
+            return Ok(notifier);
+        }
+        notifier.pinned(&webhook::resolve_public_url(&webhook.url).await?)
+    }
+
+    fn pseudonymized(&self, notifier: WebhookNotifier) -> WebhookNotifier {
+        match &self.pseudonymizer {
+            Some(pseudonymizer) => notifier.with_pseudonymizer(pseudonymizer.clone()),
+            None => notifier,
+        }
+    }
+
+    /// Fetches the enabled webhooks of the alert's owner, none without a store or with a metadata override.
+    async fn user_webhooks(&self, alert: &TriggeredAlert) -> Vec<UserWebhook> {
+        let Some((supabase, table)) = &self.webhooks else {
+            return Vec::new();
+        };
+        if metadata_targets(alert.metadata.as_ref()).is_some() {
+            return Vec::new();
+        }
+        match supabase.list_webhooks(&alert.user_id, table).await {
+            Ok(webhooks) => webhooks.into_iter().filter(|webhook| webhook.enabled).collect(),
+            Err(e) => {
+                println!("Error fetching the webhooks of {}: {}", privacy_policy().redact_user_id(&alert.user_id), e);
+                Vec::new()
+            },
+        }
+    }
+}
+
+/// Delivers the alerts one by one, attempting every alert even after a failure.
+///
+/// # Errors
+/// Returns `NotifyError::DeliveryError` naming every alert which could not be delivered.
+pub(crate) async fn notify_each<N: Notifier + ?Sized>(
+    notifier: &N,
+    alerts: &[TriggeredAlert]
+) -> Result<(), NotifyError> {
+    let mut errors: Vec<String> = Vec::new();
+    for alert in alerts {
+        if let Err(e) = notifier.notify(alert).await {
+            errors.push(format!("{}: {}", alert.hash, e));
+        }
+    }
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(NotifyError::DeliveryError(errors.join(", "))),
+    }
+}
+
+/// Reads the targets overridden in an alert's metadata, if any.
+fn metadata_targets(metadata: Option<&Value>) -> Option<Vec<RouteTarget>> {
+    let notify: &Value = metadata?.get(NOTIFY_METADATA_KEY)?;
+
+    if notify.get("muted").and_then(|v| v.as_bool()) == Some(true) {
+        return Some(Vec::new());
+    }
+
+    if let Some(url) = notify.get("webhook_url").and_then(|v| v.as_str()) {
+        let format: PayloadFormat = notify
+            .get("format")
+            .and_then(|v| v.as_str())
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default();
+        return Some(vec![RouteTarget::Webhook { url: url.to_string(), format }]);
+    }
+
+    let channels: Vec<RouteTarget> = match notify.get("channels") {
+        Some(Value::String(channel)) => vec![RouteTarget::Channel(channel.clone())],
+        Some(Value::Array(channels)) => channels
+            .iter()
+            .filter_map(|channel| channel.as_str())
+            .map(|channel| RouteTarget::Channel(channel.to_string()))
+            .collect(),
+        _ => return None,
+    };
+    Some(channels)
+}
@@ -0,0 +1,431 @@
+//! ## Outbound webhooks
+
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use serde_json::{Map, Value, json};
+use sha2::Sha256;
+
+use crate::{Alert, TriggeredAlert};
+use crate::actions::ActionSigner;
+use crate::errors::NotifyError;
+use crate::events::TRIGGER_BATCH_SCHEMA_VERSION;
+use crate::notify::{Notifier, PayloadFormat, WebhookDelivery, WebhookNotifier};
+use crate::notify::router::notify_each;
+use crate::retry::RetryPolicy;
+use crate::timeout::Timeouts;
+use crate::utils::pseudonym::Pseudonymizer;
+
+/// Header carrying `sha256=<hex HMAC-SHA256 of the body>` when the webhook has a secret.
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+/// Number of posts whose outcome a `WebhookNotifier` keeps, see [`WebhookNotifier::deliveries`].
+pub const RECENT_DELIVERIES: usize = 100;
+
+impl WebhookNotifier {
+    /// Creates a `WebhookNotifier` posting the versioned trigger event to `url`.
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            format: PayloadFormat::Event,
+            actions: None,
+            pseudonymizer: None,
+            retry: RetryPolicy::default(),
+            secret: None,
+            timeouts: Timeouts::default(),
+            pinned: None,
+            client: Timeouts::default().client(),
+            deliveries: Arc::default(),
+        }
+    }
+
+    /// Sets the connect and read timeouts of every post, 5s and 10s by default.
+    ///
+    /// # Errors
+    /// Returns `NotifyError::ConfigurationError` if the HTTP client could not be built.
+    pub fn with_timeouts(
+        mut self,
+        timeouts: Timeouts
+    ) -> Result<Self, NotifyError> {
+        self.timeouts = timeouts;
+        self.client = self.build_client()?;
+        Ok(self)
+    }
+
+    /// Sets how posts failing with a `5xx` response or a network error are retried,
+    /// [`RetryPolicy::none`] to never retry. Other responses are never retried.
+    pub fn with_retry_policy(
+        mut self,
+        retry: RetryPolicy
+    ) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Returns the outcome of the latest post of this notifier or any of its clones,
+    /// `None` before the first one.
+    pub fn last_delivery(&self) -> Option<WebhookDelivery> {
+        self.deliveries.lock().unwrap_or_else(|e| e.into_inner()).back().cloned()
+    }
+
+    /// Returns the outcomes of the latest [`RECENT_DELIVERIES`] posts of this notifier and
+    /// its clones, oldest first.
+    pub fn deliveries(&self) -> Vec<WebhookDelivery> {
+        self.deliveries.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+    }
+
+    /// Returns the outcomes of the recent posts for the alert with `hash`, oldest first.
+    pub fn deliveries_of(&self, hash: &str) -> Vec<WebhookDelivery> {
+        self.deliveries()
+            .into_iter()
+            .filter(|delivery| delivery.hashes.iter().any(|delivered| delivered == hash))
+            .collect()
+    }
+
+    /// Signs every body with `secret`, so the receiver can check it came from this crate,
+    /// see [`SIGNATURE_HEADER`].
+    pub fn with_secret(
+        mut self,
+        secret: &str
+    ) -> Self {
+        self.secret = Some(secret.to_string());
+        self
+    }
+
+    /// Sets the body format, e.g. `PayloadFormat::Ifttt` for IFTTT Webhooks.
+    pub fn with_format(
+        mut self,
+        format: PayloadFormat
+    ) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Adds signed "mute this symbol" and "delete this alert" links to every payload.
+    ///
+    /// The links are added under `actions` for `PayloadFormat::Event`, as `action_mute_symbol`
+    /// and `action_delete_alert` for `PayloadFormat::Flat` and not at all for IFTTT, whose
+    /// payload has fixed fields. The signer needs a base URL, see [`ActionSigner::with_base_url`].
+    pub fn with_action_links(
+        mut self,
+        signer: ActionSigner
+    ) -> Self {
+        self.actions = Some(signer);
+        self
+    }
+
+    /// Posts pseudonyms instead of user IDs, see [`Pseudonymizer`].
+    ///
+    /// Action links are signed for the real user, their tokens carry a pseudonym of their own,
+    /// see [`ActionSigner::resolve_owner`].
+    pub fn with_pseudonymizer(
+        mut self,
+        pseudonymizer: Pseudonymizer
+    ) -> Self {
+        self.pseudonymizer = Some(pseudonymizer);
+        self
+    }
+
+    /// Renders the body posted for a triggered alert, including the action links if any.
+    pub fn payload(&self, alert: &TriggeredAlert) -> Value {
+        let mut body: Value = match &self.pseudonymizer {
+            Some(pseudonymizer) => self.format.render(&pseudonymizer.apply(alert)),
+            None => self.format.render(alert),
+        };
+        let Some(signer) = &self.actions else {
+            return body;
+        };
+        let links: Map<String, Value> = signer.links(alert, Utc::now());
+        match (self.format, body.as_object_mut()) {
+            (PayloadFormat::Event, Some(body)) if !links.is_empty() => {
+                body.insert("actions".to_string(), Value::Object(links));
+            },
+            (PayloadFormat::Flat, Some(body)) => {
+                body.extend(links.into_iter().map(|(action, link)| (format!("action_{}", action), link)));
+            },
+            _ => {},
+        }
+        body
+    }
+
+    /// Renders the body posted for the alerts a user triggered during a cycle, a versioned
+    /// grouped payload holding the body of every alert, see [`crate::events`].
+    ///
+    /// # Returns
+    /// `None` for formats other than `PayloadFormat::Event`, whose alerts are posted one by one.
+    pub fn batch_payload(&self, alerts: &[TriggeredAlert]) -> Option<Value> {
+        let first: &TriggeredAlert = alerts.first()?;
+        if self.format != PayloadFormat::Event {
+            return None;
+        }
+        let user_id: String = match &self.pseudonymizer {
+            Some(pseudonymizer) => pseudonymizer.pseudonymize(&first.user_id),
+            None => first.user_id.clone(),
+        };
+        Some(json!({
+            "schema_version": TRIGGER_BATCH_SCHEMA_VERSION,
+            "user_id": user_id,
+            "alerts": alerts.iter().map(|alert| self.payload(alert)).collect::<Vec<Value>>(),
+        }))
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn notify(&self, alert: &TriggeredAlert) -> Result<(), NotifyError> {
+        self.post(&self.payload(alert), vec![alert.hash.clone()]).await
+    }
+
+    /// Posts a single grouped payload, see [`WebhookNotifier::batch_payload`].
+    async fn notify_batch(&self, alerts: &[TriggeredAlert]) -> Result<(), NotifyError> {
+        match self.batch_payload(alerts) {
+            Some(body) => self.post(&body, alerts.iter().map(|alert| alert.hash.clone()).collect()).await,
+            None => notify_each(self, alerts).await,
+        }
+    }
+
+    async fn notify_stale(&self, alert: &Alert, age: Duration) -> Result<(), NotifyError> {
+        let user_id: String = match &self.pseudonymizer {
+            Some(pseudonymizer) => pseudonymizer.pseudonymize(&alert.user_id),
+            None => alert.user_id.clone(),
+        };
+        self.post(&json!({
+            "type": "alert_stale",
+            "hash": alert.hash,
+            "user_id": user_id,
+            "symbol": alert.symbol,
+            "price_level": alert.price_level,
+            "age_days": age.num_days(),
+        }), vec![alert.hash.clone()]).await
+    }
+
+    /// Sends a `HEAD` request to the webhook. Endpoints which only accept `POST` still pass,
+    /// only a rejected URL or credentials (`401`, `403`, `404`, `410`) or a server error fail.
+    async fn verify(&self) -> Result<(), NotifyError> {
+        let response = self.client
+            .head(&self.url)
+            .send()
+            .await
+            .map_err(|e| NotifyError::DeliveryError(e.to_string()))?;
+
+        match response.status().as_u16() {
+            401 | 403 | 404 | 410 => Err(NotifyError::ConfigurationError(format!(
+                "Webhook returned {}", response.status()
+            ))),
+            status if status >= 500 => Err(NotifyError::DeliveryError(format!("Webhook returned {}", response.status()))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Debug implementation for `WebhookNotifier` which never prints the secret.
+impl fmt::Debug for WebhookNotifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebhookNotifier")
+            .field("url", &self.url)
+            .field("format", &self.format)
+            .field("actions", &self.actions)
+            .field("pseudonymizer", &self.pseudonymizer)
+            .field("retry", &self.retry)
+            .field("timeouts", &self.timeouts)
+            .field("secret", &self.secret.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+/// Returns the value of the `SIGNATURE_HEADER` for a body, `sha256=` and the hex HMAC-SHA256.
+pub fn sign_body(
+    secret: &str,
+    body: &[u8]
+) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    let signature: String = mac.finalize().into_bytes().iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", signature)
+}
+
+/// ## A webhook URL whose host only resolves to public addresses, see [`resolve_public_url`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicUrl {
+    /// The host name the addresses were resolved for, `None` for IP literals.
+    pub host: Option<String>,
+    pub addrs: Vec<SocketAddr>,
+}
+
+/// Checks that a webhook URL is `http` or `https` and its host only resolves to public addresses,
+/// so alert metadata or user registrations can't make the crate post to internal services.
+///
+/// Loopback, private, link-local, shared, unspecified, multicast and documentation addresses
+/// are rejected. Connect to the returned addresses, see [`WebhookNotifier::pinned`], so a second
+/// DNS lookup can't resolve to another address.
+///
+/// # Errors
+/// Returns `NotifyError::ConfigurationError` if the URL is invalid or resolves to a non-public address,
+/// or `NotifyError::DeliveryError` if its host could not be resolved.
+pub async fn resolve_public_url(url: &str) -> Result<PublicUrl, NotifyError> {
+    let parsed: Url = Url::parse(url).map_err(|e| NotifyError::ConfigurationError(format!("Invalid webhook URL: {}", e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(NotifyError::ConfigurationError(format!("Unsupported webhook scheme: {}", parsed.scheme())));
+    }
+    let port: u16 = parsed
+        .port_or_known_default()
+        .ok_or_else(|| NotifyError::ConfigurationError("Webhook URL has no port".to_string()))?;
+
+    let host: &str = parsed
+        .host_str()
+        .ok_or_else(|| NotifyError::ConfigurationError("Webhook URL has no host".to_string()))?;
+    let (host, addrs): (Option<String>, Vec<SocketAddr>) = match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => (None, vec![SocketAddr::new(ip, port)]),
+        Err(_) => {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+                .await
+                .map_err(|e| NotifyError::DeliveryError(format!("Could not resolve {}: {}", host, e)))?
+                .collect();
+            (Some(host.to_string()), addrs)
+        },
+    };
+
+    if addrs.is_empty() {
+        return Err(NotifyError::DeliveryError(format!("{} did not resolve to any address", url)));
+    }
+    if let Some(addr) = addrs.iter().find(|addr| !is_public_address(&addr.ip())) {
+        return Err(NotifyError::ConfigurationError(format!("Webhook host resolves to non-public address {}", addr.ip())));
+    }
+    Ok(PublicUrl { host, addrs })
+}
+
+/// Whether an address is reachable on the public internet, see [`resolve_public_url`].
+pub fn is_public_address(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                // Shared address space (100.64.0.0/10) and "this network" (0.0.0.0/8)
+                || (a == 100 && (64..128).contains(&b))
+                || a == 0)
+        },
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_address(&IpAddr::V4(mapped)),
+            None => {
+                let first: u16 = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // Unique local (fc00::/7) and link-local (fe80::/10)
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            },
+        },
+    }
+}
+
+impl WebhookNotifier {
+    /// Connects to the addresses a URL was checked to resolve to, see [`resolve_public_url`].
+    ///
+    /// Redirects are not followed, since their targets were never checked to be public.
+    ///
+    /// # Errors
+    /// Returns `NotifyError::ConfigurationError` if the HTTP client could not be built.
+    pub fn pinned(
+        mut self,
+        resolved: &PublicUrl
+    ) -> Result<Self, NotifyError> {
+        self.pinned = Some(resolved.clone());
+        self.client = self.build_client()?;
+        Ok(self)
+    }
+
+    /// Builds the HTTP client with the timeouts and the pinned addresses, if any.
+    fn build_client(&self) -> Result<reqwest::Client, NotifyError> {
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(self.timeouts.connect)
+            .read_timeout(self.timeouts.read);
+        if let Some(PublicUrl { host, addrs }) = &self.pinned {
+            builder = builder.redirect(reqwest::redirect::Policy::none());
+            if let Some(host) = host {
+                builder = builder.resolve_to_addrs(host, addrs);
+            }
+        }
+        builder.build().map_err(|e| NotifyError::ConfigurationError(e.to_string()))
+    }
+
+    /// Posts a body for the alerts with `hashes`, retrying `5xx` responses and network errors,
+    /// and records the outcome.
+    async fn post(
+        &self,
+        body: &Value,
+        hashes: Vec<String>
+    ) -> Result<(), NotifyError> {
+        let body: Vec<u8> = serde_json::to_vec(body).map_err(|e| NotifyError::DeliveryError(e.to_string()))?;
+        let attempts: AtomicU32 = AtomicU32::new(0);
+        let outcome: Result<u16, (Option<u16>, String)> = self.retry
+            .run(
+                || {
+                    attempts.fetch_add(1, Ordering::Relaxed);
+                    self.attempt(&body)
+                },
+                |(status, _)| status.is_none_or(|status| status >= 500)
+            )
+            .await;
+
+        let (status, error): (Option<u16>, Option<String>) = match &outcome {
+            Ok(status) => (Some(*status), None),
+            Err((status, error)) => (*status, Some(error.clone())),
+        };
+        {
+            let mut deliveries = self.deliveries.lock().unwrap_or_else(|e| e.into_inner());
+            deliveries.push_back(WebhookDelivery {
+                url: self.url.clone(),
+                hashes,
+                status,
+                attempts: attempts.into_inner(),
+                error: error.clone(),
+                delivered_at: Utc::now(),
+            });
+            while deliveries.len() > RECENT_DELIVERIES {
+                deliveries.pop_front();
+            }
+        }
+        match error {
+            Some(error) => Err(NotifyError::DeliveryError(error)),
+            None => Ok(()),
+        }
+    }
+
+    /// Posts a body once, returning the status, or the status if any and why the post failed.
+    async fn attempt(&self, body: &[u8]) -> Result<u16, (Option<u16>, String)> {
+        let mut request = self.client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, sign_body(secret, body));
+        }
+        let response = request
+            .body(body.to_vec())
+            .send()
+            .await
+            .map_err(|e| (None, e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err((Some(status.as_u16()), format!("Webhook returned {}", status)));
+        }
+        Ok(status.as_u16())
+    }
+}
@@ -0,0 +1,330 @@
+//! ## HTTP recording and playback
+//!
+//! Records the responses of the price providers to a cassette file once and replays them
+//! afterwards, so integration tests run against real response shapes without the network.
+//!
+//! | Mode     | Behaviour                                                              |
+//! |----------|------------------------------------------------------------------------|
+//! | `Record` | Sends every request and appends the response to the cassette            |
+//! | `Replay` | Answers every request from the cassette, unknown requests fail          |
+//! | `Auto`   | Replays when the cassette exists and records otherwise                  |
+//!
+//! Query parameters holding credentials, such as `api_key`, are redacted before a request is
+//! recorded or matched, so cassettes can be committed.
+
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::data::{AlpacaProvider, BinanceProvider, CoinbaseProvider, XylexApi};
+use crate::errors::XylexApiError;
+
+/// Query parameters whose values are redacted in cassettes.
+pub const SECRET_PARAMS: &[&str] = &["api_key", "apikey", "key", "token", "access_token", "signature"];
+
+/// Value recorded in place of a secret query parameter.
+pub const REDACTED: &str = "REDACTED";
+
+/// ## Whether requests are recorded or replayed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReplayMode {
+    /// Sends every request and records the response.
+    Record,
+    /// Answers every request from the cassette.
+    Replay,
+    /// Replays when the cassette exists and records otherwise.
+    #[default]
+    Auto,
+}
+
+/// ## A recorded request and its response
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    /// The request URL with secret query parameters redacted.
+    pub url: String,
+    pub status: u16,
+    pub body: Value,
+}
+
+/// ## The interactions stored in a cassette file
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+/// ## Records or replays the HTTP requests of a provider
+///
+/// Clones share the cassette, so a recording made through one clone is replayed by all.
+#[derive(Clone, Debug)]
+pub struct HttpReplay {
+    path: PathBuf,
+    mode: ReplayMode,
+    state: Arc<Mutex<ReplayState>>,
+}
+
+#[derive(Debug, Default)]
+struct ReplayState {
+    cassette: Cassette,
+    /// Whether each interaction was already replayed, repeated requests replay in recorded order.
+    used: Vec<bool>,
+}
+
+impl HttpReplay {
+    /// Opens the cassette at `path`, replaying it when the file exists and recording it otherwise.
+    ///
+    /// # Errors
+    /// Returns `XylexApiError::ConfigurationError` if the cassette exists but cannot be read.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, XylexApiError> {
+        Self::with_mode(path, ReplayMode::Auto)
+    }
+
+    /// Records a new cassette at `path`, replacing the file once the first response is recorded.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            mode: ReplayMode::Record,
+            state: Default::default(),
+        }
+    }
+
+    /// Replays the cassette at `path`.
+    ///
+    /// # Errors
+    /// Returns `XylexApiError::ConfigurationError` if the cassette cannot be read.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, XylexApiError> {
+        Self::with_mode(path, ReplayMode::Replay)
+    }
+
+    /// Opens the cassette at `path` in the mode set by `REPLAY_HTTP` (`record`, `replay` or `auto`),
+    /// defaulting to `auto`.
+    ///
+    /// # Errors
+    /// Returns `XylexApiError::ConfigurationError` if the mode is unknown or the cassette cannot be read.
+    pub fn from_env(path: impl Into<PathBuf>) -> Result<Self, XylexApiError> {
+        let mode: ReplayMode = match std::env::var("REPLAY_HTTP") {
+            Ok(mode) => mode.parse().map_err(XylexApiError::ConfigurationError)?,
+            Err(_) => ReplayMode::Auto,
+        };
+        Self::with_mode(path, mode)
+    }
+
+    /// Opens the cassette at `path` in `mode`.
+    ///
+    /// # Errors
+    /// Returns `XylexApiError::ConfigurationError` if the cassette is replayed but cannot be read.
+    pub fn with_mode(
+        path: impl Into<PathBuf>,
+        mode: ReplayMode
+    ) -> Result<Self, XylexApiError> {
+        let path: PathBuf = path.into();
+        let mode: ReplayMode = match mode {
+            ReplayMode::Auto if path.exists() => ReplayMode::Replay,
+            ReplayMode::Auto => ReplayMode::Record,
+            mode => mode,
+        };
+        if mode == ReplayMode::Record {
+            return Ok(Self::record(path));
+        }
+
+        let cassette: Cassette = read_cassette(&path)?;
+        let used: Vec<bool> = vec![false; cassette.interactions.len()];
+        Ok(Self {
+            path,
+            mode,
+            state: Arc::new(Mutex::new(ReplayState { cassette, used })),
+        })
+    }
+
+    /// Returns the cassette file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns `ReplayMode::Record` or `ReplayMode::Replay`, `Auto` is resolved when opening.
+    pub fn mode(&self) -> ReplayMode {
+        self.mode
+    }
+
+    /// Returns the recorded interactions.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.lock().cassette.interactions.clone()
+    }
+
+    /// Sends a request through the cassette.
+    ///
+    /// When recording, `send` is awaited and its status and body are recorded. When replaying,
+    /// the first unused interaction with the same method and URL is returned instead.
+    ///
+    /// # Errors
+    /// Returns the error of `send`, or `XylexApiError::UnexpectedError` if no recorded interaction
+    /// matches or the cassette cannot be written.
+    pub async fn exchange<F>(
+        &self,
+        method: &str,
+        url: &str,
+        send: F
+    ) -> Result<(u16, Value), XylexApiError>
+    where
+        F: Future<Output = Result<(u16, Value), XylexApiError>>,
+    {
+        let url: String = redact_url(url);
+        if self.mode == ReplayMode::Replay {
+            let mut state = self.lock();
+            let ReplayState { cassette, used } = &mut *state;
+            let index: usize = cassette
+                .interactions
+                .iter()
+                .enumerate()
+                .position(|(index, interaction)| {
+                    !used[index] && interaction.method == method && interaction.url == url
+                })
+                .ok_or_else(|| {
+                    XylexApiError::UnexpectedError(format!(
+                        "No recorded response for {} {} in {}",
+                        method,
+                        url,
+                        self.path.display()
+                    ))
+                })?;
+            used[index] = true;
+            let interaction: &Interaction = &cassette.interactions[index];
+            return Ok((interaction.status, interaction.body.clone()));
+        }
+
+        let (status, body) = send.await?;
+        let mut state = self.lock();
+        state.cassette.interactions.push(Interaction {
+            method: method.to_string(),
+            url,
+            status,
+            body: body.clone(),
+        });
+        state.used.push(true);
+        write_cassette(&self.path, &state.cassette)?;
+        Ok((status, body))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ReplayState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl XylexApi {
+    /// Records or replays every price request through `replay`.
+    ///
+    /// # Arguments
+    /// * `replay` - The `HttpReplay` holding the cassette.
+    ///
+    /// # Returns
+    /// Returns the `XylexApi` instance with the replay applied.
+    pub fn with_replay(
+        mut self,
+        replay: HttpReplay
+    ) -> Self {
+        self.replay = Some(replay);
+        self
+    }
+}
+
+impl BinanceProvider {
+    /// Records or replays every ticker request through `replay`.
+    pub fn with_replay(
+        mut self,
+        replay: HttpReplay
+    ) -> Self {
+        self.replay = Some(replay);
+        self
+    }
+}
+
+impl CoinbaseProvider {
+    /// Records or replays every market request through `replay`.
+    pub fn with_replay(
+        mut self,
+        replay: HttpReplay
+    ) -> Self {
+        self.replay = Some(replay);
+        self
+    }
+}
+
+impl AlpacaProvider {
+    /// Records or replays every market data request through `replay`.
+    pub fn with_replay(
+        mut self,
+        replay: HttpReplay
+    ) -> Self {
+        self.replay = Some(replay);
+        self
+    }
+}
+
+impl PartialEq for HttpReplay {
+    /// Two replays are equal when they use the same cassette file in the same mode.
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path && self.mode == other.mode
+    }
+}
+
+/// Redacts the values of the `SECRET_PARAMS` in a URL, other URLs are returned as is.
+pub fn redact_url(url: &str) -> String {
+    let Ok(mut parsed) = reqwest::Url::parse(url) else {
+        return url.to_string();
+    };
+    if parsed.query().is_none() {
+        return url.to_string();
+    }
+
+    let pairs: Vec<(String, String)> = parsed
+        .query_pairs()
+        .map(|(name, value)| match SECRET_PARAMS.contains(&name.to_ascii_lowercase().as_str()) {
+            true => (name.into_owned(), REDACTED.to_string()),
+            false => (name.into_owned(), value.into_owned()),
+        })
+        .collect();
+    parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    parsed.to_string()
+}
+
+fn read_cassette(path: &Path) -> Result<Cassette, XylexApiError> {
+    let contents: String = fs::read_to_string(path).map_err(|e| {
+        XylexApiError::ConfigurationError(format!("Failed to read cassette {}: {}", path.display(), e))
+    })?;
+    serde_json::from_str(&contents).map_err(|e| {
+        XylexApiError::ConfigurationError(format!("Invalid cassette {}: {}", path.display(), e))
+    })
+}
+
+fn write_cassette(
+    path: &Path,
+    cassette: &Cassette
+) -> Result<(), XylexApiError> {
+    let contents: String = serde_json::to_string_pretty(cassette)
+        .map_err(|e| XylexApiError::UnexpectedError(format!("Failed to serialize cassette: {}", e)))?;
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| {
+            XylexApiError::UnexpectedError(format!("Failed to create {}: {}", parent.display(), e))
+        })?;
+    }
+    fs::write(path, contents)
+        .map_err(|e| XylexApiError::UnexpectedError(format!("Failed to write cassette {}: {}", path.display(), e)))
+}
+
+impl FromStr for ReplayMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "record" => Ok(ReplayMode::Record),
+            "replay" => Ok(ReplayMode::Replay),
+            "auto" => Ok(ReplayMode::Auto),
+            other => Err(format!("Unknown replay mode `{}`, expected record, replay or auto", other)),
+        }
+    }
+}
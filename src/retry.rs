@@ -0,0 +1,120 @@
+//! ## Retrying requests with exponential backoff
+//!
+//! Used by `XylexApi` around price requests and by `Supabase` around reads and
+//! idempotent writes, so a single network blip doesn't abort a whole check cycle.
+
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// ## How often and how patiently a failed request is retried
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts including the first one, `1` disables retrying.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between two attempts, before jitter.
+    pub max_backoff: Duration,
+    /// Factor applied to the delay after every retry.
+    pub multiplier: f64,
+    /// Fraction of the delay added or removed at random, e.g. `0.2` for ±20%.
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, starting at 200ms and doubling up to 5s, with ±20% jitter.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy which never retries.
+    pub fn none() -> Self {
+        Self { max_attempts: 1, ..Self::default() }
+    }
+
+    /// Sets the number of attempts including the first one, at least `1`.
+    pub fn with_max_attempts(
+        mut self,
+        max_attempts: u32
+    ) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the delay before the first retry and its upper bound.
+    pub fn with_backoff(
+        mut self,
+        initial_backoff: Duration,
+        max_backoff: Duration
+    ) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff.max(initial_backoff);
+        self
+    }
+
+    /// Sets the fraction of the delay added or removed at random, clamped to `0.0..=1.0`.
+    pub fn with_jitter(
+        mut self,
+        jitter: f64
+    ) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Returns the delay before retry number `retry`, starting at `1`, without jitter.
+    pub fn backoff(
+        &self,
+        retry: u32
+    ) -> Duration {
+        let factor: f64 = self.multiplier.max(1.0).powi(retry.saturating_sub(1) as i32);
+        self.initial_backoff.mul_f64(factor).min(self.max_backoff)
+    }
+
+    /// Runs `operation` until it succeeds, fails with an error `is_retryable` rejects,
+    /// or `max_attempts` is reached.
+    ///
+    /// # Returns
+    /// Returns the first success, or the error of the last attempt.
+    pub async fn run<T, E, F, Fut>(
+        &self,
+        mut operation: F,
+        is_retryable: impl Fn(&E) -> bool
+    ) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt: u32 = 1;
+        loop {
+            match operation().await {
+                Err(e) if attempt < self.max_attempts && is_retryable(&e) => {
+                    tokio::time::sleep(self.jittered(self.backoff(attempt))).await;
+                    attempt += 1;
+                },
+                result => return result,
+            }
+        }
+    }
+
+    fn jittered(
+        &self,
+        delay: Duration
+    ) -> Duration {
+        if self.jitter <= 0.0 {
+            return delay;
+        }
+        // Every `RandomState` is seeded differently, good enough to spread out retries
+        let random: f64 = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+        delay.mul_f64(1.0 + self.jitter * (random * 2.0 - 1.0))
+    }
+}
@@ -0,0 +1,474 @@
+//! ## Background scheduling
+//!
+//! [`AlertScheduler`] runs the check, trigger, dispatch and cleanup cycle of a [`TradeAlerts`]
+//! system on a tokio task and stops it gracefully through a [`CancellationToken`], so the
+//! same token can shut down the scheduler together with the rest of an application.
+//! [`SchedulerHandle::shutdown`] drains the cycle in progress, e.g. before a rolling deploy
+//! replaces the process.
+//! Applications embedding the scheduler can react to triggered alerts without polling the
+//! database through [`AlertScheduler::subscribe`].
+//!
+//! Symbols can be checked at different frequencies with a [`ScheduleConfig`], e.g. crypto
+//! every 2 seconds, FX every 5 and equities every 30. Cycles then run at the greatest common
+//! divisor of the intervals, but at least [`MIN_SCHEDULE_TICK`], and each cycle only fetches
+//! the groups which are due.
+//! Cycles can also be limited to the minutes matching a cron expression with
+//! [`AlertScheduler::with_cycle_schedule`], e.g. `Schedule::Cron("*/5 9-17 * * MON-FRI".into())`.
+//!
+//! ```rust,no_run
+//! # async fn run(supabase: trade_alerts::db::Supabase, config: trade_alerts::db::TableConfig) {
+//! use std::time::Duration;
+//! use trade_alerts::AlertScheduler;
+//! use trade_alerts::data::MockProvider;
+//!
+//! let scheduler = AlertScheduler::new(MockProvider::new(), supabase, config)
+//!     .with_interval(Duration::from_secs(30));
+//! let token = scheduler.cancellation_token();
+//!
+//! let handle = scheduler.run().expect("valid configuration");
+//! tokio::signal::ctrl_c().await.unwrap();
+//! let summary = handle.shutdown().await;
+//! println!("Drained {} triggered alerts", summary.drained.len());
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use futures_util::Stream;
+use tokio::sync::{Notify, broadcast};
+use tokio::task::JoinHandle;
+pub use tokio_util::sync::CancellationToken;
+
+use crate::{AlertScheduler, EngineStatus, Health, ResourceLimits, Schedule, ScheduleConfig, SchedulerHandle, ShutdownSummary, TradeAlerts, TradeAlertsBuilder, TriggeredAlert};
+use crate::engine::{TRIGGER_CHANNEL_CAPACITY, trigger_stream};
+use crate::config::parse_duration;
+use crate::data::PriceProvider;
+use crate::db::{HeartbeatTable, LeaseTable, ShardTable, Supabase, TableConfig};
+use crate::errors::ConfigError;
+use crate::market_hours::AssetClass;
+use crate::notify::{NotificationRouter, Notifier};
+use crate::utils::cron::CronExpression;
+use crate::utils::symbol::canonical;
+
+/// Shortest time between two cycles of a [`ScheduleConfig`], unless an interval is shorter.
+///
+/// Intervals such as 1000ms and 1001ms would otherwise have a greatest common divisor of 1ms.
+pub const MIN_SCHEDULE_TICK: Duration = Duration::from_millis(250);
+
+impl AlertScheduler {
+    /// Creates a scheduler checking the alerts of `config` in `store` against quotes from `provider`.
+    ///
+    /// Cycles run every [`crate::engine::DEFAULT_CHECK_INTERVAL`] unless another interval is set.
+    pub fn new(
+        provider: impl PriceProvider + 'static,
+        store: Supabase,
+        config: TableConfig
+    ) -> Self {
+        Self {
+            builder: TradeAlertsBuilder {
+                events: Some(broadcast::channel(TRIGGER_CHANNEL_CAPACITY).0),
+                ..TradeAlerts::builder().with_provider(provider).with_supabase(store).with_table_config(config)
+            },
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// Returns a stream of the alerts the scheduler triggers once it runs, so an application
+    /// can react to them in-process instead of polling the history table.
+    ///
+    /// See [`TradeAlerts::subscribe`] for when alerts are sent and what a lagging subscriber misses.
+    pub fn subscribe(&self) -> impl Stream<Item = TriggeredAlert> + Send + 'static {
+        let events: &broadcast::Sender<TriggeredAlert> = self.builder.events.as_ref().expect("A scheduler sets the event channel");
+        trigger_stream(events.subscribe())
+    }
+
+    /// Sets the time between two cycles.
+    pub fn with_interval(
+        mut self,
+        interval: Duration
+    ) -> Self {
+        self.builder = self.builder.with_interval(interval);
+        self
+    }
+
+    /// Slows cycles down while no alert is active, see [`crate::TradeAlertsBuilder::with_idle_interval`].
+    pub fn with_idle_interval(
+        mut self,
+        idle_interval: Duration
+    ) -> Self {
+        self.builder = self.builder.with_idle_interval(idle_interval);
+        self
+    }
+
+    /// Runs cycles every interval or at the minutes matching a cron expression,
+    /// see [`crate::TradeAlertsBuilder::with_cycle_schedule`].
+    pub fn with_cycle_schedule(
+        mut self,
+        schedule: Schedule
+    ) -> Self {
+        self.builder = self.builder.with_cycle_schedule(schedule);
+        self
+    }
+
+    /// Bounds the caches kept across cycles, see [`crate::TradeAlertsBuilder::with_resource_limits`].
+    pub fn with_resource_limits(
+        mut self,
+        limits: ResourceLimits
+    ) -> Self {
+        self.builder = self.builder.with_resource_limits(limits);
+        self
+    }
+
+    /// Keeps the price state across restarts, see [`crate::TradeAlertsBuilder::with_price_snapshot_file`].
+    pub fn with_price_snapshot_file(
+        mut self,
+        path: impl Into<std::path::PathBuf>
+    ) -> Self {
+        self.builder = self.builder.with_price_snapshot_file(path);
+        self
+    }
+
+    /// Claims triggered alerts before delivering them, see [`crate::TradeAlertsBuilder::with_two_phase_triggers`].
+    pub fn with_two_phase_triggers(mut self) -> Self {
+        self.builder = self.builder.with_two_phase_triggers();
+        self
+    }
+
+    /// Delivers a user's triggers per cycle together, see [`crate::TradeAlertsBuilder::with_grouped_notifications`].
+    pub fn with_grouped_notifications(mut self) -> Self {
+        self.builder = self.builder.with_grouped_notifications();
+        self
+    }
+
+    /// Only reports what would have fired, see [`crate::TradeAlertsBuilder::with_dry_run`].
+    pub fn with_dry_run(mut self) -> Self {
+        self.builder = self.builder.with_dry_run();
+        self
+    }
+
+    /// Runs cycles only while holding the leader lease, see [`crate::TradeAlertsBuilder::with_leader_lease`].
+    pub fn with_leader_lease(
+        mut self,
+        table: LeaseTable,
+        instance_id: &str
+    ) -> Self {
+        self.builder = self.builder.with_leader_lease(table, instance_id);
+        self
+    }
+
+    /// Checks only this worker's share of the symbols, see [`crate::TradeAlertsBuilder::with_sharding`].
+    pub fn with_sharding(
+        mut self,
+        leases: LeaseTable,
+        assignments: ShardTable,
+        instance_id: &str
+    ) -> Self {
+        self.builder = self.builder.with_sharding(leases, assignments, instance_id);
+        self
+    }
+
+    /// Writes the health to a heartbeat row after every cycle, see [`crate::TradeAlertsBuilder::with_heartbeat`].
+    pub fn with_heartbeat(
+        mut self,
+        table: HeartbeatTable,
+        instance_id: &str
+    ) -> Self {
+        self.builder = self.builder.with_heartbeat(table, instance_id);
+        self
+    }
+
+    /// Sets how long the next cycle can be overdue before the scheduler counts as stuck,
+    /// see [`SchedulerHandle::health`].
+    pub fn with_stall_threshold(
+        mut self,
+        stall_after: Duration
+    ) -> Self {
+        self.builder = self.builder.with_stall_threshold(stall_after);
+        self
+    }
+
+    /// Delivers every triggered alert through a notifier, see [`crate::TradeAlertsBuilder::with_notifier`].
+    pub fn with_notifier(
+        mut self,
+        notifier: impl Notifier + 'static
+    ) -> Self {
+        self.builder = self.builder.with_notifier(notifier);
+        self
+    }
+
+    /// Routes triggered alerts with a preconfigured router.
+    pub fn with_router(
+        mut self,
+        router: NotificationRouter
+    ) -> Self {
+        self.builder = self.builder.with_router(router);
+        self
+    }
+
+    /// Archives triggered alerts to a history table instead of deleting them.
+    pub fn with_history(
+        mut self,
+        history_config: TableConfig
+    ) -> Self {
+        self.builder = self.builder.with_history(history_config);
+        self
+    }
+
+    /// Checks symbols at the intervals of `schedule` instead of all of them every interval.
+    ///
+    /// Cycles run every [`ScheduleConfig::tick`] unless another interval is set.
+    pub fn with_schedule(
+        mut self,
+        schedule: ScheduleConfig
+    ) -> Self {
+        self.builder = self.builder.with_schedule(schedule);
+        self
+    }
+
+    /// Stops the scheduler when `token` is cancelled, e.g. a child of the application's token.
+    pub fn with_cancellation_token(
+        mut self,
+        token: CancellationToken
+    ) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Returns the token which stops the scheduler when cancelled.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// Spawns the task running a cycle every interval, the first one immediately.
+    ///
+    /// Cancelling the token lets a cycle in progress finish, then the task ends.
+    /// A failed cycle is logged and retried on the next tick, see [`TradeAlerts::run_until`].
+    ///
+    /// # Errors
+    /// Returns `ConfigError::InvalidField` for a zero interval.
+    pub fn run(self) -> Result<SchedulerHandle, ConfigError> {
+        let alerts: TradeAlerts = self.builder.build()?;
+        let status: Arc<Mutex<EngineStatus>> = alerts.status.clone();
+        let stall_after: Duration = alerts.stall_after;
+        let events: broadcast::Sender<TriggeredAlert> = alerts.events.clone();
+        let wake: Arc<Notify> = alerts.wake.clone();
+        alerts.schedule_next(alerts.first_delay());
+        let task: JoinHandle<ShutdownSummary> = tokio::spawn(alerts.run_until(self.cancel.clone().cancelled_owned()));
+
+        Ok(SchedulerHandle { cancel: self.cancel, status, stall_after, events, wake, task })
+    }
+}
+
+impl SchedulerHandle {
+    /// Returns the token which stops the scheduler when cancelled.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// Returns a snapshot of the last cycles.
+    pub fn status(&self) -> EngineStatus {
+        self.status.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Returns the health of the scheduler, stuck once the next cycle is overdue by more than
+    /// the stall threshold, see [`EngineStatus::health`].
+    pub fn health(&self) -> Health {
+        self.status().health(chrono::Utc::now(), self.stall_after)
+    }
+
+    /// Returns a stream of the alerts triggered from now on, see [`AlertScheduler::subscribe`].
+    pub fn subscribe(&self) -> impl Stream<Item = TriggeredAlert> + Send + 'static {
+        trigger_stream(self.events.subscribe())
+    }
+
+    /// Runs the next cycle now if the scheduler is idle, see [`TradeAlerts::wake`].
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Whether the scheduler has stopped, e.g. because its token was cancelled.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Stops starting cycles and waits for the cycle in progress to finish, including its
+    /// notifications and the archival or deletion of its triggered alerts.
+    ///
+    /// # Returns
+    /// What was in flight, see [`ShutdownSummary`].
+    pub async fn shutdown(self) -> ShutdownSummary {
+        self.cancel.cancel();
+        self.wait().await
+    }
+
+    /// Waits for the scheduler to stop, e.g. after its token was cancelled elsewhere.
+    pub async fn wait(self) -> ShutdownSummary {
+        let started: Instant = Instant::now();
+        let mut summary: ShutdownSummary = match self.task.await {
+            Ok(summary) => summary,
+            Err(e) => {
+                println!("Scheduler task failed: {}", e);
+                ShutdownSummary { status: self.status.lock().unwrap_or_else(|e| e.into_inner()).clone(), ..ShutdownSummary::default() }
+            },
+        };
+        summary.waited = started.elapsed();
+        summary
+    }
+}
+
+impl Schedule {
+    /// Checks the interval is not zero and the cron expression parses.
+    ///
+    /// # Errors
+    /// Returns `ConfigError::InvalidField` naming the `schedule` otherwise.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: String| ConfigError::InvalidField { field: "schedule".to_string(), reason };
+        match self {
+            Schedule::Interval(interval) if interval.is_zero() => Err(invalid("interval must be greater than zero".to_string())),
+            Schedule::Interval(_) => Ok(()),
+            Schedule::Cron(expression) => expression.parse::<CronExpression>().map(|_| ()).map_err(invalid),
+        }
+    }
+}
+
+/// Parses a duration such as `"30s"`, see [`crate::config::parse_duration`], or a cron
+/// expression such as `"*/5 9-17 * * MON-FRI"`, told apart by the spaces between cron fields.
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s: &str = s.trim();
+        match s.contains(char::is_whitespace) {
+            true => s.parse::<CronExpression>().map(|_| Schedule::Cron(s.to_string())),
+            false => parse_duration(s).map(Schedule::Interval),
+        }
+    }
+}
+
+impl ScheduleConfig {
+    /// Creates a schedule checking every symbol every `default_interval`.
+    pub fn new(default_interval: Duration) -> Self {
+        Self {
+            default_interval,
+            asset_classes: HashMap::new(),
+            symbols: HashMap::new(),
+            last_checked: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Checks the symbols of an asset class, see [`AssetClass::of`], every `interval`.
+    pub fn with_asset_class(
+        mut self,
+        asset_class: AssetClass,
+        interval: Duration
+    ) -> Self {
+        self.asset_classes.insert(asset_class, interval);
+        self
+    }
+
+    /// Checks a single symbol every `interval`, over the interval of its asset class.
+    pub fn with_symbol(
+        mut self,
+        symbol: &str,
+        interval: Duration
+    ) -> Self {
+        self.symbols.insert(canonical(symbol), interval);
+        self
+    }
+
+    /// Returns the interval a symbol is checked at.
+    pub fn interval_for(&self, symbol: &str) -> Duration {
+        self.symbols
+            .get(&canonical(symbol))
+            .or_else(|| self.asset_classes.get(&AssetClass::of(symbol)))
+            .copied()
+            .unwrap_or(self.default_interval)
+    }
+
+    /// Returns the time between two cycles, the greatest common divisor of all intervals,
+    /// so every group is checked on time.
+    ///
+    /// The tick is at least [`MIN_SCHEDULE_TICK`], or the shortest interval if that is shorter,
+    /// so unrelated intervals don't make cycles run every millisecond. Groups are then checked
+    /// up to half a tick late or early, see [`ScheduleConfig::take_due`].
+    pub fn tick(&self) -> Duration {
+        let intervals = || self.asset_classes.values().chain(self.symbols.values());
+        let millis: u128 = intervals().fold(self.default_interval.as_millis(), |tick, interval| gcd(tick, interval.as_millis()));
+        let shortest: Duration = intervals().copied().fold(self.default_interval, Duration::min);
+        Duration::from_millis(millis.max(1) as u64).max(MIN_SCHEDULE_TICK.min(shortest))
+    }
+
+    /// Buckets symbols into groups by the interval they are checked at.
+    pub fn groups(&self, symbols: impl IntoIterator<Item = String>) -> BTreeMap<Duration, Vec<String>> {
+        let mut groups: BTreeMap<Duration, Vec<String>> = BTreeMap::new();
+        for symbol in symbols {
+            groups.entry(self.interval_for(&symbol)).or_default().push(symbol);
+        }
+        groups
+    }
+
+    /// Splits symbols into those whose group is due at `now` and those which are not.
+    ///
+    /// A group is due once its interval passed since it was last checked, minus half a tick
+    /// so cycles finishing slightly early don't push a group back by a whole tick.
+    /// Groups stay due until [`ScheduleConfig::mark_checked`] records their quotes were fetched.
+    pub(crate) fn take_due(
+        &self,
+        symbols: HashSet<String>,
+        now: Instant
+    ) -> (HashSet<String>, HashSet<String>) {
+        let (mut due, mut waiting): (HashSet<String>, HashSet<String>) = (HashSet::new(), HashSet::new());
+        for (interval, group) in self.groups(symbols) {
+            if self.is_interval_due(interval, now) {
+                due.extend(group);
+            } else {
+                waiting.extend(group);
+            }
+        }
+        (due, waiting)
+    }
+
+    /// Returns `true` if the group of `symbol` is due at `now`, see [`ScheduleConfig::take_due`].
+    pub fn is_due(
+        &self,
+        symbol: &str,
+        now: Instant
+    ) -> bool {
+        self.is_interval_due(self.interval_for(symbol), now)
+    }
+
+    fn is_interval_due(
+        &self,
+        interval: Duration,
+        now: Instant
+    ) -> bool {
+        let slack: Duration = self.tick() / 2;
+        self.last_checked
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&interval)
+            .is_none_or(|checked| now.saturating_duration_since(*checked) + slack >= interval)
+    }
+
+    /// Records the groups of the symbols whose quotes were fetched as checked at `now`.
+    pub(crate) fn mark_checked<'a>(
+        &self,
+        symbols: impl IntoIterator<Item = &'a str>,
+        now: Instant
+    ) {
+        let mut last_checked = self.last_checked.lock().unwrap_or_else(|e| e.into_inner());
+        for symbol in symbols {
+            last_checked.insert(self.interval_for(symbol), now);
+        }
+    }
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    match b {
+        0 => a,
+        b => gcd(b, a % b),
+    }
+}
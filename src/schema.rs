@@ -0,0 +1,106 @@
+//! ## JSON Schemas of the outgoing payloads
+//!
+//! Generated from the same types the payloads are serialized from, so webhook consumers can
+//! validate and generate code against exactly what this version of the crate emits. Every
+//! function returns a standalone draft-07 schema as JSON, ready to be published.
+//!
+//! Requires the `schema` feature.
+
+use schemars::JsonSchema;
+use schemars::schema::RootSchema;
+use serde_json::{Value, json};
+
+use crate::TriggeredAlert;
+use crate::events::{TRIGGER_BATCH_SCHEMA_VERSION, TRIGGERED_ALERT_SCHEMA_VERSION};
+use crate::notify::PayloadFormat;
+
+/// Body of the `Flat` payload, metadata keys are added with a `metadata_` prefix.
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct FlatPayload {
+    hash: String,
+    user_id: String,
+    symbol: String,
+    price_level: f64,
+    trigger_price: f64,
+    /// RFC 3339 timestamp.
+    triggered_at: String,
+    /// The type of the condition, e.g. `price_level`.
+    condition: String,
+    /// The same every time the trigger is delivered, see `TriggeredAlert::idempotency_key`.
+    idempotency_key: String,
+}
+
+/// Body of the `Ifttt` payload.
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct IftttPayload {
+    /// The symbol.
+    value1: String,
+    /// The trigger price.
+    value2: String,
+    /// The alert level.
+    value3: String,
+}
+
+/// The schema of a versioned trigger event, see [`TriggeredAlert::to_event`].
+pub fn triggered_alert_event_schema() -> Value {
+    let mut schema: Value = to_value(schemars::schema_for!(TriggeredAlert));
+    schema["title"] = json!("TriggeredAlertEvent");
+    schema["properties"]["schema_version"] = json!({
+        "description": "The version of the event schema, see the crate's `events` module.",
+        "type": "integer",
+        "const": TRIGGERED_ALERT_SCHEMA_VERSION,
+    });
+    schema["properties"]["idempotency_key"] = json!({
+        "description": "The same every time the trigger is delivered, see `TriggeredAlert::idempotency_key`.",
+        "type": "string",
+    });
+    if let Some(required) = schema["required"].as_array_mut() {
+        required.extend([json!("schema_version"), json!("idempotency_key")]);
+    }
+    schema
+}
+
+/// The schema of a user's grouped triggers, see [`crate::events::UserTriggers::to_event`], also
+/// posted by webhooks with grouped notifications, see [`crate::notify::WebhookNotifier::batch_payload`].
+pub fn user_triggers_event_schema() -> Value {
+    let mut alert: Value = triggered_alert_event_schema();
+    let definitions: Value = alert
+        .as_object_mut()
+        .and_then(|alert| alert.remove("definitions"))
+        .unwrap_or_else(|| json!({}));
+    if let Some(alert) = alert.as_object_mut() {
+        alert.remove("$schema");
+    }
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "UserTriggersEvent",
+        "type": "object",
+        "required": ["schema_version", "user_id", "alerts"],
+        "properties": {
+            "schema_version": {
+                "description": "The version of the grouped payload schema, see the crate's `events` module.",
+                "type": "integer",
+                "const": TRIGGER_BATCH_SCHEMA_VERSION,
+            },
+            "user_id": { "type": "string" },
+            "alerts": { "type": "array", "items": alert },
+        },
+        "definitions": definitions,
+    })
+}
+
+/// The schema of the webhook body sent in a `PayloadFormat`, see [`PayloadFormat::render`].
+pub fn payload_schema(format: PayloadFormat) -> Value {
+    match format {
+        PayloadFormat::Event => triggered_alert_event_schema(),
+        PayloadFormat::Flat => to_value(schemars::schema_for!(FlatPayload)),
+        PayloadFormat::Ifttt => to_value(schemars::schema_for!(IftttPayload)),
+    }
+}
+
+fn to_value(schema: RootSchema) -> Value {
+    serde_json::to_value(schema).unwrap_or(Value::Null)
+}
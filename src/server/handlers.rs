@@ -0,0 +1,479 @@
+//! ## Routes and command execution
+
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::Json;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use hmac::{Hmac, Mac};
+use serde_json::{Value, json};
+use sha2::Sha256;
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use crate::{Alert, AlertUpdate};
+use crate::actions::{ActionClaims, ActionSigner, AlertAction};
+use crate::commands::AlertCommand;
+use crate::data::PriceProvider;
+use crate::db::rest::{Filter, before, eq, ilike};
+use crate::db::{Supabase, TableConfig};
+use crate::errors::{ConfigError, SupabaseError};
+use crate::notify::router::NOTIFY_METADATA_KEY;
+use crate::server::{CommandServer, SLACK_MAX_REQUEST_AGE_SECS};
+use crate::utils::format::generate_hash;
+use crate::utils::symbol::{Symbol, same_symbol};
+
+impl CommandServer {
+    /// Creates a `CommandServer` serving neither `/slack` nor `/discord` until their secret
+    /// or key is set.
+    ///
+    /// # Parameters
+    /// - `provider`: The `PriceProvider` quoting the symbols of alerts created by commands.
+    pub fn new(
+        supabase: Supabase,
+        config: TableConfig,
+        provider: Arc<dyn PriceProvider>
+    ) -> Self {
+        Self {
+            supabase,
+            config,
+            provider,
+            slack_signing_secret: None,
+            discord_public_key: None,
+            hash_prefix: String::new(),
+            action_signer: None,
+            used_actions: Default::default(),
+        }
+    }
+
+    /// Serves `/slack`, verifying every request with the app's signing secret.
+    pub fn with_slack_signing_secret(
+        mut self,
+        secret: String
+    ) -> Self {
+        self.slack_signing_secret = Some(secret);
+        self
+    }
+
+    /// Serves `/discord`, verifying every interaction with the application's public key,
+    /// hex encoded as shown in the Discord developer portal.
+    ///
+    /// # Errors
+    /// Returns `ConfigError::InvalidField` if the key is not a valid hex encoded Ed25519 key.
+    pub fn with_discord_public_key(
+        mut self,
+        public_key: &str
+    ) -> Result<Self, ConfigError> {
+        let invalid = |reason: String| ConfigError::InvalidField { field: "discord_public_key".to_string(), reason };
+        let bytes: [u8; 32] = hex::decode(public_key.trim())
+            .map_err(|e| invalid(e.to_string()))?
+            .try_into()
+            .map_err(|_| invalid("Expected 32 bytes".to_string()))?;
+        self.discord_public_key = Some(VerifyingKey::from_bytes(&bytes).map_err(|e| invalid(e.to_string()))?);
+        Ok(self)
+    }
+
+    /// Runs the one-click actions of notification links, verifying their tokens with `signer`.
+    pub fn with_action_signer(
+        mut self,
+        signer: ActionSigner
+    ) -> Self {
+        self.action_signer = Some(signer);
+        self
+    }
+
+    /// Returns the router serving `POST /slack`, `POST /discord` and `/actions/{token}`.
+    ///
+    /// `/slack` and `/discord` are only mounted once their secret or key is set, so requests
+    /// are never accepted unverified.
+    ///
+    /// `GET /actions/{token}` only shows a confirmation form posting to the same URL, so link
+    /// scanners of mail providers can't run actions; `POST /actions/{token}` runs the action.
+    pub fn router(self) -> Router {
+        let mut router: Router<CommandServer> = Router::new().route("/actions/{token}", get(confirm_action).post(run_action));
+        if self.slack_signing_secret.is_some() {
+            router = router.route("/slack", post(slack));
+        }
+        if self.discord_public_key.is_some() {
+            router = router.route("/discord", post(discord));
+        }
+        router.with_state(self)
+    }
+
+    /// Runs a verified one-click action and returns the reply.
+    ///
+    /// The action acts on the alerts of the user named in the token only, created before
+    /// the token was issued. Muting a symbol keeps the alerts, see [`AlertAction::MuteSymbol`].
+    /// The symbol column is filtered server-side, so it must not be encrypted.
+    ///
+    /// # Errors
+    /// Returns the reply as an error if the action could not be run, e.g. because Supabase
+    /// could not be reached, so it can be tried again.
+    pub async fn execute_action(&self, claims: &ActionClaims) -> Result<String, String> {
+        let Some(signer) = &self.action_signer else {
+            return Ok("Actions are disabled".to_string());
+        };
+        match claims.action {
+            AlertAction::DeleteAlert => match self.supabase.fetch_alert_by_hash(&claims.hash, &self.config).await {
+                Ok(alert) if signer.resolve_owner(claims, [alert.user_id.as_str()]).is_some() => {
+                    match self.supabase.delete_alert_by_hash(&claims.hash, self.config.clone()).await {
+                        Ok(()) => Ok(format!("Deleted alert `{}`", claims.hash)),
+                        Err(e) => Err(format!("Failed to delete alert: {}", e)),
+                    }
+                },
+                Ok(_) => Ok(format!("Alert `{}` is not yours", claims.hash)),
+                Err(e) if is_not_found(e.as_ref()) => Ok(format!("Alert `{}` was already deleted", claims.hash)),
+                Err(e) => Err(format!("Failed to fetch alert: {}", e)),
+            },
+            AlertAction::MuteSymbol => {
+                let no_alerts_left: String = format!("No alerts left on {}", claims.symbol.to_uppercase());
+                let mut filters: Vec<Filter> = vec![
+                    before(&self.config.created_at_column_name, claims.issued_at),
+                    ilike(&self.config.symbol_column_name, &symbol_pattern(&claims.symbol)),
+                ];
+                // The owner is resolved among the owners of the alerts which could be muted
+                let mut owner_filters: Vec<Filter> = filters.clone();
+                owner_filters.push(("select".to_string(), self.config.user_id_column_name.clone()));
+                let owners: Vec<Value> = self.supabase
+                    .rest_select(&self.config.tablename, &owner_filters)
+                    .await
+                    .map_err(|e| format!("Failed to fetch alerts: {}", e))?;
+                let owners: Vec<&str> = owners
+                    .iter()
+                    .filter_map(|row| row.get(&self.config.user_id_column_name).and_then(|v| v.as_str()))
+                    .collect();
+                let Some(owner) = signer.resolve_owner(claims, owners) else {
+                    return Ok(no_alerts_left);
+                };
+
+                filters.push(eq(&self.config.user_id_column_name, &owner));
+                let rows: Vec<Value> = self.supabase
+                    .rest_select(&self.config.tablename, &filters)
+                    .await
+                    .map_err(|e| format!("Failed to fetch alerts: {}", e))?;
+                let mut alerts: Vec<Alert> = Vec::with_capacity(rows.len());
+                for mut row in rows {
+                    let alert: Alert = self.supabase.open_row(&self.config, &mut row).map_err(|e| e.to_string())
+                        .and_then(|()| Alert::from_row(&row, &self.config).map_err(|e| e.to_string()))
+                        .map_err(|e| format!("Failed to fetch alerts: {}", e))?;
+                    // The pattern also matches e.g. `EURXUSD`
+                    if alert.user_id == owner && same_symbol(&alert.symbol, &claims.symbol) {
+                        alerts.push(alert);
+                    }
+                }
+                if alerts.is_empty() {
+                    return Ok(no_alerts_left);
+                }
+
+                for alert in &alerts {
+                    let update: AlertUpdate = AlertUpdate { metadata: Some(muted(alert.metadata.clone())), ..AlertUpdate::default() };
+                    if let Err(e) = self.supabase.update_alert_by_hash(&alert.hash, update, self.config.clone()).await {
+                        return Err(format!("Failed to mute {}: {}", claims.symbol.to_uppercase(), e));
+                    }
+                }
+                Ok(format!("Muted {} alert(s) on {}", alerts.len(), claims.symbol.to_uppercase()))
+            },
+        }
+    }
+
+    /// Marks an action token used, returning `false` if it was used before.
+    ///
+    /// Used tokens are remembered until they expire, by this process only.
+    fn spend_action(
+        &self,
+        token: &str,
+        expires_at: DateTime<Utc>
+    ) -> bool {
+        let mut used = self.used_actions.lock().unwrap_or_else(|e| e.into_inner());
+        let now: DateTime<Utc> = Utc::now();
+        used.retain(|_, expires_at| *expires_at > now);
+        used.insert(token.trim().to_string(), expires_at).is_none()
+    }
+
+    /// Forgets that an action token was used, so an action which failed can be tried again.
+    fn release_action(&self, token: &str) {
+        self.used_actions.lock().unwrap_or_else(|e| e.into_inner()).remove(token.trim());
+    }
+
+    /// Runs a command for a user and returns the reply.
+    ///
+    /// # Parameters
+    /// - `user_id`: The owner of the alerts the command acts on.
+    /// - `text`: The command text, e.g. `alert eurusd above 1.10`.
+    pub async fn execute(
+        &self,
+        user_id: &str,
+        text: &str
+    ) -> String {
+        let command: AlertCommand = match text.parse() {
+            Ok(command) => command,
+            Err(e) => return e,
+        };
+
+        match command {
+            AlertCommand::Create { symbol, price_level, condition } => {
+                let hash: String = generate_hash(user_id, &symbol, price_level, &self.hash_prefix).await;
+                let alert: Alert = Alert::new(hash.clone(), price_level, symbol.clone(), user_id.to_string())
+                    .with_condition(condition);
+
+                match self.supabase.add_alert_with(self.provider.as_ref(), alert, self.config.clone()).await {
+                    Ok(_) => format!("Created alert `{}` on {} at {}", hash, symbol.to_uppercase(), price_level),
+                    Err(e) => format!("Failed to create alert: {}", e),
+                }
+            },
+            AlertCommand::Delete { hash } => {
+                // Only the owner may delete an alert
+                match self.supabase.fetch_alert_by_hash(&hash, &self.config).await {
+                    Ok(alert) if alert.user_id == user_id => {
+                        match self.supabase.delete_alert_by_hash(&hash, self.config.clone()).await {
+                            Ok(()) => format!("Deleted alert `{}`", hash),
+                            Err(e) => format!("Failed to delete alert: {}", e),
+                        }
+                    },
+                    _ => format!("No alert `{}` found", hash),
+                }
+            },
+            AlertCommand::List => {
+                match self.supabase.fetch_alerts_by_user_id(user_id, &self.config).await {
+                    Ok(alerts) if alerts.is_empty() => "You have no alerts".to_string(),
+                    Ok(alerts) => alerts
+                        .iter()
+                        .map(|alert| format!("`{}` {} at {}", alert.hash, alert.symbol.to_uppercase(), alert.price_level))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    Err(e) => format!("Failed to fetch alerts: {}", e),
+                }
+            },
+        }
+    }
+}
+
+/// Verifies a Slack request signature, `v0=hex(hmac_sha256(secret, "v0:<timestamp>:<body>"))`.
+///
+/// # Returns
+/// Returns `false` if the signature doesn't match or the timestamp is older than
+/// `SLACK_MAX_REQUEST_AGE_SECS` relative to `now` (Unix seconds).
+pub fn verify_slack_signature(
+    secret: &str,
+    timestamp: &str,
+    body: &[u8],
+    signature: &str,
+    now: i64
+) -> bool {
+    let Ok(sent_at) = timestamp.parse::<i64>() else {
+        return false;
+    };
+    if (now - sent_at).abs() > SLACK_MAX_REQUEST_AGE_SECS {
+        return false;
+    }
+    let Some(signature) = signature.strip_prefix("v0=").and_then(|hex| hex::decode(hex).ok()) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+
+    mac.update(format!("v0:{}:", timestamp).as_bytes());
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Verifies a Discord interaction signature, the hex Ed25519 signature of `<timestamp><body>`.
+///
+/// # Returns
+/// Returns `false` if the signature is malformed or doesn't match.
+pub fn verify_discord_signature(
+    public_key: &VerifyingKey,
+    timestamp: &str,
+    body: &[u8],
+    signature: &str
+) -> bool {
+    let Some(signature) = hex::decode(signature).ok().and_then(|bytes| Signature::from_slice(&bytes).ok()) else {
+        return false;
+    };
+    let mut message: Vec<u8> = timestamp.as_bytes().to_vec();
+    message.extend_from_slice(body);
+    public_key.verify(&message, &signature).is_ok()
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> &'a str {
+    headers.get(name).and_then(|v| v.to_str().ok()).unwrap_or_default()
+}
+
+async fn slack(
+    State(server): State<CommandServer>,
+    headers: HeaderMap,
+    body: Bytes
+) -> Response {
+    // The route is only mounted with a secret, refuse anyway should that ever change
+    let Some(secret) = &server.slack_signing_secret else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let verified: bool = verify_slack_signature(
+        secret,
+        header(&headers, "x-slack-request-timestamp"),
+        &body,
+        header(&headers, "x-slack-signature"),
+        chrono::Utc::now().timestamp(),
+    );
+    if !verified {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let form: Vec<(String, String)> = match serde_urlencoded::from_bytes(&body) {
+        Ok(form) => form,
+        Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+    };
+    let field = |name: &str| form.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str());
+
+    let Some(user_id) = field("user_id") else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    // `/alert eurusd above 1.10` arrives as command `/alert` with text `eurusd above 1.10`
+    let command: &str = field("command").unwrap_or_default().trim_start_matches('/');
+    let text: String = format!("{} {}", command, field("text").unwrap_or_default());
+
+    let reply: String = server.execute(&format!("slack:{}", user_id), &text).await;
+    Json(json!({ "response_type": "ephemeral", "text": reply })).into_response()
+}
+
+/// Verifies an action token, answering `404` when actions are disabled and `401` for invalid tokens.
+fn verify_action(
+    server: &CommandServer,
+    token: &str
+) -> Result<ActionClaims, (StatusCode, String)> {
+    let Some(signer) = &server.action_signer else {
+        return Err((StatusCode::NOT_FOUND, "Actions are disabled".to_string()));
+    };
+    signer
+        .verify(token, Utc::now())
+        .map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))
+}
+
+async fn confirm_action(
+    State(server): State<CommandServer>,
+    Path(token): Path<String>
+) -> Response {
+    let claims: ActionClaims = match verify_action(&server, &token) {
+        Ok(claims) => claims,
+        Err(response) => return response.into_response(),
+    };
+    let label: String = match claims.action {
+        AlertAction::MuteSymbol => format!("Mute {}", claims.symbol.to_uppercase()),
+        AlertAction::DeleteAlert => format!("Delete alert {}", claims.hash),
+    };
+    // The token is URL-safe base64 and the label only holds the symbol or hash, escaped below
+    Html(format!(
+        "<!doctype html><title>{label}</title><form method=\"post\"><button type=\"submit\">{label}</button></form>",
+        label = escape_html(&label)
+    ))
+    .into_response()
+}
+
+async fn run_action(
+    State(server): State<CommandServer>,
+    Path(token): Path<String>
+) -> Response {
+    match verify_action(&server, &token) {
+        // The token is spent before the action runs so concurrent clicks run it once
+        Ok(claims) if server.spend_action(&token, claims.expires_at) => match server.execute_action(&claims).await {
+            Ok(reply) => reply.into_response(),
+            Err(reply) => {
+                server.release_action(&token);
+                (StatusCode::BAD_GATEWAY, reply).into_response()
+            },
+        },
+        Ok(_) => (StatusCode::CONFLICT, "This link was already used").into_response(),
+        Err(response) => response.into_response(),
+    }
+}
+
+/// Adds the mute flag to an alert's metadata, keeping its other keys.
+fn muted(metadata: Option<Value>) -> Value {
+    let mut metadata: Value = match metadata {
+        Some(Value::Object(metadata)) => Value::Object(metadata),
+        _ => json!({}),
+    };
+    match metadata.get_mut(NOTIFY_METADATA_KEY) {
+        Some(Value::Object(notify)) => {
+            notify.insert("muted".to_string(), Value::Bool(true));
+        },
+        _ => metadata[NOTIFY_METADATA_KEY] = json!({ "muted": true }),
+    }
+    metadata
+}
+
+/// Returns an `ilike` pattern matching every spelling of a symbol, e.g. `EUR*USD` for `eurusd`
+/// matches `EUR/USD`, `EURUSD` and `eur-usd`.
+fn symbol_pattern(symbol: &str) -> String {
+    match Symbol::parse(symbol) {
+        Ok(symbol) => match symbol.quote() {
+            Some(quote) => format!("{}*{}", symbol.base(), quote),
+            None => symbol.base().to_string(),
+        },
+        Err(_) => symbol.trim().replace('\\', "\\\\").replace('*', "\\*").replace('%', "\\%"),
+    }
+}
+
+/// Whether a fetch failed because the row does not exist.
+fn is_not_found(error: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+    matches!(error.downcast_ref::<SupabaseError>(), Some(SupabaseError::FetchError(reason)) if reason == "No results found")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+async fn discord(
+    State(server): State<CommandServer>,
+    headers: HeaderMap,
+    body: Bytes
+) -> Response {
+    let Some(public_key) = &server.discord_public_key else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let verified: bool = verify_discord_signature(
+        public_key,
+        header(&headers, "x-signature-timestamp"),
+        &body,
+        header(&headers, "x-signature-ed25519"),
+    );
+    if !verified {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    let Ok(interaction) = serde_json::from_slice::<Value>(&body) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+
+    // Discord pings the endpoint when it is registered
+    if interaction["type"] == 1 {
+        return Json(json!({ "type": 1 })).into_response();
+    }
+
+    let user_id: Option<&str> = interaction["member"]["user"]["id"]
+        .as_str()
+        .or_else(|| interaction["user"]["id"].as_str());
+    let Some(user_id) = user_id else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+
+    // `/alert symbol:eurusd comparison:above price:1.10` arrives as the command name and its option values
+    let mut words: Vec<String> = vec![interaction["data"]["name"].as_str().unwrap_or_default().to_string()];
+    if let Some(options) = interaction["data"]["options"].as_array() {
+        words.extend(options.iter().map(|option| match &option["value"] {
+            Value::String(value) => value.clone(),
+            value => value.to_string(),
+        }));
+    }
+
+    let reply: String = server.execute(&format!("discord:{}", user_id), &words.join(" ")).await;
+    Json(json!({ "type": 4, "data": { "content": reply, "flags": 64 } })).into_response()
+}
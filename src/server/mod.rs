@@ -0,0 +1,63 @@
+//! ## Inbound command webhook (feature `server`)
+//!
+//! Accepts Slack and Discord slash-command payloads, parses them with
+//! [`crate::commands`] and creates, deletes or lists alerts through the normal
+//! `Supabase` path, replying with the generated hash.
+//!
+//! With an [`ActionSigner`], `/actions/{token}` runs the signed one-click actions linked from
+//! notifications, see [`crate::actions`].
+//!
+//! Alerts are owned by `slack:<user id>` or `discord:<user id>`, so the same person on
+//! both platforms has separate alerts. Every request is verified, `/slack` with the app's
+//! signing secret and `/discord` with the Ed25519 public key of the application. A route
+//! whose secret or key is not configured is not mounted at all.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+
+use crate::actions::ActionSigner;
+use ed25519_dalek::VerifyingKey;
+
+use crate::data::PriceProvider;
+use crate::db::{Supabase, TableConfig};
+
+pub mod handlers;
+
+/// Maximum age of a signed Slack request, older requests are rejected as replays.
+pub const SLACK_MAX_REQUEST_AGE_SECS: i64 = 300;
+
+/// ## State shared by the command webhook routes
+#[derive(Clone)]
+pub struct CommandServer {
+    pub supabase: Supabase,
+    pub config: TableConfig,
+    /// Quotes the symbols of new alerts, which are checked against the current price.
+    pub provider: Arc<dyn PriceProvider>,
+    /// Signing secret of the Slack app, `/slack` is not mounted when `None`.
+    pub slack_signing_secret: Option<String>,
+    /// Public key of the Discord application, `/discord` is not mounted when `None`.
+    pub discord_public_key: Option<VerifyingKey>,
+    /// Prefix of the hashes generated for new alerts.
+    pub hash_prefix: String,
+    /// Verifies the tokens of one-click actions, the action routes answer `404` when `None`.
+    pub action_signer: Option<ActionSigner>,
+    /// Action tokens already run by this process, with their expiry.
+    used_actions: Arc<Mutex<HashMap<String, DateTime<Utc>>>>,
+}
+
+/// Debug implementation for `CommandServer` which never prints the signing secret.
+impl fmt::Debug for CommandServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommandServer")
+            .field("supabase", &self.supabase)
+            .field("config", &self.config)
+            .field("slack_signing_secret", &self.slack_signing_secret.as_ref().map(|_| "<redacted>"))
+            .field("discord_public_key", &self.discord_public_key)
+            .field("hash_prefix", &self.hash_prefix)
+            .field("action_signer", &self.action_signer)
+            .finish()
+    }
+}
@@ -0,0 +1,110 @@
+//! ## Sharding symbols across scheduler workers
+//!
+//! For very large alert tables, several workers can split the symbols between them instead of
+//! a single leader checking all of them. Every worker holds a lease in a [`crate::db::LeaseTable`]
+//! while it runs, and the live workers are placed on a consistent [`HashRing`]. Each symbol
+//! belongs to the first worker after it on the ring, so a worker joining or leaving only moves
+//! the symbols of its neighbours, see [`crate::TradeAlertsBuilder::with_sharding`].
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use sha2::{Digest, Sha256};
+
+use crate::utils::symbol::canonical;
+
+/// Points per worker on the ring, more points spread the symbols more evenly.
+pub const DEFAULT_VIRTUAL_NODES: usize = 64;
+
+/// ## A consistent hash ring of workers
+///
+/// Symbols are hashed by their canonical form, so `eur/usd` and `EURUSD` belong to the same
+/// worker. Every worker builds the same ring from the same set of workers.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HashRing {
+    /// Workers by the position of their points.
+    points: BTreeMap<u64, String>,
+    workers: BTreeSet<String>,
+}
+
+impl HashRing {
+    /// Places every worker on the ring with `virtual_nodes` points, at least one.
+    pub fn new<S: AsRef<str>>(
+        workers: impl IntoIterator<Item = S>,
+        virtual_nodes: usize
+    ) -> Self {
+        let workers: BTreeSet<String> = workers.into_iter().map(|worker| worker.as_ref().to_string()).collect();
+        let mut points: BTreeMap<u64, String> = BTreeMap::new();
+        for worker in &workers {
+            for node in 0..virtual_nodes.max(1) {
+                points.insert(position(&format!("{}#{}", worker, node)), worker.clone());
+            }
+        }
+        Self { points, workers }
+    }
+
+    /// Returns the worker a symbol belongs to, `None` on an empty ring.
+    pub fn owner(&self, symbol: &str) -> Option<&str> {
+        let position: u64 = position(&canonical(symbol));
+        self.points
+            .range(position..)
+            .next()
+            .or_else(|| self.points.iter().next())
+            .map(|(_, worker)| worker.as_str())
+    }
+
+    /// Iterates over the workers, in order.
+    pub fn workers(&self) -> impl Iterator<Item = &str> {
+        self.workers.iter().map(String::as_str)
+    }
+
+    /// Returns the number of workers.
+    pub fn len(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Returns whether the ring holds no workers.
+    pub fn is_empty(&self) -> bool {
+        self.workers.is_empty()
+    }
+}
+
+/// ## The symbols checked by one worker
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shard {
+    /// The ring of every live worker.
+    pub ring: HashRing,
+    /// The ID of this worker.
+    pub worker: String,
+    /// The symbols of the alerts this worker owns, as stored, pushed into the alert query when set.
+    pub symbols: Option<BTreeSet<String>>,
+}
+
+impl Shard {
+    /// Creates the shard of `worker` on `ring`.
+    pub fn new(
+        ring: HashRing,
+        worker: &str
+    ) -> Self {
+        Self { ring, worker: worker.to_string(), symbols: None }
+    }
+
+    /// Sets the symbols of the alerts this worker owns, so only their alerts are fetched.
+    pub fn with_symbols(
+        mut self,
+        symbols: BTreeSet<String>
+    ) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+
+    /// Returns whether this worker checks the alerts of a symbol.
+    pub fn owns(&self, symbol: &str) -> bool {
+        self.ring.owner(symbol) == Some(self.worker.as_str())
+    }
+}
+
+/// Position of a key on the ring, stable across processes and builds.
+fn position(key: &str) -> u64 {
+    let digest = Sha256::digest(key.as_bytes());
+    u64::from_be_bytes(digest[..8].try_into().expect("A SHA-256 digest has 32 bytes"))
+}
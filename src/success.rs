@@ -3,7 +3,7 @@
 use std::fmt;
 
 /// Enum for success outcomes from Supabase services.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SupabaseSuccess {
     /// Successful authentication.
     AuthenticationSuccess,
@@ -31,7 +31,7 @@ impl fmt::Display for SupabaseSuccess {
 }
 
 /// Enum for success outcomes from the Xylex API.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum XylexApiSuccess {
     /// Successful network operation.
     NetworkSuccess,
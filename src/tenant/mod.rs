@@ -0,0 +1,40 @@
+//! Tenant isolation for B2B deployments
+//!
+//! Every tenant gets its own set of tables, named `<tenant>_<table>`. A `TenantScope`
+//! bundles the Supabase client with the tenant's table configuration so storage calls
+//! can never reach another tenant's tables by accident.
+
+use std::time::Duration;
+
+use crate::TriggeredAlert;
+use crate::db::{Supabase, TableConfig};
+use crate::errors::XylexApiError;
+
+pub mod scope;
+
+/// ## Identifier of a tenant
+/// Only lowercase ASCII letters, digits and underscores are allowed since it becomes part of table names.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TenantId(String);
+
+/// ## Tenant-scoped storage handle
+///
+/// Alerts are added, read, updated and deleted through the scope, which always passes the
+/// tenant's table configuration, so a request never reaches another tenant's rows.
+#[derive(Clone, Debug)]
+pub struct TenantScope {
+    tenant: TenantId,
+    supabase: Supabase,
+    /// The table configuration with the tenant's table names.
+    config: TableConfig,
+}
+
+/// ## Outcome of a trigger check for a single tenant
+#[derive(Debug)]
+pub struct TenantCycleReport {
+    pub tenant: TenantId,
+    /// The triggered alerts, or the error that stopped this tenant's check.
+    pub result: Result<Vec<TriggeredAlert>, XylexApiError>,
+    /// How long the check took.
+    pub duration: Duration,
+}
@@ -0,0 +1,198 @@
+//! ## Tenant scopes
+//! Creation of tenant-scoped handles and per-tenant trigger checks.
+
+use std::error::Error;
+use std::fmt;
+use std::time::Instant;
+
+use crate::{Alert, AlertUpdate};
+use crate::data::{PriceProvider, XylexApi};
+use crate::db::rest::Filter;
+use crate::db::{Supabase, TableConfig};
+use crate::errors::TableConfigError;
+use crate::success::SupabaseSuccess;
+use crate::tenant::{TenantCycleReport, TenantId, TenantScope};
+
+impl TenantId {
+    /// Creates a new `TenantId`, lowercasing the given value.
+    ///
+    /// # Errors
+    /// Returns `TableConfigError::InvalidConfiguration` if the value is empty or contains
+    /// characters other than ASCII letters, digits and underscores.
+    pub fn new(id: &str) -> Result<Self, TableConfigError> {
+        let id = id.trim().to_lowercase();
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(TableConfigError::InvalidConfiguration(format!(
+                "Invalid tenant id `{}`, only letters, digits and underscores are allowed",
+                id
+            )));
+        }
+
+        Ok(Self(id))
+    }
+
+    /// Returns the tenant id as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Display implementation for `TenantId`.
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TableConfig {
+    /// Returns a copy of this configuration pointing at the tenant's tables (`<tenant>_<table>`).
+    pub fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            tablename: format!("{}_{}", tenant, self.tablename),
+            ..self.clone()
+        }
+    }
+}
+
+impl Supabase {
+    /// Creates a handle scoped to a single tenant.
+    ///
+    /// # Parameters
+    /// - `tenant`: The tenant to scope to.
+    /// - `config`: The shared table configuration, its table name is prefixed with the tenant id.
+    pub fn tenant(
+        &self,
+        tenant: &TenantId,
+        config: &TableConfig
+    ) -> TenantScope {
+        TenantScope {
+            tenant: tenant.clone(),
+            supabase: self.clone(),
+            config: config.for_tenant(tenant),
+        }
+    }
+}
+
+impl TenantScope {
+    /// The tenant this handle is scoped to.
+    pub fn tenant(&self) -> &TenantId {
+        &self.tenant
+    }
+
+    /// The Supabase client, shared by every tenant.
+    pub fn supabase(&self) -> &Supabase {
+        &self.supabase
+    }
+
+    /// The table configuration with the tenant's table names.
+    pub fn config(&self) -> &TableConfig {
+        &self.config
+    }
+
+    /// Adds an alert to the tenant's table, see [`Supabase::add_alert_with`].
+    ///
+    /// # Errors
+    /// Returns an error if the alert is rejected or the insertion fails.
+    pub async fn add_alert<P: PriceProvider + ?Sized>(
+        &self,
+        provider: &P,
+        alert: Alert
+    ) -> Result<SupabaseSuccess, Box<dyn Error + Send + Sync>> {
+        self.supabase.add_alert_with(provider, alert, self.config.clone()).await
+    }
+
+    /// Fetches an alert of the tenant, see [`Supabase::fetch_alert_by_hash`].
+    ///
+    /// # Errors
+    /// Returns an error if the tenant has no alert with the hash or the query fails.
+    pub async fn fetch_alert_by_hash(
+        &self,
+        hash: &str
+    ) -> Result<Alert, Box<dyn Error + Send + Sync>> {
+        self.supabase.fetch_alert_by_hash(hash, &self.config).await
+    }
+
+    /// Fetches the alerts of a user of the tenant, see [`Supabase::fetch_alerts_by_user_id`].
+    ///
+    /// # Errors
+    /// Returns an error if the query fails or any of the rows is incomplete.
+    pub async fn fetch_alerts_by_user_id(
+        &self,
+        user_id: &str
+    ) -> Result<Vec<Alert>, Box<dyn Error + Send + Sync>> {
+        self.supabase.fetch_alerts_by_user_id(user_id, &self.config).await
+    }
+
+    /// Updates an alert of the tenant, see [`Supabase::update_alert_by_hash`].
+    ///
+    /// # Errors
+    /// Returns an error if the tenant has no alert with the hash or the update fails.
+    pub async fn update_alert_by_hash(
+        &self,
+        hash: &str,
+        update: AlertUpdate
+    ) -> Result<SupabaseSuccess, Box<dyn Error + Send + Sync>> {
+        self.supabase.update_alert_by_hash(hash, update, self.config.clone()).await
+    }
+
+    /// Deletes an alert of the tenant, see [`Supabase::delete_alert_by_hash`].
+    ///
+    /// # Errors
+    /// Returns an error if the tenant has no alert with the hash or the deletion fails.
+    pub async fn delete_alert_by_hash(
+        &self,
+        hash: &str
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.supabase.delete_alert_by_hash(hash, self.config.clone()).await
+    }
+
+    /// Deletes the alerts of the tenant matching every one of the filters, see [`Supabase::delete_where`].
+    ///
+    /// # Returns
+    /// A `Result` containing the number of deleted alerts or an error.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::DeletionError` if no filters are given or the request fails.
+    pub async fn delete_where(
+        &self,
+        filters: &[Filter]
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        self.supabase.delete_where(filters, &self.config).await
+    }
+}
+
+impl XylexApi {
+    /// Runs a trigger check for every tenant separately.
+    ///
+    /// An error for one tenant is recorded in its report and does not stop the other tenants.
+    ///
+    /// # Arguments
+    /// * `scopes` - The tenants to check.
+    ///
+    /// # Returns
+    /// One `TenantCycleReport` per tenant, in the order of `scopes`.
+    pub async fn check_tenants(
+        &self,
+        scopes: &[TenantScope]
+    ) -> Vec<TenantCycleReport> {
+        let mut reports = Vec::with_capacity(scopes.len());
+
+        for scope in scopes {
+            let started = Instant::now();
+            let result = self
+                .check_and_fetch_triggered_alerts(&scope.supabase, &scope.config)
+                .await;
+            if let Err(e) = &result {
+                println!("Trigger check failed for tenant {}: {}", scope.tenant, e);
+            }
+
+            reports.push(TenantCycleReport {
+                tenant: scope.tenant.clone(),
+                result,
+                duration: started.elapsed(),
+            });
+        }
+
+        reports
+    }
+}
@@ -0,0 +1,68 @@
+//! ## Request timeouts
+//!
+//! Used by `XylexApi` for price requests and by `Supabase` for database calls, so a hung
+//! endpoint fails the request instead of stalling the whole check cycle.
+
+use std::time::Duration;
+
+use crate::config::env_duration;
+use crate::errors::ConfigError;
+
+/// Longest wait for a connection by default.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest wait for the next bytes of a response by default.
+pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// ## Connect and read timeouts of a client
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Longest wait for a connection to be established.
+    pub connect: Duration,
+    /// Longest wait for the next bytes of a response once connected.
+    pub read: Duration,
+}
+
+impl Default for Timeouts {
+    /// 5s to connect and 10s between reads.
+    fn default() -> Self {
+        Self { connect: DEFAULT_CONNECT_TIMEOUT, read: DEFAULT_READ_TIMEOUT }
+    }
+}
+
+impl Timeouts {
+    /// Creates timeouts with the given connect and read timeouts.
+    pub fn new(
+        connect: Duration,
+        read: Duration
+    ) -> Self {
+        Self { connect, read }
+    }
+
+    /// Reads `{prefix}_CONNECT_TIMEOUT` and `{prefix}_READ_TIMEOUT` as durations such as `"5s"`,
+    /// falling back to the defaults for unset variables.
+    ///
+    /// # Errors
+    /// Returns `ConfigError::InvalidField` if a variable is not a valid duration.
+    pub fn from_env(prefix: &str) -> Result<Self, ConfigError> {
+        let defaults: Timeouts = Self::default();
+        Ok(Self {
+            connect: env_duration(&format!("{}_CONNECT_TIMEOUT", prefix))?.unwrap_or(defaults.connect),
+            read: env_duration(&format!("{}_READ_TIMEOUT", prefix))?.unwrap_or(defaults.read),
+        })
+    }
+
+    /// The longest a single request can take when only its overall duration can be bounded.
+    pub fn total(&self) -> Duration {
+        self.connect + self.read
+    }
+
+    /// Builds an HTTP client applying the timeouts.
+    pub fn client(&self) -> reqwest::Client {
+        reqwest::Client::builder()
+            .connect_timeout(self.connect)
+            .read_timeout(self.read)
+            .build()
+            .unwrap_or_default()
+    }
+}
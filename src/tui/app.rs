@@ -0,0 +1,215 @@
+//! ## Terminal rendering of the monitor
+
+use std::io::{self, Write};
+use std::time::Duration;
+
+use ratatui::{Frame, Terminal};
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Row, Sparkline, Table};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::db::TableConfig;
+use crate::tui::{MonitorEvent, MonitorState};
+
+/// Runs the monitor until `q` or `Esc` is pressed, or the event channel closes.
+///
+/// This call blocks, run it on a blocking thread (e.g. `tokio::task::spawn_blocking`).
+///
+/// # Arguments
+/// * `events` - The channel the check cycles report to.
+/// * `config` - Configuration with the column names of the alerts table.
+///
+/// # Errors
+/// Returns an `io::Error` if the terminal cannot be drawn to.
+pub fn run(
+    events: UnboundedReceiver<MonitorEvent>,
+    config: TableConfig
+) -> io::Result<()> {
+    run_on(events, config, io::stdout())
+}
+
+/// Runs the monitor like [`run`], drawing to `output` instead of standard output,
+/// e.g. the terminal kept by a [`crate::tui::log::LogRedirect`].
+///
+/// # Errors
+/// Returns an `io::Error` if the terminal cannot be drawn to.
+pub fn run_on<W: Write>(
+    events: UnboundedReceiver<MonitorEvent>,
+    config: TableConfig,
+    mut output: W
+) -> io::Result<()> {
+    enable_raw_mode()?;
+    if let Err(e) = execute!(output, EnterAlternateScreen) {
+        let _ = disable_raw_mode();
+        return Err(e);
+    }
+    let mut terminal: Terminal<CrosstermBackend<W>> = Terminal::new(CrosstermBackend::new(output))?;
+
+    let result: io::Result<()> = draw_until_closed(&mut terminal, events, config);
+
+    let _ = disable_raw_mode();
+    let _ = execute!(terminal.backend_mut(), LeaveAlternateScreen);
+    let _ = terminal.show_cursor();
+    result
+}
+
+fn draw_until_closed<W: Write>(
+    terminal: &mut Terminal<CrosstermBackend<W>>,
+    mut events: UnboundedReceiver<MonitorEvent>,
+    config: TableConfig
+) -> io::Result<()> {
+    let mut state: MonitorState = MonitorState::default();
+
+    loop {
+        loop {
+            match events.try_recv() {
+                Ok(event) => state.apply(event, &config),
+                Err(tokio::sync::mpsc::error::TryRecvError::Empty) => break,
+                Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => return Ok(()),
+            }
+        }
+
+        terminal.draw(|frame| draw(frame, &state))?;
+
+        if event::poll(Duration::from_millis(200))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn draw(
+    frame: &mut Frame,
+    state: &MonitorState
+) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(40), Constraint::Percentage(20)])
+        .split(frame.area());
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[0]);
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[1]);
+
+    draw_prices(frame, top[0], state);
+    draw_health(frame, top[1], state);
+    draw_nearest(frame, middle[0], state);
+    draw_triggers(frame, middle[1], state);
+    draw_cycles(frame, rows[2], state);
+}
+
+fn header(columns: &[&'static str]) -> Row<'static> {
+    Row::new(columns.to_vec()).style(Style::default().add_modifier(Modifier::BOLD))
+}
+
+fn draw_prices(
+    frame: &mut Frame,
+    area: Rect,
+    state: &MonitorState
+) {
+    let rows = state.prices.iter().map(|(symbol, price)| Row::new(vec![symbol.clone(), price.to_string()]));
+    let table = Table::new(rows, [Constraint::Percentage(50), Constraint::Percentage(50)])
+        .header(header(&["Symbol", "Price"]))
+        .block(Block::default().title("Live prices").borders(Borders::ALL));
+    frame.render_widget(table, area);
+}
+
+fn draw_health(
+    frame: &mut Frame,
+    area: Rect,
+    state: &MonitorState
+) {
+    let health = &state.health;
+    let status: (String, Color) = match health.consecutive_failures {
+        0 if health.last_success.is_some() => ("healthy".to_string(), Color::Green),
+        0 => ("waiting for first cycle".to_string(), Color::Yellow),
+        failures => (format!("failing ({} in a row)", failures), Color::Red),
+    };
+
+    let mut lines: Vec<String> = vec![
+        format!("Status: {}", status.0),
+        format!(
+            "Last success: {}",
+            health.last_success.map(|at| at.format("%H:%M:%S").to_string()).unwrap_or_else(|| "-".to_string())
+        ),
+        format!("Last error: {}", health.last_error.as_deref().unwrap_or("-")),
+        format!("Cycles: {}", state.cycles),
+    ];
+    if let Some(budget) = &health.budget {
+        lines.push(format!(
+            "Budget: {:.0} spent, {:.0} left ({:?})",
+            budget.spent, budget.remaining, budget.state
+        ));
+    }
+
+    let paragraph = Paragraph::new(lines.join("\n"))
+        .style(Style::default().fg(status.1))
+        .block(Block::default().title("Provider health").borders(Borders::ALL));
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_nearest(
+    frame: &mut Frame,
+    area: Rect,
+    state: &MonitorState
+) {
+    let rows = state.nearest.iter().map(|alert| {
+        Row::new(vec![
+            alert.symbol.clone(),
+            alert.price_level.to_string(),
+            alert.price.to_string(),
+            format!("{:.2}%", alert.distance),
+        ])
+    });
+    let table = Table::new(rows, [Constraint::Percentage(25); 4])
+        .header(header(&["Symbol", "Level", "Price", "Distance"]))
+        .block(Block::default().title("Nearest alerts").borders(Borders::ALL));
+    frame.render_widget(table, area);
+}
+
+fn draw_triggers(
+    frame: &mut Frame,
+    area: Rect,
+    state: &MonitorState
+) {
+    let items: Vec<ListItem> = state
+        .recent_triggers
+        .iter()
+        .map(|alert| {
+            ListItem::new(format!(
+                "{} {} @ {} (level {})",
+                alert.hash, alert.symbol, alert.trigger_price, alert.price_level
+            ))
+        })
+        .collect();
+    let list = List::new(items).block(Block::default().title("Recent triggers").borders(Borders::ALL));
+    frame.render_widget(list, area);
+}
+
+fn draw_cycles(
+    frame: &mut Frame,
+    area: Rect,
+    state: &MonitorState
+) {
+    let timings: Vec<u64> = state.cycle_times.iter().map(|duration| duration.as_millis() as u64).collect();
+    let title: String = match (state.cycle_times.back(), state.average_cycle_time()) {
+        (Some(last), Some(average)) => format!("Cycle timings (last {:?}, average {:?})", last, average),
+        _ => "Cycle timings".to_string(),
+    };
+    let sparkline = Sparkline::default()
+        .data(&timings)
+        .block(Block::default().title(title).borders(Borders::ALL));
+    frame.render_widget(sparkline, area);
+}
@@ -0,0 +1,58 @@
+//! ## Engine logs while the monitor runs
+//!
+//! The engine logs to standard output, which would draw over the monitor. A [`LogRedirect`]
+//! points standard output at a log file and keeps the terminal for the monitor.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::os::fd::{AsFd, AsRawFd, OwnedFd};
+use std::path::Path;
+
+/// ## Standard output redirected to a log file
+///
+/// Standard output is restored when the redirect is dropped.
+#[derive(Debug)]
+pub struct LogRedirect {
+    /// The standard output the process started with, usually the terminal.
+    terminal: OwnedFd,
+}
+
+impl LogRedirect {
+    /// Appends everything written to standard output to `path` until the redirect is dropped.
+    ///
+    /// # Errors
+    /// Returns an `io::Error` if the log file cannot be opened or standard output cannot be redirected.
+    pub fn to_file(path: &Path) -> io::Result<Self> {
+        let log: File = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut stdout = io::stdout().lock();
+        stdout.flush()?;
+        let terminal: OwnedFd = stdout.as_fd().try_clone_to_owned()?;
+        redirect_stdout(&log)?;
+        Ok(Self { terminal })
+    }
+
+    /// Returns a handle writing to the standard output the process started with, for the monitor to draw to.
+    ///
+    /// # Errors
+    /// Returns an `io::Error` if the handle cannot be duplicated.
+    pub fn terminal(&self) -> io::Result<File> {
+        Ok(File::from(self.terminal.try_clone()?))
+    }
+}
+
+impl Drop for LogRedirect {
+    fn drop(&mut self) {
+        let _ = io::stdout().flush();
+        if let Err(e) = redirect_stdout(&self.terminal) {
+            eprintln!("Failed to restore standard output: {}", e);
+        }
+    }
+}
+
+fn redirect_stdout(to: &impl AsRawFd) -> io::Result<()> {
+    // SAFETY: both descriptors are open for the duration of the call
+    match unsafe { libc::dup2(to.as_raw_fd(), libc::STDOUT_FILENO) } {
+        -1 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
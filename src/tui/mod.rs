@@ -0,0 +1,74 @@
+//! ## Terminal monitor for the alert engine
+//!
+//! An optional dashboard (feature `tui`) showing live prices, the alerts closest to
+//! triggering, recent triggers, provider health and cycle timings. It is driven by
+//! `MonitorEvent`s sent over a channel by whatever runs the check cycles.
+//! The engine's logs go to a file while the monitor runs, see [`log::LogRedirect`].
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::TriggeredAlert;
+use crate::data::{BudgetMetrics, CheckReport};
+
+pub mod app;
+#[cfg(unix)]
+pub mod log;
+pub mod state;
+
+/// Number of recent triggers kept by the monitor.
+pub const RECENT_TRIGGERS: usize = 20;
+
+/// Number of cycle timings kept by the monitor.
+pub const CYCLE_HISTORY: usize = 60;
+
+/// Number of alerts shown in the nearest alerts panel.
+pub const NEAREST_ALERTS: usize = 10;
+
+/// ## Events driving the monitor
+#[derive(Clone, Debug)]
+pub enum MonitorEvent {
+    /// A check cycle completed.
+    Cycle(CheckReport),
+    /// A check cycle failed.
+    CycleFailed(String),
+    /// The provider's credit usage changed.
+    Budget(BudgetMetrics),
+}
+
+/// ## An alert and its distance to the latest price
+#[derive(Clone, Debug, PartialEq)]
+pub struct NearestAlert {
+    pub hash: String,
+    pub symbol: String,
+    pub price_level: f64,
+    pub price: f64,
+    /// Distance between the price and the price level, in percent of the price level.
+    pub distance: f64,
+}
+
+/// ## Health of the price provider as seen by the monitor
+#[derive(Clone, Debug, Default)]
+pub struct ProviderHealth {
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub consecutive_failures: u32,
+    pub budget: Option<BudgetMetrics>,
+}
+
+/// ## Everything shown by the monitor
+#[derive(Clone, Debug, Default)]
+pub struct MonitorState {
+    /// Latest price per symbol.
+    pub prices: BTreeMap<String, f64>,
+    /// Alerts closest to triggering, nearest first.
+    pub nearest: Vec<NearestAlert>,
+    /// Most recent triggers, newest first.
+    pub recent_triggers: VecDeque<TriggeredAlert>,
+    pub health: ProviderHealth,
+    /// Duration of the most recent cycles, newest last.
+    pub cycle_times: VecDeque<Duration>,
+    pub cycles: u64,
+}
@@ -0,0 +1,84 @@
+//! ## Monitor state updates
+
+use chrono::Utc;
+
+use crate::tui::{CYCLE_HISTORY, MonitorEvent, MonitorState, NEAREST_ALERTS, NearestAlert, RECENT_TRIGGERS};
+use crate::data::CheckReport;
+use crate::db::TableConfig;
+
+impl MonitorState {
+    /// Applies an event to the state.
+    ///
+    /// # Arguments
+    /// * `event` - The event to apply.
+    /// * `config` - Configuration with the column names of the alerts table.
+    pub fn apply(
+        &mut self,
+        event: MonitorEvent,
+        config: &TableConfig
+    ) {
+        match event {
+            MonitorEvent::Cycle(report) => self.apply_cycle(report, config),
+            MonitorEvent::CycleFailed(error) => {
+                self.health.last_error = Some(error);
+                self.health.consecutive_failures += 1;
+            },
+            MonitorEvent::Budget(metrics) => self.health.budget = Some(metrics),
+        }
+    }
+
+    fn apply_cycle(
+        &mut self,
+        report: CheckReport,
+        config: &TableConfig
+    ) {
+        self.cycles += 1;
+        self.health.last_success = Some(Utc::now());
+        self.health.consecutive_failures = 0;
+
+        self.cycle_times.push_back(report.duration);
+        while self.cycle_times.len() > CYCLE_HISTORY {
+            self.cycle_times.pop_front();
+        }
+
+        for quote in &report.quotes {
+            self.prices.insert(quote.symbol.clone(), quote.price);
+        }
+
+        let mut nearest: Vec<NearestAlert> = report
+            .alerts
+            .iter()
+            .filter_map(|row| {
+                let symbol: &str = row.get(&config.symbol_column_name)?.as_str()?;
+                let price_level: f64 = row.get(&config.price_level_column_name)?.as_f64()?;
+                let price: f64 = *self.prices.get(symbol)?;
+                if price_level == 0.0 {
+                    return None;
+                }
+                Some(NearestAlert {
+                    hash: row.get(&config.hash_column_name)?.as_str()?.to_string(),
+                    symbol: symbol.to_string(),
+                    price_level,
+                    price,
+                    distance: ((price - price_level) / price_level).abs() * 100.0,
+                })
+            })
+            .collect();
+        nearest.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        nearest.truncate(NEAREST_ALERTS);
+        self.nearest = nearest;
+
+        for triggered in report.triggered {
+            self.recent_triggers.push_front(triggered);
+        }
+        self.recent_triggers.truncate(RECENT_TRIGGERS);
+    }
+
+    /// Returns the average duration of the recorded cycles.
+    pub fn average_cycle_time(&self) -> Option<std::time::Duration> {
+        if self.cycle_times.is_empty() {
+            return None;
+        }
+        Some(self.cycle_times.iter().sum::<std::time::Duration>() / self.cycle_times.len() as u32)
+    }
+}
@@ -0,0 +1,179 @@
+//! ## Cron expressions
+//!
+//! Five-field cron expressions (`minute hour day-of-month month day-of-week`) limiting when
+//! cycles run, e.g. `"*/5 9-17 * * MON-FRI"` for every 5 minutes during office hours.
+//! Expressions are evaluated in UTC.
+//!
+//! | Field        | Values            |
+//! |--------------|-------------------|
+//! | minute       | `0-59`            |
+//! | hour         | `0-23`            |
+//! | day-of-month | `1-31`            |
+//! | month        | `1-12`, `JAN-DEC` |
+//! | day-of-week  | `0-7`, `SUN-SAT`  |
+//!
+//! Fields accept `*`, single values, ranges (`9-17`), steps (`*/5`, `10-50/10`) and lists
+//! (`1,15`). Like in cron, a time matches a restricted day-of-month *or* a restricted day-of-week.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, DurationRound, NaiveDate, TimeZone, Timelike, Utc};
+
+const MONTHS: &[&str] = &["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+const WEEKDAYS: &[&str] = &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// Years searched for the next matching minute, expressions such as `0 0 30 2 *` never match.
+const SEARCH_YEARS: i32 = 5;
+
+/// ## A parsed cron expression
+///
+/// Every field is kept as a bit set of the values it matches.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronExpression {
+    source: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    /// Sunday is bit 0, `7` is folded into it.
+    days_of_week: u64,
+    /// Whether the day-of-month field starts with `*`, which changes how days are matched.
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl CronExpression {
+    /// Returns the expression as written.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns whether the minute of `at` matches the expression.
+    pub fn matches(&self, at: DateTime<Utc>) -> bool {
+        has(self.minutes, at.minute()) && has(self.hours, at.hour()) && self.matches_day(at.date_naive())
+    }
+
+    /// Returns the first whole minute strictly after `after` matching the expression, `None`
+    /// if none does within the next years.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut at: DateTime<Utc> = after.duration_trunc(Duration::minutes(1)).ok()? + Duration::minutes(1);
+        let limit: i32 = after.year() + SEARCH_YEARS;
+
+        while at.year() <= limit {
+            let date: NaiveDate = at.date_naive();
+            if !has(self.months, date.month()) {
+                let (year, month) = match date.month() {
+                    12 => (date.year() + 1, 1),
+                    month => (date.year(), month + 1),
+                };
+                at = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+            } else if !self.matches_day(date) {
+                at = Utc.from_utc_datetime(&date.succ_opt()?.and_hms_opt(0, 0, 0)?);
+            } else if !has(self.hours, at.hour()) {
+                at = at.duration_trunc(Duration::hours(1)).ok()? + Duration::hours(1);
+            } else if !has(self.minutes, at.minute()) {
+                at += Duration::minutes(1);
+            } else {
+                return Some(at);
+            }
+        }
+        None
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day_of_month: bool = has(self.days_of_month, date.day());
+        let day_of_week: bool = has(self.days_of_week, date.weekday().num_days_from_sunday());
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (false, false) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        }
+    }
+}
+
+impl FromStr for CronExpression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let [minutes, hours, days_of_month, months, days_of_week] = fields[..] else {
+            return Err(format!(
+                "`{}` has {} fields, expected 5: minute hour day-of-month month day-of-week",
+                s.trim(),
+                fields.len()
+            ));
+        };
+
+        let days_of_week_bits: u64 = parse_field(days_of_week, "day-of-week", 0, 7, WEEKDAYS, 0)?;
+        Ok(Self {
+            source: fields.join(" "),
+            minutes: parse_field(minutes, "minute", 0, 59, &[], 0)?,
+            hours: parse_field(hours, "hour", 0, 23, &[], 0)?,
+            days_of_month: parse_field(days_of_month, "day-of-month", 1, 31, &[], 0)?,
+            months: parse_field(months, "month", 1, 12, MONTHS, 1)?,
+            // 7 is Sunday too
+            days_of_week: (days_of_week_bits | days_of_week_bits >> 7) & 0x7f,
+            any_day_of_month: days_of_month.starts_with('*'),
+            any_day_of_week: days_of_week.starts_with('*'),
+        })
+    }
+}
+
+impl fmt::Display for CronExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn has(bits: u64, value: u32) -> bool {
+    bits & (1 << value) != 0
+}
+
+/// Parses one field into a bit set of the values between `min` and `max` it matches.
+///
+/// `names` are accepted for the values from `first_name` on, e.g. `JAN` for month 1.
+fn parse_field(
+    field: &str,
+    name: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    first_name: u32
+) -> Result<u64, String> {
+    let value = |part: &str| -> Result<u32, String> {
+        let value: Option<u32> = match names.iter().position(|known| known.eq_ignore_ascii_case(part)) {
+            Some(index) => Some(first_name + index as u32),
+            None => part.parse().ok(),
+        };
+        value
+            .filter(|value| (min..=max).contains(value))
+            .ok_or_else(|| format!("`{}` is not a valid {}, expected {}-{}", part, name, min, max))
+    };
+
+    let mut bits: u64 = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("`{}` is not a valid step in the {} field", step, name))?;
+                (range, step)
+            },
+            None => (part, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (value(start)?, value(end)?),
+            // `5/15` runs from 5 to the end of the field
+            None if step > 1 => (value(range)?, max),
+            None => (value(range)?, value(range)?),
+        };
+        if start > end {
+            return Err(format!("`{}` is an empty range in the {} field", range, name));
+        }
+        bits |= (start..=end).step_by(step as usize).fold(0, |bits, value| bits | 1 << value);
+    }
+    Ok(bits)
+}
@@ -0,0 +1,144 @@
+//! ## Field-level encryption
+//!
+//! Encrypts individual JSON fields with AES-256-GCM before they are stored.
+//! Encrypted values are stored as strings in the format `enc:v1:<base64(nonce || ciphertext)>`,
+//! and decrypt back to the original JSON value.
+
+use std::fmt;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::Value;
+
+use crate::errors::SupabaseError;
+use crate::utils::secrets::SecretsProvider;
+
+/// Prefix marking a stored value as encrypted.
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// Length of the AES-GCM nonce in bytes.
+const NONCE_LENGTH: usize = 12;
+
+/// Name of the secret holding the base64 encoded 256-bit encryption key.
+pub const ENCRYPTION_KEY_SECRET: &str = "TRADE_ALERTS_ENCRYPTION_KEY";
+
+/// ## Field cipher
+/// Encrypts and decrypts selected JSON fields with AES-256-GCM.
+#[derive(Clone)]
+pub struct FieldCipher {
+    cipher: Aes256Gcm,
+}
+
+/// Debug implementation for `FieldCipher` which never prints the key.
+impl fmt::Debug for FieldCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FieldCipher { .. }")
+    }
+}
+
+impl FieldCipher {
+    /// Creates a new `FieldCipher` from a raw 32 byte key.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::EncryptionError` if the key is not exactly 32 bytes long.
+    pub fn new(key: &[u8]) -> Result<Self, SupabaseError> {
+        if key.len() != 32 {
+            return Err(SupabaseError::EncryptionError(format!(
+                "Encryption key must be 32 bytes, got {}",
+                key.len()
+            )));
+        }
+
+        Ok(Self { cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)) })
+    }
+
+    /// Creates a new `FieldCipher` from the base64 encoded key stored in the secrets provider
+    /// under `TRADE_ALERTS_ENCRYPTION_KEY`.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::EncryptionError` if the secret is missing or not a valid key.
+    pub fn from_secrets(secrets: &dyn SecretsProvider) -> Result<Self, SupabaseError> {
+        let encoded = secrets.get_secret(ENCRYPTION_KEY_SECRET).ok_or_else(|| {
+            SupabaseError::EncryptionError(format!("{} is not set", ENCRYPTION_KEY_SECRET))
+        })?;
+        let key = STANDARD.decode(encoded.trim()).map_err(|e| {
+            SupabaseError::EncryptionError(format!("{} is not valid base64: {}", ENCRYPTION_KEY_SECRET, e))
+        })?;
+
+        Self::new(&key)
+    }
+
+    /// Encrypts a JSON value into its stored string representation.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::EncryptionError` if the encryption fails.
+    pub fn encrypt_value(&self, value: &Value) -> Result<Value, SupabaseError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, value.to_string().as_bytes())
+            .map_err(|e| SupabaseError::EncryptionError(e.to_string()))?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+
+        Ok(Value::String(format!("{}{}", ENCRYPTED_PREFIX, STANDARD.encode(payload))))
+    }
+
+    /// Decrypts a stored value back into the original JSON value.
+    /// Values which are not encrypted are returned unchanged.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::EncryptionError` if the value is encrypted but cannot be decrypted.
+    pub fn decrypt_value(&self, value: &Value) -> Result<Value, SupabaseError> {
+        let encoded = match value.as_str().and_then(|s| s.strip_prefix(ENCRYPTED_PREFIX)) {
+            Some(encoded) => encoded,
+            None => return Ok(value.clone()),
+        };
+
+        let payload = STANDARD
+            .decode(encoded)
+            .map_err(|e| SupabaseError::EncryptionError(e.to_string()))?;
+        if payload.len() < NONCE_LENGTH {
+            return Err(SupabaseError::EncryptionError("Encrypted value is too short".to_string()));
+        }
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LENGTH);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|e| SupabaseError::EncryptionError(e.to_string()))?;
+
+        serde_json::from_slice(&plaintext).map_err(|e| SupabaseError::EncryptionError(e.to_string()))
+    }
+
+    /// Encrypts the given columns of a JSON row in place. Missing and `null` columns are skipped.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::EncryptionError` if any of the columns fails to encrypt.
+    pub fn encrypt_columns(&self, row: &mut Value, columns: &[String]) -> Result<(), SupabaseError> {
+        for column in columns {
+            if let Some(value) = row.get_mut(column) {
+                if !value.is_null() {
+                    *value = self.encrypt_value(value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Decrypts the given columns of a JSON row in place.
+    ///
+    /// # Errors
+    /// Returns `SupabaseError::EncryptionError` if any of the columns fails to decrypt.
+    pub fn decrypt_columns(&self, row: &mut Value, columns: &[String]) -> Result<(), SupabaseError> {
+        for column in columns {
+            if let Some(value) = row.get_mut(column) {
+                *value = self.decrypt_value(value)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -29,6 +29,16 @@ pub async fn generate_hash(
     symbol: &str,
     price_level: f64,
     prefix: &str
+) -> String {
+    hash_alert(user_id, symbol, price_level, prefix)
+}
+
+/// Synchronous version of [`generate_hash`], for callers outside of an async context.
+pub fn hash_alert(
+    user_id: &str,
+    symbol: &str,
+    price_level: f64,
+    prefix: &str
 ) -> String {
     let mut hasher = Md5::new();
 
@@ -41,13 +41,7 @@ pub async fn verify(
         .await;
 
     match data {
-        Ok(data) => {
-            if data.is_empty() {
-                false
-            } else {
-                true
-            }
-        }
+        Ok(data) => !data.is_empty(),
 
         Err(e) => {
             eprintln!("Error: {}", e);
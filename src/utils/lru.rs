@@ -0,0 +1,200 @@
+//! ## Memory-bounded caches
+//!
+//! [`LruCache`] backs the per-symbol state kept across checks (previous prices, volumes,
+//! candles, ...), so a process tracking many symbols stays within the entries set by
+//! [`crate::ResourceLimits`]. Once full, the least recently used entry is evicted.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ResourceLimits;
+
+/// ## Occupancy of a cache
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Number of entries held.
+    pub entries: usize,
+    /// Largest number of entries held, `None` when unbounded.
+    pub capacity: Option<usize>,
+    /// Number of entries evicted to stay within the capacity.
+    pub evictions: u64,
+}
+
+/// ## A map evicting its least recently used entry once full
+///
+/// Reads through [`LruCache::get`] and [`LruCache::get_mut`] count as a use, [`LruCache::peek`] does not.
+#[derive(Clone, Debug)]
+pub struct LruCache<K, V> {
+    capacity: Option<usize>,
+    entries: HashMap<K, (V, u64)>,
+    /// Keys by the tick of their last use, oldest first.
+    order: BTreeMap<u64, K>,
+    tick: u64,
+    evictions: u64,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Creates a cache holding up to `capacity` entries, unbounded for `None`.
+    ///
+    /// A capacity of zero holds a single entry, so a value can always be read back after inserting it.
+    pub fn new(capacity: Option<usize>) -> Self {
+        Self {
+            capacity: capacity.map(|capacity| capacity.max(1)),
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            evictions: 0,
+        }
+    }
+
+    /// Returns the value of a key and marks it as used.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    /// Returns the value of a key mutably and marks it as used.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.tick += 1;
+        let (value, used) = self.entries.get_mut(key)?;
+        let key: K = self.order.remove(used).expect("Every entry is ordered");
+        *used = self.tick;
+        self.order.insert(self.tick, key);
+        Some(value)
+    }
+
+    /// Returns the value of a key without marking it as used.
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        self.entries.get(key).map(|(value, _)| value)
+    }
+
+    /// Returns the value of a key, inserting `default()` first if there is none.
+    pub fn get_or_insert_with(
+        &mut self,
+        key: K,
+        default: impl FnOnce() -> V
+    ) -> &mut V {
+        if !self.entries.contains_key(&key) {
+            self.insert(key.clone(), default());
+        }
+        self.get_mut(&key).expect("The entry was just inserted")
+    }
+
+    /// Inserts a value, evicting the least recently used entries if the cache is full.
+    ///
+    /// # Returns
+    /// The previous value of the key, if any.
+    pub fn insert(
+        &mut self,
+        key: K,
+        value: V
+    ) -> Option<V> {
+        self.tick += 1;
+        let previous: Option<V> = self.remove(&key);
+        self.order.insert(self.tick, key.clone());
+        self.entries.insert(key, (value, self.tick));
+        self.evict();
+        previous
+    }
+
+    /// Removes a key, returning its value.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (value, used) = self.entries.remove(key)?;
+        self.order.remove(&used);
+        Some(value)
+    }
+
+    /// Iterates over the entries, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(key, (value, _))| (key, value))
+    }
+
+    /// Iterates over the values, in no particular order.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.values().map(|(value, _)| value)
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the cache holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Changes the capacity, evicting the least recently used entries which no longer fit.
+    pub fn set_capacity(&mut self, capacity: Option<usize>) {
+        self.capacity = capacity.map(|capacity| capacity.max(1));
+        self.evict();
+    }
+
+    /// Returns the occupancy of the cache.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats { entries: self.entries.len(), capacity: self.capacity, evictions: self.evictions }
+    }
+
+    fn evict(&mut self) {
+        let Some(capacity) = self.capacity else {
+            return;
+        };
+        while self.entries.len() > capacity {
+            let Some((_, key)) = self.order.pop_first() else {
+                return;
+            };
+            self.entries.remove(&key);
+            self.evictions += 1;
+        }
+    }
+}
+
+impl ResourceLimits {
+    /// Creates limits without bounds, the caches grow with the number of symbols.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bounds the per-symbol caches, e.g. previous prices, volumes and the last quotes.
+    pub fn with_max_symbols(
+        mut self,
+        max_symbols: usize
+    ) -> Self {
+        self.max_symbols = Some(max_symbols);
+        self
+    }
+
+    /// Bounds the open and completed candles, one per symbol and timeframe.
+    pub fn with_max_candles(
+        mut self,
+        max_candles: usize
+    ) -> Self {
+        self.max_candles = Some(max_candles);
+        self
+    }
+
+    /// Bounds the pseudonyms a `Pseudonymizer` remembers for reverse lookups.
+    pub fn with_max_pseudonyms(
+        mut self,
+        max_pseudonyms: usize
+    ) -> Self {
+        self.max_pseudonyms = Some(max_pseudonyms);
+        self
+    }
+
+    /// Bounds the alerts whose last evaluation time is remembered.
+    pub fn with_max_alerts(
+        mut self,
+        max_alerts: usize
+    ) -> Self {
+        self.max_alerts = Some(max_alerts);
+        self
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Default for LruCache<K, V> {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
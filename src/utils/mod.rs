@@ -1,4 +1,12 @@
 //! Utilities for working with Alerts
 
+pub mod cron;
+pub mod crypto;
 pub mod format;
 pub mod hash;
+pub mod lru;
+pub mod output;
+pub mod privacy;
+pub mod pseudonym;
+pub mod secrets;
+pub mod symbol;
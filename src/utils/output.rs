@@ -0,0 +1,134 @@
+//! ## Structured output
+//!
+//! Renders rows as JSON, an aligned table or CSV for the command line tool,
+//! so its output can be read by humans and scripted in pipelines alike.
+
+use std::str::FromStr;
+
+use serde_json::Value;
+
+/// Output format of list and status commands.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// A JSON array of objects.
+    Json,
+    /// An aligned, human readable table.
+    #[default]
+    Table,
+    /// Comma separated values with a header line.
+    Csv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(OutputFormat::Json),
+            "table" => Ok(OutputFormat::Table),
+            "csv" => Ok(OutputFormat::Csv),
+            other => Err(format!("Unknown output format: {}", other)),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// Renders rows in this format.
+    ///
+    /// # Arguments
+    /// * `columns` - The field names to render, in order. Missing fields render as `null` or empty.
+    /// * `rows` - JSON objects holding the fields.
+    ///
+    /// # Returns
+    /// The rendered output, ending with a newline for table and CSV output.
+    pub fn render(
+        &self,
+        columns: &[&str],
+        rows: &[Value]
+    ) -> String {
+        match self {
+            OutputFormat::Json => {
+                let objects: Vec<Value> = rows
+                    .iter()
+                    .map(|row| {
+                        columns
+                            .iter()
+                            .map(|column| (column.to_string(), row.get(*column).cloned().unwrap_or(Value::Null)))
+                            .collect::<serde_json::Map<String, Value>>()
+                            .into()
+                    })
+                    .collect();
+                serde_json::to_string_pretty(&objects).unwrap_or_else(|_| "[]".to_string())
+            },
+            OutputFormat::Table => render_table(columns, rows),
+            OutputFormat::Csv => render_csv(columns, rows),
+        }
+    }
+}
+
+fn cell(row: &Value, column: &str) -> String {
+    match row.get(column) {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(value)) => value.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+fn render_table(
+    columns: &[&str],
+    rows: &[Value]
+) -> String {
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| columns.iter().map(|column| cell(row, column)).collect())
+        .collect();
+
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            cells.iter().map(|row| row[i].chars().count()).fold(column.len(), usize::max)
+        })
+        .collect();
+
+    let line = |values: Vec<&str>| -> String {
+        values
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:<width$}", value, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut output: String = line(columns.to_vec());
+    output.push('\n');
+    for row in &cells {
+        output.push_str(&line(row.iter().map(String::as_str).collect()));
+        output.push('\n');
+    }
+    output
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn render_csv(
+    columns: &[&str],
+    rows: &[Value]
+) -> String {
+    let mut output: String = columns.join(",");
+    output.push('\n');
+    for row in rows {
+        let values: Vec<String> = columns.iter().map(|column| escape_csv(&cell(row, column))).collect();
+        output.push_str(&values.join(","));
+        output.push('\n');
+    }
+    output
+}
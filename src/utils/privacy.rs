@@ -0,0 +1,172 @@
+//! ## Privacy
+//!
+//! Redaction of personal data (user IDs, emails, webhook URLs) in log output and exported events.
+//! The active `PrivacyPolicy` is read once from the `TRADE_ALERTS_PRIVACY` environment variable
+//! (`plain`, `mask` or `hash`) and can be overridden with [`set_privacy_policy`].
+
+use std::collections::HashMap;
+use std::env::var;
+use std::str::FromStr;
+use std::sync::{OnceLock, RwLock};
+
+use md5::{Digest, Md5};
+use serde_json::Value;
+
+use crate::db::TableConfig;
+
+/// How personal data is rendered in logs and exported events.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PrivacyPolicy {
+    /// Personal data is shown as is.
+    #[default]
+    Plain,
+    /// Personal data is partially masked, e.g. `us****34`.
+    Mask,
+    /// Personal data is replaced by a stable hash, so entries can still be correlated.
+    Hash,
+}
+
+static POLICY: OnceLock<RwLock<PrivacyPolicy>> = OnceLock::new();
+
+fn policy_lock() -> &'static RwLock<PrivacyPolicy> {
+    POLICY.get_or_init(|| {
+        let policy = var("TRADE_ALERTS_PRIVACY")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_default();
+        RwLock::new(policy)
+    })
+}
+
+/// Returns the privacy policy currently in effect.
+pub fn privacy_policy() -> PrivacyPolicy {
+    *policy_lock().read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Overrides the privacy policy for the whole process.
+pub fn set_privacy_policy(policy: PrivacyPolicy) {
+    *policy_lock().write().unwrap_or_else(|e| e.into_inner()) = policy;
+}
+
+impl FromStr for PrivacyPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "plain" | "off" => Ok(PrivacyPolicy::Plain),
+            "mask" => Ok(PrivacyPolicy::Mask),
+            "hash" => Ok(PrivacyPolicy::Hash),
+            other => Err(format!("Unknown privacy policy: {}", other)),
+        }
+    }
+}
+
+impl PrivacyPolicy {
+    /// Redacts a user ID.
+    pub fn redact_user_id(&self, user_id: &str) -> String {
+        match self {
+            PrivacyPolicy::Plain => user_id.to_string(),
+            PrivacyPolicy::Mask => mask(user_id),
+            PrivacyPolicy::Hash => short_hash(user_id),
+        }
+    }
+
+    /// Redacts an email address, keeping the domain when masking.
+    pub fn redact_email(&self, email: &str) -> String {
+        match self {
+            PrivacyPolicy::Plain => email.to_string(),
+            PrivacyPolicy::Mask => match email.split_once('@') {
+                Some((local, domain)) => {
+                    format!("{}***@{}", local.chars().next().unwrap_or('*'), domain)
+                },
+                None => mask(email),
+            },
+            PrivacyPolicy::Hash => short_hash(email),
+        }
+    }
+
+    /// Redacts a URL, keeping only the scheme and host when masking since paths and
+    /// query strings of webhook URLs often carry tokens.
+    pub fn redact_url(&self, url: &str) -> String {
+        match self {
+            PrivacyPolicy::Plain => url.to_string(),
+            PrivacyPolicy::Mask => {
+                let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+                let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+                if scheme.is_empty() {
+                    format!("{}/***", host)
+                } else {
+                    format!("{}://{}/***", scheme, host)
+                }
+            },
+            PrivacyPolicy::Hash => short_hash(url),
+        }
+    }
+
+    /// Redacts the personal data in a JSON value in place.
+    ///
+    /// Keys are recognised by name: keys containing `email` are treated as emails, keys
+    /// containing `url` or `webhook` as URLs, and keys containing `user_id`, `phone` or
+    /// listed in `extra_keys` as personal identifiers. Nested objects and arrays are scrubbed as well.
+    pub fn scrub_value(&self, value: &mut Value, extra_keys: &[&str]) {
+        if *self == PrivacyPolicy::Plain {
+            return;
+        }
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    let key = key.to_lowercase();
+                    match value {
+                        Value::String(s) => {
+                            if key.contains("email") {
+                                *s = self.redact_email(s);
+                            } else if key.contains("url") || key.contains("webhook") {
+                                *s = self.redact_url(s);
+                            } else if key.contains("user_id") || key.contains("phone") || extra_keys.contains(&key.as_str()) {
+                                *s = self.redact_user_id(s);
+                            }
+                        },
+                        _ => self.scrub_value(value, extra_keys),
+                    }
+                }
+            },
+            Value::Array(values) => {
+                for value in values {
+                    self.scrub_value(value, extra_keys);
+                }
+            },
+            _ => {}
+        }
+    }
+}
+
+/// Returns a copy of an alert row with its personal data redacted according to the active policy.
+///
+/// # Arguments
+/// * `row` - The row as returned by `Supabase::fetch_all_data`.
+/// * `config` - A reference to a `TableConfig`; its user ID and encrypted columns are redacted as well.
+pub fn scrub_row(row: &HashMap<String, Value>, config: &TableConfig) -> Value {
+    let mut value = Value::Object(row.clone().into_iter().collect());
+    let mut extra_keys: Vec<&str> = vec![config.user_id_column_name.as_str()];
+    extra_keys.extend(config.encrypted_columns.iter().map(String::as_str));
+
+    privacy_policy().scrub_value(&mut value, &extra_keys);
+    value
+}
+
+/// Masks all but the first and last two characters of a value.
+fn mask(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 4 {
+        return "*".repeat(chars.len());
+    }
+    let start: String = chars[..2].iter().collect();
+    let end: String = chars[chars.len() - 2..].iter().collect();
+    format!("{}{}{}", start, "*".repeat(chars.len() - 4), end)
+}
+
+/// Returns a short, stable hash of a value.
+fn short_hash(value: &str) -> String {
+    let digest = Md5::digest(value.as_bytes());
+    format!("h-{:x}", digest)[..14].to_string()
+}
@@ -0,0 +1,123 @@
+//! ## Pseudonymous user IDs
+//!
+//! Outbound webhooks and events can carry a pseudonym instead of the internal user ID, so
+//! third-party receivers never see it. A pseudonym is the prefixed, truncated HMAC-SHA256 of
+//! the user ID: stable for the same secret, so receivers can still group alerts by user, and
+//! only reversible by whoever holds the secret and the candidate user IDs.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{ResourceLimits, TriggeredAlert};
+use crate::utils::lru::{CacheStats, LruCache};
+
+/// The prefix of pseudonyms when no other one is set.
+pub const DEFAULT_PSEUDONYM_PREFIX: &str = "u_";
+
+/// Hex characters of the HMAC kept in a pseudonym, 128 bits.
+const PSEUDONYM_LENGTH: usize = 32;
+
+/// ## Maps user IDs to pseudonyms with a keyed hash
+///
+/// Remembers the user IDs it mapped, so [`Pseudonymizer::resolve`] can reverse them without
+/// candidates, up to [`ResourceLimits::max_pseudonyms`]. Cloning a `Pseudonymizer` shares what it remembers. `Debug` output never contains the secret.
+#[derive(Clone)]
+pub struct Pseudonymizer {
+    secret: Vec<u8>,
+    /// Prepended to every pseudonym, e.g. `u_`.
+    pub prefix: String,
+    seen: Arc<Mutex<LruCache<String, String>>>,
+}
+
+impl Pseudonymizer {
+    /// Creates a `Pseudonymizer` keyed with `secret`, which must stay the same for pseudonyms to stay stable.
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            secret: secret.as_ref().to_vec(),
+            prefix: DEFAULT_PSEUDONYM_PREFIX.to_string(),
+            seen: Arc::new(Mutex::new(LruCache::default())),
+        }
+    }
+
+    /// Sets the prefix of pseudonyms.
+    pub fn with_prefix(
+        mut self,
+        prefix: &str
+    ) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Bounds the pseudonyms remembered for [`Pseudonymizer::resolve`], see [`ResourceLimits`].
+    ///
+    /// Forgotten pseudonyms can still be resolved with [`Pseudonymizer::resolve_among`].
+    pub fn with_resource_limits(
+        self,
+        limits: ResourceLimits
+    ) -> Self {
+        self.lock().set_capacity(limits.max_pseudonyms);
+        self
+    }
+
+    /// Returns the occupancy of the remembered pseudonyms.
+    pub fn stats(&self) -> CacheStats {
+        self.lock().stats()
+    }
+
+    /// Returns the pseudonym of a user ID and remembers the mapping.
+    pub fn pseudonymize(&self, user_id: &str) -> String {
+        let pseudonym: String = self.pseudonym_of(user_id);
+        self.lock().insert(pseudonym.clone(), user_id.to_string());
+        pseudonym
+    }
+
+    /// Returns the alert with its user ID replaced by the pseudonym.
+    pub fn apply(&self, alert: &TriggeredAlert) -> TriggeredAlert {
+        TriggeredAlert { user_id: self.pseudonymize(&alert.user_id), ..alert.clone() }
+    }
+
+    /// Returns the user ID behind a pseudonym this `Pseudonymizer` produced, if it remembers it.
+    pub fn resolve(&self, pseudonym: &str) -> Option<String> {
+        self.lock().get(&pseudonym.to_string()).cloned()
+    }
+
+    /// Returns the user ID behind a pseudonym among `user_ids`, e.g. every user of the alerts table.
+    ///
+    /// Works for pseudonyms produced by any process keyed with the same secret and prefix.
+    pub fn resolve_among<'a>(
+        &self,
+        pseudonym: &str,
+        user_ids: impl IntoIterator<Item = &'a str>
+    ) -> Option<String> {
+        if let Some(user_id) = self.resolve(pseudonym) {
+            return Some(user_id);
+        }
+        let user_id: &str = user_ids.into_iter().find(|user_id| self.pseudonym_of(user_id) == pseudonym)?;
+        self.pseudonymize(user_id);
+        Some(user_id.to_string())
+    }
+
+    fn pseudonym_of(&self, user_id: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(user_id.as_bytes());
+        let digest: String = mac.finalize().into_bytes().iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!("{}{}", self.prefix, &digest[..PSEUDONYM_LENGTH])
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruCache<String, String>> {
+        self.seen.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Debug implementation for `Pseudonymizer` which never prints the secret or the mappings.
+impl fmt::Debug for Pseudonymizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pseudonymizer")
+            .field("secret", &"<redacted>")
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
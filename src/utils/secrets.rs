@@ -0,0 +1,27 @@
+//! ## Secrets providers
+//!
+//! Sensitive values such as encryption keys are looked up through a `SecretsProvider`
+//! so they can come from the environment, a vault or any other store.
+
+use std::env::var;
+
+use dotenv::dotenv;
+
+/// A source of named secrets.
+pub trait SecretsProvider: Send + Sync {
+    /// Returns the secret stored under `name`, or `None` if it is not set.
+    fn get_secret(&self, name: &str) -> Option<String>;
+}
+
+/// ## Environment secrets
+/// Reads secrets from environment variables, loading the `.env` file first.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EnvSecrets;
+
+impl SecretsProvider for EnvSecrets {
+    fn get_secret(&self, name: &str) -> Option<String> {
+        dotenv().ok();
+
+        var(name).ok().filter(|value| !value.trim().is_empty())
+    }
+}
@@ -0,0 +1,171 @@
+//! ## Symbols
+//!
+//! Validates and normalizes the many ways a symbol is written, `"EURUSD"`, `"eur/usd"` and
+//! `"EUR-USD"` are all the same pair, so a case or separator mismatch between an alert and
+//! a quote can't silently keep the alert from triggering.
+
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::errors::SymbolError;
+
+/// Quote assets recognised at the end of concatenated crypto pairs such as `BTCUSDT`.
+const QUOTE_ASSETS: &[&str] = &["FDUSD", "USDT", "USDC", "BUSD"];
+
+/// ## A validated symbol in canonical form
+///
+/// Pairs are stored as `BASE/QUOTE` and single instruments as `TICKER`, both uppercase.
+/// Derefs to the canonical `str`, so it can be passed wherever a symbol string is expected.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(String);
+
+/// How a provider writes pairs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolFormat {
+    /// `EUR/USD`, as used by TwelveData.
+    Slash,
+    /// `BTCUSDT`, as used by Binance.
+    Concatenated,
+    /// `BTC-USD`, as used by Coinbase.
+    Dashed,
+}
+
+impl Symbol {
+    /// Parses a symbol written as `BASE/QUOTE`, `BASE-QUOTE`, `BASE_QUOTE`, `BASE:QUOTE`,
+    /// `BASEQUOTE` or a single ticker, in any case.
+    ///
+    /// Six letters without a separator are read as a currency pair, e.g. `EURUSD`, and
+    /// concatenated crypto pairs are split before a known quote asset, e.g. `BTCUSDT`.
+    ///
+    /// # Errors
+    /// Returns `SymbolError::Empty` for blank input, or `SymbolError::Invalid` if the symbol
+    /// has more than two parts or characters other than letters, digits and `.`.
+    pub fn parse(raw: &str) -> Result<Self, SymbolError> {
+        let raw: &str = raw.trim();
+        if raw.is_empty() {
+            return Err(SymbolError::Empty);
+        }
+
+        let parts: Vec<String> = raw
+            .split(['/', '-', '_', ':', ' '])
+            .map(str::to_ascii_uppercase)
+            .collect();
+        if parts.len() > 2 || parts.iter().any(|part| part.is_empty()) {
+            return Err(SymbolError::Invalid(raw.to_string()));
+        }
+        if parts.iter().any(|part| !part.chars().all(|c| c.is_ascii_alphanumeric() || c == '.')) {
+            return Err(SymbolError::Invalid(raw.to_string()));
+        }
+
+        if let [base, quote] = parts.as_slice() {
+            return Ok(Self(format!("{}/{}", base, quote)));
+        }
+        let ticker: &str = &parts[0];
+        if ticker.len() == 6 && ticker.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Ok(Self(format!("{}/{}", &ticker[..3], &ticker[3..])));
+        }
+        for quote in QUOTE_ASSETS {
+            if let Some(base) = ticker.strip_suffix(quote).filter(|base| base.len() >= 2) {
+                return Ok(Self(format!("{}/{}", base, quote)));
+            }
+        }
+        Ok(Self(ticker.to_string()))
+    }
+
+    /// Returns the canonical form, e.g. `EUR/USD` or `AAPL`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the base of a pair, or the ticker of a single instrument.
+    pub fn base(&self) -> &str {
+        self.0.split('/').next().unwrap_or(&self.0)
+    }
+
+    /// Returns the quote currency of a pair, `None` for single instruments.
+    pub fn quote(&self) -> Option<&str> {
+        self.0.split_once('/').map(|(_, quote)| quote)
+    }
+
+    /// Writes the symbol the way a provider expects it, single instruments are returned as is.
+    pub fn format(
+        &self,
+        format: SymbolFormat
+    ) -> String {
+        match (self.quote(), format) {
+            (None, _) | (Some(_), SymbolFormat::Slash) => self.0.clone(),
+            (Some(quote), SymbolFormat::Concatenated) => format!("{}{}", self.base(), quote),
+            (Some(quote), SymbolFormat::Dashed) => format!("{}-{}", self.base(), quote),
+        }
+    }
+}
+
+/// Returns the canonical form of a symbol, or the trimmed uppercase input if it is not a valid symbol.
+pub fn canonical(raw: &str) -> String {
+    Symbol::parse(raw)
+        .map(String::from)
+        .unwrap_or_else(|_| raw.trim().to_ascii_uppercase())
+}
+
+/// Returns `true` if both strings name the same symbol, e.g. `eur/usd` and `EURUSD`.
+pub fn same_symbol(a: &str, b: &str) -> bool {
+    a == b || canonical(a) == canonical(b)
+}
+
+impl FromStr for Symbol {
+    type Err = SymbolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Symbol::parse(s)
+    }
+}
+
+impl TryFrom<&str> for Symbol {
+    type Error = SymbolError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Symbol::parse(value)
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(symbol: Symbol) -> Self {
+        symbol.0
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Symbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw: String = String::deserialize(deserializer)?;
+        Symbol::parse(&raw).map_err(serde::de::Error::custom)
+    }
+}
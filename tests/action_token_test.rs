@@ -0,0 +1,70 @@
+use chrono::{Duration, TimeZone, Utc};
+
+use trade_alerts::TriggeredAlert;
+use trade_alerts::actions::{ActionSigner, AlertAction};
+use trade_alerts::errors::ActionTokenError;
+use trade_alerts::fixtures::triggered_alert;
+use trade_alerts::notify::{PayloadFormat, WebhookNotifier};
+
+fn triggered() -> TriggeredAlert {
+    TriggeredAlert {
+        user_id: "user-1".to_string(),
+        trigger_price: 1.1002,
+        triggered_at: Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap(),
+        ..triggered_alert("abc123", "eurusd", 1.1)
+    }
+}
+
+#[test]
+fn test_action_token_round_trip() {
+    let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    let signer = ActionSigner::new("secret").with_ttl(Duration::hours(1));
+    let token = signer.token(AlertAction::MuteSymbol, &triggered(), now);
+
+    let claims = signer.verify(&token, now + Duration::minutes(59)).unwrap();
+    assert_eq!(claims.action, AlertAction::MuteSymbol);
+    assert_eq!(claims.hash, "abc123");
+    assert_ne!(claims.owner, "user-1", "Tokens never carry the user ID");
+    assert_eq!(signer.resolve_owner(&claims, ["user-2", "user-1"]).as_deref(), Some("user-1"));
+    assert_eq!(ActionSigner::new("secret").resolve_owner(&claims, ["user-1"]).as_deref(), Some("user-1"), "Resolves in other processes");
+    assert_eq!(ActionSigner::new("secret").resolve_owner(&claims, ["user-2"]), None);
+    assert_eq!(claims.symbol, "eurusd");
+
+    assert_eq!(signer.verify(&token, now + Duration::hours(2)), Err(ActionTokenError::Expired));
+    assert_eq!(ActionSigner::new("other").verify(&token, now), Err(ActionTokenError::InvalidSignature));
+    assert_eq!(signer.verify("not-a-token", now), Err(ActionTokenError::Malformed));
+
+    // Swapping in claims signed for another alert breaks the signature
+    let (_, signature) = token.split_once('.').unwrap();
+    let other = signer.token(AlertAction::DeleteAlert, &triggered(), now);
+    let (payload, _) = other.split_once('.').unwrap();
+    assert_eq!(
+        signer.verify(&format!("{}.{}", payload, signature), now),
+        Err(ActionTokenError::InvalidSignature)
+    );
+    assert!(!format!("{:?}", signer).contains("secret\""));
+}
+
+#[test]
+fn test_webhook_payload_action_links() {
+    let signer = ActionSigner::new("secret").with_base_url("https://alerts.example.com/actions/");
+    let alert = triggered();
+
+    let event = WebhookNotifier::new("https://example.com/hook".to_string())
+        .with_action_links(signer.clone())
+        .payload(&alert);
+    let mute = event["actions"]["mute_symbol"].as_str().unwrap();
+    let token = mute.strip_prefix("https://alerts.example.com/actions/").unwrap();
+    assert_eq!(signer.verify(token, Utc::now()).unwrap().action, AlertAction::MuteSymbol);
+    assert!(event["actions"]["delete_alert"].is_string());
+
+    let flat = WebhookNotifier::new("https://example.com/hook".to_string())
+        .with_format(PayloadFormat::Flat)
+        .with_action_links(signer.clone())
+        .payload(&alert);
+    assert!(flat["action_mute_symbol"].is_string());
+    assert!(flat["action_delete_alert"].is_string());
+
+    let plain = WebhookNotifier::new("https://example.com/hook".to_string()).payload(&alert);
+    assert!(plain.get("actions").is_none());
+}
@@ -0,0 +1,64 @@
+use chrono::{Duration, TimeZone, Utc};
+
+use trade_alerts::db::AgePolicy;
+#[cfg(feature = "fixtures")]
+use trade_alerts::{Alert, TriggeredAlert, errors::NotifyError, notify::Notifier};
+
+#[test]
+fn test_is_stale() {
+    let policy = AgePolicy::new(Duration::days(30));
+    let now = Utc.with_ymd_and_hms(2024, 6, 30, 12, 0, 0).unwrap();
+
+    assert!(policy.is_stale(now - Duration::days(31), now));
+    assert!(!policy.is_stale(now - Duration::days(29), now));
+    assert!(!policy.auto_archive);
+    assert!(policy.with_auto_archive(true).auto_archive);
+}
+
+/// Counts the stale warnings it sends.
+#[cfg(feature = "fixtures")]
+#[derive(Default)]
+struct StaleWarnings(std::sync::atomic::AtomicUsize);
+
+#[cfg(feature = "fixtures")]
+#[async_trait::async_trait]
+impl Notifier for StaleWarnings {
+    fn name(&self) -> &str {
+        "stale-warnings"
+    }
+
+    async fn notify(&self, _alert: &TriggeredAlert) -> Result<(), NotifyError> {
+        Ok(())
+    }
+
+    async fn notify_stale(&self, _alert: &Alert, _age: Duration) -> Result<(), NotifyError> {
+        self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_kept_alerts_are_warned_once() {
+    use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    let mut old = AlertFixture::new("xlx-old", "EUR/USD", 1.10).row(&config);
+    old[&config.created_at_column_name] = (Utc::now() - Duration::days(40)).to_rfc3339().into();
+    let mut recent = AlertFixture::new("xlx-recent", "EUR/USD", 1.12).row(&config);
+    recent[&config.created_at_column_name] = (Utc::now() - Duration::days(2)).to_rfc3339().into();
+    server.insert_rows("alerts", vec![old, recent]);
+
+    let policy = AgePolicy::new(Duration::days(30));
+    let warnings = StaleWarnings::default();
+    let supabase = server.supabase();
+    let report = supabase.enforce_age_policy(&policy, &config, &table_config("alert_history"), &[&warnings]).await.unwrap();
+    assert_eq!(report.stale.len(), 1);
+    assert!(report.archived.is_empty() && report.errors.is_empty());
+
+    let report = supabase.enforce_age_policy(&policy, &config, &table_config("alert_history"), &[&warnings]).await.unwrap();
+    assert!(report.stale.is_empty(), "The owner was already warned");
+    assert_eq!(warnings.0.load(std::sync::atomic::Ordering::Relaxed), 1);
+    assert_eq!(server.rows("alerts").len(), 2, "Stale alerts are kept without auto archive");
+}
@@ -0,0 +1,110 @@
+#![cfg(feature = "fixtures")]
+
+use serde_json::{Value, json};
+
+use trade_alerts::AlertUpdate;
+use trade_alerts::db::{AlertOp, ApplyMode, OpStatus};
+use trade_alerts::fixtures::{AlertFixture, FixtureServer, alert_rows, table_config};
+
+/// Starts a server with the fixture alerts at version 1 and a price for new alerts.
+async fn start() -> FixtureServer {
+    let server = FixtureServer::start().await;
+    let rows = alert_rows().into_iter().zip(1..).map(|(mut row, id)| {
+        row["id"] = id.into();
+        row["version"] = 1.into();
+        row
+    });
+    server.insert_rows("alerts", rows.collect());
+    server.set_price("USD/JPY", 150.0);
+    server
+}
+
+fn row(server: &FixtureServer, hash: &str) -> Value {
+    server.rows("alerts").into_iter().find(|row| row["hash"] == hash).unwrap_or_default()
+}
+
+fn price_level(server: &FixtureServer, hash: &str) -> Option<f64> {
+    server
+        .rows("alerts")
+        .iter()
+        .find(|row| row["hash"] == hash)
+        .map(|row| row["price_level"].as_f64().unwrap())
+}
+
+#[tokio::test]
+async fn test_apply_operations_best_effort() {
+    let config = table_config("alerts");
+    let server = start().await;
+    let supabase = server.supabase();
+
+    let operations = vec![
+        AlertOp::Create(AlertFixture::new("xlx-usdjpy", "USD/JPY", 152.0).alert()),
+        AlertOp::Update {
+            hash: "xlx-eurusd".to_string(),
+            update: AlertUpdate { price_level: Some(1.12), ..AlertUpdate::default() },
+        },
+        AlertOp::Delete { hash: "xlx-missing".to_string() },
+        AlertOp::Disable { hash: "xlx-gbpusd".to_string() },
+        AlertOp::Delete { hash: "xlx-aapl".to_string() },
+    ];
+    let results = supabase.apply_operations_with(&server.xylex_api(), operations, ApplyMode::BestEffort, &config).await;
+
+    let statuses: Vec<bool> = results.iter().map(|result| result.is_applied()).collect();
+    assert_eq!(statuses, vec![true, true, false, true, true], "{:?}", results);
+    assert_eq!(results[2].hash, "xlx-missing");
+    assert_eq!(price_level(&server, "xlx-usdjpy"), Some(152.0));
+    assert_eq!(price_level(&server, "xlx-eurusd"), Some(1.12));
+    assert_eq!(price_level(&server, "xlx-aapl"), None);
+    let disabled = row(&server, "xlx-gbpusd");
+    assert!(!disabled["expires_at"].is_null());
+    assert_eq!((row(&server, "xlx-eurusd")["version"].as_i64(), disabled["version"].as_i64()), (Some(2), Some(2)));
+}
+
+#[tokio::test]
+async fn test_apply_operations_rolls_back() {
+    let config = table_config("alerts");
+    let server = start().await;
+    let supabase = server.supabase();
+    let before = server.rows("alerts");
+
+    let operations = vec![
+        AlertOp::Create(AlertFixture::new("xlx-usdjpy", "USD/JPY", 152.0).alert()),
+        AlertOp::Update {
+            hash: "xlx-eurusd".to_string(),
+            update: AlertUpdate { price_level: Some(1.12), ..AlertUpdate::default() },
+        },
+        AlertOp::Delete { hash: "xlx-aapl".to_string() },
+        AlertOp::Create(AlertFixture::new("xlx-gbpusd", "GBP/USD", 1.3).alert()),
+        AlertOp::Disable { hash: "xlx-eurusd".to_string() },
+    ];
+    let results = supabase.apply_operations_with(&server.xylex_api(), operations, ApplyMode::AllOrNothing, &config).await;
+
+    let statuses: Vec<&OpStatus> = results.iter().map(|result| &result.status).collect();
+    assert_eq!(statuses[..3], [&OpStatus::RolledBack, &OpStatus::RolledBack, &OpStatus::RolledBack], "{:?}", results);
+    assert!(matches!(statuses[3], OpStatus::Failed(e) if e.contains("Duplicate")));
+    assert_eq!(statuses[4], &OpStatus::Skipped);
+
+    assert_eq!(price_level(&server, "xlx-usdjpy"), None);
+    assert_eq!(price_level(&server, "xlx-eurusd"), Some(1.10));
+    assert_eq!(price_level(&server, "xlx-aapl"), Some(200.0));
+    assert_eq!(server.rows("alerts").len(), before.len());
+
+    // Undoing an update is a new write, and a deleted row is inserted without its generated id
+    assert_eq!(row(&server, "xlx-eurusd")["version"], 3);
+    assert!(row(&server, "xlx-aapl").get("id").is_none());
+}
+
+#[tokio::test]
+async fn test_apply_operations_detects_concurrent_changes() {
+    let config = table_config("alerts");
+    let server = start().await;
+    let supabase = server.supabase();
+
+    // Another writer changes the alert between the read and the update
+    let changed = json!([{ "hash": "xlx-eurusd", "price_level": 1.15, "version": 2 }]);
+    server.queue_responses("/rest/v1/alerts", vec![(200, json!([row(&server, "xlx-eurusd")])), (200, json!([])), (200, changed.clone()), (200, changed)]);
+
+    let operations = vec![AlertOp::Disable { hash: "xlx-eurusd".to_string() }];
+    let results = supabase.apply_operations_with(&server.xylex_api(), operations, ApplyMode::BestEffort, &config).await;
+    assert!(matches!(&results[0].status, OpStatus::Failed(e) if e.contains("version 2")), "{:?}", results);
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "fixtures")]
+
+use serde_json::{Value, json};
+use trade_alerts::db::{AlertSearch, SearchHit, SearchScope};
+use trade_alerts::fixtures::{FixtureServer, table_config};
+
+fn active(id: i64, hash: &str, note: &str, tags: &[&str]) -> Value {
+    json!({
+        "id": id, "hash": hash, "user_id": "user456", "symbol": "EUR/USD", "price_level": 1.10,
+        "initial_direction": "buy", "metadata": { "note": note }, "tags": tags,
+    })
+}
+
+fn archived(hash: &str, note: &str, triggered_at: &str) -> Value {
+    json!({
+        "hash": hash, "user_id": "user456", "symbol": "EUR/USD", "price_level": 1.10, "trigger_price": 1.11,
+        "metadata": { "note": note }, "tags": ["swing"], "triggered_at": triggered_at,
+    })
+}
+
+#[test]
+fn test_search_filters() {
+    let config = table_config("alerts");
+    let search = AlertSearch::new().with_user_id("user456").with_text("50%_off*").with_tag("swing");
+    assert_eq!(
+        search.filters(&config),
+        vec![
+            ("user_id".to_string(), "eq.user456".to_string()),
+            ("metadata->>note".to_string(), "ilike.*50\\%\\_off*".to_string()),
+            ("tags".to_string(), "cs.[\"swing\"]".to_string()),
+        ]
+    );
+
+    let search = AlertSearch::new().with_full_text("breakout -failed").with_page(0, 5000);
+    assert_eq!(search.filters(&config)[0].1, "wfts.breakout -failed");
+    assert_eq!(search.limit, 1000);
+}
+
+#[tokio::test]
+async fn test_search_active_and_archived_alerts() {
+    let (config, history_config) = (table_config("alerts"), table_config("alerts_history"));
+    let server = FixtureServer::start().await;
+    server.insert_rows("alerts", vec![
+        active(1, "a1", "Breakout retest", &["swing"]),
+        active(2, "a2", "earnings run-up", &["earnings"]),
+        active(3, "a3", "Second breakout", &["swing", "breakout"]),
+    ]);
+    server.insert_rows("alerts_history", vec![
+        archived("h1", "old breakout", "2024-07-01T10:00:00Z"),
+        archived("h2", "failed breakout", "2024-07-02T10:00:00Z"),
+    ]);
+    let supabase = server.supabase();
+
+    let search = AlertSearch::new().with_text("BREAKOUT");
+    let page = supabase.search_alerts(&search, &config, &history_config).await.unwrap();
+    assert_eq!(page.hits.iter().map(SearchHit::hash).collect::<Vec<_>>(), vec!["a3", "a1"]);
+    assert_eq!(page.hits[0].note(), Some("Second breakout"));
+    assert_eq!(page.next_offset, None);
+
+    let search = search.with_scope(SearchScope::All).with_page(0, 3);
+    let page = supabase.search_alerts(&search, &config, &history_config).await.unwrap();
+    assert_eq!(page.hits.iter().map(SearchHit::hash).collect::<Vec<_>>(), vec!["a3", "a1", "h2"]);
+    assert!(matches!(page.hits[2], SearchHit::Archived(_)));
+    assert_eq!(page.next_offset, Some(3));
+
+    let page = supabase.search_alerts(&search.clone().with_page(3, 3), &config, &history_config).await.unwrap();
+    assert_eq!(page.hits.iter().map(SearchHit::hash).collect::<Vec<_>>(), vec!["h1"]);
+    assert_eq!(page.next_offset, None);
+
+    let search = AlertSearch::new().with_full_text("breakout -failed").with_scope(SearchScope::Archived);
+    let page = supabase.search_alerts(&search, &config, &history_config).await.unwrap();
+    assert_eq!(page.hits.iter().map(SearchHit::hash).collect::<Vec<_>>(), vec!["h1"]);
+
+    let search = AlertSearch::new().with_tag("swing").with_tag("breakout");
+    let page = supabase.search_alerts(&search, &config, &history_config).await.unwrap();
+    assert_eq!(page.hits.iter().map(SearchHit::hash).collect::<Vec<_>>(), vec!["a3"]);
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "fixtures")]
+
+use chrono::{Duration, Utc};
+
+use trade_alerts::data::{MockProvider, XylexApi};
+use trade_alerts::market_hours::{AssetClass, MarketHours};
+use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config, triggered_alert};
+use trade_alerts::notify::IntentLog;
+use trade_alerts::{ScheduleConfig, TradeAlerts, TriggeredAlert};
+
+#[tokio::test]
+async fn test_get_alert_status() {
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    let mut waiting = AlertFixture::new("xlx-waiting", "EUR/USD", 1.10).latest_price(1.08).row(&config);
+    waiting["id"] = 1.into();
+    let mut expired = AlertFixture::new("xlx-expired", "EUR/USD", 1.20).latest_price(1.08).row(&config);
+    expired["id"] = 2.into();
+    expired[&config.expiry_column_name] = (Utc::now() - Duration::minutes(1)).to_rfc3339().into();
+    let mut triggering = AlertFixture::new("xlx-triggering", "EUR/USD", 1.05).latest_price(1.08).row(&config);
+    triggering["id"] = 3.into();
+    triggering[&config.trigger_state_column_name] = "triggering".into();
+    server.insert_rows("alerts", vec![waiting, expired, triggering]);
+
+    let path = std::env::temp_dir().join(format!("alert_status_{}.jsonl", std::process::id()));
+    std::fs::remove_file(&path).ok();
+    let stuck = TriggeredAlert {
+        trigger_price: 1.04,
+        ..triggered_alert("xlx-triggering", "EUR/USD", 1.05)
+    };
+    IntentLog::new(&path).record(&stuck).unwrap();
+
+    let alerts = TradeAlerts::builder()
+        .with_provider(MockProvider::new().with_price("EUR/USD", 1.09))
+        .with_supabase(server.supabase())
+        .with_table_config(config.clone())
+        .with_intent_log(&path)
+        .build()
+        .unwrap();
+
+    let before = alerts.get_alert_status("xlx-waiting").await.unwrap();
+    assert!(before.active);
+    assert_eq!((before.last_evaluated_at, before.last_price, before.distance), (None, None, None));
+
+    assert!(alerts.run_once().await.unwrap().triggered.is_empty());
+    let status = alerts.get_alert_status("xlx-waiting").await.unwrap();
+    assert_eq!(status.alert.price_level, 1.10);
+    assert!(status.active && status.in_session && status.market_open && status.scheduled);
+    assert!(!status.expired && !status.triggering);
+    assert!(status.last_evaluated_at.is_some());
+    assert_eq!(status.last_price, Some(1.09));
+    assert!((status.distance.unwrap() - 0.01).abs() < 1e-9);
+    assert!((status.distance_percent.unwrap() - 0.917).abs() < 1e-3);
+    assert!(status.pending_notifications.is_empty());
+
+    let status = alerts.get_alert_status("xlx-expired").await.unwrap();
+    assert!(status.expired && !status.active);
+    assert_eq!(status.last_evaluated_at, None, "Expired alerts are not evaluated");
+
+    let status = alerts.get_alert_status("xlx-triggering").await.unwrap();
+    assert!(status.triggering && !status.active);
+    assert_eq!(status.pending_notifications.len(), 1);
+    assert_eq!(status.pending_notifications[0].alert.hash, "xlx-triggering");
+
+    assert!(alerts.get_alert_status("xlx-missing").await.is_err());
+    std::fs::remove_file(&path).ok();
+}
+
+#[tokio::test]
+async fn test_alert_status_includes_market_hours_and_schedule() {
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    server.insert_rows("alerts", vec![AlertFixture::new("xlx-waiting", "EUR/USD", 1.10).latest_price(1.08).row(&config)]);
+    let alerts = |hours: MarketHours| {
+        TradeAlerts::builder()
+            .with_provider(MockProvider::new().with_price("EUR/USD", 1.09))
+            .with_xylex(XylexApi::new(String::new(), String::new()).with_market_hours(hours))
+            .with_supabase(server.supabase())
+            .with_table_config(config.clone())
+            .with_schedule(ScheduleConfig::new(std::time::Duration::from_secs(3600)))
+            .build()
+            .unwrap()
+    };
+
+    let equity = alerts(MarketHours::new().with_asset_class("EUR/USD", AssetClass::Equity));
+    let status = equity.get_alert_status("xlx-waiting").await.unwrap();
+    assert_eq!(status.market_open, AssetClass::Equity.is_open(Utc::now()));
+    assert_eq!(status.active, status.market_open);
+
+    let crypto = alerts(MarketHours::new().with_asset_class("EUR/USD", AssetClass::Crypto));
+    let status = crypto.get_alert_status("xlx-waiting").await.unwrap();
+    assert!(status.market_open && status.scheduled && status.active, "Every group is due before the first check");
+
+    crypto.run_once().await.unwrap();
+    let status = crypto.get_alert_status("xlx-waiting").await.unwrap();
+    assert!(status.market_open && !status.scheduled && !status.active, "The group is not due again for an hour");
+}
@@ -0,0 +1,58 @@
+use serde_json::json;
+
+use trade_alerts::db::TableConfig;
+use trade_alerts::{Alert, Condition};
+
+#[test]
+fn test_alert_from_row() {
+    let config: TableConfig = TableConfig::default();
+
+    let row = json!({
+        "id": 1,
+        "hash": "xlx-a-1234",
+        "price_level": 1.0950,
+        "user_id": "user123",
+        "symbol": "eur/usd",
+        "initial_direction": "buy",
+        "condition": { "type": "spread_above", "value": 2.5 },
+        "expires_at": "2030-01-01T00:00:00+00:00",
+        "metadata": { "note": "breakout retest" },
+        "tags": ["breakout", "swing"],
+        "version": 3,
+    });
+
+    let alert: Alert = Alert::from_row(&row, &config).expect("Failed to parse alert row");
+    assert_eq!(alert.hash, "xlx-a-1234");
+    assert_eq!(alert.price_level, 1.0950);
+    assert_eq!(alert.user_id, "user123");
+    assert_eq!(alert.symbol, "eur/usd");
+    assert_eq!(alert.direction.as_deref(), Some("buy"));
+    assert_eq!(alert.condition, Condition::SpreadAbove(2.5));
+    assert!(alert.expires_at.is_some());
+    assert_eq!(alert.metadata, Some(json!({ "note": "breakout retest" })));
+    assert_eq!(alert.tags, vec!["breakout".to_string(), "swing".to_string()]);
+    assert_eq!(alert.version, Some(3));
+
+    let incomplete = json!({ "hash": "xlx-a-1234", "price_level": 1.0950 });
+    assert!(Alert::from_row(&incomplete, &config).is_err());
+}
+
+#[test]
+fn test_alert_and_config_serde() {
+    let alert = Alert::new("xlx-a-1234".to_string(), 1.1, "eur/usd".to_string(), "user123".to_string())
+        .with_condition(Condition::PriceAbove(1.2));
+    let json = serde_json::to_value(&alert).expect("Failed to serialize alert");
+    assert_eq!(serde_json::from_value::<Alert>(json).expect("Failed to deserialize alert"), alert);
+
+    let minimal: Alert = serde_json::from_value(json!({
+        "hash": "xlx-a-1234",
+        "price_level": 1.1,
+        "user_id": "user123",
+        "symbol": "eur/usd",
+    })).expect("Failed to deserialize minimal alert");
+    assert_eq!(minimal.condition, Condition::PriceLevel);
+
+    let config: TableConfig = serde_json::from_value(json!({ "tablename": "alerts_v2" }))
+        .expect("Failed to deserialize table config");
+    assert_eq!(config, TableConfig { tablename: "alerts_v2".to_string(), ..TableConfig::default() });
+}
@@ -0,0 +1,50 @@
+use trade_alerts::data::{AlpacaPlan, AlpacaProvider};
+use trade_alerts::data::alpaca::{ALPACA_DATA_ENDPOINT, normalize_alpaca_symbol};
+
+#[test]
+fn test_alpaca_plans() {
+    assert_eq!(AlpacaPlan::Basic.feed(), "iex");
+    assert_eq!(AlpacaPlan::AlgoTraderPlus.feed(), "sip");
+    assert_eq!("algo-trader-plus".parse::<AlpacaPlan>(), Ok(AlpacaPlan::AlgoTraderPlus));
+
+    let provider = AlpacaProvider::new("key".to_string(), "secret".to_string());
+    assert_eq!(provider.endpoint, ALPACA_DATA_ENDPOINT);
+    assert!((provider.rate_limit.requests_per_second - 200.0 / 60.0).abs() < 1e-9);
+    assert!(!format!("{:?}", provider).contains("secret"));
+
+    let provider = provider.with_plan(AlpacaPlan::AlgoTraderPlus);
+    assert!((provider.rate_limit.requests_per_second - 10_000.0 / 60.0).abs() < 1e-9);
+    assert_eq!(normalize_alpaca_symbol("brk/b"), "BRK.B");
+    assert_eq!(normalize_alpaca_symbol(" aapl "), "AAPL");
+}
+
+#[cfg(feature = "replay-http")]
+#[tokio::test]
+async fn test_alpaca_cassette() {
+    use trade_alerts::data::PriceProvider;
+    use trade_alerts::errors::XylexApiError;
+    use trade_alerts::replay_http::HttpReplay;
+
+    let cassette = "tests/cassettes/alpaca_snapshots.json";
+    let provider = AlpacaProvider::new("key".to_string(), "secret".to_string())
+        .with_replay(HttpReplay::replay(cassette).unwrap());
+
+    // AAPL traded during the session, MSFT only pre-market so the previous close is quoted
+    let quotes = provider.fetch_prices(&["aapl", "MSFT"]).await.unwrap();
+    assert_eq!(quotes[0].symbol, "aapl");
+    assert_eq!((quotes[0].price, quotes[0].bid, quotes[0].ask), (232.98, Some(232.97), Some(233.0)));
+    assert_eq!(quotes[0].volume, Some(1210455.0));
+    assert_eq!((quotes[1].price, quotes[1].bid), (459.54, None));
+
+    let unknown = provider.fetch_price("NOPE").await;
+    assert_eq!(unknown, Err(XylexApiError::InvalidSymbol("symbol not found: NOPE".to_string())));
+
+    let provider = AlpacaProvider::new("key".to_string(), "secret".to_string())
+        .with_extended_hours()
+        .with_replay(HttpReplay::replay(cassette).unwrap());
+    let msft = provider.fetch_price("MSFT").await.unwrap();
+    assert_eq!((msft.price, msft.bid, msft.ask), (468.1, None, Some(468.5)));
+
+    let provider = provider.with_plan(AlpacaPlan::AlgoTraderPlus);
+    assert!(matches!(provider.fetch_price("AAPL").await, Err(XylexApiError::ConfigurationError(_))));
+}
@@ -0,0 +1,68 @@
+use chrono::{DateTime, Duration, Utc};
+
+use trade_alerts::backtest::{BacktestReport, backtest};
+use trade_alerts::data::Candle;
+use trade_alerts::{Alert, Condition, Timeframe};
+
+fn candles(
+    symbol: &str,
+    start: DateTime<Utc>,
+    prices: &[(f64, f64, f64, f64)]
+) -> Vec<Candle> {
+    prices
+        .iter()
+        .enumerate()
+        .map(|(i, &(open, high, low, close))| Candle {
+            symbol: symbol.to_string(),
+            timeframe: Timeframe::OneHour,
+            open_time: start + Duration::hours(i as i64),
+            open,
+            high,
+            low,
+            close,
+        })
+        .collect()
+}
+
+#[test]
+fn test_backtest_report() {
+    let start: DateTime<Utc> = DateTime::parse_from_rfc3339("2030-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+    let history = candles("eur/usd", start, &[
+        (1.10, 1.11, 1.09, 1.10),
+        (1.10, 1.13, 1.10, 1.12),
+        (1.12, 1.16, 1.12, 1.15),
+    ]);
+
+    let alerts = vec![
+        Alert::new("up".to_string(), 1.12, "eur/usd".to_string(), "user".to_string()),
+        Alert::new("far".to_string(), 1.50, "eur/usd".to_string(), "user".to_string()),
+        Alert::new("above".to_string(), 0.0, "eur/usd".to_string(), "user".to_string())
+            .with_condition(Condition::PriceAbove(1.15)),
+        Alert::new("unknown".to_string(), 1.0, "gbp/usd".to_string(), "user".to_string()),
+    ];
+
+    let report: BacktestReport = backtest(&alerts, &history);
+    assert_eq!((report.triggered_count, report.untriggered_count), (2, 2));
+    assert_eq!(report.start, Some(start));
+
+    let up = &report.outcomes[0];
+    assert_eq!(up.direction.as_deref(), Some("sell"));
+    assert_eq!(up.triggered_at, Some(start + Duration::hours(1)));
+    assert_eq!(up.trigger_price, Some(1.12));
+    assert_eq!(report.outcomes[2].time_to_trigger_secs, Some(7200));
+
+    let untriggered: Vec<&str> = report.untriggered().iter().map(|outcome| outcome.hash.as_str()).collect();
+    assert_eq!(untriggered, vec!["far", "unknown"]);
+    assert_eq!(report.time_to_trigger.min_secs, Some(3600));
+    assert_eq!(report.time_to_trigger.max_secs, Some(7200));
+    assert_eq!(report.time_to_trigger.mean_secs, Some(5400.0));
+
+    let decoded: BacktestReport = serde_json::from_str(&serde_json::to_string(&report).unwrap()).unwrap();
+    assert_eq!(decoded, report);
+
+    let csv: String = report.to_csv();
+    let mut lines = csv.lines();
+    assert!(lines.next().unwrap().starts_with("hash,user_id,symbol,price_level"));
+    assert!(lines.next().unwrap().starts_with("up,user,eur/usd,1.12,sell,true,2030-01-01T01:00:00+00:00,1.12,3600"));
+    assert_eq!(csv.lines().count(), 5);
+}
@@ -0,0 +1,64 @@
+use serde_json::json;
+
+use trade_alerts::data::PreviousPrices;
+use trade_alerts::db::TableConfig;
+use trade_alerts::errors::AlertError;
+use trade_alerts::{Alert, BandMode, Condition};
+
+#[test]
+fn test_band_modes() {
+    let enter = Condition::Band(BandMode::Enter);
+    assert!(enter.is_met_on_band(1.10, 1.12, Some(1.09), 1.11));
+    assert!(enter.is_met_on_band(1.12, 1.10, Some(1.13), 1.12));
+    assert!(!enter.is_met_on_band(1.10, 1.12, Some(1.105), 1.11));
+    assert!(!enter.is_met_on_band(1.10, 1.12, None, 1.11));
+
+    let exit = Condition::Band(BandMode::Exit);
+    assert!(exit.is_met_on_band(1.10, 1.12, Some(1.11), 1.13));
+    assert!(!exit.is_met_on_band(1.10, 1.12, Some(1.09), 1.13));
+
+    assert!(Condition::Band(BandMode::Inside).is_met_on_band(1.10, 1.12, None, 1.10));
+    assert!(Condition::Band(BandMode::Outside).is_met_on_band(1.10, 1.12, None, 1.121));
+    assert!(!Condition::PriceLevel.is_met_on_band(1.10, 1.12, Some(1.09), 1.11));
+}
+
+#[test]
+fn test_band_alert_roundtrip() {
+    let alert = Alert::builder()
+        .user_id("user123")
+        .symbol("eur/usd")
+        .price_level(1.10)
+        .band(1.12, BandMode::Exit)
+        .build()
+        .unwrap();
+    assert_eq!(alert.upper_price_level, Some(1.12));
+    assert_eq!(Condition::from_value(Some(&alert.condition.to_value())), Some(Condition::Band(BandMode::Exit)));
+
+    let missing = Alert::builder()
+        .user_id("user123")
+        .symbol("eur/usd")
+        .price_level(1.10)
+        .condition(Condition::Band(BandMode::Enter))
+        .build();
+    assert_eq!(missing, Err(AlertError::MissingField("upper_price_level".to_string())));
+
+    let row = json!({
+        "hash": "xlx-band",
+        "price_level": 1.10,
+        "upper_price_level": 1.12,
+        "user_id": "user123",
+        "symbol": "eur/usd",
+        "condition": { "type": "band", "value": "enter" },
+    });
+    let alert = Alert::from_row(&row, &TableConfig::default()).unwrap();
+    assert_eq!(alert.upper_price_level, Some(1.12));
+    assert_eq!(alert.condition, Condition::Band(BandMode::Enter));
+}
+
+#[test]
+fn test_previous_prices() {
+    let prices = PreviousPrices::default();
+    assert_eq!(prices.record("eur/usd", 1.10), None);
+    assert_eq!(prices.record("eur/usd", 1.11), Some(1.10));
+    assert_eq!(prices.clone().get("eur/usd"), Some(1.11));
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "fixtures")]
+
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+
+use trade_alerts::retry::RetryPolicy;
+use trade_alerts::data::{CreditBudget, RateLimiter, XylexApi};
+use trade_alerts::fixtures::FixtureServer;
+
+/// Starts a fixture server quoting every symbol of the tests at `1.5`.
+async fn serve_quotes() -> FixtureServer {
+    let server = FixtureServer::start().await;
+    for symbol in ["AAPL", "MSFT", "TSLA", "A", "B", "C", "D", "E", "F", "G", "H"] {
+        server.set_price(symbol, 1.5);
+    }
+    server
+}
+
+#[tokio::test]
+async fn test_batched_quotes() {
+    let server = serve_quotes().await;
+    let api = XylexApi::new("key".to_string(), server.price_endpoint());
+
+    let quotes = api.fetch_quotes_for_symbols(["AAPL", "MSFT", "TSLA"]).await.expect("Failed to fetch quotes");
+    assert_eq!(quotes.iter().map(|quote| quote.symbol.as_str()).collect::<Vec<_>>(), vec!["AAPL", "MSFT", "TSLA"]);
+    assert_eq!(server.requests_to("/price").len(), 1);
+
+    let api = api.with_batch_size(Some(2));
+    api.fetch_quotes_for_symbols(["AAPL", "MSFT", "TSLA"]).await.expect("Failed to fetch quotes");
+    assert_eq!(server.requests_to("/price").len(), 3);
+}
+
+#[tokio::test]
+async fn test_batched_quotes_fallback() {
+    let server = serve_quotes().await;
+    server.disable_batch_quotes();
+    let api = XylexApi::new("key".to_string(), server.price_endpoint());
+
+    let quotes = api.fetch_quotes_for_symbols(["AAPL", "MSFT"]).await.expect("Failed to fetch quotes");
+    assert_eq!(quotes.len(), 2);
+    assert_eq!(server.requests_to("/price").len(), 3, "One failed batch, then one request per symbol");
+
+    api.fetch_quotes_for_symbols(["AAPL", "MSFT"]).await.expect("Failed to fetch quotes");
+    assert_eq!(server.requests_to("/price").len(), 5, "Batching should stay disabled");
+}
+
+#[tokio::test]
+async fn test_concurrent_quotes() {
+    let server = serve_quotes().await;
+    server.set_delay(Duration::from_millis(200));
+    let api = XylexApi::new("key".to_string(), server.price_endpoint()).with_batch_size(None).with_concurrency(4);
+    let symbols = ["A", "B", "C", "D", "E", "F", "G", "H"];
+
+    let quotes = api.fetch_quotes_for_symbols(symbols).await.expect("Failed to fetch quotes");
+    assert_eq!(server.requests_to("/price").len(), 8);
+    assert!(server.peak_in_flight() > 1, "Requests should run concurrently");
+    assert!(server.peak_in_flight() <= 4, "At most 4 requests should be in flight");
+    assert_eq!(quotes.iter().map(|quote| quote.symbol.as_str()).collect::<Vec<_>>(), symbols, "Quotes should keep the symbol order");
+}
+
+#[tokio::test]
+async fn test_rate_limited_quotes() {
+    let server = serve_quotes().await;
+    let mut api = XylexApi::new("key".to_string(), server.price_endpoint()).with_batch_size(Some(2));
+    api.rate_limit = Some(RateLimiter::new(20.0).with_burst(1.0));
+
+    let started = Instant::now();
+    api.fetch_quotes_for_symbols(["A", "B", "C", "D", "E", "F"]).await.expect("Failed to fetch quotes");
+    assert_eq!(server.requests_to("/price").len(), 3);
+    assert!(started.elapsed() >= Duration::from_millis(90), "Batches should wait for the rate limit");
+
+    let api = api.with_batch_size(None);
+    let started = Instant::now();
+    api.fetch_quotes_for_symbols(["A", "B", "C", "D", "E"]).await.expect("Failed to fetch quotes");
+    assert_eq!(server.requests_to("/price").len(), 8);
+    assert!(started.elapsed() >= Duration::from_millis(190), "Concurrent requests should share the rate limit");
+}
+
+#[tokio::test]
+async fn test_rate_limited_batches_keep_batching() {
+    let server = serve_quotes().await;
+    server.queue_responses("/price", vec![(429, json!({ "code": 429, "status": "error" }))]);
+    let policy = RetryPolicy { initial_backoff: Duration::from_millis(10), ..RetryPolicy::default() };
+    let api = XylexApi::new("key".to_string(), server.price_endpoint()).with_retry_policy(policy);
+
+    let quotes = api.fetch_quotes_for_symbols(["AAPL", "MSFT"]).await.expect("Failed to fetch quotes");
+    assert_eq!(quotes.len(), 2);
+    assert_eq!(server.requests_to("/price").len(), 2, "The rate limited batch should be retried");
+
+    api.fetch_quotes_for_symbols(["AAPL", "MSFT"]).await.expect("Failed to fetch quotes");
+    assert_eq!(server.requests_to("/price").len(), 3, "Batching should stay enabled");
+}
+
+#[tokio::test]
+async fn test_batches_are_charged_per_symbol() {
+    let server = serve_quotes().await;
+    let api = XylexApi::new("key".to_string(), server.price_endpoint()).with_budget(CreditBudget::new(1.0, 100.0));
+
+    api.fetch_quotes_for_symbols(["AAPL", "MSFT", "TSLA"]).await.expect("Failed to fetch quotes");
+    let metrics = api.budget_metrics().expect("No budget");
+    assert_eq!((metrics.requests, metrics.spent), (1, 3.0));
+}
@@ -0,0 +1,135 @@
+use trade_alerts::db::BatchInsertion;
+
+#[test]
+fn test_batch_insertion_is_inserted() {
+    let inserted = BatchInsertion { hash: "abc".to_string(), error: None };
+    let rejected = BatchInsertion { hash: "def".to_string(), error: Some("Duplicate alert".to_string()) };
+
+    assert!(inserted.is_inserted());
+    assert!(!rejected.is_inserted());
+}
+
+#[cfg(feature = "fixtures")]
+mod fixtures {
+    use serde_json::Value;
+    use trade_alerts::Alert;
+    use trade_alerts::db::{BatchInsertion, DuplicatePolicy, QuotaPolicy, TableConfig};
+    use trade_alerts::fixtures::{AlertFixture, FixtureServer, alert_rows};
+
+    fn alert(hash: &str, user_id: &str, price_level: f64) -> Alert {
+        AlertFixture::new(hash, "EUR/USD", price_level).user_id(user_id).alert()
+    }
+
+    fn inserted(results: &[BatchInsertion]) -> Vec<&str> {
+        results.iter().filter(|result| result.is_inserted()).map(|result| result.hash.as_str()).collect()
+    }
+
+    fn stored(server: &FixtureServer) -> Vec<String> {
+        server.rows("alerts").iter().filter_map(|row| row["hash"].as_str().map(String::from)).collect()
+    }
+
+    async fn server() -> FixtureServer {
+        let server: FixtureServer = FixtureServer::start().await;
+        server.set_rows("alerts", alert_rows());
+        server.set_price("EUR/USD", 1.08);
+        server
+    }
+
+    #[tokio::test]
+    async fn test_batch_skips_existing_and_repeated_hashes() {
+        let server: FixtureServer = server().await;
+        let alerts: Vec<Alert> = vec![
+            alert("xlx-eurusd", "user123", 1.20),
+            alert("xlx-new", "user123", 1.20),
+            alert("xlx-new", "user123", 1.30),
+        ];
+
+        let results = server.supabase().add_alerts_batch_with(&server.xylex_api(), alerts, TableConfig::default()).await;
+
+        assert_eq!(inserted(&results), vec!["xlx-new"]);
+        assert_eq!(results[0].error.as_deref(), Some("Duplicate alert"));
+        assert_eq!(results[2].error.as_deref(), Some("Duplicate alert"));
+        assert_eq!(stored(&server), vec!["xlx-eurusd", "xlx-gbpusd", "xlx-aapl", "xlx-new"]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_enforces_quota_per_user() {
+        let server: FixtureServer = server().await;
+        let supabase = server.supabase().with_quota_policy(QuotaPolicy { max_active_alerts_per_user: 3 });
+        let alerts: Vec<Alert> = vec![
+            alert("xlx-a", "user123", 1.20),
+            alert("xlx-b", "user456", 1.20),
+            alert("xlx-c", "user123", 1.30),
+            alert("xlx-d", "user456", 1.30),
+        ];
+
+        let results = supabase.add_alerts_batch_with(&server.xylex_api(), alerts, TableConfig::default()).await;
+
+        // user123 already has two alerts, user456 one
+        assert_eq!(inserted(&results), vec!["xlx-a", "xlx-b", "xlx-d"]);
+        assert!(results[2].error.as_deref().is_some_and(|e| e.starts_with("Quota exceeded")));
+    }
+
+    #[tokio::test]
+    async fn test_batch_rejects_identical_alerts() {
+        let server: FixtureServer = server().await;
+        let supabase = server.supabase().with_duplicate_policy(DuplicatePolicy::Reject);
+        let alerts: Vec<Alert> = vec![
+            alert("xlx-same", "user123", 1.10),
+            alert("xlx-first", "user123", 1.20),
+            alert("xlx-second", "user123", 1.20),
+        ];
+
+        let results = supabase.add_alerts_batch_with(&server.xylex_api(), alerts, TableConfig::default()).await;
+
+        assert_eq!(inserted(&results), vec!["xlx-first"]);
+        assert!(results[0].error.as_deref().is_some_and(|e| e.contains("xlx-eurusd")));
+        assert!(results[2].error.as_deref().is_some_and(|e| e.contains("xlx-first")));
+    }
+
+    #[tokio::test]
+    async fn test_batch_replaces_identical_alerts() {
+        let server: FixtureServer = server().await;
+        let rows: Vec<Value> = alert_rows()
+            .into_iter()
+            .zip(1..)
+            .map(|(mut row, id)| {
+                row["id"] = Value::from(id);
+                row
+            })
+            .collect();
+        server.set_rows("alerts", rows);
+        let supabase = server.supabase().with_duplicate_policy(DuplicatePolicy::Replace);
+        let alerts: Vec<Alert> = vec![
+            alert("xlx-same", "user123", 1.10),
+            alert("xlx-first", "user123", 1.20),
+            alert("xlx-second", "user123", 1.20),
+        ];
+
+        let results = supabase.add_alerts_batch_with(&server.xylex_api(), alerts, TableConfig::default()).await;
+
+        assert_eq!(inserted(&results), vec!["xlx-same", "xlx-second"]);
+        assert!(results[1].error.as_deref().is_some_and(|e| e.contains("xlx-second")));
+        assert_eq!(stored(&server), vec!["xlx-gbpusd", "xlx-aapl", "xlx-same", "xlx-second"]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_retries_rejected_chunk_row_by_row() {
+        let server: FixtureServer = server().await;
+        server.set_unique("alerts", &["user_id", "symbol", "price_level"]);
+        let alerts: Vec<Alert> = vec![
+            alert("xlx-a", "user123", 1.20),
+            alert("xlx-taken", "user123", 1.10),
+            alert("xlx-b", "user123", 1.30),
+        ];
+
+        let results = server.supabase().add_alerts_batch_with(&server.xylex_api(), alerts, TableConfig::default()).await;
+
+        assert_eq!(inserted(&results), vec!["xlx-a", "xlx-b"]);
+        assert!(results[1].error.is_some());
+        let inserts: usize = server.requests().iter().filter(|request| request.starts_with("POST /rest/v1/alerts")).count();
+        assert_eq!(inserts, 4);
+        let rows: Vec<Value> = server.rows("alerts");
+        assert_eq!(rows.len(), 5);
+    }
+}
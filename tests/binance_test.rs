@@ -0,0 +1,21 @@
+use trade_alerts::data::BinanceProvider;
+use trade_alerts::data::binance::{BINANCE_API_ENDPOINT, normalize_binance_symbol};
+
+#[test]
+fn test_normalize_binance_symbol() {
+    assert_eq!(normalize_binance_symbol("BTC/USDT"), "BTCUSDT");
+    assert_eq!(normalize_binance_symbol("eth-usdt"), "ETHUSDT");
+    assert_eq!(normalize_binance_symbol("sol_usdc"), "SOLUSDC");
+    assert_eq!(normalize_binance_symbol("BNBUSDT"), "BNBUSDT");
+}
+
+#[test]
+fn test_binance_provider_builder() {
+    let provider = BinanceProvider::new();
+    assert_eq!(provider.endpoint, BINANCE_API_ENDPOINT);
+    assert!(!provider.include_24h_stats);
+
+    let provider = provider.with_endpoint("https://api.binance.us/").with_24h_stats();
+    assert_eq!(provider.endpoint, "https://api.binance.us");
+    assert!(provider.include_24h_stats);
+}
@@ -0,0 +1,18 @@
+use trade_alerts::bootstrap::{CheckStatus, SetupCheck, SetupReport};
+
+#[test]
+fn test_setup_report() {
+    let check = |name: &str, status: CheckStatus| SetupCheck { name: name.to_string(), status, detail: "detail".to_string() };
+
+    let ready = SetupReport { checks: vec![check("environment", CheckStatus::Passed)] };
+    assert!(ready.is_ready());
+    assert_eq!(ready.to_string(), "[ok] environment: detail\n");
+
+    let broken = SetupReport {
+        checks: vec![check("provider_auth", CheckStatus::Failed), check("price_fetch", CheckStatus::Skipped)],
+    };
+    assert!(!broken.is_ready());
+    assert_eq!(broken.failures().len(), 1);
+    assert_eq!(broken.to_string(), "[failed] provider_auth: detail\n[skipped] price_fetch: detail\n");
+}
+
@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use trade_alerts::data::{BudgetPolicy, BudgetState, CreditBudget};
+
+#[test]
+fn test_credit_budget_states() {
+    let budget: CreditBudget = CreditBudget::new(1.0, 10.0).with_policy(BudgetPolicy {
+        slow_down_at: 0.5,
+        slow_down_delay: Duration::from_millis(0),
+        prioritize_at: 0.8,
+    });
+
+    for _ in 0..4 {
+        assert_eq!(budget.charge().expect("Charge failed"), BudgetState::Normal);
+    }
+    assert_eq!(budget.charge().expect("Charge failed"), BudgetState::SlowDown);
+
+    for _ in 0..3 {
+        budget.charge().expect("Charge failed");
+    }
+    assert_eq!(budget.state(), BudgetState::Prioritize);
+    assert_eq!(budget.remaining_requests(), 2);
+
+    budget.charge().expect("Charge failed");
+    assert_eq!(budget.charge().expect("Charge failed"), BudgetState::Exhausted);
+    assert!(budget.charge().is_err(), "Charging past the budget should fail");
+
+    let metrics = budget.metrics();
+    assert_eq!(metrics.requests, 10);
+    assert_eq!(metrics.spent, 10.0);
+    assert_eq!(metrics.remaining, 0.0);
+}
@@ -0,0 +1,36 @@
+use trade_alerts::errors::AlertError;
+use trade_alerts::utils::format::hash_alert;
+use trade_alerts::{Alert, Condition};
+
+#[test]
+fn test_alert_builder() {
+    let alert = Alert::builder()
+        .symbol("eur/usd")
+        .user_id("user123")
+        .price_level(1.1)
+        .condition(Condition::PriceAbove(1.1))
+        .hash_prefix("xlx-")
+        .build()
+        .expect("Failed to build alert");
+
+    assert_eq!(alert.symbol, "eur/usd");
+    assert_eq!(alert.user_id, "user123");
+    assert_eq!(alert.condition, Condition::PriceAbove(1.1));
+    assert_eq!(alert.hash, hash_alert("user123", "eur/usd", 1.1, "xlx-"));
+
+    let explicit = Alert::builder().hash("h1").symbol("eur/usd").user_id("user123").price_level(1.1).build();
+    assert_eq!(explicit.map(|alert| alert.hash), Ok("h1".to_string()));
+
+    assert_eq!(
+        Alert::builder().symbol("eur/usd").price_level(1.1).build(),
+        Err(AlertError::MissingField("user_id".to_string()))
+    );
+    assert!(matches!(
+        Alert::builder().symbol(" ").user_id("user123").price_level(1.1).build(),
+        Err(AlertError::InvalidField(_))
+    ));
+    assert!(matches!(
+        Alert::builder().symbol("eur/usd").user_id("user123").price_level(f64::NAN).build(),
+        Err(AlertError::InvalidField(_))
+    ));
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "fixtures")]
+
+use serde_json::json;
+
+use trade_alerts::db::rest::{eq, is_in};
+use trade_alerts::errors::SupabaseError;
+use trade_alerts::fixtures::{AlertFixture, FixtureServer, alert_rows, table_config};
+
+/// Starts a server with the fixture alerts and a second EUR/USD alert of another user.
+async fn start() -> FixtureServer {
+    let server = FixtureServer::start().await;
+    server.insert_rows("alerts", alert_rows());
+    server.insert_rows("alerts", vec![AlertFixture::new("xlx-eurusd-2", "EUR/USD", 1.05).user_id("user789").row(&table_config("alerts"))]);
+    server
+}
+
+fn hashes(server: &FixtureServer) -> Vec<String> {
+    server.rows("alerts").iter().filter_map(|row| row["hash"].as_str().map(String::from)).collect()
+}
+
+#[tokio::test]
+async fn test_delete_alerts_by_user_id() {
+    let server = start().await;
+    let supabase = server.supabase();
+    let config = table_config("alerts");
+
+    assert_eq!(supabase.delete_alerts_by_user_id("user123", &config).await.unwrap(), 2);
+    assert_eq!(hashes(&server), vec!["xlx-aapl", "xlx-eurusd-2"]);
+    assert_eq!(supabase.delete_alerts_by_user_id("user123", &config).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_delete_alerts_by_symbol() {
+    let server = start().await;
+    let supabase = server.supabase();
+    let config = table_config("alerts");
+
+    assert_eq!(supabase.delete_alerts_by_symbol("EUR/USD", &config).await.unwrap(), 2);
+    assert_eq!(hashes(&server), vec!["xlx-gbpusd", "xlx-aapl"]);
+}
+
+#[tokio::test]
+async fn test_delete_where() {
+    let server = start().await;
+    let supabase = server.supabase();
+    let config = table_config("alerts");
+
+    let filters = [eq("user_id", "user123"), is_in("hash", &["xlx-gbpusd", "xlx-aapl"])];
+    assert_eq!(supabase.delete_where(&filters, &config).await.unwrap(), 1);
+    assert_eq!(hashes(&server), vec!["xlx-eurusd", "xlx-aapl", "xlx-eurusd-2"]);
+
+    // Without filters every alert would be deleted, so nothing is sent
+    let error = supabase.delete_where(&[], &config).await.unwrap_err();
+    assert!(matches!(error.downcast_ref::<SupabaseError>(), Some(SupabaseError::DeletionError(_))));
+    assert_eq!(server.rows("alerts").len(), 3);
+    assert_eq!(server.requests().iter().filter(|request| request.starts_with("DELETE")).count(), 1);
+}
+
+#[tokio::test]
+async fn test_failed_bulk_delete() {
+    let server = start().await;
+    let supabase = server.supabase();
+    server.queue_responses("/rest/v1/alerts", vec![(500, json!({ "message": "statement timeout" }))]);
+
+    let error = supabase.delete_alerts_by_symbol("EUR/USD", &table_config("alerts")).await.unwrap_err();
+    assert!(matches!(error.downcast_ref::<SupabaseError>(), Some(SupabaseError::DeletionError(e)) if e.contains("statement timeout")));
+    assert_eq!(server.rows("alerts").len(), 4);
+}
@@ -0,0 +1,92 @@
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+
+use trade_alerts::data::CandleAggregator;
+use trade_alerts::{EvaluateOn, Timeframe};
+
+#[test]
+fn test_candle_aggregation() {
+    let start: DateTime<Utc> = DateTime::parse_from_rfc3339("2030-01-01T10:00:00Z").unwrap().with_timezone(&Utc);
+    assert_eq!(Timeframe::OneHour.candle_start(start + Duration::minutes(59)), start);
+
+    let candles = CandleAggregator::default();
+    assert!(candles.record("eur/usd", Timeframe::FiveMinutes, 1.10, start).is_none());
+    assert!(candles.record("eur/usd", Timeframe::FiveMinutes, 1.15, start + Duration::minutes(2)).is_none());
+    assert!(candles.record("eur/usd", Timeframe::FiveMinutes, 1.11, start + Duration::minutes(4)).is_none());
+
+    let closed = candles
+        .record("eur/usd", Timeframe::FiveMinutes, 1.12, start + Duration::minutes(5))
+        .expect("Candle should close at the boundary");
+    assert_eq!((closed.open, closed.high, closed.low, closed.close), (1.10, 1.15, 1.10, 1.11));
+    assert_eq!(closed.open_time, start);
+    assert_eq!(candles.current("eur/usd", Timeframe::FiveMinutes).unwrap().open, 1.12);
+}
+
+#[test]
+fn test_evaluate_on_from_value() {
+    assert_eq!(EvaluateOn::from_value(None), Some(EvaluateOn::Tick));
+
+    let stored = json!({ "type": "candle_close", "value": "1h" });
+    assert_eq!(EvaluateOn::from_value(Some(&stored)), Some(EvaluateOn::CandleClose(Timeframe::OneHour)));
+    assert_eq!(EvaluateOn::CandleClose(Timeframe::OneHour).to_value(), stored);
+    assert_eq!(EvaluateOn::from_value(Some(&json!({ "type": "candle_close", "value": "7m" }))), None);
+}
+
+#[test]
+fn test_calendar_timeframes() {
+    // A Wednesday
+    let at: DateTime<Utc> = DateTime::parse_from_rfc3339("2030-01-16T15:30:00Z").unwrap().with_timezone(&Utc);
+    let day = |day: &str| DateTime::parse_from_rfc3339(&format!("2030-{}T00:00:00Z", day)).unwrap().with_timezone(&Utc);
+
+    assert!(Timeframe::OneWeek.is_calendar() && !Timeframe::OneDay.is_calendar());
+    assert_eq!(Timeframe::OneWeek.candle_start(at), day("01-14"));
+    assert_eq!(Timeframe::OneWeek.next_candle_start(at), day("01-21"));
+    assert_eq!(Timeframe::OneWeek.previous_candle_start(at), day("01-07"));
+    assert_eq!(Timeframe::OneMonth.candle_start(at), day("01-01"));
+    assert_eq!(Timeframe::OneMonth.next_candle_start(day("02-10")), day("03-01"));
+    assert_eq!(Timeframe::OneMonth.previous_candle_start(at), DateTime::parse_from_rfc3339("2029-12-01T00:00:00Z").unwrap());
+
+    let stored = json!({ "type": "candle_close", "value": "1w" });
+    assert_eq!(EvaluateOn::from_value(Some(&stored)), Some(EvaluateOn::CandleClose(Timeframe::OneWeek)));
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_weekly_close_alerts() {
+    use trade_alerts::data::{Candle, MockProvider};
+    use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+
+    let config = table_config("alerts");
+    let server = FixtureServer::start().await;
+    let weekly = |hash: &str, created_at: DateTime<Utc>| {
+        let mut row = AlertFixture::new(hash, "EUR/USD", 1.10).latest_price(1.08).row(&config);
+        row[&config.evaluate_on_column_name] = EvaluateOn::CandleClose(Timeframe::OneWeek).to_value();
+        row[&config.created_at_column_name] = json!(created_at.to_rfc3339());
+        row
+    };
+    let now = Utc::now();
+    server.insert_rows("alerts", vec![weekly("xlx-weekly", now - Duration::weeks(3)), weekly("xlx-new", now)]);
+
+    let candle = |open_time: DateTime<Utc>, close: f64| Candle {
+        symbol: "EUR/USD".to_string(),
+        timeframe: Timeframe::OneWeek,
+        open_time,
+        open: 1.08,
+        high: close.max(1.08),
+        low: close.min(1.08),
+        close,
+    };
+    let last_week = Timeframe::OneWeek.previous_candle_start(now);
+    server.set_candles("EUR/USD", vec![candle(last_week, 1.12), candle(Timeframe::OneWeek.candle_start(now), 1.05)]);
+
+    let api = server.xylex_api().with_history_endpoint(server.history_endpoint());
+    let provider = MockProvider::new().with_price("EUR/USD", 1.05);
+    for _ in 0..2 {
+        let report = api.check_alerts_with(&provider, &server.supabase(), &config).await.unwrap();
+        let triggered: Vec<(&str, f64)> = report.triggered.iter().map(|alert| (alert.hash.as_str(), alert.trigger_price)).collect();
+        assert_eq!(triggered, vec![("xlx-weekly", 1.12)], "Only the close of last week counts, and only for the older alert");
+    }
+
+    let fetches = server.requests().iter().filter(|request| request.contains("interval=1week")).count();
+    assert_eq!(fetches, 1, "The closed candle should be cached until the next week closes");
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "fixtures")]
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+
+use trade_alerts::{TradeAlerts, TriggeredAlert};
+use trade_alerts::data::MockProvider;
+use trade_alerts::db::StorageBucket;
+use trade_alerts::errors::NotifyError;
+use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+use trade_alerts::notify::{ChartRenderer, Notifier};
+
+/// Draws the same PNG for every alert, none for `GBP/USD`.
+struct StaticChart;
+
+#[async_trait]
+impl ChartRenderer for StaticChart {
+    async fn render(&self, alert: &TriggeredAlert) -> Option<Vec<u8>> {
+        (alert.symbol != "GBP/USD").then(|| b"\x89PNG".to_vec())
+    }
+}
+
+/// Records the chart URL of every delivery.
+struct UrlRecorder(Arc<Mutex<Vec<Option<String>>>>);
+
+#[async_trait]
+impl Notifier for UrlRecorder {
+    fn name(&self) -> &str {
+        "recorder"
+    }
+
+    async fn notify(&self, alert: &TriggeredAlert) -> Result<(), NotifyError> {
+        self.0.lock().unwrap().push(alert.chart_url().map(String::from));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_chart_snapshots() {
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    let history_config = table_config("alert_history");
+    for (id, (hash, symbol)) in [("xlx-a", "EUR/USD"), ("xlx-b", "GBP/USD")].into_iter().enumerate() {
+        let mut row = AlertFixture::new(hash, symbol, 1.10).latest_price(1.08).row(&config);
+        row["id"] = (id as i64 + 1).into();
+        server.insert_rows("alerts", vec![row]);
+    }
+    server.insert_object("charts", "triggers/old.png", Vec::new(), Utc::now() - chrono::Duration::days(30));
+    server.insert_object("charts", "triggers/recent.png", Vec::new(), Utc::now() - chrono::Duration::days(1));
+    server.insert_object("charts", "other/old.png", Vec::new(), Utc::now() - chrono::Duration::days(30));
+
+    let bucket = StorageBucket::new("charts")
+        .with_prefix("/triggers/")
+        .with_retention(Duration::from_secs(7 * 24 * 3600));
+    let urls = Arc::new(Mutex::new(Vec::new()));
+    let alerts = TradeAlerts::builder()
+        .with_provider(MockProvider::new().with_price("EUR/USD", 1.11).with_price("GBP/USD", 1.11))
+        .with_supabase(server.supabase())
+        .with_table_config(config)
+        .with_history(history_config.clone())
+        .with_notifier(UrlRecorder(urls.clone()))
+        .with_chart_snapshots(StaticChart, bucket.clone())
+        .build()
+        .unwrap();
+
+    let report = alerts.run_once().await.unwrap();
+    let charted = report.triggered.iter().find(|alert| alert.hash == "xlx-a").unwrap();
+    let file = format!("triggers/{}", charted.chart_file_name());
+    let url = server.supabase().public_url(&bucket, &file);
+    assert_eq!(charted.chart_url(), Some(url.as_str()));
+    assert!(url.ends_with(&format!("/storage/v1/object/public/charts/{}", file)));
+    assert!(report.triggered.iter().find(|alert| alert.hash == "xlx-b").unwrap().chart_url().is_none());
+
+    let mut delivered = urls.lock().unwrap().clone();
+    delivered.sort();
+    assert_eq!(delivered, vec![None, Some(url.clone())], "Notifications link the chart");
+    let history = server.rows("alert_history");
+    let archived = history.iter().find(|row| row["hash"] == "xlx-a").unwrap();
+    assert_eq!(archived[&history_config.chart_url_column_name], url.as_str());
+    assert_eq!(
+        server.objects("charts"),
+        vec!["other/old.png".to_string(), "triggers/recent.png".to_string(), file],
+        "Only old charts under the prefix are deleted"
+    );
+
+    // Old charts are deleted at most once an hour
+    let mut row = AlertFixture::new("xlx-c", "EUR/USD", 1.10).latest_price(1.08).row(&table_config("alerts"));
+    row["id"] = 3.into();
+    server.insert_rows("alerts", vec![row]);
+    server.insert_object("charts", "triggers/older.png", Vec::new(), Utc::now() - chrono::Duration::days(30));
+    assert_eq!(alerts.run_once().await.unwrap().triggered.len(), 1);
+    assert!(server.objects("charts").contains(&"triggers/older.png".to_string()));
+    assert_eq!(listed(&server), 1);
+}
+
+fn listed(server: &FixtureServer) -> usize {
+    server.requests().iter().filter(|request| request.starts_with("POST /storage/v1/object/list/")).count()
+}
+
+#[tokio::test]
+async fn test_listing_stops_at_the_first_recent_chart() {
+    let server = FixtureServer::start().await;
+    let bucket = StorageBucket::new("charts").with_prefix("triggers");
+    server.insert_object("charts", "triggers/old.png", Vec::new(), Utc::now() - chrono::Duration::days(30));
+    for index in 0..150 {
+        let created_at = Utc::now() - chrono::Duration::minutes(index);
+        server.insert_object("charts", &format!("triggers/recent-{}.png", index), Vec::new(), created_at);
+    }
+
+    let cutoff = Utc::now() - chrono::Duration::days(7);
+    assert_eq!(server.supabase().delete_objects_before(&bucket, cutoff).await.unwrap(), 1);
+    assert_eq!(listed(&server), 1, "The second page only holds recent charts");
+    assert_eq!(server.objects("charts").len(), 150);
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "fixtures")]
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use trade_alerts::TriggeredAlert;
+use trade_alerts::errors::NotifyError;
+use trade_alerts::fixtures::{FixtureServer, alert_rows, table_config};
+use trade_alerts::notify::{NotificationRouter, Notifier, RouteTarget};
+
+/// Records the hash of every delivered alert.
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+#[async_trait]
+impl Notifier for Recorder {
+    fn name(&self) -> &str {
+        "recorder"
+    }
+
+    async fn notify(&self, alert: &TriggeredAlert) -> Result<(), NotifyError> {
+        self.0.lock().unwrap().push(alert.hash.clone());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_check_and_notify() {
+    let config = table_config("alerts");
+    let server = FixtureServer::start().await;
+    server.insert_rows("alerts", alert_rows());
+    server.set_price("EUR/USD", 1.11);
+    server.set_price("GBP/USD", 1.26);
+    server.set_price("AAPL", 195.0);
+
+    let delivered = Arc::new(Mutex::new(Vec::new()));
+    let router = NotificationRouter::new()
+        .with_channel("recorder", Recorder(delivered.clone()))
+        .with_default_channels(vec!["recorder".to_string(), "missing".to_string()]);
+
+    let notified = server.xylex_api().check_and_notify(&server.supabase(), &config, &router).await.unwrap();
+    assert_eq!(notified.len(), 1);
+    let (alert, deliveries) = &notified[0];
+    assert_eq!(alert.hash, "xlx-eurusd");
+    assert_eq!(*delivered.lock().unwrap(), vec!["xlx-eurusd".to_string()]);
+    assert_eq!(deliveries[0].target, RouteTarget::Channel("recorder".to_string()));
+    assert!(deliveries[0].error.is_none());
+    assert!(deliveries[1].error.is_some(), "An unknown channel is reported, not fatal");
+}
+
+#[tokio::test]
+async fn test_check_and_notify_grouped_and_dry_run() {
+    let config = table_config("alerts");
+    let server = FixtureServer::start().await;
+    server.insert_rows("alerts", alert_rows());
+    server.set_price("EUR/USD", 1.11);
+    server.set_price("GBP/USD", 1.26);
+    server.set_price("AAPL", 195.0);
+
+    let delivered = Arc::new(Mutex::new(Vec::new()));
+    let router = NotificationRouter::new()
+        .with_channel("recorder", Recorder(delivered.clone()))
+        .with_default_channels(vec!["recorder".to_string()]);
+
+    let notified = server.xylex_api().with_dry_run().check_and_notify(&server.supabase(), &config, &router).await.unwrap();
+    assert_eq!(notified.len(), 1);
+    assert!(notified[0].1.is_empty(), "A dry run delivers nothing");
+    assert!(delivered.lock().unwrap().is_empty());
+
+    let router = router.with_grouping();
+    let notified = server.xylex_api().check_and_notify(&server.supabase(), &config, &router).await.unwrap();
+    assert_eq!(notified.len(), 1);
+    let (alert, deliveries) = &notified[0];
+    assert_eq!(alert.hash, "xlx-eurusd");
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(deliveries[0].target, RouteTarget::Channel("recorder".to_string()));
+    assert_eq!(*delivered.lock().unwrap(), vec!["xlx-eurusd".to_string()]);
+}
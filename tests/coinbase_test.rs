@@ -0,0 +1,113 @@
+use trade_alerts::data::CoinbaseProvider;
+use trade_alerts::data::coinbase::{COINBASE_API_ENDPOINT, COINBASE_PAGE_SIZE, normalize_coinbase_symbol};
+
+#[test]
+fn test_normalize_coinbase_symbol() {
+    assert_eq!(normalize_coinbase_symbol("BTC/USD"), "BTC-USD");
+    assert_eq!(normalize_coinbase_symbol("ethusd"), "ETH-USD");
+    assert_eq!(normalize_coinbase_symbol("SOLUSDT"), "SOL-USDT");
+    assert_eq!(normalize_coinbase_symbol("btc-usd"), "BTC-USD");
+}
+
+#[test]
+fn test_coinbase_provider_builder() {
+    let provider = CoinbaseProvider::new();
+    assert_eq!(provider.endpoint, COINBASE_API_ENDPOINT);
+    assert_eq!(provider.page_size, COINBASE_PAGE_SIZE);
+
+    let provider = provider.with_endpoint("https://api.coinbase.com/").with_page_size(0);
+    assert_eq!(provider.endpoint, "https://api.coinbase.com");
+    assert_eq!(provider.page_size, 1);
+}
+
+#[cfg(feature = "replay-http")]
+#[tokio::test]
+async fn test_coinbase_cassette() {
+    use trade_alerts::data::PriceProvider;
+    use trade_alerts::errors::XylexApiError;
+    use trade_alerts::replay_http::HttpReplay;
+
+    let replay = HttpReplay::replay("tests/cassettes/coinbase_products.json").unwrap();
+    let provider = CoinbaseProvider::new().with_page_size(2).with_replay(replay);
+
+    let symbols = provider.supported_symbols().await.unwrap().unwrap();
+    assert_eq!(symbols, vec!["BTC/USD", "ETH/USD", "SOL/USDT"]);
+
+    let quotes = provider.fetch_prices(&["BTCUSD", "eth/usd"]).await.unwrap();
+    assert_eq!(quotes[0].symbol, "BTCUSD");
+    assert_eq!(quotes[0].price, 66311.24);
+    assert_eq!(quotes[1].volume, Some(118530.21451973));
+
+    let unknown = provider.fetch_price("DOGE/EUR").await;
+    assert_eq!(unknown, Err(XylexApiError::InvalidSymbol("ProductID DOGE-EUR could not be found".to_string())));
+}
+
+#[cfg(feature = "fixtures")]
+mod fixtures {
+    use serde_json::{Value, json};
+
+    use trade_alerts::data::{CoinbaseProvider, PriceProvider};
+    use trade_alerts::errors::XylexApiError;
+    use trade_alerts::fixtures::FixtureServer;
+
+    const PRODUCTS: &str = "/api/v3/brokerage/market/products";
+
+    fn product(product_id: &str, price: &str) -> Value {
+        let (base, quote) = product_id.split_once('-').unwrap();
+        json!({
+            "product_id": product_id,
+            "price": price,
+            "base_display_symbol": base,
+            "quote_display_symbol": quote,
+            "status": "online",
+            "trading_disabled": false,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_unknown_products_are_skipped() {
+        let server = FixtureServer::start().await;
+        let provider = CoinbaseProvider::new().with_endpoint(server.url());
+
+        server.queue_responses(PRODUCTS, vec![(200, json!({ "products": [product("BTC-USD", "66311.24")] }))]);
+        let quotes = provider.fetch_prices(&["BTC/USD", "DOGE/EUR"]).await.unwrap();
+        assert_eq!(quotes.iter().map(|quote| quote.symbol.as_str()).collect::<Vec<_>>(), vec!["BTC/USD"]);
+
+        // A batch rejected for one product id is fetched one by one
+        server.queue_responses(&format!("{}/BTC-USD", PRODUCTS), vec![(200, product("BTC-USD", "66311.24"))]);
+        server.queue_responses(&format!("{}/DOGE-EUR", PRODUCTS), vec![(404, json!({ "error": "NOT_FOUND", "message": "ProductID DOGE-EUR could not be found" }))]);
+        server.queue_responses(PRODUCTS, vec![(400, json!({ "error": "INVALID_ARGUMENT", "error_details": "invalid product_id DOGE-EUR", "message": "invalid product_id DOGE-EUR" }))]);
+        let quotes = provider.fetch_prices(&["BTC/USD", "DOGE/EUR"]).await.unwrap();
+        assert_eq!((quotes.len(), quotes[0].price), (1, 66311.24));
+        assert_eq!(server.requests_to(PRODUCTS).len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_bad_requests_are_mapped_by_their_body() {
+        let server = FixtureServer::start().await;
+        let provider = CoinbaseProvider::new().with_endpoint(server.url());
+
+        server.queue_responses(PRODUCTS, vec![(400, json!({ "error": "INVALID_ARGUMENT", "message": "valid product_id is required" }))]);
+        assert_eq!(
+            provider.fetch_price("???").await,
+            Err(XylexApiError::InvalidSymbol("valid product_id is required".to_string()))
+        );
+
+        server.queue_responses(PRODUCTS, vec![(400, json!({ "error": "INVALID_ARGUMENT", "message": "limit must be at most 250" }))]);
+        assert_eq!(
+            provider.products().await,
+            Err(XylexApiError::NetworkError("Coinbase returned 400: limit must be at most 250".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_paging_stops_when_the_listing_does_not_advance() {
+        let server = FixtureServer::start().await;
+        let page = json!({ "products": [product("BTC-USD", "66311.24"), product("ETH-USD", "3120.5")] });
+        server.queue_responses(PRODUCTS, vec![(200, page.clone()), (200, page.clone()), (200, page)]);
+
+        let provider = CoinbaseProvider::new().with_endpoint(server.url()).with_page_size(2);
+        assert_eq!(provider.products().await.unwrap().len(), 2);
+        assert_eq!(server.requests_to(PRODUCTS).len(), 2, "An endpoint ignoring the offset should not be paged forever");
+    }
+}
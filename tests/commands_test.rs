@@ -0,0 +1,27 @@
+use trade_alerts::Condition;
+use trade_alerts::commands::AlertCommand;
+
+#[test]
+fn test_parse_commands() {
+    assert_eq!(
+        "alert EURUSD above 1.10".parse::<AlertCommand>(),
+        Ok(AlertCommand::Create {
+            symbol: "eurusd".to_string(),
+            price_level: 1.10,
+            condition: Condition::PriceAbove(1.10),
+        })
+    );
+    assert!(matches!(
+        "alert btcusd at 65000".parse::<AlertCommand>(),
+        Ok(AlertCommand::Create { condition: Condition::PriceLevel, .. })
+    ));
+    assert_eq!(
+        "  delete xlx-a-1234 ".parse::<AlertCommand>(),
+        Ok(AlertCommand::Delete { hash: "xlx-a-1234".to_string() })
+    );
+    assert_eq!("list".parse::<AlertCommand>(), Ok(AlertCommand::List));
+
+    assert!("alert eurusd near 1.10".parse::<AlertCommand>().is_err());
+    assert!("alert eurusd above -1".parse::<AlertCommand>().is_err());
+    assert!("hello".parse::<AlertCommand>().is_err());
+}
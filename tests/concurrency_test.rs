@@ -0,0 +1,43 @@
+#![cfg(feature = "fixtures")]
+
+use serde_json::json;
+
+use trade_alerts::{AlertUpdate, TriggeredAlert};
+use trade_alerts::errors::SupabaseError;
+use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config, triggered_alert};
+
+#[tokio::test]
+async fn test_concurrent_write_conflicts() {
+    let fixtures = FixtureServer::start().await;
+    let config = table_config("alerts");
+    let mut row = AlertFixture::new("xlx-a", "EUR/USD", 1.1).row(&config);
+    row["id"] = json!(1);
+    row["version"] = json!(1);
+    fixtures.insert_rows("alerts", vec![row]);
+
+    let supabase = fixtures.supabase();
+    let version = supabase.fetch_alert_version("xlx-a", &config).await.unwrap();
+    assert_eq!(version, 1);
+
+    // Another instance edits the alert after it was read
+    let concurrent = AlertUpdate { price_level: Some(1.2), ..AlertUpdate::default() };
+    supabase.update_alert_by_hash("xlx-a", concurrent, config.clone()).await.unwrap();
+    assert_eq!(supabase.fetch_alert_version("xlx-a", &config).await.unwrap(), 2);
+
+    let stale = AlertUpdate { price_level: Some(1.3), ..AlertUpdate::default() };
+    let error = supabase.update_alert_if_version("xlx-a", stale, version, &config).await.unwrap_err();
+    assert!(matches!(error.downcast_ref::<SupabaseError>(), Some(SupabaseError::Conflict(_))), "{}", error);
+    assert_eq!(fixtures.rows("alerts")[0]["price_level"], json!(1.2));
+
+    let triggered = TriggeredAlert {
+        trigger_price: 1.2001,
+        ..triggered_alert("xlx-a", "EUR/USD", 1.2)
+    };
+    supabase.record_trigger(&triggered, &config).await.unwrap();
+    supabase.transfer_alert("xlx-a", "user456", &config).await.unwrap();
+    assert_eq!(supabase.fetch_alert_version("xlx-a", &config).await.unwrap(), 4);
+
+    let error = supabase.delete_alert_if_version("xlx-a", 2, &config).await.unwrap_err();
+    assert!(matches!(error.downcast_ref::<SupabaseError>(), Some(SupabaseError::Conflict(_))), "{}", error);
+    assert_eq!(fixtures.rows("alerts").len(), 1);
+}
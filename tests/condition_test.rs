@@ -0,0 +1,89 @@
+use chrono::{Duration, Utc};
+use serde_json::json;
+
+use trade_alerts::Condition;
+
+#[test]
+fn test_time_based_condition() {
+    let now = Utc::now();
+    let condition = Condition::At(now + Duration::minutes(5));
+
+    assert!(condition.is_time_based());
+    assert!(!condition.is_due(now));
+    assert!(condition.is_due(now + Duration::minutes(5)));
+    assert!(!Condition::PriceLevel.is_due(now));
+
+    let stored = json!({ "type": "at", "value": "2030-01-01T14:30:00Z" });
+    let parsed = Condition::from_value(Some(&stored)).expect("Failed to parse time-based condition");
+    assert!(matches!(parsed, Condition::At(_)));
+    assert_eq!(Condition::from_value(Some(&parsed.to_value())), Some(parsed));
+}
+
+#[test]
+fn test_condition_tree() {
+    use trade_alerts::ConditionTree;
+    use trade_alerts::data::Quote;
+
+    let leaf = |symbol: &str, condition: Condition| ConditionTree::Leaf {
+        symbol: symbol.to_string(),
+        condition: Box::new(condition),
+    };
+    let tree = ConditionTree::All(vec![
+        leaf("eur/usd", Condition::PriceAbove(1.10)),
+        ConditionTree::Any(vec![
+            leaf("gbp/usd", Condition::PriceBelow(1.25)),
+            leaf("usd/jpy", Condition::PriceAbove(160.0)),
+        ]),
+    ]);
+    assert_eq!(tree.symbols(), vec!["eur/usd", "gbp/usd", "usd/jpy"]);
+
+    let quotes = vec![
+        Quote::new("eur/usd".to_string(), 1.11),
+        Quote::new("gbp/usd".to_string(), 1.24),
+    ];
+    assert!(tree.is_met(0.0, "buy", &quotes));
+    assert!(!tree.is_met(0.0, "buy", &quotes[..1]), "Missing quotes should not hold");
+
+    let condition = Condition::Compound(tree);
+    assert_eq!(Condition::from_value(Some(&condition.to_value())), Some(condition));
+}
+
+#[test]
+fn test_volume_conditions() {
+    use trade_alerts::data::{Quote, VolumeHistory};
+
+    let history = VolumeHistory::new(3);
+    assert_eq!(history.record("btc/usd", 100.0), None);
+    assert_eq!(history.record("btc/usd", 200.0), Some(100.0));
+    assert_eq!(history.record("btc/usd", 300.0), Some(150.0));
+    assert_eq!(history.record("btc/usd", 400.0), Some(200.0));
+    assert_eq!(history.average("btc/usd"), Some(300.0));
+
+    let quote = Quote {
+        volume: Some(1000.0),
+        average_volume: Some(300.0),
+        ..Quote::new("btc/usd".to_string(), 65000.0)
+    };
+    assert!(Condition::VolumeAbove(900.0).is_met(0.0, "buy", &quote));
+    assert!(Condition::VolumeSpike { multiple_of_average: 3.0 }.is_met(0.0, "buy", &quote));
+    assert!(!Condition::VolumeSpike { multiple_of_average: 4.0 }.is_met(0.0, "buy", &quote));
+    assert!(!Condition::VolumeAbove(1.0).is_met(0.0, "buy", &Quote::new("btc/usd".to_string(), 1.0)));
+}
+
+#[test]
+fn test_price_level_tolerance() {
+    use trade_alerts::Tolerance;
+    use trade_alerts::data::Quote;
+
+    let touch = Quote::new("eur/usd".to_string(), 1.1001);
+    let through = Quote::new("eur/usd".to_string(), 1.1003);
+
+    assert!(Condition::PriceLevel.is_met_with_tolerance(1.1, "sell", &touch, None));
+    assert!(!Condition::PriceLevel.is_met_with_tolerance(1.1, "sell", &touch, Some(Tolerance::Pips(2.0))));
+    assert!(Condition::PriceLevel.is_met_with_tolerance(1.1, "sell", &through, Some(Tolerance::Pips(2.0))));
+    assert!(!Condition::PriceAbove(1.1).is_met_with_tolerance(0.0, "buy", &through, Some(Tolerance::Percent(1.0))));
+
+    let stored = Tolerance::Percent(0.5).to_value();
+    assert_eq!(Tolerance::from_value(Some(&stored)), Some(Tolerance::Percent(0.5)));
+    assert_eq!(Tolerance::from_value(None), None);
+}
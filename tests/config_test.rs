@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+use trade_alerts::config::{duration_field, parse_duration};
+use trade_alerts::errors::ConfigError;
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+    assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+    assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+    assert_eq!(parse_duration("2d"), Ok(Duration::from_secs(172_800)));
+    assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+    assert_eq!(parse_duration(" 45 "), Ok(Duration::from_secs(45)));
+
+    assert!(parse_duration("").is_err());
+    assert!(parse_duration("5").is_ok());
+    assert!(parse_duration("5w").is_err());
+    assert!(parse_duration("m5").is_err());
+    assert!(parse_duration("1h30").is_err());
+}
+
+#[test]
+fn test_duration_field_names_the_field() {
+    match duration_field("POLL_INTERVAL", "soon") {
+        Err(ConfigError::InvalidField { field, .. }) => assert_eq!(field, "POLL_INTERVAL"),
+        other => panic!("Expected an invalid field error, got {:?}", other),
+    }
+    assert!(duration_field("POLL_INTERVAL", "soon").unwrap_err().to_string().contains("POLL_INTERVAL"));
+}
@@ -0,0 +1,26 @@
+#![cfg(feature = "fixtures")]
+
+use trade_alerts::errors::XylexApiError;
+use trade_alerts::fixtures::FixtureServer;
+
+#[tokio::test]
+async fn test_convert() {
+    let server = FixtureServer::start().await;
+    server.set_price("EUR/USD", 1.25);
+    server.set_price("USD/JPY", 150.0);
+    server.set_price("GBP/EUR", 1.2);
+    let api = server.xylex_api();
+
+    assert_eq!(api.convert(100.0, "eur", "USD").await.unwrap(), 125.0);
+    assert_eq!(api.convert(125.0, "USD", "EUR").await.unwrap(), 100.0, "Inverse pair");
+    assert!((api.convert(100.0, "EUR", "JPY").await.unwrap() - 18_750.0).abs() < 1e-9, "Over USD");
+    assert!((api.convert(18_750.0, "JPY", "EUR").await.unwrap() - 100.0).abs() < 1e-9);
+    assert!((api.convert(10.0, "GBP", "USD").await.unwrap() - 15.0).abs() < 1e-9, "Over EUR");
+
+    let requests = server.requests().len();
+    assert_eq!(api.convert(42.0, "CHF", "chf").await.unwrap(), 42.0);
+    assert_eq!(server.requests().len(), requests, "Same currency needs no request");
+
+    assert!(matches!(api.convert(1.0, "EUR", "CHF").await, Err(XylexApiError::InvalidSymbol(_))));
+    assert!(matches!(api.convert(1.0, "EURO", "USD").await, Err(XylexApiError::InvalidSymbol(_))));
+}
@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+
+use trade_alerts::{Schedule, TradeAlerts};
+use trade_alerts::data::BinanceProvider;
+use trade_alerts::db::{Supabase, TableConfig};
+use trade_alerts::errors::ConfigError;
+use trade_alerts::utils::cron::CronExpression;
+
+fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+    // March 2024, the 1st is a Friday
+    Utc.with_ymd_and_hms(2024, 3, day, hour, minute, 0).unwrap()
+}
+
+#[test]
+fn test_cron_expression() {
+    let office: CronExpression = "*/5  9-17 * * MON-FRI".parse().unwrap();
+    assert_eq!(office.to_string(), "*/5 9-17 * * MON-FRI");
+    assert!(office.matches(at(1, 9, 0)) && office.matches(at(1, 17, 55)));
+    assert!(!office.matches(at(1, 9, 3)) && !office.matches(at(1, 18, 0)));
+    assert!(!office.matches(at(2, 12, 0)), "Saturday is outside the window");
+
+    assert_eq!(office.next_after(at(1, 9, 0)), Some(at(1, 9, 5)), "The next minute is strictly later");
+    assert_eq!(office.next_after(at(1, 9, 2) + chrono::Duration::seconds(30)), Some(at(1, 9, 5)));
+    assert_eq!(office.next_after(at(1, 17, 55)), Some(at(4, 9, 0)), "Friday evening waits for Monday");
+
+    let ends: CronExpression = "0 12 1,15 * SUN".parse().unwrap();
+    assert!(ends.matches(at(15, 12, 0)) && ends.matches(at(3, 12, 0)), "Either day field matches");
+    assert!(!ends.matches(at(4, 12, 0)));
+    let sunday: CronExpression = "30 0 * * 7".parse().unwrap();
+    assert_eq!(sunday.next_after(at(1, 0, 0)), Some(at(3, 0, 30)));
+    let yearly: CronExpression = "0 0 1 jan *".parse().unwrap();
+    assert_eq!(yearly.next_after(at(1, 0, 0)), Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()));
+    assert_eq!("0 0 30 2 *".parse::<CronExpression>().unwrap().next_after(at(1, 0, 0)), None);
+
+    for invalid in ["* * * *", "60 * * * *", "* 9-25 * * *", "*/0 * * * *", "* * * * FUNDAY", "* 17-9 * * *"] {
+        assert!(invalid.parse::<CronExpression>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_cycle_schedule() {
+    assert_eq!("30s".parse::<Schedule>(), Ok(Schedule::Interval(Duration::from_secs(30))));
+    assert_eq!("*/5 9-17 * * MON-FRI".parse::<Schedule>(), Ok(Schedule::Cron("*/5 9-17 * * MON-FRI".to_string())));
+    assert!("*/5 9-17 * *".parse::<Schedule>().is_err());
+    assert!(Schedule::Interval(Duration::ZERO).validate().is_err());
+    assert!(Schedule::Cron("* * * * *".to_string()).validate().is_ok());
+
+    let builder = TradeAlerts::builder()
+        .with_supabase(Supabase::new("key".to_string(), "https://example.supabase.co".to_string()))
+        .with_table_config(TableConfig::default())
+        .with_provider(BinanceProvider::new());
+
+    let invalid = builder.clone().with_cycle_schedule(Schedule::Cron("*/5 9-25 * * *".to_string())).build();
+    assert!(matches!(invalid, Err(ConfigError::InvalidField { field, .. }) if field == "schedule"));
+
+    let alerts = builder.clone().with_cycle_schedule(Schedule::Cron("*/5 9-17 * * MON-FRI".to_string())).build().unwrap();
+    assert_eq!(alerts.cron().map(|cron| cron.as_str()), Some("*/5 9-17 * * MON-FRI"));
+
+    let alerts = builder
+        .with_cycle_schedule(Schedule::Cron("* * * * *".to_string()))
+        .with_interval(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    assert!(alerts.cron().is_none(), "The last schedule set wins");
+    assert_eq!(alerts.interval(), Duration::from_secs(5));
+}
@@ -0,0 +1,26 @@
+use serde_json::json;
+
+use trade_alerts::utils::crypto::FieldCipher;
+
+#[test]
+fn test_field_cipher_round_trip() {
+    let cipher: FieldCipher = FieldCipher::new(&[42u8; 32]).expect("Failed to create cipher");
+    let columns: Vec<String> = vec!["notes".to_string(), "phone".to_string()];
+
+    let original = json!({
+        "hash": "xlx-a-1234",
+        "notes": { "text": "breakout retest" },
+        "phone": "+31612345678",
+    });
+
+    let mut row = original.clone();
+    cipher.encrypt_columns(&mut row, &columns).expect("Failed to encrypt");
+    assert_eq!(row["hash"], original["hash"]);
+    assert!(row["notes"].as_str().unwrap().starts_with("enc:v1:"));
+    assert!(!row["phone"].as_str().unwrap().contains("31612345678"));
+
+    cipher.decrypt_columns(&mut row, &columns).expect("Failed to decrypt");
+    assert_eq!(row, original);
+
+    assert!(FieldCipher::new(&[1u8; 16]).is_err(), "Short keys should be rejected");
+}
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use trade_alerts::data::{CycleBudget, PreviousPrices, PriceProvider, Quote, XylexApi};
+use trade_alerts::db::TableConfig;
+use trade_alerts::errors::XylexApiError;
+
+/// Takes `delay` for every request.
+struct SlowProvider {
+    delay: Duration,
+}
+
+#[async_trait]
+impl PriceProvider for SlowProvider {
+    async fn fetch_price(&self, symbol: &str) -> Result<Quote, XylexApiError> {
+        tokio::time::sleep(self.delay).await;
+        Ok(Quote::new(symbol.to_string(), 1.0))
+    }
+}
+
+fn row(symbol: &str, user_id: &str, price_level: f64) -> HashMap<String, Value> {
+    HashMap::from([
+        ("symbol".to_string(), json!(symbol)),
+        ("user_id".to_string(), json!(user_id)),
+        ("price_level".to_string(), json!(price_level)),
+    ])
+}
+
+#[test]
+fn test_symbols_are_ordered_by_priority() {
+    let alerts = vec![row("far", "free", 2.0), row("near", "free", 1.01), row("vip", "pro", 5.0)];
+    let previous = PreviousPrices::default();
+    for symbol in ["far", "near", "vip"] {
+        previous.record(symbol, 1.0);
+    }
+
+    let budget = CycleBudget::new(Duration::from_secs(1)).with_user_priority("pro", 10);
+    let ordered = budget.order(vec!["far", "near", "vip", "unknown"], &alerts, &TableConfig::default(), &previous);
+    assert_eq!(ordered, vec!["vip", "near", "far", "unknown"]);
+}
+
+#[tokio::test]
+async fn test_tail_is_deferred_to_the_next_cycle() {
+    let provider = SlowProvider { delay: Duration::from_millis(100) };
+    let budget = CycleBudget::new(Duration::from_millis(150)).with_chunk_size(1);
+    let api = XylexApi::new("key".to_string(), "url".to_string()).with_cycle_budget(budget.clone());
+    let config = TableConfig::default();
+    let alerts = vec![row("a", "u", 1.0), row("b", "u", 1.0), row("c", "u", 1.0), row("d", "u", 1.0)];
+
+    let (quotes, deferred) = api
+        .fetch_within_budget(&provider, vec!["a", "b", "c", "d"], &alerts, &config, Instant::now())
+        .await
+        .unwrap();
+    assert_eq!(quotes.iter().map(|quote| quote.symbol.as_str()).collect::<Vec<_>>(), vec!["a", "b"]);
+    assert_eq!(deferred, vec!["c".to_string(), "d".to_string()]);
+
+    let stats = budget.stats();
+    assert_eq!((stats.cycles, stats.over_budget_cycles, stats.deferred_symbols), (1, 1, 2));
+
+    // Deferred symbols go first in the next cycle
+    let (quotes, deferred) = api
+        .fetch_within_budget(&provider, vec!["a", "b", "c", "d"], &alerts, &config, Instant::now())
+        .await
+        .unwrap();
+    assert_eq!(quotes.iter().map(|quote| quote.symbol.as_str()).collect::<Vec<_>>(), vec!["c", "d"]);
+    assert_eq!(deferred, vec!["a".to_string(), "b".to_string()]);
+}
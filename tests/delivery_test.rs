@@ -0,0 +1,94 @@
+#![cfg(feature = "fixtures")]
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use trade_alerts::{TradeAlerts, TriggeredAlert};
+use trade_alerts::data::MockProvider;
+use trade_alerts::errors::NotifyError;
+use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+use trade_alerts::notify::{NotificationRouter, Notifier};
+
+/// Records the hashes it is notified of, failing every delivery when `fail` is set.
+struct Channel {
+    name: &'static str,
+    fail: bool,
+    calls: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl Notifier for Channel {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    async fn notify(&self, alert: &TriggeredAlert) -> Result<(), NotifyError> {
+        self.calls.lock().unwrap().push(alert.hash.clone());
+        match self.fail {
+            true => Err(NotifyError::DeliveryError("Receiver is down".to_string())),
+            false => Ok(()),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_failed_deliveries_keep_the_alert() {
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    let mut row = AlertFixture::new("xlx-a", "EUR/USD", 1.10).latest_price(1.08).row(&config);
+    row["id"] = 1.into();
+    server.insert_rows("alerts", vec![row]);
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let build = |fail: bool| {
+        TradeAlerts::builder()
+            .with_provider(MockProvider::new().with_price("EUR/USD", 1.11))
+            .with_supabase(server.supabase())
+            .with_table_config(config.clone())
+            .with_notifier(Channel { name: "down", fail: true, calls: calls.clone() })
+            .with_notifier(Channel { name: "flaky", fail, calls: calls.clone() })
+            .build()
+            .unwrap()
+    };
+
+    let report = build(true).run_once().await.unwrap();
+    assert_eq!(report.triggered.len(), 1);
+    assert_eq!(calls.lock().unwrap().len(), 2);
+    assert_eq!(server.rows("alerts").len(), 1, "An alert no channel received is kept");
+
+    // One channel receiving it is enough
+    let report = build(false).run_once().await.unwrap();
+    assert_eq!(report.triggered.len(), 1, "The kept alert triggers again");
+    assert!(server.rows("alerts").is_empty());
+}
+
+#[tokio::test]
+async fn test_notifiers_and_router_are_merged() {
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    let (notifier_calls, router_calls) = (Arc::new(Mutex::new(Vec::new())), Arc::new(Mutex::new(Vec::new())));
+    let router = NotificationRouter::new()
+        .with_channel("router", Channel { name: "router", fail: false, calls: router_calls.clone() })
+        .with_default_channels(vec!["router".to_string()]);
+
+    for (id, (hash, router_first)) in [("xlx-a", false), ("xlx-b", true)].into_iter().enumerate() {
+        let mut row = AlertFixture::new(hash, "EUR/USD", 1.10).latest_price(1.08).row(&config);
+        row["id"] = (id as i64 + 1).into();
+        server.insert_rows("alerts", vec![row]);
+        let notifier = Channel { name: "notifier", fail: false, calls: notifier_calls.clone() };
+        let builder = TradeAlerts::builder()
+            .with_provider(MockProvider::new().with_price("EUR/USD", 1.11))
+            .with_supabase(server.supabase())
+            .with_table_config(config.clone());
+        let builder = match router_first {
+            true => builder.with_router(router.clone()).with_notifier(notifier),
+            false => builder.with_notifier(notifier).with_router(router.clone()),
+        };
+        builder.with_grouped_notifications().build().unwrap().run_once().await.unwrap();
+    }
+
+    assert_eq!(*notifier_calls.lock().unwrap(), vec!["xlx-a", "xlx-b"], "The notifier is kept in either order");
+    assert_eq!(*router_calls.lock().unwrap(), vec!["xlx-a", "xlx-b"]);
+    assert!(server.rows("alerts").is_empty());
+}
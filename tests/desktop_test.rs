@@ -0,0 +1,18 @@
+#![cfg(feature = "desktop")]
+
+use trade_alerts::TriggeredAlert;
+use trade_alerts::fixtures::triggered_alert;
+use trade_alerts::notify::{DesktopNotifier, Notifier};
+
+#[test]
+fn test_desktop_message() {
+    let alert = TriggeredAlert {
+        trigger_price: 1.1002,
+        ..triggered_alert("xlx-a-1234", "eur/usd", 1.1)
+    };
+
+    let (summary, body) = DesktopNotifier::message(&alert);
+    assert_eq!(summary, "EUR/USD alert triggered");
+    assert_eq!(body, "EUR/USD traded at 1.1002 (alert level 1.1)");
+    assert_eq!(DesktopNotifier::new().name(), "desktop");
+}
@@ -0,0 +1,49 @@
+use trade_alerts::{Alert, BandMode, Condition, Tolerance};
+use trade_alerts::db::{DistancePolicy, Supabase};
+use trade_alerts::errors::SupabaseError;
+
+fn alert(price_level: f64) -> Alert {
+    Alert::new("xlx-a-1234".to_string(), price_level, "eur/usd".to_string(), "user123".to_string())
+}
+
+#[test]
+fn test_parse_min_distance() {
+    assert_eq!("5pips".parse(), Ok(Tolerance::Pips(5.0)));
+    assert_eq!(" 0.1 % ".parse(), Ok(Tolerance::Percent(0.1)));
+    assert!("5".parse::<Tolerance>().is_err());
+    assert!("-1pips".parse::<Tolerance>().is_err());
+}
+
+#[test]
+fn test_too_close_to_market() {
+    let supabase = Supabase::new("key".to_string(), "url".to_string())
+        .with_distance_policy(DistancePolicy::reject(Tolerance::Pips(5.0)));
+
+    assert!(supabase.check_distance(&alert(1.1010), 1.1000).is_ok());
+    let error = supabase.check_distance(&alert(1.1002), 1.1000).unwrap_err();
+    assert!(matches!(error.downcast_ref::<SupabaseError>(), Some(SupabaseError::TooCloseToMarket(_))), "{}", error);
+
+    // Both edges of a band count
+    let band = alert(1.0900).with_band(1.1003, BandMode::Enter);
+    assert!(supabase.check_distance(&band, 1.1000).is_err());
+    let spread = alert(1.1000).with_condition(Condition::SpreadAbove(3.0));
+    assert!(supabase.check_distance(&spread, 1.1000).is_ok(), "Only price levels are checked");
+
+    // Conditions which already hold would fire on the next check
+    let above = alert(1.0900).with_condition(Condition::PriceAbove(1.0900));
+    assert!(supabase.check_distance(&above, 1.1000).is_err());
+    assert!(supabase.check_distance(&above.with_condition(Condition::PriceAbove(1.1100)), 1.1000).is_ok());
+    let below = alert(1.1100).with_condition(Condition::PriceBelow(1.1100));
+    assert!(supabase.check_distance(&below, 1.1000).is_err());
+    assert!(supabase.check_distance(&below.with_condition(Condition::PriceBelow(1.0900)), 1.1000).is_ok());
+    let inside = alert(1.0900).with_band(1.1100, BandMode::Enter);
+    let reason = DistancePolicy::reject(Tolerance::Pips(5.0)).violation(&inside, 1.1000).unwrap();
+    assert!(reason.contains("already inside the band"), "{}", reason);
+    assert!(supabase.check_distance(&inside.clone().with_band(1.1100, BandMode::Exit), 1.1000).is_ok());
+    assert!(supabase.check_distance(&alert(1.1100).with_band(1.1200, BandMode::Enter), 1.1000).is_ok());
+    assert!(supabase.check_distance(&alert(1.1100).with_band(1.1200, BandMode::Outside), 1.1000).is_err());
+
+    let warning = supabase.with_distance_policy(DistancePolicy::warn(Tolerance::Percent(1.0)));
+    assert!(warning.check_distance(&alert(1.1002), 1.1000).is_ok());
+    assert!(DistancePolicy::warn(Tolerance::Percent(1.0)).violation(&alert(1.1002), 1.1000).is_some());
+}
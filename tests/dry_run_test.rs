@@ -0,0 +1,61 @@
+#![cfg(feature = "fixtures")]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use async_trait::async_trait;
+
+use trade_alerts::{TradeAlerts, TriggeredAlert};
+use trade_alerts::data::MockProvider;
+use trade_alerts::errors::NotifyError;
+use trade_alerts::fixtures::{FixtureServer, alert_rows, table_config};
+use trade_alerts::notify::Notifier;
+
+/// Counts the deliveries.
+struct Counter(Arc<AtomicUsize>);
+
+#[async_trait]
+impl Notifier for Counter {
+    fn name(&self) -> &str {
+        "counter"
+    }
+
+    async fn notify(&self, _alert: &TriggeredAlert) -> Result<(), NotifyError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_dry_run() {
+    let server = FixtureServer::start().await;
+    let rows = alert_rows().into_iter().zip(1..).map(|(mut row, id)| {
+        row["id"] = id.into();
+        row
+    });
+    server.insert_rows("alerts", rows.collect());
+    let before = server.rows("alerts");
+    let notified = Arc::new(AtomicUsize::new(0));
+
+    let alerts = TradeAlerts::builder()
+        .with_provider(MockProvider::new().with_price("EUR/USD", 1.12).with_price("GBP/USD", 1.26).with_price("AAPL", 195.0))
+        .with_supabase(server.supabase())
+        .with_table_config(table_config("alerts"))
+        .with_history(table_config("alert_history"))
+        .with_notifier(Counter(notified.clone()))
+        .with_two_phase_triggers()
+        .with_dry_run()
+        .build()
+        .unwrap();
+
+    let report = alerts.run_once().await.unwrap();
+    assert_eq!(report.triggered.iter().map(|alert| alert.hash.as_str()).collect::<Vec<_>>(), vec!["xlx-eurusd"]);
+    assert_eq!((alerts.status().last_triggered, alerts.status().last_dry_run_triggered), (0, 1));
+    assert_eq!(alerts.health().dry_run_triggered, 1, "Dry run triggers are reported separately");
+    assert_eq!(notified.load(Ordering::SeqCst), 0, "Nothing is sent");
+    assert_eq!(server.rows("alerts"), before, "No row is claimed, recorded or deleted");
+    assert!(server.rows("alert_history").is_empty(), "Nothing is archived");
+
+    // The alert would fire again on the next cycle
+    assert_eq!(alerts.run_once().await.unwrap().triggered.len(), 1);
+}
@@ -0,0 +1,10 @@
+use trade_alerts::db::DuplicatePolicy;
+
+#[test]
+fn test_parse_duplicate_policy() {
+    assert_eq!("reject".parse(), Ok(DuplicatePolicy::Reject));
+    assert_eq!(" Replace ".parse(), Ok(DuplicatePolicy::Replace));
+    assert_eq!("allow".parse(), Ok(DuplicatePolicy::Allow));
+    assert!("merge".parse::<DuplicatePolicy>().is_err());
+    assert_eq!(DuplicatePolicy::default(), DuplicatePolicy::Allow);
+}
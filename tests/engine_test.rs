@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use trade_alerts::{EngineStatus, TradeAlerts};
+use trade_alerts::data::BinanceProvider;
+use trade_alerts::db::{Supabase, TableConfig};
+use trade_alerts::engine::DEFAULT_CHECK_INTERVAL;
+use trade_alerts::errors::ConfigError;
+use trade_alerts::notify::WebhookNotifier;
+
+#[test]
+fn test_trade_alerts_builder() {
+    let supabase = Supabase::new("key".to_string(), "https://example.supabase.co".to_string());
+
+    let missing = TradeAlerts::builder().with_supabase(supabase.clone()).build();
+    assert_eq!(missing.err(), Some(ConfigError::MissingField("table_config".to_string())));
+
+    let missing = TradeAlerts::builder()
+        .with_supabase(supabase.clone())
+        .with_table_config(TableConfig::default())
+        .build();
+    assert_eq!(missing.err(), Some(ConfigError::MissingField("provider".to_string())));
+
+    let zero = TradeAlerts::builder()
+        .with_supabase(supabase.clone())
+        .with_table_config(TableConfig::default())
+        .with_provider(BinanceProvider::new())
+        .with_interval(Duration::ZERO)
+        .build();
+    assert!(matches!(zero, Err(ConfigError::InvalidField { field, .. }) if field == "interval"));
+
+    let alerts = TradeAlerts::builder()
+        .with_supabase(supabase)
+        .with_table_config(TableConfig::default())
+        .with_provider(BinanceProvider::new())
+        .with_notifier(WebhookNotifier::new("https://example.com/hook".to_string()))
+        .build()
+        .expect("Failed to build the alert system");
+    assert_eq!(alerts.interval(), DEFAULT_CHECK_INTERVAL);
+}
+
+#[tokio::test]
+async fn test_trade_alerts_status() {
+    let path = std::env::temp_dir().join(format!("trade_alerts_status_{}.json", std::process::id()));
+    let build = || {
+        TradeAlerts::builder()
+            .with_supabase(Supabase::new("key".to_string(), "http://127.0.0.1:9".to_string()))
+            .with_table_config(TableConfig::default())
+            .with_provider(BinanceProvider::new())
+            .with_status_file(&path)
+            .build()
+            .expect("Failed to build the alert system")
+    };
+
+    let alerts = build();
+    assert_eq!(alerts.status(), EngineStatus::default());
+
+    assert!(alerts.run_once().await.is_err());
+    assert!(alerts.run_once().await.is_err());
+    let status = alerts.status();
+    assert_eq!(status.cycles, 2);
+    assert_eq!(status.consecutive_errors, 2);
+    assert!(status.last_error.is_some());
+    assert!(status.last_run_at.is_some() && status.last_duration.is_some());
+
+    assert_eq!(build().status(), status, "The status should be loaded from the status file");
+    std::fs::remove_file(&path).ok();
+}
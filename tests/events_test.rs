@@ -0,0 +1,72 @@
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+
+use trade_alerts::events::{TRIGGERED_ALERT_SCHEMA_VERSION, group_by_user};
+use trade_alerts::fixtures::triggered_alert;
+use trade_alerts::{Condition, TriggeredAlert};
+
+#[test]
+fn test_triggered_alert_event_versions() {
+    let alert = TriggeredAlert {
+        trigger_price: 1.1002,
+        condition: Condition::SpreadAbove(3.0),
+        metadata: Some(json!({ "note": "nfp" })),
+        ..triggered_alert("xlx-a-1234", "eur/usd", 1.1)
+    };
+
+    let event = alert.to_event();
+    assert_eq!(event["schema_version"], TRIGGERED_ALERT_SCHEMA_VERSION);
+    assert_eq!(TriggeredAlert::from_event(&event).expect("Failed to decode current event"), alert);
+
+    let v1 = json!({
+        "schema_version": 1,
+        "hash": "xlx-a-1234",
+        "user_id": "user123",
+        "symbol": "eur/usd",
+        "price_level": 1.1,
+        "trigger_price": 1.1002,
+    });
+    let decoded = TriggeredAlert::from_event(&v1).expect("Failed to decode v1 event");
+    assert_eq!(decoded.condition, Condition::PriceLevel);
+    assert_eq!(decoded.metadata, None);
+    assert_eq!(decoded.triggered_at, DateTime::<Utc>::default());
+    assert_eq!(decoded.quote_time, None);
+
+    let mut future = event.clone();
+    future["schema_version"] = json!(99);
+    future["added_later"] = json!("ignored");
+    assert!(TriggeredAlert::from_event(&future).is_ok(), "Newer events should decode additively");
+
+    assert!(TriggeredAlert::from_event_json("{\"schema_version\": 0}").is_err());
+}
+
+#[test]
+fn test_triggered_alert_clock_skew() {
+    let triggered_at = DateTime::parse_from_rfc3339("2030-01-01T14:30:05Z").unwrap().with_timezone(&Utc);
+    let mut alert = TriggeredAlert {
+        trigger_price: 1.1002,
+        triggered_at,
+        ..triggered_alert("xlx-a-1234", "eur/usd", 1.1)
+    };
+    assert_eq!(alert.clock_skew(), None);
+
+    alert.quote_time = Some(triggered_at - Duration::seconds(5));
+    assert_eq!(alert.clock_skew(), Some(Duration::seconds(5)));
+}
+
+#[test]
+fn test_group_by_user() {
+    let alert = |hash: &str, user_id: &str| TriggeredAlert {
+        user_id: user_id.to_string(),
+        trigger_price: 1.1002,
+        ..triggered_alert(hash, "eur/usd", 1.1)
+    };
+    let triggered = vec![alert("a", "bob"), alert("b", "alice"), alert("c", "bob")];
+
+    let groups = group_by_user(&triggered);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].user_id, "bob");
+    assert_eq!(groups[0].alerts.iter().map(|alert| alert.hash.as_str()).collect::<Vec<_>>(), vec!["a", "c"]);
+    assert_eq!(groups[1].user_id, "alice");
+    assert_eq!(groups[1].to_event()["alerts"][0]["schema_version"], TRIGGERED_ALERT_SCHEMA_VERSION);
+}
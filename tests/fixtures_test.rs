@@ -0,0 +1,35 @@
+#![cfg(feature = "fixtures")]
+
+use trade_alerts::fixtures::{AlertFixture, FixtureServer, alert_rows, batch_quote_response, table_config};
+
+#[test]
+fn test_alert_fixture_rows() {
+    let config = table_config("alerts");
+    let fixture = AlertFixture::new("xlx", "eur/usd", 1.10).user_id("user456").latest_price(1.12);
+    assert_eq!(fixture.alert().direction.as_deref(), Some("buy"));
+
+    let row = fixture.row(&config);
+    assert_eq!(row["user_id"], "user456");
+    assert_eq!(row["initial_direction"], "buy");
+    assert_eq!(fixture.record(&config)["hash"], "xlx");
+
+    assert_eq!(alert_rows().len(), 3);
+    assert_eq!(batch_quote_response(&[("AAPL", 190.5)])["AAPL"]["price"], "190.5");
+}
+
+#[tokio::test]
+async fn test_check_against_the_fixture_server() {
+    let config = table_config("alerts");
+    let server = FixtureServer::start().await;
+    server.insert_rows("alerts", alert_rows());
+    server.set_price("EUR/USD", 1.11);
+    server.set_price("GBP/USD", 1.26);
+    server.set_price("AAPL", 195.0);
+
+    let supabase = server.supabase();
+    let report = server.xylex_api().check_alerts(&supabase, &config).await.unwrap();
+    assert_eq!(report.alerts.len(), 3);
+    assert_eq!(report.quotes.len(), 3);
+    assert_eq!(report.triggered.iter().map(|alert| alert.hash.as_str()).collect::<Vec<_>>(), vec!["xlx-eurusd"]);
+    assert!(server.requests().iter().any(|request| request.starts_with("GET /rest/v1/alerts")));
+}
@@ -0,0 +1,70 @@
+use trade_alerts::db::{DeletionReport, Supabase, TableConfig, TableDeletion, UserDataTable};
+use trade_alerts::retry::RetryPolicy;
+
+fn tables() -> Vec<UserDataTable> {
+    vec![
+        UserDataTable::new("alerts".to_string(), "user_id".to_string()),
+        UserDataTable::new("preferences".to_string(), "owner".to_string()),
+    ]
+}
+
+#[test]
+fn test_deletion_report() {
+    let deletion = |tablename: &str, deleted: usize, error: Option<&str>| TableDeletion {
+        tablename: tablename.to_string(),
+        deleted,
+        error: error.map(String::from),
+    };
+    let report = DeletionReport {
+        user_id: "user123".to_string(),
+        tables: vec![deletion("alerts", 2, None), deletion("alert_history", 3, None)],
+    };
+    assert!(report.is_complete());
+    assert_eq!(report.total_deleted(), 5);
+
+    let partial = DeletionReport { tables: vec![deletion("alerts", 2, None), deletion("preferences", 0, Some("500"))], ..report };
+    assert!(!partial.is_complete());
+    assert_eq!(partial.total_deleted(), 2);
+
+    assert_eq!(UserDataTable::from(&TableConfig::default()), UserDataTable::new("alerts".to_string(), "user_id".to_string()));
+}
+
+#[tokio::test]
+async fn test_every_table_is_reported_when_deleting_fails() {
+    // Nothing listens on the port of a stopped server
+    let url = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+    let supabase = Supabase::new("key".to_string(), url).with_retry_policy(RetryPolicy::none());
+
+    let report = supabase.delete_all_user_data("user123", &tables()).await;
+
+    assert!(!report.is_complete());
+    assert_eq!(report.tables.len(), 2);
+    assert!(report.tables.iter().all(|table| table.error.is_some() && table.deleted == 0));
+}
+
+#[cfg(feature = "fixtures")]
+mod fixtures {
+    use serde_json::json;
+    use trade_alerts::fixtures::{FixtureServer, alert_rows};
+
+    use super::tables;
+
+    #[tokio::test]
+    async fn test_delete_all_user_data() {
+        let server = FixtureServer::start().await;
+        server.set_rows("alerts", alert_rows());
+        server.set_rows("preferences", vec![json!({ "owner": "user123", "theme": "dark" }), json!({ "owner": "user456" })]);
+
+        let report = server.supabase().delete_all_user_data("user123", &tables()).await;
+
+        assert!(report.is_complete());
+        assert_eq!(report.tables.iter().map(|table| table.deleted).collect::<Vec<_>>(), vec![2, 1]);
+        assert_eq!(report.total_deleted(), 3);
+        // Other users' data is kept
+        assert_eq!(server.rows("alerts").len(), 1);
+        assert_eq!(server.rows("preferences"), vec![json!({ "owner": "user456" })]);
+    }
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "fixtures")]
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use trade_alerts::TriggeredAlert;
+use trade_alerts::errors::NotifyError;
+use trade_alerts::events::{TRIGGER_BATCH_SCHEMA_VERSION, TRIGGERED_ALERT_SCHEMA_VERSION};
+use trade_alerts::fixtures::{FixtureServer, triggered_alert};
+use trade_alerts::notify::{NotificationRouter, Notifier};
+
+/// Records the hashes of every call, one entry per call.
+struct Recorder(Arc<Mutex<Vec<Vec<String>>>>);
+
+#[async_trait]
+impl Notifier for Recorder {
+    fn name(&self) -> &str {
+        "recorder"
+    }
+
+    async fn notify(&self, alert: &TriggeredAlert) -> Result<(), NotifyError> {
+        self.0.lock().unwrap().push(vec![alert.hash.clone()]);
+        Ok(())
+    }
+}
+
+fn triggered(hash: &str, user_id: &str, metadata: Option<Value>) -> TriggeredAlert {
+    TriggeredAlert {
+        user_id: user_id.to_string(),
+        trigger_price: 1.1001,
+        metadata,
+        ..triggered_alert(hash, "EUR/USD", 1.1)
+    }
+}
+
+#[tokio::test]
+async fn test_grouped_notifications() {
+    let server = FixtureServer::start().await;
+    let url = server.webhook_url("grouped");
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let router = NotificationRouter::new()
+        .with_channel("recorder", Recorder(calls.clone()))
+        .with_default_channels(vec!["recorder".to_string()])
+        .with_grouping()
+        .with_private_webhooks();
+    assert!(router.is_grouped());
+
+    let hook = Some(json!({ "notify": { "webhook_url": url } }));
+    let routed = router
+        .route_grouped(&[
+            triggered("a", "bob", hook.clone()),
+            triggered("b", "alice", hook.clone()),
+            triggered("c", "bob", hook),
+            triggered("d", "bob", None),
+        ])
+        .await;
+    assert_eq!(routed.iter().map(|(group, _)| group.user_id.as_str()).collect::<Vec<_>>(), vec!["bob", "alice"]);
+    assert_eq!(routed[0].1.len(), 2, "Bob's alerts went to the webhook and the default channel");
+    assert!(routed.iter().flat_map(|(_, deliveries)| deliveries).all(|delivery| delivery.error.is_none()));
+
+    let mut bodies: Vec<Value> = server.webhook_requests("grouped").iter().map(|request| request.json()).collect();
+    bodies.sort_by_key(|body| body["user_id"].as_str().unwrap().to_string());
+    assert_eq!(bodies.len(), 2, "One request per user");
+    assert_eq!(bodies[1]["schema_version"], TRIGGER_BATCH_SCHEMA_VERSION);
+    assert_eq!(bodies[1]["user_id"], "bob");
+    let hashes: Vec<&str> = bodies[1]["alerts"].as_array().unwrap().iter().map(|alert| alert["hash"].as_str().unwrap()).collect();
+    assert_eq!(hashes, vec!["a", "c"]);
+    assert_eq!(bodies[1]["alerts"][0]["schema_version"], TRIGGERED_ALERT_SCHEMA_VERSION);
+    assert_eq!(bodies[0]["alerts"].as_array().unwrap().len(), 1);
+
+    // Channels which cannot group alerts get them one by one
+    assert_eq!(*calls.lock().unwrap(), vec![vec!["d".to_string()]]);
+}
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+
+use trade_alerts::{EngineStatus, HealthState};
+
+#[test]
+fn test_health_from_status() {
+    let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    let stall_after = Duration::from_secs(60);
+    let running = EngineStatus { next_run_at: Some(now + chrono::Duration::seconds(5)), ..EngineStatus::default() };
+
+    assert_eq!(EngineStatus::default().health(now, stall_after).state, HealthState::Stopped);
+    assert_eq!(running.health(now, stall_after).state, HealthState::Starting);
+
+    let healthy = EngineStatus {
+        last_run_at: Some(now - chrono::Duration::seconds(5)),
+        last_duration: Some(Duration::from_millis(250)),
+        last_symbols_checked: 3,
+        last_triggered: 1,
+        cycles: 4,
+        ..running.clone()
+    };
+    let health = healthy.health(now, stall_after);
+    assert_eq!(health.state, HealthState::Healthy);
+    assert!(health.is_healthy());
+    assert_eq!((health.last_duration_ms, health.symbols_checked, health.triggered), (Some(250), 3, 1));
+    assert_eq!(health.overdue_ms, None);
+
+    let failing = EngineStatus { consecutive_errors: 2, last_error: Some("timeout".to_string()), ..healthy.clone() };
+    assert_eq!(failing.health(now, stall_after).state, HealthState::Degraded);
+
+    // A cycle started at 12:00:05 is still running at 12:02
+    let later = now + chrono::Duration::minutes(2);
+    let stuck = failing.health(later, stall_after);
+    assert_eq!(stuck.state, HealthState::Stuck);
+    assert_eq!(stuck.overdue_ms, Some(115_000));
+    assert_eq!(healthy.health(later, Duration::from_secs(300)).state, HealthState::Healthy);
+    assert_eq!(stuck.state.to_string(), "stuck");
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_scheduler_writes_heartbeats() {
+    use trade_alerts::AlertScheduler;
+    use trade_alerts::data::MockProvider;
+    use trade_alerts::db::HeartbeatTable;
+    use trade_alerts::fixtures::{FixtureServer, alert_rows, table_config};
+
+    let server = FixtureServer::start().await;
+    server.insert_rows("alerts", alert_rows());
+    let supabase = server.supabase();
+    let table = HeartbeatTable::default();
+    let provider = MockProvider::new().with_price("EUR/USD", 1.09).with_price("GBP/USD", 1.26).with_price("AAPL", 195.0);
+
+    let handle = AlertScheduler::new(provider, supabase.clone(), table_config("alerts"))
+        .with_interval(Duration::from_millis(20))
+        .with_heartbeat(table.clone(), "worker-1")
+        .run()
+        .unwrap();
+    assert_eq!(handle.health().state, HealthState::Starting);
+
+    let written = async {
+        loop {
+            if let Some(health) = supabase.fetch_heartbeat("worker-1", &table).await.unwrap() {
+                if health.cycles >= 2 {
+                    return health;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    let health = tokio::time::timeout(Duration::from_secs(10), written).await.expect("A heartbeat should be written");
+    assert_eq!(health.state, HealthState::Healthy);
+    assert_eq!(health.symbols_checked, 3);
+    assert_eq!(server.rows("heartbeats").len(), 1, "Heartbeats replace the row of the instance");
+    assert!(handle.health().is_healthy());
+
+    handle.shutdown().await;
+    let health = supabase.fetch_heartbeat("worker-1", &table).await.unwrap().unwrap();
+    assert_eq!(health.state, HealthState::Stopped);
+    assert_eq!(supabase.fetch_heartbeat("worker-2", &table).await.unwrap(), None);
+}
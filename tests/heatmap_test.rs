@@ -0,0 +1,22 @@
+use trade_alerts::db::HeatMap;
+
+#[test]
+fn test_heat_map_buckets() {
+    let mut heat_map = HeatMap::new(1.0);
+    heat_map.record("eur/usd", 1.1000);
+    heat_map.record("eur/usd", 1.1005);
+    heat_map.record("eur/usd", 1.2000);
+    heat_map.record("btc/usd", 65000.0);
+    heat_map.record("btc/usd", -1.0);
+
+    let histograms = heat_map.histograms();
+    assert_eq!(histograms.len(), 2);
+    assert_eq!(histograms[0].symbol, "btc/usd");
+    assert_eq!(histograms[0].buckets.len(), 1);
+
+    let eur_usd = &histograms[1];
+    assert_eq!(eur_usd.buckets.iter().map(|bucket| bucket.count).collect::<Vec<_>>(), vec![2, 1]);
+    let first = &eur_usd.buckets[0];
+    assert!(first.lower <= 1.1000 && 1.1005 < first.upper);
+    assert!((first.upper / first.lower - 1.01).abs() < 1e-9);
+}
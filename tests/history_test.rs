@@ -0,0 +1,42 @@
+use serde_json::json;
+
+use trade_alerts::TriggeredAlert;
+use trade_alerts::db::TableConfig;
+use trade_alerts::db::history::history_row;
+
+#[test]
+fn test_history_row() {
+    let config = TableConfig::default();
+    let history_config = TableConfig {
+        tablename: "alert_history".to_string(),
+        trigger_price_column_name: "fill_price".to_string(),
+        ..TableConfig::default()
+    };
+
+    let row = json!({
+        "id": 7,
+        "hash": "xlx-a-1234",
+        "price_level": 1.1,
+        "user_id": "user123",
+        "symbol": "eur/usd",
+        "hit": true,
+        "latest_price": 1.1001,
+        "trigger_price": 1.1003,
+        "triggered_at": "2030-01-01T14:30:00+00:00",
+    });
+
+    let history = history_row(&row, &config, &history_config);
+    assert!(history.get("id").is_none());
+    assert_eq!(history["fill_price"], 1.1003);
+    assert_eq!(history["hit"], true);
+
+    let archived = TriggeredAlert::from_row(&history, &history_config).expect("Failed to parse history row");
+    assert_eq!(archived.hash, "xlx-a-1234");
+    assert_eq!(archived.trigger_price, 1.1003);
+
+    // Rows archived before the trigger was recorded fall back to the last recorded price
+    let unrecorded = json!({ "id": 8, "hash": "xlx-a-5678", "latest_price": 1.2 });
+    let history = history_row(&unrecorded, &config, &history_config);
+    assert_eq!(history["fill_price"], 1.2);
+    assert!(history["triggered_at"].is_string());
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "fixtures")]
+
+use std::time::Duration;
+
+use trade_alerts::TradeAlerts;
+use trade_alerts::data::MockProvider;
+use trade_alerts::errors::ConfigError;
+use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+
+/// Waits until `condition` holds, failing after a few seconds.
+async fn wait_for(condition: impl Fn() -> bool, what: &str) {
+    let waiting = async {
+        while !condition() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(10), waiting).await.unwrap_or_else(|_| panic!("{}", what));
+}
+
+#[tokio::test]
+async fn test_idle_downshift() {
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    let build = |idle_interval: Duration| {
+        TradeAlerts::builder()
+            .with_provider(MockProvider::new().with_price("EUR/USD", 1.08))
+            .with_supabase(server.supabase())
+            .with_table_config(config.clone())
+            .with_interval(Duration::from_millis(20))
+            .with_idle_interval(idle_interval)
+    };
+    assert!(matches!(build(Duration::ZERO).build(), Err(ConfigError::InvalidField { field, .. }) if field == "idle_interval"));
+
+    let handle = build(Duration::from_secs(3600)).build().unwrap().start().await;
+    wait_for(|| handle.status().idle, "An empty table should downshift").await;
+    let next_run_at = handle.status().next_run_at.unwrap();
+    assert!(next_run_at - chrono::Utc::now() > chrono::Duration::minutes(30), "The next cycle waits for the idle interval");
+
+    server.insert_rows("alerts", vec![AlertFixture::new("xlx-eurusd", "EUR/USD", 1.10).latest_price(1.08).row(&config)]);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let cycles = handle.status().cycles;
+    assert!(handle.status().idle && cycles == 1, "No cycle runs before the idle interval, {} ran", cycles);
+
+    handle.wake();
+    wait_for(|| !handle.status().idle, "A wake should resume full speed").await;
+    wait_for(|| handle.status().cycles > cycles + 2, "Cycles should run every interval again").await;
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_wake_at_full_speed_is_dropped() {
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    server.insert_rows("alerts", vec![AlertFixture::new("xlx-eurusd", "EUR/USD", 1.10).latest_price(1.08).row(&config)]);
+    let handle = TradeAlerts::builder()
+        .with_provider(MockProvider::new().with_price("EUR/USD", 1.08))
+        .with_supabase(server.supabase())
+        .with_table_config(config.clone())
+        .with_interval(Duration::from_millis(20))
+        .with_idle_interval(Duration::from_secs(3600))
+        .build()
+        .unwrap()
+        .start()
+        .await;
+    wait_for(|| handle.status().cycles > 0, "The first cycle should run").await;
+
+    handle.wake();
+    let cycles = handle.status().cycles;
+    wait_for(|| handle.status().cycles > cycles + 1, "Cycles should run every interval").await;
+    server.set_rows("alerts", Vec::new());
+    wait_for(|| handle.status().idle, "An empty table should downshift").await;
+
+    let cycles = handle.status().cycles;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(handle.status().cycles, cycles, "A wake from before going idle should not end the idle interval");
+    handle.stop().await;
+}
@@ -0,0 +1,39 @@
+use trade_alerts::Condition;
+use trade_alerts::indicators::{ema, rsi, sma, sma_crossed};
+
+#[test]
+fn test_moving_averages() {
+    let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+
+    assert_eq!(sma(&values, 5), Some(3.0));
+    assert_eq!(sma(&values, 2), Some(4.5));
+    assert_eq!(sma(&values, 6), None);
+    assert_eq!(ema(&values, 5), Some(3.0));
+    // alpha = 2 / 4, seeded with the SMA of 1, 2, 3
+    assert_eq!(ema(&values, 3), Some(4.0));
+}
+
+#[test]
+fn test_rsi() {
+    let rising: Vec<f64> = (0..30).map(f64::from).collect();
+    assert_eq!(rsi(&rising, 14), Some(100.0));
+    assert_eq!(rsi(&rising[..14], 14), None);
+
+    let alternating: Vec<f64> = (0..30).map(|i| if i % 2 == 0 { 1.0 } else { 2.0 }).collect();
+    let value = rsi(&alternating, 14).unwrap();
+    assert!((40.0..60.0).contains(&value), "Alternating prices should be neutral, got {}", value);
+
+    assert!(Condition::RsiAbove(70.0).is_met_on_closes(&rising));
+    assert!(!Condition::RsiBelow(30.0).is_met_on_closes(&rising));
+}
+
+#[test]
+fn test_sma_cross() {
+    // The fast SMA was below the slow SMA and jumps above it on the last close
+    let closes = [5.0, 4.0, 3.0, 2.0, 1.0, 10.0];
+
+    assert!(sma_crossed(&closes, 2, 4));
+    assert!(!sma_crossed(&closes[..5], 2, 4));
+    assert!(Condition::SmaCross { fast: 2, slow: 4 }.is_met_on_closes(&closes));
+    assert_eq!(Condition::SmaCross { fast: 20, slow: 50 }.candles_needed(), Some(51));
+}
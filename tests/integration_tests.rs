@@ -59,7 +59,7 @@ async fn test_trade_alerts_integration() {
         &supabase,
         &config
     ).await {
-        Ok(alerts) => alerts,
+        Ok(alerts) => alerts.into_iter().map(|alert| alert.hash).collect(),
         Err(e) => {
             println!("Failed to check and fetch triggered alert hashes: {:?}", e);
             return;
@@ -0,0 +1,91 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use async_trait::async_trait;
+
+use trade_alerts::TriggeredAlert;
+use trade_alerts::errors::NotifyError;
+use trade_alerts::fixtures::triggered_alert;
+use trade_alerts::notify::{IntentLog, NotificationRouter, Notifier};
+
+fn triggered(hash: &str) -> TriggeredAlert {
+    TriggeredAlert {
+        trigger_price: 1.1002,
+        ..triggered_alert(hash, "eur/usd", 1.1)
+    }
+}
+
+/// Counts deliveries and fails while `down` is set.
+struct FlakyNotifier {
+    down: Arc<AtomicBool>,
+    delivered: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Notifier for FlakyNotifier {
+    fn name(&self) -> &str {
+        "flaky"
+    }
+
+    async fn notify(&self, _alert: &TriggeredAlert) -> Result<(), NotifyError> {
+        if self.down.load(Ordering::SeqCst) {
+            return Err(NotifyError::DeliveryError("down".to_string()));
+        }
+        self.delivered.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+fn log_path(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("trade_alerts_{}_{}.jsonl", name, std::process::id()));
+    std::fs::remove_file(&path).ok();
+    path
+}
+
+#[test]
+fn test_intent_log() {
+    let path = log_path("intents");
+    let log = IntentLog::new(&path);
+    assert!(log.pending().unwrap().is_empty());
+
+    let first = log.record(&triggered("a")).unwrap();
+    log.record(&triggered("b")).unwrap();
+    log.complete(&first).unwrap();
+    std::fs::OpenOptions::new().append(true).open(&path).and_then(|mut file| {
+        use std::io::Write;
+        write!(file, "{{\"id\": \"cut sho")
+    }).unwrap();
+
+    let pending = log.pending().unwrap();
+    assert_eq!(pending.iter().map(|intent| intent.alert.hash.as_str()).collect::<Vec<_>>(), vec!["b"]);
+
+    log.compact().unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+    assert_eq!(log.pending().unwrap(), pending);
+    std::fs::remove_file(&path).ok();
+}
+
+#[tokio::test]
+async fn test_router_replays_intents() {
+    let path = log_path("replay");
+    let down = Arc::new(AtomicBool::new(true));
+    let delivered = Arc::new(AtomicUsize::new(0));
+    let router = NotificationRouter::new()
+        .with_channel("flaky", FlakyNotifier { down: down.clone(), delivered: delivered.clone() })
+        .with_default_channels(vec!["flaky".to_string()])
+        .with_intent_log(IntentLog::new(&path));
+
+    assert!(router.route(&triggered("a")).await[0].error.is_some());
+    assert_eq!(IntentLog::new(&path).pending().unwrap().len(), 1);
+
+    down.store(false, Ordering::SeqCst);
+    router.route(&triggered("b")).await;
+    let replayed = router.replay_intents().await;
+    assert_eq!(replayed.len(), 1);
+    assert_eq!(replayed[0].0.alert.hash, "a");
+    assert_eq!(delivered.load(Ordering::SeqCst), 2);
+
+    assert!(IntentLog::new(&path).pending().unwrap().is_empty());
+    assert!(router.replay_intents().await.is_empty());
+    std::fs::remove_file(&path).ok();
+}
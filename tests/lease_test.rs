@@ -0,0 +1,193 @@
+#![cfg(feature = "fixtures")]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::json;
+
+use trade_alerts::{TradeAlerts, TriggeredAlert};
+use trade_alerts::data::{MockProvider, PriceProvider, Quote};
+use trade_alerts::db::LeaseTable;
+use trade_alerts::errors::{ConfigError, NotifyError, XylexApiError};
+use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+use trade_alerts::notify::Notifier;
+
+/// Takes `delay` for every request, and records that one started.
+struct SlowProvider {
+    delay: Duration,
+    started: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl PriceProvider for SlowProvider {
+    async fn fetch_price(&self, symbol: &str) -> Result<Quote, XylexApiError> {
+        self.started.store(true, Ordering::SeqCst);
+        tokio::time::sleep(self.delay).await;
+        Ok(Quote::new(symbol.to_string(), 1.0))
+    }
+}
+
+/// Takes `delay` for every delivery, and counts the deliveries started.
+struct SlowNotifier {
+    delay: Duration,
+    started: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Notifier for SlowNotifier {
+    fn name(&self) -> &str {
+        "slow"
+    }
+
+    async fn notify(&self, _alert: &TriggeredAlert) -> Result<(), NotifyError> {
+        self.started.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(self.delay).await;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_lease_takeover() {
+    let server = FixtureServer::start().await;
+    let supabase = server.supabase();
+    let table = LeaseTable::default();
+    let ttl = Duration::from_secs(60);
+
+    assert_eq!(supabase.fetch_lease("alerts", &table).await.unwrap(), None);
+    assert!(supabase.acquire_lease("alerts", "a", ttl, &table).await.unwrap());
+    assert!(!supabase.acquire_lease("alerts", "b", ttl, &table).await.unwrap(), "The lease is held");
+    assert!(supabase.acquire_lease("alerts", "a", ttl, &table).await.unwrap(), "The holder renews it");
+    assert!(supabase.acquire_lease("other", "b", ttl, &table).await.unwrap(), "Leases are independent");
+
+    supabase.release_lease("alerts", "b", &table).await.unwrap();
+    assert!(!supabase.acquire_lease("alerts", "b", ttl, &table).await.unwrap(), "Only the holder releases a lease");
+    supabase.release_lease("alerts", "a", &table).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    assert!(supabase.acquire_lease("alerts", "b", Duration::ZERO, &table).await.unwrap(), "Released leases are taken over");
+
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    let lease = supabase.fetch_lease("alerts", &table).await.unwrap().unwrap();
+    assert_eq!(lease.holder, "b");
+    assert!(lease.is_expired(Utc::now()));
+    assert!(supabase.acquire_lease("alerts", "a", ttl, &table).await.unwrap(), "Expired leases are taken over");
+    assert_eq!(server.rows("leases").len(), 2);
+}
+
+/// Waits until `condition` holds, failing after a few seconds.
+async fn wait_for(condition: impl Fn() -> bool, what: &str) {
+    let waiting = async {
+        while !condition() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(10), waiting).await.unwrap_or_else(|_| panic!("{}", what));
+}
+
+#[tokio::test]
+async fn test_only_the_leader_runs_cycles() {
+    let server = FixtureServer::start().await;
+    let build = |instance_id: &str| {
+        TradeAlerts::builder()
+            .with_provider(MockProvider::new())
+            .with_supabase(server.supabase())
+            .with_table_config(table_config("alerts"))
+            .with_interval(Duration::from_millis(20))
+            .with_leader_lease(LeaseTable::default(), instance_id)
+            .with_lease_ttl(Duration::from_secs(30))
+            .build()
+            .unwrap()
+    };
+
+    let leader = build("a").start().await;
+    wait_for(|| leader.status().cycles > 0, "The first instance should lead").await;
+    let follower = build("b").start().await;
+    wait_for(|| follower.status().is_leader.is_some(), "The follower should try to lead").await;
+    assert_eq!(leader.status().is_leader, Some(true));
+    assert_eq!(follower.status().is_leader, Some(false));
+    assert_eq!(follower.status().cycles, 0, "The follower runs no cycle");
+
+    leader.stop().await;
+    wait_for(|| follower.status().cycles > 0, "The follower should take over the released lease").await;
+    assert_eq!(follower.status().is_leader, Some(true));
+    follower.stop().await;
+}
+
+#[tokio::test]
+async fn test_lease_is_renewed_during_long_cycles() {
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    server.insert_rows("alerts", vec![AlertFixture::new("slow", "EUR/USD", 1.10).latest_price(1.08).row(&config)]);
+    let ttl = Duration::from_millis(150);
+    let started = Arc::new(AtomicBool::new(false));
+
+    let leader = TradeAlerts::builder()
+        .with_provider(SlowProvider { delay: Duration::from_millis(600), started: started.clone() })
+        .with_supabase(server.supabase())
+        .with_table_config(config)
+        .with_interval(Duration::from_millis(20))
+        .with_leader_lease(LeaseTable::default(), "a")
+        .with_lease_ttl(ttl)
+        .build()
+        .unwrap()
+        .start()
+        .await;
+    wait_for(|| started.load(Ordering::SeqCst), "The cycle should start").await;
+
+    tokio::time::sleep(ttl * 2).await;
+    let supabase = server.supabase();
+    assert!(!supabase.acquire_lease("alerts", "b", ttl, &LeaseTable::default()).await.unwrap(), "The lease outlasts its TTL during a cycle");
+    leader.stop().await;
+}
+
+#[tokio::test]
+async fn test_lease_lost_during_delivery() {
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    let mut row = AlertFixture::new("xlx-eurusd", "EUR/USD", 1.10).latest_price(1.08).row(&config);
+    row["id"] = json!(1);
+    server.insert_rows("alerts", vec![row]);
+    let ttl = Duration::from_millis(150);
+    let started = Arc::new(AtomicUsize::new(0));
+
+    let leader = TradeAlerts::builder()
+        .with_provider(MockProvider::new().with_price("EUR/USD", 1.11))
+        .with_supabase(server.supabase())
+        .with_table_config(config)
+        .with_notifier(SlowNotifier { delay: Duration::from_millis(500), started: started.clone() })
+        .with_interval(Duration::from_millis(20))
+        .with_leader_lease(LeaseTable::default(), "a")
+        .with_lease_ttl(ttl)
+        .build()
+        .unwrap()
+        .start()
+        .await;
+    wait_for(|| started.load(Ordering::SeqCst) > 0, "The alert should be delivered").await;
+
+    // The lease can't be renewed while the alert is being delivered
+    server.queue_responses("/rest/v1/leases", vec![(503, json!({ "message": "unavailable" })); 3]);
+    wait_for(|| server.rows("alerts").is_empty(), "The delivered alert should still be deleted").await;
+    wait_for(|| leader.status().is_leader == Some(true), "The lease should be acquired again").await;
+    tokio::time::sleep(ttl).await;
+    assert_eq!(started.load(Ordering::SeqCst), 1, "The next cycle does not fire the alert again");
+    leader.stop().await;
+}
+
+#[test]
+fn test_lease_must_outlast_the_interval() {
+    let build = |ttl: Duration| {
+        TradeAlerts::builder()
+            .with_provider(MockProvider::new())
+            .with_supabase(trade_alerts::db::Supabase::new("key".to_string(), "http://127.0.0.1:9".to_string()))
+            .with_table_config(table_config("alerts"))
+            .with_interval(Duration::from_secs(10))
+            .with_leader_lease(LeaseTable::default(), "a")
+            .with_lease_ttl(ttl)
+            .build()
+    };
+    let result = build(Duration::from_secs(10));
+    assert!(matches!(&result, Err(ConfigError::InvalidField { field, .. }) if field == "lease_ttl"), "{:?}", result.err());
+    assert!(build(Duration::from_secs(15)).is_ok(), "A short TTL is only warned about");
+}
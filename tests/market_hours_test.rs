@@ -0,0 +1,77 @@
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::json;
+use trade_alerts::Alert;
+use trade_alerts::db::TableConfig;
+use trade_alerts::market_hours::{AssetClass, MarketHours, Session, in_sessions};
+
+fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+}
+
+#[test]
+fn test_asset_classes() {
+    assert_eq!(AssetClass::of("EUR/USD"), AssetClass::Forex);
+    assert_eq!(AssetClass::of("gbpjpy"), AssetClass::Forex);
+    assert_eq!(AssetClass::of("BTCUSDT"), AssetClass::Crypto);
+    assert_eq!(AssetClass::of("ETH/USD"), AssetClass::Crypto);
+    assert_eq!(AssetClass::of("AAPL"), AssetClass::Equity);
+
+    let hours = MarketHours::new().with_asset_class("XAU/USD", AssetClass::Forex);
+    assert_eq!(hours.asset_class("xauusd"), AssetClass::Forex);
+}
+
+#[test]
+fn test_market_open() {
+    // Wednesday 2024-07-10, New York is on UTC-4
+    assert!(AssetClass::Equity.is_open(at(2024, 7, 10, 14, 0)));
+    assert!(!AssetClass::Equity.is_open(at(2024, 7, 10, 13, 0)));
+    assert!(!AssetClass::Equity.is_open(at(2024, 7, 13, 15, 0)));
+    // Winter, New York is on UTC-5
+    assert!(AssetClass::Equity.is_open(at(2024, 1, 10, 14, 30)));
+    assert!(!AssetClass::Equity.is_open(at(2024, 1, 10, 14, 0)));
+
+    // FX closes Friday 17:00 and opens Sunday 17:00 New York
+    assert!(AssetClass::Forex.is_open(at(2024, 7, 12, 20, 59)));
+    assert!(!AssetClass::Forex.is_open(at(2024, 7, 12, 21, 0)));
+    assert!(!AssetClass::Forex.is_open(at(2024, 7, 13, 12, 0)));
+    assert!(AssetClass::Forex.is_open(at(2024, 7, 14, 21, 0)));
+
+    assert!(AssetClass::Crypto.is_open(at(2024, 7, 13, 3, 0)));
+}
+
+#[test]
+fn test_sessions() {
+    // London is on UTC+1 in summer
+    assert!(Session::London.is_active(at(2024, 7, 10, 7, 0)));
+    assert!(!Session::London.is_active(at(2024, 7, 10, 16, 0)));
+    assert!(Session::London.is_active(at(2024, 1, 10, 16, 0)));
+    assert!(Session::Tokyo.is_active(at(2024, 7, 10, 0, 0)));
+    assert_eq!(Session::active(at(2024, 7, 10, 13, 0)), vec![Session::London, Session::NewYork]);
+
+    assert!(in_sessions(&[], at(2024, 7, 13, 12, 0)));
+    assert!(!in_sessions(&[Session::London], at(2024, 7, 13, 12, 0)));
+
+    assert_eq!("NY".parse::<Session>(), Ok(Session::NewYork));
+    assert!("mars".parse::<Session>().is_err());
+}
+
+#[test]
+fn test_alert_sessions_round_trip() {
+    let config = TableConfig::default();
+    let alert = Alert::builder()
+        .user_id("user456")
+        .symbol("EUR/USD")
+        .price_level(1.10)
+        .sessions(vec![Session::London, Session::NewYork])
+        .build()
+        .unwrap();
+
+    let row = json!({
+        "hash": alert.hash,
+        "user_id": "user456",
+        "symbol": "EUR/USD",
+        "price_level": 1.10,
+        "sessions": ["london", "new_york"],
+    });
+    assert_eq!(Alert::from_row(&row, &config).unwrap().sessions, alert.sessions);
+}
@@ -0,0 +1,52 @@
+use trade_alerts::data::{MockProvider, PriceProvider};
+use trade_alerts::data::mock::ramp;
+
+#[tokio::test]
+async fn test_mock_provider_scripts() {
+    assert_eq!(ramp(1.08, 1.12, 5).len(), 5);
+    assert!((ramp(1.08, 1.12, 5)[2] - 1.10).abs() < 1e-12);
+    assert_eq!(ramp(1.0, 2.0, 1), vec![2.0]);
+    assert!(ramp(1.0, 2.0, 0).is_empty());
+
+    let provider = MockProvider::new()
+        .with_price("AAPL", 190.0)
+        .with_ramp("EUR/USD", 1.08, 1.12, 3);
+    let shared = provider.clone();
+
+    let mut prices = Vec::new();
+    for _ in 0..4 {
+        prices.push(provider.fetch_price("eurusd").await.unwrap().price);
+    }
+    assert_eq!(prices, vec![1.08, 1.1, 1.12, 1.12], "The last price should hold");
+    assert!(provider.is_exhausted());
+
+    let quote = provider.fetch_price("aapl").await.unwrap();
+    assert_eq!((quote.symbol.as_str(), quote.price), ("aapl", 190.0));
+    shared.set_price("AAPL", 191.0);
+    assert_eq!(provider.price("AAPL"), Some(191.0));
+
+    assert!(provider.fetch_price("GBP/USD").await.is_err());
+    assert_eq!(provider.supported_symbols().await.unwrap(), Some(vec!["AAPL".to_string(), "EUR/USD".to_string()]));
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_mock_provider_triggers_alerts() {
+    use trade_alerts::fixtures::{FixtureServer, alert_rows, table_config};
+
+    let config = table_config("alerts");
+    let server = FixtureServer::start().await;
+    server.insert_rows("alerts", alert_rows());
+    let supabase = server.supabase();
+
+    let provider = MockProvider::new()
+        .with_ramp("EUR/USD", 1.08, 1.12, 3)
+        .with_price("GBP/USD", 1.26)
+        .with_price("AAPL", 195.0);
+
+    let first = server.xylex_api().check_alerts_with(&provider, &supabase, &config).await.unwrap();
+    assert!(first.triggered.is_empty());
+
+    let second = server.xylex_api().check_alerts_with(&provider, &supabase, &config).await.unwrap();
+    assert_eq!(second.triggered.iter().map(|alert| alert.hash.as_str()).collect::<Vec<_>>(), vec!["xlx-eurusd"]);
+}
@@ -0,0 +1,80 @@
+use trade_alerts::data::MovementGate;
+
+#[test]
+fn test_movement_gate() {
+    let gate = MovementGate::new(0.5).with_symbol("btc/usd", 2.0);
+
+    assert!(gate.should_evaluate("eur/usd", 1.0), "The first price is always evaluated");
+    assert!(!gate.should_evaluate("eur/usd", 1.003));
+    assert!(gate.should_evaluate("eur/usd", 1.006), "Small moves should add up");
+    assert!(!gate.should_evaluate("eur/usd", 1.0065));
+
+    assert!(gate.should_evaluate("btc/usd", 100.0));
+    assert!(!gate.should_evaluate("btc/usd", 101.0));
+    assert!(gate.should_evaluate("btc/usd", 97.5));
+
+    let shared = gate.clone();
+    assert!(!shared.should_evaluate("eur/usd", 1.006), "Clones share the recorded prices");
+}
+
+#[test]
+fn test_movement_gate_crossed() {
+    let gate = MovementGate::new(0.5);
+    assert!(!gate.crossed("eur/usd", 1.0, &[1.0]), "Symbols without a recorded price never cross");
+
+    assert!(gate.should_evaluate("eur/usd", 1.0));
+    assert!(!gate.should_evaluate("eur/usd", 1.002));
+    assert!(gate.crossed("eur/usd", 1.002, &[1.001]), "A small move across a level is evaluated");
+    assert!(gate.crossed("eur/usd", 0.999, &[1.2, 0.9995]), "Crossings count in both directions");
+    assert!(!gate.crossed("eur/usd", 1.002, &[1.003, 0.99]));
+    assert!(!gate.crossed("eur/usd", 1.002, &[]));
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_small_move_across_a_level_triggers() {
+    use trade_alerts::data::MockProvider;
+    use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    server.insert_rows("alerts", vec![
+        AlertFixture::new("xlx-crossed", "EUR/USD", 1.095).latest_price(1.09).row(&config),
+        AlertFixture::new("xlx-far", "EUR/USD", 1.2).latest_price(1.09).row(&config),
+    ]);
+
+    let gate = MovementGate::new(5.0);
+    let xylex = server.xylex_api().with_movement_gate(gate.clone());
+    gate.seed("EUR/USD", 1.09);
+    let provider = MockProvider::new().with_price("EUR/USD", 1.10);
+    let report = xylex.check_alerts_with(&provider, &server.supabase(), &config).await.unwrap();
+    let triggered: Vec<&str> = report.triggered.iter().map(|alert| alert.hash.as_str()).collect();
+    assert_eq!(triggered, vec!["xlx-crossed"], "A move below the threshold still crosses a level");
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_conditions_without_levels_are_not_gated() {
+    use trade_alerts::Condition;
+    use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    server.insert_rows("alerts", vec![
+        AlertFixture::new("xlx-spread", "EUR/USD", 1.09).latest_price(1.08).condition(Condition::SpreadAbove(3.0)).row(&config),
+    ]);
+    server.set_price("EUR/USD", 1.09);
+
+    let gate = MovementGate::new(5.0);
+    let xylex = server.xylex_api().with_movement_gate(gate.clone());
+    gate.seed("EUR/USD", 1.09);
+    server.set_book("EUR/USD", 1.0899, 1.0901);
+    let report = xylex.check_alerts_with(&xylex, &server.supabase(), &config).await.unwrap();
+    assert!(report.triggered.is_empty());
+
+    // The spread widens while the price stays flat
+    server.set_book("EUR/USD", 1.0890, 1.0910);
+    let report = xylex.check_alerts_with(&xylex, &server.supabase(), &config).await.unwrap();
+    let triggered: Vec<&str> = report.triggered.iter().map(|alert| alert.hash.as_str()).collect();
+    assert_eq!(triggered, vec!["xlx-spread"], "The spread changes without the price moving");
+}
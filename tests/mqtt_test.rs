@@ -0,0 +1,18 @@
+#![cfg(feature = "mqtt")]
+
+use trade_alerts::TriggeredAlert;
+use trade_alerts::fixtures::triggered_alert;
+use trade_alerts::notify::{MqttNotifier, Notifier};
+
+#[tokio::test]
+async fn test_mqtt_topic() {
+    let notifier = MqttNotifier::new("localhost", 1883, "trade_alerts_test", "alerts/{user_id}/{symbol}".to_string());
+    let alert = TriggeredAlert {
+        user_id: "user#1".to_string(),
+        trigger_price: 65010.0,
+        ..triggered_alert("xlx-a-1234", "btc/usd", 65000.0)
+    };
+
+    assert_eq!(notifier.topic_for(&alert), "alerts/user1/btcusd");
+    assert_eq!(notifier.name(), "mqtt");
+}
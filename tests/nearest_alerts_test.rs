@@ -0,0 +1,105 @@
+use chrono::{Duration, Utc};
+use serde_json::json;
+
+use trade_alerts::fixtures::triggered_alert;
+use trade_alerts::notify::context::nearest_alerts;
+use trade_alerts::{Alert, TriggeredAlert};
+
+fn triggered(metadata: Option<serde_json::Value>) -> TriggeredAlert {
+    TriggeredAlert {
+        metadata,
+        ..triggered_alert("xlx-eurusd", "EUR/USD", 1.1)
+    }
+}
+
+fn alert(hash: &str, symbol: &str, price_level: f64) -> Alert {
+    Alert::builder().hash(hash).user_id("user123").symbol(symbol).price_level(price_level).build().unwrap()
+}
+
+#[test]
+fn test_nearest_alerts() {
+    let mut expired = alert("xlx-expired", "EUR/USD", 1.1001);
+    expired.expires_at = Some(Utc::now() - Duration::hours(1));
+    let alerts = vec![
+        alert("xlx-eurusd", "EUR/USD", 1.1),
+        alert("xlx-far", "EUR/USD", 1.21),
+        alert("xlx-below", "eurusd", 1.089),
+        alert("xlx-above", "EUR/USD", 1.122),
+        alert("xlx-gbpusd", "GBP/USD", 1.1),
+        expired,
+    ];
+
+    let nearest = nearest_alerts(&triggered(None), &alerts, 2);
+    assert_eq!(nearest.iter().map(|nearest| nearest.hash.as_str()).collect::<Vec<_>>(), vec!["xlx-below", "xlx-above"]);
+    assert!((nearest[0].distance + 0.011).abs() < 1e-9);
+    assert!((nearest[1].distance_percent - 2.0).abs() < 1e-9);
+
+    let enriched = triggered(Some(json!({ "note": "breakout" }))).with_nearest_alerts(&nearest);
+    assert_eq!(enriched.nearest_alerts(), nearest);
+    assert_eq!(enriched.metadata.as_ref().unwrap()["note"], "breakout");
+    let wrapped = triggered(Some(json!("breakout"))).with_nearest_alerts(&nearest);
+    assert_eq!(wrapped.metadata.as_ref().unwrap()["metadata"], "breakout");
+    assert!(triggered(None).nearest_alerts().is_empty());
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_router_adds_nearest_alerts() {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use trade_alerts::errors::NotifyError;
+    use trade_alerts::fixtures::{AlertFixture, FixtureServer, alert_rows, table_config};
+    use trade_alerts::notify::{NotificationRouter, Notifier};
+
+    /// Keeps every alert it is handed.
+    struct Capture(Arc<Mutex<Vec<TriggeredAlert>>>);
+
+    #[async_trait]
+    impl Notifier for Capture {
+        fn name(&self) -> &str {
+            "capture"
+        }
+
+        async fn notify(&self, alert: &TriggeredAlert) -> Result<(), NotifyError> {
+            self.0.lock().unwrap().push(alert.clone());
+            Ok(())
+        }
+    }
+
+    let config = table_config("alerts");
+    let server = FixtureServer::start().await;
+    server.insert_rows("alerts", alert_rows());
+    server.insert_rows("alerts", vec![AlertFixture::new("xlx-eurusd-2", "EUR/USD", 1.12).latest_price(1.08).row(&config)]);
+    let mut expired = AlertFixture::new("xlx-expired", "EUR/USD", 1.1001).row(&config);
+    expired[&config.expiry_column_name] = json!((Utc::now() - Duration::hours(1)).to_rfc3339());
+    let mut hit = AlertFixture::new("xlx-hit", "EUR/USD", 1.1002).row(&config);
+    hit["hit"] = json!(true);
+    server.insert_rows("alerts", vec![expired, hit]);
+    let delivered = Arc::new(Mutex::new(Vec::new()));
+    let router = NotificationRouter::new()
+        .with_channel("capture", Capture(delivered.clone()))
+        .with_default_channels(vec!["capture".to_string()])
+        .with_nearest_alerts(server.supabase(), config, 5);
+
+    router.route(&triggered(None)).await;
+    let mut other_user = triggered(None);
+    other_user.user_id = "user456".to_string();
+    router.route(&other_user).await;
+
+    {
+        let delivered = delivered.lock().unwrap();
+        let nearest = delivered[0].nearest_alerts();
+        assert_eq!(nearest.len(), 1, "Expired and triggered alerts are left out");
+        assert_eq!((nearest[0].hash.as_str(), nearest[0].price_level), ("xlx-eurusd-2", 1.12));
+        assert_eq!(delivered[1].metadata, None, "Users without other alerts on the symbol get none");
+    }
+
+    let fetches = || server.requests().iter().filter(|request| request.starts_with("GET /rest/v1/alerts")).count();
+    let before = fetches();
+    let mut second = triggered(None);
+    second.hash = "xlx-eurusd-3".to_string();
+    router.route_all(&[triggered(None), second, other_user]).await;
+    assert_eq!(fetches() - before, 2, "The alerts of every user are fetched once per cycle");
+    assert_eq!(delivered.lock().unwrap()[2].nearest_alerts().len(), 1);
+}
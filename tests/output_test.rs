@@ -0,0 +1,28 @@
+use serde_json::json;
+
+use trade_alerts::utils::output::OutputFormat;
+
+#[test]
+fn test_output_formats() {
+    let columns = ["hash", "symbol", "price_level", "metadata"];
+    let rows = vec![
+        json!({ "hash": "xlx-a-1", "symbol": "eur/usd", "price_level": 1.1, "metadata": { "note": "a, b" } }),
+        json!({ "hash": "xlx-a-2", "symbol": "btc/usd", "price_level": 65000.0, "extra": true }),
+    ];
+
+    assert_eq!("CSV".parse::<OutputFormat>(), Ok(OutputFormat::Csv));
+    assert!("yaml".parse::<OutputFormat>().is_err());
+
+    let csv = OutputFormat::Csv.render(&columns, &rows);
+    assert_eq!(
+        csv,
+        "hash,symbol,price_level,metadata\nxlx-a-1,eur/usd,1.1,\"{\"\"note\"\":\"\"a, b\"\"}\"\nxlx-a-2,btc/usd,65000.0,\n"
+    );
+
+    let table = OutputFormat::Table.render(&columns, &rows);
+    assert!(table.starts_with("hash     symbol   price_level  metadata\n"));
+    assert_eq!(table.lines().count(), 3);
+
+    let parsed: serde_json::Value = serde_json::from_str(&OutputFormat::Json.render(&columns, &rows)).unwrap();
+    assert_eq!(parsed[1], json!({ "hash": "xlx-a-2", "symbol": "btc/usd", "price_level": 65000.0, "metadata": null }));
+}
@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+use trade_alerts::fixtures::triggered_alert;
+use trade_alerts::notify::PayloadFormat;
+use trade_alerts::{Condition, TriggeredAlert};
+
+fn triggered() -> TriggeredAlert {
+    TriggeredAlert {
+        trigger_price: 65010.5,
+        condition: Condition::SpreadAbove(3.0),
+        metadata: Some(json!({ "note": "breakout", "tags": ["swing"] })),
+        triggered_at: DateTime::parse_from_rfc3339("2030-01-01T14:30:00Z").unwrap().with_timezone(&Utc),
+        ..triggered_alert("xlx-a-1234", "btc/usd", 65000.0)
+    }
+}
+
+#[test]
+fn test_payload_formats() {
+    let alert = triggered();
+
+    assert_eq!(
+        PayloadFormat::Ifttt.render(&alert),
+        json!({ "value1": "btc/usd", "value2": "65010.5", "value3": "65000" })
+    );
+
+    let flat = PayloadFormat::Flat.render(&alert);
+    assert_eq!(flat["condition"], "spread_above");
+    assert_eq!(flat["metadata_note"], "breakout");
+    assert_eq!(flat["metadata_tags"], "[\"swing\"]");
+    assert_eq!(flat["triggered_at"], "2030-01-01T14:30:00+00:00");
+
+    assert_eq!(PayloadFormat::Event.render(&alert), alert.to_event());
+    assert_eq!("zapier".parse::<PayloadFormat>(), Ok(PayloadFormat::Flat));
+}
@@ -0,0 +1,33 @@
+#![cfg(feature = "fixtures")]
+
+use serde_json::Value;
+
+use trade_alerts::fixtures::FixtureServer;
+use trade_alerts::notify::{NotificationRouter, Notifier, WebhookNotifier};
+
+#[tokio::test]
+async fn test_preflight() {
+    // `/gone` is missing and `/hook` only accepts POST, as a webhook would
+    let server = FixtureServer::start().await;
+    server.queue_responses("/gone", vec![(404, Value::Null)]);
+    server.queue_responses("/hook", vec![(405, Value::Null), (405, Value::Null)]);
+    let base = server.url();
+    assert!(WebhookNotifier::new(format!("{}/hook", base)).verify().await.is_ok());
+
+    let router = NotificationRouter::new()
+        .with_channel("zapier", WebhookNotifier::new(format!("{}/hook", base)))
+        .with_channel("ifttt", WebhookNotifier::new(format!("{}/gone", base)))
+        .with_channel("offline", WebhookNotifier::new("http://127.0.0.1:9/hook".to_string()))
+        .with_user_channels("user123", vec!["slack".to_string()])
+        .with_default_channels(vec!["zapier".to_string()]);
+
+    let report = router.preflight().await;
+    let channels: Vec<&str> = report.checks.iter().map(|check| check.channel.as_str()).collect();
+    assert_eq!(channels, vec!["ifttt", "offline", "slack", "zapier"]);
+    assert!(!report.is_ok());
+
+    let failed: Vec<&str> = report.failures().iter().map(|check| check.channel.as_str()).collect();
+    assert_eq!(failed, vec!["ifttt", "offline", "slack"]);
+    assert!(report.checks[0].error.as_deref().unwrap().contains("404"));
+    assert!(NotificationRouter::new().preflight().await.is_ok());
+}
@@ -0,0 +1,67 @@
+use serde_json::json;
+
+use trade_alerts::data::NumberFormat;
+
+#[test]
+fn test_lenient_prices() {
+    let format = NumberFormat::new();
+    assert_eq!(format.parse(&json!("1.0852")), Ok(1.0852));
+    assert_eq!(format.parse(&json!(1.0852)), Ok(1.0852));
+    assert_eq!(format.parse(&json!(" 1,234.5 ")), Ok(1234.5));
+    assert_eq!(format.parse(&json!("1 234 567")), Ok(1234567.0));
+    assert_eq!(format.parse(&json!("1.2e-4")), Ok(0.00012));
+    assert_eq!(format.parse(&json!("-1,000.25E2")), Ok(-100025.0));
+
+    assert_eq!(format.parse(&json!("1,2345")), Err("misplaced thousands separator".to_string()));
+    assert_eq!(format.parse(&json!("1.234,5")), Err("thousands separator after the decimal separator".to_string()));
+    assert_eq!(format.parse(&json!("NaN")), Err("not a number".to_string()));
+    assert_eq!(format.parse(&json!("1,000,000")), Ok(1000000.0));
+    assert_eq!(
+        format.parse(&json!("1,234")),
+        Err("ambiguous separator, configure the thousands separator".to_string())
+    );
+    assert_eq!(format.with_thousands_separator(',').parse(&json!("1,234")), Ok(1234.0));
+    assert_eq!(format.parse(&json!("1 234")), Ok(1234.0), "Spaces only group digits");
+    assert!(format.parse(&json!("1e400")).is_err(), "Infinite prices are rejected");
+    assert!(format.parse(&json!(true)).is_err());
+}
+
+#[test]
+fn test_decimal_comma() {
+    let format = NumberFormat::new().with_decimal_separator(',');
+    assert_eq!(format.parse(&json!("1.234,5")), Ok(1234.5));
+    assert_eq!(format.parse(&json!("1'234,5")), Ok(1234.5));
+    assert_eq!(format.parse(&json!("0,5")), Ok(0.5));
+    assert_eq!(format.parse(&json!(0.5)), Ok(0.5), "Numbers don't depend on the separator");
+    assert!(format.parse(&json!("1.5")).is_err(), "A dot is a thousands separator");
+}
+
+#[test]
+fn test_strict_prices() {
+    let format = NumberFormat::new().strict();
+    assert_eq!(format.parse(&json!("1.0852")), Ok(1.0852));
+    assert_eq!(format.parse(&json!("1.2e-4")), Ok(0.00012));
+    assert_eq!(format.parse(&json!(1.0852)), Ok(1.0852), "Numbers are unambiguous");
+    assert!(format.parse(&json!("1,234.5")).is_err());
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_invalid_price_error() {
+    use trade_alerts::data::XylexApi;
+    use trade_alerts::errors::XylexApiError;
+    use trade_alerts::fixtures::FixtureServer;
+
+    let server = FixtureServer::start().await;
+    server.queue_responses("/price", vec![(200, json!({ "price": "1,08.52" }))]);
+
+    let api = XylexApi::new("key".to_string(), server.price_endpoint());
+    let error = api.request_real_time_price("EUR/USD").await.unwrap_err();
+    assert_eq!(error, XylexApiError::InvalidPrice {
+        symbol: "EUR/USD".to_string(),
+        field: "price".to_string(),
+        raw: "\"1,08.52\"".to_string(),
+        reason: "misplaced thousands separator".to_string(),
+    });
+    assert_eq!(error.to_string(), "Invalid price for EUR/USD: \"1,08.52\" (misplaced thousands separator)");
+}
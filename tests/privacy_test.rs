@@ -0,0 +1,26 @@
+use serde_json::json;
+
+use trade_alerts::utils::privacy::PrivacyPolicy;
+
+#[test]
+fn test_privacy_policy_redaction() {
+    let policy: PrivacyPolicy = "mask".parse().expect("Failed to parse policy");
+
+    assert_eq!(policy.redact_user_id("user1234"), "us****34");
+    assert_eq!(policy.redact_email("floris@xylex.ai"), "f***@xylex.ai");
+    assert_eq!(policy.redact_url("https://hooks.example.com/T000/secret?x=1"), "https://hooks.example.com/***");
+
+    let mut event = json!({
+        "hash": "xlx-a-1234",
+        "user_id": "user1234",
+        "metadata": { "webhook_url": "https://hooks.example.com/T000/secret" },
+    });
+    policy.scrub_value(&mut event, &[]);
+    assert_eq!(event["hash"], "xlx-a-1234");
+    assert_eq!(event["user_id"], "us****34");
+    assert_eq!(event["metadata"]["webhook_url"], "https://hooks.example.com/***");
+
+    let hashed = PrivacyPolicy::Hash.redact_user_id("user1234");
+    assert_eq!(hashed, PrivacyPolicy::Hash.redact_user_id("user1234"));
+    assert!(!hashed.contains("user1234"));
+}
@@ -0,0 +1,137 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use trade_alerts::data::chain::CACHE_SOURCE;
+use trade_alerts::data::{PriceProvider, ProviderChain, Quote};
+use trade_alerts::errors::XylexApiError;
+
+/// Quotes every symbol at `price`, failing `broken` symbols and everything once `down` is set.
+#[derive(Clone, Default)]
+struct FakeProvider {
+    price: f64,
+    broken: Vec<&'static str>,
+    delay: Option<Duration>,
+    down: Arc<AtomicBool>,
+    requests: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl PriceProvider for FakeProvider {
+    async fn fetch_price(&self, symbol: &str) -> Result<Quote, XylexApiError> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        if let Some(delay) = self.delay {
+            tokio::time::sleep(delay).await;
+        }
+        if self.down.load(Ordering::SeqCst) || self.broken.contains(&symbol) {
+            return Err(XylexApiError::NetworkError(format!("no quote for {}", symbol)));
+        }
+        Ok(Quote::new(symbol.to_string(), self.price))
+    }
+}
+
+#[tokio::test]
+async fn test_chain_falls_back_per_symbol() {
+    let primary = FakeProvider { price: 1.0, broken: vec!["gbp/usd"], ..FakeProvider::default() };
+    let fallback = FakeProvider { price: 2.0, ..FakeProvider::default() };
+    let chain = ProviderChain::new("xylex", primary).with_fallback("twelvedata", fallback.clone());
+    assert_eq!(chain.providers(), vec!["xylex", "twelvedata"]);
+
+    let quotes = chain.fetch_prices(&["eur/usd", "gbp/usd"]).await.unwrap();
+    assert_eq!(quotes.iter().map(|quote| quote.price).collect::<Vec<_>>(), vec![1.0, 2.0]);
+    assert_eq!(chain.source("EURUSD").as_deref(), Some("xylex"));
+    assert_eq!(chain.source("gbp/usd").as_deref(), Some("twelvedata"));
+    assert_eq!(fallback.requests.load(Ordering::SeqCst), 1);
+    assert_eq!(chain.source("usd/jpy"), None);
+}
+
+#[tokio::test]
+async fn test_chain_times_out_and_serves_the_cache() {
+    let slow = FakeProvider { price: 1.0, delay: Some(Duration::from_secs(5)), ..FakeProvider::default() };
+    let fallback = FakeProvider { price: 2.0, ..FakeProvider::default() };
+    let chain = ProviderChain::new("slow", slow)
+        .with_fallback("fallback", fallback.clone())
+        .with_timeout(Duration::from_millis(20))
+        .with_cache(Duration::from_secs(60));
+
+    assert_eq!(chain.fetch_price("eur/usd").await.unwrap().price, 2.0);
+    assert_eq!(chain.source("eur/usd").as_deref(), Some("fallback"));
+
+    fallback.down.store(true, Ordering::SeqCst);
+    assert_eq!(chain.fetch_price("eur/usd").await.unwrap().price, 2.0);
+    assert_eq!(chain.source("eur/usd").as_deref(), Some(CACHE_SOURCE));
+    assert!(chain.fetch_price("gbp/usd").await.is_err(), "Symbols never fetched have no cached quote");
+
+    let uncached = ProviderChain::new("fallback", fallback);
+    assert!(matches!(uncached.fetch_price("eur/usd").await, Err(XylexApiError::NetworkError(_))));
+}
+
+#[tokio::test]
+async fn test_chain_reports_failover_transitions() {
+    let primary = FakeProvider { price: 1.0, ..FakeProvider::default() };
+    let fallback = FakeProvider { price: 2.0, ..FakeProvider::default() };
+    let chain = ProviderChain::new("primary", primary.clone())
+        .with_fallback("fallback", fallback)
+        .with_switch_suppression(Duration::from_millis(50));
+
+    chain.fetch_price("eur/usd").await.unwrap();
+    assert!(!chain.in_failover_transition("eur/usd"), "The first quote is no switch");
+
+    primary.down.store(true, Ordering::SeqCst);
+    chain.fetch_price("eur/usd").await.unwrap();
+    assert!(chain.in_failover_transition("EURUSD"));
+    assert!(!chain.in_failover_transition("gbp/usd"));
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(!chain.in_failover_transition("eur/usd"), "The window passed");
+
+    primary.down.store(false, Ordering::SeqCst);
+    chain.fetch_price("eur/usd").await.unwrap();
+    assert!(chain.in_failover_transition("eur/usd"), "Switching back is a switch too");
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_crossings_during_failover_await_confirmation() {
+    use trade_alerts::data::XylexApi;
+    use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+    use trade_alerts::{BandMode, Condition};
+
+    assert_eq!(Condition::Band(BandMode::Enter).crossing_confirmation(), Some(Condition::Band(BandMode::Inside)));
+    assert_eq!(Condition::SpreadAbove(3.0).crossing_confirmation(), None);
+
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    let mut row = AlertFixture::new("xlx-eurusd", "EUR/USD", 1.10).latest_price(1.08).row(&config);
+    row["id"] = 1.into();
+    server.insert_rows("alerts", vec![row]);
+
+    let primary = FakeProvider { price: 1.09, ..FakeProvider::default() };
+    let fallback = FakeProvider { price: 1.11, ..FakeProvider::default() };
+    let chain = ProviderChain::new("primary", primary.clone())
+        .with_fallback("fallback", fallback)
+        .with_switch_suppression(Duration::from_secs(60));
+    let xylex = XylexApi::new(String::new(), String::new());
+    let check = || async { xylex.check_alerts_with(&chain, &server.supabase(), &config).await.unwrap().triggered.len() };
+
+    assert_eq!(check().await, 0);
+    primary.down.store(true, Ordering::SeqCst);
+    assert_eq!(check().await, 0, "The crossing right after the switch is suppressed");
+    assert_eq!(check().await, 1, "The next poll confirms it");
+
+    primary.down.store(false, Ordering::SeqCst);
+    assert_eq!(check().await, 0);
+    primary.down.store(true, Ordering::SeqCst);
+    assert_eq!(check().await, 0, "Suppressed again after the next switch");
+    primary.down.store(false, Ordering::SeqCst);
+    assert_eq!(check().await, 0, "The primary's price does not confirm it");
+
+    // A crossing of an alert deleted meanwhile is forgotten
+    primary.down.store(true, Ordering::SeqCst);
+    assert_eq!(check().await, 0);
+    assert!(xylex.awaiting_confirmation("xlx-eurusd"));
+    server.set_rows("alerts", Vec::new());
+    assert_eq!(check().await, 0);
+    assert!(!xylex.awaiting_confirmation("xlx-eurusd"));
+}
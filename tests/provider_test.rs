@@ -0,0 +1,28 @@
+use async_trait::async_trait;
+
+use trade_alerts::data::{PriceProvider, Quote};
+use trade_alerts::errors::XylexApiError;
+
+struct FixedProvider;
+
+#[async_trait]
+impl PriceProvider for FixedProvider {
+    async fn fetch_price(&self, symbol: &str) -> Result<Quote, XylexApiError> {
+        match symbol {
+            "eur/usd" => Ok(Quote::new(symbol.to_string(), 1.1)),
+            _ => Err(XylexApiError::InvalidSymbol(symbol.to_string())),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_price_provider_defaults() {
+    let provider: &dyn PriceProvider = &FixedProvider;
+
+    let quotes = provider.fetch_prices(&["eur/usd", "eur/usd"]).await.expect("Failed to fetch prices");
+    assert_eq!(quotes.len(), 2);
+    assert_eq!(quotes[0].price, 1.1);
+
+    assert!(provider.fetch_prices(&["eur/usd", "xau/usd"]).await.is_err());
+    assert_eq!(provider.supported_symbols().await.unwrap(), None);
+}
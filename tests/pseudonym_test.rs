@@ -0,0 +1,63 @@
+use trade_alerts::TriggeredAlert;
+use trade_alerts::fixtures::triggered_alert;
+use trade_alerts::notify::{PayloadFormat, WebhookNotifier};
+use trade_alerts::utils::pseudonym::Pseudonymizer;
+
+fn triggered(user_id: &str) -> TriggeredAlert {
+    TriggeredAlert {
+        user_id: user_id.to_string(),
+        trigger_price: 1.1001,
+        ..triggered_alert("xlx-eurusd", "EUR/USD", 1.1)
+    }
+}
+
+#[test]
+fn test_pseudonymize_and_resolve() {
+    let pseudonymizer = Pseudonymizer::new("pseudonym-secret");
+    let pseudonym = pseudonymizer.pseudonymize("user123");
+    assert!(pseudonym.starts_with("u_") && pseudonym.len() == 34, "{}", pseudonym);
+    assert!(!pseudonym.contains("user123"));
+    assert_eq!(pseudonym, pseudonymizer.pseudonymize("user123"), "Pseudonyms should be stable");
+    assert_ne!(pseudonym, pseudonymizer.pseudonymize("user456"));
+    assert_ne!(pseudonym, Pseudonymizer::new("other-secret").pseudonymize("user123"));
+    assert_eq!(Pseudonymizer::new("pseudonym-secret").with_prefix("anon-").pseudonymize("user123")[5..], pseudonym[2..]);
+
+    assert_eq!(pseudonymizer.clone().resolve(&pseudonym), Some("user123".to_string()));
+    assert_eq!(pseudonymizer.resolve("u_unknown"), None);
+
+    // Another process keyed with the same secret resolves it among the known users
+    let other = Pseudonymizer::new("pseudonym-secret");
+    assert_eq!(other.resolve(&pseudonym), None);
+    assert_eq!(other.resolve_among(&pseudonym, ["user456", "user123"]), Some("user123".to_string()));
+    assert_eq!(other.resolve(&pseudonym), Some("user123".to_string()));
+    assert_eq!(other.resolve_among("u_unknown", ["user123"]), None);
+
+    assert!(!format!("{:?}", pseudonymizer).contains("pseudonym-secret"));
+}
+
+#[test]
+fn test_webhook_payload_is_pseudonymized() {
+    let pseudonymizer = Pseudonymizer::new("pseudonym-secret");
+    let alert = triggered("user123");
+
+    for format in [PayloadFormat::Event, PayloadFormat::Flat] {
+        let plain = WebhookNotifier::new("https://example.com/hook".to_string()).with_format(format);
+        assert!(plain.payload(&alert).to_string().contains("user123"));
+
+        let notifier = plain.with_pseudonymizer(pseudonymizer.clone());
+        let payload = notifier.payload(&alert).to_string();
+        assert!(!payload.contains("user123"), "{}", payload);
+        assert!(payload.contains(&pseudonymizer.pseudonymize("user123")), "{}", payload);
+    }
+}
+
+#[cfg(feature = "mqtt")]
+#[tokio::test]
+async fn test_mqtt_topic_is_pseudonymized() {
+    use trade_alerts::notify::MqttNotifier;
+
+    let pseudonymizer = Pseudonymizer::new("pseudonym-secret");
+    let notifier = MqttNotifier::new("localhost", 1883, "trade_alerts_pseudonym_test", "alerts/{user_id}".to_string())
+        .with_pseudonymizer(pseudonymizer.clone());
+    assert_eq!(notifier.topic_for(&triggered("user123")), format!("alerts/{}", pseudonymizer.pseudonymize("user123")));
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "fixtures")]
+
+use futures_util::future::join_all;
+use trade_alerts::Alert;
+use trade_alerts::db::{QuotaPolicy, Supabase, TableConfig};
+use trade_alerts::errors::SupabaseError;
+use trade_alerts::fixtures::{AlertFixture, FixtureServer, alert_rows};
+
+async fn start(max_active_alerts_per_user: usize) -> (FixtureServer, Supabase) {
+    let server = FixtureServer::start().await;
+    server.set_rows("alerts", alert_rows());
+    server.set_price("EUR/USD", 1.08);
+    let supabase = server.supabase().with_quota_policy(QuotaPolicy { max_active_alerts_per_user });
+    (server, supabase)
+}
+
+fn alert(hash: &str) -> Alert {
+    AlertFixture::new(hash, "EUR/USD", 1.20).alert()
+}
+
+fn alerts_of(server: &FixtureServer, user_id: &str) -> usize {
+    server.rows("alerts").iter().filter(|row| row["user_id"] == user_id).count()
+}
+
+#[tokio::test]
+async fn test_quota_rejects_alerts_over_the_limit() {
+    let (server, supabase) = start(2).await;
+
+    let error = supabase.add_alert_with(&server.xylex_api(), alert("xlx-new"), TableConfig::default()).await.unwrap_err();
+
+    assert!(matches!(error.downcast_ref::<SupabaseError>(), Some(SupabaseError::QuotaExceeded(_))));
+    assert_eq!(alerts_of(&server, "user123"), 2);
+}
+
+#[tokio::test]
+async fn test_expired_alerts_do_not_count_against_the_quota() {
+    let (server, supabase) = start(2).await;
+    let mut rows = alert_rows();
+    rows[0]["expires_at"] = "2020-01-01T00:00:00Z".into();
+    server.set_rows("alerts", rows);
+
+    supabase.add_alert_with(&server.xylex_api(), alert("xlx-new"), TableConfig::default()).await.unwrap();
+
+    assert_eq!(alerts_of(&server, "user123"), 3);
+}
+
+#[tokio::test]
+async fn test_concurrent_additions_stay_within_the_quota() {
+    let (server, supabase) = start(3).await;
+    let api = server.xylex_api();
+
+    // Every call counts two alerts before any of them inserts
+    let results = join_all((0..5).map(|i| {
+        supabase.add_alert_with(&api, alert(&format!("xlx-new-{}", i)), TableConfig::default())
+    }))
+    .await;
+
+    assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+    assert_eq!(alerts_of(&server, "user123"), 3);
+    assert!(server.requests().iter().any(|request| request.starts_with("POST /rest/v1/rpc/insert_alerts_within_quota")));
+}
+
+#[tokio::test]
+async fn test_without_quota_alerts_are_inserted_directly() {
+    let server = FixtureServer::start().await;
+    server.set_rows("alerts", alert_rows());
+    server.set_price("EUR/USD", 1.08);
+
+    server.supabase().add_alert_with(&server.xylex_api(), alert("xlx-new"), TableConfig::default()).await.unwrap();
+
+    assert_eq!(alerts_of(&server, "user123"), 3);
+    assert!(!server.requests().iter().any(|request| request.contains("/rpc/")));
+}
@@ -0,0 +1,38 @@
+use std::time::{Duration, Instant};
+
+use trade_alerts::data::RateLimiter;
+
+#[tokio::test]
+async fn test_token_bucket() {
+    let limiter = RateLimiter::new(50.0).with_burst(3.0);
+
+    let started = Instant::now();
+    for _ in 0..3 {
+        limiter.acquire().await;
+    }
+    assert!(started.elapsed() < Duration::from_millis(20), "The burst should not wait");
+
+    let shared = limiter.clone();
+    let waiting = tokio::spawn(async move {
+        for _ in 0..3 {
+            shared.acquire().await;
+        }
+    });
+    for _ in 0..2 {
+        limiter.acquire().await;
+    }
+    waiting.await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(95), "Clones should share the bucket");
+}
+
+#[tokio::test]
+async fn test_invalid_rates_never_panic() {
+    for rate in [0.0, -1.0, f64::NAN, f64::INFINITY, f64::MIN_POSITIVE] {
+        let limiter = RateLimiter::new(rate).with_burst(1.0);
+        assert!(limiter.requests_per_second > 0.0 && limiter.requests_per_second.is_finite());
+        limiter.acquire().await;
+        // The second request waits, capped instead of overflowing
+        let waited = tokio::time::timeout(Duration::from_millis(20), limiter.acquire()).await;
+        assert_eq!(waited.is_err(), rate != f64::INFINITY, "Rate {}", rate);
+    }
+}
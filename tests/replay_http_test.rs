@@ -0,0 +1,63 @@
+#![cfg(all(feature = "replay-http", feature = "fixtures"))]
+
+use std::path::PathBuf;
+
+use trade_alerts::data::{BinanceProvider, PriceProvider};
+use trade_alerts::errors::XylexApiError;
+use trade_alerts::fixtures::FixtureServer;
+use trade_alerts::replay_http::{HttpReplay, ReplayMode, redact_url};
+
+fn cassette(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("trade_alerts_{}_{}.json", name, std::process::id()))
+}
+
+#[test]
+fn test_redact_url() {
+    assert_eq!(
+        redact_url("https://api.example.com/price?symbol=AAPL&api_key=secret"),
+        "https://api.example.com/price?symbol=AAPL&api_key=REDACTED"
+    );
+    assert_eq!(redact_url("https://api.example.com/price"), "https://api.example.com/price");
+}
+
+#[tokio::test]
+async fn test_record_then_replay() {
+    let path = cassette("record");
+    let _ = std::fs::remove_file(&path);
+    let server = FixtureServer::start().await;
+    server.set_price("AAPL", 190.5);
+
+    let recorder = HttpReplay::open(&path).unwrap();
+    assert_eq!(recorder.mode(), ReplayMode::Record);
+    let recorded = server.xylex_api().with_replay(recorder).fetch_price("AAPL").await.unwrap();
+    assert_eq!(recorded.price, 190.5);
+
+    // The server's price moves on, the replay still answers with the recorded response
+    server.set_price("AAPL", 200.0);
+    let requests = server.requests().len();
+    let replay = HttpReplay::open(&path).unwrap();
+    assert_eq!(replay.mode(), ReplayMode::Replay);
+    assert!(replay.interactions()[0].url.contains("REDACTED"));
+
+    let api = server.xylex_api().with_replay(replay);
+    assert_eq!(api.fetch_price("AAPL").await.unwrap().price, 190.5);
+    assert_eq!(server.requests().len(), requests);
+    assert!(matches!(api.fetch_price("AAPL").await, Err(XylexApiError::UnexpectedError(_))));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_binance_cassette() {
+    let replay = HttpReplay::replay("tests/cassettes/binance_24hr.json").unwrap();
+    let provider = BinanceProvider::new().with_24h_stats().with_replay(replay);
+
+    let quotes = provider.fetch_prices(&["BTC/USDT", "eth-usdt"]).await.unwrap();
+    assert_eq!(quotes[0].symbol, "BTC/USDT");
+    assert_eq!(quotes[0].price, 66311.24);
+    assert_eq!(quotes[0].bid, Some(66311.23));
+    assert_eq!(quotes[1].volume, Some(221043.7854));
+    assert!(quotes[1].timestamp.is_some());
+
+    let unknown = provider.fetch_price("DOESNOTEXIST").await;
+    assert_eq!(unknown, Err(XylexApiError::InvalidSymbol("Invalid symbol.".to_string())));
+}
@@ -0,0 +1,84 @@
+use futures_util::StreamExt;
+
+use trade_alerts::data::{PriceProvider, ReplayProvider, StreamingProvider};
+
+const TICKS: &str = "\
+time,symbol,close,volume
+2024-03-01T12:00:00Z,EUR/USD,1.0800,10
+2024-03-01T12:01:00Z,EUR/USD,1.0850,12
+2024-03-01T12:00:30Z,\"AAPL\",190,
+1709294520,eurusd,1.0900,8
+";
+
+#[tokio::test]
+async fn test_replay_csv() {
+    let provider = ReplayProvider::from_csv_str(TICKS).unwrap();
+    assert_eq!(provider.ticks().len(), 4);
+    assert_eq!(provider.ticks()[1].symbol, "AAPL", "Ticks should be sorted by time");
+    assert_eq!(provider.ticks()[3].price, 1.09);
+    assert_eq!(provider.ticks()[0].volume, Some(10.0));
+    assert!(provider.replay_time().is_none());
+
+    // In real time the clock stays at the first tick
+    let quote = provider.fetch_price("eurusd").await.unwrap();
+    assert_eq!((quote.symbol.as_str(), quote.price), ("eurusd", 1.08));
+    assert!(provider.fetch_price("AAPL").await.is_err(), "AAPL has no tick yet");
+    assert!(provider.fetch_price("GBP/USD").await.is_err());
+    assert!(!provider.is_finished());
+
+    let fast = ReplayProvider::from_csv_str(TICKS).unwrap().with_speed(f64::INFINITY);
+    assert_eq!(fast.fetch_price("EUR/USD").await.unwrap().price, 1.09);
+    assert!(fast.is_finished());
+    assert_eq!(fast.supported_symbols().await.unwrap(), Some(vec!["EUR/USD".to_string(), "AAPL".to_string()]));
+
+    let error = ReplayProvider::from_csv_str("time,symbol,price\n2024-03-01T12:00:00Z,EUR/USD,abc\n").unwrap_err();
+    assert!(error.contains("line 2"), "{}", error);
+    assert!(ReplayProvider::from_csv_str("time,symbol,price\n").is_err());
+}
+
+#[tokio::test]
+async fn test_replay_stream() {
+    let provider = ReplayProvider::from_csv_str(TICKS).unwrap().with_speed(60_000.0);
+    let quotes: Vec<_> = provider.subscribe(&["EURUSD".to_string()]).await.unwrap().collect().await;
+
+    assert_eq!(quotes.iter().map(|quote| quote.price).collect::<Vec<_>>(), vec![1.08, 1.085, 1.09]);
+    assert!(quotes.iter().all(|quote| quote.symbol == "EURUSD"));
+}
+
+#[cfg(feature = "parquet")]
+#[tokio::test]
+async fn test_replay_parquet() {
+    use std::sync::Arc;
+
+    use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+
+    let schema = parse_message_type(
+        "message ticks { required int64 timestamp (TIMESTAMP(MILLIS,true)); required binary symbol (UTF8); required double price; }",
+    )
+    .unwrap();
+    let path = std::env::temp_dir().join(format!("replay-{}.parquet", std::process::id()));
+    let file = std::fs::File::create(&path).unwrap();
+    let mut writer = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(WriterProperties::builder().build())).unwrap();
+    let mut group = writer.next_row_group().unwrap();
+
+    let mut column = group.next_column().unwrap().unwrap();
+    column.typed::<Int64Type>().write_batch(&[1709294400000, 1709294460000], None, None).unwrap();
+    column.close().unwrap();
+    let mut column = group.next_column().unwrap().unwrap();
+    column.typed::<ByteArrayType>().write_batch(&[ByteArray::from("BTC/USD"), ByteArray::from("BTC/USD")], None, None).unwrap();
+    column.close().unwrap();
+    let mut column = group.next_column().unwrap().unwrap();
+    column.typed::<DoubleType>().write_batch(&[62000.0, 62500.0], None, None).unwrap();
+    column.close().unwrap();
+    group.close().unwrap();
+    writer.close().unwrap();
+
+    let provider = ReplayProvider::from_parquet(&path).unwrap().with_speed(f64::INFINITY);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(provider.ticks().len(), 2);
+    assert_eq!(provider.range().0.timestamp(), 1709294400);
+    assert_eq!(provider.fetch_price("btcusd").await.unwrap().price, 62500.0);
+}
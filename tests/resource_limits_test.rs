@@ -0,0 +1,90 @@
+use chrono::{TimeZone, Utc};
+
+use trade_alerts::{ResourceLimits, Timeframe, TradeAlerts};
+use trade_alerts::data::{MockProvider, MovementGate, ProviderChain, XylexApi};
+use trade_alerts::notify::NotificationRouter;
+use trade_alerts::db::{Supabase, TableConfig};
+use trade_alerts::utils::lru::{CacheStats, LruCache};
+use trade_alerts::utils::pseudonym::Pseudonymizer;
+
+#[test]
+fn test_lru_cache() {
+    let mut cache: LruCache<&str, f64> = LruCache::new(Some(2));
+    cache.insert("EUR/USD", 1.08);
+    cache.insert("GBP/USD", 1.26);
+    assert_eq!(cache.get(&"EUR/USD"), Some(&1.08));
+    assert_eq!(cache.insert("AAPL", 190.0), None);
+    assert_eq!(cache.peek(&"GBP/USD"), None, "GBP/USD was used least recently");
+    assert_eq!(cache.stats(), CacheStats { entries: 2, capacity: Some(2), evictions: 1 });
+
+    assert_eq!(cache.insert("AAPL", 191.0), Some(190.0));
+    *cache.get_or_insert_with("BTC/USD", || 0.0) += 62000.0;
+    assert_eq!(cache.peek(&"BTC/USD"), Some(&62000.0));
+    assert_eq!(cache.peek(&"EUR/USD"), None);
+
+    cache.set_capacity(Some(1));
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.stats().evictions, 3);
+    cache.set_capacity(None);
+    for symbol in ["EUR/USD", "GBP/USD", "AAPL"] {
+        cache.insert(symbol, 1.0);
+    }
+    assert_eq!(cache.stats(), CacheStats { entries: 4, capacity: None, evictions: 3 });
+}
+
+#[test]
+fn test_resource_limits_bound_the_caches() {
+    let limits = ResourceLimits::new().with_max_symbols(2).with_max_candles(3).with_max_pseudonyms(1);
+    let xylex = XylexApi::new("key".to_string(), "https://example.com".to_string())
+        .with_resource_limits(limits)
+        .with_movement_gate(MovementGate::new(0.1));
+
+    let at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    for symbol in ["EUR/USD", "GBP/USD", "AAPL", "BTC/USD"] {
+        xylex.previous_prices.record(symbol, 1.0);
+        xylex.volumes.record(symbol, 10.0);
+        xylex.movement_gate.as_ref().unwrap().should_evaluate(symbol, 1.0);
+        for timeframe in [Timeframe::FiveMinutes, Timeframe::OneHour] {
+            xylex.candles.record(symbol, timeframe, 1.0, at);
+        }
+    }
+    assert_eq!(xylex.previous_prices.get("EUR/USD"), None, "The oldest symbols are evicted");
+    assert_eq!(xylex.previous_prices.get("BTC/USD"), Some(1.0));
+
+    let stats = xylex.cache_stats();
+    assert_eq!(stats["previous_prices"], CacheStats { entries: 2, capacity: Some(2), evictions: 2 });
+    assert_eq!(stats["volumes"].entries, 2);
+    assert_eq!(stats["movement_gate"].entries, 2);
+    assert_eq!(stats["candles"], CacheStats { entries: 3, capacity: Some(3), evictions: 5 });
+    assert_eq!(stats["period_candles"], CacheStats { entries: 0, capacity: Some(3), evictions: 0 });
+
+    let alerts = TradeAlerts::builder()
+        .with_supabase(Supabase::new("key".to_string(), "https://example.supabase.co".to_string()))
+        .with_table_config(TableConfig::default())
+        .with_xylex(XylexApi::new("key".to_string(), "https://example.com".to_string()))
+        .with_resource_limits(limits)
+        .build()
+        .unwrap();
+    assert_eq!(alerts.cache_stats()["volumes"].capacity, Some(2));
+
+    // The builder bounds the caches of the provider chain and the router's pseudonymizer
+    let chain = ProviderChain::new("mock", MockProvider::new());
+    let routed = Pseudonymizer::new("secret");
+    TradeAlerts::builder()
+        .with_supabase(Supabase::new("key".to_string(), "https://example.supabase.co".to_string()))
+        .with_table_config(TableConfig::default())
+        .with_provider(chain.clone())
+        .with_router(NotificationRouter::new().with_pseudonymizer(routed.clone()))
+        .with_resource_limits(limits)
+        .build()
+        .unwrap();
+    assert_eq!(chain.cache_stats().capacity, Some(2));
+    assert_eq!(routed.stats().capacity, Some(1));
+
+    let pseudonymizer = Pseudonymizer::new("secret").with_resource_limits(limits);
+    let first = pseudonymizer.pseudonymize("user123");
+    pseudonymizer.pseudonymize("user456");
+    assert_eq!(pseudonymizer.resolve(&first), None);
+    assert_eq!(pseudonymizer.resolve_among(&first, ["user123"]), Some("user123".to_string()));
+    assert_eq!(pseudonymizer.stats().evictions, 2);
+}
@@ -0,0 +1,34 @@
+use trade_alerts::db::Supabase;
+use trade_alerts::db::rest::{contains, eq, gte, is_in, lte};
+
+#[test]
+fn test_eq_filter() {
+    assert_eq!(eq("user_id", "user123"), ("user_id".to_string(), "eq.user123".to_string()));
+}
+
+#[test]
+fn test_in_filter_quotes_values() {
+    let filter = is_in("symbol", &["EURUSD", "a,\"b\""]);
+
+    assert_eq!(filter, ("symbol".to_string(), "in.(\"EURUSD\",\"a,\\\"b\\\"\")".to_string()));
+}
+
+#[test]
+fn test_range_filters() {
+    assert_eq!(gte("price_level", 1.0995), ("price_level".to_string(), "gte.1.0995".to_string()));
+    assert_eq!(lte("price_level", 1.1005), ("price_level".to_string(), "lte.1.1005".to_string()));
+}
+
+#[test]
+fn test_contains_filter() {
+    assert_eq!(contains("tags", &["breakout"]), ("tags".to_string(), "cs.[\"breakout\"]".to_string()));
+}
+
+#[test]
+fn test_supabase_debug_redacts_key() {
+    let supabase = Supabase::new("secret-service-key".to_string(), "https://example.supabase.co".to_string());
+    let debug = format!("{:?}", supabase);
+
+    assert!(!debug.contains("secret-service-key"));
+    assert!(debug.contains("https://example.supabase.co"));
+}
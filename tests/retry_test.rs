@@ -0,0 +1,108 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use trade_alerts::retry::RetryPolicy;
+
+fn fast_policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy::default()
+        .with_max_attempts(max_attempts)
+        .with_backoff(Duration::from_millis(1), Duration::from_millis(5))
+}
+
+#[test]
+fn test_backoff_grows_up_to_the_maximum() {
+    let policy = RetryPolicy::default().with_backoff(Duration::from_millis(100), Duration::from_millis(300));
+    assert_eq!(policy.backoff(1), Duration::from_millis(100));
+    assert_eq!(policy.backoff(2), Duration::from_millis(200));
+    assert_eq!(policy.backoff(3), Duration::from_millis(300));
+    assert_eq!(RetryPolicy::none().max_attempts, 1);
+}
+
+#[tokio::test]
+async fn test_only_retryable_errors_are_retried() {
+    let attempts = AtomicUsize::new(0);
+    let result: Result<(), &str> = fast_policy(5)
+        .run(
+            || async { Err(if attempts.fetch_add(1, Ordering::SeqCst) < 2 { "blip" } else { "fatal" }) },
+            |e| *e == "blip"
+        )
+        .await;
+    assert_eq!(result, Err("fatal"));
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[cfg(feature = "fixtures")]
+mod fixtures {
+    use serde_json::{Value, json};
+
+    use trade_alerts::AlertUpdate;
+    use trade_alerts::data::{CreditBudget, XylexApi};
+    use trade_alerts::db::{Supabase, TableConfig};
+    use trade_alerts::errors::XylexApiError;
+    use trade_alerts::fixtures::FixtureServer;
+    use trade_alerts::retry::RetryPolicy;
+
+    use super::fast_policy;
+
+    /// Starts a fixture server answering its first Supabase requests with `responses` in turn.
+    async fn serve_responses(responses: Vec<(u16, Value)>) -> FixtureServer {
+        let server = FixtureServer::start().await;
+        server.queue_responses("/rest/v1/", responses);
+        server
+    }
+
+    #[tokio::test]
+    async fn test_price_request_survives_network_blips() {
+        let server = FixtureServer::start().await;
+        server.set_price("eur/usd", 1.5);
+        server.drop_connections(2);
+        let api = XylexApi::new("key".to_string(), server.price_endpoint())
+            .with_retry_policy(fast_policy(3))
+            .with_budget(CreditBudget::new(1.0, 100.0));
+        assert_eq!(api.request_real_time_price("eur/usd").await.unwrap(), 1.5);
+        assert_eq!(server.requests().len(), 3);
+        assert_eq!(api.budget_metrics().unwrap().spent, 3.0, "Every attempt should be charged");
+
+        server.drop_connections(1);
+        let api = XylexApi::new("key".to_string(), server.price_endpoint()).with_retry_policy(RetryPolicy::none());
+        assert!(matches!(api.request_real_time_price("eur/usd").await, Err(XylexApiError::NetworkError(_))));
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_supabase_retries_only_transient_errors() {
+        let config = TableConfig::default();
+        let server = serve_responses(vec![(503, json!({})), (429, json!({})), (200, json!([{ "hash": "h", "version": 3 }]))]).await;
+        let supabase = Supabase::new("key".to_string(), server.url().to_string()).with_retry_policy(fast_policy(3));
+        assert_eq!(supabase.fetch_alert_version("h", &config).await.unwrap(), 3);
+        assert_eq!(server.requests().len(), 3);
+
+        let server = serve_responses(vec![(400, json!({}))]).await;
+        let supabase = Supabase::new("key".to_string(), server.url().to_string()).with_retry_policy(fast_policy(3));
+        assert!(supabase.fetch_alert_version("h", &config).await.is_err());
+        assert_eq!(server.requests().len(), 1, "Client errors should not be retried");
+    }
+
+    #[tokio::test]
+    async fn test_retried_update_is_not_a_conflict() {
+        let config = TableConfig::default();
+        let update = AlertUpdate { price_level: Some(1.5), ..AlertUpdate::default() };
+
+        // The first attempt is applied but answered with a 503, so the retry matches no rows
+        let server = serve_responses(vec![
+            (503, json!({})),
+            (200, json!([])),
+            (200, json!([{ "hash": "h", "price_level": 1.5, "version": 4 }])),
+        ])
+        .await;
+        let supabase = Supabase::new("key".to_string(), server.url().to_string()).with_retry_policy(fast_policy(3));
+        assert_eq!(supabase.update_alert_if_version("h", update.clone(), 3, &config).await.unwrap(), 4);
+        assert_eq!(server.requests().len(), 3);
+
+        let current = json!([{ "hash": "h", "price_level": 2.0, "version": 4 }]);
+        let server = serve_responses(vec![(200, json!([])), (200, current.clone()), (200, current)]).await;
+        let supabase = Supabase::new("key".to_string(), server.url().to_string()).with_retry_policy(fast_policy(3));
+        let result = supabase.update_alert_if_version("h", update, 3, &config).await;
+        assert!(result.unwrap_err().to_string().contains("version 4"), "Another writer's change is a conflict");
+    }
+}
@@ -0,0 +1,80 @@
+use serde_json::json;
+
+use trade_alerts::TriggeredAlert;
+use trade_alerts::fixtures::triggered_alert;
+use trade_alerts::notify::{NotificationRouter, PayloadFormat, RouteTarget, WebhookNotifier};
+
+fn triggered(user_id: &str, metadata: Option<serde_json::Value>) -> TriggeredAlert {
+    TriggeredAlert {
+        user_id: user_id.to_string(),
+        trigger_price: 1.1002,
+        metadata,
+        ..triggered_alert("xlx-a-1234", "eur/usd", 1.1)
+    }
+}
+
+#[test]
+fn test_route_precedence() {
+    let router = NotificationRouter::new()
+        .with_channel("webhook", WebhookNotifier::new("https://example.com/default".to_string()))
+        .with_user_channels("user123", vec!["slack".to_string()])
+        .with_default_channels(vec!["webhook".to_string()]);
+
+    assert_eq!(router.targets(&triggered("other", None)), vec![RouteTarget::Channel("webhook".to_string())]);
+    assert_eq!(router.targets(&triggered("user123", None)), vec![RouteTarget::Channel("slack".to_string())]);
+
+    let webhook = json!({ "notify": { "webhook_url": "https://example.com/workflow", "format": "ifttt" } });
+    assert_eq!(
+        router.targets(&triggered("user123", Some(webhook))),
+        vec![RouteTarget::Webhook { url: "https://example.com/workflow".to_string(), format: PayloadFormat::Ifttt }]
+    );
+
+    let channels = json!({ "notify": { "channels": ["discord", "webhook"] } });
+    assert_eq!(
+        router.targets(&triggered("user123", Some(channels))),
+        vec![RouteTarget::Channel("discord".to_string()), RouteTarget::Channel("webhook".to_string())]
+    );
+
+    let muted = json!({ "notify": { "channels": ["discord"], "muted": true } });
+    assert!(router.targets(&triggered("user123", Some(muted))).is_empty());
+}
+
+#[test]
+fn test_public_addresses() {
+    use std::net::IpAddr;
+
+    use trade_alerts::notify::webhook::is_public_address;
+
+    let public = |ip: &str| is_public_address(&ip.parse::<IpAddr>().unwrap());
+    assert!(public("93.184.216.34"));
+    assert!(public("2606:2800:220:1::1"));
+    for internal in [
+        "127.0.0.1", "10.0.0.1", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0",
+        "::1", "::", "fd00::1", "fe80::1", "::ffff:127.0.0.1",
+    ] {
+        assert!(!public(internal), "{} is not public", internal);
+    }
+}
+
+#[tokio::test]
+async fn test_metadata_webhooks_must_be_public() {
+    use trade_alerts::errors::NotifyError;
+    use trade_alerts::notify::webhook::resolve_public_url;
+
+    for url in ["http://127.0.0.1:8080/hook", "http://[::1]/hook", "http://169.254.169.254/latest/meta-data", "http://localhost/hook"] {
+        assert!(
+            matches!(resolve_public_url(url).await, Err(NotifyError::ConfigurationError(_))),
+            "{} must be rejected",
+            url
+        );
+    }
+    assert!(matches!(resolve_public_url("file:///etc/passwd").await, Err(NotifyError::ConfigurationError(_))));
+    assert!(matches!(resolve_public_url("not a url").await, Err(NotifyError::ConfigurationError(_))));
+    assert_eq!(resolve_public_url("https://93.184.216.34/hook").await.unwrap().addrs[0].port(), 443);
+
+    let router = NotificationRouter::new();
+    let hook = json!({ "notify": { "webhook_url": "http://127.0.0.1:9/hook" } });
+    let deliveries = router.route(&triggered("user123", Some(hook))).await;
+    assert_eq!(deliveries.len(), 1);
+    assert!(deliveries[0].error.as_deref().unwrap().contains("non-public"), "{:?}", deliveries[0].error);
+}
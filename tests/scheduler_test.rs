@@ -0,0 +1,223 @@
+#![cfg(feature = "fixtures")]
+
+use std::time::Duration;
+
+use trade_alerts::{AlertScheduler, ScheduleConfig};
+use trade_alerts::scheduler::MIN_SCHEDULE_TICK;
+use trade_alerts::data::MockProvider;
+use trade_alerts::errors::ConfigError;
+use trade_alerts::fixtures::{FixtureServer, alert_rows, table_config};
+use trade_alerts::market_hours::AssetClass;
+
+#[tokio::test]
+async fn test_scheduler_runs_until_cancelled() {
+    let server = FixtureServer::start().await;
+    // Triggered alerts are deleted by their id
+    let rows = alert_rows().into_iter().zip(1..).map(|(mut row, id)| {
+        row["id"] = id.into();
+        row
+    });
+    server.insert_rows("alerts", rows.collect());
+    let provider = MockProvider::new()
+        .with_ramp("EUR/USD", 1.08, 1.12, 3)
+        .with_price("GBP/USD", 1.26)
+        .with_price("AAPL", 195.0);
+
+    let scheduler = AlertScheduler::new(provider, server.supabase(), table_config("alerts"))
+        .with_interval(Duration::from_millis(20));
+    let token = scheduler.cancellation_token();
+    let handle = scheduler.run().unwrap();
+
+    let triggered = async {
+        while server.rows("alerts").iter().any(|row| row["hash"] == "xlx-eurusd") {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(10), triggered).await.expect("EUR/USD should trigger and be deleted");
+    assert_eq!(server.rows("alerts").len(), 2);
+
+    token.cancel();
+    let summary = tokio::time::timeout(Duration::from_secs(10), handle.wait()).await.expect("Cancelling should stop the task");
+    assert!(summary.status.cycles >= 1);
+    assert_eq!(summary.status.next_run_at, None);
+}
+
+#[tokio::test]
+async fn test_shutdown_drains_cycle_in_flight() {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use tokio::sync::Notify;
+    use trade_alerts::TriggeredAlert;
+    use trade_alerts::errors::NotifyError;
+    use trade_alerts::notify::Notifier;
+
+    /// Signals when a delivery starts, then takes a while.
+    struct SlowNotifier(Arc<Notify>);
+
+    #[async_trait]
+    impl Notifier for SlowNotifier {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        async fn notify(&self, _alert: &TriggeredAlert) -> Result<(), NotifyError> {
+            self.0.notify_one();
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            Ok(())
+        }
+    }
+
+    let server = FixtureServer::start().await;
+    let rows = alert_rows().into_iter().zip(1..).map(|(mut row, id)| {
+        row["id"] = id.into();
+        row
+    });
+    server.insert_rows("alerts", rows.collect());
+    let provider = MockProvider::new().with_price("EUR/USD", 1.12).with_price("GBP/USD", 1.26).with_price("AAPL", 195.0);
+    let delivering = Arc::new(Notify::new());
+
+    let handle = AlertScheduler::new(provider, server.supabase(), table_config("alerts"))
+        .with_interval(Duration::from_secs(60))
+        .with_notifier(SlowNotifier(delivering.clone()))
+        .run()
+        .unwrap();
+    tokio::time::timeout(Duration::from_secs(10), delivering.notified()).await.expect("The first cycle should notify");
+
+    let summary = handle.shutdown().await;
+    assert!(summary.cycle_in_flight);
+    assert_eq!(summary.drained, vec!["xlx-eurusd".to_string()]);
+    assert!(summary.waited >= Duration::from_millis(100), "Shutdown should wait for the delivery");
+    assert_eq!(summary.status.cycles, 1);
+    assert!(!server.rows("alerts").iter().any(|row| row["hash"] == "xlx-eurusd"), "The cleanup should finish too");
+}
+
+#[tokio::test]
+async fn test_scheduler_rejects_zero_interval() {
+    let server = FixtureServer::start().await;
+    let scheduler = AlertScheduler::new(MockProvider::new(), server.supabase(), table_config("alerts"))
+        .with_interval(Duration::ZERO);
+    assert!(matches!(scheduler.run(), Err(ConfigError::InvalidField { .. })));
+}
+
+#[test]
+fn test_schedule_config() {
+    let schedule = ScheduleConfig::new(Duration::from_secs(30))
+        .with_asset_class(AssetClass::Crypto, Duration::from_secs(2))
+        .with_asset_class(AssetClass::Forex, Duration::from_secs(5))
+        .with_symbol("eurusd", Duration::from_secs(1));
+
+    assert_eq!(schedule.interval_for("BTC/USD"), Duration::from_secs(2));
+    assert_eq!(schedule.interval_for("GBP/USD"), Duration::from_secs(5));
+    assert_eq!(schedule.interval_for("EUR/USD"), Duration::from_secs(1));
+    assert_eq!(schedule.interval_for("AAPL"), Duration::from_secs(30));
+    assert_eq!(schedule.tick(), Duration::from_secs(1));
+    assert_eq!(schedule.clone().with_symbol("eurusd", Duration::from_millis(2500)).tick(), Duration::from_millis(500));
+    // Unrelated intervals don't collapse the tick to a millisecond
+    let unrelated = ScheduleConfig::new(Duration::from_millis(1000)).with_symbol("eurusd", Duration::from_millis(1001));
+    assert_eq!(unrelated.tick(), MIN_SCHEDULE_TICK);
+    assert_eq!(ScheduleConfig::new(Duration::from_millis(100)).tick(), Duration::from_millis(100));
+
+    let groups = schedule.groups(["BTC/USD", "ETH/USD", "AAPL"].map(String::from));
+    assert_eq!(groups[&Duration::from_secs(2)], vec!["BTC/USD".to_string(), "ETH/USD".to_string()]);
+    assert_eq!(groups[&Duration::from_secs(30)], vec!["AAPL".to_string()]);
+}
+
+#[tokio::test]
+async fn test_schedule_only_fetches_due_groups() {
+    let config = table_config("alerts");
+    let server = FixtureServer::start().await;
+    server.insert_rows("alerts", alert_rows());
+    for (symbol, price) in [("EUR/USD", 1.08), ("GBP/USD", 1.27), ("AAPL", 190.0)] {
+        server.set_price(symbol, price);
+    }
+    let schedule = ScheduleConfig::new(Duration::from_secs(3600)).with_asset_class(AssetClass::Forex, Duration::from_secs(1));
+    let api = server.xylex_api().with_schedule(schedule);
+    let fetched = |symbol: &str| server.requests().iter().filter(|request| request.starts_with("GET /price") && request.contains(symbol)).count();
+
+    api.check_alerts_with(&api, &server.supabase(), &config).await.unwrap();
+    assert_eq!((fetched("EUR"), fetched("AAPL")), (1, 1), "Every group is due on the first check");
+
+    api.check_alerts_with(&api, &server.supabase(), &config).await.unwrap();
+    assert_eq!((fetched("EUR"), fetched("AAPL")), (1, 1), "No group is due yet");
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    api.check_alerts_with(&api, &server.supabase(), &config).await.unwrap();
+    assert_eq!((fetched("EUR"), fetched("GBP"), fetched("AAPL")), (2, 2, 1), "Only FX is due again");
+}
+
+#[tokio::test]
+async fn test_failed_groups_stay_due() {
+    let config = table_config("alerts");
+    let server = FixtureServer::start().await;
+    server.insert_rows("alerts", alert_rows());
+    // EUR/USD has no quote until its price is set
+    for (symbol, price) in [("GBP/USD", 1.27), ("AAPL", 190.0)] {
+        server.set_price(symbol, price);
+    }
+    let schedule = ScheduleConfig::new(Duration::from_secs(3600)).with_symbol("EUR/USD", Duration::from_secs(1800));
+    let api = server.xylex_api().with_schedule(schedule);
+    let fetched = |symbol: &str| server.requests().iter().filter(|request| request.starts_with("GET /price") && request.contains(symbol)).count();
+
+    assert!(api.check_alerts_with(&api, &server.supabase(), &config).await.is_err());
+    server.set_price("EUR/USD", 1.08);
+    api.check_alerts_with(&api, &server.supabase(), &config).await.unwrap();
+    assert_eq!(fetched("EUR"), 2, "The group of the failed fetch is fetched again");
+
+    api.check_alerts_with(&api, &server.supabase(), &config).await.unwrap();
+    assert_eq!(fetched("EUR"), 2, "No group is due once fetched");
+}
+
+#[tokio::test]
+async fn test_stop_flushes_pending_writes() {
+    use async_trait::async_trait;
+    use trade_alerts::errors::NotifyError;
+    use trade_alerts::notify::Notifier;
+    use trade_alerts::{TradeAlerts, TriggeredAlert};
+
+    /// Accepts every delivery.
+    struct Accepting;
+
+    #[async_trait]
+    impl Notifier for Accepting {
+        fn name(&self) -> &str {
+            "accepting"
+        }
+
+        async fn notify(&self, _alert: &TriggeredAlert) -> Result<(), NotifyError> {
+            Ok(())
+        }
+    }
+
+    let server = FixtureServer::start().await;
+    let rows = alert_rows().into_iter().zip(1..).map(|(mut row, id)| {
+        row["id"] = id.into();
+        row
+    });
+    server.insert_rows("alerts", rows.collect());
+    let intents = std::env::temp_dir().join(format!("trade_alerts_flush_{}.jsonl", std::process::id()));
+    std::fs::remove_file(&intents).ok();
+
+    let handle = TradeAlerts::builder()
+        .with_provider(MockProvider::new().with_price("EUR/USD", 1.12).with_price("GBP/USD", 1.26).with_price("AAPL", 195.0))
+        .with_supabase(server.supabase())
+        .with_table_config(table_config("alerts"))
+        .with_interval(Duration::from_secs(60))
+        .with_notifier(Accepting)
+        .with_intent_log(&intents)
+        .build()
+        .unwrap()
+        .start()
+        .await;
+    let delivered = async {
+        while server.rows("alerts").iter().any(|row| row["hash"] == "xlx-eurusd") {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(10), delivered).await.expect("EUR/USD should trigger and be deleted");
+    assert!(std::fs::read_to_string(&intents).unwrap().lines().count() > 0, "The delivery is logged");
+
+    handle.stop().await;
+    assert_eq!(std::fs::read_to_string(&intents).unwrap().lines().count(), 0, "The completed intents are compacted away");
+    std::fs::remove_file(&intents).ok();
+}
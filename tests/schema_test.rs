@@ -0,0 +1,44 @@
+#![cfg(feature = "schema")]
+
+use serde_json::{Value, json};
+
+use trade_alerts::events::TRIGGERED_ALERT_SCHEMA_VERSION;
+use trade_alerts::fixtures::triggered_alert;
+use trade_alerts::notify::PayloadFormat;
+use trade_alerts::schema::{payload_schema, triggered_alert_event_schema, user_triggers_event_schema};
+use trade_alerts::{Condition, TriggeredAlert};
+
+fn triggered() -> TriggeredAlert {
+    TriggeredAlert {
+        trigger_price: 1.1002,
+        condition: Condition::SpreadAbove(3.0),
+        ..triggered_alert("xlx-a-1234", "eur/usd", 1.1)
+    }
+}
+
+/// Asserts every field of `payload` is described by `schema`.
+fn assert_covers(schema: &Value, payload: &Value) {
+    for key in payload.as_object().expect("Payloads are objects").keys() {
+        assert!(schema["properties"].get(key).is_some(), "{} is missing from the schema", key);
+    }
+}
+
+#[test]
+fn test_event_schema() {
+    let schema = triggered_alert_event_schema();
+    assert_eq!(schema["properties"]["schema_version"]["const"], json!(TRIGGERED_ALERT_SCHEMA_VERSION));
+    assert!(schema["required"].as_array().unwrap().contains(&json!("schema_version")));
+    assert!(schema["definitions"].get("Condition").is_some());
+    assert_covers(&schema, &triggered().to_event());
+
+    let grouped = user_triggers_event_schema();
+    assert_eq!(grouped["properties"]["alerts"]["items"]["title"], "TriggeredAlertEvent");
+    assert!(grouped["definitions"].get("Condition").is_some());
+}
+
+#[test]
+fn test_payload_schemas() {
+    for format in [PayloadFormat::Event, PayloadFormat::Flat, PayloadFormat::Ifttt] {
+        assert_covers(&payload_schema(format), &format.render(&triggered()));
+    }
+}
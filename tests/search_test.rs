@@ -0,0 +1,42 @@
+#![cfg(feature = "fixtures")]
+
+use serde_json::json;
+
+use trade_alerts::data::{BinanceProvider, PriceProvider, XylexApi};
+use trade_alerts::fixtures::FixtureServer;
+
+#[tokio::test]
+async fn test_xylex_symbol_search() {
+    let api = XylexApi::new("key".to_string(), "http://127.0.0.1:9/price".to_string());
+    assert_eq!(api.search_symbols("AA").await.unwrap(), None, "Search needs a search endpoint");
+
+    let server = FixtureServer::start().await;
+    server.queue_responses("/symbol_search", vec![(200, json!({ "data": [
+        { "symbol": "AAPL", "instrument_name": "Apple Inc", "exchange": "NASDAQ", "instrument_type": "Common Stock", "currency": "USD" },
+        { "symbol": "AAL", "instrument_name": "American Airlines Group Inc", "exchange": "NASDAQ", "instrument_type": "Common Stock", "currency": "" },
+        { "instrument_name": "Without a symbol" },
+    ]}))]);
+    let api = api.with_search_endpoint(format!("{}/symbol_search", server.url()));
+
+    let found = api.search_symbols("AA").await.unwrap().expect("Search should be supported");
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].symbol, "AAPL");
+    assert_eq!(found[0].name.as_deref(), Some("Apple Inc"));
+    assert_eq!(found[1].currency, None);
+}
+
+#[tokio::test]
+async fn test_binance_symbol_search() {
+    let server = FixtureServer::start().await;
+    server.queue_responses("/api/v3/exchangeInfo", vec![(200, json!({ "symbols": [
+        { "symbol": "WBTCBTC", "status": "TRADING", "baseAsset": "WBTC", "quoteAsset": "BTC" },
+        { "symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT" },
+        { "symbol": "BTCEUR", "status": "BREAK", "baseAsset": "BTC", "quoteAsset": "EUR" },
+        { "symbol": "ETHUSDT", "status": "TRADING", "baseAsset": "ETH", "quoteAsset": "USDT" },
+    ]}))]);
+    let provider = BinanceProvider::new().with_endpoint(server.url());
+
+    let found = provider.search_symbols("btc").await.unwrap().expect("Search should be supported");
+    assert_eq!(found.iter().map(|instrument| instrument.symbol.as_str()).collect::<Vec<_>>(), vec!["BTC/USDT", "WBTC/BTC"]);
+    assert_eq!(found[0].currency.as_deref(), Some("USDT"));
+}
@@ -0,0 +1,204 @@
+#![cfg(feature = "server")]
+
+use std::sync::Arc;
+
+use ed25519_dalek::{Signer, SigningKey};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use trade_alerts::data::XylexApi;
+use trade_alerts::db::{Supabase, TableConfig};
+use trade_alerts::server::CommandServer;
+use trade_alerts::server::handlers::verify_slack_signature;
+
+#[test]
+fn test_verify_slack_signature() {
+    let body = b"command=%2Falert&text=eurusd+above+1.10&user_id=U123";
+    let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+    mac.update(b"v0:1700000000:");
+    mac.update(body);
+    let signature = format!("v0={}", hex::encode(mac.finalize().into_bytes()));
+
+    assert!(verify_slack_signature("secret", "1700000000", body, &signature, 1700000010));
+    assert!(!verify_slack_signature("other", "1700000000", body, &signature, 1700000010));
+    assert!(!verify_slack_signature("secret", "1700000000", b"tampered", &signature, 1700000010));
+    assert!(!verify_slack_signature("secret", "1700000000", body, &signature, 1700001000), "Replays should be rejected");
+}
+
+/// Serves the command routes of `server` on a local port.
+async fn serve(server: CommandServer) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, server.router()).await.unwrap() });
+    base
+}
+
+fn command_server() -> CommandServer {
+    let provider = XylexApi::new("key".to_string(), "http://127.0.0.1:9".to_string());
+    CommandServer::new(Supabase::new("key".to_string(), "http://127.0.0.1:9".to_string()), TableConfig::default(), Arc::new(provider))
+}
+
+#[tokio::test]
+async fn test_unsigned_commands_rejected() {
+    let client = reqwest::Client::new();
+    let form = "command=%2Falert&text=list&user_id=U123";
+
+    // Without a secret or key the routes are not mounted at all
+    let base = serve(command_server()).await;
+    for route in ["slack", "discord"] {
+        let response = client.post(format!("{}/{}", base, route)).body(form).send().await.unwrap();
+        assert_eq!(response.status(), 404, "/{} should not be mounted", route);
+    }
+
+    let key = SigningKey::from_bytes(&[7; 32]);
+    let server = command_server()
+        .with_slack_signing_secret("secret".to_string())
+        .with_discord_public_key(&hex::encode(key.verifying_key().to_bytes()))
+        .unwrap();
+    assert!(command_server().with_discord_public_key("not hex").is_err());
+    let base = serve(server).await;
+
+    let response = client.post(format!("{}/slack", base)).body(form).send().await.unwrap();
+    assert_eq!(response.status(), 401, "Unsigned Slack requests are rejected");
+
+    let ping = br#"{"type":1}"#;
+    let response = client.post(format!("{}/discord", base)).body(ping.to_vec()).send().await.unwrap();
+    assert_eq!(response.status(), 401, "Unsigned Discord interactions are rejected");
+
+    let other = SigningKey::from_bytes(&[8; 32]);
+    let sign = |key: &SigningKey, body: &[u8]| hex::encode(key.sign(&[b"1700000000".as_slice(), body].concat()).to_bytes());
+    let response = client
+        .post(format!("{}/discord", base))
+        .header("X-Signature-Timestamp", "1700000000")
+        .header("X-Signature-Ed25519", sign(&other, ping))
+        .body(ping.to_vec())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 401, "Interactions signed by another key are rejected");
+
+    let response = client
+        .post(format!("{}/discord", base))
+        .header("X-Signature-Timestamp", "1700000000")
+        .header("X-Signature-Ed25519", sign(&key, ping))
+        .body(ping.to_vec())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.json::<serde_json::Value>().await.unwrap()["type"], 1);
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_action_links() {
+    use chrono::{Duration, Utc};
+    use serde_json::json;
+
+    use trade_alerts::TriggeredAlert;
+    use trade_alerts::actions::{ActionSigner, AlertAction};
+    use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config, triggered_alert};
+
+    let fixtures = FixtureServer::start().await;
+    let config = table_config("alerts");
+    let issued_at = Utc::now();
+    let row = |id: i64, hash: &str, symbol: &str, user_id: &str, created_at| {
+        let mut row = AlertFixture::new(hash, symbol, 1.1).user_id(user_id).row(&config);
+        row["id"] = json!(id);
+        row["created_at"] = json!(created_at);
+        row
+    };
+    let old = (issued_at - Duration::hours(1)).to_rfc3339();
+    let new = (issued_at + Duration::minutes(1)).to_rfc3339();
+    fixtures.insert_rows("alerts", vec![
+        row(1, "xlx-old", "EUR/USD", "user-1", old.clone()),
+        row(2, "xlx-new", "EUR/USD", "user-1", new),
+        row(3, "xlx-other", "EUR/USD", "user-2", old.clone()),
+        row(4, "xlx-gbp", "GBP/USD", "user-1", old.clone()),
+        row(5, "xlx-moved", "EUR/USD", "user-2", old.clone()),
+    ]);
+    // A malformed alert of another user on the same symbol
+    let mut malformed = row(6, "xlx-malformed", "EURUSD", "user-3", old);
+    malformed["price_level"] = json!("not a number");
+    fixtures.insert_rows("alerts", vec![malformed]);
+
+    let signer = ActionSigner::new("secret");
+    let server = CommandServer::new(fixtures.supabase(), config.clone(), Arc::new(fixtures.xylex_api()))
+        .with_action_signer(signer.clone());
+    let base = serve(server).await;
+    let client = reqwest::Client::new();
+    let token = |action, hash: &str| {
+        let alert = TriggeredAlert {
+            user_id: "user-1".to_string(),
+            trigger_price: 1.1002,
+            triggered_at: issued_at,
+            ..triggered_alert(hash, "eurusd", 1.1)
+        };
+        signer.token(action, &alert, issued_at)
+    };
+    let post = |token: String| {
+        let client = client.clone();
+        let url = format!("{}/actions/{}", base, token);
+        async move {
+            let response = client.post(url).send().await.unwrap();
+            (response.status().as_u16(), response.text().await.unwrap())
+        }
+    };
+
+    // Opening the link only shows the confirmation form
+    let mute = token(AlertAction::MuteSymbol, "xlx-old");
+    let form = client.get(format!("{}/actions/{}", base, mute)).send().await.unwrap().text().await.unwrap();
+    assert!(form.contains("<form method=\"post\">") && form.contains("Mute EURUSD"));
+    assert!(fixtures.rows("alerts").iter().all(|row| row["metadata"].get("notify").is_none()));
+
+    // A failed action does not use up the link
+    fixtures.queue_responses("/rest/v1/alerts", vec![(503, json!({ "message": "unavailable" }))]);
+    let (status, reply) = post(mute.clone()).await;
+    assert_eq!(status, 502);
+    assert!(reply.starts_with("Failed to fetch alerts"), "{}", reply);
+
+    let (status, reply) = post(mute.clone()).await;
+    assert_eq!((status, reply.as_str()), (200, "Muted 1 alert(s) on EURUSD"));
+    let rows = fixtures.rows("alerts");
+    assert_eq!(rows.len(), 6, "Muting keeps the alerts");
+    let muted: Vec<&str> = rows
+        .iter()
+        .filter(|row| row["metadata"]["notify"]["muted"] == json!(true))
+        .map(|row| row["hash"].as_str().unwrap())
+        .collect();
+    assert_eq!(muted, vec!["xlx-old"], "Only older alerts of the owner on the symbol are muted");
+    assert_eq!(post(mute).await.0, 409, "Tokens run once");
+
+    let (status, reply) = post(token(AlertAction::DeleteAlert, "xlx-moved")).await;
+    assert_eq!((status, reply.as_str()), (200, "Alert `xlx-moved` is not yours"));
+    assert_eq!(fixtures.rows("alerts").len(), 6);
+
+    let (_, reply) = post(token(AlertAction::DeleteAlert, "xlx-old")).await;
+    assert_eq!(reply, "Deleted alert `xlx-old`");
+    assert!(fixtures.rows("alerts").iter().all(|row| row["hash"] != "xlx-old"));
+    let (_, reply) = post(token(AlertAction::DeleteAlert, "xlx-gone")).await;
+    assert_eq!(reply, "Alert `xlx-gone` was already deleted");
+
+    assert_eq!(post("not-a-token".to_string()).await.0, 401);
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_create_command() {
+    use trade_alerts::fixtures::{FixtureServer, table_config};
+
+    // Alerts are checked against the server's provider, not one configured from the environment
+    let fixtures = FixtureServer::start().await;
+    let server = CommandServer::new(fixtures.supabase(), table_config("alerts"), Arc::new(fixtures.xylex_api()));
+    fixtures.set_price("eurusd", 1.08);
+
+    let reply = server.execute("slack:U123", "alert eurusd above 1.10").await;
+    assert!(reply.starts_with("Created alert"), "{}", reply);
+    let rows = fixtures.rows("alerts");
+    assert_eq!((rows.len(), rows[0]["user_id"].as_str()), (1, Some("slack:U123")));
+
+    // Provider errors are replied instead of failing the request
+    let reply = server.execute("slack:U123", "alert gbpusd above 1.30").await;
+    assert!(reply.starts_with("Failed to create alert"), "{}", reply);
+    assert_eq!(fixtures.rows("alerts").len(), 1);
+}
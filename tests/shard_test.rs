@@ -0,0 +1,104 @@
+use trade_alerts::shard::{DEFAULT_VIRTUAL_NODES, HashRing, Shard};
+
+fn symbols() -> Vec<String> {
+    (0..200).map(|i| format!("SYM{}/USD", i)).collect()
+}
+
+#[test]
+fn test_hash_ring() {
+    assert_eq!(HashRing::new(Vec::<String>::new(), DEFAULT_VIRTUAL_NODES).owner("EUR/USD"), None);
+
+    let ring = HashRing::new(["a", "b", "c"], DEFAULT_VIRTUAL_NODES);
+    assert_eq!(ring, HashRing::new(["c", "a", "b", "a"], DEFAULT_VIRTUAL_NODES), "Every worker builds the same ring");
+    assert_eq!(ring.owner("eur/usd"), ring.owner("EURUSD"), "Symbols are hashed by their canonical form");
+    for worker in ["a", "b", "c"] {
+        let shard = Shard::new(ring.clone(), worker);
+        let owned = symbols().iter().filter(|symbol| shard.owns(symbol)).count();
+        assert!(owned > 20, "{} only owns {} symbols", worker, owned);
+    }
+
+    // A worker joining only takes symbols over, the others keep theirs
+    let joined = HashRing::new(["a", "b", "c", "d"], DEFAULT_VIRTUAL_NODES);
+    let mut moved = 0;
+    for symbol in symbols() {
+        let (before, after) = (ring.owner(&symbol).unwrap(), joined.owner(&symbol).unwrap());
+        if before != after {
+            assert_eq!(after, "d");
+            moved += 1;
+        }
+    }
+    assert!(moved > 0 && moved < symbols().len() / 2, "{} symbols moved", moved);
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_sharded_workers() {
+    use std::time::Duration;
+
+    use trade_alerts::TradeAlerts;
+    use trade_alerts::data::MockProvider;
+    use trade_alerts::db::{LeaseTable, ShardTable};
+    use trade_alerts::errors::ConfigError;
+    use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+
+    /// Waits until `condition` holds, failing after a few seconds.
+    async fn wait_for(condition: impl Fn() -> bool, what: &str) {
+        let waiting = async {
+            while !condition() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(10), waiting).await.unwrap_or_else(|_| panic!("{}", what));
+    }
+
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    let symbols: Vec<String> = (0..6).map(|i| format!("SYM{}/USD", i)).collect();
+    let mut provider = MockProvider::new();
+    for (i, symbol) in symbols.iter().enumerate() {
+        provider = provider.with_price(symbol, 1.0);
+        let row = AlertFixture::new(&format!("xlx-{}", i), symbol, 2.0).latest_price(1.0).row(&config);
+        server.insert_rows("alerts", vec![row]);
+    }
+    let build = |instance_id: &str| {
+        TradeAlerts::builder()
+            .with_provider(provider.clone())
+            .with_supabase(server.supabase())
+            .with_table_config(config.clone())
+            .with_interval(Duration::from_millis(20))
+            .with_sharding(LeaseTable::default(), ShardTable::default(), instance_id)
+            .with_lease_ttl(Duration::from_secs(30))
+    };
+    let leading = build("a").with_leader_lease(LeaseTable::default(), "a").build();
+    assert!(matches!(leading, Err(ConfigError::InvalidField { field, .. }) if field == "sharding"));
+
+    let a = build("a").build().unwrap().start().await;
+    wait_for(|| a.status().shard_symbols == Some(symbols.len()), "The first worker should check every symbol").await;
+    wait_for(|| a.status().cycles > 3, "The first worker should run cycles").await;
+    let requests = server.requests();
+    let full_scans = requests.iter().filter(|request| request.starts_with("GET /rest/v1/alerts") && !request.contains("symbol=in.")).count();
+    assert!(requests.iter().any(|request| request.contains("symbol=in.")), "Owned symbols are pushed into the query");
+    assert_eq!(full_scans, 1, "The symbols are fetched once, not on every tick: {:?}", requests);
+    let b = build("b").build().unwrap().start().await;
+    wait_for(
+        || a.status().shard_workers == Some(2) && b.status().shard_workers == Some(2),
+        "Both workers should see each other",
+    )
+    .await;
+
+    let ring = HashRing::new(["a", "b"], DEFAULT_VIRTUAL_NODES);
+    let owned_by = |worker: &str| symbols.iter().filter(|symbol| ring.owner(symbol) == Some(worker)).count();
+    assert_eq!(a.status().shard_symbols, Some(owned_by("a")));
+    assert_eq!(b.status().shard_symbols, Some(owned_by("b")));
+    let assignments = server.supabase().fetch_shard_assignments(&ShardTable::default()).await.unwrap();
+    assert_eq!(assignments.len(), symbols.len(), "Every symbol is recorded once");
+    for assignment in &assignments {
+        assert_eq!(Some(assignment.worker.as_str()), ring.owner(&assignment.symbol));
+    }
+
+    b.stop().await;
+    wait_for(|| a.status().shard_symbols == Some(symbols.len()), "The remaining worker should take the symbols back").await;
+    let assignments = server.supabase().fetch_shard_assignments(&ShardTable::default()).await.unwrap();
+    assert!(assignments.iter().all(|assignment| assignment.worker == "a"));
+    a.stop().await;
+}
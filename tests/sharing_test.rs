@@ -0,0 +1,158 @@
+use trade_alerts::db::SharedAlertTable;
+
+#[test]
+fn test_default_shared_alert_table() {
+    let shares = SharedAlertTable::default();
+
+    assert_eq!(shares.tablename, "alert_shares");
+    assert_eq!(shares.hash_column_name, "alert_hash");
+    assert_eq!(shares.user_id_column_name, "user_id");
+}
+
+#[cfg(feature = "fixtures")]
+mod fixtures {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use serde_json::{Value, json};
+    use trade_alerts::data::MockProvider;
+    use trade_alerts::db::{SharedAlertTable, TableConfig};
+    use trade_alerts::errors::NotifyError;
+    use trade_alerts::fixtures::{FixtureServer, alert_rows, table_config, triggered_alert};
+    use trade_alerts::notify::Notifier;
+    use trade_alerts::timeout::Timeouts;
+    use trade_alerts::{TradeAlerts, TriggeredAlert};
+
+    fn share(hash: &str, user_id: &str) -> Value {
+        json!({ "alert_hash": hash, "user_id": user_id })
+    }
+
+    fn triggered(hash: &str, user_id: &str) -> TriggeredAlert {
+        TriggeredAlert {
+            user_id: user_id.to_string(),
+            trigger_price: 1.12,
+            ..triggered_alert(hash, "EUR/USD", 1.10)
+        }
+    }
+
+    /// Records the user of every delivered alert.
+    #[derive(Clone, Default)]
+    struct Recording(Arc<Mutex<Vec<String>>>);
+
+    #[async_trait]
+    impl Notifier for Recording {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn notify(&self, alert: &TriggeredAlert) -> Result<(), NotifyError> {
+            self.0.lock().unwrap().push(alert.user_id.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_share_and_unshare_alert() {
+        let server = FixtureServer::start().await;
+        let supabase = server.supabase();
+        let shares = SharedAlertTable::default();
+
+        supabase.share_alert("xlx-eurusd", "user456", &shares).await.unwrap();
+        supabase.share_alert("xlx-eurusd", "user789", &shares).await.unwrap();
+        assert_eq!(supabase.fetch_alert_members("xlx-eurusd", &shares).await.unwrap(), vec!["user456", "user789"]);
+
+        supabase.unshare_alert("xlx-eurusd", "user456", &shares).await.unwrap();
+        assert_eq!(supabase.fetch_alert_members("xlx-eurusd", &shares).await.unwrap(), vec!["user789"]);
+    }
+
+    #[tokio::test]
+    async fn test_sharing_times_out() {
+        let server = FixtureServer::start().await;
+        server.hang("/rest/v1/alert_shares");
+        let supabase = server.supabase().with_timeouts(Timeouts::new(Duration::from_millis(100), Duration::from_millis(100)));
+
+        let started = std::time::Instant::now();
+        let error = supabase.share_alert("xlx-eurusd", "user456", &SharedAlertTable::default()).await.unwrap_err();
+        assert!(error.to_string().contains("timed out"), "{}", error);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_shared_alerts() {
+        let server = FixtureServer::start().await;
+        server.set_rows("alerts", alert_rows());
+        server.set_rows("alert_shares", vec![share("xlx-eurusd", "user456"), share("xlx-gbpusd", "user789")]);
+
+        let alerts = server
+            .supabase()
+            .fetch_shared_alerts("user456", &SharedAlertTable::default(), &table_config("alerts"))
+            .await
+            .unwrap();
+
+        assert_eq!(alerts.iter().map(|alert| alert.hash.as_str()).collect::<Vec<_>>(), vec!["xlx-eurusd"]);
+        assert_eq!(alerts[0].user_id, "user123");
+    }
+
+    #[tokio::test]
+    async fn test_expand_shared_triggers() {
+        let server = FixtureServer::start().await;
+        server.set_rows(
+            "alert_shares",
+            vec![share("xlx-eurusd", "user456"), share("xlx-eurusd", "user123"), share("xlx-other", "user789")],
+        );
+
+        let expanded = server
+            .supabase()
+            .expand_shared_triggers(vec![triggered("xlx-eurusd", "user123")], &SharedAlertTable::default())
+            .await
+            .unwrap();
+
+        // The owner isn't notified twice, and shares of other alerts don't matter
+        let users: Vec<&str> = expanded.iter().map(|alert| alert.user_id.as_str()).collect();
+        assert_eq!(users, vec!["user123", "user456"]);
+        assert!(expanded.iter().all(|alert| alert.hash == "xlx-eurusd"));
+    }
+
+    #[tokio::test]
+    async fn test_transfer_alert() {
+        let server = FixtureServer::start().await;
+        server.set_rows("alerts", alert_rows());
+        let config: TableConfig = table_config("alerts");
+
+        server.supabase().transfer_alert("xlx-eurusd", "user456", &config).await.unwrap();
+
+        let rows = server.rows("alerts");
+        let row = rows.iter().find(|row| row["hash"] == "xlx-eurusd").unwrap();
+        assert_eq!(row["user_id"], "user456");
+    }
+
+    #[tokio::test]
+    async fn test_triggered_alerts_notify_members() {
+        let server = FixtureServer::start().await;
+        let rows = alert_rows().into_iter().zip(1..).map(|(mut row, id)| {
+            row["id"] = id.into();
+            row
+        });
+        server.set_rows("alerts", rows.collect());
+        server.set_rows("alert_shares", vec![share("xlx-eurusd", "user456"), share("xlx-eurusd", "user789")]);
+        let recording = Recording::default();
+
+        let alerts = TradeAlerts::builder()
+            .with_provider(MockProvider::new().with_price("EUR/USD", 1.12).with_price("GBP/USD", 1.26).with_price("AAPL", 195.0))
+            .with_supabase(server.supabase())
+            .with_table_config(table_config("alerts"))
+            .with_interval(Duration::from_secs(60))
+            .with_notifier(recording.clone())
+            .with_shared_alerts(SharedAlertTable::default())
+            .build()
+            .unwrap();
+        alerts.run_once().await.unwrap();
+
+        assert_eq!(*recording.0.lock().unwrap(), vec!["user123", "user456", "user789"]);
+        // The alert is deleted once, not once per member
+        assert!(!server.rows("alerts").iter().any(|row| row["hash"] == "xlx-eurusd"));
+        let deletes: usize = server.requests().iter().filter(|request| request.starts_with("DELETE /rest/v1/alerts")).count();
+        assert_eq!(deletes, 1);
+    }
+}
@@ -0,0 +1,51 @@
+use trade_alerts::Condition;
+use trade_alerts::data::Quote;
+
+fn quote(symbol: &str, bid: f64, ask: f64) -> Quote {
+    Quote { bid: Some(bid), ask: Some(ask), ..Quote::new(symbol.to_string(), (bid + ask) / 2.0) }
+}
+
+#[test]
+fn test_spread_in_pips() {
+    let eurusd = quote("EUR/USD", 1.0850, 1.0853);
+    assert!((eurusd.spread().unwrap() - 0.0003).abs() < 1e-9);
+    assert!((eurusd.spread_pips().unwrap() - 3.0).abs() < 1e-6);
+
+    let usdjpy = quote("USD/JPY", 150.10, 150.15);
+    assert_eq!(usdjpy.pip_size(), 0.01);
+    assert!((usdjpy.spread_pips().unwrap() - 5.0).abs() < 1e-6);
+
+    let no_book = Quote::new("EUR/USD".to_string(), 1.0850);
+    assert_eq!(no_book.spread(), None);
+    assert_eq!(no_book.spread_pips(), None);
+}
+
+#[test]
+fn test_spread_above_condition() {
+    let condition = Condition::SpreadAbove(2.5);
+
+    assert!(condition.is_met(1.10, "buy", &quote("EUR/USD", 1.0850, 1.0853)));
+    assert!(!condition.is_met(1.10, "buy", &quote("EUR/USD", 1.0850, 1.0852)));
+    assert!(!condition.is_met(1.10, "buy", &Quote::new("EUR/USD".to_string(), 1.0850)), "Quotes without a book never hold");
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_quotes_carry_bid_and_ask() {
+    use trade_alerts::fixtures::FixtureServer;
+
+    let server = FixtureServer::start().await;
+    server.set_price("EUR/USD", 1.08515);
+    server.set_book("EUR/USD", 1.0850, 1.0853);
+    server.set_price("GBP/USD", 1.27);
+    let api = server.xylex_api();
+
+    // The bid is sent as a string and the ask as a number
+    let quote = api.request_real_time_quote("EUR/USD").await.unwrap();
+    assert_eq!((quote.price, quote.bid, quote.ask), (1.08515, Some(1.0850), Some(1.0853)));
+    assert!(Condition::SpreadAbove(2.5).is_met(1.10, "buy", &quote));
+
+    let quote = api.request_real_time_quote("GBP/USD").await.unwrap();
+    assert_eq!((quote.bid, quote.ask), (None, None));
+    assert_eq!(api.request_real_time_price("GBP/USD").await.unwrap(), 1.27);
+}
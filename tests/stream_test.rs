@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::{StreamExt, stream};
+
+use trade_alerts::data::{LatestQuotes, PriceProvider, Quote, QuoteStream, ReconnectingProvider, StreamingProvider};
+use trade_alerts::errors::XylexApiError;
+use trade_alerts::retry::RetryPolicy;
+
+/// Streams one quote per subscription, priced by the subscription number, and fails every third subscription.
+struct FlakyFeed {
+    subscriptions: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl StreamingProvider for FlakyFeed {
+    async fn subscribe(&self, symbols: &[String]) -> Result<QuoteStream, XylexApiError> {
+        let subscription = self.subscriptions.fetch_add(1, Ordering::SeqCst) + 1;
+        if subscription.is_multiple_of(3) {
+            return Err(XylexApiError::NetworkError("connection refused".to_string()));
+        }
+        Ok(Box::pin(stream::iter(vec![Quote::new(symbols[0].clone(), subscription as f64)])))
+    }
+}
+
+#[tokio::test]
+async fn test_latest_quotes() {
+    let latest = LatestQuotes::new();
+    assert_eq!(latest.supported_symbols().await.unwrap(), Some(vec![]));
+    assert!(latest.fetch_price("eur/usd").await.is_err());
+
+    latest.record(Quote::new("eur/usd".to_string(), 1.1));
+    latest.record(Quote::new("eur/usd".to_string(), 1.2));
+    assert_eq!(latest.fetch_price("eur/usd").await.unwrap().price, 1.2);
+    assert_eq!(latest.supported_symbols().await.unwrap(), Some(vec!["eur/usd".to_string()]));
+}
+
+#[cfg(feature = "websocket")]
+#[tokio::test]
+async fn test_websocket_provider() {
+    use futures_util::SinkExt;
+    use tokio_tungstenite::tungstenite::Message;
+    use trade_alerts::data::WebSocketProvider;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (tcp, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(tcp).await.unwrap();
+        let subscribe = socket.next().await.unwrap().unwrap();
+        assert_eq!(subscribe, Message::Text(r#"{"subscribe":["eur/usd"]}"#.to_string()));
+        socket.send(Message::Text(r#"{"type":"heartbeat"}"#.to_string())).await.unwrap();
+        socket.send(Message::Text(r#"{"symbol":"eur/usd","price":"1.1","bid":1.0999}"#.to_string())).await.unwrap();
+        socket.send(Message::Text(r#"[{"symbol":"eur/usd","price":1.2}]"#.to_string())).await.unwrap();
+        socket.close(None).await.unwrap();
+    });
+
+    let provider = WebSocketProvider::new(format!("ws://{}", addr)).with_subscribe_message(r#"{"subscribe":{symbols}}"#);
+    let quotes: Vec<Quote> = provider
+        .subscribe(&["eur/usd".to_string()])
+        .await
+        .expect("Failed to subscribe")
+        .collect()
+        .await;
+
+    assert_eq!(quotes.iter().map(|quote| quote.price).collect::<Vec<_>>(), vec![1.1, 1.2]);
+    assert_eq!(quotes[0].bid, Some(1.0999));
+}
+
+#[tokio::test]
+async fn test_reconnecting_provider() {
+    let snapshot = LatestQuotes::new();
+    snapshot.record(Quote::new("eur/usd".to_string(), 0.5));
+
+    let subscriptions = Arc::new(AtomicUsize::new(0));
+    let provider = ReconnectingProvider::new(FlakyFeed { subscriptions: subscriptions.clone() })
+        .with_snapshot(snapshot)
+        .with_retry_policy(
+            RetryPolicy::default()
+                .with_max_attempts(2)
+                .with_backoff(Duration::from_millis(1), Duration::from_millis(5)),
+        );
+
+    let prices: Vec<f64> = provider
+        .subscribe(&["eur/usd".to_string()])
+        .await
+        .expect("Failed to subscribe")
+        .take(6)
+        .map(|quote| quote.price)
+        .collect()
+        .await;
+
+    // Every reconnect is followed by the snapshot, the third subscription failed and was retried
+    assert_eq!(prices, vec![1.0, 0.5, 2.0, 0.5, 4.0, 0.5]);
+    assert_eq!(subscriptions.load(Ordering::SeqCst), 5);
+}
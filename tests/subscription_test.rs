@@ -0,0 +1,56 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::{FutureExt, StreamExt, stream};
+
+use trade_alerts::data::{Quote, QuoteStream, StreamingProvider, SubscriptionManager};
+use trade_alerts::errors::XylexApiError;
+
+/// Records every subscription and streams a quote of each subscribed symbol.
+#[derive(Clone, Default)]
+struct RecordingFeed {
+    subscriptions: Arc<Mutex<Vec<Vec<String>>>>,
+}
+
+#[async_trait]
+impl StreamingProvider for RecordingFeed {
+    async fn subscribe(&self, symbols: &[String]) -> Result<QuoteStream, XylexApiError> {
+        if symbols.iter().any(|symbol| symbol == "refused") {
+            return Err(XylexApiError::InvalidSymbol("refused".to_string()));
+        }
+        self.subscriptions.lock().unwrap().push(symbols.to_vec());
+        Ok(Box::pin(stream::iter(symbols.iter().map(|symbol| Quote::new(symbol.clone(), 1.0)).collect::<Vec<_>>())))
+    }
+}
+
+#[tokio::test]
+async fn test_subscriptions_follow_the_alerts() {
+    let feed = RecordingFeed::default();
+    let manager = SubscriptionManager::new(feed.clone()).with_reconcile_interval(Duration::from_secs(5));
+    assert_eq!(manager.reconcile_interval, Duration::from_secs(5));
+
+    let change = manager.update(["eur/usd".to_string()]).await.unwrap().unwrap();
+    assert_eq!((change.added, change.removed), (vec!["eur/usd".to_string()], vec![]));
+    assert_eq!(change.quotes.collect::<Vec<_>>().await.len(), 1);
+
+    // Another spelling of a subscribed symbol is not a change
+    assert!(manager.update(["EURUSD".to_string()]).await.unwrap().is_none());
+
+    let change = manager.update(["EURUSD".to_string(), "gbp/usd".to_string()]).await.unwrap().unwrap();
+    assert_eq!((change.added, change.removed), (vec!["gbp/usd".to_string()], vec![]));
+    assert_eq!(manager.symbols(), vec!["EURUSD".to_string(), "gbp/usd".to_string()]);
+
+    // A refused subscription keeps the subscribed symbols
+    assert!(manager.update(["refused".to_string()]).await.is_err());
+    assert_eq!(manager.symbols().len(), 2);
+
+    let mut change = manager.update(Vec::new()).await.unwrap().unwrap();
+    assert_eq!(change.removed, vec!["EURUSD".to_string(), "gbp/usd".to_string()]);
+    assert!(change.quotes.next().now_or_never().is_none(), "An empty subscription should stay pending");
+
+    assert_eq!(
+        *feed.subscriptions.lock().unwrap(),
+        vec![vec!["eur/usd".to_string()], vec!["EURUSD".to_string(), "gbp/usd".to_string()]]
+    );
+}
@@ -0,0 +1,52 @@
+use trade_alerts::data::{LatestQuotes, Quote};
+use trade_alerts::errors::SymbolError;
+use trade_alerts::utils::symbol::{Symbol, SymbolFormat, same_symbol};
+use trade_alerts::{Alert, Condition, ConditionTree};
+
+#[test]
+fn test_symbol_formats_normalize() {
+    for raw in ["EURUSD", "eur/usd", "EUR-USD", " eur_usd ", "EUR:USD"] {
+        assert_eq!(Symbol::parse(raw).unwrap().as_str(), "EUR/USD", "{}", raw);
+    }
+    assert_eq!(Symbol::parse("btcusdt").unwrap().as_str(), "BTC/USDT");
+    assert_eq!(Symbol::parse("aapl").unwrap().as_str(), "AAPL");
+    assert_eq!(Symbol::parse("BRK.B").unwrap().quote(), None);
+
+    assert_eq!(Symbol::parse("  "), Err(SymbolError::Empty));
+    assert!(matches!(Symbol::parse("EUR/USD/JPY"), Err(SymbolError::Invalid(_))));
+    assert!(matches!(Symbol::parse("EUR$"), Err(SymbolError::Invalid(_))));
+    assert!("eur/".parse::<Symbol>().is_err());
+}
+
+#[test]
+fn test_symbol_provider_formats() {
+    let symbol: Symbol = "btc/usdt".parse().unwrap();
+    assert_eq!(symbol.base(), "BTC");
+    assert_eq!(symbol.format(SymbolFormat::Slash), "BTC/USDT");
+    assert_eq!(symbol.format(SymbolFormat::Concatenated), "BTCUSDT");
+    assert_eq!(symbol.format(SymbolFormat::Dashed), "BTC-USDT");
+    assert_eq!(Symbol::parse("aapl").unwrap().format(SymbolFormat::Dashed), "AAPL");
+
+    assert_eq!(serde_json::to_string(&symbol).unwrap(), r#""BTC/USDT""#);
+    assert_eq!(serde_json::from_str::<Symbol>(r#""btc-usdt""#).unwrap(), symbol);
+    assert!(serde_json::from_str::<Symbol>(r#""""#).is_err());
+}
+
+#[test]
+fn test_case_mismatches_still_match() {
+    assert!(same_symbol("eur/usd", "EURUSD"));
+    assert!(!same_symbol("eur/usd", "GBPUSD"));
+
+    let alert = Alert::new("xlx".to_string(), 1.1, Symbol::parse("eur-usd").unwrap(), "user123".to_string());
+    assert_eq!(alert.symbol, "EUR/USD");
+
+    let quotes = LatestQuotes::new();
+    quotes.record(Quote::new("EURUSD".to_string(), 1.2));
+    assert_eq!(quotes.get("eur/usd").map(|quote| (quote.symbol, quote.price)), Some(("eur/usd".to_string(), 1.2)));
+
+    let tree = ConditionTree::Leaf {
+        symbol: "eur/usd".to_string(),
+        condition: Box::new(Condition::PriceAbove(1.15)),
+    };
+    assert!(tree.is_met(1.1, "buy", &[Quote::new("EURUSD".to_string(), 1.2)]));
+}
@@ -0,0 +1,82 @@
+use trade_alerts::db::TableConfig;
+use trade_alerts::tenant::TenantId;
+
+#[test]
+fn test_tenant_id_validation() {
+    assert_eq!(TenantId::new(" Acme_1 ").unwrap().as_str(), "acme_1");
+    assert!(TenantId::new("").is_err());
+    assert!(TenantId::new("acme; drop table").is_err());
+}
+
+#[test]
+fn test_tenant_table_names() {
+    let tenant = TenantId::new("acme").unwrap();
+
+    assert_eq!(TableConfig::default().for_tenant(&tenant).tablename, "acme_alerts");
+}
+
+#[cfg(feature = "fixtures")]
+mod fixtures {
+    use trade_alerts::AlertUpdate;
+    use trade_alerts::db::rest::eq;
+    use trade_alerts::fixtures::{AlertFixture, FixtureServer, alert_rows, table_config};
+    use trade_alerts::tenant::{TenantId, TenantScope};
+
+    /// Two tenants with the same alerts, under the same hashes and IDs.
+    async fn start() -> (FixtureServer, TenantScope, TenantScope) {
+        let server = FixtureServer::start().await;
+        for table in ["acme_alerts", "globex_alerts"] {
+            let rows = alert_rows().into_iter().zip(1..).map(|(mut row, id)| {
+                row["id"] = id.into();
+                row
+            });
+            server.set_rows(table, rows.collect());
+        }
+        server.set_price("EUR/USD", 1.08);
+        let supabase = server.supabase();
+        let acme = supabase.tenant(&TenantId::new("acme").unwrap(), &table_config("alerts"));
+        let globex = supabase.tenant(&TenantId::new("globex").unwrap(), &table_config("alerts"));
+        (server, acme, globex)
+    }
+
+    fn hashes(server: &FixtureServer, table: &str) -> Vec<String> {
+        server.rows(table).iter().filter_map(|row| row["hash"].as_str().map(String::from)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_reads_stay_within_the_tenant() {
+        let (server, acme, globex) = start().await;
+        server.insert_rows("globex_alerts", vec![AlertFixture::new("xlx-globex", "EUR/USD", 1.20).row(&table_config("alerts"))]);
+
+        assert!(globex.fetch_alert_by_hash("xlx-globex").await.is_ok());
+        assert!(acme.fetch_alert_by_hash("xlx-globex").await.is_err());
+        assert_eq!(acme.fetch_alerts_by_user_id("user123").await.unwrap().len(), 2);
+        assert_eq!(globex.fetch_alerts_by_user_id("user123").await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_writes_stay_within_the_tenant() {
+        let (server, acme, _globex) = start().await;
+
+        acme.add_alert(&server.xylex_api(), AlertFixture::new("xlx-new", "EUR/USD", 1.20).alert()).await.unwrap();
+        let update = AlertUpdate { price_level: Some(1.15), ..AlertUpdate::default() };
+        acme.update_alert_by_hash("xlx-eurusd", update).await.unwrap();
+
+        assert!(hashes(&server, "acme_alerts").contains(&"xlx-new".to_string()));
+        assert!(!hashes(&server, "globex_alerts").contains(&"xlx-new".to_string()));
+        let level = |table: &str| server.rows(table).iter().find(|row| row["hash"] == "xlx-eurusd").unwrap()["price_level"].clone();
+        assert_eq!(level("acme_alerts"), 1.15);
+        assert_eq!(level("globex_alerts"), 1.10);
+    }
+
+    #[tokio::test]
+    async fn test_deletes_stay_within_the_tenant() {
+        let (server, acme, _globex) = start().await;
+
+        acme.delete_alert_by_hash("xlx-eurusd").await.unwrap();
+        assert_eq!(acme.delete_where(&[eq("user_id", "user456")]).await.unwrap(), 1);
+
+        assert_eq!(hashes(&server, "acme_alerts"), vec!["xlx-gbpusd"]);
+        assert_eq!(hashes(&server, "globex_alerts"), vec!["xlx-eurusd", "xlx-gbpusd", "xlx-aapl"]);
+    }
+}
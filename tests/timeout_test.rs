@@ -0,0 +1,36 @@
+use trade_alerts::data::XylexApi;
+use trade_alerts::timeout::{DEFAULT_CONNECT_TIMEOUT, DEFAULT_READ_TIMEOUT, Timeouts};
+
+#[test]
+fn test_default_timeouts() {
+    let timeouts = Timeouts::default();
+    assert_eq!((timeouts.connect, timeouts.read), (DEFAULT_CONNECT_TIMEOUT, DEFAULT_READ_TIMEOUT));
+    assert_eq!(timeouts.total(), DEFAULT_CONNECT_TIMEOUT + DEFAULT_READ_TIMEOUT);
+    assert_eq!(XylexApi::new("key".to_string(), "url".to_string()).timeouts, timeouts);
+}
+
+#[cfg(feature = "fixtures")]
+mod fixtures {
+    use std::time::{Duration, Instant};
+
+    use trade_alerts::data::XylexApi;
+    use trade_alerts::errors::XylexApiError;
+    use trade_alerts::fixtures::FixtureServer;
+    use trade_alerts::retry::RetryPolicy;
+    use trade_alerts::timeout::Timeouts;
+
+    #[tokio::test]
+    async fn test_hung_endpoint_times_out() {
+        // Accepts connections and never answers
+        let server = FixtureServer::start().await;
+        server.hang("/price");
+        let api = XylexApi::new("key".to_string(), server.price_endpoint())
+            .with_retry_policy(RetryPolicy::none())
+            .with_timeouts(Timeouts::new(Duration::from_secs(1), Duration::from_millis(100)));
+
+        let started = Instant::now();
+        let result = api.request_real_time_price("eur/usd").await;
+        assert!(matches!(result, Err(XylexApiError::Timeout(_))), "{:?}", result);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "fixtures")]
+
+use std::time::Duration;
+
+use futures_util::StreamExt;
+
+use trade_alerts::AlertScheduler;
+use trade_alerts::data::MockProvider;
+use trade_alerts::fixtures::{FixtureServer, alert_rows, table_config};
+
+#[tokio::test]
+async fn test_subscribe_to_triggered_alerts() {
+    let server = FixtureServer::start().await;
+    // Triggered alerts are deleted by their id
+    let rows = alert_rows().into_iter().zip(1..).map(|(mut row, id)| {
+        row["id"] = id.into();
+        row
+    });
+    server.insert_rows("alerts", rows.collect());
+    let provider = MockProvider::new()
+        .with_ramp("EUR/USD", 1.08, 1.12, 3)
+        .with_price("GBP/USD", 1.26)
+        .with_price("AAPL", 195.0);
+
+    let scheduler = AlertScheduler::new(provider, server.supabase(), table_config("alerts"))
+        .with_interval(Duration::from_millis(20));
+    let subscribed = scheduler.subscribe();
+    let handle = scheduler.run().unwrap();
+    let late = handle.subscribe();
+    tokio::pin!(subscribed, late);
+
+    let alert = tokio::time::timeout(Duration::from_secs(10), subscribed.next())
+        .await
+        .expect("EUR/USD should trigger")
+        .unwrap();
+    assert_eq!(alert.hash, "xlx-eurusd");
+    assert!(
+        server.rows("alerts").iter().all(|row| row["hash"] != "xlx-eurusd"),
+        "Alerts are sent once they were deleted"
+    );
+
+    handle.shutdown().await;
+    let rest = tokio::time::timeout(Duration::from_secs(10), subscribed.collect::<Vec<_>>())
+        .await
+        .expect("The stream should end with the scheduler");
+    assert!(rest.is_empty(), "Every alert is sent once");
+    let late = tokio::time::timeout(Duration::from_secs(10), late.collect::<Vec<_>>()).await.unwrap();
+    assert!(late.len() <= 1);
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "tui")]
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde_json::{Value, json};
+
+use trade_alerts::data::{CheckReport, Quote};
+use trade_alerts::db::TableConfig;
+use trade_alerts::tui::{MonitorEvent, MonitorState};
+
+fn row(hash: &str, symbol: &str, price_level: f64) -> HashMap<String, Value> {
+    json!({ "hash": hash, "symbol": symbol, "price_level": price_level })
+        .as_object()
+        .unwrap()
+        .clone()
+        .into_iter()
+        .collect()
+}
+
+#[test]
+fn test_monitor_state() {
+    let config = TableConfig::default();
+    let mut state = MonitorState::default();
+
+    state.apply(MonitorEvent::CycleFailed("timeout".to_string()), &config);
+    assert_eq!(state.health.consecutive_failures, 1);
+
+    state.apply(
+        MonitorEvent::Cycle(CheckReport {
+            alerts: vec![row("far", "eur/usd", 1.2), row("near", "eur/usd", 1.101)],
+            quotes: vec![Quote::new("eur/usd".to_string(), 1.1)],
+            triggered: Vec::new(),
+            duration: Duration::from_millis(40),
+            deferred: Vec::new(),
+        }),
+        &config,
+    );
+
+    assert_eq!(state.health.consecutive_failures, 0);
+    assert_eq!(state.prices.get("eur/usd"), Some(&1.1));
+    assert_eq!(state.nearest[0].hash, "near");
+    assert_eq!(state.average_cycle_time(), Some(Duration::from_millis(40)));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_log_redirect() {
+    use std::io::Write;
+    use trade_alerts::tui::log::LogRedirect;
+
+    let path = std::env::temp_dir().join(format!("monitor_{}.log", std::process::id()));
+    std::fs::remove_file(&path).ok();
+
+    let redirect = LogRedirect::to_file(&path).unwrap();
+    // Written to the file descriptor, the test harness only captures `println!`
+    writeln!(std::io::stdout(), "Checking alert for symbol: EUR/USD").unwrap();
+    drop(redirect);
+    writeln!(std::io::stdout(), "Monitor closed").unwrap();
+
+    let log = std::fs::read_to_string(&path).unwrap();
+    assert!(log.contains("Checking alert for symbol: EUR/USD\n"));
+    assert!(!log.contains("Monitor closed"), "Standard output is restored once the redirect is dropped");
+    std::fs::remove_file(&path).ok();
+}
@@ -0,0 +1,166 @@
+use chrono::{TimeZone, Utc};
+
+use trade_alerts::TriggeredAlert;
+use trade_alerts::fixtures::triggered_alert;
+
+fn triggered(hash: &str) -> TriggeredAlert {
+    TriggeredAlert {
+        trigger_price: 1.1002,
+        triggered_at: Utc.timestamp_nanos(1_709_294_400_123_456_789),
+        ..triggered_alert(hash, "EUR/USD", 1.1)
+    }
+}
+
+#[test]
+fn test_idempotency_key() {
+    let alert = triggered("xlx-a");
+    assert_eq!(alert.idempotency_key(), "xlx-a@1709294400123");
+
+    let event = alert.to_event();
+    assert_eq!(event["idempotency_key"], "xlx-a@1709294400123");
+    assert_eq!(TriggeredAlert::from_event(&event).unwrap().idempotency_key(), alert.idempotency_key());
+
+    let redelivered = TriggeredAlert { triggered_at: Utc.timestamp_millis_opt(1_709_294_400_123).unwrap(), ..alert.clone() };
+    assert_eq!(redelivered.idempotency_key(), alert.idempotency_key(), "Sub-millisecond precision is not part of the key");
+    assert_ne!(triggered("xlx-b").idempotency_key(), alert.idempotency_key());
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_two_phase_triggers() {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+
+    use trade_alerts::TradeAlerts;
+    use trade_alerts::data::MockProvider;
+    use trade_alerts::errors::NotifyError;
+    use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+    use trade_alerts::notify::Notifier;
+
+    /// Records the idempotency key of every delivery.
+    struct KeyRecorder(Arc<Mutex<Vec<String>>>);
+
+    #[async_trait]
+    impl Notifier for KeyRecorder {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+
+        async fn notify(&self, alert: &TriggeredAlert) -> Result<(), NotifyError> {
+            self.0.lock().unwrap().push(alert.idempotency_key());
+            Ok(())
+        }
+    }
+
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    let row = |hash: &str, id: i64| {
+        let mut row = AlertFixture::new(hash, "EUR/USD", 1.10).latest_price(1.08).row(&config);
+        row["id"] = id.into();
+        row
+    };
+    let keys = Arc::new(Mutex::new(Vec::new()));
+    let alerts = TradeAlerts::builder()
+        .with_provider(MockProvider::new().with_price("EUR/USD", 1.11))
+        .with_supabase(server.supabase())
+        .with_table_config(config.clone())
+        .with_notifier(KeyRecorder(keys.clone()))
+        .with_two_phase_triggers()
+        .build()
+        .unwrap();
+
+    // A crash after claiming left this alert triggering
+    let left = triggered("xlx-left");
+    let mut claimed = row("xlx-left", 1);
+    server.insert_rows("alerts", vec![claimed.clone()]);
+    assert!(server.supabase().claim_trigger(&left, alerts.instance_id(), &config).await.unwrap());
+    assert!(!server.supabase().claim_trigger(&left, "other", &config).await.unwrap(), "A claimed alert is not claimed twice");
+    claimed = server.rows("alerts").remove(0);
+    assert_eq!(claimed[&config.trigger_state_column_name], "triggering");
+    assert_eq!(claimed[&config.idempotency_key_column_name], left.idempotency_key());
+
+    server.insert_rows("alerts", vec![row("xlx-new", 2)]);
+    let report = alerts.run_once().await.unwrap();
+    assert_eq!(report.triggered.iter().map(|alert| alert.hash.as_str()).collect::<Vec<_>>(), vec!["xlx-new"]);
+    assert_eq!(server.rows("alerts"), vec![claimed], "Alerts being delivered are skipped and kept");
+    assert_eq!(keys.lock().unwrap().len(), 1);
+
+    let recovered = alerts.recover_triggers().await;
+    assert_eq!(recovered.iter().map(|alert| alert.hash.as_str()).collect::<Vec<_>>(), vec!["xlx-left"]);
+    assert_eq!(keys.lock().unwrap()[1], left.idempotency_key(), "Recovered alerts keep their key");
+    assert!(server.rows("alerts").is_empty());
+    assert!(alerts.recover_triggers().await.is_empty());
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_only_stale_claims_are_recovered() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+
+    use trade_alerts::TradeAlerts;
+    use trade_alerts::data::MockProvider;
+    use trade_alerts::errors::NotifyError;
+    use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+    use trade_alerts::notify::Notifier;
+
+    /// Fails every delivery while `down` is set.
+    struct Receiver(Arc<AtomicBool>);
+
+    #[async_trait]
+    impl Notifier for Receiver {
+        fn name(&self) -> &str {
+            "receiver"
+        }
+
+        async fn notify(&self, _alert: &TriggeredAlert) -> Result<(), NotifyError> {
+            match self.0.load(Ordering::SeqCst) {
+                true => Err(NotifyError::DeliveryError("Receiver is down".to_string())),
+                false => Ok(()),
+            }
+        }
+    }
+
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    let row = |hash: &str, id: i64| {
+        let mut row = AlertFixture::new(hash, "EUR/USD", 1.10).latest_price(1.08).row(&config);
+        row["id"] = id.into();
+        row
+    };
+    let down = Arc::new(AtomicBool::new(true));
+    let alerts = TradeAlerts::builder()
+        .with_provider(MockProvider::new().with_price("EUR/USD", 1.11))
+        .with_supabase(server.supabase())
+        .with_table_config(config.clone())
+        .with_notifier(Receiver(down.clone()))
+        .with_two_phase_triggers()
+        .with_lease_ttl(Duration::from_secs(60))
+        .build()
+        .unwrap();
+
+    // Another live instance is delivering this one, a crashed one claimed the other long ago
+    server.insert_rows("alerts", vec![row("xlx-live", 1), row("xlx-crashed", 2)]);
+    assert!(server.supabase().claim_trigger(&triggered("xlx-live"), "other", &config).await.unwrap());
+    assert!(server.supabase().claim_trigger(&triggered("xlx-crashed"), "crashed", &config).await.unwrap());
+    let mut rows = server.rows("alerts");
+    rows[1][&config.claimed_at_column_name] = (Utc::now() - chrono::Duration::minutes(5)).to_rfc3339().into();
+    server.set_rows("alerts", rows);
+
+    // The receiver is down, the stale claim is recovered but released again
+    let recovered = alerts.recover_triggers().await;
+    assert_eq!(recovered.iter().map(|alert| alert.hash.as_str()).collect::<Vec<_>>(), vec!["xlx-crashed"]);
+    let rows = server.rows("alerts");
+    assert_eq!(rows.len(), 2, "Undelivered alerts are kept");
+    assert_eq!(rows[0][&config.claimed_by_column_name], "other");
+    assert!(rows[1][&config.trigger_state_column_name].is_null(), "The claim of an undelivered alert is released");
+
+    down.store(false, Ordering::SeqCst);
+    let report = alerts.run_once().await.unwrap();
+    assert_eq!(report.triggered.iter().map(|alert| alert.hash.as_str()).collect::<Vec<_>>(), vec!["xlx-crashed"]);
+    assert_eq!(server.rows("alerts").len(), 1, "Only the alert claimed by the live instance is left");
+}
@@ -0,0 +1,81 @@
+use chrono::{TimeZone, Utc};
+use serde_json::json;
+
+use trade_alerts::AlertUpdate;
+use trade_alerts::db::TableConfig;
+
+#[test]
+fn test_update_body_only_holds_changed_fields() {
+    let config = TableConfig { price_level_column_name: "level".to_string(), ..TableConfig::default() };
+    let expires_at = Utc.with_ymd_and_hms(2030, 1, 1, 14, 30, 0).unwrap();
+    let update = AlertUpdate { price_level: Some(1.2), expires_at: Some(Some(expires_at)), ..AlertUpdate::default() };
+
+    assert!(!update.is_empty());
+    assert_eq!(update.to_value(&config), json!({ "level": 1.2, "expires_at": "2030-01-01T14:30:00+00:00" }));
+
+    // Removing the expiry sends `null`, leaving it unchanged sends nothing
+    let permanent = AlertUpdate { expires_at: Some(None), ..AlertUpdate::default() };
+    assert!(!permanent.is_empty());
+    assert_eq!(permanent.to_value(&config), json!({ "expires_at": null }));
+    assert!(AlertUpdate::default().is_empty());
+    assert_eq!(AlertUpdate::default().to_value(&config), json!({}));
+}
+
+#[cfg(feature = "fixtures")]
+mod fixtures {
+    use serde_json::json;
+    use trade_alerts::{Alert, AlertUpdate};
+    use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+
+    async fn start() -> (FixtureServer, Alert) {
+        let server = FixtureServer::start().await;
+        let fixture = AlertFixture::new("xlx-a", "EUR/USD", 1.1).latest_price(1.08);
+        let mut row = fixture.row(&table_config("alerts"));
+        row["id"] = json!(1);
+        server.insert_rows("alerts", vec![row]);
+        (server, fixture.alert())
+    }
+
+    #[tokio::test]
+    async fn test_update_changes_stored_and_local_alert() {
+        let (server, mut alert) = start().await;
+        let before = server.rows("alerts")[0].clone();
+        let update = AlertUpdate {
+            price_level: Some(1.12),
+            symbol: Some("GBP/USD".to_string()),
+            metadata: Some(json!({ "note": "moved" })),
+            ..AlertUpdate::default()
+        };
+
+        alert.update(&server.supabase(), update, &table_config("alerts")).await.unwrap();
+
+        assert_eq!((alert.price_level, alert.symbol.as_str()), (1.12, "GBP/USD"));
+        assert_eq!(alert.metadata, Some(json!({ "note": "moved" })));
+        let row = &server.rows("alerts")[0];
+        assert_eq!((row["price_level"].clone(), row["symbol"].clone()), (json!(1.12), json!("GBP/USD")));
+        // Columns which weren't part of the update are left untouched
+        assert_eq!(row["initial_direction"], before["initial_direction"]);
+        assert_eq!(row["user_id"], before["user_id"]);
+    }
+
+    #[tokio::test]
+    async fn test_empty_update_sends_nothing() {
+        let (server, mut alert) = start().await;
+
+        alert.update(&server.supabase(), AlertUpdate::default(), &table_config("alerts")).await.unwrap();
+
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_update_leaves_the_alert_unchanged() {
+        let (server, _) = start().await;
+        let mut missing = AlertFixture::new("xlx-missing", "EUR/USD", 1.1).alert();
+        let update = AlertUpdate { price_level: Some(1.12), ..AlertUpdate::default() };
+
+        assert!(missing.update(&server.supabase(), update, &table_config("alerts")).await.is_err());
+
+        assert_eq!(missing.price_level, 1.1);
+        assert_eq!(server.rows("alerts")[0]["price_level"], json!(1.1));
+    }
+}
@@ -0,0 +1,126 @@
+#![cfg(feature = "fixtures")]
+
+use trade_alerts::TriggeredAlert;
+use trade_alerts::db::WebhookTable;
+use trade_alerts::fixtures::{FixtureServer, triggered_alert};
+use trade_alerts::notify::webhook::{SIGNATURE_HEADER, sign_body};
+use trade_alerts::notify::{NotificationRouter, PayloadFormat, RouteTarget};
+
+fn triggered(user_id: &str) -> TriggeredAlert {
+    TriggeredAlert {
+        user_id: user_id.to_string(),
+        trigger_price: 1.1001,
+        ..triggered_alert("xlx-eurusd", "EUR/USD", 1.1)
+    }
+}
+
+#[tokio::test]
+async fn test_user_webhook_crud() {
+    let table = WebhookTable::default();
+    let server = FixtureServer::start().await;
+    let supabase = server.supabase();
+
+    let webhook = supabase
+        .register_webhook("user123", "https://93.184.216.34/hook", PayloadFormat::Flat, &table)
+        .await
+        .unwrap();
+    assert!(webhook.id.starts_with("wh_") && webhook.secret.starts_with("whsec_"));
+    assert!(!format!("{:?}", webhook).contains(&webhook.secret));
+    assert!(supabase.register_webhook("user123", "ftp://example.com", PayloadFormat::Flat, &table).await.is_err());
+    for internal in ["http://127.0.0.1:8080/hook", "http://169.254.169.254/latest", "http://[::1]/hook", "http://localhost/hook"] {
+        assert!(
+            supabase.register_webhook("user123", internal, PayloadFormat::Flat, &table).await.is_err(),
+            "{} must be rejected",
+            internal
+        );
+    }
+
+    let listed = supabase.list_webhooks("user123", &table).await.unwrap();
+    assert_eq!(listed, vec![webhook.clone()]);
+    assert!(supabase.list_webhooks("user456", &table).await.unwrap().is_empty());
+
+    assert!(supabase.disable_webhook("user456", &webhook.id, &table).await.is_err(), "Other users can't change it");
+    supabase.disable_webhook("user123", &webhook.id, &table).await.unwrap();
+    assert!(!supabase.list_webhooks("user123", &table).await.unwrap()[0].enabled);
+
+    let secret = supabase.rotate_webhook_secret("user123", &webhook.id, &table).await.unwrap();
+    assert_ne!(secret, webhook.secret);
+    assert_eq!(supabase.list_webhooks("user123", &table).await.unwrap()[0].secret, secret);
+
+    supabase.delete_webhook("user123", &webhook.id, &table).await.unwrap();
+    assert!(supabase.list_webhooks("user123", &table).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_router_delivers_to_user_webhooks() {
+    let table = WebhookTable::default();
+    let server = FixtureServer::start().await;
+    let supabase = server.supabase().with_private_webhooks();
+
+    let webhook = supabase
+        .register_webhook("user123", &server.webhook_url("user123"), PayloadFormat::Event, &table)
+        .await
+        .unwrap();
+    let disabled = supabase
+        .register_webhook("user123", &server.webhook_url("disabled"), PayloadFormat::Event, &table)
+        .await
+        .unwrap();
+    supabase.disable_webhook("user123", &disabled.id, &table).await.unwrap();
+
+    let public_only = NotificationRouter::new().with_webhook_store(supabase.clone(), table.clone());
+    let deliveries = public_only.route(&triggered("user123")).await;
+    assert!(deliveries[0].error.as_deref().unwrap().contains("non-public"), "{:?}", deliveries[0].error);
+    assert!(server.requests_to("/hooks/").is_empty());
+
+    let router = NotificationRouter::new().with_webhook_store(supabase, table).with_private_webhooks();
+    let deliveries = router.route(&triggered("user123")).await;
+    assert_eq!(deliveries.len(), 1);
+    assert!(matches!(&deliveries[0].target, RouteTarget::UserWebhook { id, .. } if *id == webhook.id));
+    assert_eq!(deliveries[0].error, None);
+    assert!(router.route(&triggered("user456")).await.is_empty());
+
+    let requests = server.requests_to("/hooks/");
+    assert_eq!((requests.len(), requests[0].method.as_str(), requests[0].target.as_str()), (1, "POST", "/hooks/user123"));
+    let signature = sign_body(&webhook.secret, requests[0].body.as_bytes());
+    assert_eq!(requests[0].header(SIGNATURE_HEADER), Some(signature.as_str()));
+}
+
+#[tokio::test]
+async fn test_webhook_secrets_are_encrypted() {
+    use trade_alerts::utils::crypto::FieldCipher;
+
+    let table = WebhookTable::default();
+    let server = FixtureServer::start().await;
+    let supabase = server.supabase().with_cipher(FieldCipher::new(&[7u8; 32]).unwrap());
+
+    let webhook = supabase
+        .register_webhook("user123", "https://93.184.216.34/hook", PayloadFormat::Flat, &table)
+        .await
+        .unwrap();
+    let stored = server.rows("webhooks")[0]["secret"].as_str().unwrap().to_string();
+    assert!(stored.starts_with("enc:v1:") && !stored.contains(&webhook.secret));
+    assert_eq!(supabase.list_webhooks("user123", &table).await.unwrap()[0].secret, webhook.secret);
+
+    let secret = supabase.rotate_webhook_secret("user123", &webhook.id, &table).await.unwrap();
+    assert!(!server.rows("webhooks")[0]["secret"].as_str().unwrap().contains(&secret));
+    assert_eq!(supabase.list_webhooks("user123", &table).await.unwrap()[0].secret, secret);
+}
+
+#[tokio::test]
+async fn test_webhooks_are_fetched_once_per_user() {
+    let table = WebhookTable::default();
+    let server = FixtureServer::start().await;
+    let supabase = server.supabase().with_private_webhooks();
+    supabase
+        .register_webhook("user123", &server.webhook_url("user123"), PayloadFormat::Event, &table)
+        .await
+        .unwrap();
+
+    let router = NotificationRouter::new().with_webhook_store(supabase, table).with_private_webhooks();
+    let routed = router.route_all(&[triggered("user123"), triggered("user123"), triggered("user456")]).await;
+    assert_eq!(routed.iter().map(Vec::len).collect::<Vec<_>>(), vec![1, 1, 0]);
+    assert_eq!(server.webhook_requests("user123").len(), 2);
+
+    let fetches = server.requests().iter().filter(|request| request.starts_with("GET /rest/v1/webhooks")).count();
+    assert_eq!(fetches, 2, "One fetch per user");
+}
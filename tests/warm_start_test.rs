@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+
+use chrono::{Duration, TimeZone, Utc};
+
+use trade_alerts::data::{MovementGate, PriceSnapshot, Quote, XylexApi};
+
+fn quote(symbol: &str, price: f64, minute: u32) -> Quote {
+    Quote {
+        symbol: symbol.to_string(),
+        price,
+        bid: None,
+        ask: None,
+        volume: None,
+        average_volume: None,
+        timestamp: Some(Utc.with_ymd_and_hms(2024, 3, 1, 12, minute, 0).unwrap()),
+    }
+}
+
+#[test]
+fn test_price_snapshot() {
+    let snapshot = PriceSnapshot::from_quotes(&[quote("EUR/USD", 1.09, 1), quote("AAPL", 190.0, 0), quote("EUR/USD", 1.08, 0)]);
+    assert_eq!(snapshot.prices, BTreeMap::from([("AAPL".to_string(), 190.0), ("EUR/USD".to_string(), 1.09)]));
+    assert_eq!(snapshot.taken_at, Utc.with_ymd_and_hms(2024, 3, 1, 12, 1, 0).unwrap());
+    assert_eq!(snapshot.age(snapshot.taken_at + Duration::minutes(5)).as_secs(), 300);
+
+    let xylex = XylexApi::new("key".to_string(), "https://example.com".to_string()).with_movement_gate(MovementGate::new(1.0));
+    xylex.previous_prices.record("AAPL", 191.0);
+    let seeded_snapshot = PriceSnapshot { evaluated: BTreeMap::from([("EUR/USD".to_string(), 1.09)]), ..snapshot };
+    assert_eq!(xylex.seed_prices(&seeded_snapshot), 1, "Recorded prices are kept");
+    assert_eq!(xylex.previous_prices.get("AAPL"), Some(191.0));
+    assert_eq!(xylex.previous_prices.get("EUR/USD"), Some(1.09));
+    assert!(!xylex.movement_gate.as_ref().unwrap().should_evaluate("EUR/USD", 1.095), "The gate starts from the seeded price");
+
+    let current = xylex.price_snapshot();
+    assert_eq!(current.prices.len(), 2);
+    let path = std::env::temp_dir().join(format!("price-snapshot-{}.json", std::process::id()));
+    std::fs::write(&path, "stale").unwrap();
+    current.save(&path).unwrap();
+    assert_eq!(PriceSnapshot::load(&path), Some(current));
+    assert!(!path.with_extension("saving").exists(), "The temporary file is renamed over the snapshot");
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(PriceSnapshot::load(&path), None);
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_warm_start_detects_band_crossings_on_the_first_cycle() {
+    use trade_alerts::{BandMode, Condition, TradeAlerts};
+    use trade_alerts::data::MockProvider;
+    use trade_alerts::fixtures::{AlertFixture, FixtureServer, table_config};
+
+    let server = FixtureServer::start().await;
+    let config = table_config("alerts");
+    let mut band = AlertFixture::new("xlx-band", "EUR/USD", 1.10).condition(Condition::Band(BandMode::Enter)).row(&config);
+    band[&config.upper_price_level_column_name] = 1.12.into();
+    let path = std::env::temp_dir().join(format!("warm-start-{}.json", std::process::id()));
+    // The price was below the band before the restart
+    let before_restart = PriceSnapshot::from_quotes(&[Quote { timestamp: Some(Utc::now()), ..quote("EUR/USD", 1.09, 0) }]);
+
+    let build = |snapshot: Option<PriceSnapshot>| {
+        let builder = TradeAlerts::builder()
+            .with_provider(MockProvider::new().with_price("EUR/USD", 1.11))
+            .with_supabase(server.supabase())
+            .with_table_config(config.clone())
+            .with_price_snapshot_file(&path);
+        match snapshot {
+            Some(snapshot) => builder.with_warm_start(snapshot),
+            None => builder,
+        }
+        .build()
+        .unwrap()
+    };
+
+    server.insert_rows("alerts", vec![band.clone()]);
+    let cold = build(None).run_once().await.unwrap();
+    assert!(cold.triggered.is_empty(), "Without a previous price the first cycle only records it");
+    assert_eq!(PriceSnapshot::load(&path).unwrap().prices["EUR/USD"], 1.11);
+    std::fs::remove_file(&path).unwrap();
+
+    let stale = PriceSnapshot { taken_at: Utc::now() - Duration::hours(2), ..before_restart.clone() };
+    assert!(build(Some(stale)).run_once().await.unwrap().triggered.is_empty(), "Stale snapshots are ignored");
+    std::fs::remove_file(&path).unwrap();
+
+    let warm = build(Some(before_restart)).run_once().await.unwrap();
+    assert_eq!(warm.triggered.iter().map(|alert| alert.hash.as_str()).collect::<Vec<_>>(), vec!["xlx-band"]);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "fixtures")]
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use serde_json::json;
+
+use trade_alerts::TriggeredAlert;
+use trade_alerts::fixtures::{FixtureServer, triggered_alert};
+use trade_alerts::notify::webhook::{PublicUrl, SIGNATURE_HEADER, sign_body};
+use trade_alerts::notify::{Notifier, WebhookNotifier};
+use trade_alerts::retry::RetryPolicy;
+use trade_alerts::timeout::Timeouts;
+
+/// A webhook endpoint answering with `statuses` in turn, then `200`.
+fn hook_with_statuses(
+    server: &FixtureServer,
+    name: &str,
+    statuses: &[u16]
+) -> String {
+    let url: String = server.webhook_url(name);
+    server.queue_responses(&format!("/hooks/{}", name), statuses.iter().map(|status| (*status, json!({}))).collect());
+    url
+}
+
+fn triggered() -> TriggeredAlert {
+    TriggeredAlert {
+        user_id: "user-1".to_string(),
+        trigger_price: 1.1001,
+        ..triggered_alert("xlx-eurusd", "EUR/USD", 1.1)
+    }
+}
+
+fn fast_retry() -> RetryPolicy {
+    RetryPolicy::default()
+        .with_max_attempts(3)
+        .with_backoff(Duration::from_millis(5), Duration::from_millis(20))
+}
+
+#[tokio::test]
+async fn test_webhook_retries_server_errors() {
+    let server = FixtureServer::start().await;
+    let url = hook_with_statuses(&server, "hook", &[503, 500]);
+    let notifier = WebhookNotifier::new(url.clone()).with_secret("s3cret").with_retry_policy(fast_retry());
+    assert!(notifier.last_delivery().is_none());
+
+    notifier.notify(&triggered()).await.unwrap();
+    let delivery = notifier.clone().last_delivery().unwrap();
+    assert_eq!((delivery.url.as_str(), delivery.status, delivery.attempts), (url.as_str(), Some(200), 3));
+    assert!(delivery.error.is_none());
+
+    // Every attempt carries the same body and signature
+    let requests = server.webhook_requests("hook");
+    assert_eq!(requests.len(), 3);
+    for request in &requests {
+        let signature = sign_body("s3cret", request.body.as_bytes());
+        assert_eq!(request.header(SIGNATURE_HEADER), Some(signature.as_str()), "Unsigned attempt: {:?}", request.headers);
+    }
+}
+
+#[tokio::test]
+async fn test_webhook_gives_up() {
+    let server = FixtureServer::start().await;
+    let url = hook_with_statuses(&server, "flaky", &[500, 502, 504, 500]);
+    let notifier = WebhookNotifier::new(url).with_retry_policy(fast_retry());
+    assert!(notifier.notify(&triggered()).await.is_err());
+    let delivery = notifier.last_delivery().unwrap();
+    assert_eq!((delivery.status, delivery.attempts), (Some(504), 3));
+    assert_eq!(delivery.error.as_deref(), Some("Webhook returned 504 Gateway Timeout"));
+    assert_eq!(server.webhook_requests("flaky").len(), 3);
+
+    // Client errors are never retried
+    let url = hook_with_statuses(&server, "rejecting", &[400]);
+    let notifier = WebhookNotifier::new(url).with_retry_policy(fast_retry());
+    assert!(notifier.notify(&triggered()).await.is_err());
+    assert_eq!(notifier.last_delivery().unwrap().attempts, 1);
+    assert_eq!(server.webhook_requests("rejecting").len(), 1);
+
+    // Neither is anything with `RetryPolicy::none`
+    let notifier = WebhookNotifier::new("http://127.0.0.1:9/hook".to_string()).with_retry_policy(RetryPolicy::none());
+    assert!(notifier.notify(&triggered()).await.is_err());
+    let delivery = notifier.last_delivery().unwrap();
+    assert_eq!((delivery.status, delivery.attempts), (None, 1));
+}
+
+#[tokio::test]
+async fn test_webhook_records_every_delivery() {
+    let server = FixtureServer::start().await;
+    let url = hook_with_statuses(&server, "hook", &[400]);
+    let notifier = WebhookNotifier::new(url).with_retry_policy(RetryPolicy::none());
+    let gbpusd = TriggeredAlert { hash: "xlx-gbpusd".to_string(), symbol: "GBP/USD".to_string(), ..triggered() };
+
+    assert!(notifier.notify(&triggered()).await.is_err());
+    notifier.clone().notify(&gbpusd).await.unwrap();
+
+    assert_eq!(notifier.deliveries().len(), 2);
+    let failed = notifier.deliveries_of("xlx-eurusd");
+    assert_eq!((failed.len(), failed[0].status), (1, Some(400)));
+    let delivered = notifier.deliveries_of("xlx-gbpusd");
+    assert_eq!((delivered.len(), delivered[0].status), (1, Some(200)));
+    assert_eq!(notifier.last_delivery().unwrap().hashes, vec!["xlx-gbpusd".to_string()]);
+}
+
+#[tokio::test]
+async fn test_webhook_times_out() {
+    // Accepts connections but never answers
+    let server = FixtureServer::start().await;
+    server.hang("/hooks/slow");
+    let url = server.webhook_url("slow");
+
+    let notifier = WebhookNotifier::new(url)
+        .with_retry_policy(RetryPolicy::none())
+        .with_timeouts(Timeouts::new(Duration::from_secs(1), Duration::from_millis(200)))
+        .unwrap();
+    let started = std::time::Instant::now();
+    assert!(notifier.notify(&triggered()).await.is_err());
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(notifier.last_delivery().unwrap().status, None);
+}
+
+#[tokio::test]
+async fn test_pinned_webhook_does_not_follow_redirects() {
+    // A receiver which passed the public address check redirects to a loopback service
+    let server = FixtureServer::start().await;
+    server.redirect("/hooks/public", &server.webhook_url("internal"));
+    let addr: SocketAddr = server.url().trim_start_matches("http://").parse().unwrap();
+    let resolved = PublicUrl { host: Some("hooks.example.com".to_string()), addrs: vec![addr] };
+
+    let notifier = WebhookNotifier::new(format!("http://hooks.example.com:{}/hooks/public", addr.port()))
+        .with_retry_policy(RetryPolicy::none())
+        .pinned(&resolved)
+        .unwrap();
+    assert!(notifier.notify(&triggered()).await.is_err());
+    assert_eq!(notifier.last_delivery().unwrap().status, Some(302));
+    assert_eq!(server.webhook_requests("public").len(), 1);
+    assert!(server.webhook_requests("internal").is_empty());
+}